@@ -24,6 +24,7 @@ $PluginRegistry = @{
     "invert"       = @{ Package = "ffgl-invert";       Stem = "ffgl_invert";       Display = "Invert"       }
     "blur"         = @{ Package = "ffgl-blur";         Stem = "ffgl_blur";         Display = "Blur"         }
     "kitchen-sink" = @{ Package = "ffgl-kitchen-sink"; Stem = "ffgl_kitchen_sink"; Display = "KitchenSink"  }
+    "chroma-key"   = @{ Package = "ffgl-chroma-key";   Stem = "ffgl_chroma_key";   Display = "ChromaKey"    }
 }
 
 function Show-Usage {
@@ -31,7 +32,7 @@ function Show-Usage {
 Usage: .\build.ps1 [OPTIONS]
 
 Options:
-  -Plugin    PLUGIN     Plugin to build: passthrough, invert, blur, kitchen-sink, all (default: all)
+  -Plugin    PLUGIN     Plugin to build: passthrough, invert, blur, kitchen-sink, chroma-key, all (default: all)
   -Profile   PROFILE    Build profile: debug, release (default: release)
   -Toolchain TOOLCHAIN  Windows toolchain: msvc, gnu (default: msvc)
   -Verbose              Enable verbose output
@@ -102,7 +103,7 @@ if ($Arch -notin @("x86_64", "current")) {
 $target = Get-RustTarget $Toolchain
 $profileDir = if ($Profile -eq "debug") { "debug" } else { $Profile }
 
-$plugins = if ($Plugin -eq "all") { @("passthrough", "invert", "blur", "kitchen-sink") } else { @($Plugin) }
+$plugins = if ($Plugin -eq "all") { @("passthrough", "invert", "blur", "kitchen-sink", "chroma-key") } else { @($Plugin) }
 
 # Validate plugin names
 foreach ($p in $plugins) {
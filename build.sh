@@ -21,7 +21,7 @@ BLUE='\033[0;34m'
 NC='\033[0m' # No Color
 
 # ── Plugin registry (Bash 3.2-compatible) ────────────────────────────────────
-ALL_PLUGINS="passthrough invert blur kitchen-sink"
+ALL_PLUGINS="passthrough invert blur kitchen-sink chroma-key"
 
 # Cargo package name for a plugin key
 get_plugin_package() {
@@ -30,6 +30,7 @@ get_plugin_package() {
         invert)       echo "ffgl-invert"       ;;
         blur)         echo "ffgl-blur"         ;;
         kitchen-sink) echo "ffgl-kitchen-sink" ;;
+        chroma-key)   echo "ffgl-chroma-key"   ;;
         *) return 1 ;;
     esac
 }
@@ -41,6 +42,7 @@ get_plugin_stem() {
         invert)       echo "ffgl_invert"       ;;
         blur)         echo "ffgl_blur"         ;;
         kitchen-sink) echo "ffgl_kitchen_sink" ;;
+        chroma-key)   echo "ffgl_chroma_key"   ;;
         *) return 1 ;;
     esac
 }
@@ -52,6 +54,7 @@ get_plugin_display() {
         invert)       echo "Invert"       ;;
         blur)         echo "Blur"         ;;
         kitchen-sink) echo "KitchenSink"  ;;
+        chroma-key)   echo "ChromaKey"    ;;
         *) return 1 ;;
     esac
 }
@@ -63,7 +66,7 @@ usage() {
     echo "Options:"
     echo "  --platform PLATFORM    Target platform: macos, windows, all, current (default: current)"
     echo "  --arch ARCH            Target architecture: arm64, x86_64, universal, current (default: current)"
-    echo "  --plugin PLUGIN        Plugin to build: passthrough, invert, blur, kitchen-sink, all (default: all)"
+    echo "  --plugin PLUGIN        Plugin to build: passthrough, invert, blur, kitchen-sink, chroma-key, all (default: all)"
     echo "  --profile PROFILE      Build profile: debug, release (default: release)"
     echo "  --toolchain TOOLCHAIN  Windows toolchain: msvc, gnu (default: msvc)"
     echo "  --verbose              Enable verbose output"
@@ -75,6 +75,7 @@ impl Op {
                 | Op::SetParameter
                 | Op::GetParameterDisplay
                 | Op::GetParameterType
+                | Op::GetInputStatus
         )
     }
 }
@@ -5,11 +5,15 @@ use crate::ffi::copy_str_to_host_buffer;
 use crate::ffi::*;
 use crate::info;
 use crate::FFGLData;
+use crate::GLInput;
 
 use crate::handler::{FFGLHandler, FFGLInstance};
 use crate::log::try_init_default_subscriber;
 use crate::parameters::ParamInfo;
+use crate::state::{self, StateParam};
 
+use std::ffi::{c_char, CStr};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::OnceLock;
 use std::{any::Any, ffi::CString};
 
@@ -18,11 +22,71 @@ use crate::conversions::*;
 use crate::handler;
 use anyhow::{Context, Error};
 
+/// Tell `renderer` about input slots that gained or lost a texture since the
+/// last frame. Slots the host stopped passing count as disconnected.
+fn notify_input_changes<I: FFGLInstance>(
+    renderer: &mut I,
+    connected: &mut Vec<bool>,
+    input: &GLInput,
+) {
+    connected.resize(connected.len().max(input.texture_count()), false);
+    for (index, was_connected) in connected.iter_mut().enumerate() {
+        let is_connected = input.texture(index).is_some();
+        if is_connected != *was_connected {
+            *was_connected = is_connected;
+            debug!(index, is_connected, "Input connection changed");
+            renderer.on_input_changed(index, is_connected);
+        }
+    }
+}
+
 fn param<H: FFGLHandler>(handler: &'static H, index: FFGLVal) -> &'static dyn ParamInfo {
-    handler.param_info(unsafe { index.num as usize })
+    param_at(handler, unsafe { index.num as usize })
+}
+
+/// The plugin's parameter `index`, or the [state](crate::state) parameter
+/// after them.
+fn param_at<H: FFGLHandler>(handler: &'static H, index: usize) -> &'static dyn ParamInfo {
+    if is_state_param(handler, index) {
+        &StateParam
+    } else {
+        handler.param_info(index)
+    }
+}
+
+/// A plugin's 0–1 value in the units the host shows, for parameters with a
+/// [range](ParamInfo::has_range).
+fn to_host(param: &dyn ParamInfo, value: f32) -> f32 {
+    if param.has_range() {
+        param.scale(value)
+    } else {
+        value
+    }
+}
+
+/// The inverse of [to_host].
+fn from_host(param: &dyn ParamInfo, value: f32) -> f32 {
+    if param.has_range() {
+        param.normalize(value)
+    } else {
+        value
+    }
+}
+
+fn is_state_param<H: FFGLHandler>(handler: &'static H, index: usize) -> bool {
+    CAPS.get().is_some_and(|caps| caps.persist_state) && index == handler.num_params()
+}
+
+/// Whether the plugin's parameter `index` holds a string rather than a float.
+fn is_text_param<H: FFGLHandler>(handler: &'static H, index: usize) -> bool {
+    param_at(handler, index).param_type().is_text()
 }
 
 static INFO: OnceLock<info::PluginInfo> = OnceLock::new();
+static CAPS: OnceLock<info::PluginCaps> = OnceLock::new();
+/// Set when the host enables top-left texture orientation. Hosts do this
+/// before instantiating, so it is copied into each new instance's data.
+static TOP_LEFT_ORIGIN: AtomicBool = AtomicBool::new(false);
 static INFO_STRUCT: OnceLock<PluginInfoStruct> = OnceLock::new();
 static ABOUT: OnceLock<CString> = OnceLock::new();
 static DESCRIPTION: OnceLock<CString> = OnceLock::new();
@@ -42,7 +106,7 @@ static HANDLER: OnceLock<Box<dyn Any + Send + Sync>> = OnceLock::new();
 
 use tracing::debug_span;
 use tracing::trace_span;
-use tracing::{debug, info, trace};
+use tracing::{debug, info, trace, warn};
 
 /// backtrace didn't seem to work. Maybe a problem with FFI. This is a hacky way to get the source
 macro_rules! e {
@@ -62,6 +126,7 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
     INITIALIZED.get_or_init(|| {
         // Initialize the logger
         let _ = try_init_default_subscriber();
+        crate::panic::install_hook();
     });
 
     let handler = HANDLER
@@ -71,8 +136,10 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
 
     // Initialize plugin info if not already initialized
     let plugin_info = INFO.get_or_init(|| handler.plugin_info());
+    let caps = CAPS.get_or_init(|| handler.plugin_caps());
 
     let name = plugin_info.name_str();
+    crate::log::init_file_logging(name);
 
     let _span = if !function.is_noisy() {
         debug_span!("entry", "fn" = ?function, name, "in" = unsafe { input_value.num })
@@ -106,14 +173,32 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
             let cap_num = unsafe { input_value.num };
             let cap: Option<PluginCapacity> = num::FromPrimitive::from_u32(cap_num);
 
+            let supported = |yes: bool| -> FFGLVal {
+                if yes {
+                    SupportVal::Supported.into()
+                } else {
+                    SupportVal::Unsupported.into()
+                }
+            };
+
             let result = match cap {
-                Some(PluginCapacity::MinInputFrames) => FFGLVal { num: 0 },
-                Some(PluginCapacity::MaxInputFrames) => FFGLVal { num: 1 },
+                Some(PluginCapacity::MinInputFrames) => FFGLVal {
+                    num: caps.min_inputs,
+                },
+                Some(PluginCapacity::MaxInputFrames) => FFGLVal {
+                    num: caps.max_inputs,
+                },
+
+                Some(PluginCapacity::Video16b) => supported(caps.video_16bit),
+                Some(PluginCapacity::Video24) => supported(caps.video_24bit),
+                Some(PluginCapacity::Video32) => supported(caps.video_32bit),
 
                 Some(PluginCapacity::ProcessOpenGl) => SupportVal::Supported.into(),
-                Some(PluginCapacity::SetTime) => SupportVal::Supported.into(),
+                Some(PluginCapacity::SetTime) => supported(caps.set_time),
 
-                Some(PluginCapacity::TopLeftTextureOrientation) => SupportVal::Supported.into(),
+                Some(PluginCapacity::TopLeftTextureOrientation) => {
+                    supported(caps.top_left_orientation)
+                }
 
                 _ => SupportVal::Unsupported.into(),
             };
@@ -128,7 +213,10 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
             let cap: Option<PluginCapacity> = num::FromPrimitive::from_u32(cap_num);
 
             let result: FFGLVal = match cap {
-                Some(PluginCapacity::TopLeftTextureOrientation) => SuccessVal::Success.into(),
+                Some(PluginCapacity::TopLeftTextureOrientation) if caps.top_left_orientation => {
+                    TOP_LEFT_ORIGIN.store(true, Ordering::Relaxed);
+                    SuccessVal::Success.into()
+                }
                 _ => SuccessVal::Fail.into(),
             };
 
@@ -138,15 +226,26 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
         }
 
         Op::GetNumParameters => FFGLVal {
-            num: handler.num_params() as u32,
+            num: (handler.num_params() + caps.persist_state as usize) as u32,
         },
 
-        Op::GetParameterDefault => param(handler, input_value).default_val().into(),
+        Op::GetParameterDefault if is_text_param(handler, unsafe { input_value.num } as usize) => {
+            state::EMPTY.into()
+        }
+        Op::GetParameterDefault => {
+            let p = param(handler, input_value);
+            to_host(p, p.default_val()).into()
+        }
+        Op::GetParameterVisibility
+            if is_state_param(handler, unsafe { input_value.num } as usize) =>
+        {
+            BoolVal::False.into()
+        }
         Op::GetParameterGroup => {
             let input: &GetStringStruct = unsafe { input_value.as_ref() };
             let buffer = input.stringBuffer;
 
-            let group = H::param_info(handler, input.parameterNumber as usize).group();
+            let group = param_at(handler, input.parameterNumber as usize).group();
 
             unsafe {
                 copy_str_to_host_buffer(
@@ -164,8 +263,7 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
             let input: &GetStringStruct = unsafe { input_value.as_ref() };
             let buffer = input.stringBuffer;
 
-            let display_name =
-                H::param_info(handler, input.parameterNumber as usize).display_name();
+            let display_name = param_at(handler, input.parameterNumber as usize).display_name();
 
             unsafe {
                 copy_str_to_host_buffer(
@@ -180,13 +278,41 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
             SuccessVal::Success.into()
         }
         Op::GetParameterName => param(handler, input_value).name().into(),
+        Op::GetParameterDisplay => {
+            let index = unsafe { input_value.num } as usize;
+            let inst = instance.context(e!("No instance"))?;
+            let text = if is_state_param(handler, index) {
+                String::new()
+            } else if is_text_param(handler, index) {
+                inst.renderer.get_text_param(index)
+            } else {
+                let value = inst.renderer.get_param(index);
+                param_at(handler, index).display_value(value)
+            };
+            inst.display_text = CString::new(text).unwrap_or_default();
+            inst.display_text.as_ptr().into()
+        }
         Op::GetParameterType => param(handler, input_value).param_type().into(),
 
-        Op::GetParameter => instance
-            .context(e!("No instance"))?
-            .renderer
-            .get_param(unsafe { input_value.num } as usize)
-            .into(),
+        Op::GetParameter if is_state_param(handler, unsafe { input_value.num } as usize) => {
+            let inst = instance.context(e!("No instance"))?;
+            inst.state_text = state::encode(&inst.renderer.get_state());
+            inst.state_text.as_ptr().into()
+        }
+        Op::GetParameter if is_text_param(handler, unsafe { input_value.num } as usize) => {
+            let index = unsafe { input_value.num } as usize;
+            let inst = instance.context(e!("No instance"))?;
+            let text = inst.renderer.get_text_param(index);
+            inst.param_text = CString::new(text).unwrap_or_default();
+            inst.param_text.as_ptr().into()
+        }
+        Op::GetParameter => {
+            let value = instance
+                .context(e!("No instance"))?
+                .renderer
+                .get_param(unsafe { input_value.num } as usize);
+            to_host(param(handler, input_value), value).into()
+        }
 
         Op::SetParameter => {
             let input: &SetParameterStruct = unsafe { input_value.as_ref() };
@@ -194,9 +320,42 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
 
             let index_usize = index as usize;
 
+            if is_state_param(handler, index_usize) {
+                let text = unsafe { input.NewParameterValue.PointerValue } as *const c_char;
+                if text.is_null() {
+                    return Ok(SuccessVal::Fail.into());
+                }
+                let text = unsafe { CStr::from_ptr(text) }.to_bytes();
+                let inst = instance.context(e!("No instance"))?;
+                if text.is_empty() {
+                    // The default value: nothing stored yet.
+                } else if let Some(state) = state::decode(text) {
+                    debug!(len = state.len(), "Restoring state");
+                    inst.renderer.set_state(&state);
+                } else {
+                    warn!(len = text.len(), "Ignoring state that isn't valid base64");
+                }
+                return Ok(SuccessVal::Success.into());
+            }
+
+            if is_text_param(handler, index_usize) {
+                let text = unsafe { input.NewParameterValue.PointerValue } as *const c_char;
+                let text = if text.is_null() {
+                    Default::default()
+                } else {
+                    unsafe { CStr::from_ptr(text) }.to_string_lossy()
+                };
+                instance
+                    .context(e!("No instance"))?
+                    .renderer
+                    .set_text_param(index_usize, &text);
+                return Ok(SuccessVal::Success.into());
+            }
+
             // dunno why they store this in a u32, whatever..
             let new_value = f32::from_bits(unsafe { input.NewParameterValue.UIntValue });
 
+            let new_value = from_host(param_at(handler, index_usize), new_value);
             instance
                 .context(e!("No instance"))?
                 .renderer
@@ -208,7 +367,7 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
             let input: &mut GetRangeStruct = unsafe { (input_value).as_mut() };
 
             let index = input.parameterNumber;
-            let p = handler.param_info(index as usize);
+            let p = param_at(handler, index as usize);
 
             input.range = RangeStruct {
                 min: p.min(),
@@ -220,7 +379,7 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
 
         Op::GetNumParameterElements => {
             let index = unsafe { input_value.num } as usize;
-            (handler.param_info(index).num_elements() as u32).into()
+            (param_at(handler, index).num_elements() as u32).into()
         }
 
         Op::GetParameterElementName => {
@@ -229,8 +388,7 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
             let param_index = input.ParameterNumber;
             let elm_index = input.ElementNumber;
 
-            handler
-                .param_info(param_index as usize)
+            param_at(handler, param_index as usize)
                 .element_name(elm_index as usize)
                 .into()
         }
@@ -241,14 +399,34 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
             let param_index = input.ParameterNumber;
             let elm_index = input.ElementNumber;
 
-            handler
-                .param_info(param_index as usize)
+            param_at(handler, param_index as usize)
                 .element_value(elm_index as usize)
                 .into()
         }
 
         Op::GetNumElementSeparators => 0u32.into(),
 
+        Op::GetThumbnail => match &plugin_info.thumbnail {
+            Some(thumbnail) => {
+                let input: &mut GetThumbnailStruct = unsafe { input_value.as_mut() };
+                input.width = thumbnail.width();
+                input.height = thumbnail.height();
+                // Hosts may ask for the size first, with no buffer.
+                if !input.rgbaPixelBuffer.is_null() {
+                    let rgba = thumbnail.rgba();
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(
+                            rgba.as_ptr(),
+                            input.rgbaPixelBuffer.cast::<u8>(),
+                            rgba.len(),
+                        )
+                    };
+                }
+                SuccessVal::Success.into()
+            }
+            None => SuccessVal::Fail.into(),
+        },
+
         Op::GetInfo => INFO_STRUCT.get().context(e!("No info"))?.into(),
 
         Op::GetExtendedInfo => {
@@ -259,7 +437,8 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
         Op::InstantiateGL => {
             let viewport: &FFGLViewportStruct = unsafe { input_value.as_ref() };
 
-            let data = FFGLData::new(viewport);
+            let mut data = FFGLData::new(viewport);
+            data.top_left_origin = TOP_LEFT_ORIGIN.load(Ordering::Relaxed);
             let renderer = H::new_instance(handler, &data)
                 .context("Failed to instantiate renderer")
                 .context(format!(
@@ -267,7 +446,14 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
                     std::str::from_utf8(&plugin_info.name).unwrap()
                 ))?;
 
-            let inst = handler::Instance { data, renderer };
+            let inst = handler::Instance {
+                data,
+                renderer,
+                connected_inputs: Vec::new(),
+                state_text: CString::default(),
+                display_text: CString::default(),
+                param_text: CString::default(),
+            };
 
             info!(
                 id = ?plugin_info.unique_id,
@@ -281,6 +467,7 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
             let inst = instance.context(e!("No instance"))?;
 
             debug!(?inst, "DEINSTGL");
+            inst.renderer.deinstantiate();
             unsafe {
                 drop(Box::from_raw(inst as *mut handler::Instance<H::Instance>));
             }
@@ -291,10 +478,17 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
         Op::ProcessOpenGL => {
             let gl_process_info: &ProcessOpenGLStruct = unsafe { input_value.as_ref() };
 
-            let handler::Instance { data, renderer } = instance.context(e!("No instance"))?;
-            let gl_input = gl_process_info.into();
+            let handler::Instance {
+                data,
+                renderer,
+                connected_inputs,
+                ..
+            } = instance.context(e!("No instance"))?;
+            let gl_input: GLInput = gl_process_info.into();
 
+            notify_input_changes(renderer, connected_inputs, &gl_input);
             renderer.draw(data, gl_input);
+            data.frame_index += 1;
 
             SuccessVal::Success.into()
         }
@@ -319,14 +513,34 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
         Op::Resize => {
             let viewport: &FFGLViewportStruct = unsafe { input_value.as_ref() };
 
-            let handler::Instance { data, .. } = instance.context(e!("No instance"))?;
+            let handler::Instance { data, renderer, .. } = instance.context(e!("No instance"))?;
             data.viewport = *viewport;
 
             debug!(v = ?viewport, "RESIZE");
+            renderer.on_resize(viewport.width, viewport.height);
+            SuccessVal::Success.into()
+        }
+
+        // Hosts may send these before instantiating; there's nothing to tell
+        // then.
+        Op::Connect => {
+            if let Some(inst) = instance {
+                inst.renderer.on_connect();
+            }
+            SuccessVal::Success.into()
+        }
+        Op::Disconnect => {
+            if let Some(inst) = instance {
+                inst.renderer.on_disconnect();
+            }
             SuccessVal::Success.into()
         }
 
-        Op::Connect => SuccessVal::Success.into(),
+        Op::GetInputStatus => instance
+            .context(e!("No instance"))?
+            .renderer
+            .input_status(unsafe { input_value.num } as usize)
+            .into(),
 
         Op::Instantiate | Op::Deinstantiate | Op::ProcessFrame | Op::ProcessFrameCopy => {
             SuccessVal::Fail.into()
@@ -334,7 +548,10 @@ pub fn default_ffgl_entry<H: FFGLHandler + 'static>(
 
         Op::InitialiseV2 => SuccessVal::Success.into(),
         Op::Initialise => SuccessVal::Success.into(),
-        Op::Deinitialise => SuccessVal::Success.into(),
+        Op::Deinitialise => {
+            handler.deinitialise();
+            SuccessVal::Success.into()
+        }
 
         _ => SuccessVal::Fail.into(),
     };
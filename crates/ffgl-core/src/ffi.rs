@@ -254,6 +254,16 @@ pub struct ProcessOpenGLStruct {
     pub HostFBO: u32,
 }
 
+/// Struct for FF_GET_THUMBNAIL. The plugin fills in the size, and the pixels
+/// when `rgbaPixelBuffer` is non-null (`width * height` RGBA texels).
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct GetThumbnailStruct {
+    pub width: u32,
+    pub height: u32,
+    pub rgbaPixelBuffer: *mut u32,
+}
+
 /// Struct for getting a parameter element name.
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -0,0 +1,143 @@
+//! A handler for plugins whose parameters are defined at runtime.
+//! 1. Build a [ParamRegistry] in [DynamicFFGLInstance::registry], e.g. from a
+//!    shader's ISF header
+//! 2. Implement [FFGLInstance] to read and write values by registry index
+//! 3. Call [crate::plugin_main] with a [DynamicFFGLHandler], such as:
+//!    ```rust plugin_main!(DynamicFFGLHandler<MyInstanceType>);```
+//!
+//! The handler wraps each instance in a [Guarded], which catches panics in
+//! its callbacks as [SimpleFFGLInstance](super::simplified::SimpleFFGLInstance)
+//! does.
+
+use super::{FFGLHandler, FFGLInstance};
+
+use crate::panic::{catch_draw_panic, catch_panic};
+use crate::parameters::handler::ParamInfoHandler;
+use crate::parameters::{InputStatus, ParamInfo, ParamRegistry};
+use crate::{FFGLData, GLInput};
+
+/// A handler that takes parameter metadata from a [ParamRegistry] built
+/// once at plugin load.
+pub struct DynamicFFGLHandler<T: DynamicFFGLInstance> {
+    registry: ParamRegistry,
+    _marker: std::marker::PhantomData<T>,
+}
+
+/// Implement this trait for a plugin whose parameters are only known at
+/// runtime.
+pub trait DynamicFFGLInstance: FFGLInstance + Send + Sync {
+    /// Build the parameter list. Only called once per plugin, before any
+    /// parameter is queried.
+    fn registry() -> ParamRegistry;
+
+    fn new(inst_data: &FFGLData, registry: &'static ParamRegistry) -> Self;
+
+    fn plugin_info() -> crate::info::PluginInfo;
+
+    /// See [`FFGLHandler::plugin_caps`].
+    fn plugin_caps() -> crate::info::PluginCaps {
+        crate::info::PluginCaps::default()
+    }
+}
+
+/// A [DynamicFFGLInstance] with its callbacks guarded with [`catch_panic`]:
+/// a panicking `get_param` reads as 0.0, a panicking `input_status` as in use,
+/// a panicking `get_state` or `get_text_param` as empty, and a panicking
+/// `draw` falls back to passing input 0 through to the host FBO. Any other
+/// panicking callback is logged and skipped.
+pub struct Guarded<T>(pub T);
+
+impl<T: DynamicFFGLInstance> FFGLInstance for Guarded<T> {
+    fn get_param(&self, index: usize) -> f32 {
+        catch_panic("get_param", || self.0.get_param(index)).unwrap_or(0.0)
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        catch_panic("set_param", || self.0.set_param(index, value));
+    }
+
+    fn get_text_param(&self, index: usize) -> String {
+        catch_panic("get_text_param", || self.0.get_text_param(index)).unwrap_or_default()
+    }
+
+    fn set_text_param(&mut self, index: usize, value: &str) {
+        catch_panic("set_text_param", || self.0.set_text_param(index, value));
+    }
+
+    fn draw(&mut self, inst_data: &FFGLData, frame_data: GLInput) {
+        catch_draw_panic(inst_data, frame_data, |frame_data| {
+            self.0.draw(inst_data, frame_data)
+        });
+    }
+
+    fn on_resize(&mut self, width: u32, height: u32) {
+        catch_panic("on_resize", || self.0.on_resize(width, height));
+    }
+
+    fn on_connect(&mut self) {
+        catch_panic("on_connect", || self.0.on_connect());
+    }
+
+    fn on_disconnect(&mut self) {
+        catch_panic("on_disconnect", || self.0.on_disconnect());
+    }
+
+    fn input_status(&self, index: usize) -> InputStatus {
+        catch_panic("input_status", || self.0.input_status(index)).unwrap_or(InputStatus::InUse)
+    }
+
+    fn on_input_changed(&mut self, index: usize, connected: bool) {
+        catch_panic("on_input_changed", || {
+            self.0.on_input_changed(index, connected)
+        });
+    }
+
+    fn deinstantiate(&mut self) {
+        catch_panic("deinstantiate", || self.0.deinstantiate());
+    }
+
+    fn get_state(&self) -> Vec<u8> {
+        catch_panic("get_state", || self.0.get_state()).unwrap_or_default()
+    }
+
+    fn set_state(&mut self, state: &[u8]) {
+        catch_panic("set_state", || self.0.set_state(state));
+    }
+}
+
+impl<T: DynamicFFGLInstance> FFGLHandler for DynamicFFGLHandler<T> {
+    type Instance = Guarded<T>;
+    type NewInstanceError = std::convert::Infallible;
+
+    fn init() -> Self {
+        let registry = T::registry();
+        tracing::debug!(?registry, "Built parameter registry");
+        Self {
+            registry,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn num_params(&self) -> usize {
+        self.registry.num_params()
+    }
+
+    fn param_info(&'static self, index: usize) -> &'static dyn ParamInfo {
+        self.registry.param_info(index)
+    }
+
+    fn plugin_info(&self) -> crate::info::PluginInfo {
+        T::plugin_info()
+    }
+
+    fn plugin_caps(&self) -> crate::info::PluginCaps {
+        T::plugin_caps()
+    }
+
+    fn new_instance(
+        &'static self,
+        inst_data: &FFGLData,
+    ) -> Result<Self::Instance, Self::NewInstanceError> {
+        Ok(Guarded(T::new(inst_data, &self.registry)))
+    }
+}
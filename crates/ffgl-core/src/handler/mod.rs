@@ -1,5 +1,6 @@
 //! This module provides the main traits for creating FFGL plugins.
-//! Use [simplified] for a simpler way to create plugins.
+//! Use [simplified] for a simpler way to create plugins, or [dynamic] for
+//! parameters defined at runtime.
 
 use parameters::ParamInfo;
 
@@ -10,12 +11,21 @@ use std::fmt::Debug;
 
 use crate::inputs::FFGLData;
 
+use crate::parameters::InputStatus;
 use crate::{info, inputs::GLInput, parameters};
 
 #[doc(hidden)]
 pub struct Instance<T> {
     pub(crate) data: FFGLData,
     pub(crate) renderer: T,
+    /// Which input slots had a texture last frame.
+    pub(crate) connected_inputs: Vec<bool>,
+    /// The last encoded state handed to the host, kept alive for it to read.
+    pub(crate) state_text: std::ffi::CString,
+    /// The last parameter display string handed to the host.
+    pub(crate) display_text: std::ffi::CString,
+    /// The last text parameter value handed to the host.
+    pub(crate) param_text: std::ffi::CString,
 }
 
 impl<I> Debug for Instance<I> {
@@ -32,8 +42,55 @@ pub trait FFGLInstance {
     fn get_param(&self, index: usize) -> f32;
     fn set_param(&mut self, index: usize, value: f32);
 
+    /// The value of a [text or file](crate::parameters::ParameterTypes::is_text)
+    /// parameter. File parameters hold the path the user picked.
+    fn get_text_param(&self, _index: usize) -> String {
+        String::new()
+    }
+
+    /// Called when the host sets a text or file parameter.
+    fn set_text_param(&mut self, _index: usize, _value: &str) {}
+
     /// Called by [crate::conversions::Op::ProcessOpenGL] to draw the plugin
     fn draw(&mut self, inst_data: &FFGLData, frame_data: GLInput);
+
+    /// Called by [crate::conversions::Op::Resize] with the new viewport size,
+    /// ahead of the next [FFGLInstance::draw].
+    fn on_resize(&mut self, _width: u32, _height: u32) {}
+
+    /// Called by [crate::conversions::Op::Connect]
+    fn on_connect(&mut self) {}
+
+    /// Called by [crate::conversions::Op::Disconnect]
+    fn on_disconnect(&mut self) {}
+
+    /// Answers [crate::conversions::Op::GetInputStatus]. Hosts may skip
+    /// rendering inputs reported as not in use.
+    fn input_status(&self, _index: usize) -> InputStatus {
+        InputStatus::InUse
+    }
+
+    /// Called before [FFGLInstance::draw] when input `index` gained or lost
+    /// its texture since the previous frame. The first frame reports every
+    /// connected input.
+    fn on_input_changed(&mut self, _index: usize, _connected: bool) {}
+
+    /// Called by [crate::conversions::Op::DeinstantiateGL] while the host's
+    /// GL context is still current, right before the instance is dropped.
+    /// Free GL and GPU resources here rather than in `Drop`, which may run
+    /// without a context.
+    fn deinstantiate(&mut self) {}
+
+    /// Settings beyond the numeric parameters, for the host to save with the
+    /// composition. Only asked for with
+    /// [info::PluginCaps::persist_state]; see [crate::state].
+    fn get_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Restore what [FFGLInstance::get_state] returned in an earlier session.
+    /// Not called when the host has no state stored.
+    fn set_state(&mut self, _state: &[u8]) {}
 }
 
 /// This type is created once per plugin load.
@@ -51,10 +108,21 @@ pub trait FFGLHandler: Send + Sync {
 
     fn plugin_info(&'static self) -> info::PluginInfo;
 
+    /// Answers to the host's capability queries. Only called once per plugin.
+    fn plugin_caps(&'static self) -> info::PluginCaps {
+        info::PluginCaps::default()
+    }
+
     fn new_instance(
         &'static self,
         inst_data: &FFGLData,
     ) -> Result<Self::Instance, Self::NewInstanceError>;
+
+    /// Called by [crate::conversions::Op::Deinitialise] when the host is done
+    /// with the plugin, after its instances are gone. No GL context is
+    /// guaranteed to be current.
+    fn deinitialise(&'static self) {}
 }
 
+pub mod dynamic;
 pub mod simplified;
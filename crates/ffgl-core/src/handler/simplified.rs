@@ -5,7 +5,8 @@
 
 use super::FFGLHandler;
 
-use crate::parameters::ParamInfo;
+use crate::panic::{catch_draw_panic, catch_panic};
+use crate::parameters::{InputStatus, ParamInfo};
 
 use crate::{FFGLData, GLInput};
 
@@ -29,6 +30,11 @@ pub trait SimpleFFGLInstance: FFGLInstance + Send + Sync {
 
     fn plugin_info() -> crate::info::PluginInfo;
 
+    /// See [`FFGLHandler::plugin_caps`].
+    fn plugin_caps() -> crate::info::PluginCaps {
+        crate::info::PluginCaps::default()
+    }
+
     fn get_param(&self, _index: usize) -> f32 {
         panic!("No params")
     }
@@ -36,21 +42,129 @@ pub trait SimpleFFGLInstance: FFGLInstance + Send + Sync {
         panic!("No params")
     }
 
+    /// See [FFGLInstance::get_text_param].
+    fn get_text_param(&self, _index: usize) -> String {
+        String::new()
+    }
+
+    /// See [FFGLInstance::set_text_param].
+    fn set_text_param(&mut self, _index: usize, _value: &str) {}
+
+    /// Parameter `index` in the units of its declared range, e.g. pixels
+    /// for a radius declared as `0..20`.
+    fn param_scaled(&self, index: usize) -> f32 {
+        Self::param_info(index).scale(SimpleFFGLInstance::get_param(self, index))
+    }
+
     /// Called by [crate::conversions::Op::ProcessOpenGL] to draw the plugin
     fn draw(&mut self, inst_data: &FFGLData, frame_data: GLInput);
+
+    /// See [FFGLInstance::on_resize]. Resources sized to the viewport can be
+    /// reallocated here rather than mid-frame.
+    fn on_resize(&mut self, _width: u32, _height: u32) {}
+
+    /// See [FFGLInstance::on_connect].
+    fn on_connect(&mut self) {}
+
+    /// See [FFGLInstance::on_disconnect].
+    fn on_disconnect(&mut self) {}
+
+    /// See [FFGLInstance::input_status].
+    fn input_status(&self, _index: usize) -> InputStatus {
+        InputStatus::InUse
+    }
+
+    /// See [FFGLInstance::on_input_changed]. Mixers and mask-aware effects
+    /// can use this to drop state tied to an input that went away.
+    fn on_input_changed(&mut self, _index: usize, _connected: bool) {}
+
+    /// See [FFGLInstance::deinstantiate].
+    fn deinstantiate(&mut self) {}
+
+    /// See [FFGLInstance::get_state].
+    fn get_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// See [FFGLInstance::set_state].
+    fn set_state(&mut self, _state: &[u8]) {}
+
+    /// See [FFGLHandler::deinitialise].
+    fn deinitialise() {}
 }
 
+/// Callbacks are guarded with [`catch_panic`]: a panicking `get_param` reads
+/// as 0.0, a panicking `input_status` as in use, a panicking `get_state` or
+/// `get_text_param` as empty, and a panicking `draw` falls back to passing
+/// input 0 through to the host FBO. Any other panicking callback is logged
+/// and skipped.
 impl<T: SimpleFFGLInstance> FFGLInstance for T {
     fn get_param(&self, index: usize) -> f32 {
-        SimpleFFGLInstance::get_param(self, index)
+        catch_panic("get_param", || SimpleFFGLInstance::get_param(self, index)).unwrap_or(0.0)
     }
 
     fn set_param(&mut self, index: usize, value: f32) {
-        SimpleFFGLInstance::set_param(self, index, value)
+        catch_panic("set_param", || {
+            SimpleFFGLInstance::set_param(self, index, value)
+        });
+    }
+
+    fn get_text_param(&self, index: usize) -> String {
+        catch_panic("get_text_param", || {
+            SimpleFFGLInstance::get_text_param(self, index)
+        })
+        .unwrap_or_default()
+    }
+
+    fn set_text_param(&mut self, index: usize, value: &str) {
+        catch_panic("set_text_param", || {
+            SimpleFFGLInstance::set_text_param(self, index, value)
+        });
     }
 
     fn draw(&mut self, inst_data: &FFGLData, frame_data: GLInput) {
-        SimpleFFGLInstance::draw(self, inst_data, frame_data)
+        catch_draw_panic(inst_data, frame_data, |frame_data| {
+            SimpleFFGLInstance::draw(self, inst_data, frame_data)
+        });
+    }
+
+    fn on_resize(&mut self, width: u32, height: u32) {
+        catch_panic("on_resize", || {
+            SimpleFFGLInstance::on_resize(self, width, height)
+        });
+    }
+
+    fn on_connect(&mut self) {
+        catch_panic("on_connect", || SimpleFFGLInstance::on_connect(self));
+    }
+
+    fn on_disconnect(&mut self) {
+        catch_panic("on_disconnect", || SimpleFFGLInstance::on_disconnect(self));
+    }
+
+    fn input_status(&self, index: usize) -> InputStatus {
+        catch_panic("input_status", || {
+            SimpleFFGLInstance::input_status(self, index)
+        })
+        .unwrap_or(InputStatus::InUse)
+    }
+
+    fn on_input_changed(&mut self, index: usize, connected: bool) {
+        catch_panic("on_input_changed", || {
+            SimpleFFGLInstance::on_input_changed(self, index, connected)
+        });
+    }
+
+    fn deinstantiate(&mut self) {
+        catch_panic("deinstantiate", || SimpleFFGLInstance::deinstantiate(self));
+    }
+
+    fn get_state(&self) -> Vec<u8> {
+        catch_panic("get_state", || SimpleFFGLInstance::get_state(self)).unwrap_or_default()
+    }
+
+    fn set_state(&mut self, state: &[u8]) {
+        catch_panic("set_state", || SimpleFFGLInstance::set_state(self, state));
     }
 }
 
@@ -76,7 +190,15 @@ impl<T: SimpleFFGLInstance> FFGLHandler for SimpleFFGLHandler<T> {
         T::plugin_info()
     }
 
+    fn plugin_caps(&self) -> crate::info::PluginCaps {
+        T::plugin_caps()
+    }
+
     fn new_instance(&self, inst_data: &FFGLData) -> Result<Self::Instance, Self::NewInstanceError> {
         Ok(T::new(inst_data))
     }
+
+    fn deinitialise(&self) {
+        catch_panic("deinitialise", T::deinitialise);
+    }
 }
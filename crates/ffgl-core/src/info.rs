@@ -1,13 +1,16 @@
 //! Use these to configure what the host expects from your plugin
 
+use std::error::Error;
 use std::ffi::c_void;
 use std::ffi::CStr;
+use std::fmt;
 
 use num_derive::FromPrimitive;
 use num_derive::ToPrimitive;
 use num_traits::ToPrimitive;
 
 use crate::ffi::*;
+use crate::thumbnail::Thumbnail;
 
 #[repr(u32)]
 #[derive(FromPrimitive, ToPrimitive, Debug, Clone, Copy, PartialEq, Default)]
@@ -37,6 +40,62 @@ impl FFGLVersion {
     }
 }
 
+/// What the plugin reports to the host's `FF_GETPLUGINCAPS` queries.
+///
+/// The default matches a single-input effect that handles `SetTime` and
+/// accepts top-left texture orientation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginCaps {
+    /// Legacy CPU bit depths (FFGL 1.x `ProcessFrame`); unused by GL hosts.
+    pub video_16bit: bool,
+    pub video_24bit: bool,
+    pub video_32bit: bool,
+    /// Host time is delivered through [`FFGLData::set_time`](crate::FFGLData::set_time).
+    pub set_time: bool,
+    /// Accept input textures with a top-left origin when the host offers
+    /// them. Whether it did is in
+    /// [`FFGLData::top_left_origin`](crate::FFGLData::top_left_origin).
+    pub top_left_orientation: bool,
+    pub min_inputs: u32,
+    pub max_inputs: u32,
+    /// Store [`FFGLInstance::get_state`](crate::handler::FFGLInstance::get_state)
+    /// with the host's composition, through a hidden [`state`](crate::state)
+    /// parameter. Not a host-queried cap.
+    pub persist_state: bool,
+}
+
+impl Default for PluginCaps {
+    fn default() -> Self {
+        Self {
+            video_16bit: false,
+            video_24bit: false,
+            video_32bit: false,
+            set_time: true,
+            top_left_orientation: true,
+            min_inputs: 0,
+            max_inputs: 1,
+            persist_state: false,
+        }
+    }
+}
+
+impl PluginCaps {
+    /// The default caps with an input range suited to `ty`: none for a
+    /// source, one for an effect, two for a mixer.
+    pub fn for_type(ty: PluginType) -> Self {
+        let (min_inputs, max_inputs) = match ty {
+            PluginType::Source => (0, 0),
+            PluginType::Effect => (1, 1),
+            PluginType::Mixer => (2, 2),
+        };
+        Self {
+            min_inputs,
+            max_inputs,
+            ..Self::default()
+        }
+    }
+}
+
 pub(crate) const FFGL_VERSION_RESOLUME: FFGLVersion = FFGLVersion::V2_1;
 
 #[derive(Debug, Clone, Default)]
@@ -46,9 +105,18 @@ pub struct PluginInfo {
     pub ty: PluginType,
     pub about: String,
     pub description: String,
+    /// Preview shown in the host's effect browser, see
+    /// [`include_thumbnail!`](crate::include_thumbnail).
+    pub thumbnail: Option<Thumbnail>,
 }
 
 impl PluginInfo {
+    /// Build a [`PluginInfo`] from plain strings, validated by
+    /// [`PluginInfoBuilder::build`].
+    pub fn builder() -> PluginInfoBuilder {
+        PluginInfoBuilder::default()
+    }
+
     /// Converts to str, trimming null bytes
     pub fn name_str(&self) -> &str {
         let index_first_null = self
@@ -68,6 +136,235 @@ impl PluginInfo {
     }
 }
 
+/// Longest plugin name in bytes. The host's field is 16 bytes, one of which
+/// is kept for the NUL terminator.
+pub const MAX_NAME_LEN: usize = 15;
+
+/// Why a plugin name or unique ID was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginInfoError {
+    /// [`PluginInfoBuilder::name`] was never called.
+    MissingName,
+    /// Neither [`PluginInfoBuilder::unique_id`] nor
+    /// [`PluginInfoBuilder::unique_id_from`] was called.
+    MissingUniqueId,
+    EmptyName,
+    /// Longer than [`MAX_NAME_LEN`] bytes.
+    NameTooLong {
+        len: usize,
+    },
+    /// Contains non-ASCII or control characters.
+    NameNotAscii,
+    /// Not exactly 4 bytes.
+    UniqueIdLength {
+        len: usize,
+    },
+    /// Contains anything but printable, non-space ASCII.
+    UniqueIdNotPrintable,
+}
+
+impl PluginInfoError {
+    /// Description without the offending values, usable in const panics.
+    pub const fn message(&self) -> &'static str {
+        match self {
+            PluginInfoError::MissingName => "plugin name not set",
+            PluginInfoError::MissingUniqueId => "plugin unique ID not set",
+            PluginInfoError::EmptyName => "plugin name is empty",
+            PluginInfoError::NameTooLong { .. } => "plugin name is longer than 15 bytes",
+            PluginInfoError::NameNotAscii => "plugin name must be printable ASCII",
+            PluginInfoError::UniqueIdLength { .. } => "plugin unique ID must be 4 bytes",
+            PluginInfoError::UniqueIdNotPrintable => {
+                "plugin unique ID must be printable ASCII without spaces"
+            }
+        }
+    }
+}
+
+impl fmt::Display for PluginInfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginInfoError::NameTooLong { len } | PluginInfoError::UniqueIdLength { len } => {
+                write!(f, "{} (got {len})", self.message())
+            }
+            _ => f.write_str(self.message()),
+        }
+    }
+}
+
+impl Error for PluginInfoError {}
+
+/// The NUL-padded name field for `name`. See [`plugin_name!`](crate::plugin_name)
+/// to check a literal at compile time.
+pub const fn parse_name(name: &str) -> Result<[u8; 16], PluginInfoError> {
+    let bytes = name.as_bytes();
+    if bytes.is_empty() {
+        return Err(PluginInfoError::EmptyName);
+    }
+    if bytes.len() > MAX_NAME_LEN {
+        return Err(PluginInfoError::NameTooLong { len: bytes.len() });
+    }
+    let mut out = [0; 16];
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii() || bytes[i].is_ascii_control() {
+            return Err(PluginInfoError::NameNotAscii);
+        }
+        out[i] = bytes[i];
+        i += 1;
+    }
+    Ok(out)
+}
+
+/// The unique ID field for `id`. See [`unique_id!`](crate::unique_id) to
+/// check a literal at compile time.
+pub const fn parse_unique_id(id: &str) -> Result<[u8; 4], PluginInfoError> {
+    let bytes = id.as_bytes();
+    if bytes.len() != 4 {
+        return Err(PluginInfoError::UniqueIdLength { len: bytes.len() });
+    }
+    let mut out = [0; 4];
+    let mut i = 0;
+    while i < 4 {
+        if !bytes[i].is_ascii_graphic() {
+            return Err(PluginInfoError::UniqueIdNotPrintable);
+        }
+        out[i] = bytes[i];
+        i += 1;
+    }
+    Ok(out)
+}
+
+/// A unique ID derived from `seed`: four alphanumeric characters taken from
+/// its FNV-1a hash. The same seed always gives the same ID, so a plugin can
+/// use its crate name (see [`crate_unique_id!`](crate::crate_unique_id))
+/// without picking one by hand. Different seeds can still collide; pick an
+/// ID explicitly if a host reports a clash.
+pub const fn derive_unique_id(seed: &str) -> [u8; 4] {
+    const ALPHABET: &[u8; 62] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let bytes = seed.as_bytes();
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+        i += 1;
+    }
+    let mut id = [0; 4];
+    let mut i = 0;
+    while i < 4 {
+        id[i] = ALPHABET[(hash % 62) as usize];
+        hash /= 62;
+        i += 1;
+    }
+    id
+}
+
+/// The name field for a string literal, checked at compile time.
+#[macro_export]
+macro_rules! plugin_name {
+    ($name:expr) => {
+        const {
+            match $crate::info::parse_name($name) {
+                Ok(name) => name,
+                Err(e) => panic!("{}", e.message()),
+            }
+        }
+    };
+}
+
+/// The unique ID field for a string literal, checked at compile time.
+#[macro_export]
+macro_rules! unique_id {
+    ($id:expr) => {
+        const {
+            match $crate::info::parse_unique_id($id) {
+                Ok(id) => id,
+                Err(e) => panic!("{}", e.message()),
+            }
+        }
+    };
+}
+
+/// A unique ID derived from the calling crate's name, see
+/// [`derive_unique_id`](crate::info::derive_unique_id).
+#[macro_export]
+macro_rules! crate_unique_id {
+    () => {
+        const { $crate::info::derive_unique_id(env!("CARGO_PKG_NAME")) }
+    };
+}
+
+/// Builds a [`PluginInfo`] from plain strings:
+///
+/// ```
+/// # use ffgl_core::info::{PluginInfo, PluginType};
+/// let info = PluginInfo::builder()
+///     .name("Invert")
+///     .unique_id("INVT")
+///     .ty(PluginType::Effect)
+///     .build()
+///     .unwrap();
+/// assert_eq!(&info.name, b"Invert\0\0\0\0\0\0\0\0\0\0");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PluginInfoBuilder {
+    name: Option<String>,
+    unique_id: Option<String>,
+    info: PluginInfo,
+}
+
+impl PluginInfoBuilder {
+    /// Display name, at most [`MAX_NAME_LEN`] bytes of printable ASCII.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Four printable ASCII characters identifying the plugin to the host.
+    pub fn unique_id(mut self, id: impl Into<String>) -> Self {
+        self.unique_id = Some(id.into());
+        self
+    }
+
+    /// Derive the unique ID from `seed`, see [`derive_unique_id`].
+    pub fn unique_id_from(mut self, seed: &str) -> Self {
+        let id = derive_unique_id(seed);
+        self.unique_id = Some(id.iter().map(|&b| b as char).collect());
+        self
+    }
+
+    pub fn ty(mut self, ty: PluginType) -> Self {
+        self.info.ty = ty;
+        self
+    }
+
+    pub fn about(mut self, about: impl Into<String>) -> Self {
+        self.info.about = about.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.info.description = description.into();
+        self
+    }
+
+    pub fn thumbnail(mut self, thumbnail: Option<Thumbnail>) -> Self {
+        self.info.thumbnail = thumbnail;
+        self
+    }
+
+    /// Validate the name and unique ID and assemble the [`PluginInfo`].
+    pub fn build(self) -> Result<PluginInfo, PluginInfoError> {
+        let name = self.name.ok_or(PluginInfoError::MissingName)?;
+        let unique_id = self.unique_id.ok_or(PluginInfoError::MissingUniqueId)?;
+        Ok(PluginInfo {
+            name: parse_name(&name)?,
+            unique_id: parse_unique_id(&unique_id)?,
+            ..self.info
+        })
+    }
+}
+
 pub fn plugin_info(
     unique_id: &[i8; 4],
     name: &[i8; 16],
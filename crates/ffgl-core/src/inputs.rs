@@ -1,13 +1,18 @@
 //! Inputs from the host to your plugin
 
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::ffi::*;
 
 /// Texture inputs
+///
+/// `textures` has one entry per input slot the host passed; an entry is
+/// `None` when that input is not connected.
 #[derive(Debug)]
 pub struct GLInput<'a> {
-    pub textures: &'a [FFGLTextureStruct],
+    pub textures: &'a [Option<&'a FFGLTextureStruct>],
     pub host: u32,
 }
 
@@ -17,9 +22,11 @@ impl<'a> From<&'a ProcessOpenGLStruct> for GLInput<'a> {
             textures: if val.numInputTextures == 0 || val.inputTextures.is_null() {
                 &[]
             } else {
+                // `inputTextures` is an array of (possibly null) pointers,
+                // which has the layout of `[Option<&FFGLTextureStruct>]`.
                 unsafe {
                     std::slice::from_raw_parts(
-                        *val.inputTextures as *const _,
+                        val.inputTextures as *const Option<&FFGLTextureStruct>,
                         val.numInputTextures as usize,
                     )
                 }
@@ -29,6 +36,104 @@ impl<'a> From<&'a ProcessOpenGLStruct> for GLInput<'a> {
     }
 }
 
+impl<'a> GLInput<'a> {
+    /// Number of input slots the host passed, connected or not.
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// The texture connected to input `index`, if any.
+    pub fn texture(&self, index: usize) -> Option<&'a FFGLTextureStruct> {
+        self.textures.get(index).copied().flatten()
+    }
+
+    /// Blit the image part of input 0 into the host FBO's `viewport`, scaled
+    /// to fill it, through a temporary read FBO. The framebuffer bindings and
+    /// scissor test are left as found. Draws nothing if input 0 is
+    /// disconnected.
+    ///
+    /// # Safety
+    /// A GL context must be current.
+    pub unsafe fn blit_input_to_host(&self, viewport: &FFGLViewportStruct) -> anyhow::Result<()> {
+        use gl::types::{GLint, GLuint};
+
+        let Some(input) = self.texture(0) else {
+            return Ok(());
+        };
+        let vp = viewport;
+        if input.Width == 0 || input.Height == 0 || vp.width == 0 || vp.height == 0 {
+            return Ok(());
+        }
+        // Hosts lacking these get nothing drawn rather than a panic from an
+        // unloaded entry point.
+        if !gl::BlitFramebuffer::is_loaded() || !gl::GenFramebuffers::is_loaded() {
+            anyhow::bail!("no glBlitFramebuffer");
+        }
+
+        let mut read_fbo: GLint = 0;
+        let mut draw_fbo: GLint = 0;
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut read_fbo);
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut draw_fbo);
+        let scissor_enabled = gl::IsEnabled(gl::SCISSOR_TEST) == gl::TRUE;
+
+        let mut fbo: GLuint = 0;
+        gl::GenFramebuffers(1, &mut fbo);
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, fbo);
+        gl::FramebufferTexture2D(
+            gl::READ_FRAMEBUFFER,
+            gl::COLOR_ATTACHMENT0,
+            gl::TEXTURE_2D,
+            input.Handle,
+            0,
+        );
+        let status = gl::CheckFramebufferStatus(gl::READ_FRAMEBUFFER);
+        if status == gl::FRAMEBUFFER_COMPLETE {
+            // Blits are clipped by the scissor box; the host's is unrelated.
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.host);
+            gl::BlitFramebuffer(
+                0,
+                0,
+                input.Width as GLint,
+                input.Height as GLint,
+                vp.x as GLint,
+                vp.y as GLint,
+                (vp.x + vp.width) as GLint,
+                (vp.y + vp.height) as GLint,
+                gl::COLOR_BUFFER_BIT,
+                gl::LINEAR,
+            );
+            if scissor_enabled {
+                gl::Enable(gl::SCISSOR_TEST);
+            }
+        }
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, read_fbo as GLuint);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, draw_fbo as GLuint);
+        gl::DeleteFramebuffers(1, &fbo);
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            anyhow::bail!(
+                "read framebuffer for texture {} incomplete ({status:#x})",
+                input.Handle
+            );
+        }
+        Ok(())
+    }
+}
+
+impl FFGLTextureStruct {
+    /// Part of the allocated texture covered by the image, as UV scale
+    /// factors. Less than 1.0 when the host pads textures to a larger
+    /// `HardwareWidth`/`HardwareHeight` (e.g. power-of-two allocations).
+    pub fn uv_scale(&self) -> (f32, f32) {
+        (
+            self.Width as f32 / self.HardwareWidth.max(self.Width).max(1) as f32,
+            self.Height as f32 / self.HardwareHeight.max(self.Height).max(1) as f32,
+        )
+    }
+}
+
 /// Standard data that hosts provide to all programs
 #[derive(Debug)]
 pub struct FFGLData {
@@ -36,6 +141,15 @@ pub struct FFGLData {
     pub viewport: FFGLViewportStruct,
     pub host_time: SystemTime,
     pub host_beat: SetBeatinfoStruct,
+    /// Input textures have their origin at the top left: the plugin declared
+    /// [`PluginCaps::top_left_orientation`](crate::info::PluginCaps::top_left_orientation)
+    /// and the host enabled it.
+    pub top_left_origin: bool,
+    /// Random value drawn when the instance was created. Stacked instances
+    /// get different seeds, and so does the same instance after a reload.
+    pub instance_seed: u32,
+    /// Number of frames drawn so far; incremented after each draw.
+    pub frame_index: u64,
 }
 
 impl FFGLData {
@@ -48,9 +162,22 @@ impl FFGLData {
                 bpm: 120.0,
                 barPhase: 0.0,
             },
+            top_left_origin: false,
+            // `RandomState` is keyed from the OS's random source.
+            instance_seed: RandomState::new().hash_one(Instant::now()) as u32,
+            frame_index: 0,
         }
     }
 
+    /// A seed for the current frame's random numbers, mixed from
+    /// [`instance_seed`](Self::instance_seed) and
+    /// [`frame_index`](Self::frame_index). Changes every frame and differs
+    /// between instances.
+    pub fn frame_seed(&self) -> u32 {
+        let frame = self.frame_index as u32 ^ (self.frame_index >> 32) as u32;
+        pcg_hash(self.instance_seed ^ pcg_hash(frame))
+    }
+
     pub fn set_beat(&mut self, beat: SetBeatinfoStruct) {
         self.host_beat = beat;
     }
@@ -63,3 +190,11 @@ impl FFGLData {
         (self.viewport.width, self.viewport.height)
     }
 }
+
+/// The PCG hash from Jarzynski and Olano, "Hash Functions for GPU
+/// Rendering" (2020), as in the shader helpers.
+fn pcg_hash(v: u32) -> u32 {
+    let state = v.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    (word >> 22) ^ word
+}
@@ -32,6 +32,9 @@ pub mod ffi;
 pub mod info;
 pub mod inputs;
 pub mod log;
+pub mod panic;
+pub mod state;
+pub mod thumbnail;
 
 pub mod handler;
 pub mod plugin_main;
@@ -3,11 +3,15 @@
 use std::{
     borrow::Cow,
     ffi::{c_char, CString},
-    io,
-    sync::OnceLock,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 static LOADING_LOGGER: OnceLock<FFGLLogger> = OnceLock::new();
+static FILE_LOG: OnceLock<Option<Mutex<RotatingFile>>> = OnceLock::new();
 
 /// Type of the logging function the plugin can call
 #[doc(hidden)]
@@ -28,6 +32,12 @@ impl io::Write for FFGLWriter {
             str = Cow::Owned(str.to_string().trim_end_matches('\n').to_string());
         }
 
+        if let Some(Some(file)) = FILE_LOG.get() {
+            if let Ok(mut file) = file.lock() {
+                file.write_line(&str);
+            }
+        }
+
         if let Some(logger) = LOADING_LOGGER.get() {
             let str = CString::new(str.as_bytes()).unwrap();
 
@@ -68,6 +78,134 @@ pub fn init_default_subscriber() {
     }
 }
 
+/// Where and how much to log to disk, read from the environment.
+///
+/// Hosts usually swallow stdout/stderr, so file logging is the reliable way
+/// to collect diagnostics from users. It is off unless `FFGL_LOG_DIR` is set:
+///
+/// - `FFGL_LOG_DIR`: root directory; each plugin logs to its own
+///   subdirectory, named after the plugin.
+/// - `FFGL_LOG_MAX_BYTES`: size at which the file is rotated (default 5 MiB).
+/// - `FFGL_LOG_MAX_FILES`: rotated files kept besides the current one
+///   (default 4).
+///
+/// The usual `RUST_LOG` filter applies to file output as well.
+#[derive(Debug, Clone)]
+pub struct FileLogConfig {
+    pub dir: PathBuf,
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+impl FileLogConfig {
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var_os("FFGL_LOG_DIR").filter(|d| !d.is_empty())?;
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse().ok());
+        Some(Self {
+            dir: dir.into(),
+            max_bytes: var("FFGL_LOG_MAX_BYTES").unwrap_or(5 * 1024 * 1024),
+            max_files: var("FFGL_LOG_MAX_FILES")
+                .map(|n: u64| n as usize)
+                .unwrap_or(4),
+        })
+    }
+}
+
+/// Size-rotated log file: `name.log`, then `name.log.1` (newest) up to
+/// `name.log.<max_files>`.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    fn open(config: &FileLogConfig, plugin_name: &str) -> io::Result<Self> {
+        let name: String = plugin_name
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let dir = config.dir.join(&name);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{name}.log"));
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            written,
+            max_bytes: config.max_bytes,
+            max_files: config.max_files,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.written >= self.max_bytes {
+            if let Err(err) = self.rotate() {
+                eprintln!("Failed to rotate {}: {err}", self.path.display());
+            }
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let line = format!("[{}.{:03}] {line}\n", now.as_secs(), now.subsec_millis());
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.written += line.len() as u64;
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |n: usize| PathBuf::from(format!("{}.{n}", self.path.display()));
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated(self.max_files));
+            for n in (1..self.max_files).rev() {
+                let _ = fs::rename(rotated(n), rotated(n + 1));
+            }
+            fs::rename(&self.path, rotated(1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
+
+/// Start writing log output to a rotating file for `plugin_name`, if
+/// [`FileLogConfig::from_env`] enables it. Also installs the panic hook so
+/// panics end up in the file with a backtrace.
+///
+/// Called automatically by the FFGL entry point; only the first call has an
+/// effect. Returns whether file logging is active.
+pub fn init_file_logging(plugin_name: &str) -> bool {
+    let file = FILE_LOG.get_or_init(|| {
+        let config = FileLogConfig::from_env()?;
+        match RotatingFile::open(&config, plugin_name) {
+            Ok(file) => Some(Mutex::new(file)),
+            Err(err) => {
+                eprintln!("Failed to open log file in {}: {err}", config.dir.display());
+                None
+            }
+        }
+    });
+    if file.is_some() {
+        crate::panic::install_hook();
+    }
+    file.is_some()
+}
+
 /// Only called by the plugin loader
 #[doc(hidden)]
 pub fn init_logger(logger: FFGLLogger) {
@@ -84,9 +222,7 @@ pub fn init_logger(logger: FFGLLogger) {
         }
     };
 
-    std::panic::set_hook(Box::new(|cause| {
-        tracing::error!("{}", cause);
-    }));
+    crate::panic::install_hook();
 }
 
 use tracing_subscriber::{filter::LevelFilter, util::SubscriberInitExt};
@@ -0,0 +1,84 @@
+//! Panic containment for plugin callbacks.
+//!
+//! A panic must never unwind across the FFGL C ABI. [`crate::plugin_main`]
+//! already catches panics at that boundary, but by then the frame is lost and
+//! the host gets a failure code. [`catch_panic`] contains a panic closer to the
+//! plugin code that raised it, so the caller can degrade (e.g. draw a
+//! passthrough frame) and keep going.
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use crate::{FFGLData, GLInput};
+
+static HOOK: Once = Once::new();
+
+thread_local! {
+    /// Backtrace of the most recent panic on this thread, captured by the hook.
+    static LAST_BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Install the panic hook that logs the panic message and records a backtrace
+/// for [`catch_panic`]. Called automatically by the FFGL entry point; calling
+/// it again has no effect.
+pub fn install_hook() {
+    HOOK.call_once(|| {
+        panic::set_hook(Box::new(|cause| {
+            tracing::error!("{}", cause);
+            let backtrace = Backtrace::force_capture();
+            LAST_BACKTRACE.with(|cell| *cell.borrow_mut() = Some(backtrace));
+        }));
+    });
+}
+
+/// Run `f`, catching a panic. On panic, logs `what` with the panic message and
+/// backtrace and returns `None`.
+///
+/// The closure is treated as unwind safe: callers are expected to discard or
+/// reset whatever state the panicking code was mutating.
+pub fn catch_panic<R>(what: &str, f: impl FnOnce() -> R) -> Option<R> {
+    install_hook();
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(r) => Some(r),
+        Err(payload) => {
+            let backtrace = LAST_BACKTRACE.with(|cell| cell.borrow_mut().take());
+            tracing::error!(
+                "PANIC in {what}: {}\n{}",
+                payload_message(&*payload),
+                backtrace.map(|b| b.to_string()).unwrap_or_default(),
+            );
+            None
+        }
+    }
+}
+
+/// Run a plugin's `draw` through [`catch_panic`]. If it panics, input 0 is
+/// passed through to the host FBO so the host doesn't show an empty frame.
+pub(crate) fn catch_draw_panic(
+    inst_data: &FFGLData,
+    frame_data: GLInput,
+    draw: impl FnOnce(GLInput),
+) {
+    let passthrough = GLInput {
+        textures: frame_data.textures,
+        host: frame_data.host,
+    };
+    if catch_panic("draw", || draw(frame_data)).is_none() {
+        if let Err(e) = unsafe { passthrough.blit_input_to_host(&inst_data.viewport) } {
+            tracing::warn!("Passthrough after a panicking draw failed: {e}");
+        }
+    }
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "<non-string panic payload>"
+    }
+}
@@ -0,0 +1,59 @@
+//! Shader-ready values from FFGL color parameters.
+//!
+//! Hosts show [Hue](super::ParameterTypes::Hue),
+//! [Saturation](super::ParameterTypes::Saturation),
+//! [Brightness](super::ParameterTypes::Brightness) and
+//! [Alpha](super::ParameterTypes::Alpha) parameters as one color picker, each
+//! sent as 0–1. Hue runs red, yellow, green, cyan, blue, magenta and back to
+//! red at 1, as in Resolume's picker. Converting on the CPU and passing RGB
+//! to the shader keeps every effect on that convention.
+
+/// RGB for a hue, saturation and brightness, all 0–1. Hue wraps around.
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let saturation = saturation.clamp(0.0, 1.0);
+    let value = value.clamp(0.0, 1.0);
+    let h = hue.rem_euclid(1.0) * 6.0;
+    let c = value * saturation;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = value - c;
+    [r + m, g + m, b + m]
+}
+
+/// [hsv_to_rgb] with alpha appended, for `float4` uniforms.
+pub fn hsva_to_rgba(hue: f32, saturation: f32, value: f32, alpha: f32) -> [f32; 4] {
+    let [r, g, b] = hsv_to_rgb(hue, saturation, value);
+    [r, g, b, alpha.clamp(0.0, 1.0)]
+}
+
+/// Hue, saturation and brightness (0–1) of an RGB color, e.g. to report a
+/// default color as hue and saturation parameter defaults. Grays have hue 0.
+pub fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let sector = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    let hue = sector / 6.0;
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    [hue, saturation, max]
+}
+
+/// A hue parameter in degrees, 0–360.
+pub fn hue_to_degrees(hue: f32) -> f32 {
+    hue.rem_euclid(1.0) * 360.0
+}
@@ -26,6 +26,8 @@ pub enum ParameterTypes {
     Saturation = FF_TYPE_SATURATION,
     Brightness = FF_TYPE_BRIGHTNESS,
     Alpha = FF_TYPE_ALPHA,
+    Text = FF_TYPE_TEXT,
+    File = FF_TYPE_FILE,
 }
 
 impl From<ParameterTypes> for FFGLVal {
@@ -38,15 +40,27 @@ impl ParameterTypes {
     pub fn default_value(&self) -> f32 {
         0.0
     }
+
+    /// Whether values of this type are strings, passed to and from the host
+    /// as `char*` rather than floats.
+    pub fn is_text(&self) -> bool {
+        matches!(self, ParameterTypes::Text | ParameterTypes::File)
+    }
 }
 
 #[repr(u32)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InputStatus {
     NotInUse = FF_INPUT_NOTINUSE,
     InUse = FF_INPUT_INUSE,
 }
 
+impl From<InputStatus> for FFGLVal {
+    fn from(value: InputStatus) -> Self {
+        FFGLVal { num: value as u32 }
+    }
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy)]
 pub enum ParameterUsages {
@@ -107,6 +121,67 @@ pub trait ParamInfo {
     fn group(&self) -> &str {
         ""
     }
+
+    /// Whether the host sees this parameter in [ParamInfo::min]..[ParamInfo::max]
+    /// units. FFGL 2 ranges apply to standard and integer parameters; the
+    /// plugin still gets and sets them as 0–1.
+    fn has_range(&self) -> bool {
+        matches!(
+            self.param_type(),
+            ParameterTypes::Standard | ParameterTypes::Integer
+        )
+    }
+
+    /// A 0–1 value mapped onto [ParamInfo::min]..[ParamInfo::max].
+    fn scale(&self, normalized: f32) -> f32 {
+        self.min() + normalized * (self.max() - self.min())
+    }
+
+    /// The inverse of [ParamInfo::scale], clamped to 0–1.
+    fn normalize(&self, value: f32) -> f32 {
+        let span = self.max() - self.min();
+        if span == 0.0 {
+            0.0
+        } else {
+            ((value - self.min()) / span).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Text the host shows for `value`, answering
+    /// [crate::conversions::Op::GetParameterDisplay]. The default is
+    /// [display_value] without a unit.
+    fn display_value(&self, value: f32) -> String {
+        display_value(self, value, "")
+    }
+}
+
+/// Format `value` the way [ParamInfo::display_value] does by default:
+/// toggles as On/Off, options by element name, hues in degrees, and other
+/// types mapped onto [ParamInfo::min]..[ParamInfo::max], with integers
+/// rounded. A non-empty `unit` follows the number, e.g. `12 px`.
+pub fn display_value<P: ParamInfo + ?Sized>(param: &P, value: f32, unit: &str) -> String {
+    let unit = if unit.is_empty() {
+        String::new()
+    } else {
+        format!(" {unit}")
+    };
+    let scaled = param.scale(value);
+    match param.param_type() {
+        ParameterTypes::Boolean | ParameterTypes::Event => {
+            if value >= 0.5 { "On" } else { "Off" }.to_owned()
+        }
+        ParameterTypes::Option => (0..param.num_elements())
+            .min_by(|&a, &b| {
+                let distance = |i| (param.element_value(i) - value).abs();
+                distance(a).total_cmp(&distance(b))
+            })
+            .map(|i| param.element_name(i).to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        ParameterTypes::Hue => format!("{:.0}\u{b0}", super::color::hue_to_degrees(value)),
+        ParameterTypes::Integer => format!("{}{unit}", scaled.round() as i64),
+        ParameterTypes::Text => String::new(),
+        _ => format!("{scaled:.2}{unit}"),
+    }
 }
 
 pub trait ParamValue {
@@ -134,6 +209,8 @@ pub struct SimpleParamInfo {
     pub group: Option<String>,
     pub display_name: Option<String>,
     pub elements: Option<Vec<(CString, f32)>>,
+    /// Shown after the value in the host, e.g. `px`.
+    pub unit: Option<String>,
 }
 
 impl SimpleParamInfo {
@@ -178,6 +255,10 @@ impl ParamInfo for SimpleParamInfo {
         self.group.as_deref().unwrap_or("")
     }
 
+    fn display_value(&self, value: f32) -> String {
+        display_value(self, value, self.unit.as_deref().unwrap_or(""))
+    }
+
     fn element_name(&self, index: usize) -> &CStr {
         if let Some(elements) = &self.elements {
             elements
@@ -1,8 +1,15 @@
 //! Structs and enums for FFGL parameters.
 //! Use [info::SimpleParamInfo] for most simple instances.
-//! Implement [info::ParamInfo] yourself for more complex cases.
+//! Implement [info::ParamInfo] yourself for more complex cases, or collect
+//! parameters built at runtime in a [ParamRegistry].
+//! Keep an instance's values in a [ParamStore] to know which changed.
 
 pub mod builtin;
+pub mod color;
 pub mod handler;
 mod info;
+mod registry;
+mod store;
 pub use info::*;
+pub use registry::ParamRegistry;
+pub use store::ParamStore;
@@ -0,0 +1,80 @@
+use super::handler::ParamInfoHandler;
+use super::info::ParamInfo;
+
+/// Parameter metadata built at runtime, once per plugin load.
+///
+/// For plugins whose parameters aren't known at compile time: shader loaders
+/// reading an ISF header, preset systems, generated effects. Build one in
+/// [`DynamicFFGLInstance::registry`](crate::handler::dynamic::DynamicFFGLInstance::registry);
+/// every instance then shares it.
+#[derive(Default)]
+pub struct ParamRegistry {
+    params: Vec<Box<dyn ParamInfo + Send + Sync>>,
+}
+
+impl ParamRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a parameter and return its index.
+    pub fn push(&mut self, param: impl ParamInfo + Send + Sync + 'static) -> usize {
+        self.params.push(Box::new(param));
+        self.params.len() - 1
+    }
+
+    /// Builder-style [`push`](Self::push).
+    pub fn with(mut self, param: impl ParamInfo + Send + Sync + 'static) -> Self {
+        self.push(param);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&dyn ParamInfo> {
+        self.params.get(index).map(|p| &**p as &dyn ParamInfo)
+    }
+
+    /// Index of the parameter called `name`.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.params
+            .iter()
+            .position(|p| p.name().to_str() == Ok(name))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ParamInfo> {
+        self.params.iter().map(|p| &**p as &dyn ParamInfo)
+    }
+
+    /// Every parameter's default value, in order. A starting point for an
+    /// instance's values.
+    pub fn defaults(&self) -> Vec<f32> {
+        self.iter().map(|p| p.default_val()).collect()
+    }
+}
+
+impl ParamInfoHandler for ParamRegistry {
+    fn num_params(&self) -> usize {
+        self.len()
+    }
+
+    fn param_info(&self, index: usize) -> &dyn ParamInfo {
+        self.get(index).unwrap_or_else(|| {
+            panic!("param_info: index {index} out of bounds (host sent invalid parameter index)")
+        })
+    }
+}
+
+impl std::fmt::Debug for ParamRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.params.iter().map(|p| p.name()))
+            .finish()
+    }
+}
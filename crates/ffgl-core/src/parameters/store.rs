@@ -0,0 +1,91 @@
+use super::info::ParamInfo;
+
+/// Parameter values that remember which ones changed.
+///
+/// [`set`](Self::set) marks a parameter as changed when its value differs
+/// from the stored one, so a plugin can skip rebuilding lookup tables,
+/// kernel weights or pipelines on frames where nothing it depends on moved.
+/// Every parameter starts out changed, so the first frame builds everything.
+///
+/// Call [`clear_changed`](Self::clear_changed) once a frame has used the
+/// values. GPU plugins built on `ffgl-gpu` can return the store from
+/// `GpuPlugin::param_store` and have the framework clear it after each
+/// frame it drew.
+#[derive(Debug, Clone, Default)]
+pub struct ParamStore {
+    values: Vec<f32>,
+    changed: Vec<bool>,
+}
+
+impl ParamStore {
+    /// A store holding `values`, all marked as changed.
+    pub fn new(values: Vec<f32>) -> Self {
+        let changed = vec![true; values.len()];
+        Self { values, changed }
+    }
+
+    /// A store holding each parameter's default value.
+    pub fn from_defaults<'a, P: ParamInfo + ?Sized + 'a>(
+        params: impl IntoIterator<Item = &'a P>,
+    ) -> Self {
+        Self::new(params.into_iter().map(|p| p.default_val()).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The value at `index`, or 0 for an index out of range.
+    pub fn get(&self, index: usize) -> f32 {
+        self.values.get(index).copied().unwrap_or(0.0)
+    }
+
+    /// Store `value` at `index`, marking it changed if it differs from the
+    /// current value. Indices out of range are ignored.
+    pub fn set(&mut self, index: usize, value: f32) {
+        if let Some(current) = self.values.get_mut(index) {
+            if *current != value {
+                *current = value;
+                self.changed[index] = true;
+            }
+        }
+    }
+
+    /// All values, in parameter order.
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Whether the parameter at `index` changed since the last
+    /// [`clear_changed`](Self::clear_changed).
+    pub fn changed(&self, index: usize) -> bool {
+        self.changed.get(index).copied().unwrap_or(false)
+    }
+
+    /// Whether any parameter changed since the last
+    /// [`clear_changed`](Self::clear_changed).
+    pub fn any_changed(&self) -> bool {
+        self.changed.contains(&true)
+    }
+
+    /// Whether any parameter in `indices` changed, e.g. the group feeding
+    /// one lookup table.
+    pub fn any_changed_in(&self, indices: impl IntoIterator<Item = usize>) -> bool {
+        indices.into_iter().any(|i| self.changed(i))
+    }
+
+    /// Mark every parameter as changed, e.g. after GPU resources were
+    /// rebuilt and need filling again.
+    pub fn mark_all_changed(&mut self) {
+        self.changed.fill(true);
+    }
+
+    /// Forget the changes, once a frame has used the current values.
+    pub fn clear_changed(&mut self) {
+        self.changed.fill(false);
+    }
+}
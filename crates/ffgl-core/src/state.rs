@@ -0,0 +1,77 @@
+//! Plugin state beyond the numeric parameters, stored by the host.
+//!
+//! FFGL has no opcode for opaque state, but hosts save the value of text
+//! parameters with the composition and set it again on load. With
+//! [`PluginCaps::persist_state`](crate::info::PluginCaps::persist_state) the
+//! entry point appends one hidden text parameter after the plugin's own. The
+//! host reads it through [`FFGLInstance::get_state`] and writes it through
+//! [`FFGLInstance::set_state`], base64-encoded in between.
+//!
+//! [`FFGLInstance::get_state`]: crate::handler::FFGLInstance::get_state
+//! [`FFGLInstance::set_state`]: crate::handler::FFGLInstance::set_state
+
+use std::ffi::{CStr, CString};
+
+use crate::parameters::{ParamInfo, ParameterTypes};
+
+/// The hidden text parameter holding the state.
+pub struct StateParam;
+
+impl ParamInfo for StateParam {
+    fn name(&self) -> &CStr {
+        c"Plugin State"
+    }
+
+    fn param_type(&self) -> ParameterTypes {
+        ParameterTypes::Text
+    }
+}
+
+/// Default value of the state parameter: no state.
+pub(crate) const EMPTY: &CStr = c"";
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// `state` as padded base64, ready to hand to the host.
+pub(crate) fn encode(state: &[u8]) -> CString {
+    let mut out = Vec::with_capacity(state.len().div_ceil(3) * 4);
+    for chunk in state.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63]);
+            } else {
+                out.push(b'=');
+            }
+        }
+    }
+    CString::new(out).expect("base64 has no NUL bytes")
+}
+
+/// Decode the base64 text the host stored. `None` if it isn't valid base64.
+pub(crate) fn decode(text: &[u8]) -> Option<Vec<u8>> {
+    let text = text.trim_ascii();
+    if !text.len().is_multiple_of(4) {
+        return None;
+    }
+    let value = |c: u8| ALPHABET.iter().position(|&a| a == c).map(|v| v as u32);
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    for chunk in text.chunks(4) {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 {
+            return None;
+        }
+        let mut n = 0;
+        for &c in &chunk[..4 - padding] {
+            n = n << 6 | value(c)?;
+        }
+        n <<= 6 * padding;
+        out.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+    Some(out)
+}
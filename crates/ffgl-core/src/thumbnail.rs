@@ -0,0 +1,147 @@
+//! Preview image shown in the host's effect browser (FFGL 2.2
+//! `FF_GET_THUMBNAIL`).
+//!
+//! Set [`PluginInfo::thumbnail`](crate::info::PluginInfo::thumbnail), most
+//! easily by embedding a PNG next to the plugin's source:
+//!
+//! ```ignore
+//! fn plugin_info() -> PluginInfo {
+//!     PluginInfo {
+//!         thumbnail: ffgl_core::include_thumbnail!("../thumbnail.png"),
+//!         ..
+//!     }
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+/// An 8-bit RGBA image, rows top to bottom.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+/// Why a [`Thumbnail`] couldn't be created.
+#[derive(Debug)]
+pub enum ThumbnailError {
+    /// `rgba` isn't `width * height * 4` bytes.
+    Size { width: u32, height: u32, len: usize },
+    /// The PNG couldn't be decoded.
+    #[cfg(feature = "png")]
+    Png(png::DecodingError),
+}
+
+impl fmt::Display for ThumbnailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThumbnailError::Size { width, height, len } => write!(
+                f,
+                "{width}x{height} RGBA thumbnail needs {} bytes, got {len}",
+                *width as usize * *height as usize * 4
+            ),
+            #[cfg(feature = "png")]
+            ThumbnailError::Png(e) => write!(f, "invalid PNG thumbnail: {e}"),
+        }
+    }
+}
+
+impl Error for ThumbnailError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "png")]
+            ThumbnailError::Png(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Thumbnail {
+    /// Wrap `width * height` RGBA pixels, 4 bytes each.
+    pub fn from_rgba(width: u32, height: u32, rgba: Vec<u8>) -> Result<Self, ThumbnailError> {
+        if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+            return Err(ThumbnailError::Size {
+                width,
+                height,
+                len: rgba.len(),
+            });
+        }
+        Ok(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    /// Decode a PNG of any color type and bit depth to RGBA8.
+    #[cfg(feature = "png")]
+    pub fn from_png(bytes: &[u8]) -> Result<Self, ThumbnailError> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(ThumbnailError::Png)?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut buf).map_err(ThumbnailError::Png)?;
+        buf.truncate(frame.buffer_size());
+
+        let rgba = match frame.color_type {
+            png::ColorType::Rgba => buf,
+            png::ColorType::Rgb => buf
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buf
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            // Indexed is expanded to RGB(A) by `normalize_to_color8`.
+            png::ColorType::Grayscale | png::ColorType::Indexed => {
+                buf.iter().flat_map(|&v| [v, v, v, 255]).collect()
+            }
+        };
+        Self::from_rgba(frame.width, frame.height, rgba)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Pixel data, `width * height * 4` bytes.
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+}
+
+/// Only the size: the pixels would flood the log wherever
+/// [`PluginInfo`](crate::info::PluginInfo) is printed.
+impl fmt::Debug for Thumbnail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Thumbnail")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Embed a PNG file as an `Option<Thumbnail>`, for
+/// [`PluginInfo::thumbnail`](crate::info::PluginInfo::thumbnail). The path
+/// is resolved like [`include_bytes!`]. A file that fails to decode is
+/// logged and yields `None`.
+#[cfg(feature = "png")]
+#[macro_export]
+macro_rules! include_thumbnail {
+    ($path:expr) => {
+        match $crate::thumbnail::Thumbnail::from_png(include_bytes!($path)) {
+            Ok(thumbnail) => Some(thumbnail),
+            Err(e) => {
+                $crate::tracing::error!("Thumbnail {}: {e}", $path);
+                None
+            }
+        }
+    };
+}
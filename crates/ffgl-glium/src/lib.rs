@@ -3,41 +3,142 @@
 //! Use [`FFGLGlium`] in your plugin to render frames with a glium context.
 //!
 //! Just call [`FFGLGlium::draw`] inside your
-//! [`ffgl_core::handler::FFGLInstance::draw`] method.
+//! [`ffgl_core::handler::FFGLInstance::draw`] method, or
+//! [`FFGLGlium::draw_inputs`] to bind the host inputs by slot with the
+//! [`uniforms`] helpers.
+//!
+//! Failures are returned as [`GliumError`] rather than panicking inside the
+//! host. If glium cannot use the host's context at all, [`FFGLGlium::new`]
+//! returns a null renderer whose draws do nothing.
 //!
 //! ### Warning
 //!
 //! This module makes assumptions about the OpenGL context inside the host.
 //! Bugs and crashes may occur. Testing infrastructure is required.
 
-use std::{error::Error, fmt::Formatter, rc::Rc};
+use std::{error::Error, fmt, fmt::Formatter, rc::Rc};
 
 use ffgl_core::*;
 use glium::{
     backend::Context,
-    framebuffer::{RenderBuffer, SimpleFrameBuffer},
-    CapabilitiesSource, Frame, Surface, Texture2d,
+    framebuffer::{RenderBuffer, RenderBufferCreationError, SimpleFrameBuffer, ValidationError},
+    texture::{SrgbTexture2d, TextureCreationError, UncompressedFloatFormat},
+    uniforms::MagnifySamplerFilter,
+    CapabilitiesSource, Frame, IncompatibleOpenGl, Surface, SwapBuffersError, Texture2d,
 };
 use std::fmt::Debug;
 use tracing::trace;
+use uniforms::{HostInput, HostInputs};
 
 mod gl_backend;
 pub mod glsl;
 pub mod texture;
+pub mod uniforms;
 pub mod validate_gl;
 
-/// Cached render buffer to avoid per-frame GL allocations.
+/// Pixel format of the intermediate target the draw closure renders into,
+/// set with [`FFGLGlium::set_render_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderTargetFormat {
+    /// 8 bits per channel, unsigned normalized.
+    #[default]
+    Rgba8,
+    /// 8 bits per channel, sRGB-encoded. Shaders write linear color, which
+    /// is encoded on write; the encoded values are copied to the host.
+    Srgb8,
+    /// 16-bit float per channel, for high-precision rendering.
+    Rgba16Float,
+    /// 32-bit float per channel.
+    Rgba32Float,
+}
+
+impl RenderTargetFormat {
+    /// Filter for scaling the rendered frame to the output size. 32-bit
+    /// float targets are not linearly filterable on every driver.
+    fn blit_filter(self, render_res: (u32, u32), out_res: (u32, u32)) -> MagnifySamplerFilter {
+        if render_res == out_res || self == Self::Rgba32Float {
+            MagnifySamplerFilter::Nearest
+        } else {
+            MagnifySamplerFilter::Linear
+        }
+    }
+}
+
+/// The intermediate render target's storage.
+enum RenderTarget {
+    Buffer(RenderBuffer),
+    /// glium has no sRGB render buffers, so sRGB renders into a texture.
+    Srgb(SrgbTexture2d),
+}
+
+impl RenderTarget {
+    fn new(
+        ctx: &Rc<Context>,
+        format: RenderTargetFormat,
+        (width, height): (u32, u32),
+    ) -> Result<Self, GliumError> {
+        let float_format = match format {
+            RenderTargetFormat::Rgba8 => UncompressedFloatFormat::U8U8U8U8,
+            RenderTargetFormat::Rgba16Float => UncompressedFloatFormat::F16F16F16F16,
+            RenderTargetFormat::Rgba32Float => UncompressedFloatFormat::F32F32F32F32,
+            RenderTargetFormat::Srgb8 => {
+                return texture::new_texture_srgb_2d(ctx, (width, height))
+                    .map(Self::Srgb)
+                    .map_err(GliumError::Texture);
+            }
+        };
+        RenderBuffer::new(ctx, float_format, width, height)
+            .map(Self::Buffer)
+            .map_err(GliumError::RenderBuffer)
+    }
+}
+
+/// Cached render target to avoid per-frame GL allocations.
 struct CachedRenderBuffer {
-    rb: RenderBuffer,
+    target: RenderTarget,
     dims: (u32, u32),
+    format: RenderTargetFormat,
+}
+
+/// Errors from setting up or drawing with [`FFGLGlium`].
+#[derive(Debug, Clone)]
+pub enum GliumError {
+    /// glium cannot use the host's OpenGL context (at creation, or when
+    /// re-syncing with the host's state).
+    Context(IncompatibleOpenGl),
+    /// The intermediate render buffer could not be allocated.
+    RenderBuffer(RenderBufferCreationError),
+    /// The intermediate sRGB render texture could not be allocated.
+    Texture(TextureCreationError),
+    /// The render buffer could not be attached to a framebuffer.
+    Framebuffer(ValidationError),
+    /// Finishing the frame or swapping buffers failed.
+    SwapBuffers(SwapBuffersError),
+}
+
+impl fmt::Display for GliumError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Context(e) => write!(f, "unusable OpenGL context: {e}"),
+            Self::RenderBuffer(e) => write!(f, "render buffer could not be created: {e}"),
+            Self::Texture(e) => write!(f, "render texture could not be created: {e}"),
+            Self::Framebuffer(e) => write!(f, "framebuffer could not be created: {e}"),
+            Self::SwapBuffers(e) => write!(f, "swapping buffers failed: {e}"),
+        }
+    }
 }
 
+impl Error for GliumError {}
+
 /// Use this struct to render frames with a glium context, making assumptions
 /// about the OpenGL context inside an FFGL host.
 pub struct FFGLGlium {
-    pub ctx: Rc<Context>,
+    /// The glium context, or `None` for a null renderer (see
+    /// [`new`](Self::new)).
+    pub ctx: Option<Rc<Context>>,
     backend: Rc<gl_backend::RawGlBackend>,
     cached_rb: Option<CachedRenderBuffer>,
+    render_format: RenderTargetFormat,
 }
 
 impl Debug for FFGLGlium {
@@ -51,7 +152,25 @@ pub type DefaultSurface<'a> = SimpleFrameBuffer<'a>;
 
 impl FFGLGlium {
     /// Create a new glium context from host-provided FFGL instance data.
+    ///
+    /// If glium cannot use the host's context, the error is logged and a null
+    /// renderer is returned: its draws do nothing and succeed. Use
+    /// [`try_new`](Self::try_new) to handle the error instead.
     pub fn new(inst_data: &FFGLData) -> Self {
+        Self::try_new(inst_data).unwrap_or_else(|e| {
+            tracing::error!("glium context creation failed, drawing disabled: {e}");
+            Self {
+                ctx: None,
+                backend: Rc::new(gl_backend::RawGlBackend::new(inst_data.get_dimensions())),
+                cached_rb: None,
+                render_format: RenderTargetFormat::default(),
+            }
+        })
+    }
+
+    /// Create a new glium context from host-provided FFGL instance data,
+    /// failing if glium cannot use the host's context.
+    pub fn try_new(inst_data: &FFGLData) -> Result<Self, GliumError> {
         let backend = Rc::new(gl_backend::RawGlBackend::new(inst_data.get_dimensions()));
 
         tracing::debug!("BACKEND: {backend:?}");
@@ -62,8 +181,8 @@ impl FFGLGlium {
                 false,
                 glium::debug::DebugCallbackBehavior::Ignore,
             )
-            .unwrap()
-        };
+        }
+        .map_err(GliumError::Context)?;
 
         let valid_versions = &ctx.get_capabilities().supported_glsl_versions;
 
@@ -71,11 +190,28 @@ impl FFGLGlium {
 
         tracing::debug!("OPENGL_VERSION {}", ctx.get_opengl_version_string());
 
-        Self {
-            ctx,
+        Ok(Self {
+            ctx: Some(ctx),
             backend,
             cached_rb: None,
-        }
+            render_format: RenderTargetFormat::default(),
+        })
+    }
+
+    /// Set the format of the target the draw closure renders into (RGBA8 by
+    /// default). Takes effect at the next draw.
+    pub fn set_render_format(&mut self, format: RenderTargetFormat) {
+        self.render_format = format;
+    }
+
+    /// Current render target format.
+    pub fn render_format(&self) -> RenderTargetFormat {
+        self.render_format
+    }
+
+    /// Whether this is a null renderer (see [`new`](Self::new)).
+    pub fn is_null(&self) -> bool {
+        self.ctx.is_none()
     }
 
     /// Main draw loop: create renderbuffer, import host textures, call user
@@ -89,72 +225,149 @@ impl FFGLGlium {
             &mut DefaultSurface,
             Vec<Texture2d>,
         ) -> Result<(), Box<dyn Error>>,
-    ) {
-        unsafe {
-            self.ctx.rebuild(self.backend.clone()).unwrap();
+    ) -> Result<(), GliumError> {
+        self.draw_at(render_res, (0, 0), out_res, frame_data, render_frame)
+    }
+
+    /// Like [`draw`](Self::draw), but places the result at `out_origin` in
+    /// the host FBO. Use the host viewport's x/y here.
+    pub fn draw_at(
+        &mut self,
+        render_res: (u32, u32),
+        out_origin: (u32, u32),
+        out_res: (u32, u32),
+        frame_data: GLInput<'_>,
+        render_frame: &mut impl FnMut(
+            &mut DefaultSurface,
+            Vec<Texture2d>,
+        ) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), GliumError> {
+        self.render_at(render_res, out_origin, out_res, frame_data, |fb, inputs| {
+            render_frame(fb, inputs.into_textures())
+        })
+    }
+
+    /// Like [`draw`](Self::draw), but hands the closure the host inputs by
+    /// slot, for binding with the [`uniforms`] helpers.
+    pub fn draw_inputs(
+        &mut self,
+        render_res: (u32, u32),
+        out_res: (u32, u32),
+        frame_data: GLInput<'_>,
+        render_frame: &mut impl FnMut(&mut DefaultSurface, &HostInputs) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), GliumError> {
+        self.draw_inputs_at(render_res, (0, 0), out_res, frame_data, render_frame)
+    }
+
+    /// Like [`draw_inputs`](Self::draw_inputs), but places the result at
+    /// `out_origin` in the host FBO.
+    pub fn draw_inputs_at(
+        &mut self,
+        render_res: (u32, u32),
+        out_origin: (u32, u32),
+        out_res: (u32, u32),
+        frame_data: GLInput<'_>,
+        render_frame: &mut impl FnMut(&mut DefaultSurface, &HostInputs) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), GliumError> {
+        self.render_at(render_res, out_origin, out_res, frame_data, |fb, inputs| {
+            render_frame(fb, &inputs)
+        })
+    }
+
+    fn render_at(
+        &mut self,
+        render_res: (u32, u32),
+        out_origin: (u32, u32),
+        out_res: (u32, u32),
+        frame_data: GLInput<'_>,
+        render_frame: impl FnOnce(&mut DefaultSurface, HostInputs) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), GliumError> {
+        let Some(ctx) = &self.ctx else {
+            return Ok(());
         };
+        unsafe { ctx.rebuild(self.backend.clone()) }.map_err(GliumError::Context)?;
 
-        // Cache the render buffer -- only recreate when dimensions change
-        if self.cached_rb.as_ref().map(|c| c.dims) != Some(render_res) {
-            let rb = RenderBuffer::new(
-                &self.ctx,
-                glium::texture::UncompressedFloatFormat::U8U8U8U8,
-                render_res.0,
-                render_res.1,
-            )
-            .expect("RenderBuffer could not be created");
-            self.cached_rb = Some(CachedRenderBuffer {
-                rb,
-                dims: render_res,
-            });
-        }
+        // Cache the render target -- only recreate when dimensions or format change
+        let format = self.render_format;
+        let target = match &mut self.cached_rb {
+            Some(cached) if cached.dims == render_res && cached.format == format => &cached.target,
+            cached => {
+                let target = RenderTarget::new(ctx, format, render_res)?;
+                &cached
+                    .insert(CachedRenderBuffer {
+                        target,
+                        dims: render_res,
+                        format,
+                    })
+                    .target
+            }
+        };
 
-        let rb = &self.cached_rb.as_ref().unwrap().rb;
-        let mut fb = SimpleFrameBuffer::new(&self.ctx, rb)
-            .expect("SimpleFrameBuffer could not be created");
+        let mut fb = match target {
+            RenderTarget::Buffer(rb) => SimpleFrameBuffer::new(ctx, rb),
+            RenderTarget::Srgb(texture) => SimpleFrameBuffer::new(ctx, texture),
+        }
+        .map_err(GliumError::Framebuffer)?;
 
-        let textures: Vec<_> = frame_data
+        let slots = frame_data
             .textures
             .iter()
-            .map(|texture_info| unsafe {
-                Texture2d::from_id(
-                    &self.ctx,
-                    glium::texture::UncompressedFloatFormat::U8U8U8U8,
-                    texture_info.Handle,
-                    false,
-                    glium::texture::MipmapsOption::NoMipmap,
-                    glium::texture::Dimensions::Texture2d {
-                        width: texture_info.Width,
-                        height: texture_info.Height,
-                    },
-                )
+            .map(|slot| {
+                slot.map(|texture_info| {
+                    let texture = unsafe {
+                        Texture2d::from_id(
+                            ctx,
+                            glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                            texture_info.Handle,
+                            false,
+                            glium::texture::MipmapsOption::NoMipmap,
+                            // Describe the real allocation; the image covers
+                            // `FFGLTextureStruct::uv_scale` of it when padded.
+                            glium::texture::Dimensions::Texture2d {
+                                width: texture_info.HardwareWidth.max(texture_info.Width),
+                                height: texture_info.HardwareHeight.max(texture_info.Height),
+                            },
+                        )
+                    };
+                    let (u, v) = texture_info.uv_scale();
+                    HostInput {
+                        texture,
+                        uv_scale: [u, v],
+                    }
+                })
             })
             .collect();
 
-        if let Err(err) = render_frame(&mut fb, textures) {
+        if let Err(err) = render_frame(&mut fb, HostInputs::new(slots)) {
             tracing::error!("Render ERROR: {err:?}");
         }
 
         trace!(?out_res, ?render_res, "RENDERED");
 
-        let frame = Frame::new(self.ctx.clone(), out_res);
-        fb.fill(&frame, glium::uniforms::MagnifySamplerFilter::Nearest);
+        let frame = Frame::new(ctx.clone(), out_res);
+        let filter = format.blit_filter(render_res, out_res);
+        fb.fill(&frame, filter);
 
-        unsafe {
+        let rebuilt = unsafe {
             gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, frame_data.host);
-            blit_fb(render_res, out_res);
-            self.ctx.rebuild(self.backend.clone()).unwrap();
+            blit_fb(render_res, out_origin, out_res, filter);
+            ctx.rebuild(self.backend.clone())
         };
 
-        frame.finish().unwrap();
+        // glium panics if a frame is dropped unfinished, so finish it first.
+        frame.finish().map_err(GliumError::SwapBuffers)?;
+        rebuilt.map_err(GliumError::Context)
     }
 
     /// Swap buffers and rebind the host FBO as the draw framebuffer.
-    pub fn set_default_db_to_ffgl_fb(&self, frame_data: &GLInput<'_>) {
-        self.ctx.swap_buffers().expect("swap_buffers failed");
+    pub fn set_default_db_to_ffgl_fb(&self, frame_data: &GLInput<'_>) -> Result<(), GliumError> {
+        if let Some(ctx) = &self.ctx {
+            ctx.swap_buffers().map_err(GliumError::SwapBuffers)?;
+        }
         unsafe {
             gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, frame_data.host);
         }
+        Ok(())
     }
 }
 
@@ -163,17 +376,26 @@ impl FFGLGlium {
 /// # Safety
 ///
 /// Caller must ensure valid GL context and correctly bound framebuffers.
-unsafe fn blit_fb((read_w, read_h): (u32, u32), (write_w, write_h): (u32, u32)) {
+unsafe fn blit_fb(
+    (read_w, read_h): (u32, u32),
+    (write_x, write_y): (u32, u32),
+    (write_w, write_h): (u32, u32),
+    filter: MagnifySamplerFilter,
+) {
+    let filter = match filter {
+        MagnifySamplerFilter::Nearest => gl::NEAREST,
+        MagnifySamplerFilter::Linear => gl::LINEAR,
+    };
     gl::BlitFramebuffer(
         0,
         0,
         read_w as gl::types::GLint,
         read_h as gl::types::GLint,
-        0,
-        0,
-        write_w as gl::types::GLint,
-        write_h as gl::types::GLint,
+        write_x as gl::types::GLint,
+        write_y as gl::types::GLint,
+        (write_x + write_w) as gl::types::GLint,
+        (write_y + write_h) as gl::types::GLint,
         gl::COLOR_BUFFER_BIT,
-        gl::NEAREST,
+        filter,
     );
 }
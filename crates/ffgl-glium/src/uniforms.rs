@@ -0,0 +1,261 @@
+//! Helpers for binding host inputs and common uniforms in glium shaders.
+//!
+//! [`FFGLGlium::draw_inputs`](crate::FFGLGlium::draw_inputs) hands the render
+//! closure a [`HostInputs`] indexed by input slot. [`ShaderUniforms`] collects
+//! the standard frame uniforms, sampled inputs and plugin values into one
+//! [`Uniforms`] for `Surface::draw`:
+//!
+//! ```ignore
+//! const SOURCE: InputSlot = InputSlot::new("u_source", 0);
+//! const MASK: InputSlot = InputSlot::new("u_mask", 1);
+//!
+//! glium.draw_inputs(res, res, frame_data, &mut |target, inputs| {
+//!     let uniforms = ShaderUniforms::new(FrameUniforms::new(data))
+//!         .input(inputs, SOURCE, SamplerSettings::LINEAR)
+//!         .input(inputs, MASK, SamplerSettings::NEAREST)
+//!         .value("u_amount", &amount);
+//!     target.draw(&quad, &indices, &program, &uniforms, &Default::default())?;
+//!     Ok(())
+//! })?;
+//! ```
+
+use std::borrow::Cow;
+
+use ffgl_core::FFGLData;
+use glium::uniforms::{
+    AsUniformValue, MagnifySamplerFilter, MinifySamplerFilter, SamplerBehavior,
+    SamplerWrapFunction, UniformValue, Uniforms,
+};
+use glium::Texture2d;
+
+/// A host input slot and the sampler uniform it is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputSlot {
+    /// Sampler uniform name in the shader. The input's UV scale is bound
+    /// alongside it as `<name>_uv_scale`.
+    pub name: &'static str,
+    /// FFGL input index (0 for the first input).
+    pub index: usize,
+}
+
+impl InputSlot {
+    pub const fn new(name: &'static str, index: usize) -> Self {
+        Self { name, index }
+    }
+}
+
+/// How an input texture is filtered and wrapped when sampled.
+///
+/// Host textures may be padded (see [`HostInputs::uv_scale`]), so clamping
+/// stops at the allocation's edge, not the image's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplerSettings {
+    /// Bilinear filtering when `true`, nearest texel otherwise.
+    pub linear: bool,
+    pub wrap: SamplerWrapFunction,
+}
+
+impl SamplerSettings {
+    /// Bilinear filtering, clamped to the edge.
+    pub const LINEAR: Self = Self {
+        linear: true,
+        wrap: SamplerWrapFunction::Clamp,
+    };
+
+    /// Nearest texel, clamped to the edge. For pixel-exact lookups.
+    pub const NEAREST: Self = Self {
+        linear: false,
+        wrap: SamplerWrapFunction::Clamp,
+    };
+
+    /// The equivalent glium sampler state (no mipmaps, no anisotropy).
+    pub fn behavior(self) -> SamplerBehavior {
+        let (minify_filter, magnify_filter) = if self.linear {
+            (MinifySamplerFilter::Linear, MagnifySamplerFilter::Linear)
+        } else {
+            (MinifySamplerFilter::Nearest, MagnifySamplerFilter::Nearest)
+        };
+        SamplerBehavior {
+            wrap_function: (self.wrap, self.wrap, self.wrap),
+            minify_filter,
+            magnify_filter,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        Self::LINEAR
+    }
+}
+
+/// A connected host input.
+pub(crate) struct HostInput {
+    pub(crate) texture: Texture2d,
+    pub(crate) uv_scale: [f32; 2],
+}
+
+/// The host's input textures for one frame, by slot.
+pub struct HostInputs {
+    slots: Vec<Option<HostInput>>,
+}
+
+impl HostInputs {
+    pub(crate) fn new(slots: Vec<Option<HostInput>>) -> Self {
+        Self { slots }
+    }
+
+    /// Connected textures in slot order, disconnected slots skipped.
+    pub(crate) fn into_textures(self) -> Vec<Texture2d> {
+        self.slots
+            .into_iter()
+            .flatten()
+            .map(|i| i.texture)
+            .collect()
+    }
+
+    /// Number of input slots the host passed, connected or not.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// The texture connected to `slot`, if any.
+    pub fn texture(&self, slot: InputSlot) -> Option<&Texture2d> {
+        Some(&self.input(slot)?.texture)
+    }
+
+    /// Part of `slot`'s texture covered by the image (see
+    /// [`FFGLTextureStruct::uv_scale`](ffgl_core::ffi::FFGLTextureStruct::uv_scale)).
+    /// `[1.0, 1.0]` when disconnected.
+    pub fn uv_scale(&self, slot: InputSlot) -> [f32; 2] {
+        self.input(slot).map_or([1.0, 1.0], |i| i.uv_scale)
+    }
+
+    fn input(&self, slot: InputSlot) -> Option<&HostInput> {
+        self.slots.get(slot.index)?.as_ref()
+    }
+}
+
+/// Per-frame values most shaders need, bound by [`ShaderUniforms`] as
+/// `u_time`, `u_resolution`, `u_bpm`, `u_bar_phase` and `u_seed`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameUniforms {
+    /// Seconds since the plugin instance was created.
+    pub time: f32,
+    /// Viewport size in pixels.
+    pub resolution: [f32; 2],
+    /// Host tempo in beats per minute.
+    pub bpm: f32,
+    /// Position within the current bar, 0-1.
+    pub bar_phase: f32,
+    /// Random seed for this frame of this instance (see
+    /// [`FFGLData::frame_seed`]), for the helpers in [`RANDOM_GLSL`].
+    pub seed: u32,
+}
+
+impl FrameUniforms {
+    pub fn new(data: &FFGLData) -> Self {
+        let (width, height) = data.get_dimensions();
+        Self {
+            time: data.created_at.elapsed().as_secs_f32(),
+            resolution: [width as f32, height as f32],
+            bpm: data.host_beat.bpm,
+            bar_phase: data.host_beat.barPhase,
+            seed: data.frame_seed(),
+        }
+    }
+}
+
+/// Hash-based random numbers for GLSL 1.30 and later, seeded with
+/// `u_seed`. Paste it into the shader source after the `#version` line:
+///
+/// ```glsl
+/// uniform uint u_seed;
+/// // ...
+/// float noise = ffgl_random(uvec2(gl_FragCoord.xy), u_seed);
+/// ```
+///
+/// The functions match `ffgl_gpu::random`: `ffgl_hash` is the PCG hash from
+/// Jarzynski and Olano, `ffgl_random` returns a value in `[0, 1)` and
+/// `ffgl_random4` four of them.
+pub const RANDOM_GLSL: &str = r#"
+uint ffgl_hash(uint v) {
+    uint state = v * 747796405u + 2891336453u;
+    uint word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+float ffgl_random(uvec2 pixel, uint seed) {
+    return float(ffgl_hash(pixel.x ^ ffgl_hash(pixel.y ^ ffgl_hash(seed))) >> 8u) * (1.0 / 16777216.0);
+}
+
+vec4 ffgl_random4(uvec2 pixel, uint seed) {
+    uint h = ffgl_hash(pixel.x ^ ffgl_hash(pixel.y ^ ffgl_hash(seed)));
+    uint a = ffgl_hash(h);
+    uint b = ffgl_hash(a);
+    uint c = ffgl_hash(b);
+    return vec4(uvec4(h, a, b, c) >> 8u) * (1.0 / 16777216.0);
+}
+"#;
+
+/// A set of named uniform values, usable wherever glium takes
+/// [`Uniforms`].
+#[derive(Default)]
+pub struct ShaderUniforms<'a> {
+    values: Vec<(Cow<'static, str>, UniformValue<'a>)>,
+}
+
+impl<'a> ShaderUniforms<'a> {
+    /// Start from the standard frame uniforms.
+    pub fn new(frame: FrameUniforms) -> Self {
+        Self {
+            values: vec![
+                ("u_time".into(), UniformValue::Float(frame.time)),
+                ("u_resolution".into(), UniformValue::Vec2(frame.resolution)),
+                ("u_bpm".into(), UniformValue::Float(frame.bpm)),
+                ("u_bar_phase".into(), UniformValue::Float(frame.bar_phase)),
+                ("u_seed".into(), UniformValue::UnsignedInt(frame.seed)),
+            ],
+        }
+    }
+
+    /// Bind `slot`'s texture under `slot.name` with `settings`, and its UV
+    /// scale as `<name>_uv_scale`. Nothing is bound for a disconnected
+    /// input.
+    pub fn input(
+        mut self,
+        inputs: &'a HostInputs,
+        slot: InputSlot,
+        settings: SamplerSettings,
+    ) -> Self {
+        if let Some(input) = inputs.input(slot) {
+            let sampler = UniformValue::Texture2d(&input.texture, Some(settings.behavior()));
+            self.values.push((slot.name.into(), sampler));
+            self.values.push((
+                format!("{}_uv_scale", slot.name).into(),
+                UniformValue::Vec2(input.uv_scale),
+            ));
+        }
+        self
+    }
+
+    /// Bind any other value, e.g. a parameter or one of the plugin's own
+    /// textures.
+    pub fn value(mut self, name: &'static str, value: &'a dyn AsUniformValue) -> Self {
+        self.values.push((name.into(), value.as_uniform_value()));
+        self
+    }
+}
+
+impl Uniforms for ShaderUniforms<'_> {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        for (name, value) in &self.values {
+            output(name, *value);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Adaptive internal resolution.
+//!
+//! [`AdaptiveResolution`] measures how long each [`draw_gpu_effect`] call
+//! takes and scales the processing resolution to hold a target frame time.
+//! Because the draw loop waits on the previous frame's GPU work, the measured
+//! time covers both CPU submission and GPU execution.
+//!
+//! [`draw_gpu_effect`]: crate::draw_gpu_effect
+
+use std::time::Duration;
+
+/// Lowest scale either bound can be set to.
+const MIN_SCALE: f32 = 0.125;
+
+/// Frame-time driven controller for the processing resolution scale.
+///
+/// Return it from [`GpuPlugin::adaptive_resolution`](crate::GpuPlugin::adaptive_resolution)
+/// and the framework will feed it every frame. The host's `internal_resolution`
+/// value still acts as an upper bound.
+///
+/// The controller keeps an exponential moving average of the frame time and
+/// only steps the scale when the average leaves the `target ± hysteresis`
+/// band, and at most once every `settle_frames` frames. Each step reallocates
+/// the bridge textures, so the band should be wide enough to avoid
+/// oscillating between two sizes.
+#[derive(Debug, Clone)]
+pub struct AdaptiveResolution {
+    /// Target frame time.
+    pub target: Duration,
+    /// Lowest scale the controller will drop to, in `[0.125, 1.0]`.
+    ///
+    /// Values outside that range are clamped into it, NaN counts as 0.125,
+    /// and a `min_scale` above `max_scale` swaps the two.
+    pub min_scale: f32,
+    /// Highest scale the controller will climb to, in `[0.125, 1.0]`. NaN
+    /// counts as 1.0; see [`min_scale`](Self::min_scale).
+    pub max_scale: f32,
+    /// Fraction of `target` the average may drift by before the scale changes.
+    pub hysteresis: f32,
+    /// Scale change per step.
+    pub step: f32,
+    /// Minimum number of frames between two scale changes.
+    pub settle_frames: u32,
+
+    scale: f32,
+    average: Option<f32>,
+    frames_since_change: u32,
+}
+
+impl AdaptiveResolution {
+    /// Create a controller that aims for `target_fps` frames per second,
+    /// scaling between 0.25 and 1.0.
+    pub fn new(target_fps: f32) -> Self {
+        Self {
+            target: Duration::from_secs_f32(1.0 / target_fps.max(1.0)),
+            min_scale: 0.25,
+            max_scale: 1.0,
+            hysteresis: 0.15,
+            step: 0.125,
+            settle_frames: 30,
+            scale: 1.0,
+            average: None,
+            frames_since_change: 0,
+        }
+    }
+
+    /// Current resolution scale.
+    pub fn scale(&self) -> f32 {
+        let (min, max) = self.bounds();
+        self.scale.clamp(min, max)
+    }
+
+    /// `min_scale` and `max_scale`, made valid for `f32::clamp`.
+    fn bounds(&self) -> (f32, f32) {
+        let valid = |scale: f32, nan: f32| {
+            if scale.is_nan() {
+                nan
+            } else {
+                scale.clamp(MIN_SCALE, 1.0)
+            }
+        };
+        let (min, max) = (valid(self.min_scale, MIN_SCALE), valid(self.max_scale, 1.0));
+        (min.min(max), min.max(max))
+    }
+
+    /// Smoothed frame time, if any frames have been recorded.
+    pub fn average_frame_time(&self) -> Option<Duration> {
+        self.average.map(Duration::from_secs_f32)
+    }
+
+    /// Record the duration of one frame and update the scale.
+    pub fn record_frame(&mut self, frame_time: Duration) {
+        const SMOOTHING: f32 = 0.1;
+
+        let t = frame_time.as_secs_f32();
+        let avg = match self.average {
+            Some(avg) => avg + (t - avg) * SMOOTHING,
+            None => t,
+        };
+        self.average = Some(avg);
+        self.frames_since_change = self.frames_since_change.saturating_add(1);
+
+        if self.frames_since_change < self.settle_frames {
+            return;
+        }
+
+        let target = self.target.as_secs_f32();
+        let current = self.scale();
+        let next = if avg > target * (1.0 + self.hysteresis) {
+            current - self.step
+        } else if avg < target * (1.0 - self.hysteresis) {
+            current + self.step
+        } else {
+            current
+        };
+        let (min, max) = self.bounds();
+        let next = next.clamp(min, max);
+
+        if next != current {
+            self.scale = next;
+            self.frames_since_change = 0;
+            // Restart averaging so the next decision reflects the new size.
+            self.average = None;
+        }
+    }
+
+    /// Forget measurements and return to the maximum scale.
+    pub fn reset(&mut self) {
+        self.scale = self.bounds().1;
+        self.average = None;
+        self.frames_since_change = 0;
+    }
+}
@@ -0,0 +1,73 @@
+//! The thread each instance draws on.
+//!
+//! [`draw_gpu_effect`] keeps its GPU context and bridge in thread-locals,
+//! alongside the GL context the host makes current on that thread. Hosts
+//! that move an instance to another thread would otherwise leave it drawing
+//! with pipelines from the old thread's context, or, if it comes back,
+//! with a bridge and `gpu_init` state from before it left. Each move is
+//! logged and handled according to the plugin's [`ThreadMigration`].
+//!
+//! [`draw_gpu_effect`]: crate::draw_gpu_effect
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread::ThreadId;
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::ThreadMigration;
+
+static OWNERS: Mutex<BTreeMap<u64, ThreadId>> = Mutex::new(BTreeMap::new());
+
+fn owners() -> MutexGuard<'static, BTreeMap<u64, ThreadId>> {
+    OWNERS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Where a frame is being drawn relative to the instance's previous one.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Affinity {
+    /// The same thread, or the instance's first frame.
+    Same,
+    /// A new thread, which now owns the instance.
+    Moved,
+    /// Another thread than the one the instance is pinned to.
+    Foreign,
+}
+
+/// Check the calling thread against `instance_id`'s owner, taking it over
+/// unless the plugin pinned the instance.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn enter(instance_id: u64, policy: ThreadMigration) -> Affinity {
+    let current = std::thread::current().id();
+    let mut owners = owners();
+    let owner = *owners.entry(instance_id).or_insert(current);
+    if owner == current {
+        return Affinity::Same;
+    }
+    match policy {
+        ThreadMigration::Rebuild => {
+            owners.insert(instance_id, current);
+            tracing::info!(
+                instance = instance_id,
+                from = ?owner,
+                to = ?current,
+                "Instance moved to another thread, rebuilding its GPU resources there"
+            );
+            Affinity::Moved
+        }
+        ThreadMigration::Pin => {
+            gpu_interop::warn_limited!(
+                instance = instance_id,
+                pinned = ?owner,
+                called = ?current,
+                "Instance drawn outside its pinned thread, passing frames through"
+            );
+            Affinity::Foreign
+        }
+    }
+}
+
+/// Forget `instance_id`. Called by [`budget::unregister`](crate::budget::unregister).
+pub(crate) fn unregister(instance_id: u64) {
+    owners().remove(&instance_id);
+}
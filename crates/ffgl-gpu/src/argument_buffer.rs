@@ -0,0 +1,127 @@
+//! Metal argument buffers for large binding sets.
+//!
+//! Binding textures and buffers one at a time costs an encoder call per
+//! resource on every dispatch, and a kernel can only take 31 buffers and 128
+//! textures directly. An [`ArgumentBuffer`] encodes the resources once into a
+//! single buffer laid out like the kernel's argument struct, and
+//! [`GpuContext::dispatch_compute_with_arguments`] binds that one buffer.
+//!
+//! ```metal
+//! struct Layers {
+//!     array<texture2d<float>, 16> textures [[id(0)]];
+//!     device const float* weights [[id(16)]];
+//! };
+//!
+//! kernel void composite(constant Layers& layers [[buffer(0)]],
+//!                       texture2d<float, access::write> output [[texture(0)]],
+//!                       uint2 gid [[thread_position_in_grid]]) { ... }
+//! ```
+//!
+//! ```rust,ignore
+//! let mut layers = ctx.create_argument_buffer(&pipeline, 0)?;
+//! for (i, texture) in self.layers.iter().enumerate() {
+//!     layers.set_texture(texture.metal_texture(), i);
+//! }
+//! layers.set_buffer(&self.weights, 16);
+//! ctx.dispatch_compute_with_arguments(
+//!     &pipeline, &layers, &[input.output], &[], &[], grid, (16, 16),
+//! )?;
+//! ```
+//!
+//! Only resources that change need to be set again. The buffer lives in
+//! shared memory and is read by the GPU when the dispatch runs, so don't
+//! change it while a dispatch using it is still in flight. macOS only.
+
+#[cfg(target_os = "macos")]
+pub use metal_impl::ArgumentBuffer;
+
+#[cfg(target_os = "macos")]
+mod metal_impl {
+    use std::collections::BTreeMap;
+
+    use anyhow::Result;
+    use objc2::rc::Retained;
+    use objc2::runtime::ProtocolObject;
+    use objc2_metal::*;
+
+    use crate::buffer::GpuBuffer;
+    use crate::context::GpuContext;
+    use crate::pipeline::ComputePipeline;
+
+    /// Resources encoded into an argument buffer for one kernel argument,
+    /// created with [`GpuContext::create_argument_buffer`].
+    pub struct ArgumentBuffer {
+        encoder: Retained<ProtocolObject<dyn MTLArgumentEncoder>>,
+        buffer: Retained<ProtocolObject<dyn MTLBuffer>>,
+        /// Kernel buffer index the argument buffer is bound at.
+        index: usize,
+        /// Resources referenced through the buffer, by argument id. Each is
+        /// declared to the encoder with `useResource` so Metal makes it
+        /// resident and tracks hazards on it.
+        resources: BTreeMap<usize, Retained<ProtocolObject<dyn MTLResource>>>,
+    }
+
+    impl ArgumentBuffer {
+        /// Encode `texture` at argument id `id`.
+        pub fn set_texture(&mut self, texture: &ProtocolObject<dyn MTLTexture>, id: usize) {
+            unsafe { self.encoder.setTexture_atIndex(Some(texture), id) };
+            let resource: &ProtocolObject<dyn MTLResource> = ProtocolObject::from_ref(texture);
+            self.resources.insert(id, resource.retain());
+        }
+
+        /// Encode `buffer` at argument id `id`.
+        pub fn set_buffer(&mut self, buffer: &GpuBuffer, id: usize) {
+            unsafe {
+                self.encoder
+                    .setBuffer_offset_atIndex(Some(&buffer.metal), 0, id)
+            };
+            let resource: &ProtocolObject<dyn MTLResource> =
+                ProtocolObject::from_ref(&*buffer.metal);
+            self.resources.insert(id, resource.retain());
+        }
+
+        /// Kernel buffer index this argument buffer binds to.
+        pub fn index(&self) -> usize {
+            self.index
+        }
+
+        /// Bind the argument buffer on `encoder` and declare every referenced
+        /// resource.
+        pub(crate) fn bind(&self, encoder: &ProtocolObject<dyn MTLComputeCommandEncoder>) {
+            unsafe { encoder.setBuffer_offset_atIndex(Some(&self.buffer), 0, self.index) };
+            for resource in self.resources.values() {
+                encoder
+                    .useResource_usage(resource, MTLResourceUsage::Read | MTLResourceUsage::Write);
+            }
+        }
+    }
+
+    impl GpuContext {
+        /// Create an argument buffer for the kernel argument at buffer index
+        /// `index` of `pipeline`, which must be declared as a struct of
+        /// resources with `[[id(n)]]` attributes.
+        pub fn create_argument_buffer(
+            &self,
+            pipeline: &ComputePipeline,
+            index: usize,
+        ) -> Result<ArgumentBuffer> {
+            let encoder = unsafe { pipeline.function.newArgumentEncoderWithBufferIndex(index) };
+            let length = encoder.encodedLength().max(1);
+            let buffer = self
+                .device
+                .device()
+                .newBufferWithLength_options(length, MTLResourceOptions::StorageModeShared)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Failed to allocate argument buffer of {length} bytes")
+                })?;
+            unsafe { encoder.setArgumentBuffer_offset(Some(&buffer), 0) };
+
+            Ok(ArgumentBuffer {
+                encoder,
+                buffer,
+                index,
+                resources: BTreeMap::new(),
+            })
+        }
+    }
+}
@@ -0,0 +1,1011 @@
+//! Texture copy and blit helpers.
+//!
+//! [`GpuContext::copy_texture`] performs a 1:1 copy of the overlapping
+//! region of two textures with the same pixel format, via the platform's copy
+//! engine. [`GpuContext::blit_texture`] stretches the whole source over the
+//! whole destination with a fullscreen draw and the requested
+//! [`BlitFilter`], converting formats along the way.
+//!
+//! These cover ping-pong and cache-previous-frame patterns without every
+//! plugin having to ship its own copy shader per backend.
+//!
+//! [`GpuContext::copy_buffer_to_texture`] and
+//! [`GpuContext::copy_texture_to_buffer`] move pixel data between a
+//! [`GpuBuffer`](crate::GpuBuffer) and a texture with an explicit row pitch,
+//! for uploading CPU-generated images and capturing frames.
+//! [`GpuContext::read_texture`] reads a texture's pixels back to the CPU in
+//! one blocking call, for diagnostics.
+//! [`GpuContext::create_texture_from_rgba`] wraps the common case of
+//! uploading a static RGBA8 image (mask, logo, gradient) into a
+//! [`GpuTexture`](crate::GpuTexture).
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use anyhow::Result;
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::buffer::GpuBuffer;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::context::GpuContext;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::intermediate::IntermediateFormat;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::texture::GpuTexture;
+
+/// Sampling filter used by [`GpuContext::blit_texture`] when the source and
+/// destination sizes differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlitFilter {
+    /// Nearest-neighbour sampling (hard pixel edges).
+    Nearest,
+    /// Bilinear sampling.
+    #[default]
+    Linear,
+}
+
+/// Check that a `width` x `height` image with `bpp` bytes per pixel laid
+/// out with `bytes_per_row` fits in a buffer of `buffer_size` bytes.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn check_row_layout(
+    op: &str,
+    width: usize,
+    height: usize,
+    bpp: usize,
+    bytes_per_row: usize,
+    buffer_size: usize,
+) -> Result<()> {
+    if bytes_per_row < width * bpp || bytes_per_row % bpp != 0 {
+        return Err(anyhow::anyhow!(
+            "{op}: bytes_per_row {bytes_per_row} must be a multiple of {bpp} and at least \
+             {} for a {width}px wide texture",
+            width * bpp,
+        ));
+    }
+    if buffer_size < bytes_per_row * height {
+        return Err(anyhow::anyhow!(
+            "{op}: buffer of {buffer_size} bytes is smaller than {height} rows of \
+             {bytes_per_row} bytes",
+        ));
+    }
+    Ok(())
+}
+
+/// Validate a tightly packed RGBA8 image for `texture` and swizzle it to
+/// BGRA8, the framework's 8-bit texture format on every backend.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn rgba_to_bgra(texture: &GpuTexture, rgba: &[u8]) -> Result<Vec<u8>> {
+    if texture.format != IntermediateFormat::Rgba8 {
+        return Err(anyhow::anyhow!(
+            "RGBA upload: texture is {:?}, not Rgba8",
+            texture.format,
+        ));
+    }
+    let (width, height) = (texture.width, texture.height);
+    let expected = width as usize * height as usize * 4;
+    if rgba.len() != expected {
+        return Err(anyhow::anyhow!(
+            "RGBA upload: expected {expected} bytes for {width}x{height}, got {}",
+            rgba.len(),
+        ));
+    }
+    let mut bgra = rgba.to_vec();
+    for px in bgra.chunks_exact_mut(4) {
+        px.swap(0, 2);
+    }
+    Ok(bgra)
+}
+
+// ---------------------------------------------------------------------------
+// macOS Metal implementation
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "macos")]
+mod metal_impl {
+    use super::*;
+    use crate::dispatch::{CommandBuffer, PendingWork};
+    use objc2::rc::Retained;
+    use objc2::runtime::ProtocolObject;
+    use objc2_foundation::NSString;
+    use objc2_metal::*;
+    use std::cell::RefCell;
+
+    /// Fullscreen-triangle blit shaders, compiled on first use.
+    const BLIT_MSL: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct BlitVertexOut {
+    float4 position [[position]];
+    float2 uv;
+};
+
+vertex BlitVertexOut ffgl_gpu_blit_vertex(uint vid [[vertex_id]]) {
+    float2 uv = float2((vid << 1) & 2, vid & 2);
+    BlitVertexOut out;
+    out.position = float4(uv * float2(2.0, -2.0) + float2(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fragment float4 ffgl_gpu_blit_fragment(BlitVertexOut in [[stage_in]],
+                                       texture2d<float> src [[texture(0)]],
+                                       constant uint& use_linear [[buffer(0)]]) {
+    constexpr sampler nearest_sampler(filter::nearest, address::clamp_to_edge);
+    constexpr sampler linear_sampler(filter::linear, address::clamp_to_edge);
+    return use_linear != 0 ? src.sample(linear_sampler, in.uv)
+                           : src.sample(nearest_sampler, in.uv);
+}
+"#;
+
+    /// Lazily-created blit shaders, with one render pipeline per
+    /// destination format, used by `blit_texture`.
+    pub(crate) struct BlitPipeline {
+        vs_func: Retained<ProtocolObject<dyn MTLFunction>>,
+        fs_func: Retained<ProtocolObject<dyn MTLFunction>>,
+        states: RefCell<
+            Vec<(
+                MTLPixelFormat,
+                Retained<ProtocolObject<dyn MTLRenderPipelineState>>,
+            )>,
+        >,
+    }
+
+    impl BlitPipeline {
+        fn new(device: &ProtocolObject<dyn MTLDevice>) -> Result<Self> {
+            let library = device
+                .newLibraryWithSource_options_error(&NSString::from_str(BLIT_MSL), None)
+                .map_err(|e| anyhow::anyhow!("Failed to compile blit shaders: {e}"))?;
+
+            let vs_func = library
+                .newFunctionWithName(&NSString::from_str("ffgl_gpu_blit_vertex"))
+                .ok_or_else(|| anyhow::anyhow!("Blit vertex function not found"))?;
+            let fs_func = library
+                .newFunctionWithName(&NSString::from_str("ffgl_gpu_blit_fragment"))
+                .ok_or_else(|| anyhow::anyhow!("Blit fragment function not found"))?;
+
+            Ok(Self {
+                vs_func,
+                fs_func,
+                states: RefCell::default(),
+            })
+        }
+
+        /// The pipeline state drawing into `format`, created on first use.
+        fn state(
+            &self,
+            device: &ProtocolObject<dyn MTLDevice>,
+            format: MTLPixelFormat,
+        ) -> Result<Retained<ProtocolObject<dyn MTLRenderPipelineState>>> {
+            if let Some((_, state)) = self.states.borrow().iter().find(|(f, _)| *f == format) {
+                return Ok(state.clone());
+            }
+
+            let desc = MTLRenderPipelineDescriptor::new();
+            desc.setVertexFunction(Some(&self.vs_func));
+            desc.setFragmentFunction(Some(&self.fs_func));
+            {
+                let attachment = unsafe { desc.colorAttachments().objectAtIndexedSubscript(0) };
+                attachment.setPixelFormat(format);
+                attachment.setBlendingEnabled(false);
+            }
+
+            let state = device
+                .newRenderPipelineStateWithDescriptor_error(&desc)
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to create blit pipeline for {format:?}: {e}")
+                })?;
+            self.states.borrow_mut().push((format, state.clone()));
+            Ok(state)
+        }
+    }
+
+    /// Encode a blit-engine copy of the overlapping region of `src` and
+    /// `dst` onto `command_buffer`.
+    fn encode_copy_inner(
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        src: &ProtocolObject<dyn MTLTexture>,
+        dst: &ProtocolObject<dyn MTLTexture>,
+    ) -> Result<()> {
+        if src.pixelFormat() != dst.pixelFormat() {
+            return Err(anyhow::anyhow!(
+                "copy_texture: pixel formats differ ({:?} vs {:?}); use blit_texture instead",
+                src.pixelFormat(),
+                dst.pixelFormat(),
+            ));
+        }
+
+        let encoder = command_buffer
+            .blitCommandEncoder()
+            .ok_or_else(|| anyhow::anyhow!("Failed to create Metal blit encoder"))?;
+
+        let origin = MTLOrigin { x: 0, y: 0, z: 0 };
+        let size = MTLSize {
+            width: src.width().min(dst.width()),
+            height: src.height().min(dst.height()),
+            depth: 1,
+        };
+        unsafe {
+            encoder.copyFromTexture_sourceSlice_sourceLevel_sourceOrigin_sourceSize_toTexture_destinationSlice_destinationLevel_destinationOrigin(
+                src, 0, 0, origin, size, dst, 0, 0, origin,
+            );
+        }
+        encoder.endEncoding();
+        Ok(())
+    }
+
+    /// Bytes per pixel for the uncompressed formats the framework uses.
+    fn bytes_per_pixel(format: MTLPixelFormat) -> Result<usize> {
+        match format {
+            MTLPixelFormat::BGRA8Unorm
+            | MTLPixelFormat::BGRA8Unorm_sRGB
+            | MTLPixelFormat::RGBA8Unorm
+            | MTLPixelFormat::RGBA8Unorm_sRGB
+            | MTLPixelFormat::RG11B10Float => Ok(4),
+            MTLPixelFormat::RGBA16Float => Ok(8),
+            MTLPixelFormat::RGBA32Float => Ok(16),
+            other => Err(anyhow::anyhow!("Unsupported pixel format for buffer copy: {other:?}")),
+        }
+    }
+
+    /// Encode a buffer -> texture copy covering the whole texture.
+    fn encode_buffer_to_texture_inner(
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        buffer: &GpuBuffer,
+        bytes_per_row: usize,
+        texture: &ProtocolObject<dyn MTLTexture>,
+    ) -> Result<()> {
+        let (width, height) = (texture.width(), texture.height());
+        let bpp = bytes_per_pixel(texture.pixelFormat())?;
+        check_row_layout("copy_buffer_to_texture", width, height, bpp, bytes_per_row, buffer.size)?;
+
+        let encoder = command_buffer
+            .blitCommandEncoder()
+            .ok_or_else(|| anyhow::anyhow!("Failed to create Metal blit encoder"))?;
+        unsafe {
+            encoder.copyFromBuffer_sourceOffset_sourceBytesPerRow_sourceBytesPerImage_sourceSize_toTexture_destinationSlice_destinationLevel_destinationOrigin(
+                &buffer.metal,
+                0,
+                bytes_per_row,
+                bytes_per_row * height,
+                MTLSize { width, height, depth: 1 },
+                texture,
+                0,
+                0,
+                MTLOrigin { x: 0, y: 0, z: 0 },
+            );
+        }
+        encoder.endEncoding();
+        Ok(())
+    }
+
+    /// Encode a texture -> buffer copy covering the whole texture.
+    fn encode_texture_to_buffer_inner(
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        texture: &ProtocolObject<dyn MTLTexture>,
+        buffer: &GpuBuffer,
+        bytes_per_row: usize,
+    ) -> Result<()> {
+        let (width, height) = (texture.width(), texture.height());
+        let bpp = bytes_per_pixel(texture.pixelFormat())?;
+        check_row_layout("copy_texture_to_buffer", width, height, bpp, bytes_per_row, buffer.size)?;
+
+        let encoder = command_buffer
+            .blitCommandEncoder()
+            .ok_or_else(|| anyhow::anyhow!("Failed to create Metal blit encoder"))?;
+        unsafe {
+            encoder.copyFromTexture_sourceSlice_sourceLevel_sourceOrigin_sourceSize_toBuffer_destinationOffset_destinationBytesPerRow_destinationBytesPerImage(
+                texture,
+                0,
+                0,
+                MTLOrigin { x: 0, y: 0, z: 0 },
+                MTLSize { width, height, depth: 1 },
+                &buffer.metal,
+                0,
+                bytes_per_row,
+                bytes_per_row * height,
+            );
+        }
+        encoder.endEncoding();
+        Ok(())
+    }
+
+    /// Encode a filtered fullscreen blit from `src` to `dst` onto
+    /// `command_buffer`.
+    fn encode_blit_inner(
+        ctx: &GpuContext,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        src: &ProtocolObject<dyn MTLTexture>,
+        dst: &ProtocolObject<dyn MTLTexture>,
+        filter: BlitFilter,
+    ) -> Result<()> {
+        let pipeline = match ctx.blit.get() {
+            Some(p) => p,
+            None => {
+                let p = BlitPipeline::new(ctx.device.device())?;
+                ctx.blit.get_or_init(|| p)
+            }
+        };
+        let state = pipeline.state(ctx.device.device(), dst.pixelFormat())?;
+
+        let render_desc = MTLRenderPassDescriptor::new();
+        {
+            let attachment = unsafe { render_desc.colorAttachments().objectAtIndexedSubscript(0) };
+            attachment.setTexture(Some(dst));
+            attachment.setLoadAction(MTLLoadAction::DontCare);
+            attachment.setStoreAction(MTLStoreAction::Store);
+        }
+
+        let encoder = command_buffer
+            .renderCommandEncoderWithDescriptor(&render_desc)
+            .ok_or_else(|| anyhow::anyhow!("Failed to create blit render encoder"))?;
+
+        let use_linear: u32 = (filter == BlitFilter::Linear) as u32;
+        encoder.setRenderPipelineState(&state);
+        unsafe {
+            encoder.setFragmentTexture_atIndex(Some(src), 0);
+            encoder.setFragmentBytes_length_atIndex(
+                std::ptr::NonNull::from(&use_linear).cast(),
+                std::mem::size_of::<u32>(),
+                0,
+            );
+            encoder.drawPrimitives_vertexStart_vertexCount(MTLPrimitiveType::Triangle, 0, 3);
+        }
+        encoder.endEncoding();
+        Ok(())
+    }
+
+    impl GpuContext {
+        /// Copy the overlapping top-left region of `src` into `dst` using the
+        /// blit engine, and return a [`PendingWork`] token.
+        ///
+        /// Both textures must share a pixel format; use
+        /// [`blit_texture`](Self::blit_texture) to scale or convert. Pass
+        /// [`GpuTexture::metal_texture`](crate::GpuTexture::metal_texture)
+        /// for framework-owned textures.
+        pub fn copy_texture(
+            &self,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+        ) -> Result<PendingWork> {
+            let command_buffer = self
+                .device
+                .command_queue()
+                .commandBuffer()
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal command buffer"))?;
+
+            encode_copy_inner(&command_buffer, src, dst)?;
+
+            Ok(PendingWork::commit(command_buffer))
+        }
+
+        /// Encode a [`copy_texture`](Self::copy_texture) on an existing
+        /// command buffer.
+        pub fn encode_copy_texture(
+            &self,
+            cb: &CommandBuffer,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+        ) -> Result<()> {
+            encode_copy_inner(&cb.inner, src, dst)
+        }
+
+        /// Stretch `src` over the whole of `dst` with a fullscreen draw using
+        /// the given `filter`, and return a [`PendingWork`] token.
+        ///
+        /// `dst` must be renderable (as are
+        /// [`GpuTexture`](crate::GpuTexture)s and the bridge output texture);
+        /// its format may differ from `src`'s. The blit shaders are compiled
+        /// on first use, and a pipeline for each destination format the
+        /// first time it is blitted to.
+        pub fn blit_texture(
+            &self,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            filter: BlitFilter,
+        ) -> Result<PendingWork> {
+            let command_buffer = self
+                .device
+                .command_queue()
+                .commandBuffer()
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal command buffer"))?;
+
+            encode_blit_inner(self, &command_buffer, src, dst, filter)?;
+
+            Ok(PendingWork::commit(command_buffer))
+        }
+
+        /// Encode a [`blit_texture`](Self::blit_texture) on an existing
+        /// command buffer.
+        pub fn encode_blit_texture(
+            &self,
+            cb: &CommandBuffer,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            filter: BlitFilter,
+        ) -> Result<()> {
+            encode_blit_inner(self, &cb.inner, src, dst, filter)
+        }
+
+        /// Copy pixel rows from `buffer` into the whole of `texture` and
+        /// return a [`PendingWork`] token.
+        ///
+        /// Rows start `bytes_per_row` bytes apart in the buffer, which must
+        /// be a multiple of the pixel size and at least one row wide. Create
+        /// the buffer with [`create_shared_buffer`](Self::create_shared_buffer)
+        /// to fill it from the CPU.
+        pub fn copy_buffer_to_texture(
+            &self,
+            buffer: &GpuBuffer,
+            bytes_per_row: usize,
+            texture: &ProtocolObject<dyn MTLTexture>,
+        ) -> Result<PendingWork> {
+            let command_buffer = self
+                .device
+                .command_queue()
+                .commandBuffer()
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal command buffer"))?;
+
+            encode_buffer_to_texture_inner(&command_buffer, buffer, bytes_per_row, texture)?;
+
+            Ok(PendingWork::commit(command_buffer))
+        }
+
+        /// Encode a [`copy_buffer_to_texture`](Self::copy_buffer_to_texture)
+        /// on an existing command buffer.
+        pub fn encode_copy_buffer_to_texture(
+            &self,
+            cb: &CommandBuffer,
+            buffer: &GpuBuffer,
+            bytes_per_row: usize,
+            texture: &ProtocolObject<dyn MTLTexture>,
+        ) -> Result<()> {
+            encode_buffer_to_texture_inner(&cb.inner, buffer, bytes_per_row, texture)
+        }
+
+        /// Copy the whole of `texture` into `buffer` with rows
+        /// `bytes_per_row` bytes apart, and return a [`PendingWork`] token.
+        ///
+        /// Wait on the token before reading a shared buffer's contents from
+        /// the CPU.
+        pub fn copy_texture_to_buffer(
+            &self,
+            texture: &ProtocolObject<dyn MTLTexture>,
+            buffer: &GpuBuffer,
+            bytes_per_row: usize,
+        ) -> Result<PendingWork> {
+            let command_buffer = self
+                .device
+                .command_queue()
+                .commandBuffer()
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal command buffer"))?;
+
+            encode_texture_to_buffer_inner(&command_buffer, texture, buffer, bytes_per_row)?;
+
+            Ok(PendingWork::commit(command_buffer))
+        }
+
+        /// Encode a [`copy_texture_to_buffer`](Self::copy_texture_to_buffer)
+        /// on an existing command buffer.
+        pub fn encode_copy_texture_to_buffer(
+            &self,
+            cb: &CommandBuffer,
+            texture: &ProtocolObject<dyn MTLTexture>,
+            buffer: &GpuBuffer,
+            bytes_per_row: usize,
+        ) -> Result<()> {
+            encode_texture_to_buffer_inner(&cb.inner, texture, buffer, bytes_per_row)
+        }
+
+        /// Read the whole of `texture` back to the CPU, tightly packed in its
+        /// own pixel format.
+        ///
+        /// Blocks until the copy, and so all work committed before it,
+        /// completes. Meant for diagnostics, not per-frame use.
+        pub fn read_texture(&self, texture: &ProtocolObject<dyn MTLTexture>) -> Result<Vec<u8>> {
+            let (width, height) = (texture.width(), texture.height());
+            let bpp = bytes_per_pixel(texture.pixelFormat())?;
+            let buffer = self.create_shared_buffer(width * height, bpp)?;
+            self.copy_texture_to_buffer(texture, &buffer, width * bpp)?.wait();
+
+            let mut bytes = vec![0u8; width * height * bpp];
+            // SAFETY: the buffer is shared, at least `bytes.len()` long, and
+            // the GPU is done writing it.
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    buffer.metal.contents().as_ptr() as *const u8,
+                    bytes.as_mut_ptr(),
+                    bytes.len(),
+                );
+            }
+            Ok(bytes)
+        }
+
+        /// Create a [`GpuTexture`] from a tightly packed RGBA8 image.
+        ///
+        /// The pixels are swizzled to BGRA and uploaded through a temporary
+        /// shared buffer on the command queue, so later dispatches see the
+        /// data without an explicit wait.
+        pub fn create_texture_from_rgba(
+            &self,
+            width: u32,
+            height: u32,
+            rgba: &[u8],
+        ) -> Result<GpuTexture> {
+            let texture = self.create_texture(width, height)?;
+            self.update_texture_from_rgba(&texture, rgba)?;
+            Ok(texture)
+        }
+
+        /// Replace the contents of `texture` with a tightly packed RGBA8
+        /// image of the same dimensions.
+        pub fn update_texture_from_rgba(&self, texture: &GpuTexture, rgba: &[u8]) -> Result<()> {
+            let bgra = rgba_to_bgra(texture, rgba)?;
+
+            // SAFETY: `bgra` is a live, non-empty allocation; Metal copies it.
+            let staging = unsafe {
+                self.device.device().newBufferWithBytes_length_options(
+                    std::ptr::NonNull::new_unchecked(bgra.as_ptr() as *mut _),
+                    bgra.len(),
+                    MTLResourceOptions::StorageModeShared,
+                )
+            }
+            .ok_or_else(|| anyhow::anyhow!("Failed to allocate RGBA staging buffer"))?;
+            let staging = GpuBuffer {
+                size: bgra.len(),
+                metal: staging,
+                _tracked: None,
+            };
+
+            self.copy_buffer_to_texture(&staging, texture.width as usize * 4, &texture.metal)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) use metal_impl::BlitPipeline;
+
+// ---------------------------------------------------------------------------
+// Windows DX11 implementation
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+mod dx11_impl {
+    use super::*;
+    use windows::core::PCSTR;
+    use windows::Win32::Graphics::Direct3D::Fxc::D3DCompile;
+    use windows::Win32::Graphics::Direct3D::{ID3DBlob, ID3DInclude};
+    use windows::Win32::Graphics::Direct3D11::*;
+    use windows::Win32::Graphics::Dxgi::Common::*;
+
+    /// Fullscreen-triangle blit shaders, compiled on first use.
+    const BLIT_HLSL: &str = r#"
+Texture2D<float4> src : register(t0);
+SamplerState samp : register(s0);
+
+struct BlitVertexOut {
+    float4 position : SV_Position;
+    float2 uv : TEXCOORD0;
+};
+
+BlitVertexOut vs_main(uint id : SV_VertexID) {
+    BlitVertexOut o;
+    o.uv = float2((id << 1) & 2, id & 2);
+    o.position = float4(o.uv * float2(2.0, -2.0) + float2(-1.0, 1.0), 0.0, 1.0);
+    return o;
+}
+
+float4 ps_main(BlitVertexOut i) : SV_Target {
+    return src.Sample(samp, i.uv);
+}
+"#;
+
+    /// Lazily-created shaders and samplers used by `blit_texture`.
+    pub(crate) struct BlitPipeline {
+        vs: ID3D11VertexShader,
+        ps: ID3D11PixelShader,
+        point_sampler: ID3D11SamplerState,
+        linear_sampler: ID3D11SamplerState,
+    }
+
+    /// Compile one entry point of an HLSL source with `D3DCompile`.
+    pub(crate) fn compile_hlsl(source: &str, entry: &[u8], target: &[u8]) -> Result<ID3DBlob> {
+        let mut code = None;
+        let mut errors = None;
+        let result = unsafe {
+            D3DCompile(
+                source.as_ptr() as *const _,
+                source.len(),
+                PCSTR::null(),
+                None,
+                None::<&ID3DInclude>,
+                PCSTR(entry.as_ptr()),
+                PCSTR(target.as_ptr()),
+                0,
+                0,
+                &mut code,
+                Some(&mut errors),
+            )
+        };
+        if let Err(e) = result {
+            let log = errors
+                .map(|blob: ID3DBlob| {
+                    String::from_utf8_lossy(unsafe { blob_bytes(&blob) }).into_owned()
+                })
+                .unwrap_or_default();
+            return Err(anyhow::anyhow!("Failed to compile HLSL shader: {e} {log}"));
+        }
+        code.ok_or_else(|| anyhow::anyhow!("D3DCompile returned no bytecode"))
+    }
+
+    /// View the contents of a `ID3DBlob` as bytes.
+    pub(crate) unsafe fn blob_bytes(blob: &ID3DBlob) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(blob.GetBufferPointer() as *const u8, blob.GetBufferSize())
+        }
+    }
+
+    impl BlitPipeline {
+        fn new(device: &ID3D11Device) -> Result<Self> {
+            let vs_blob = compile_hlsl(BLIT_HLSL, b"vs_main\0", b"vs_5_0\0")?;
+            let ps_blob = compile_hlsl(BLIT_HLSL, b"ps_main\0", b"ps_5_0\0")?;
+
+            let mut vs = None;
+            unsafe { device.CreateVertexShader(blob_bytes(&vs_blob), None, Some(&mut vs as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create blit vertex shader: {e}"))?;
+            let vs = vs.ok_or_else(|| anyhow::anyhow!("D3D11 CreateVertexShader returned null"))?;
+
+            let mut ps = None;
+            unsafe { device.CreatePixelShader(blob_bytes(&ps_blob), None, Some(&mut ps as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create blit pixel shader: {e}"))?;
+            let ps = ps.ok_or_else(|| anyhow::anyhow!("D3D11 CreatePixelShader returned null"))?;
+
+            let make_sampler = |filter: D3D11_FILTER| -> Result<ID3D11SamplerState> {
+                let desc = D3D11_SAMPLER_DESC {
+                    Filter: filter,
+                    AddressU: D3D11_TEXTURE_ADDRESS_CLAMP,
+                    AddressV: D3D11_TEXTURE_ADDRESS_CLAMP,
+                    AddressW: D3D11_TEXTURE_ADDRESS_CLAMP,
+                    MaxAnisotropy: 1,
+                    ComparisonFunc: D3D11_COMPARISON_NEVER,
+                    MinLOD: 0.0,
+                    MaxLOD: f32::MAX,
+                    ..Default::default()
+                };
+                let mut sampler = None;
+                unsafe { device.CreateSamplerState(&desc, Some(&mut sampler as *mut _)) }
+                    .map_err(|e| anyhow::anyhow!("Failed to create blit sampler: {e}"))?;
+                sampler.ok_or_else(|| anyhow::anyhow!("D3D11 CreateSamplerState returned null"))
+            };
+
+            Ok(Self {
+                vs,
+                ps,
+                point_sampler: make_sampler(D3D11_FILTER_MIN_MAG_MIP_POINT)?,
+                linear_sampler: make_sampler(D3D11_FILTER_MIN_MAG_MIP_LINEAR)?,
+            })
+        }
+    }
+
+    /// Bytes per pixel for the uncompressed formats the framework uses.
+    pub(crate) fn bytes_per_pixel(format: DXGI_FORMAT) -> Result<usize> {
+        match format {
+            DXGI_FORMAT_B8G8R8A8_UNORM
+            | DXGI_FORMAT_B8G8R8A8_UNORM_SRGB
+            | DXGI_FORMAT_R8G8B8A8_UNORM
+            | DXGI_FORMAT_R8G8B8A8_UNORM_SRGB
+            | DXGI_FORMAT_R11G11B10_FLOAT => Ok(4),
+            DXGI_FORMAT_R16G16B16A16_FLOAT => Ok(8),
+            DXGI_FORMAT_R32G32B32A32_FLOAT => Ok(16),
+            other => Err(anyhow::anyhow!("Unsupported texture format for buffer copy: {other:?}")),
+        }
+    }
+
+    impl GpuContext {
+        /// Copy the overlapping top-left region of `src` into `dst` on the
+        /// [`command_context`](Self::command_context).
+        ///
+        /// Uses `CopyResource` when the sizes match and
+        /// `CopySubresourceRegion` otherwise. Both textures must have
+        /// copy-compatible formats; use [`blit_texture`](Self::blit_texture)
+        /// to scale or convert. Pass
+        /// [`GpuTexture::dx11_texture`](crate::GpuTexture::dx11_texture) for
+        /// framework-owned textures.
+        pub fn copy_texture(&self, src: &ID3D11Texture2D, dst: &ID3D11Texture2D) {
+            let mut src_desc = D3D11_TEXTURE2D_DESC::default();
+            let mut dst_desc = D3D11_TEXTURE2D_DESC::default();
+            unsafe {
+                src.GetDesc(&mut src_desc);
+                dst.GetDesc(&mut dst_desc);
+            }
+
+            let ctx = self.command_context();
+            if (src_desc.Width, src_desc.Height) == (dst_desc.Width, dst_desc.Height) {
+                unsafe { ctx.CopyResource(dst, src) };
+                return;
+            }
+
+            let src_box = D3D11_BOX {
+                left: 0,
+                top: 0,
+                front: 0,
+                right: src_desc.Width.min(dst_desc.Width),
+                bottom: src_desc.Height.min(dst_desc.Height),
+                back: 1,
+            };
+            unsafe { ctx.CopySubresourceRegion(dst, 0, 0, 0, 0, src, 0, Some(&src_box)) };
+        }
+
+        /// Encode a [`copy_texture`](Self::copy_texture) on `cb`, for code
+        /// shared with the Metal backend.
+        pub fn encode_copy_texture(
+            &self,
+            cb: &crate::CommandBuffer,
+            src: &ID3D11Texture2D,
+            dst: &ID3D11Texture2D,
+        ) -> Result<()> {
+            let _ = cb;
+            self.copy_texture(src, dst);
+            Ok(())
+        }
+
+        /// Stretch `src` over the whole of `dst` with a fullscreen draw using
+        /// the given `filter`.
+        ///
+        /// `src` must be bindable as a shader resource and `dst` as a render
+        /// target. Formats may differ. The blit shaders are compiled on first
+        /// use. All touched pipeline state is unbound afterward.
+        pub fn blit_texture(
+            &self,
+            src: &ID3D11Texture2D,
+            dst: &ID3D11Texture2D,
+            filter: BlitFilter,
+        ) -> Result<()> {
+            let device = self.device.device();
+            let ctx = self.command_context();
+
+            let pipeline = match self.blit.get() {
+                Some(p) => p,
+                None => {
+                    let p = BlitPipeline::new(device)?;
+                    self.blit.get_or_init(|| p)
+                }
+            };
+
+            let mut desc = D3D11_TEXTURE2D_DESC::default();
+            unsafe { dst.GetDesc(&mut desc) };
+
+            let mut srv = None;
+            unsafe { device.CreateShaderResourceView(src, None, Some(&mut srv as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create SRV for blit source: {e}"))?;
+            let mut rtv = None;
+            unsafe { device.CreateRenderTargetView(dst, None, Some(&mut rtv as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create RTV for blit target: {e}"))?;
+
+            let sampler = match filter {
+                BlitFilter::Nearest => &pipeline.point_sampler,
+                BlitFilter::Linear => &pipeline.linear_sampler,
+            };
+
+            unsafe {
+                let viewport = D3D11_VIEWPORT {
+                    TopLeftX: 0.0,
+                    TopLeftY: 0.0,
+                    Width: desc.Width as f32,
+                    Height: desc.Height as f32,
+                    MinDepth: 0.0,
+                    MaxDepth: 1.0,
+                };
+                ctx.RSSetViewports(Some(&[viewport]));
+
+                ctx.IASetInputLayout(None);
+                ctx.IASetPrimitiveTopology(
+                    windows::Win32::Graphics::Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+                );
+                ctx.VSSetShader(&pipeline.vs, None);
+                ctx.PSSetShader(&pipeline.ps, None);
+                ctx.PSSetShaderResources(0, Some(&[srv]));
+                ctx.PSSetSamplers(0, Some(&[Some(sampler.clone())]));
+                ctx.OMSetRenderTargets(Some(&[rtv]), None);
+
+                ctx.Draw(3, 0);
+
+                let null_rtvs: [Option<ID3D11RenderTargetView>; 1] = Default::default();
+                ctx.OMSetRenderTargets(Some(&null_rtvs), None);
+                let null_srvs: [Option<ID3D11ShaderResourceView>; 1] = Default::default();
+                ctx.PSSetShaderResources(0, Some(&null_srvs));
+            }
+
+            Ok(())
+        }
+
+        /// Encode a [`blit_texture`](Self::blit_texture) on `cb`, for code
+        /// shared with the Metal backend.
+        pub fn encode_blit_texture(
+            &self,
+            cb: &crate::CommandBuffer,
+            src: &ID3D11Texture2D,
+            dst: &ID3D11Texture2D,
+            filter: BlitFilter,
+        ) -> Result<()> {
+            let _ = cb;
+            self.blit_texture(src, dst, filter)
+        }
+
+        /// Copy pixel rows from `buffer` into the whole of `texture`.
+        ///
+        /// Rows start `bytes_per_row` bytes apart in the buffer. D3D11 cannot
+        /// copy between buffers and textures on the GPU, so this reads the
+        /// buffer back through a staging copy and re-uploads it with
+        /// `UpdateSubresource`, which stalls until prior GPU work completes.
+        pub fn copy_buffer_to_texture(
+            &self,
+            buffer: &GpuBuffer,
+            bytes_per_row: usize,
+            texture: &ID3D11Texture2D,
+        ) -> Result<()> {
+            // Reads back on the immediate context, after any recorded work.
+            self.flush_recording();
+            let device = self.device.device();
+            let ctx = self.device.context();
+
+            let mut tex_desc = D3D11_TEXTURE2D_DESC::default();
+            unsafe { texture.GetDesc(&mut tex_desc) };
+            let bpp = bytes_per_pixel(tex_desc.Format)?;
+            check_row_layout(
+                "copy_buffer_to_texture",
+                tex_desc.Width as usize,
+                tex_desc.Height as usize,
+                bpp,
+                bytes_per_row,
+                buffer.size,
+            )?;
+
+            let mut staging_desc = D3D11_BUFFER_DESC::default();
+            unsafe { buffer.dx11_buffer.GetDesc(&mut staging_desc) };
+            staging_desc.Usage = D3D11_USAGE_STAGING;
+            staging_desc.BindFlags = 0;
+            staging_desc.CPUAccessFlags = D3D11_CPU_ACCESS_READ.0 as u32;
+
+            let mut staging = None;
+            unsafe { device.CreateBuffer(&staging_desc, None, Some(&mut staging as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create staging buffer: {e}"))?;
+            let staging =
+                staging.ok_or_else(|| anyhow::anyhow!("D3D11 CreateBuffer returned null"))?;
+
+            unsafe {
+                ctx.CopyResource(&staging, &buffer.dx11_buffer);
+                let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+                ctx.Map(&staging, 0, D3D11_MAP_READ, 0, Some(&mut mapped))
+                    .map_err(|e| anyhow::anyhow!("Failed to map staging buffer: {e}"))?;
+                ctx.UpdateSubresource(texture, 0, None, mapped.pData, bytes_per_row as u32, 0);
+                ctx.Unmap(&staging, 0);
+            }
+
+            Ok(())
+        }
+
+        /// Copy the whole of `texture` into `buffer` with rows
+        /// `bytes_per_row` bytes apart.
+        ///
+        /// Goes through a CPU-readable staging texture (D3D11 cannot copy
+        /// between textures and buffers on the GPU), so this stalls until
+        /// prior GPU work completes.
+        pub fn copy_texture_to_buffer(
+            &self,
+            texture: &ID3D11Texture2D,
+            buffer: &GpuBuffer,
+            bytes_per_row: usize,
+        ) -> Result<()> {
+            let packed = self.read_staging(texture, Some((bytes_per_row, buffer.size)))?;
+            self.update_buffer(buffer, &packed);
+            Ok(())
+        }
+
+        /// Read the whole of `texture` back to the CPU, tightly packed in its
+        /// own pixel format.
+        ///
+        /// Stalls until prior GPU work completes. Meant for diagnostics, not
+        /// per-frame use.
+        pub fn read_texture(&self, texture: &ID3D11Texture2D) -> Result<Vec<u8>> {
+            self.read_staging(texture, None)
+        }
+
+        /// Copy `texture` through a staging texture and return its rows,
+        /// `bytes_per_row` apart and checked against a buffer of the given
+        /// size, or tightly packed for `None`.
+        fn read_staging(
+            &self,
+            texture: &ID3D11Texture2D,
+            layout: Option<(usize, usize)>,
+        ) -> Result<Vec<u8>> {
+            // Reads back on the immediate context, after any recorded work.
+            self.flush_recording();
+            let device = self.device.device();
+            let ctx = self.device.context();
+
+            let mut staging_desc = D3D11_TEXTURE2D_DESC::default();
+            unsafe { texture.GetDesc(&mut staging_desc) };
+            let (width, height) = (staging_desc.Width as usize, staging_desc.Height as usize);
+            let bpp = bytes_per_pixel(staging_desc.Format)?;
+            let bytes_per_row = match layout {
+                Some((bytes_per_row, buffer_size)) => {
+                    check_row_layout(
+                        "copy_texture_to_buffer",
+                        width,
+                        height,
+                        bpp,
+                        bytes_per_row,
+                        buffer_size,
+                    )?;
+                    bytes_per_row
+                }
+                None => width * bpp,
+            };
+
+            staging_desc.Usage = D3D11_USAGE_STAGING;
+            staging_desc.BindFlags = 0;
+            staging_desc.CPUAccessFlags = D3D11_CPU_ACCESS_READ.0 as u32;
+            staging_desc.MiscFlags = 0;
+
+            let mut staging = None;
+            unsafe { device.CreateTexture2D(&staging_desc, None, Some(&mut staging as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create staging texture: {e}"))?;
+            let staging =
+                staging.ok_or_else(|| anyhow::anyhow!("D3D11 CreateTexture2D returned null"))?;
+
+            // Repack rows from the driver's pitch into the requested one.
+            let mut packed = vec![0u8; bytes_per_row * height];
+            unsafe {
+                ctx.CopyResource(&staging, texture);
+                let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+                ctx.Map(&staging, 0, D3D11_MAP_READ, 0, Some(&mut mapped))
+                    .map_err(|e| anyhow::anyhow!("Failed to map staging texture: {e}"))?;
+                let src = mapped.pData as *const u8;
+                for (y, row) in packed.chunks_exact_mut(bytes_per_row).enumerate() {
+                    std::ptr::copy_nonoverlapping(
+                        src.add(y * mapped.RowPitch as usize),
+                        row.as_mut_ptr(),
+                        width * bpp,
+                    );
+                }
+                ctx.Unmap(&staging, 0);
+            }
+            Ok(packed)
+        }
+
+        /// Create a [`GpuTexture`] from a tightly packed RGBA8 image.
+        ///
+        /// The pixels are swizzled to BGRA and uploaded with
+        /// `UpdateSubresource`.
+        pub fn create_texture_from_rgba(
+            &self,
+            width: u32,
+            height: u32,
+            rgba: &[u8],
+        ) -> Result<GpuTexture> {
+            let texture = self.create_texture(width, height)?;
+            self.update_texture_from_rgba(&texture, rgba)?;
+            Ok(texture)
+        }
+
+        /// Replace the contents of `texture` with a tightly packed RGBA8
+        /// image of the same dimensions.
+        pub fn update_texture_from_rgba(&self, texture: &GpuTexture, rgba: &[u8]) -> Result<()> {
+            let bgra = rgba_to_bgra(texture, rgba)?;
+            unsafe {
+                self.command_context().UpdateSubresource(
+                    &texture.dx11_texture,
+                    0,
+                    None,
+                    bgra.as_ptr() as *const _,
+                    texture.width * 4,
+                    0,
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) use dx11_impl::{blob_bytes, bytes_per_pixel, compile_hlsl, BlitPipeline};
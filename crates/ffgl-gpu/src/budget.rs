@@ -0,0 +1,353 @@
+//! Process-wide accounting of plugin instances and their GPU memory.
+//!
+//! Hosts often stack many instances of a plugin in one composition, each
+//! holding its own bridge textures plus whatever intermediates its
+//! [`GpuPlugin`](crate::GpuPlugin) creates. [`draw_gpu_effect`] records every
+//! instance's usage here each frame; [`usage`] and [`total`] report it.
+//!
+//! With a [`SoftCap`] set, instances stop pooling retired bridge textures once
+//! the total exceeds the cap. If that is not enough they lower their internal
+//! resolution (in steps of 1/8, never below [`SoftCap::min_scale`]) until the
+//! bridge textures fit in what the intermediates leave. Changes to the
+//! budget are logged at info level.
+//!
+//! ffgl-gpu doesn't see the host destroy an instance. Call [`unregister`]
+//! from the instance's `deinstantiate`, as the examples do; otherwise an
+//! instance holding no intermediates is forgotten after [`IDLE_TIMEOUT`]
+//! without a draw.
+//!
+//! [`draw_gpu_effect`]: crate::draw_gpu_effect
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How long an instance without intermediates stays registered after its
+/// last draw.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// GPU memory held for one instance (or all of them), in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GpuMemory {
+    /// Bridge textures for the current frame size.
+    pub bridge: u64,
+    /// Retired bridge textures kept for reuse.
+    pub pool: u64,
+    /// Textures the plugin created through [`GpuContext`](crate::GpuContext).
+    pub intermediates: u64,
+}
+
+impl GpuMemory {
+    pub fn total(&self) -> u64 {
+        self.bridge + self.pool + self.intermediates
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            bridge: self.bridge + other.bridge,
+            pool: self.pool + other.pool,
+            intermediates: self.intermediates + other.intermediates,
+        }
+    }
+}
+
+/// One registered instance, as returned by [`usage`].
+#[derive(Debug, Clone, Copy)]
+pub struct InstanceUsage {
+    pub instance_id: u64,
+    pub memory: GpuMemory,
+    pub since_last_draw: Duration,
+}
+
+/// Memory limit shared by all instances in the process.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoftCap {
+    /// Total bytes the instances should stay under.
+    pub max_bytes: u64,
+    /// Lowest internal resolution scale the cap may impose, in
+    /// `[0.125, 1.0]`.
+    pub min_scale: f32,
+}
+
+impl SoftCap {
+    /// A cap of `max_bytes`, scaling down to 0.25 at most.
+    pub fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            min_scale: 0.25,
+        }
+    }
+}
+
+// Only the draw loops (macOS and Windows) create entries.
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+struct Entry {
+    memory: GpuMemory,
+    /// Bytes per pixel of the bridge textures, for the full-resolution
+    /// estimate.
+    bytes_per_pixel: u64,
+    /// Bridge textures at scale 1.0 for the last frame's viewport.
+    full_res_bridge: u64,
+    last_draw: Instant,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Entry {
+    fn new() -> Self {
+        Self {
+            memory: GpuMemory::default(),
+            bytes_per_pixel: 4,
+            full_res_bridge: 0,
+            last_draw: Instant::now(),
+        }
+    }
+}
+
+struct Registry {
+    instances: BTreeMap<u64, Entry>,
+    cap: Option<SoftCap>,
+    /// Last budget handed out, to log changes.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    budget: Budget,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    instances: BTreeMap::new(),
+    cap: None,
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    budget: Budget::UNLIMITED,
+});
+
+fn registry() -> MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Set or clear the process-wide soft cap. Takes effect from the next frame
+/// drawn.
+pub fn set_soft_cap(cap: Option<SoftCap>) {
+    registry().cap = cap;
+}
+
+/// The current soft cap, if any.
+pub fn soft_cap() -> Option<SoftCap> {
+    registry().cap
+}
+
+/// Every registered instance, by ID.
+pub fn usage() -> Vec<InstanceUsage> {
+    let now = Instant::now();
+    registry()
+        .instances
+        .iter()
+        .map(|(&instance_id, e)| InstanceUsage {
+            instance_id,
+            memory: e.memory,
+            since_last_draw: now.duration_since(e.last_draw),
+        })
+        .collect()
+}
+
+/// Memory held by all registered instances together.
+pub fn total() -> GpuMemory {
+    registry()
+        .instances
+        .values()
+        .fold(GpuMemory::default(), |sum, e| sum.add(e.memory))
+}
+
+/// Number of registered instances.
+pub fn instance_count() -> usize {
+    registry().instances.len()
+}
+
+/// Remove `instance_id` from the registry, e.g. from the plugin instance's
+/// `deinstantiate`. Also forgets its [`pacing`](crate::pacing) stats and the thread
+/// it draws on.
+pub fn unregister(instance_id: u64) {
+    registry().instances.remove(&instance_id);
+    crate::pacing::unregister(instance_id);
+    crate::affinity::unregister(instance_id);
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+thread_local! {
+    /// Instance being drawn on this thread; owns textures created meanwhile.
+    static CURRENT_INSTANCE: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Marks the instance being drawn until dropped.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) struct CurrentInstance(Option<u64>);
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn enter(instance_id: u64) -> CurrentInstance {
+    CurrentInstance(CURRENT_INSTANCE.with(|c| c.replace(Some(instance_id))))
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Drop for CurrentInstance {
+    fn drop(&mut self) {
+        CURRENT_INSTANCE.with(|c| c.set(self.0));
+    }
+}
+
+/// An intermediate texture's share of its creator's memory, released on
+/// drop.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) struct Allocation {
+    instance: Option<u64>,
+    bytes: u64,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Allocation {
+    /// Charge `bytes` to the instance currently drawing on this thread, if
+    /// any.
+    pub(crate) fn new(bytes: u64) -> Self {
+        let instance = CURRENT_INSTANCE.with(Cell::get);
+        if let Some(id) = instance {
+            let mut registry = registry();
+            let entry = registry.instances.entry(id).or_insert_with(Entry::new);
+            entry.memory.intermediates += bytes;
+        }
+        Self { instance, bytes }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        let Some(id) = self.instance else {
+            return;
+        };
+        if let Some(entry) = registry().instances.get_mut(&id) {
+            entry.memory.intermediates = entry.memory.intermediates.saturating_sub(self.bytes);
+        }
+    }
+}
+
+/// What the soft cap allows for a frame.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Budget {
+    /// Upper bound for the internal resolution scale.
+    pub(crate) scale: f32,
+    /// Whether retired bridge textures may be pooled.
+    pub(crate) pooling: bool,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Budget {
+    const UNLIMITED: Self = Self {
+        scale: 1.0,
+        pooling: true,
+    };
+
+    /// Bridge pool capacity for this budget.
+    pub(crate) fn pool_capacity(self) -> usize {
+        if self.pooling {
+            gpu_interop::DEFAULT_POOL_CAPACITY
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Registry {
+    fn budget(&self) -> Budget {
+        let Some(cap) = self.cap else {
+            return Budget::UNLIMITED;
+        };
+        let (full_res, pool, fixed) = self.instances.values().fold((0, 0, 0), |(b, p, f), e| {
+            (
+                b + e.full_res_bridge,
+                p + e.memory.pool,
+                f + e.memory.intermediates,
+            )
+        });
+        if full_res + pool + fixed <= cap.max_bytes {
+            return Budget::UNLIMITED;
+        }
+        if full_res + fixed <= cap.max_bytes || full_res == 0 {
+            return Budget {
+                scale: 1.0,
+                pooling: false,
+            };
+        }
+        // Bridge memory grows with the square of the scale. Round down to
+        // 1/8 steps so small changes in demand don't reallocate every frame.
+        let fit = (cap.max_bytes.saturating_sub(fixed) as f64 / full_res as f64).sqrt() as f32;
+        let min_scale = cap.min_scale.clamp(0.125, 1.0);
+        Budget {
+            scale: ((fit * 8.0).floor() / 8.0).clamp(min_scale, 1.0),
+            pooling: false,
+        }
+    }
+}
+
+/// Register a frame of `instance_id` at a `width` x `height` viewport and
+/// return its budget.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn begin_frame(instance_id: u64, width: u32, height: u32) -> Budget {
+    let now = Instant::now();
+    let mut registry = registry();
+    let mut idle = Vec::new();
+    registry.instances.retain(|&id, e| {
+        let keep = id == instance_id
+            || e.memory.intermediates > 0
+            || now.duration_since(e.last_draw) < IDLE_TIMEOUT;
+        if !keep {
+            idle.push(id);
+        }
+        keep
+    });
+    // Forget the idle instances everywhere else state is kept per instance.
+    for id in idle {
+        crate::pacing::unregister(id);
+        crate::affinity::unregister(id);
+    }
+    let entry = registry
+        .instances
+        .entry(instance_id)
+        .or_insert_with(Entry::new);
+    entry.last_draw = now;
+    // Two pairs of input and output textures.
+    entry.full_res_bridge = 4 * width as u64 * height as u64 * entry.bytes_per_pixel;
+
+    let budget = registry.budget();
+    if budget != registry.budget {
+        let total = registry
+            .instances
+            .values()
+            .fold(GpuMemory::default(), |sum, e| sum.add(e.memory));
+        tracing::info!(
+            instances = registry.instances.len(),
+            total_mb = total.total() / (1024 * 1024),
+            scale = budget.scale,
+            pooling = budget.pooling,
+            "GPU memory budget changed"
+        );
+        registry.budget = budget;
+    }
+    budget
+}
+
+/// Record the bridge memory `instance_id` holds after drawing (or zero once
+/// another instance has taken the bridge over).
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn record_bridge(
+    instance_id: u64,
+    memory: gpu_interop::BridgeMemory,
+    format: Option<gpu_interop::TextureFormat>,
+) {
+    if let Some(entry) = registry().instances.get_mut(&instance_id) {
+        entry.memory.bridge = memory.active;
+        entry.memory.pool = memory.pooled;
+        if let Some(format) = format {
+            entry.bytes_per_pixel = format.bytes_per_pixel() as u64;
+        }
+    }
+}
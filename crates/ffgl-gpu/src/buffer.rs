@@ -9,6 +9,8 @@ use objc2::rc::Retained;
 use objc2::runtime::ProtocolObject;
 #[cfg(target_os = "macos")]
 use objc2_metal::MTLBuffer;
+#[cfg(target_os = "macos")]
+use std::cell::RefCell;
 
 /// A GPU buffer for structured data used in compute shaders.
 ///
@@ -29,6 +31,11 @@ pub struct GpuBuffer {
     pub(crate) dx11_uav: windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView,
     #[cfg(target_os = "windows")]
     pub(crate) dx11_srv: windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView,
+
+    /// Counts the buffer in its context's memory report. `None` for
+    /// transient staging buffers.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) _tracked: Option<crate::memory::Tracked>,
 }
 
 impl GpuBuffer {
@@ -61,3 +68,123 @@ impl GpuBuffer {
         &self.dx11_srv
     }
 }
+
+// ---------------------------------------------------------------------------
+// Uniform staging pool (macOS)
+// ---------------------------------------------------------------------------
+
+/// Largest payload Metal accepts through `setBytes` / `setVertexBytes` /
+/// `setFragmentBytes`. Larger uniform data is staged through a buffer.
+#[cfg(target_os = "macos")]
+pub(crate) const MAX_INLINE_BYTES: usize = 4096;
+
+/// Upper bound on pooled staging buffers. Beyond this, oversized payloads
+/// get a one-off buffer that is released once its command buffer retires.
+#[cfg(target_os = "macos")]
+const MAX_POOLED_STAGING_BUFFERS: usize = 16;
+
+#[cfg(target_os = "macos")]
+struct StagingEntry {
+    buffer: Retained<ProtocolObject<dyn MTLBuffer>>,
+    /// Command buffer that last referenced this buffer. The entry can be
+    /// reused once it has completed.
+    in_flight: Option<Retained<ProtocolObject<dyn objc2_metal::MTLCommandBuffer>>>,
+}
+
+#[cfg(target_os = "macos")]
+impl StagingEntry {
+    fn is_free(&self) -> bool {
+        use objc2_metal::{MTLCommandBuffer, MTLCommandBufferStatus};
+        self.in_flight.as_ref().is_none_or(|cb| {
+            let status = cb.status();
+            status == MTLCommandBufferStatus::Completed || status == MTLCommandBufferStatus::Error
+        })
+    }
+}
+
+/// Pool of `StorageModeShared` Metal buffers used to transparently stage
+/// uniform payloads that exceed [`MAX_INLINE_BYTES`].
+///
+/// Each buffer is tagged with the command buffer that last used it and is
+/// only handed out again after that command buffer has completed, so the
+/// GPU never reads a buffer the CPU is overwriting.
+#[cfg(target_os = "macos")]
+#[derive(Default)]
+pub(crate) struct StagingPool {
+    entries: RefCell<Vec<StagingEntry>>,
+}
+
+#[cfg(target_os = "macos")]
+impl StagingPool {
+    /// Total size of the pooled buffers.
+    pub(crate) fn allocated_bytes(&self) -> u64 {
+        self.entries
+            .borrow()
+            .iter()
+            .map(|e| e.buffer.length() as u64)
+            .sum()
+    }
+
+    /// Copy `data` into a free staging buffer owned by `command_buffer` and
+    /// return it for binding.
+    pub(crate) fn stage(
+        &self,
+        device: &ProtocolObject<dyn objc2_metal::MTLDevice>,
+        command_buffer: &ProtocolObject<dyn objc2_metal::MTLCommandBuffer>,
+        data: &[u8],
+    ) -> Option<Retained<ProtocolObject<dyn MTLBuffer>>> {
+        use objc2::Message;
+        use objc2_metal::{MTLDevice, MTLResourceOptions};
+
+        let mut entries = self.entries.borrow_mut();
+
+        let index = match entries
+            .iter()
+            .position(|e| e.is_free() && e.buffer.length() >= data.len())
+        {
+            Some(i) => i,
+            None => {
+                // Round up so slightly larger payloads next frame can reuse it.
+                let size = data.len().next_power_of_two();
+                let buffer = device
+                    .newBufferWithLength_options(size, MTLResourceOptions::StorageModeShared)?;
+                if entries.len() >= MAX_POOLED_STAGING_BUFFERS {
+                    // Drop one idle entry to make room, or fall back to a
+                    // one-off buffer kept alive by the command buffer.
+                    match entries.iter().position(StagingEntry::is_free) {
+                        Some(i) => {
+                            entries.swap_remove(i);
+                        }
+                        None => {
+                            unsafe { copy_to_buffer(&buffer, data) };
+                            return Some(buffer);
+                        }
+                    }
+                }
+                entries.push(StagingEntry {
+                    buffer,
+                    in_flight: None,
+                });
+                entries.len() - 1
+            }
+        };
+
+        let entry = &mut entries[index];
+        unsafe { copy_to_buffer(&entry.buffer, data) };
+        entry.in_flight = Some(command_buffer.retain());
+        Some(entry.buffer.clone())
+    }
+}
+
+/// # Safety
+///
+/// `buffer` must be CPU-accessible, at least `data.len()` bytes long, and not
+/// currently being read by the GPU.
+#[cfg(target_os = "macos")]
+pub(crate) unsafe fn copy_to_buffer(buffer: &ProtocolObject<dyn MTLBuffer>, data: &[u8]) {
+    std::ptr::copy_nonoverlapping(
+        data.as_ptr(),
+        buffer.contents().as_ptr() as *mut u8,
+        data.len(),
+    );
+}
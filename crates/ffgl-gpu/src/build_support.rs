@@ -36,6 +36,36 @@
 //! // HLSL
 //! let compute_shader = ffgl_gpu::include_hlsl_shader!("compute");
 //! ```
+//!
+//! # Dispatch bounds
+//!
+//! Metal dispatches exactly `grid` threads, but D3D11 launches whole thread
+//! groups, so a kernel on a frame whose size isn't a multiple of its group
+//! size runs threads past the edge. Unless every kernel checks its thread ID
+//! against the texture size, those threads write out of bounds. Compile with
+//! [`HlslOptions::bounds_guard`] to wrap each compute entry point in that
+//! check:
+//!
+//! ```rust,ignore
+//! ffgl_gpu::build_support::compile_hlsl_shaders_with(
+//!     std::path::Path::new("src/shaders"),
+//!     &entries,
+//!     &ffgl_gpu::build_support::HlslOptions { bounds_guard: true },
+//! );
+//! ```
+//!
+//! The wrapper declares `cbuffer FfglGpuGrid : register(b13)`, which
+//! [`GpuContext::dispatch_compute`](crate::GpuContext::dispatch_compute)
+//! fills with the grid size. Kernels can also declare it themselves
+//! (`uint2 ffgl_gpu_grid;`) and check it by hand.
+//!
+//! # Shipped headers
+//!
+//! Both backends also get `OUT_DIR` on the include path, holding the random
+//! number helpers from [`random`](crate::random): `#include
+//! "ffgl_gpu_random.h"` in Metal or `"ffgl_gpu_random.hlsli"` in HLSL, and
+//! the texture addressing helpers from [`sampling`](crate::sampling) in
+//! `"ffgl_gpu_sampling.h"` / `"ffgl_gpu_sampling.hlsli"`.
 
 use std::path::Path;
 
@@ -82,6 +112,9 @@ pub fn compile_metal_shaders(shader_dir: &Path) {
         return;
     }
 
+    write_header(&out_dir, crate::random::MSL_HEADER, crate::random::RANDOM_MSL);
+    write_header(&out_dir, crate::sampling::MSL_HEADER, crate::sampling::SAMPLING_MSL);
+
     // Compile each .metal to .air
     let mut air_files = Vec::new();
     for metal_file in &metal_files {
@@ -99,6 +132,8 @@ pub fn compile_metal_shaders(shader_dir: &Path) {
                 metal_file.to_str().unwrap(),
                 "-I",
                 shader_dir.to_str().unwrap(),
+                "-I",
+                &out_dir,
                 "-o",
                 &air_file,
             ])
@@ -138,6 +173,7 @@ pub fn compile_metal_shaders(shader_dir: &Path) {
 }
 
 /// An HLSL shader entry point to compile.
+#[derive(Debug, Clone, Copy)]
 pub struct HlslEntry {
     /// HLSL source file name (relative to the shader directory).
     pub file: &'static str,
@@ -147,6 +183,30 @@ pub struct HlslEntry {
     pub target: &'static str,
 }
 
+/// An [`HlslEntry`] compiled with preprocessor defines, under its own name.
+///
+/// Variants of one kernel (a horizontal and a vertical blur pass, say) can
+/// then share a source file and entry point, selecting the difference with
+/// `#if`. Each variant is still its own shader object.
+#[derive(Debug, Clone, Copy)]
+pub struct HlslVariant {
+    pub entry: HlslEntry,
+    /// Name of the compiled `.cso`, as passed to
+    /// [`include_hlsl_shader!`](crate::include_hlsl_shader).
+    pub name: &'static str,
+    /// `(name, value)` pairs passed to fxc as `/D name=value`.
+    pub defines: &'static [(&'static str, &'static str)],
+}
+
+/// Options for [`compile_hlsl_shaders_with`] and [`compile_hlsl_variants`].
+#[derive(Debug, Clone, Default)]
+pub struct HlslOptions {
+    /// Wrap every compute entry point (`cs_*` target) so threads outside the
+    /// dispatched grid return before running it. See the
+    /// [module docs](self#dispatch-bounds).
+    pub bounds_guard: bool,
+}
+
 /// Compile HLSL shaders from a directory.
 ///
 /// Each [`HlslEntry`] specifies a source file, entry point, and target
@@ -157,55 +217,162 @@ pub struct HlslEntry {
 /// in the shader directory.
 #[cfg(target_os = "windows")]
 pub fn compile_hlsl_shaders(shader_dir: &Path, entries: &[HlslEntry]) {
-    use std::process::Command;
+    compile_hlsl_shaders_with(shader_dir, entries, &HlslOptions::default());
+}
+
+/// [`compile_hlsl_shaders`] with [`HlslOptions`].
+#[cfg(target_os = "windows")]
+pub fn compile_hlsl_shaders_with(shader_dir: &Path, entries: &[HlslEntry], options: &HlslOptions) {
+    let Some((fxc, out_dir)) = prepare_hlsl(shader_dir) else {
+        return;
+    };
+    for entry in entries {
+        compile_hlsl_entry(
+            &fxc,
+            shader_dir,
+            &out_dir,
+            entry,
+            entry.entry_point,
+            &[],
+            options,
+        );
+    }
+    rerun_if_hlsl_changed(shader_dir);
+}
+
+/// Compile [`HlslVariant`]s: entry points built once per set of
+/// preprocessor defines, each to its own `.cso`.
+///
+/// ```rust,ignore
+/// use ffgl_gpu::build_support::{HlslEntry, HlslVariant};
+///
+/// const BLUR: HlslEntry = HlslEntry {
+///     file: "blur.hlsl",
+///     entry_point: "blur",
+///     target: "cs_5_0",
+/// };
+/// ffgl_gpu::build_support::compile_hlsl_variants(
+///     std::path::Path::new("shaders"),
+///     &[
+///         HlslVariant { entry: BLUR, name: "blur_h", defines: &[("VERTICAL", "0")] },
+///         HlslVariant { entry: BLUR, name: "blur_v", defines: &[("VERTICAL", "1")] },
+///     ],
+///     &Default::default(),
+/// );
+/// ```
+#[cfg(target_os = "windows")]
+pub fn compile_hlsl_variants(shader_dir: &Path, variants: &[HlslVariant], options: &HlslOptions) {
+    let Some((fxc, out_dir)) = prepare_hlsl(shader_dir) else {
+        return;
+    };
+    for variant in variants {
+        compile_hlsl_entry(
+            &fxc,
+            shader_dir,
+            &out_dir,
+            &variant.entry,
+            variant.name,
+            variant.defines,
+            options,
+        );
+    }
+    rerun_if_hlsl_changed(shader_dir);
+}
 
+/// Locate fxc and `OUT_DIR`, or warn and return `None` if `shader_dir` is
+/// missing.
+#[cfg(target_os = "windows")]
+fn prepare_hlsl(shader_dir: &Path) -> Option<(std::path::PathBuf, String)> {
     if !shader_dir.is_dir() {
         println!(
             "cargo:warning=No HLSL shader directory found at {shader_dir:?}, \
              skipping shader compilation"
         );
-        return;
+        return None;
     }
 
     let out_dir = std::env::var("OUT_DIR").unwrap();
 
     let fxc =
         find_fxc().expect("Could not find fxc.exe. Install Windows SDK or add fxc.exe to PATH.");
+    write_header(&out_dir, crate::random::HLSL_HEADER, crate::random::RANDOM_HLSL);
+    write_header(&out_dir, crate::sampling::HLSL_HEADER, crate::sampling::SAMPLING_HLSL);
+    Some((fxc, out_dir))
+}
 
-    for entry in entries {
-        let input_path = shader_dir.join(entry.file);
-        let output_path = format!("{out_dir}/{}.cso", entry.entry_point);
+/// Write one of the headers shipped with the crate (see [`crate::random`]
+/// and [`crate::sampling`])
+/// to `out_dir`, which the compile functions add to the include path.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn write_header(out_dir: &str, name: &str, source: &str) {
+    let path = Path::new(out_dir).join(name);
+    std::fs::write(&path, source)
+        .unwrap_or_else(|e| panic!("Failed to write {}: {e}", path.display()));
+}
 
-        let status = Command::new(&fxc)
-            .args([
-                "/T",
-                entry.target,
-                "/E",
-                entry.entry_point,
-                "/I",
-                shader_dir.to_str().unwrap(),
-                "/Fo",
-                &output_path,
-                "/nologo",
-                "/O3",
-                input_path.to_str().unwrap(),
-            ])
-            .status()
-            .unwrap_or_else(|e| {
+/// Compile `entry` with `defines` to `{name}.cso` in `out_dir`.
+#[cfg(target_os = "windows")]
+fn compile_hlsl_entry(
+    fxc: &Path,
+    shader_dir: &Path,
+    out_dir: &str,
+    entry: &HlslEntry,
+    name: &str,
+    defines: &[(&str, &str)],
+    options: &HlslOptions,
+) {
+    use std::process::Command;
+
+    let mut input_path = shader_dir.join(entry.file);
+    let output_path = format!("{out_dir}/{name}.cso");
+
+    if options.bounds_guard && entry.target.starts_with("cs_") {
+        let source = std::fs::read_to_string(&input_path)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {e}", entry.file));
+        let wrapper =
+            guard_compute_entry(&source, entry.file, entry.entry_point).unwrap_or_else(|| {
                 panic!(
-                    "Failed to run fxc.exe for {}:{}: {e}",
-                    entry.file, entry.entry_point
+                    "bounds_guard: no `[numthreads(...)] void {}(...)` in {}",
+                    entry.entry_point, entry.file
                 )
             });
-        assert!(
-            status.success(),
-            "HLSL compilation failed for {}:{}",
-            entry.file,
-            entry.entry_point
-        );
+        input_path = Path::new(out_dir).join(format!("{name}.guarded.hlsl"));
+        std::fs::write(&input_path, wrapper)
+            .unwrap_or_else(|e| panic!("Failed to write {}: {e}", input_path.display()));
     }
 
-    // Re-run if any shader source changes
+    let mut cmd = Command::new(fxc);
+    cmd.args([
+        "/T",
+        entry.target,
+        "/E",
+        entry.entry_point,
+        "/I",
+        shader_dir.to_str().unwrap(),
+        "/I",
+        out_dir,
+        "/Fo",
+        &output_path,
+        "/nologo",
+        "/O3",
+    ]);
+    for (define, value) in defines {
+        cmd.arg("/D").arg(format!("{define}={value}"));
+    }
+    let status = cmd
+        .arg(input_path.to_str().unwrap())
+        .status()
+        .unwrap_or_else(|e| panic!("Failed to run fxc.exe for {}:{name}: {e}", entry.file));
+    assert!(
+        status.success(),
+        "HLSL compilation failed for {}:{name}",
+        entry.file
+    );
+}
+
+/// Re-run the build script if any shader source changes.
+#[cfg(target_os = "windows")]
+fn rerun_if_hlsl_changed(shader_dir: &Path) {
     if let Ok(entries) = std::fs::read_dir(shader_dir) {
         for entry in entries.flatten() {
             let path = entry.path();
@@ -219,6 +386,86 @@ pub fn compile_hlsl_shaders(shader_dir: &Path, entries: &[HlslEntry]) {
     }
 }
 
+/// Register of the `FfglGpuGrid` constant buffer declared by guarded
+/// kernels, the last of the 14 D3D11 allows.
+#[cfg(target_os = "windows")]
+const GRID_CBUFFER_REGISTER: u32 = 13;
+
+/// Source for a wrapper around compute entry point `entry` of `file`: the
+/// original is renamed and included, and a new entry point with the same
+/// `numthreads` and parameters returns early for threads outside the grid.
+///
+/// Returns `None` if `entry` isn't declared as `[numthreads(..)] void
+/// entry(..)` in `source`.
+#[cfg(target_os = "windows")]
+fn guard_compute_entry(source: &str, file: &str, entry: &str) -> Option<String> {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    // The declaration: `void <entry>(`, not part of a longer identifier.
+    let (name_at, params_at) = source.match_indices(entry).find_map(|(i, _)| {
+        let before = source[..i].trim_end();
+        let after = source[i + entry.len()..].trim_start();
+        let standalone = !source[..i].ends_with(is_ident);
+        (standalone && before.ends_with("void") && after.starts_with('('))
+            .then(|| (i, source.len() - after.len() + 1))
+    })?;
+    let attr_at = source[..name_at].rfind("[numthreads")?;
+    let attr_end = attr_at + source[attr_at..name_at].find(']')? + 1;
+    let numthreads = &source[attr_at..attr_end];
+
+    // Parameter list up to the matching parenthesis.
+    let mut depth = 1;
+    let params_end = params_at
+        + source[params_at..].find(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        })?;
+    let params: Vec<&str> = source[params_at..params_end]
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    // Forward each parameter by name; find or add the dispatch thread ID.
+    let mut names = Vec::new();
+    let mut thread_id = None;
+    for param in &params {
+        let (decl, semantic) = param.split_once(':').unwrap_or((param, ""));
+        let name = decl.trim().rsplit(|c: char| !is_ident(c)).next()?;
+        if semantic.trim().eq_ignore_ascii_case("SV_DispatchThreadID") {
+            thread_id = Some(name);
+        }
+        names.push(name);
+    }
+    let mut wrapper_params = params.join(", ");
+    let thread_id = thread_id.unwrap_or_else(|| {
+        if !wrapper_params.is_empty() {
+            wrapper_params.push_str(", ");
+        }
+        wrapper_params.push_str("uint3 ffgl_gpu_id : SV_DispatchThreadID");
+        "ffgl_gpu_id"
+    });
+
+    Some(format!(
+        "#define {entry} {entry}_unguarded\n\
+         #include \"{file}\"\n\
+         #undef {entry}\n\
+         \n\
+         cbuffer FfglGpuGrid : register(b{GRID_CBUFFER_REGISTER}) {{ uint2 ffgl_gpu_grid; }};\n\
+         \n\
+         {numthreads}\n\
+         void {entry}({wrapper_params}) {{\n\
+         \x20   if ({thread_id}.x >= ffgl_gpu_grid.x || {thread_id}.y >= ffgl_gpu_grid.y) return;\n\
+         \x20   {entry}_unguarded({});\n\
+         }}\n",
+        names.join(", ")
+    ))
+}
+
 /// Find fxc.exe: check PATH first, then scan Windows SDK directories.
 #[cfg(target_os = "windows")]
 fn find_fxc() -> Option<std::path::PathBuf> {
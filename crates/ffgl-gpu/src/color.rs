@@ -0,0 +1,459 @@
+//! Built-in color conversion passes for the bridge textures.
+//!
+//! When a plugin opts into sRGB color management (see
+//! [`GpuPlugin::color_space`](crate::GpuPlugin::color_space)), the draw loop
+//! decodes the input to linear light before `gpu_draw` and encodes the output
+//! afterward. On macOS the bridge does this in hardware through the
+//! `BGRA8Unorm_sRGB` pixel format. On Windows the shared textures have no
+//! sRGB variant, so a small compute pass converts them in place.
+//!
+//! The same pass handles [`AlphaMode::Straight`](crate::AlphaMode::Straight):
+//! it un-premultiplies the input and re-premultiplies the output, in the
+//! host's encoded space, on both platforms.
+//!
+//! It also blends the output with the dry input for
+//! [`GpuPlugin::mix`](crate::GpuPlugin::mix), so an effect gets an A/B
+//! ramp without a blend pass of its own. Declare [`mix_param`] among the
+//! plugin's parameters and return its value from `mix`. On macOS the blend
+//! happens in the host's encoded space, on Windows in the space `gpu_draw`
+//! worked in.
+//!
+//! Steps are applied in the order mix, un-premultiply, decode, encode,
+//! premultiply.
+
+use std::ffi::CString;
+
+use ffgl_core::parameters::{ParameterTypes, SimpleParamInfo};
+
+/// Decode sRGB-encoded color to linear.
+#[cfg(target_os = "windows")]
+pub(crate) const SRGB_DECODE: u32 = 1 << 0;
+/// Encode linear color to sRGB.
+#[cfg(target_os = "windows")]
+pub(crate) const SRGB_ENCODE: u32 = 1 << 1;
+/// Divide color by alpha.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) const UNPREMULTIPLY: u32 = 1 << 2;
+/// Multiply color by alpha.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) const PREMULTIPLY: u32 = 1 << 3;
+/// Blend with the dry input.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) const MIX: u32 = 1 << 4;
+
+/// A "Mix" parameter, defaulting to fully processed, for
+/// [`GpuPlugin::mix`](crate::GpuPlugin::mix).
+pub fn mix_param() -> SimpleParamInfo {
+    SimpleParamInfo {
+        name: CString::new("Mix").unwrap(),
+        param_type: ParameterTypes::Standard,
+        default: Some(1.0),
+        ..Default::default()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod metal_impl {
+    use crate::context::GpuContext;
+    use crate::dispatch::PendingWork;
+    use anyhow::Result;
+    use objc2::rc::Retained;
+    use objc2::runtime::ProtocolObject;
+    use objc2_foundation::NSString;
+    use objc2_metal::*;
+    use std::cell::RefCell;
+
+    /// Conversion kernel. Writes to a scratch texture that is copied back
+    /// over the source afterward.
+    const COLOR_MSL: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct Params {
+    uint ops;
+    float mix;
+};
+
+kernel void ffgl_gpu_color(texture2d<float, access::read> src [[texture(0)]],
+                           texture2d<float, access::write> dst [[texture(1)]],
+                           texture2d<float, access::read> dry [[texture(2)]],
+                           constant Params& p [[buffer(0)]],
+                           uint2 gid [[thread_position_in_grid]]) {
+    if (gid.x >= dst.get_width() || gid.y >= dst.get_height()) return;
+
+    uint ops = p.ops;
+    float4 c = src.read(gid);
+    if ((ops & 16) != 0) c = mix(dry.read(gid), c, p.mix);
+    if ((ops & 4) != 0 && c.a > 0.0) c.rgb /= c.a;
+    if ((ops & 8) != 0) c.rgb *= c.a;
+    dst.write(c, gid);
+}
+"#;
+
+    /// Lazily-created compute pipeline and scratch texture for
+    /// [`GpuContext::convert_color`].
+    pub(crate) struct ColorPipeline {
+        state: Retained<ProtocolObject<dyn MTLComputePipelineState>>,
+        scratch: RefCell<Option<Retained<ProtocolObject<dyn MTLTexture>>>>,
+    }
+
+    impl ColorPipeline {
+        /// Size of the scratch texture, if one has been created.
+        pub(crate) fn scratch_bytes(&self) -> u64 {
+            self.scratch
+                .borrow()
+                .as_ref()
+                .map_or(0, |s| s.allocatedSize() as u64)
+        }
+
+        fn new(device: &ProtocolObject<dyn MTLDevice>) -> Result<Self> {
+            let library = device
+                .newLibraryWithSource_options_error(&NSString::from_str(COLOR_MSL), None)
+                .map_err(|e| anyhow::anyhow!("Failed to compile color shader: {e}"))?;
+            let function = library
+                .newFunctionWithName(&NSString::from_str("ffgl_gpu_color"))
+                .ok_or_else(|| anyhow::anyhow!("Color kernel function not found"))?;
+            let state = device
+                .newComputePipelineStateWithFunction_error(&function)
+                .map_err(|e| anyhow::anyhow!("Failed to create color pipeline: {e}"))?;
+            Ok(Self {
+                state,
+                scratch: RefCell::new(None),
+            })
+        }
+    }
+
+    impl GpuContext {
+        /// Apply the conversion steps in `ops` (see the constants in
+        /// [`crate::color`]) to `texture` in place, and return a
+        /// [`PendingWork`] token. Returns `None` if `ops` is empty.
+        ///
+        /// With [`MIX`](crate::color::MIX), `dry` is the texture blended in
+        /// and the amount of `texture` kept.
+        ///
+        /// sRGB textures are processed through a `BGRA8Unorm` view, so the
+        /// steps operate on the stored (encoded) values.
+        pub(crate) fn convert_color(
+            &self,
+            texture: &ProtocolObject<dyn MTLTexture>,
+            ops: u32,
+            dry: Option<(&ProtocolObject<dyn MTLTexture>, f32)>,
+        ) -> Result<Option<PendingWork>> {
+            if ops == 0 {
+                return Ok(None);
+            }
+
+            let device = self.device.device();
+            let pipeline = match self.color.get() {
+                Some(p) => p,
+                None => {
+                    let p = ColorPipeline::new(device)?;
+                    self.color.get_or_init(|| p)
+                }
+            };
+
+            let unorm_view = |texture: &ProtocolObject<dyn MTLTexture>| {
+                if texture.pixelFormat() == MTLPixelFormat::BGRA8Unorm_sRGB {
+                    texture
+                        .newTextureViewWithPixelFormat(MTLPixelFormat::BGRA8Unorm)
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("Failed to create texture view for color pass")
+                        })
+                } else {
+                    Ok(texture.retain())
+                }
+            };
+            let view = unorm_view(texture)?;
+            let (dry_view, amount) = match dry {
+                Some((dry, amount)) if ops & crate::color::MIX != 0 => (unorm_view(dry)?, amount),
+                // The kernel still declares the texture.
+                _ => (view.clone(), 1.0),
+            };
+            let params: [u32; 2] = [ops, f32::to_bits(amount)];
+
+            let mut scratch = pipeline.scratch.borrow_mut();
+            let stale = scratch.as_ref().is_none_or(|s| {
+                (s.width(), s.height(), s.pixelFormat())
+                    != (view.width(), view.height(), view.pixelFormat())
+            });
+            if stale {
+                let desc = MTLTextureDescriptor::new();
+                desc.setTextureType(MTLTextureType::Type2D);
+                desc.setPixelFormat(view.pixelFormat());
+                unsafe {
+                    desc.setWidth(view.width());
+                    desc.setHeight(view.height());
+                }
+                desc.setStorageMode(MTLStorageMode::Private);
+                desc.setUsage(MTLTextureUsage::ShaderWrite);
+                *scratch = Some(
+                    device
+                        .newTextureWithDescriptor(&desc)
+                        .ok_or_else(|| anyhow::anyhow!("Failed to create color scratch texture"))?,
+                );
+            }
+            let scratch = scratch.as_ref().unwrap();
+
+            let command_buffer = self
+                .device
+                .command_queue()
+                .commandBuffer()
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal command buffer"))?;
+
+            let encoder = command_buffer
+                .computeCommandEncoder()
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal compute encoder"))?;
+            encoder.setComputePipelineState(&pipeline.state);
+            unsafe {
+                encoder.setTexture_atIndex(Some(&view), 0);
+                encoder.setTexture_atIndex(Some(scratch), 1);
+                encoder.setTexture_atIndex(Some(&dry_view), 2);
+                encoder.setBytes_length_atIndex(
+                    std::ptr::NonNull::from(&params).cast(),
+                    std::mem::size_of::<[u32; 2]>(),
+                    0,
+                );
+            }
+            encoder.dispatchThreads_threadsPerThreadgroup(
+                MTLSize {
+                    width: view.width(),
+                    height: view.height(),
+                    depth: 1,
+                },
+                MTLSize {
+                    width: 8,
+                    height: 8,
+                    depth: 1,
+                },
+            );
+            encoder.endEncoding();
+
+            let blit = command_buffer
+                .blitCommandEncoder()
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal blit encoder"))?;
+            unsafe { blit.copyFromTexture_toTexture(scratch, &view) };
+            blit.endEncoding();
+
+            Ok(Some(PendingWork::commit(command_buffer)))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) use metal_impl::ColorPipeline;
+
+#[cfg(target_os = "windows")]
+mod dx11_impl {
+    use crate::blit::{blob_bytes, compile_hlsl};
+    use crate::context::GpuContext;
+    use anyhow::Result;
+    use std::cell::RefCell;
+    use windows::Win32::Graphics::Direct3D11::*;
+
+    /// In-place color conversion. `src` and `dst` are the same texture; the
+    /// pass writes to a scratch copy that is copied back afterward.
+    const COLOR_HLSL: &str = r#"
+Texture2D<float4> src : register(t0);
+Texture2D<float4> dry : register(t1);
+RWTexture2D<float4> dst : register(u0);
+
+cbuffer Params : register(b0) {
+    uint ops;
+    float amount;
+    uint2 _pad;
+};
+
+float3 srgb_to_linear(float3 c) {
+    return c <= 0.04045 ? c / 12.92 : pow((c + 0.055) / 1.055, 2.4);
+}
+
+float3 linear_to_srgb(float3 c) {
+    c = saturate(c);
+    return c <= 0.0031308 ? c * 12.92 : 1.055 * pow(c, 1.0 / 2.4) - 0.055;
+}
+
+[numthreads(8, 8, 1)]
+void cs_main(uint3 id : SV_DispatchThreadID) {
+    uint w, h;
+    dst.GetDimensions(w, h);
+    if (id.x >= w || id.y >= h) return;
+
+    float4 c = src[id.xy];
+    if (ops & 16) c = lerp(dry[id.xy], c, amount);
+    if ((ops & 4) && c.a > 0.0) c.rgb /= c.a;
+    if (ops & 1) c.rgb = srgb_to_linear(c.rgb);
+    if (ops & 2) c.rgb = linear_to_srgb(c.rgb);
+    if (ops & 8) c.rgb *= c.a;
+    dst[id.xy] = c;
+}
+"#;
+
+    /// Scratch target matching the converted texture.
+    struct Scratch {
+        texture: ID3D11Texture2D,
+        uav: ID3D11UnorderedAccessView,
+        desc: D3D11_TEXTURE2D_DESC,
+    }
+
+    /// Lazily-created compute shader and scratch texture for
+    /// [`GpuContext::convert_color`].
+    pub(crate) struct ColorPipeline {
+        shader: ID3D11ComputeShader,
+        params: ID3D11Buffer,
+        scratch: RefCell<Option<Scratch>>,
+    }
+
+    impl ColorPipeline {
+        /// Size of the scratch texture, if one has been created.
+        pub(crate) fn scratch_bytes(&self) -> u64 {
+            self.scratch
+                .borrow()
+                .as_ref()
+                .map_or(0, |s| scratch_size(&s.desc))
+        }
+
+        fn new(device: &ID3D11Device) -> Result<Self> {
+            let blob = compile_hlsl(COLOR_HLSL, b"cs_main\0", b"cs_5_0\0")?;
+            let mut shader = None;
+            unsafe {
+                device.CreateComputeShader(blob_bytes(&blob), None, Some(&mut shader as *mut _))
+            }
+                .map_err(|e| anyhow::anyhow!("Failed to create color compute shader: {e}"))?;
+            let shader =
+                shader.ok_or_else(|| anyhow::anyhow!("D3D11 CreateComputeShader returned null"))?;
+
+            let desc = D3D11_BUFFER_DESC {
+                ByteWidth: 16,
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: D3D11_BIND_CONSTANT_BUFFER.0 as u32,
+                ..Default::default()
+            };
+            let mut params = None;
+            unsafe { device.CreateBuffer(&desc, None, Some(&mut params as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create color params buffer: {e}"))?;
+            let params = params.ok_or_else(|| anyhow::anyhow!("D3D11 CreateBuffer returned null"))?;
+
+            Ok(Self {
+                shader,
+                params,
+                scratch: RefCell::new(None),
+            })
+        }
+    }
+
+    /// Bytes taken by a framework scratch texture described by `desc`.
+    pub(crate) fn scratch_size(desc: &D3D11_TEXTURE2D_DESC) -> u64 {
+        let bpp = crate::blit::bytes_per_pixel(desc.Format).unwrap_or(4);
+        desc.Width as u64 * desc.Height as u64 * bpp as u64
+    }
+
+    fn create_scratch(device: &ID3D11Device, like: &D3D11_TEXTURE2D_DESC) -> Result<Scratch> {
+        let desc = D3D11_TEXTURE2D_DESC {
+            Width: like.Width,
+            Height: like.Height,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: like.Format,
+            SampleDesc: like.SampleDesc,
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: D3D11_BIND_UNORDERED_ACCESS.0 as u32,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+        };
+        let mut texture = None;
+        unsafe { device.CreateTexture2D(&desc, None, Some(&mut texture as *mut _)) }
+            .map_err(|e| anyhow::anyhow!("Failed to create color scratch texture: {e}"))?;
+        let texture =
+            texture.ok_or_else(|| anyhow::anyhow!("D3D11 CreateTexture2D returned null"))?;
+        let mut uav = None;
+        unsafe { device.CreateUnorderedAccessView(&texture, None, Some(&mut uav as *mut _)) }
+            .map_err(|e| anyhow::anyhow!("Failed to create color scratch UAV: {e}"))?;
+        let uav =
+            uav.ok_or_else(|| anyhow::anyhow!("D3D11 CreateUnorderedAccessView returned null"))?;
+        Ok(Scratch { texture, uav, desc })
+    }
+
+    impl GpuContext {
+        /// Apply the conversion steps in `ops` (see the constants in
+        /// [`crate::color`]) to `texture` in place on the
+        /// [`command_context`](Self::command_context).
+        ///
+        /// With [`MIX`](crate::color::MIX), `dry` is the texture blended in
+        /// and the amount of `texture` kept.
+        pub(crate) fn convert_color(
+            &self,
+            texture: &ID3D11Texture2D,
+            ops: u32,
+            dry: Option<(&ID3D11Texture2D, f32)>,
+        ) -> Result<()> {
+            if ops == 0 {
+                return Ok(());
+            }
+
+            let device = self.device.device();
+            let ctx = self.command_context();
+
+            let pipeline = match self.color.get() {
+                Some(p) => p,
+                None => {
+                    let p = ColorPipeline::new(device)?;
+                    self.color.get_or_init(|| p)
+                }
+            };
+
+            let mut desc = D3D11_TEXTURE2D_DESC::default();
+            unsafe { texture.GetDesc(&mut desc) };
+
+            let mut scratch = pipeline.scratch.borrow_mut();
+            let stale = scratch.as_ref().is_none_or(|s| {
+                (s.desc.Width, s.desc.Height, s.desc.Format)
+                    != (desc.Width, desc.Height, desc.Format)
+            });
+            if stale {
+                *scratch = Some(create_scratch(device, &desc)?);
+            }
+            let scratch = scratch.as_ref().unwrap();
+
+            let mut srv = None;
+            unsafe { device.CreateShaderResourceView(texture, None, Some(&mut srv as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create SRV for color pass: {e}"))?;
+            let (dry_srv, amount) = match dry {
+                Some((dry, amount)) if ops & crate::color::MIX != 0 => {
+                    let mut dry_srv = None;
+                    unsafe {
+                        device.CreateShaderResourceView(dry, None, Some(&mut dry_srv as *mut _))
+                    }
+                    .map_err(|e| anyhow::anyhow!("Failed to create SRV for mix: {e}"))?;
+                    (dry_srv, amount)
+                }
+                _ => (None, 1.0),
+            };
+
+            let params: [u32; 4] = [ops, amount.to_bits(), 0, 0];
+            unsafe {
+                ctx.UpdateSubresource(&pipeline.params, 0, None, params.as_ptr() as *const _, 0, 0);
+
+                ctx.CSSetShader(&pipeline.shader, None);
+                ctx.CSSetShaderResources(0, Some(&[srv, dry_srv]));
+                let uavs = [Some(scratch.uav.clone())];
+                ctx.CSSetUnorderedAccessViews(0, 1, Some(uavs.as_ptr()), None);
+                ctx.CSSetConstantBuffers(0, Some(&[Some(pipeline.params.clone())]));
+                ctx.Dispatch(desc.Width.div_ceil(8), desc.Height.div_ceil(8), 1);
+
+                let null_uavs: [Option<ID3D11UnorderedAccessView>; 1] = Default::default();
+                ctx.CSSetUnorderedAccessViews(0, 1, Some(null_uavs.as_ptr()), None);
+                let null_srvs: [Option<ID3D11ShaderResourceView>; 2] = Default::default();
+                ctx.CSSetShaderResources(0, Some(&null_srvs));
+                let null_cbufs: [Option<ID3D11Buffer>; 1] = Default::default();
+                ctx.CSSetConstantBuffers(0, Some(&null_cbufs));
+
+                ctx.CopyResource(texture, &scratch.texture);
+            }
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) use dx11_impl::{scratch_size, ColorPipeline};
@@ -0,0 +1,193 @@
+//! Callbacks run when submitted GPU work completes, for
+//! [`PendingWork::on_completed`](crate::PendingWork::on_completed).
+//!
+//! On macOS every command buffer the framework commits carries a completed
+//! handler. It runs the callbacks attached so far and marks the work done, so
+//! callbacks attached later run straight away.
+//!
+//! On Windows, [`GpuContext::submit`](crate::GpuContext::submit) signals a
+//! D3D11.4 fence. One thread per context waits on it and runs callbacks in
+//! submission order. Runtimes without fences (before Windows 10 1703) block
+//! in `submit` until the work completes instead.
+//!
+//! Callbacks run on a Metal completion thread or the fence thread, or on the
+//! caller's thread if the work has already completed. They must not touch GL
+//! or the [`GpuContext`](crate::GpuContext).
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) type Callback = Box<dyn FnOnce() + Send>;
+
+#[cfg(target_os = "macos")]
+pub(crate) use metal_impl::Completion;
+
+#[cfg(target_os = "windows")]
+pub(crate) use dx11_impl::FenceThread;
+
+/// Queues a callback on a fence thread, to run once the fence reaches the
+/// given value.
+#[cfg(target_os = "windows")]
+pub(crate) type CallbackSender = std::sync::mpsc::Sender<(u64, Callback)>;
+
+/// Split `callback` into `count` callbacks, the last of which to run runs
+/// it. Used to wait on several pieces of work at once.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn countdown(count: usize, callback: Callback) -> impl Fn() -> Callback {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    let shared = Arc::new((AtomicUsize::new(count), Mutex::new(Some(callback))));
+    move || {
+        let shared = shared.clone();
+        Box::new(move || {
+            if shared.0.fetch_sub(1, Ordering::AcqRel) == 1 {
+                let callback = shared
+                    .1
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take();
+                if let Some(callback) = callback {
+                    callback();
+                }
+            }
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod metal_impl {
+    use super::Callback;
+    use std::sync::{Mutex, PoisonError};
+
+    /// Callbacks waiting on one command buffer.
+    #[derive(Default)]
+    pub(crate) struct Completion(Mutex<State>);
+
+    #[derive(Default)]
+    struct State {
+        done: bool,
+        callbacks: Vec<Callback>,
+    }
+
+    impl Completion {
+        /// Run `callback` once the command buffer completes, or now if it
+        /// already has.
+        pub(crate) fn add(&self, callback: Callback) {
+            let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+            if state.done {
+                drop(state);
+                callback();
+            } else {
+                state.callbacks.push(callback);
+            }
+        }
+
+        /// Called from the command buffer's completed handler.
+        pub(crate) fn finish(&self) {
+            let callbacks = {
+                let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+                state.done = true;
+                std::mem::take(&mut state.callbacks)
+            };
+            for callback in callbacks {
+                callback();
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod dx11_impl {
+    use super::{Callback, CallbackSender};
+    use std::cell::Cell;
+    use std::sync::mpsc::{self, Receiver};
+    use std::time::Duration;
+
+    use windows::core::{Interface, PCWSTR};
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Graphics::Direct3D11::{
+        ID3D11Device5, ID3D11DeviceContext4, ID3D11Fence, D3D11_FENCE_FLAG_NONE,
+    };
+    use windows::Win32::System::Threading::{CreateEventW, WaitForSingleObject, INFINITE};
+
+    /// `ID3D11Fence` is free-threaded: `GetCompletedValue` and
+    /// `SetEventOnCompletion` may be called from any thread.
+    struct SendFence(ID3D11Fence);
+
+    unsafe impl Send for SendFence {}
+
+    /// A fence and the thread that runs callbacks as it advances.
+    pub(crate) struct FenceThread {
+        fence: ID3D11Fence,
+        context: ID3D11DeviceContext4,
+        last_signaled: Cell<u64>,
+        sender: CallbackSender,
+    }
+
+    impl FenceThread {
+        /// Returns `None` if the runtime does not support D3D11.4 fences.
+        pub(crate) fn new(device: &gpu_interop::dx11::Dx11Device) -> Option<Self> {
+            let device5: ID3D11Device5 = device.device().cast().ok()?;
+            let context: ID3D11DeviceContext4 = device.context().cast().ok()?;
+            let mut fence: Option<ID3D11Fence> = None;
+            unsafe { device5.CreateFence(0, D3D11_FENCE_FLAG_NONE, &mut fence) }.ok()?;
+            let fence = fence?;
+
+            let (sender, receiver) = mpsc::channel();
+            let waiter = SendFence(fence.clone());
+            std::thread::Builder::new()
+                .name("ffgl-gpu fence".into())
+                .spawn(move || run(waiter, receiver))
+                .ok()?;
+            Some(Self {
+                fence,
+                context,
+                last_signaled: Cell::new(0),
+                sender,
+            })
+        }
+
+        /// Enqueue a signal after all work submitted so far on the immediate
+        /// context and return its value.
+        pub(crate) fn signal(&self) -> windows::core::Result<u64> {
+            let value = self.last_signaled.get() + 1;
+            unsafe { self.context.Signal(&self.fence, value) }?;
+            self.last_signaled.set(value);
+            Ok(value)
+        }
+
+        pub(crate) fn sender(&self) -> CallbackSender {
+            self.sender.clone()
+        }
+    }
+
+    /// Run each callback once the fence reaches its value, in the order they
+    /// were attached. A callback attached to older work after one on newer
+    /// work waits for the newer work too. Exits when the context and every
+    /// `PendingWork` from it are gone.
+    fn run(fence: SendFence, receiver: Receiver<(u64, Callback)>) {
+        let fence = fence.0;
+        let event = unsafe { CreateEventW(None, false, false, PCWSTR::null()) }.ok();
+        for (value, callback) in receiver {
+            while unsafe { fence.GetCompletedValue() } < value {
+                match event {
+                    Some(event) if wait_event(&fence, value, event) => {}
+                    _ => std::thread::sleep(Duration::from_millis(1)),
+                }
+            }
+            callback();
+        }
+        if let Some(event) = event {
+            unsafe {
+                let _ = CloseHandle(event);
+            }
+        }
+    }
+
+    fn wait_event(fence: &ID3D11Fence, value: u64, event: HANDLE) -> bool {
+        if unsafe { fence.SetEventOnCompletion(value, event) }.is_err() {
+            return false;
+        }
+        unsafe { WaitForSingleObject(event, INFINITE) };
+        true
+    }
+}
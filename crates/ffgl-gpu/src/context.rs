@@ -3,6 +3,10 @@
 //! Created lazily on first draw. On macOS this holds a [`MetalDevice`] and the
 //! compiled Metal shader library. On Windows it holds a [`Dx11Device`] (shaders
 //! are loaded individually per-pipeline from bytecode).
+//!
+//! A DX11 context can optionally record each frame's work into a deferred
+//! context and execute the resulting command list once, see
+//! [`GpuContext::with_deferred_recording`].
 
 use anyhow::Result;
 
@@ -12,6 +16,8 @@ use objc2::rc::Retained;
 use objc2::runtime::ProtocolObject;
 #[cfg(target_os = "macos")]
 use objc2_metal::MTLLibrary;
+#[cfg(target_os = "windows")]
+use windows::Win32::Graphics::Direct3D11::{ID3D11Buffer, ID3D11CommandList, ID3D11DeviceContext};
 
 /// GPU context wrapping platform-specific device + loaded shader library.
 ///
@@ -23,9 +29,66 @@ pub struct GpuContext {
     pub(crate) device: gpu_interop::metal::MetalDevice,
     #[cfg(target_os = "macos")]
     pub(crate) library: Retained<ProtocolObject<dyn MTLLibrary>>,
+    /// Staging buffers for uniform payloads larger than `setBytes` allows.
+    #[cfg(target_os = "macos")]
+    pub(crate) staging: crate::buffer::StagingPool,
+    /// Heap-backed pool for `acquire_texture`.
+    #[cfg(target_os = "macos")]
+    pub(crate) textures: crate::heap::TexturePool,
 
     #[cfg(target_os = "windows")]
     pub(crate) device: gpu_interop::dx11::Dx11Device,
+    /// Deferred context that frame work is recorded into, when enabled.
+    #[cfg(target_os = "windows")]
+    pub(crate) deferred: Option<ID3D11DeviceContext>,
+    /// Whether dispatches currently go to `deferred`.
+    #[cfg(target_os = "windows")]
+    pub(crate) recording: std::cell::Cell<bool>,
+    /// Grid size for kernels with a bounds guard, created on first use.
+    #[cfg(target_os = "windows")]
+    pub(crate) grid_cbuf: std::cell::OnceCell<Option<ID3D11Buffer>>,
+    /// Constant buffers for the uniforms of [`Passes`](crate::passes::Passes)
+    /// chains, with their sizes: the shared block first, then one per named
+    /// uniform. Grown when a chain needs more.
+    #[cfg(target_os = "windows")]
+    pub(crate) pass_cbufs: std::cell::RefCell<Vec<(ID3D11Buffer, usize)>>,
+    /// Fence and callback thread for [`submit`](Self::submit), created on
+    /// first use. `None` without D3D11.4 fences.
+    #[cfg(target_os = "windows")]
+    pub(crate) fence: std::cell::OnceCell<Option<crate::completion::FenceThread>>,
+
+    /// Fullscreen blit pipeline, created on first `blit_texture` call.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) blit: std::cell::OnceCell<crate::blit::BlitPipeline>,
+
+    /// Color conversion pass, created on first use by the draw loop.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) color: std::cell::OnceCell<crate::color::ColorPipeline>,
+
+    /// Built-in filter kernels, created on first use.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) filters: std::cell::OnceCell<crate::filters::FilterPipeline>,
+
+    /// Chroma key pass, created on first use.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) keying: std::cell::OnceCell<crate::keying::KeyingPipeline>,
+
+    /// Optical flow kernels, created on first use.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) flow: std::cell::OnceCell<crate::flow::FlowPipeline>,
+
+    /// Text render pipeline, created on first use.
+    #[cfg(all(feature = "text", any(target_os = "macos", target_os = "windows")))]
+    pub(crate) text: std::cell::OnceCell<crate::text::TextPipeline>,
+
+    /// Textures handed out by [`DrawInput::intermediate`](crate::DrawInput::intermediate).
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) intermediates: crate::intermediate::NamedTextures,
+
+    /// Memory held by what the context handed out, for
+    /// [`memory_report`](Self::memory_report).
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) memory: crate::memory::MemoryCounters,
 }
 
 impl GpuContext {
@@ -47,7 +110,21 @@ impl GpuContext {
             .newLibraryWithData_error(&data)
             .map_err(|e| anyhow::anyhow!("Failed to load Metal library: {e}"))?;
 
-        Ok(Self { device, library })
+        Ok(Self {
+            device,
+            library,
+            staging: Default::default(),
+            textures: Default::default(),
+            blit: Default::default(),
+            color: Default::default(),
+            filters: Default::default(),
+            keying: Default::default(),
+            flow: Default::default(),
+            #[cfg(feature = "text")]
+            text: Default::default(),
+            intermediates: Default::default(),
+            memory: Default::default(),
+        })
     }
 
     /// Create a DX11 GPU context.
@@ -58,7 +135,67 @@ impl GpuContext {
     pub fn new() -> Result<Self> {
         let device = gpu_interop::dx11::Dx11Device::new()
             .ok_or_else(|| anyhow::anyhow!("Failed to create D3D11 device"))?;
-        Ok(Self { device })
+        Ok(Self {
+            device,
+            deferred: None,
+            recording: Default::default(),
+            grid_cbuf: Default::default(),
+            pass_cbufs: Default::default(),
+            fence: Default::default(),
+            blit: Default::default(),
+            color: Default::default(),
+            filters: Default::default(),
+            keying: Default::default(),
+            flow: Default::default(),
+            #[cfg(feature = "text")]
+            text: Default::default(),
+            intermediates: Default::default(),
+            memory: Default::default(),
+        })
+    }
+
+    /// Create a DX11 GPU context that records each frame's passes into a
+    /// deferred context and submits them as one command list.
+    ///
+    /// While a frame is being recorded, [`dispatch_compute`](Self::dispatch_compute),
+    /// [`dispatch_render`](Self::dispatch_render) and the other `GpuContext`
+    /// methods encode onto the deferred context; the framework executes the
+    /// command list on the immediate context before signalling the bridge.
+    /// Plugins issuing their own D3D11 calls should use
+    /// [`command_context`](Self::command_context) so they land in order.
+    ///
+    /// The device is created without `D3D11_CREATE_DEVICE_SINGLETHREADED`,
+    /// which deferred contexts require.
+    #[cfg(target_os = "windows")]
+    pub fn with_deferred_recording() -> Result<Self> {
+        let device = gpu_interop::dx11::Dx11Device::new_with_deferred_contexts()
+            .ok_or_else(|| anyhow::anyhow!("Failed to create D3D11 device"))?;
+        let mut deferred = None;
+        unsafe {
+            device
+                .device()
+                .CreateDeferredContext(0, Some(&mut deferred as *mut _))
+        }
+        .map_err(|e| anyhow::anyhow!("Failed to create D3D11 deferred context: {e}"))?;
+        let deferred =
+            deferred.ok_or_else(|| anyhow::anyhow!("D3D11 CreateDeferredContext returned null"))?;
+        Ok(Self {
+            device,
+            deferred: Some(deferred),
+            recording: Default::default(),
+            grid_cbuf: Default::default(),
+            pass_cbufs: Default::default(),
+            fence: Default::default(),
+            blit: Default::default(),
+            color: Default::default(),
+            filters: Default::default(),
+            keying: Default::default(),
+            flow: Default::default(),
+            #[cfg(feature = "text")]
+            text: Default::default(),
+            intermediates: Default::default(),
+            memory: Default::default(),
+        })
     }
 
     /// Borrow the underlying Metal device (macOS).
@@ -73,9 +210,138 @@ impl GpuContext {
         &self.library
     }
 
+    /// Names of the kernel and shader functions plugins can create
+    /// pipelines from by name, sorted.
+    ///
+    /// On macOS these are the functions in the loaded Metal library. D3D11
+    /// has no shader library, each pipeline brings its own bytecode or
+    /// source, so on Windows the list is empty.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub fn list_entry_points(&self) -> Vec<String> {
+        #[cfg(target_os = "macos")]
+        return crate::pipeline::function_names(&self.library);
+        #[cfg(target_os = "windows")]
+        return Vec::new();
+    }
+
     /// Borrow the underlying DX11 device (Windows).
     #[cfg(target_os = "windows")]
     pub fn dx11_device(&self) -> &gpu_interop::dx11::Dx11Device {
         &self.device
     }
+
+    /// The context GPU work should be issued on right now: the deferred
+    /// context while a frame is being recorded, the immediate context
+    /// otherwise.
+    #[cfg(target_os = "windows")]
+    pub fn command_context(&self) -> &ID3D11DeviceContext {
+        match &self.deferred {
+            Some(deferred) if self.recording.get() => deferred,
+            _ => self.device.context(),
+        }
+    }
+
+    /// Start recording frame work into the deferred context. No-op without
+    /// deferred recording. Work left over from a frame that never reached
+    /// [`end_recording`](Self::end_recording) is discarded.
+    #[cfg(target_os = "windows")]
+    pub(crate) fn begin_recording(&self) {
+        let Some(deferred) = &self.deferred else {
+            return;
+        };
+        if self.recording.get() {
+            let _ = unsafe { deferred.FinishCommandList(false, None) };
+        }
+        self.recording.set(true);
+    }
+
+    /// Execute everything recorded so far on the immediate context and keep
+    /// recording. Called before operations that must run on the immediate
+    /// context, such as CPU readbacks.
+    #[cfg(target_os = "windows")]
+    pub(crate) fn flush_recording(&self) {
+        let Some(deferred) = &self.deferred else {
+            return;
+        };
+        if !self.recording.get() {
+            return;
+        }
+        let mut list: Option<ID3D11CommandList> = None;
+        match unsafe { deferred.FinishCommandList(false, Some(&mut list as *mut _)) } {
+            Ok(()) => {
+                if let Some(list) = list {
+                    unsafe { self.device.context().ExecuteCommandList(&list, true) };
+                }
+            }
+            Err(e) => gpu_interop::error_limited!("Failed to finish D3D11 command list: {e}"),
+        }
+    }
+
+    /// Execute the recorded frame on the immediate context and stop
+    /// recording.
+    #[cfg(target_os = "windows")]
+    pub(crate) fn end_recording(&self) {
+        self.flush_recording();
+        self.recording.set(false);
+    }
+
+    /// Return a [`PendingWork`](crate::PendingWork) token for the work issued
+    /// through this context so far, e.g. to attach
+    /// [`on_completed`](crate::PendingWork::on_completed) callbacks.
+    ///
+    /// Executes anything recorded on the deferred context first. Without
+    /// D3D11.4 fences this blocks until the work completes.
+    #[cfg(target_os = "windows")]
+    pub fn submit(&self) -> crate::PendingWork {
+        self.flush_recording();
+        let fence = self.fence.get_or_init(|| {
+            let fence = crate::completion::FenceThread::new(&self.device);
+            if fence.is_none() {
+                tracing::debug!("D3D11.4 fences unavailable, submit will block");
+            }
+            fence
+        });
+        if let Some(fence) = fence {
+            match fence.signal() {
+                Ok(value) => {
+                    unsafe { self.device.context().Flush() };
+                    return crate::PendingWork {
+                        fence: Some((value, fence.sender())),
+                        joined: Vec::new(),
+                    };
+                }
+                Err(e) => gpu_interop::warn_limited!("Failed to signal D3D11 fence: {e}"),
+            }
+        }
+        self.wait_idle();
+        crate::PendingWork {
+            fence: None,
+            joined: Vec::new(),
+        }
+    }
+
+    /// Block on the device's event query until the immediate context is
+    /// idle.
+    #[cfg(target_os = "windows")]
+    fn wait_idle(&self) {
+        let context = self.device.context();
+        let query = self.device.query();
+        unsafe { context.End(query) };
+        loop {
+            let mut done: u32 = 0;
+            let polled = unsafe {
+                context.GetData(
+                    query,
+                    Some(&mut done as *mut u32 as *mut _),
+                    std::mem::size_of::<u32>() as u32,
+                    0,
+                )
+            };
+            // A removed device never completes the query.
+            if polled.is_err() || done != 0 {
+                return;
+            }
+            std::thread::yield_now();
+        }
+    }
 }
@@ -0,0 +1,204 @@
+//! Which GPU the host's GL context and the compute device run on.
+//!
+//! The bridges share textures between the two APIs, which only works when
+//! both are on the same physical adapter. On laptops with switchable
+//! graphics the host can end up on the discrete GPU while the default Metal
+//! or D3D11 device is the integrated one (or the reverse). Interop then
+//! fails with errors that say nothing about adapters, or produces black
+//! frames. The draw loop compares [`DeviceInfo::current_gl`] with
+//! [`GpuContext::device_info`](crate::GpuContext::device_info) when it
+//! creates the context and logs a warning if they don't match.
+
+use std::ffi::CStr;
+use std::fmt;
+
+/// Vendor, renderer and driver strings for one side of the bridge.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DeviceInfo {
+    /// Hardware vendor, e.g. `NVIDIA Corporation`.
+    pub vendor: String,
+    /// Adapter name, e.g. `NVIDIA GeForce RTX 3080/PCIe/SSE2`.
+    pub renderer: String,
+    /// Driver version, where the API reports one.
+    pub driver: Option<String>,
+}
+
+impl DeviceInfo {
+    /// `GL_VENDOR`, `GL_RENDERER` and `GL_VERSION` of the current context.
+    ///
+    /// # Safety
+    ///
+    /// A GL context must be current and the `gl` function pointers loaded.
+    pub unsafe fn current_gl() -> Self {
+        Self {
+            vendor: gl_string(gl::VENDOR).unwrap_or_default(),
+            renderer: gl_string(gl::RENDERER).unwrap_or_default(),
+            driver: gl_string(gl::VERSION),
+        }
+    }
+
+    /// Whether `self` and `other` look like the same physical adapter.
+    ///
+    /// The APIs name adapters differently (GL appends bus and instruction
+    /// set suffixes, vendors spell themselves several ways), so this matches
+    /// the vendor family and then checks that one adapter name contains the
+    /// other. Returns `true` when either side is too vague to tell.
+    pub fn same_adapter(&self, other: &Self) -> bool {
+        let vendors = (self.vendor_family(), other.vendor_family());
+        if let (Some(a), Some(b)) = vendors {
+            if a != b {
+                return false;
+            }
+        }
+        let (a, b) = (self.adapter_name(), other.adapter_name());
+        a.is_empty() || b.is_empty() || a.contains(&b) || b.contains(&a)
+    }
+
+    /// Vendor family, from the vendor string or else the adapter name.
+    fn vendor_family(&self) -> Option<&'static str> {
+        vendor_family(&self.vendor).or_else(|| vendor_family(&self.renderer))
+    }
+
+    /// The renderer lowercased, without GL's `/PCIe/SSE2` suffixes,
+    /// trademark marks or punctuation.
+    fn adapter_name(&self) -> String {
+        let name = self.renderer.split('/').next().unwrap_or_default();
+        name.to_lowercase()
+            .replace("(r)", "")
+            .replace("(tm)", "")
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect()
+    }
+}
+
+impl fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.renderer, self.vendor)?;
+        if let Some(driver) = &self.driver {
+            write!(f, ", driver {driver}")?;
+        }
+        Ok(())
+    }
+}
+
+fn vendor_family(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    let has_word = |word: &str| {
+        name.split(|c: char| !c.is_alphanumeric())
+            .any(|w| w == word)
+    };
+    if name.contains("nvidia") {
+        Some("nvidia")
+    } else if has_word("amd") || has_word("ati") || name.contains("radeon") {
+        Some("amd")
+    } else if name.contains("intel") {
+        Some("intel")
+    } else if has_word("apple") {
+        Some("apple")
+    } else if name.contains("qualcomm") || name.contains("adreno") {
+        Some("qualcomm")
+    } else if name.contains("microsoft") {
+        Some("microsoft")
+    } else {
+        None
+    }
+}
+
+unsafe fn gl_string(name: gl::types::GLenum) -> Option<String> {
+    let value = gl::GetString(name);
+    (!value.is_null()).then(|| {
+        CStr::from_ptr(value as *const _)
+            .to_string_lossy()
+            .into_owned()
+    })
+}
+
+/// Warn if the compute device isn't the adapter the current GL context runs
+/// on. Called once per context, when the draw loop creates it.
+///
+/// # Safety
+///
+/// A GL context must be current.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) unsafe fn check_same_adapter(ctx: &crate::GpuContext) {
+    let gl = DeviceInfo::current_gl();
+    let device = ctx.device_info();
+    tracing::debug!(gl = %gl, device = %device, "GPU adapters");
+    if !gl.same_adapter(&device) {
+        tracing::warn!(
+            "GL context and compute device are on different GPUs: GL is on {gl}, \
+             the compute device is {device}. Texture sharing will likely fail or \
+             produce black frames; run the host on the same GPU, e.g. through \
+             the OS graphics settings."
+        );
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl crate::GpuContext {
+    /// Vendor, adapter name and driver version of the compute device.
+    ///
+    /// Metal reports no vendor or driver version, so the vendor is derived
+    /// from the adapter name and `driver` is `None`.
+    #[cfg(target_os = "macos")]
+    pub fn device_info(&self) -> DeviceInfo {
+        use objc2_metal::MTLDevice;
+
+        let renderer = self.device.device().name().to_string();
+        DeviceInfo {
+            vendor: vendor_family(&renderer).unwrap_or_default().to_owned(),
+            renderer,
+            driver: None,
+        }
+    }
+
+    /// Vendor, adapter name and driver version of the compute device, from
+    /// its DXGI adapter.
+    #[cfg(target_os = "windows")]
+    pub fn device_info(&self) -> DeviceInfo {
+        use windows::core::Interface;
+        use windows::Win32::Graphics::Dxgi::{IDXGIAdapter, IDXGIDevice};
+
+        let adapter: windows::core::Result<IDXGIAdapter> = self
+            .device
+            .device()
+            .cast::<IDXGIDevice>()
+            .and_then(|device| unsafe { device.GetAdapter() });
+        let Ok(adapter) = adapter else {
+            return DeviceInfo::default();
+        };
+        let Ok(desc) = (unsafe { adapter.GetDesc() }) else {
+            return DeviceInfo::default();
+        };
+        let name = &desc.Description;
+        let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+        let renderer = String::from_utf16_lossy(&name[..len]);
+        let vendor = match desc.VendorId {
+            0x10de => "NVIDIA".to_owned(),
+            0x1002 | 0x1022 => "AMD".to_owned(),
+            0x8086 => "Intel".to_owned(),
+            0x1414 => "Microsoft".to_owned(),
+            0x5143 => "Qualcomm".to_owned(),
+            id => format!("{id:#06x}"),
+        };
+        // The user-mode driver version, packed as four 16-bit parts.
+        let driver = unsafe { adapter.CheckInterfaceSupport(&IDXGIDevice::IID) }
+            .ok()
+            .map(|v| {
+                let v = v as u64;
+                format!(
+                    "{}.{}.{}.{}",
+                    v >> 48,
+                    (v >> 32) & 0xffff,
+                    (v >> 16) & 0xffff,
+                    v & 0xffff
+                )
+            });
+        DeviceInfo {
+            vendor,
+            renderer,
+            driver,
+        }
+    }
+}
@@ -26,22 +26,200 @@ pub enum Binding<'a> {
     UniformData(&'a [u8]),
 }
 
+// ---------------------------------------------------------------------------
+// DX11 resource bindings — a GpuTexture, a GpuBuffer or a view
+// ---------------------------------------------------------------------------
+
+/// A resource a DX11 dispatch reads, at one SRV slot. A list of them binds
+/// each entry at its index, from `t0`.
+///
+/// A [`GpuTexture`](crate::GpuTexture) or a structured
+/// [`GpuBuffer`](crate::GpuBuffer) is bound through its own view; views made
+/// elsewhere, such as [`DrawInput::input_srv`](crate::DrawInput::input_srv),
+/// convert with `into()`, so one list can hold all of them. `View(None)`
+/// skips a register:
+///
+/// ```rust,ignore
+/// let srvs = [
+///     input.input_srv.clone().into(),
+///     SrvBinding::Texture(&blurred),
+///     SrvBinding::View(None),
+///     SrvBinding::Buffer(&particles), // StructuredBuffer at t3
+/// ];
+/// ```
+#[cfg(target_os = "windows")]
+#[derive(Clone)]
+pub enum SrvBinding<'a> {
+    /// A texture created through the context.
+    Texture(&'a crate::GpuTexture),
+    /// A structured buffer, read as a `StructuredBuffer`.
+    Buffer(&'a GpuBuffer),
+    /// A shader resource view, or `None` for an empty slot.
+    View(Option<windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView>),
+}
+
+#[cfg(target_os = "windows")]
+impl SrvBinding<'_> {
+    pub(crate) fn view(
+        &self,
+    ) -> Option<windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView> {
+        match self {
+            SrvBinding::Texture(t) => t.srv(),
+            SrvBinding::Buffer(b) => Some(b.dx11_srv.clone()),
+            SrvBinding::View(view) => view.clone(),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl<'a> From<&'a crate::GpuTexture> for SrvBinding<'a> {
+    fn from(texture: &'a crate::GpuTexture) -> Self {
+        SrvBinding::Texture(texture)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl<'a> From<&'a GpuBuffer> for SrvBinding<'a> {
+    fn from(buffer: &'a GpuBuffer) -> Self {
+        SrvBinding::Buffer(buffer)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl From<windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView> for SrvBinding<'_> {
+    fn from(view: windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView) -> Self {
+        SrvBinding::View(Some(view))
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl From<Option<windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView>>
+    for SrvBinding<'_>
+{
+    fn from(view: Option<windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView>) -> Self {
+        SrvBinding::View(view)
+    }
+}
+
+/// A resource a DX11 compute dispatch writes, at one UAV slot from `u0`.
+/// Like [`SrvBinding`], for the views in
+/// [`DrawInput::output_uav`](crate::DrawInput::output_uav) and the like.
+#[cfg(target_os = "windows")]
+#[derive(Clone)]
+pub enum UavBinding<'a> {
+    /// A texture created through the context.
+    Texture(&'a crate::GpuTexture),
+    /// A structured buffer, written as a `RWStructuredBuffer`.
+    Buffer(&'a GpuBuffer),
+    /// An unordered access view, or `None` for an empty slot.
+    View(Option<windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView>),
+}
+
+#[cfg(target_os = "windows")]
+impl UavBinding<'_> {
+    pub(crate) fn view(
+        &self,
+    ) -> Option<windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView> {
+        match self {
+            UavBinding::Texture(t) => t.uav(),
+            UavBinding::Buffer(b) => Some(b.dx11_uav.clone()),
+            UavBinding::View(view) => view.clone(),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl<'a> From<&'a crate::GpuTexture> for UavBinding<'a> {
+    fn from(texture: &'a crate::GpuTexture) -> Self {
+        UavBinding::Texture(texture)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl<'a> From<&'a GpuBuffer> for UavBinding<'a> {
+    fn from(buffer: &'a GpuBuffer) -> Self {
+        UavBinding::Buffer(buffer)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl From<windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView> for UavBinding<'_> {
+    fn from(view: windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView) -> Self {
+        UavBinding::View(Some(view))
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl From<Option<windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView>>
+    for UavBinding<'_>
+{
+    fn from(view: Option<windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView>) -> Self {
+        UavBinding::View(view)
+    }
+}
+
+/// The texture a DX11 render dispatch draws into: a
+/// [`GpuTexture`](crate::GpuTexture), drawn through its own render target
+/// view, or any `ID3D11Texture2D` such as
+/// [`DrawInput::output_texture`](crate::DrawInput::output_texture).
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy)]
+pub enum RenderTarget<'a> {
+    /// A texture created through the context.
+    Texture(&'a crate::GpuTexture),
+    /// A texture created elsewhere; a view is created for the draw.
+    Texture2D(&'a windows::Win32::Graphics::Direct3D11::ID3D11Texture2D),
+}
+
+#[cfg(target_os = "windows")]
+impl<'a> From<&'a crate::GpuTexture> for RenderTarget<'a> {
+    fn from(texture: &'a crate::GpuTexture) -> Self {
+        RenderTarget::Texture(texture)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl<'a> From<&'a windows::Win32::Graphics::Direct3D11::ID3D11Texture2D> for RenderTarget<'a> {
+    fn from(texture: &'a windows::Win32::Graphics::Direct3D11::ID3D11Texture2D) -> Self {
+        RenderTarget::Texture2D(texture)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Compute pass — in-progress compute encoding
 // ---------------------------------------------------------------------------
 
 /// A command buffer for encoding multiple passes.
 ///
-/// On macOS this wraps a `MTLCommandBuffer`. Create one with
-/// [`GpuContext::create_command_buffer`], encode compute and render passes
-/// on it, then call [`GpuContext::commit`] to submit all work at once.
+/// Create one with [`GpuContext::create_command_buffer`], encode compute and
+/// render passes on it, then call [`GpuContext::commit`] to submit all work
+/// at once.
+///
+/// On macOS this wraps a `MTLCommandBuffer`. Metal automatically serialises
+/// encoders on the same command buffer, so there is no need for mid-frame
+/// `PendingWork::wait()` calls between passes.
+///
+/// On Windows this wraps a deferred `ID3D11DeviceContext` of its own.
+/// Passes are recorded into it as they are encoded, and `commit` turns them
+/// into a command list and executes it on the immediate context, after
+/// anything the [`GpuContext`] issued before. Constant buffers are read when
+/// the list executes, so update one that changes between passes with
+/// [`encode_update_constant_buffer`](GpuContext::encode_update_constant_buffer).
 ///
-/// Metal automatically serialises encoders on the same command buffer, so
-/// there is no need for mid-frame `PendingWork::wait()` calls between passes.
+/// Deferred contexts need a device created without
+/// `D3D11_CREATE_DEVICE_SINGLETHREADED`, i.e. a context made with
+/// [`with_deferred_recording`](GpuContext::with_deferred_recording). On a
+/// single-threaded device there is no deferred context, and passes are
+/// issued on [`command_context`](GpuContext::command_context) as they are
+/// encoded.
 pub struct CommandBuffer {
     #[cfg(target_os = "macos")]
     pub(crate) inner:
         objc2::rc::Retained<objc2::runtime::ProtocolObject<dyn objc2_metal::MTLCommandBuffer>>,
+    /// Deferred context the passes are recorded into, `None` on a
+    /// single-threaded device.
+    #[cfg(target_os = "windows")]
+    pub(crate) context: Option<windows::Win32::Graphics::Direct3D11::ID3D11DeviceContext>,
 }
 
 /// A token representing GPU work that has been submitted but may not yet be
@@ -50,20 +228,90 @@ pub struct PendingWork {
     #[cfg(target_os = "macos")]
     pub(crate) command_buffer:
         objc2::rc::Retained<objc2::runtime::ProtocolObject<dyn objc2_metal::MTLCommandBuffer>>,
+    #[cfg(target_os = "macos")]
+    completion: std::sync::Arc<crate::completion::Completion>,
+
+    /// Fence value marking the work, and the channel to its fence thread.
+    /// `None` once the work is known to be complete.
+    #[cfg(target_os = "windows")]
+    pub(crate) fence: Option<(u64, crate::completion::CallbackSender)>,
+
+    /// Work merged in with [`join`](Self::join).
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) joined: Vec<PendingWork>,
 }
 
 #[cfg(target_os = "macos")]
 impl PendingWork {
-    /// Block until the GPU work completes.
+    /// Commit `command_buffer`, with a completed handler for
+    /// [`on_completed`](Self::on_completed) callbacks.
+    pub(crate) fn commit(
+        command_buffer: objc2::rc::Retained<
+            objc2::runtime::ProtocolObject<dyn objc2_metal::MTLCommandBuffer>,
+        >,
+    ) -> Self {
+        use block2::RcBlock;
+        use objc2_metal::MTLCommandBuffer;
+
+        let completion = std::sync::Arc::new(crate::completion::Completion::default());
+        let handler = completion.clone();
+        let block = RcBlock::new(
+            move |_: std::ptr::NonNull<objc2::runtime::ProtocolObject<dyn MTLCommandBuffer>>| {
+                handler.finish()
+            },
+        );
+        // Metal copies the block; handlers can only be added before commit.
+        unsafe { command_buffer.addCompletedHandler(RcBlock::as_ptr(&block)) };
+        command_buffer.commit();
+        Self {
+            command_buffer,
+            completion,
+            joined: Vec::new(),
+        }
+    }
+
+    /// Block until the GPU work, including any joined work, completes.
     pub fn wait(&self) {
         use objc2_metal::MTLCommandBuffer;
         self.command_buffer.waitUntilCompleted();
+        for work in &self.joined {
+            work.wait();
+        }
+    }
+
+    fn on_own_completed(&self, callback: crate::completion::Callback) {
+        self.completion.add(callback);
+    }
+
+    /// Encode follow-up work on a new command buffer and commit it after
+    /// this work, e.g. a blit combining the results of several
+    /// [`join`](Self::join)ed command buffers.
+    ///
+    /// Metal runs a queue's command buffers in commit order, so the
+    /// follow-up sees this work's results without a CPU wait. This only
+    /// holds for work committed through the same `ctx`. The returned token
+    /// covers both.
+    pub fn then_dispatch(
+        self,
+        ctx: &GpuContext,
+        encode: impl FnOnce(&CommandBuffer) -> Result<()>,
+    ) -> Result<PendingWork> {
+        let cb = ctx.create_command_buffer()?;
+        encode(&cb)?;
+        Ok(ctx.commit(cb).join([self]))
     }
 
     /// Consume this token and return the underlying Metal command buffer.
     ///
     /// Useful for storing in a [`GlMetalBridge`](gpu_interop::metal::GlMetalBridge)
-    /// for pipelined synchronization.
+    /// for pipelined synchronization. Callbacks attached with
+    /// [`on_completed`](Self::on_completed) still run.
+    ///
+    /// For a [`join`](Self::join)ed token this is the command buffer of the
+    /// token `join` was called on. Call it on the last one committed, as
+    /// [`then_dispatch`](Self::then_dispatch) does: Metal completes a
+    /// queue's command buffers in commit order, so that one finishing means
+    /// all of them have.
     pub fn into_command_buffer(
         self,
     ) -> objc2::rc::Retained<objc2::runtime::ProtocolObject<dyn objc2_metal::MTLCommandBuffer>>
@@ -72,15 +320,105 @@ impl PendingWork {
     }
 }
 
+#[cfg(target_os = "windows")]
+impl PendingWork {
+    /// Block until the GPU work, including any joined work, completes.
+    pub fn wait(&self) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        self.on_completed(move || {
+            let _ = sender.send(());
+        });
+        let _ = receiver.recv();
+    }
+
+    fn on_own_completed(&self, callback: crate::completion::Callback) {
+        match &self.fence {
+            Some((value, sender)) => {
+                if let Err(std::sync::mpsc::SendError((_, callback))) =
+                    sender.send((*value, callback))
+                {
+                    callback();
+                }
+            }
+            None => callback(),
+        }
+    }
+
+    /// Issue follow-up work with `dispatch` and [`submit`](GpuContext::submit)
+    /// it, e.g. a copy combining the results of several
+    /// [`join`](Self::join)ed submissions.
+    ///
+    /// The immediate context runs commands in the order they were issued, so
+    /// the follow-up sees this work's results without a CPU wait. This only
+    /// holds for work issued through the same `ctx`. The returned token
+    /// covers both.
+    pub fn then_dispatch(
+        self,
+        ctx: &GpuContext,
+        dispatch: impl FnOnce(&GpuContext) -> Result<()>,
+    ) -> Result<PendingWork> {
+        dispatch(ctx)?;
+        Ok(ctx.submit().join([self]))
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl PendingWork {
+    /// Run `callback` once the GPU work, including any joined work,
+    /// completes, without blocking, e.g. to recycle a buffer, record a
+    /// timing or start a readback. Runs it right away if the work has
+    /// already completed.
+    ///
+    /// The callback may run on a Metal completion thread or the context's
+    /// fence thread, so it must not touch GL or the [`GpuContext`].
+    pub fn on_completed(&self, callback: impl FnOnce() + Send + 'static) {
+        if self.joined.is_empty() {
+            self.on_own_completed(Box::new(callback));
+            return;
+        }
+        let done = crate::completion::countdown(1 + self.joined.len(), Box::new(callback));
+        self.on_own_completed(done());
+        for work in &self.joined {
+            work.on_completed(done());
+        }
+    }
+
+    /// Merge `others` into this token, so [`wait`](Self::wait) and
+    /// [`on_completed`](Self::on_completed) cover all of it, e.g. after
+    /// fanning work out over several command buffers:
+    ///
+    /// ```rust,ignore
+    /// let all = last.join([first, second]);
+    /// all.on_completed(move || recycle(buffers));
+    /// ```
+    pub fn join(mut self, others: impl IntoIterator<Item = PendingWork>) -> PendingWork {
+        self.joined.extend(others);
+        self
+    }
+}
+
 // ---------------------------------------------------------------------------
 // macOS Metal implementation
 // ---------------------------------------------------------------------------
 
+#[cfg(target_os = "macos")]
+pub(crate) use metal_impl::intermediate_texture_descriptor;
+
 #[cfg(target_os = "macos")]
 mod metal_impl {
     use super::*;
+    use crate::argument_buffer::ArgumentBuffer;
+    use crate::budget::Allocation;
+    use crate::buffer::MAX_INLINE_BYTES;
+    use crate::memory::Tracked;
+    use crate::intermediate::IntermediateFormat;
+    use crate::pipeline::{
+        check_uniform_size, function_names, missing_function, ShaderSource, UniformSlot,
+    };
+    use crate::texture::GpuTexture;
+    use objc2::rc::Retained;
     use objc2::runtime::ProtocolObject;
-    use objc2_foundation::NSString;
+    use objc2_foundation::{NSArray, NSString};
     use objc2_metal::*;
 
     /// Fullscreen quad vertex data: 4 vertices, each with (x, y, u, v).
@@ -92,11 +430,94 @@ mod metal_impl {
         [1.0, 1.0, 1.0, 0.0],   // top-right
     ];
 
+    /// Pipeline options requesting binding reflection, so uniforms can be
+    /// bound by name and their sizes validated at dispatch time.
+    fn reflection_options() -> MTLPipelineOption {
+        MTLPipelineOption::BindingInfo | MTLPipelineOption::BufferTypeInfo
+    }
+
+    /// Collect the used buffer bindings of a reflected pipeline stage.
+    fn reflect_uniforms(bindings: &NSArray<ProtocolObject<dyn MTLBinding>>) -> Vec<UniformSlot> {
+        bindings
+            .iter()
+            .filter(|binding| binding.r#type() == MTLBindingType::Buffer && binding.isUsed())
+            .map(|binding| {
+                // SAFETY: bindings of type `Buffer` conform to `MTLBufferBinding`.
+                let buffer = unsafe {
+                    &*(&*binding as *const ProtocolObject<dyn MTLBinding>
+                        as *const ProtocolObject<dyn MTLBufferBinding>)
+                };
+                UniformSlot {
+                    index: binding.index(),
+                    size: buffer.bufferDataSize(),
+                    alignment: buffer.bufferAlignment().max(1),
+                    name: binding.name().to_string(),
+                }
+            })
+            .collect()
+    }
+
+    /// Bind uniform `data` at `index`, inline via `set_bytes` when it fits
+    /// within Metal's 4 KB `setBytes` limit, otherwise staged through a pooled
+    /// shared buffer owned by `command_buffer` and bound via `set_buffer`.
+    fn bind_bytes(
+        ctx: &GpuContext,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        data: &[u8],
+        index: usize,
+        set_bytes: impl FnOnce(std::ptr::NonNull<std::ffi::c_void>, usize, usize),
+        set_buffer: impl FnOnce(&ProtocolObject<dyn MTLBuffer>, usize),
+    ) {
+        if data.len() <= MAX_INLINE_BYTES {
+            // SAFETY: `data` is a live slice; Metal copies it during the call.
+            let ptr = unsafe { std::ptr::NonNull::new_unchecked(data.as_ptr() as *mut _) };
+            set_bytes(ptr, data.len(), index);
+            return;
+        }
+
+        match ctx
+            .staging
+            .stage(ctx.device.device(), command_buffer, data)
+        {
+            Some(buffer) => set_buffer(&buffer, index),
+            None => gpu_interop::error_limited!(
+                "Failed to stage {} bytes of uniform data for index {index}",
+                data.len()
+            ),
+        }
+    }
+
+    /// Descriptor for an intermediate texture in private storage, readable,
+    /// writable by compute kernels, and renderable.
+    pub(crate) fn intermediate_texture_descriptor(
+        width: u32,
+        height: u32,
+        format: IntermediateFormat,
+    ) -> Retained<MTLTextureDescriptor> {
+        let desc = MTLTextureDescriptor::new();
+        desc.setTextureType(MTLTextureType::Type2D);
+        desc.setPixelFormat(format.metal());
+        unsafe {
+            desc.setWidth(width as usize);
+            desc.setHeight(height as usize);
+        }
+        desc.setStorageMode(MTLStorageMode::Private);
+        desc.setUsage(
+            MTLTextureUsage::ShaderRead
+                | MTLTextureUsage::ShaderWrite
+                | MTLTextureUsage::RenderTarget,
+        );
+        desc
+    }
+
     /// Encode a compute dispatch onto `encoder`: set pipeline, bind resources,
     /// dispatch threads, and end the encoder.
     fn encode_compute_inner(
+        ctx: &GpuContext,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
         encoder: &ProtocolObject<dyn MTLComputeCommandEncoder>,
         pipeline: &ComputePipeline,
+        arguments: Option<&ArgumentBuffer>,
         textures: &[&ProtocolObject<dyn MTLTexture>],
         buffers: &[(&GpuBuffer, usize)],
         bytes: &[(&[u8], usize)],
@@ -105,6 +526,11 @@ mod metal_impl {
     ) {
         encoder.setComputePipelineState(&pipeline.state);
 
+        if let Some(arguments) = arguments {
+            arguments.bind(encoder);
+            ctx.textures.use_heaps(encoder);
+        }
+
         for (i, tex) in textures.iter().enumerate() {
             unsafe {
                 encoder.setTexture_atIndex(Some(*tex), i);
@@ -118,13 +544,15 @@ mod metal_impl {
         }
 
         for (data, idx) in bytes {
-            unsafe {
-                encoder.setBytes_length_atIndex(
-                    std::ptr::NonNull::new_unchecked(data.as_ptr() as *mut _),
-                    data.len(),
-                    *idx,
-                );
-            }
+            check_uniform_size("compute", &pipeline.uniforms, *idx, data.len());
+            bind_bytes(
+                ctx,
+                command_buffer,
+                data,
+                *idx,
+                |ptr, len, i| unsafe { encoder.setBytes_length_atIndex(ptr, len, i) },
+                |buf, i| unsafe { encoder.setBuffer_offset_atIndex(Some(buf), 0, i) },
+            );
         }
 
         let grid_size = MTLSize {
@@ -141,51 +569,235 @@ mod metal_impl {
         encoder.endEncoding();
     }
 
+    /// Encode a fullscreen render pass onto `command_buffer`: set up the
+    /// render target, bind vertex and fragment resources, draw the quad, and
+    /// end the encoder.
+    ///
+    /// Vertex buffer index 0 is reserved for the fullscreen quad, so
+    /// user-supplied vertex buffers and bytes must use index 1 or higher.
+    fn encode_render_inner(
+        ctx: &GpuContext,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        pipeline: &RenderPipeline,
+        output_texture: &ProtocolObject<dyn MTLTexture>,
+        fragment_textures: &[&ProtocolObject<dyn MTLTexture>],
+        fragment_bytes: &[(&[u8], usize)],
+        vertex_textures: &[&ProtocolObject<dyn MTLTexture>],
+        vertex_buffers: &[(&GpuBuffer, usize)],
+        vertex_bytes: &[(&[u8], usize)],
+    ) -> Result<()> {
+        debug_assert!(
+            vertex_buffers.iter().all(|(_, idx)| *idx != 0)
+                && vertex_bytes.iter().all(|(_, idx)| *idx != 0),
+            "vertex buffer index 0 is reserved for the fullscreen quad",
+        );
+
+        let render_desc = MTLRenderPassDescriptor::new();
+        {
+            let attachment = unsafe {
+                render_desc
+                    .colorAttachments()
+                    .objectAtIndexedSubscript(0)
+            };
+            attachment.setTexture(Some(output_texture));
+            attachment.setLoadAction(MTLLoadAction::DontCare);
+            attachment.setStoreAction(MTLStoreAction::Store);
+        }
+
+        let encoder = command_buffer
+            .renderCommandEncoderWithDescriptor(&render_desc)
+            .ok_or_else(|| anyhow::anyhow!("Failed to create render encoder"))?;
+
+        encoder.setRenderPipelineState(&pipeline.state);
+
+        // Bind fullscreen quad vertex buffer at index 0
+        unsafe {
+            encoder.setVertexBuffer_offset_atIndex(Some(&pipeline.quad_vb), 0, 0);
+        }
+
+        // Bind vertex textures, buffers, and constant data
+        for (i, tex) in vertex_textures.iter().enumerate() {
+            unsafe {
+                encoder.setVertexTexture_atIndex(Some(*tex), i);
+            }
+        }
+        for (buf, index) in vertex_buffers {
+            unsafe {
+                encoder.setVertexBuffer_offset_atIndex(Some(&buf.metal), 0, *index);
+            }
+        }
+        for (data, index) in vertex_bytes {
+            check_uniform_size("vertex", &pipeline.vertex_uniforms, *index, data.len());
+            bind_bytes(
+                ctx,
+                command_buffer,
+                data,
+                *index,
+                |ptr, len, i| unsafe { encoder.setVertexBytes_length_atIndex(ptr, len, i) },
+                |buf, i| unsafe { encoder.setVertexBuffer_offset_atIndex(Some(buf), 0, i) },
+            );
+        }
+
+        // Bind fragment textures
+        for (i, tex) in fragment_textures.iter().enumerate() {
+            unsafe {
+                encoder.setFragmentTexture_atIndex(Some(*tex), i);
+            }
+        }
+
+        // Bind fragment constant data
+        for (data, index) in fragment_bytes {
+            check_uniform_size("fragment", &pipeline.fragment_uniforms, *index, data.len());
+            bind_bytes(
+                ctx,
+                command_buffer,
+                data,
+                *index,
+                |ptr, len, i| unsafe { encoder.setFragmentBytes_length_atIndex(ptr, len, i) },
+                |buf, i| unsafe { encoder.setFragmentBuffer_offset_atIndex(Some(buf), 0, i) },
+            );
+        }
+
+        // Draw fullscreen quad as triangle strip (4 vertices)
+        unsafe {
+            encoder.drawPrimitives_vertexStart_vertexCount(MTLPrimitiveType::TriangleStrip, 0, 4);
+        }
+
+        encoder.endEncoding();
+        Ok(())
+    }
+
+    /// Look up `name` in `library`, listing what it does hold if it's missing.
+    fn function_named(
+        library: &ProtocolObject<dyn MTLLibrary>,
+        name: &str,
+        kind: &str,
+    ) -> Result<Retained<ProtocolObject<dyn MTLFunction>>> {
+        library
+            .newFunctionWithName(&NSString::from_str(name))
+            .ok_or_else(|| missing_function(kind, name, &function_names(library)))
+    }
+
     impl GpuContext {
-        /// Create a compute pipeline from a named kernel function in the loaded
-        /// Metal shader library.
-        pub fn create_compute_pipeline(&self, name: &str) -> Result<ComputePipeline> {
-            let func_name = NSString::from_str(name);
-            let function = self
-                .library
-                .newFunctionWithName(&func_name)
-                .ok_or_else(|| anyhow::anyhow!("Metal function '{name}' not found in library"))?;
+        /// Create a compute pipeline from a [`ShaderSource`].
+        ///
+        /// A name (or `&str`) looks the kernel up in the loaded Metal shader
+        /// library, bytecode is a `.metallib` holding a single kernel, and
+        /// source is MSL compiled on the spot.
+        ///
+        /// The pipeline's buffer bindings are reflected, so
+        /// [`Passes`](crate::Passes) can bind uniforms by name; in debug
+        /// builds mismatched uniform sizes are reported at dispatch time.
+        pub fn create_compute_pipeline<'s>(
+            &self,
+            source: impl Into<ShaderSource<'s>>,
+        ) -> Result<ComputePipeline> {
+            let (function, name) = self.kernel_function(source.into())?;
 
-            let state = self
-                .device
-                .device()
-                .newComputePipelineStateWithFunction_error(&function)
-                .map_err(|e| {
-                    anyhow::anyhow!("Failed to create compute pipeline for '{name}': {e}")
-                })?;
+            let mut reflection = None;
+            let state = unsafe {
+                self.device
+                    .device()
+                    .newComputePipelineStateWithFunction_options_reflection_error(
+                        &function,
+                        reflection_options(),
+                        Some(&mut reflection),
+                    )
+            }
+            .map_err(|e| anyhow::anyhow!("Failed to create compute pipeline for '{name}': {e}"))?;
+
+            let uniforms = reflection
+                .map(|r| reflect_uniforms(&r.bindings()))
+                .unwrap_or_default();
+
+            // Apple's recommended shape: one SIMD group wide, as many rows as
+            // the pipeline allows.
+            let width = state.threadExecutionWidth().max(1);
+            let threadgroup = (
+                width,
+                (state.maxTotalThreadsPerThreadgroup() / width).max(1),
+            );
+
+            Ok(ComputePipeline {
+                state,
+                function,
+                uniforms,
+                threadgroup,
+                _tracked: Tracked::new(&self.memory.pipelines, 0),
+            })
+        }
+
+        /// Resolve a [`ShaderSource`] to a kernel function and its name.
+        fn kernel_function(
+            &self,
+            source: ShaderSource<'_>,
+        ) -> Result<(Retained<ProtocolObject<dyn MTLFunction>>, String)> {
+            let device = self.device.device();
+            let (library, name) = match source {
+                ShaderSource::NamedEntry(name) => (self.library.clone(), name.to_owned()),
+                ShaderSource::Bytecode(bytes) => {
+                    let data = dispatch2::DispatchData::from_bytes(bytes);
+                    let library = device
+                        .newLibraryWithData_error(&data)
+                        .map_err(|e| anyhow::anyhow!("Failed to load Metal library: {e}"))?;
+                    let names = library.functionNames();
+                    if names.count() != 1 {
+                        anyhow::bail!(
+                            "Metal library holds {} functions, expected a single kernel",
+                            names.count()
+                        );
+                    }
+                    let name = names
+                        .firstObject()
+                        .map(|n| n.to_string())
+                        .unwrap_or_default();
+                    (library, name)
+                }
+                ShaderSource::Source { code, entry } => {
+                    let library = device
+                        .newLibraryWithSource_options_error(&NSString::from_str(code), None)
+                        .map_err(|e| anyhow::anyhow!("Failed to compile Metal shader: {e}"))?;
+                    (library, entry.to_owned())
+                }
+            };
 
-            Ok(ComputePipeline { state })
+            let function = function_named(&library, &name, "Metal function")?;
+            Ok((function, name))
         }
 
         /// Create a render pipeline from vertex and fragment function names.
         ///
         /// The pipeline is configured for BGRA8Unorm output and alpha blending
-        /// disabled, suitable for fullscreen quad rendering.
+        /// disabled, suitable for fullscreen quad rendering. Both stages are
+        /// reflected as for
+        /// [`create_compute_pipeline`](Self::create_compute_pipeline).
         pub fn create_render_pipeline(
             &self,
             vertex_name: &str,
             fragment_name: &str,
         ) -> Result<RenderPipeline> {
-            let vs_name = NSString::from_str(vertex_name);
-            let fs_name = NSString::from_str(fragment_name);
+            self.create_render_pipeline_with_format(
+                vertex_name,
+                fragment_name,
+                IntermediateFormat::Rgba8,
+            )
+        }
 
-            let vs_func = self
-                .library
-                .newFunctionWithName(&vs_name)
-                .ok_or_else(|| {
-                    anyhow::anyhow!("Metal vertex function '{vertex_name}' not found")
-                })?;
-            let fs_func = self
-                .library
-                .newFunctionWithName(&fs_name)
-                .ok_or_else(|| {
-                    anyhow::anyhow!("Metal fragment function '{fragment_name}' not found")
-                })?;
+        /// [`create_render_pipeline`](Self::create_render_pipeline) drawing
+        /// into `format` instead of BGRA8Unorm.
+        ///
+        /// Metal ties a render pipeline to its target's pixel format, so a
+        /// pass rendering into a float intermediate needs one of these.
+        /// [`Passes`](crate::Passes) converts through a scratch texture when
+        /// the formats differ.
+        pub fn create_render_pipeline_with_format(
+            &self,
+            vertex_name: &str,
+            fragment_name: &str,
+            format: IntermediateFormat,
+        ) -> Result<RenderPipeline> {
+            let vs_func = function_named(&self.library, vertex_name, "Metal vertex function")?;
+            let fs_func = function_named(&self.library, fragment_name, "Metal fragment function")?;
 
             let desc = MTLRenderPipelineDescriptor::new();
             desc.setVertexFunction(Some(&vs_func));
@@ -195,20 +807,34 @@ mod metal_impl {
                 let attachment = unsafe {
                     desc.colorAttachments().objectAtIndexedSubscript(0)
                 };
-                attachment.setPixelFormat(MTLPixelFormat::BGRA8Unorm);
+                attachment.setPixelFormat(format.metal());
                 attachment.setBlendingEnabled(false);
             }
 
+            let mut reflection = None;
             let state = self
                 .device
                 .device()
-                .newRenderPipelineStateWithDescriptor_error(&desc)
+                .newRenderPipelineStateWithDescriptor_options_reflection_error(
+                    &desc,
+                    reflection_options(),
+                    Some(&mut reflection),
+                )
                 .map_err(|e| {
                     anyhow::anyhow!(
                         "Failed to create render pipeline '{vertex_name}'/'{fragment_name}': {e}"
                     )
                 })?;
 
+            let (vertex_uniforms, fragment_uniforms) = reflection
+                .map(|r| {
+                    (
+                        reflect_uniforms(&r.vertexBindings()),
+                        reflect_uniforms(&r.fragmentBindings()),
+                    )
+                })
+                .unwrap_or_default();
+
             // Create fullscreen quad vertex buffer
             let quad_data = FULLSCREEN_QUAD;
             let quad_bytes = quad_data.as_ptr() as *const std::ffi::c_void;
@@ -226,7 +852,11 @@ mod metal_impl {
 
             Ok(RenderPipeline {
                 state,
+                format,
                 quad_vb,
+                vertex_uniforms,
+                fragment_uniforms,
+                _tracked: Tracked::new(&self.memory.pipelines, quad_len as u64),
             })
         }
 
@@ -246,12 +876,15 @@ mod metal_impl {
                 .device()
                 .newBufferWithLength_options(size, MTLResourceOptions::StorageModePrivate)
                 .ok_or_else(|| {
-                    anyhow::anyhow!("Failed to allocate Metal buffer of {size} bytes")
+                    self.allocation_error(format!(
+                        "Failed to allocate Metal buffer of {size} bytes"
+                    ))
                 })?;
 
             Ok(GpuBuffer {
                 size,
                 metal: buffer,
+                _tracked: Some(Tracked::new(&self.memory.buffers, size as u64)),
             })
         }
 
@@ -269,12 +902,57 @@ mod metal_impl {
                 .device()
                 .newBufferWithLength_options(size, MTLResourceOptions::StorageModeShared)
                 .ok_or_else(|| {
-                    anyhow::anyhow!("Failed to allocate shared Metal buffer of {size} bytes")
+                    self.allocation_error(format!(
+                        "Failed to allocate shared Metal buffer of {size} bytes"
+                    ))
                 })?;
 
             Ok(GpuBuffer {
                 size,
                 metal: buffer,
+                _tracked: Some(Tracked::new(&self.memory.buffers, size as u64)),
+            })
+        }
+
+        /// Create a `BGRA8Unorm` texture for intermediate passes.
+        ///
+        /// The texture lives in private (GPU-only) storage and can be read,
+        /// written by compute kernels, and used as a render target.
+        pub fn create_texture(&self, width: u32, height: u32) -> Result<GpuTexture> {
+            self.create_texture_with_format(width, height, IntermediateFormat::Rgba8)
+        }
+
+        /// Create a texture for intermediate passes in `format`, with the
+        /// same storage and usage as [`create_texture`](Self::create_texture).
+        ///
+        /// Render pipelines only draw into their own format; see
+        /// [`create_render_pipeline_with_format`](Self::create_render_pipeline_with_format).
+        pub fn create_texture_with_format(
+            &self,
+            width: u32,
+            height: u32,
+            format: IntermediateFormat,
+        ) -> Result<GpuTexture> {
+            let desc = intermediate_texture_descriptor(width, height, format);
+
+            let texture = self
+                .device
+                .device()
+                .newTextureWithDescriptor(&desc)
+                .ok_or_else(|| {
+                    self.allocation_error(format!(
+                        "Failed to create Metal texture ({width}x{height})"
+                    ))
+                })?;
+
+            let bytes = width as u64 * height as u64 * format.bytes_per_pixel() as u64;
+            Ok(GpuTexture {
+                width,
+                height,
+                format,
+                metal: texture,
+                _allocation: Allocation::new(bytes),
+                _tracked: Some(Tracked::new(&self.memory.intermediates, bytes)),
             })
         }
 
@@ -283,7 +961,13 @@ mod metal_impl {
         /// [`PendingWork`] token.
         ///
         /// Textures are bound sequentially starting at index 0. Buffers and
-        /// bytes are bound at their specified slot indices.
+        /// bytes are bound at their specified slot indices. Byte payloads
+        /// larger than Metal's 4 KB `setBytes` limit are transparently staged
+        /// through a pooled buffer, so shaders should declare them as
+        /// `constant T&` / `device const T*` buffer arguments either way.
+        ///
+        /// Exactly `grid` threads run; the last threadgroups in each row and
+        /// column are cut short rather than running past the edge.
         pub fn dispatch_compute(
             &self,
             pipeline: &ComputePipeline,
@@ -304,17 +988,78 @@ mod metal_impl {
                 .ok_or_else(|| anyhow::anyhow!("Failed to create Metal compute encoder"))?;
 
             encode_compute_inner(
-                &encoder, pipeline, textures, buffers, bytes, grid, threadgroup,
+                self,
+                &command_buffer,
+                &encoder,
+                pipeline,
+                None,
+                textures,
+                buffers,
+                bytes,
+                grid,
+                threadgroup,
             );
 
-            command_buffer.commit();
-            Ok(PendingWork { command_buffer })
+            Ok(PendingWork::commit(command_buffer))
+        }
+
+        /// [`dispatch_compute`](Self::dispatch_compute) with the pipeline's
+        /// [`threadgroup_size`](ComputePipeline::threadgroup_size).
+        pub fn dispatch_auto(
+            &self,
+            pipeline: &ComputePipeline,
+            textures: &[&ProtocolObject<dyn MTLTexture>],
+            buffers: &[(&GpuBuffer, usize)],
+            bytes: &[(&[u8], usize)],
+            grid: (usize, usize),
+        ) -> Result<PendingWork> {
+            self.dispatch_compute(
+                pipeline,
+                textures,
+                buffers,
+                bytes,
+                grid,
+                pipeline.threadgroup,
+            )
+        }
+
+        /// Like [`dispatch_compute`](Self::dispatch_compute), but also binds
+        /// an [`ArgumentBuffer`] at its buffer index, so large sets of
+        /// textures and buffers cost one binding per dispatch.
+        pub fn dispatch_compute_with_arguments(
+            &self,
+            pipeline: &ComputePipeline,
+            arguments: &ArgumentBuffer,
+            textures: &[&ProtocolObject<dyn MTLTexture>],
+            buffers: &[(&GpuBuffer, usize)],
+            bytes: &[(&[u8], usize)],
+            grid: (usize, usize),
+            threadgroup: (usize, usize),
+        ) -> Result<PendingWork> {
+            let cb = self.create_command_buffer()?;
+            self.encode_compute_pass_with_arguments(
+                &cb,
+                pipeline,
+                arguments,
+                textures,
+                buffers,
+                bytes,
+                grid,
+                threadgroup,
+            )?;
+            Ok(self.commit(cb))
         }
 
         /// Dispatch a fullscreen render pass: renders a quad using the given
         /// render pipeline with the output texture as the render target and
         /// input textures bound to fragment shader slots.
         ///
+        /// Vertex-stage resources (e.g. transforms or animation parameters)
+        /// are bound via `vertex_textures` (sequentially from index 0),
+        /// `vertex_buffers`, and `vertex_bytes` (at their specified indices).
+        /// Vertex buffer index 0 holds the fullscreen quad, so use index 1 or
+        /// higher for your own vertex data.
+        ///
         /// Returns a [`PendingWork`] token for synchronization.
         pub fn dispatch_render(
             &self,
@@ -322,6 +1067,9 @@ mod metal_impl {
             output_texture: &ProtocolObject<dyn MTLTexture>,
             fragment_textures: &[&ProtocolObject<dyn MTLTexture>],
             fragment_bytes: &[(&[u8], usize)],
+            vertex_textures: &[&ProtocolObject<dyn MTLTexture>],
+            vertex_buffers: &[(&GpuBuffer, usize)],
+            vertex_bytes: &[(&[u8], usize)],
         ) -> Result<PendingWork> {
             let command_buffer = self
                 .device
@@ -329,59 +1077,19 @@ mod metal_impl {
                 .commandBuffer()
                 .ok_or_else(|| anyhow::anyhow!("Failed to create command buffer for render"))?;
 
-            let render_desc = MTLRenderPassDescriptor::new();
-            {
-                let attachment = unsafe {
-                    render_desc
-                        .colorAttachments()
-                        .objectAtIndexedSubscript(0)
-                };
-                attachment.setTexture(Some(output_texture));
-                attachment.setLoadAction(MTLLoadAction::DontCare);
-                attachment.setStoreAction(MTLStoreAction::Store);
-            }
-
-            let encoder = command_buffer
-                .renderCommandEncoderWithDescriptor(&render_desc)
-                .ok_or_else(|| anyhow::anyhow!("Failed to create render encoder"))?;
-
-            encoder.setRenderPipelineState(&pipeline.state);
-
-            // Bind fullscreen quad vertex buffer at index 0
-            unsafe {
-                encoder.setVertexBuffer_offset_atIndex(Some(&pipeline.quad_vb), 0, 0);
-            }
-
-            // Bind fragment textures
-            for (i, tex) in fragment_textures.iter().enumerate() {
-                unsafe {
-                    encoder.setFragmentTexture_atIndex(Some(*tex), i);
-                }
-            }
-
-            // Bind fragment constant data
-            for (data, index) in fragment_bytes {
-                unsafe {
-                    encoder.setFragmentBytes_length_atIndex(
-                        std::ptr::NonNull::new_unchecked(data.as_ptr() as *mut _),
-                        data.len(),
-                        *index,
-                    );
-                }
-            }
-
-            // Draw fullscreen quad as triangle strip (4 vertices)
-            unsafe {
-                encoder
-                    .drawPrimitives_vertexStart_vertexCount(MTLPrimitiveType::TriangleStrip, 0, 4);
-            }
-
-            encoder.endEncoding();
-            command_buffer.commit();
-
-            Ok(PendingWork {
-                command_buffer,
-            })
+            encode_render_inner(
+                self,
+                &command_buffer,
+                pipeline,
+                output_texture,
+                fragment_textures,
+                fragment_bytes,
+                vertex_textures,
+                vertex_buffers,
+                vertex_bytes,
+            )?;
+
+            Ok(PendingWork::commit(command_buffer))
         }
 
         // =================================================================
@@ -427,7 +1135,51 @@ mod metal_impl {
                 .ok_or_else(|| anyhow::anyhow!("Failed to create Metal compute encoder"))?;
 
             encode_compute_inner(
-                &encoder, pipeline, textures, buffers, bytes, grid, threadgroup,
+                self,
+                &cb.inner,
+                &encoder,
+                pipeline,
+                None,
+                textures,
+                buffers,
+                bytes,
+                grid,
+                threadgroup,
+            );
+
+            Ok(())
+        }
+
+        /// Encode a compute pass with an [`ArgumentBuffer`] on an existing
+        /// command buffer (see
+        /// [`dispatch_compute_with_arguments`](Self::dispatch_compute_with_arguments)).
+        pub fn encode_compute_pass_with_arguments(
+            &self,
+            cb: &CommandBuffer,
+            pipeline: &ComputePipeline,
+            arguments: &ArgumentBuffer,
+            textures: &[&ProtocolObject<dyn MTLTexture>],
+            buffers: &[(&GpuBuffer, usize)],
+            bytes: &[(&[u8], usize)],
+            grid: (usize, usize),
+            threadgroup: (usize, usize),
+        ) -> Result<()> {
+            let encoder = cb
+                .inner
+                .computeCommandEncoder()
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal compute encoder"))?;
+
+            encode_compute_inner(
+                self,
+                &cb.inner,
+                &encoder,
+                pipeline,
+                Some(arguments),
+                textures,
+                buffers,
+                bytes,
+                grid,
+                threadgroup,
             );
 
             Ok(())
@@ -445,60 +1197,21 @@ mod metal_impl {
             output_texture: &ProtocolObject<dyn MTLTexture>,
             fragment_textures: &[&ProtocolObject<dyn MTLTexture>],
             fragment_bytes: &[(&[u8], usize)],
+            vertex_textures: &[&ProtocolObject<dyn MTLTexture>],
+            vertex_buffers: &[(&GpuBuffer, usize)],
+            vertex_bytes: &[(&[u8], usize)],
         ) -> Result<()> {
-            let render_desc = MTLRenderPassDescriptor::new();
-            {
-                let attachment = unsafe {
-                    render_desc
-                        .colorAttachments()
-                        .objectAtIndexedSubscript(0)
-                };
-                attachment.setTexture(Some(output_texture));
-                attachment.setLoadAction(MTLLoadAction::DontCare);
-                attachment.setStoreAction(MTLStoreAction::Store);
-            }
-
-            let encoder = cb
-                .inner
-                .renderCommandEncoderWithDescriptor(&render_desc)
-                .ok_or_else(|| anyhow::anyhow!("Failed to create render encoder"))?;
-
-            encoder.setRenderPipelineState(&pipeline.state);
-
-            // Bind fullscreen quad vertex buffer at index 0
-            unsafe {
-                encoder.setVertexBuffer_offset_atIndex(Some(&pipeline.quad_vb), 0, 0);
-            }
-
-            // Bind fragment textures
-            for (i, tex) in fragment_textures.iter().enumerate() {
-                unsafe {
-                    encoder.setFragmentTexture_atIndex(Some(*tex), i);
-                }
-            }
-
-            // Bind fragment constant data
-            for (data, index) in fragment_bytes {
-                unsafe {
-                    encoder.setFragmentBytes_length_atIndex(
-                        std::ptr::NonNull::new_unchecked(data.as_ptr() as *mut _),
-                        data.len(),
-                        *index,
-                    );
-                }
-            }
-
-            // Draw fullscreen quad as triangle strip (4 vertices)
-            unsafe {
-                encoder.drawPrimitives_vertexStart_vertexCount(
-                    MTLPrimitiveType::TriangleStrip,
-                    0,
-                    4,
-                );
-            }
-
-            encoder.endEncoding();
-            Ok(())
+            encode_render_inner(
+                self,
+                &cb.inner,
+                pipeline,
+                output_texture,
+                fragment_textures,
+                fragment_bytes,
+                vertex_textures,
+                vertex_buffers,
+                vertex_bytes,
+            )
         }
 
         /// Commit a command buffer and return a [`PendingWork`] token.
@@ -508,10 +1221,7 @@ mod metal_impl {
         /// [`GlMetalBridge::store_command_buffer`](gpu_interop::metal::GlMetalBridge::store_command_buffer)
         /// for pipelined double-buffered synchronisation.
         pub fn commit(&self, cb: CommandBuffer) -> PendingWork {
-            cb.inner.commit();
-            PendingWork {
-                command_buffer: cb.inner,
-            }
+            PendingWork::commit(cb.inner)
         }
     }
 }
@@ -523,8 +1233,17 @@ mod metal_impl {
 #[cfg(target_os = "windows")]
 mod dx11_impl {
     use super::*;
-    use windows::core::PCSTR;
-    use windows::Win32::Graphics::Direct3D::D3D_SRV_DIMENSION_BUFFER;
+    use crate::blit::{blob_bytes, compile_hlsl};
+    use crate::budget::Allocation;
+    use crate::memory::Tracked;
+    use crate::intermediate::IntermediateFormat;
+    use crate::pipeline::{check_uniform_size, ShaderSource, UniformSlot};
+    use crate::texture::GpuTexture;
+    use windows::core::{Interface, PCSTR};
+    use windows::Win32::Graphics::Direct3D::Fxc::D3DReflect;
+    use windows::Win32::Graphics::Direct3D::{
+        D3D_SIT_CBUFFER, D3D_SRV_DIMENSION_BUFFER, D3D_SRV_DIMENSION_TEXTURE2D,
+    };
     use windows::Win32::Graphics::Direct3D11::*;
     use windows::Win32::Graphics::Dxgi::Common::*;
 
@@ -537,12 +1256,162 @@ mod dx11_impl {
         [1.0, 1.0, 1.0, 0.0],   // top-right
     ];
 
+    /// Reflect the constant buffers declared by compiled shader `bytecode`.
+    ///
+    /// Returns an empty list if reflection fails, which disables binding
+    /// uniforms by name and uniform validation for the stage.
+    fn reflect_cbuffers(bytecode: &[u8]) -> Vec<UniformSlot> {
+        let reflection = match reflect(bytecode) {
+            Ok(reflection) => reflection,
+            Err(e) => {
+                tracing::warn!("D3DReflect failed, named uniforms and validation disabled: {e}");
+                return Vec::new();
+            }
+        };
+
+        let mut desc = D3D11_SHADER_DESC::default();
+        if unsafe { reflection.GetDesc(&mut desc) }.is_err() {
+            return Vec::new();
+        }
+
+        (0..desc.BoundResources)
+            .filter_map(|i| {
+                let mut bind = D3D11_SHADER_INPUT_BIND_DESC::default();
+                unsafe { reflection.GetResourceBindingDesc(i, &mut bind) }.ok()?;
+                if bind.Type != D3D_SIT_CBUFFER {
+                    return None;
+                }
+                let cbuffer = unsafe { reflection.GetConstantBufferByName(bind.Name) }?;
+                let mut cb_desc = D3D11_SHADER_BUFFER_DESC::default();
+                unsafe { cbuffer.GetDesc(&mut cb_desc) }.ok()?;
+                Some(UniformSlot {
+                    index: bind.BindPoint as usize,
+                    size: cb_desc.Size as usize,
+                    alignment: 16,
+                    name: unsafe { bind.Name.to_string() }.unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    fn reflect(bytecode: &[u8]) -> windows::core::Result<ID3D11ShaderReflection> {
+        let mut raw = std::ptr::null_mut();
+        unsafe {
+            D3DReflect(
+                bytecode.as_ptr() as *const _,
+                bytecode.len(),
+                &ID3D11ShaderReflection::IID,
+                &mut raw,
+            )?;
+            Ok(ID3D11ShaderReflection::from_raw(raw))
+        }
+    }
+
+    /// Register of the `FfglGpuGrid` cbuffer a kernel compiled with
+    /// [`HlslOptions::bounds_guard`](crate::build_support::HlslOptions::bounds_guard)
+    /// declares, if any.
+    fn reflect_grid_slot(bytecode: &[u8]) -> Option<u32> {
+        let reflection = reflect(bytecode).ok()?;
+        let mut bind = D3D11_SHADER_INPUT_BIND_DESC::default();
+        unsafe {
+            reflection.GetResourceBindingDescByName(PCSTR(b"FfglGpuGrid\0".as_ptr()), &mut bind)
+        }
+        .ok()?;
+        (bind.Type == D3D_SIT_CBUFFER).then_some(bind.BindPoint)
+    }
+
+    /// The `[numthreads(x, y, _)]` a compiled kernel declares.
+    fn reflect_threadgroup(bytecode: &[u8]) -> Option<(usize, usize)> {
+        let reflection = reflect(bytecode).ok()?;
+        let (mut x, mut y) = (0, 0);
+        unsafe { reflection.GetThreadGroupSize(Some(&mut x), Some(&mut y), None) };
+        (x > 0 && y > 0).then_some((x as usize, y as usize))
+    }
+
+    /// Compare the constant buffers bound for `stage` against its reflected
+    /// cbuffer sizes. No-op in release builds or when nothing was reflected.
+    fn check_cbufs(stage: &str, expected: &[UniformSlot], cbufs: &[Option<ID3D11Buffer>]) {
+        if !cfg!(debug_assertions) || expected.is_empty() {
+            return;
+        }
+        for (slot, cbuf) in cbufs.iter().enumerate() {
+            if let Some(cbuf) = cbuf {
+                let mut desc = D3D11_BUFFER_DESC::default();
+                unsafe { cbuf.GetDesc(&mut desc) };
+                check_uniform_size(stage, expected, slot, desc.ByteWidth as usize);
+            }
+        }
+    }
+
+    /// The resource behind `view`.
+    fn view_resource(view: &ID3D11View) -> Option<ID3D11Resource> {
+        let mut resource = None;
+        unsafe { view.GetResource(&mut resource) };
+        resource
+    }
+
+    /// The resources behind the shader resource views a pass reads.
+    fn read_resources<'v>(
+        views: impl IntoIterator<Item = &'v ID3D11ShaderResourceView>,
+    ) -> Vec<ID3D11Resource> {
+        views.into_iter().filter_map(|v| view_resource(v)).collect()
+    }
+
+    /// Warn if a pass binds a resource it writes for reading as well. D3D11
+    /// binds `NULL` for the SRV, so the pass reads zeros.
+    fn check_feedback(stage: &str, reads: &[ID3D11Resource], writes: &[ID3D11Resource]) {
+        if writes.iter().any(|w| reads.contains(w)) {
+            gpu_interop::warn_limited!(
+                "{stage} pass reads a texture it also writes; D3D11 binds nothing \
+                 for the read"
+            );
+        }
+    }
+
+    /// Map `buffer` on `ctx` with `WRITE_DISCARD`, copy `data` in, and unmap.
+    fn write_constant_buffer(ctx: &ID3D11DeviceContext, buffer: &ID3D11Buffer, data: &[u8]) {
+        unsafe {
+            let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+            let hr = ctx.Map(buffer, 0, D3D11_MAP_WRITE_DISCARD, 0, Some(&mut mapped));
+            if hr.is_ok() {
+                debug_assert!(
+                    data.len() <= mapped.RowPitch as usize,
+                    "update_constant_buffer: data ({} bytes) exceeds mapped region ({} bytes)",
+                    data.len(),
+                    mapped.RowPitch,
+                );
+                std::ptr::copy_nonoverlapping(data.as_ptr(), mapped.pData as *mut u8, data.len());
+                ctx.Unmap(buffer, 0);
+            }
+        }
+    }
+
     impl GpuContext {
-        /// Create a compute pipeline from pre-compiled HLSL bytecode (`.cso`).
-        pub fn create_compute_pipeline(
+        /// Create a compute pipeline from a [`ShaderSource`].
+        ///
+        /// Bytecode (or a byte slice) is a pre-compiled HLSL `.cso`, and
+        /// source is HLSL compiled for `cs_5_0` on the spot. D3D11 has no
+        /// shader library, so a named entry is an error.
+        ///
+        /// In debug builds the shader's constant buffers are reflected so
+        /// that undersized cbuffers are reported at dispatch time.
+        pub fn create_compute_pipeline<'s>(
             &self,
-            bytecode: &[u8],
+            source: impl Into<ShaderSource<'s>>,
         ) -> Result<ComputePipeline> {
+            let blob;
+            let bytecode = match source.into() {
+                ShaderSource::NamedEntry(name) => anyhow::bail!(
+                    "D3D11 has no shader library to find '{name}' in; pass bytecode or source"
+                ),
+                ShaderSource::Bytecode(bytes) => bytes,
+                ShaderSource::Source { code, entry } => {
+                    let entry = std::ffi::CString::new(entry)?;
+                    blob = compile_hlsl(code, entry.as_bytes_with_nul(), b"cs_5_0\0")?;
+                    unsafe { blob_bytes(&blob) }
+                }
+            };
+
             let mut shader = None;
             unsafe {
                 self.device
@@ -554,7 +1423,18 @@ mod dx11_impl {
             let shader =
                 shader.ok_or_else(|| anyhow::anyhow!("D3D11 CreateComputeShader returned null"))?;
 
-            Ok(ComputePipeline { shader })
+            let threadgroup = reflect_threadgroup(bytecode).unwrap_or_else(|| {
+                tracing::warn!("Couldn't reflect the kernel's numthreads, assuming 16x16");
+                (16, 16)
+            });
+
+            Ok(ComputePipeline {
+                shader,
+                grid_slot: reflect_grid_slot(bytecode),
+                uniforms: reflect_cbuffers(bytecode),
+                threadgroup,
+                _tracked: Tracked::new(&self.memory.pipelines, 0),
+            })
         }
 
         /// Create a render pipeline from pre-compiled HLSL vertex and pixel
@@ -662,6 +1542,9 @@ mod dx11_impl {
                 input_layout,
                 quad_vb,
                 sampler,
+                vertex_uniforms: reflect_cbuffers(vs_bytecode),
+                fragment_uniforms: reflect_cbuffers(ps_bytecode),
+                _tracked: Tracked::new(&self.memory.pipelines, vb_desc.ByteWidth as u64),
             })
         }
 
@@ -690,7 +1573,11 @@ mod dx11_impl {
                     Some(&mut buffer as *mut _),
                 )
             }
-            .map_err(|e| anyhow::anyhow!("Failed to create D3D11 structured buffer: {e}"))?;
+            .map_err(|e| {
+                self.allocation_error(format!(
+                    "Failed to create D3D11 structured buffer of {size} bytes: {e}"
+                ))
+            })?;
             let buffer =
                 buffer.ok_or_else(|| anyhow::anyhow!("D3D11 CreateBuffer returned null"))?;
 
@@ -750,78 +1637,379 @@ mod dx11_impl {
                 dx11_buffer: buffer,
                 dx11_uav: uav,
                 dx11_srv: srv,
+                _tracked: Some(Tracked::new(&self.memory.buffers, size as u64)),
+            })
+        }
+
+        /// Create a `B8G8R8A8_UNORM` texture for intermediate passes, with
+        /// SRV, UAV, and RTV views so it can be read, written by compute
+        /// shaders, and rendered into.
+        pub fn create_texture(&self, width: u32, height: u32) -> Result<GpuTexture> {
+            self.create_texture_with_format(width, height, IntermediateFormat::Rgba8)
+        }
+
+        /// Create a texture for intermediate passes in `format`, with the
+        /// same views as [`create_texture`](Self::create_texture).
+        pub fn create_texture_with_format(
+            &self,
+            width: u32,
+            height: u32,
+            format: IntermediateFormat,
+        ) -> Result<GpuTexture> {
+            let device = self.device.device();
+            let dxgi_format = format.dxgi();
+
+            let desc = D3D11_TEXTURE2D_DESC {
+                Width: width,
+                Height: height,
+                MipLevels: 1,
+                ArraySize: 1,
+                Format: dxgi_format,
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0,
+                },
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: (D3D11_BIND_SHADER_RESOURCE.0
+                    | D3D11_BIND_UNORDERED_ACCESS.0
+                    | D3D11_BIND_RENDER_TARGET.0) as u32,
+                CPUAccessFlags: 0,
+                MiscFlags: 0,
+            };
+
+            let mut texture = None;
+            unsafe { device.CreateTexture2D(&desc, None, Some(&mut texture as *mut _)) }
+                .map_err(|e| {
+                    self.allocation_error(format!(
+                        "Failed to create D3D11 texture ({width}x{height}): {e}"
+                    ))
+                })?;
+            let texture =
+                texture.ok_or_else(|| anyhow::anyhow!("D3D11 CreateTexture2D returned null"))?;
+
+            let srv_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
+                Format: dxgi_format,
+                ViewDimension: D3D_SRV_DIMENSION_TEXTURE2D,
+                Anonymous: D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
+                    Texture2D: D3D11_TEX2D_SRV {
+                        MostDetailedMip: 0,
+                        MipLevels: 1,
+                    },
+                },
+            };
+            let mut srv = None;
+            unsafe {
+                device.CreateShaderResourceView(&texture, Some(&srv_desc), Some(&mut srv as *mut _))
+            }
+            .map_err(|e| anyhow::anyhow!("Failed to create D3D11 texture SRV: {e}"))?;
+            let srv = srv.ok_or_else(|| anyhow::anyhow!("D3D11 CreateSRV returned null"))?;
+
+            let uav_desc = D3D11_UNORDERED_ACCESS_VIEW_DESC {
+                Format: dxgi_format,
+                ViewDimension: D3D11_UAV_DIMENSION_TEXTURE2D,
+                Anonymous: D3D11_UNORDERED_ACCESS_VIEW_DESC_0 {
+                    Texture2D: D3D11_TEX2D_UAV { MipSlice: 0 },
+                },
+            };
+            let mut uav = None;
+            unsafe {
+                device.CreateUnorderedAccessView(
+                    &texture,
+                    Some(&uav_desc),
+                    Some(&mut uav as *mut _),
+                )
+            }
+            .map_err(|e| anyhow::anyhow!("Failed to create D3D11 texture UAV: {e}"))?;
+            let uav = uav.ok_or_else(|| anyhow::anyhow!("D3D11 CreateUAV returned null"))?;
+
+            let mut rtv = None;
+            unsafe { device.CreateRenderTargetView(&texture, None, Some(&mut rtv as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create D3D11 texture RTV: {e}"))?;
+            let rtv = rtv.ok_or_else(|| anyhow::anyhow!("D3D11 CreateRTV returned null"))?;
+
+            let bytes = width as u64 * height as u64 * format.bytes_per_pixel() as u64;
+            Ok(GpuTexture {
+                width,
+                height,
+                format,
+                dx11_texture: texture,
+                dx11_srv: srv,
+                dx11_uav: uav,
+                dx11_rtv: rtv,
+                _allocation: Allocation::new(bytes),
+                _tracked: Some(Tracked::new(&self.memory.intermediates, bytes)),
             })
         }
 
-        /// Dispatch a compute shader on the immediate context.
+        /// Dispatch a compute shader on the
+        /// [`command_context`](Self::command_context).
         ///
         /// Binds the compute shader, UAVs, SRVs, and constant buffers, then
-        /// dispatches enough thread groups to cover `grid` total threads with
-        /// the given `threadgroup` size. Unbinds all CS resources after dispatch
-        /// to prevent resource hazards in multi-pass scenarios.
+        /// dispatches enough thread groups to cover `grid` total threads. The
+        /// group size is fixed by the kernel's `numthreads`; a `threadgroup`
+        /// that disagrees is logged and the kernel's size used instead.
+        /// Unbinds all CS resources after dispatch to prevent resource
+        /// hazards in multi-pass scenarios.
+        ///
+        /// Render targets and CS UAVs still bound to a resource this pass
+        /// reads are unbound before the SRVs are bound, so a texture written
+        /// by an earlier pass (or by the plugin's own D3D11 calls) reads back
+        /// correctly.
+        ///
+        /// Each entry of `uavs` / `srvs`, a [`GpuTexture`], a structured
+        /// [`GpuBuffer`] or a view (see [`SrvBinding`]), is bound at its
+        /// index: `uavs[i]` at `u{i}`, `srvs[i]` at `t{i}`.
+        ///
+        /// Threads in the last group past `grid` still run. Kernels compiled
+        /// with [`HlslOptions::bounds_guard`](crate::build_support::HlslOptions::bounds_guard),
+        /// or declaring the `FfglGpuGrid` cbuffer themselves, get `grid`
+        /// bound there to return early on.
         pub fn dispatch_compute(
             &self,
             pipeline: &ComputePipeline,
-            uavs: &[Option<ID3D11UnorderedAccessView>],
-            srvs: &[Option<ID3D11ShaderResourceView>],
+            uavs: &[UavBinding<'_>],
+            srvs: &[SrvBinding<'_>],
+            cbufs: &[Option<ID3D11Buffer>],
+            grid: (usize, usize),
+            threadgroup: (usize, usize),
+        ) {
+            self.dispatch_compute_on(
+                self.command_context(),
+                pipeline,
+                uavs,
+                srvs,
+                cbufs,
+                grid,
+                threadgroup,
+            );
+        }
+
+        /// [`dispatch_compute`](Self::dispatch_compute) issued on `ctx`.
+        fn dispatch_compute_on(
+            &self,
+            ctx: &ID3D11DeviceContext,
+            pipeline: &ComputePipeline,
+            uavs: &[UavBinding<'_>],
+            srvs: &[SrvBinding<'_>],
             cbufs: &[Option<ID3D11Buffer>],
             grid: (usize, usize),
             threadgroup: (usize, usize),
         ) {
+            check_cbufs("compute", &pipeline.uniforms, cbufs);
+            let uavs: Vec<_> = uavs.iter().map(UavBinding::view).collect();
+            let srvs: Vec<_> = srvs.iter().map(SrvBinding::view).collect();
+
+            if threadgroup != pipeline.threadgroup {
+                gpu_interop::warn_limited!(
+                    "dispatch_compute: threadgroup {threadgroup:?} doesn't match the kernel's \
+                     numthreads {:?}, using the kernel's",
+                    pipeline.threadgroup
+                );
+            }
+            let threadgroup = pipeline.threadgroup;
             let groups_x = ((grid.0 + threadgroup.0 - 1) / threadgroup.0) as u32;
             let groups_y = ((grid.1 + threadgroup.1 - 1) / threadgroup.1) as u32;
 
-            let ctx = self.device.context();
+            // Slots to unbind after the dispatch: at least the first 8, as
+            // before, and every one this dispatch bound.
+            let srv_slots = srvs
+                .len()
+                .max(8)
+                .min(D3D11_COMMONSHADER_INPUT_RESOURCE_SLOT_COUNT as usize);
+            let uav_slots = uavs.len().max(8).min(D3D11_1_UAV_SLOT_COUNT as usize);
+
+            let reads = read_resources(srvs.iter().flatten());
+            let writes: Vec<_> = uavs
+                .iter()
+                .flatten()
+                .filter_map(|v| view_resource(v))
+                .collect();
+            check_feedback("compute", &reads, &writes);
+            Self::release_outputs(ctx, &reads);
+
             unsafe {
                 ctx.CSSetShader(&pipeline.shader, None);
                 if !uavs.is_empty() {
                     ctx.CSSetUnorderedAccessViews(0, uavs.len() as u32, Some(uavs.as_ptr() as *const _), None);
                 }
                 if !srvs.is_empty() {
-                    ctx.CSSetShaderResources(0, Some(srvs));
+                    ctx.CSSetShaderResources(0, Some(&srvs));
                 }
                 if !cbufs.is_empty() {
                     ctx.CSSetConstantBuffers(0, Some(cbufs));
                 }
+                if let Some(slot) = pipeline.grid_slot {
+                    self.bind_grid(ctx, slot, grid);
+                }
                 ctx.Dispatch(groups_x, groups_y, 1);
 
                 // Unbind all CS resources to prevent hazards when the same
-                // texture is used as SRV in a subsequent pass.
-                let null_uavs: [Option<ID3D11UnorderedAccessView>; 8] = Default::default();
-                let null_srvs: [Option<ID3D11ShaderResourceView>; 8] = Default::default();
+                // texture or buffer is used as SRV in a subsequent pass.
+                let null_uavs: Vec<Option<ID3D11UnorderedAccessView>> = vec![None; uav_slots];
+                let null_srvs: Vec<Option<ID3D11ShaderResourceView>> = vec![None; srv_slots];
                 let null_cbufs: [Option<ID3D11Buffer>; 1] = Default::default();
                 ctx.CSSetUnorderedAccessViews(0, null_uavs.len() as u32, Some(null_uavs.as_ptr() as *const _), None);
                 ctx.CSSetShaderResources(0, Some(&null_srvs));
                 ctx.CSSetConstantBuffers(0, Some(&null_cbufs));
+                if let Some(slot) = pipeline.grid_slot {
+                    ctx.CSSetConstantBuffers(slot, Some(&null_cbufs));
+                }
+            }
+        }
+
+        /// [`dispatch_compute`](Self::dispatch_compute) with the kernel's
+        /// [`threadgroup_size`](ComputePipeline::threadgroup_size).
+        pub fn dispatch_auto(
+            &self,
+            pipeline: &ComputePipeline,
+            uavs: &[UavBinding<'_>],
+            srvs: &[SrvBinding<'_>],
+            cbufs: &[Option<ID3D11Buffer>],
+            grid: (usize, usize),
+        ) {
+            self.dispatch_compute(pipeline, uavs, srvs, cbufs, grid, pipeline.threadgroup);
+        }
+
+        /// Unbind render targets and CS UAVs whose resources are in `reads`,
+        /// before those are bound as shader resources.
+        ///
+        /// D3D11 refuses to bind an SRV whose resource is still bound for
+        /// output and binds `NULL` instead, so a pass reading what the
+        /// previous one wrote would sample black. The framework's own passes
+        /// unbind their outputs, but blits, third-party code or the plugin's
+        /// own calls on the command context may not.
+        fn release_outputs(ctx: &ID3D11DeviceContext, reads: &[ID3D11Resource]) {
+            if reads.is_empty() {
+                return;
+            }
+            let is_read =
+                |view: &ID3D11View| view_resource(view).is_some_and(|r| reads.contains(&r));
+
+            let mut rtvs: [Option<ID3D11RenderTargetView>; 8] = Default::default();
+            let mut dsv = None;
+            unsafe { ctx.OMGetRenderTargets(Some(&mut rtvs), Some(&mut dsv as *mut _)) };
+            let mut released = false;
+            for rtv in &mut rtvs {
+                if rtv.as_ref().is_some_and(|v| is_read(v)) {
+                    *rtv = None;
+                    released = true;
+                }
             }
+            if released {
+                unsafe { ctx.OMSetRenderTargets(Some(&rtvs), dsv.as_ref()) };
+            }
+
+            let mut uavs: [Option<ID3D11UnorderedAccessView>; 8] = Default::default();
+            unsafe { ctx.CSGetUnorderedAccessViews(0, Some(&mut uavs)) };
+            for (slot, uav) in uavs.iter().enumerate() {
+                if uav.as_ref().is_some_and(|v| is_read(v)) {
+                    let null: [Option<ID3D11UnorderedAccessView>; 1] = Default::default();
+                    unsafe {
+                        ctx.CSSetUnorderedAccessViews(slot as u32, 1, Some(null.as_ptr()), None)
+                    };
+                    released = true;
+                }
+            }
+
+            if released {
+                tracing::trace!("Unbound outputs that the next pass reads");
+            }
+        }
+
+        /// Fill the shared `FfglGpuGrid` cbuffer with `grid` and bind it at
+        /// `slot` of `ctx`.
+        fn bind_grid(&self, ctx: &ID3D11DeviceContext, slot: u32, grid: (usize, usize)) {
+            let buffer = self
+                .grid_cbuf
+                .get_or_init(|| gpu_interop::dx11::create_dynamic_cbuf(self.device.device(), 16));
+            let Some(buffer) = buffer else {
+                gpu_interop::error_limited!("Failed to create the dispatch grid constant buffer");
+                return;
+            };
+            let mut data = [0u8; 16];
+            data[..4].copy_from_slice(&(grid.0 as u32).to_ne_bytes());
+            data[4..8].copy_from_slice(&(grid.1 as u32).to_ne_bytes());
+            write_constant_buffer(ctx, buffer, &data);
+            unsafe { ctx.CSSetConstantBuffers(slot, Some(&[Some(buffer.clone())])) };
         }
 
         /// Dispatch a fullscreen render pass using the given render pipeline.
         ///
-        /// Creates a temporary render target view from `output_texture`, sets
-        /// up the viewport, draws a fullscreen quad, and unbinds all resources
-        /// afterward to prevent hazards.
-        pub fn dispatch_render(
+        /// Draws into `output_texture` through its own render target view
+        /// when it is a [`GpuTexture`], or a temporary one otherwise, sets up
+        /// the viewport, draws a fullscreen quad, and unbinds all resources
+        /// afterward to prevent hazards. As in
+        /// [`dispatch_compute`](Self::dispatch_compute), outputs still bound
+        /// to a texture this pass reads are unbound first.
+        ///
+        /// Vertex-stage resources (e.g. transforms or animation parameters)
+        /// are bound via `vertex_srvs`, which can hold structured buffers as
+        /// in [`dispatch_compute`](Self::dispatch_compute), and
+        /// `vertex_cbufs`, each entry at its index. The pipeline's sampler is
+        /// bound to both stages.
+        pub fn dispatch_render<'t>(
+            &self,
+            pipeline: &RenderPipeline,
+            output_texture: impl Into<RenderTarget<'t>>,
+            pixel_srvs: &[SrvBinding<'_>],
+            pixel_cbufs: &[Option<ID3D11Buffer>],
+            vertex_srvs: &[SrvBinding<'_>],
+            vertex_cbufs: &[Option<ID3D11Buffer>],
+        ) -> Result<()> {
+            self.dispatch_render_on(
+                self.command_context(),
+                pipeline,
+                output_texture.into(),
+                pixel_srvs,
+                pixel_cbufs,
+                vertex_srvs,
+                vertex_cbufs,
+            )
+        }
+
+        /// [`dispatch_render`](Self::dispatch_render) issued on `ctx`.
+        fn dispatch_render_on(
             &self,
+            ctx: &ID3D11DeviceContext,
             pipeline: &RenderPipeline,
-            output_texture: &ID3D11Texture2D,
-            pixel_srvs: &[Option<ID3D11ShaderResourceView>],
+            output_texture: RenderTarget<'_>,
+            pixel_srvs: &[SrvBinding<'_>],
             pixel_cbufs: &[Option<ID3D11Buffer>],
+            vertex_srvs: &[SrvBinding<'_>],
+            vertex_cbufs: &[Option<ID3D11Buffer>],
         ) -> Result<()> {
+            check_cbufs("vertex", &pipeline.vertex_uniforms, vertex_cbufs);
+            check_cbufs("pixel", &pipeline.fragment_uniforms, pixel_cbufs);
+            let pixel_srvs: Vec<_> = pixel_srvs.iter().map(SrvBinding::view).collect();
+            let vertex_srvs: Vec<_> = vertex_srvs.iter().map(SrvBinding::view).collect();
+
             let device = self.device.device();
-            let ctx = self.device.context();
+
+            let (output_texture, rtv) = match output_texture {
+                RenderTarget::Texture(t) => (t.dx11_texture(), t.dx11_rtv().clone()),
+                RenderTarget::Texture2D(texture) => {
+                    // Create temporary RTV
+                    let mut rtv = None;
+                    unsafe {
+                        device.CreateRenderTargetView(texture, None, Some(&mut rtv as *mut _))
+                    }
+                    .map_err(|e| {
+                        anyhow::anyhow!("Failed to create RTV for render dispatch: {e}")
+                    })?;
+                    let rtv =
+                        rtv.ok_or_else(|| anyhow::anyhow!("D3D11 CreateRTV returned null"))?;
+                    (texture, rtv)
+                }
+            };
 
             // Query texture dimensions for viewport
             let mut desc = D3D11_TEXTURE2D_DESC::default();
             unsafe { output_texture.GetDesc(&mut desc) };
 
-            // Create temporary RTV
-            let mut rtv = None;
-            unsafe {
-                device.CreateRenderTargetView(output_texture, None, Some(&mut rtv as *mut _))
-            }
-            .map_err(|e| anyhow::anyhow!("Failed to create RTV for render dispatch: {e}"))?;
-            let rtv = rtv.ok_or_else(|| anyhow::anyhow!("D3D11 CreateRTV returned null"))?;
+            let reads = read_resources(pixel_srvs.iter().chain(&vertex_srvs).flatten());
+            let writes: Vec<_> = view_resource(&rtv).into_iter().collect();
+            check_feedback("render", &reads, &writes);
+            Self::release_outputs(ctx, &reads);
 
             unsafe {
                 // Set viewport
@@ -852,11 +2040,18 @@ mod dx11_impl {
 
                 // Vertex shader
                 ctx.VSSetShader(&pipeline.vs, None);
+                if !vertex_srvs.is_empty() {
+                    ctx.VSSetShaderResources(0, Some(&vertex_srvs));
+                }
+                if !vertex_cbufs.is_empty() {
+                    ctx.VSSetConstantBuffers(0, Some(vertex_cbufs));
+                }
+                ctx.VSSetSamplers(0, Some(&[Some(pipeline.sampler.clone())]));
 
                 // Pixel shader
                 ctx.PSSetShader(&pipeline.ps, None);
                 if !pixel_srvs.is_empty() {
-                    ctx.PSSetShaderResources(0, Some(pixel_srvs));
+                    ctx.PSSetShaderResources(0, Some(&pixel_srvs));
                 }
                 if !pixel_cbufs.is_empty() {
                     ctx.PSSetConstantBuffers(0, Some(pixel_cbufs));
@@ -876,32 +2071,173 @@ mod dx11_impl {
                 ctx.PSSetShaderResources(0, Some(&null_srvs));
                 let null_cbufs: [Option<ID3D11Buffer>; 1] = Default::default();
                 ctx.PSSetConstantBuffers(0, Some(&null_cbufs));
+
+                // Unbind vertex-stage resources as well.
+                let vs_srv_slots = vertex_srvs
+                    .len()
+                    .max(8)
+                    .min(D3D11_COMMONSHADER_INPUT_RESOURCE_SLOT_COUNT as usize);
+                let null_vs_srvs: Vec<Option<ID3D11ShaderResourceView>> = vec![None; vs_srv_slots];
+                ctx.VSSetShaderResources(0, Some(&null_vs_srvs));
+                let null_vs_cbufs: Vec<Option<ID3D11Buffer>> = vec![None; vertex_cbufs.len().max(1)];
+                ctx.VSSetConstantBuffers(0, Some(&null_vs_cbufs));
+            }
+
+            Ok(())
+        }
+
+        // =================================================================
+        // Multi-pass command buffer API
+        // =================================================================
+
+        /// Create a command buffer for encoding multiple passes, with a
+        /// deferred context of its own when the device allows it. See
+        /// [`CommandBuffer`].
+        pub fn create_command_buffer(&self) -> Result<CommandBuffer> {
+            let mut context = None;
+            let created = unsafe {
+                self.device
+                    .device()
+                    .CreateDeferredContext(0, Some(&mut context as *mut _))
+            };
+            if let Err(e) = created {
+                // Single-threaded devices refuse deferred contexts.
+                tracing::trace!("No deferred context for the command buffer: {e}");
             }
+            Ok(CommandBuffer { context })
+        }
 
+        /// Record a compute pass on `cb`: the same dispatch as
+        /// [`dispatch_compute`](Self::dispatch_compute). Call
+        /// [`commit`](Self::commit) after encoding all passes.
+        pub fn encode_compute_pass(
+            &self,
+            cb: &CommandBuffer,
+            pipeline: &ComputePipeline,
+            uavs: &[UavBinding<'_>],
+            srvs: &[SrvBinding<'_>],
+            cbufs: &[Option<ID3D11Buffer>],
+            grid: (usize, usize),
+            threadgroup: (usize, usize),
+        ) -> Result<()> {
+            self.dispatch_compute_on(
+                self.recording_context(cb),
+                pipeline,
+                uavs,
+                srvs,
+                cbufs,
+                grid,
+                threadgroup,
+            );
             Ok(())
         }
 
+        /// Record a fullscreen render pass on `cb`: the same draw as
+        /// [`dispatch_render`](Self::dispatch_render).
+        pub fn encode_render_pass<'t>(
+            &self,
+            cb: &CommandBuffer,
+            pipeline: &RenderPipeline,
+            output_texture: impl Into<RenderTarget<'t>>,
+            pixel_srvs: &[SrvBinding<'_>],
+            pixel_cbufs: &[Option<ID3D11Buffer>],
+            vertex_srvs: &[SrvBinding<'_>],
+            vertex_cbufs: &[Option<ID3D11Buffer>],
+        ) -> Result<()> {
+            self.dispatch_render_on(
+                self.recording_context(cb),
+                pipeline,
+                output_texture.into(),
+                pixel_srvs,
+                pixel_cbufs,
+                vertex_srvs,
+                vertex_cbufs,
+            )
+        }
+
+        /// Record an [`update_constant_buffer`](Self::update_constant_buffer)
+        /// on `cb`, so the passes encoded after it read `data` however the
+        /// buffer is updated before `cb` is committed.
+        pub fn encode_update_constant_buffer(
+            &self,
+            cb: &CommandBuffer,
+            buffer: &ID3D11Buffer,
+            data: &[u8],
+        ) {
+            write_constant_buffer(self.recording_context(cb), buffer, data);
+        }
+
+        /// The context passes encoded on `cb` are issued on.
+        fn recording_context<'c>(&'c self, cb: &'c CommandBuffer) -> &'c ID3D11DeviceContext {
+            cb.context
+                .as_ref()
+                .unwrap_or_else(|| self.command_context())
+        }
+
+        /// Execute the passes recorded on `cb` on the immediate context and
+        /// return a [`PendingWork`] token for them.
+        ///
+        /// Work the context issued before is executed first, so the token
+        /// completes once that has too.
+        pub fn commit(&self, cb: CommandBuffer) -> PendingWork {
+            if let Some(deferred) = &cb.context {
+                self.flush_recording();
+                let mut list: Option<ID3D11CommandList> = None;
+                match unsafe { deferred.FinishCommandList(false, Some(&mut list as *mut _)) } {
+                    Ok(()) => {
+                        if let Some(list) = list {
+                            unsafe { self.device.context().ExecuteCommandList(&list, true) };
+                        }
+                    }
+                    Err(e) => {
+                        gpu_interop::error_limited!("Failed to finish D3D11 command list: {e}")
+                    }
+                }
+            }
+            self.submit()
+        }
+
+        /// Upload `data` into a structured [`GpuBuffer`] via
+        /// `UpdateSubresource`.
+        ///
+        /// `data` must not be larger than the buffer; the call is skipped (and
+        /// an error logged) otherwise.
+        pub fn update_buffer(&self, buffer: &GpuBuffer, data: &[u8]) {
+            if data.len() > buffer.size {
+                gpu_interop::error_limited!(
+                    "update_buffer: data ({} bytes) exceeds buffer size ({} bytes)",
+                    data.len(),
+                    buffer.size,
+                );
+                return;
+            }
+            let dst_box = D3D11_BOX {
+                left: 0,
+                top: 0,
+                front: 0,
+                right: data.len() as u32,
+                bottom: 1,
+                back: 1,
+            };
+            unsafe {
+                self.command_context().UpdateSubresource(
+                    &buffer.dx11_buffer,
+                    0,
+                    Some(&dst_box),
+                    data.as_ptr() as *const _,
+                    0,
+                    0,
+                );
+            }
+        }
+
         /// Map a dynamic constant buffer, copy data into it, and unmap.
         ///
         /// The buffer must have been created with `D3D11_USAGE_DYNAMIC` and
         /// `D3D11_CPU_ACCESS_WRITE` (e.g. via
         /// [`create_dynamic_cbuf`](gpu_interop::dx11::create_dynamic_cbuf)).
         pub fn update_constant_buffer(&self, buffer: &ID3D11Buffer, data: &[u8]) {
-            let ctx = self.device.context();
-            unsafe {
-                let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
-                let hr = ctx.Map(buffer, 0, D3D11_MAP_WRITE_DISCARD, 0, Some(&mut mapped));
-                if hr.is_ok() {
-                    debug_assert!(
-                        data.len() <= mapped.RowPitch as usize,
-                        "update_constant_buffer: data ({} bytes) exceeds mapped region ({} bytes)",
-                        data.len(),
-                        mapped.RowPitch,
-                    );
-                    std::ptr::copy_nonoverlapping(data.as_ptr(), mapped.pData as *mut u8, data.len());
-                    ctx.Unmap(buffer, 0);
-                }
-            }
+            write_constant_buffer(self.command_context(), buffer, data);
         }
     }
 }
@@ -5,6 +5,8 @@
 //! - GL-to-GPU bridge management (Metal via IOSurface, DX11 via
 //!   WGL_NV_DX_interop2)
 //! - Double-buffered pipelining (one frame latency)
+//! - Optional region-of-interest and tiled processing
+//! - Host viewport offsets (hosts rendering into part of a larger target)
 //! - GL state save/restore
 //! - Instance tracking (resource release on instance switch)
 //!
@@ -12,19 +14,67 @@
 //! different plugin instances from the same thread.
 
 use crate::context::GpuContext;
+use crate::fallback::Fallback;
 use crate::plugin::{DrawInput, GpuPlugin};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::color::{MIX, PREMULTIPLY, UNPREMULTIPLY};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::pacing::Presented;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::plugin::{AlphaMode, MissingInput, ScalingPolicy, Tiling};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use ffgl_core::ffi::FFGLTextureStruct;
 use ffgl_core::inputs::GLInput;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use ffgl_core::panic::catch_panic;
 use ffgl_core::FFGLData;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
 use gl::types::{GLenum, GLint, GLuint};
 use gpu_interop::GpuBridge as _;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use gpu_interop::PixelRect;
 use std::cell::RefCell;
-use tracing::error;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use gpu_interop::{error_limited, warn_limited};
 
 // ---------------------------------------------------------------------------
 // GL state save / restore
 // ---------------------------------------------------------------------------
 
+/// Indexed buffer binding points and image units saved per frame. Our own
+/// passes only touch the low slots; saving every slot the driver exposes
+/// would cost dozens of queries per frame.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const SAVED_INDEXED_BINDINGS: u32 = 8;
+
+/// One indexed buffer binding (`glBindBufferRange` arguments).
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct BufferBinding {
+    buffer: GLint,
+    start: i64,
+    size: i64,
+}
+
+/// One image unit binding (`glBindImageTexture` arguments).
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct ImageBinding {
+    texture: GLint,
+    level: GLint,
+    layered: GLint,
+    layer: GLint,
+    access: GLint,
+    format: GLint,
+}
+
 /// Saved GL state that we restore after our raw GL operations.
+///
+/// Uniform buffers need GL 3.1, image units GL 4.2 and shader storage
+/// buffers GL 4.3; on older contexts (macOS stops at 4.1) those fields stay
+/// empty and are not touched on restore.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct SavedGlState {
     pack_buffer: GLint,
     unpack_buffer: GLint,
@@ -35,8 +85,60 @@ struct SavedGlState {
     active_texture: GLint,
     vao: GLint,
     viewport: [GLint; 4],
+    program: GLint,
+    blend: bool,
+    blend_func: [GLint; 4],
+    blend_equation: [GLint; 2],
+    scissor_test: bool,
+    scissor_box: [GLint; 4],
+    uniform_buffer: GLint,
+    uniform_buffers: Vec<BufferBinding>,
+    storage_buffer: GLint,
+    storage_buffers: Vec<BufferBinding>,
+    image_units: Vec<ImageBinding>,
+}
+
+/// GL version of the current context as `(major, minor)`.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+unsafe fn gl_version() -> (GLint, GLint) {
+    let (mut major, mut minor) = (0, 0);
+    gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+    gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    (major, minor)
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+unsafe fn save_buffer_bindings(
+    binding: GLenum,
+    start: GLenum,
+    size: GLenum,
+    max: GLenum,
+) -> Vec<BufferBinding> {
+    let mut count = 0;
+    gl::GetIntegerv(max, &mut count);
+    (0..(count.max(0) as u32).min(SAVED_INDEXED_BINDINGS))
+        .map(|i| {
+            let mut b = BufferBinding::default();
+            gl::GetIntegeri_v(binding, i, &mut b.buffer);
+            gl::GetInteger64i_v(start, i, &mut b.start);
+            gl::GetInteger64i_v(size, i, &mut b.size);
+            b
+        })
+        .collect()
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+unsafe fn restore_buffer_bindings(target: GLenum, bindings: &[BufferBinding]) {
+    for (i, b) in bindings.iter().enumerate() {
+        if b.buffer != 0 && b.size > 0 {
+            gl::BindBufferRange(target, i as GLuint, b.buffer as GLuint, b.start as _, b.size as _);
+        } else {
+            gl::BindBufferBase(target, i as GLuint, b.buffer as GLuint);
+        }
+    }
 }
 
+#[cfg(any(target_os = "macos", target_os = "windows"))]
 impl SavedGlState {
     unsafe fn save() -> Self {
         let mut s = Self {
@@ -49,6 +151,17 @@ impl SavedGlState {
             active_texture: 0,
             vao: 0,
             viewport: [0; 4],
+            program: 0,
+            blend: false,
+            blend_func: [0; 4],
+            blend_equation: [0; 2],
+            scissor_test: false,
+            scissor_box: [0; 4],
+            uniform_buffer: 0,
+            uniform_buffers: Vec::new(),
+            storage_buffer: 0,
+            storage_buffers: Vec::new(),
+            image_units: Vec::new(),
         };
         gl::GetIntegerv(gl::PIXEL_PACK_BUFFER_BINDING, &mut s.pack_buffer);
         gl::GetIntegerv(gl::PIXEL_UNPACK_BUFFER_BINDING, &mut s.unpack_buffer);
@@ -59,6 +172,53 @@ impl SavedGlState {
         gl::GetIntegerv(gl::ACTIVE_TEXTURE, &mut s.active_texture);
         gl::GetIntegerv(gl::VERTEX_ARRAY_BINDING, &mut s.vao);
         gl::GetIntegerv(gl::VIEWPORT, s.viewport.as_mut_ptr());
+
+        gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut s.program);
+        s.blend = gl::IsEnabled(gl::BLEND) == gl::TRUE;
+        gl::GetIntegerv(gl::BLEND_SRC_RGB, &mut s.blend_func[0]);
+        gl::GetIntegerv(gl::BLEND_DST_RGB, &mut s.blend_func[1]);
+        gl::GetIntegerv(gl::BLEND_SRC_ALPHA, &mut s.blend_func[2]);
+        gl::GetIntegerv(gl::BLEND_DST_ALPHA, &mut s.blend_func[3]);
+        gl::GetIntegerv(gl::BLEND_EQUATION_RGB, &mut s.blend_equation[0]);
+        gl::GetIntegerv(gl::BLEND_EQUATION_ALPHA, &mut s.blend_equation[1]);
+        s.scissor_test = gl::IsEnabled(gl::SCISSOR_TEST) == gl::TRUE;
+        gl::GetIntegerv(gl::SCISSOR_BOX, s.scissor_box.as_mut_ptr());
+
+        let version = gl_version();
+        if version >= (3, 1) {
+            gl::GetIntegerv(gl::UNIFORM_BUFFER_BINDING, &mut s.uniform_buffer);
+            s.uniform_buffers = save_buffer_bindings(
+                gl::UNIFORM_BUFFER_BINDING,
+                gl::UNIFORM_BUFFER_START,
+                gl::UNIFORM_BUFFER_SIZE,
+                gl::MAX_UNIFORM_BUFFER_BINDINGS,
+            );
+        }
+        if version >= (4, 3) {
+            gl::GetIntegerv(gl::SHADER_STORAGE_BUFFER_BINDING, &mut s.storage_buffer);
+            s.storage_buffers = save_buffer_bindings(
+                gl::SHADER_STORAGE_BUFFER_BINDING,
+                gl::SHADER_STORAGE_BUFFER_START,
+                gl::SHADER_STORAGE_BUFFER_SIZE,
+                gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS,
+            );
+        }
+        if version >= (4, 2) {
+            let mut units = 0;
+            gl::GetIntegerv(gl::MAX_IMAGE_UNITS, &mut units);
+            s.image_units = (0..(units.max(0) as u32).min(SAVED_INDEXED_BINDINGS))
+                .map(|i| {
+                    let mut b = ImageBinding::default();
+                    gl::GetIntegeri_v(gl::IMAGE_BINDING_NAME, i, &mut b.texture);
+                    gl::GetIntegeri_v(gl::IMAGE_BINDING_LEVEL, i, &mut b.level);
+                    gl::GetIntegeri_v(gl::IMAGE_BINDING_LAYERED, i, &mut b.layered);
+                    gl::GetIntegeri_v(gl::IMAGE_BINDING_LAYER, i, &mut b.layer);
+                    gl::GetIntegeri_v(gl::IMAGE_BINDING_ACCESS, i, &mut b.access);
+                    gl::GetIntegeri_v(gl::IMAGE_BINDING_FORMAT, i, &mut b.format);
+                    b
+                })
+                .collect();
+        }
         s
     }
 
@@ -77,6 +237,69 @@ impl SavedGlState {
             self.viewport[2],
             self.viewport[3],
         );
+
+        gl::UseProgram(self.program as GLuint);
+        if self.blend {
+            gl::Enable(gl::BLEND);
+        } else {
+            gl::Disable(gl::BLEND);
+        }
+        gl::BlendFuncSeparate(
+            self.blend_func[0] as GLenum,
+            self.blend_func[1] as GLenum,
+            self.blend_func[2] as GLenum,
+            self.blend_func[3] as GLenum,
+        );
+        gl::BlendEquationSeparate(
+            self.blend_equation[0] as GLenum,
+            self.blend_equation[1] as GLenum,
+        );
+        if self.scissor_test {
+            gl::Enable(gl::SCISSOR_TEST);
+        } else {
+            gl::Disable(gl::SCISSOR_TEST);
+        }
+        gl::Scissor(
+            self.scissor_box[0],
+            self.scissor_box[1],
+            self.scissor_box[2],
+            self.scissor_box[3],
+        );
+
+        // Indexed binds also change the generic binding point, so it is
+        // restored after them.
+        if !self.uniform_buffers.is_empty() {
+            restore_buffer_bindings(gl::UNIFORM_BUFFER, &self.uniform_buffers);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.uniform_buffer as GLuint);
+        }
+        if !self.storage_buffers.is_empty() {
+            restore_buffer_bindings(gl::SHADER_STORAGE_BUFFER, &self.storage_buffers);
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, self.storage_buffer as GLuint);
+        }
+        for (i, b) in self.image_units.iter().enumerate() {
+            gl::BindImageTexture(
+                i as GLuint,
+                b.texture as GLuint,
+                b.level,
+                b.layered as u8,
+                b.layer,
+                b.access as GLenum,
+                b.format as GLenum,
+            );
+        }
+    }
+
+    /// Restore the state and, in debug builds, check that it took.
+    unsafe fn restore_checked(&self) {
+        self.restore();
+
+        #[cfg(debug_assertions)]
+        {
+            let after = Self::save();
+            if after != *self {
+                tracing::warn!(before = ?self, after = ?after, "GL state not fully restored");
+            }
+        }
     }
 }
 
@@ -94,22 +317,198 @@ fn is_context_current() -> bool {
     unsafe { !gl::GetString(gl::VERSION).is_null() }
 }
 
-fn passthrough(glium_ctx: &mut ffgl_glium::FFGLGlium, data: &FFGLData, frame_data: GLInput<'_>) {
-    use glium::Surface;
-    let (width, height) = data.get_dimensions();
-    glium_ctx.draw(
-        (width, height),
-        (width, height),
-        frame_data,
-        &mut |target, textures| {
-            if let Some(input_texture) = textures.first() {
-                input_texture
-                    .as_surface()
-                    .fill(target, glium::uniforms::MagnifySamplerFilter::Linear);
+fn passthrough(fallback: &mut dyn Fallback, data: &FFGLData, frame_data: GLInput<'_>) {
+    fallback.passthrough(data, frame_data);
+    crate::gl_leak::check("passthrough");
+}
+
+// ---------------------------------------------------------------------------
+// Processing size
+// ---------------------------------------------------------------------------
+
+/// Processing size for `region` at `res_scale` under `policy`, plus the part
+/// of `region` mapped to it. Anything in `region` outside the mapped
+/// rectangle is letterboxed.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn processing_size(
+    policy: ScalingPolicy,
+    region: PixelRect,
+    res_scale: f32,
+) -> (u32, u32, PixelRect) {
+    let (w, h) = (region.width, region.height);
+    let scaled_w = ((w as f32 * res_scale) as u32).max(2);
+    let scaled_h = ((h as f32 * res_scale) as u32).max(2);
+
+    match policy {
+        ScalingPolicy::Stretch => (scaled_w, scaled_h, region),
+        ScalingPolicy::EvenDimensions => ((scaled_w & !1).max(2), (scaled_h & !1).max(2), region),
+        ScalingPolicy::PreserveAspect => {
+            fn gcd(a: u32, b: u32) -> u32 {
+                if b == 0 {
+                    a
+                } else {
+                    gcd(b, a % b)
+                }
+            }
+            let g = gcd(w, h).max(1);
+            let (aspect_w, aspect_h) = (w / g, h / g);
+            let k = (g as f32 * res_scale) as u32;
+            if k >= 1 && aspect_w * k >= 2 && aspect_h * k >= 2 {
+                return (aspect_w * k, aspect_h * k, region);
             }
-            Ok(())
-        },
+            // The exact ratio has no representable size at this scale: fit
+            // the largest centred rectangle with the processing aspect.
+            let (mw, mh) = if w as u64 * scaled_h as u64 >= h as u64 * scaled_w as u64 {
+                (((h as u64 * scaled_w as u64) / scaled_h as u64) as u32, h)
+            } else {
+                (w, ((w as u64 * scaled_h as u64) / scaled_w as u64) as u32)
+            };
+            (scaled_w, scaled_h, centered(region, mw.max(1), mh.max(1)))
+        }
+        ScalingPolicy::IntegerDivisor => {
+            let n = ((1.0 / res_scale - 1e-3).ceil() as u32).max(1);
+            let pw = (w / n).max(2);
+            let ph = (h / n).max(2);
+            (pw, ph, centered(region, (pw * n).min(w), (ph * n).min(h)))
+        }
+    }
+}
+
+/// A `width` x `height` rectangle centred in `outer`.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn centered(outer: PixelRect, width: u32, height: u32) -> PixelRect {
+    PixelRect::new(
+        outer.x + (outer.width - width) / 2,
+        outer.y + (outer.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Map `rect`, in viewport pixels, onto the image part of the host texture.
+///
+/// The image occupies `Width` x `Height` at the texture origin, which need not
+/// match the viewport; beyond that the texture may be padded up to
+/// `HardwareWidth` x `HardwareHeight` and the padding must not be read.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn texture_rect(
+    rect: PixelRect,
+    (viewport_w, viewport_h): (u32, u32),
+    texture: &FFGLTextureStruct,
+) -> PixelRect {
+    let (tex_w, tex_h) = (texture.Width, texture.Height);
+    if (tex_w, tex_h) == (viewport_w, viewport_h) || tex_w == 0 || tex_h == 0 {
+        return rect;
+    }
+    let sx = tex_w as f32 / viewport_w.max(1) as f32;
+    let sy = tex_h as f32 / viewport_h.max(1) as f32;
+    let x0 = (rect.x as f32 * sx).round() as u32;
+    let y0 = (rect.y as f32 * sy).round() as u32;
+    let x1 = ((rect.x + rect.width) as f32 * sx).round() as u32;
+    let y1 = ((rect.y + rect.height) as f32 * sy).round() as u32;
+    PixelRect::new(x0, y0, (x1 - x0).max(1), (y1 - y0).max(1))
+}
+
+/// Clear `rect` of `fbo` to transparent black, leaving scissor and clear
+/// color state as found.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+unsafe fn clear_host_region(fbo: GLuint, rect: PixelRect) {
+    let scissor_enabled = gl::IsEnabled(gl::SCISSOR_TEST) == gl::TRUE;
+    let mut scissor_box: [GLint; 4] = [0; 4];
+    let mut clear_color: [f32; 4] = [0.0; 4];
+    gl::GetIntegerv(gl::SCISSOR_BOX, scissor_box.as_mut_ptr());
+    gl::GetFloatv(gl::COLOR_CLEAR_VALUE, clear_color.as_mut_ptr());
+
+    gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, fbo);
+    gl::Enable(gl::SCISSOR_TEST);
+    gl::Scissor(
+        rect.x as GLint,
+        rect.y as GLint,
+        rect.width as GLint,
+        rect.height as GLint,
     );
+    gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+    gl::Clear(gl::COLOR_BUFFER_BIT);
+
+    gl::ClearColor(clear_color[0], clear_color[1], clear_color[2], clear_color[3]);
+    gl::Scissor(scissor_box[0], scissor_box[1], scissor_box[2], scissor_box[3]);
+    if !scissor_enabled {
+        gl::Disable(gl::SCISSOR_TEST);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tiling
+// ---------------------------------------------------------------------------
+
+/// One tile of a tiled frame.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+struct Tile {
+    /// Host region blitted into the bridge (the tile plus overlap).
+    source: PixelRect,
+    /// Processing width of `source`.
+    width: u32,
+    /// Processing height of `source`.
+    height: u32,
+    /// Part of the processed tile written back, in processing pixels.
+    crop: PixelRect,
+    /// Host region written back (the tile without overlap).
+    target: PixelRect,
+}
+
+/// Split `region` into tiles, or `None` if it fits in a single tile.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn plan_tiles(
+    region: PixelRect,
+    tiling: &Tiling,
+    frame_width: u32,
+    frame_height: u32,
+    res_scale: f32,
+) -> Option<Vec<Tile>> {
+    let tile_w = tiling.tile_width.max(16);
+    let tile_h = tiling.tile_height.max(16);
+    if region.width <= tile_w && region.height <= tile_h {
+        return None;
+    }
+
+    let scale = |v: u32| ((v as f32 * res_scale) as u32).max(2);
+    let mut tiles = Vec::new();
+    let mut y = region.y;
+    while y < region.y + region.height {
+        let th = tile_h.min(region.y + region.height - y);
+        let mut x = region.x;
+        while x < region.x + region.width {
+            let tw = tile_w.min(region.x + region.width - x);
+            let target = PixelRect::new(x, y, tw, th);
+            let x0 = x.saturating_sub(tiling.overlap);
+            let y0 = y.saturating_sub(tiling.overlap);
+            let source = PixelRect::new(
+                x0,
+                y0,
+                (x + tw + tiling.overlap).min(frame_width) - x0,
+                (y + th + tiling.overlap).min(frame_height) - y0,
+            );
+            let (width, height) = (scale(source.width), scale(source.height));
+            let crop_x = (((x - x0) as f32 * res_scale) as u32).min(width - 1);
+            let crop_y = (((y - y0) as f32 * res_scale) as u32).min(height - 1);
+            let crop = PixelRect::new(
+                crop_x,
+                crop_y,
+                scale(tw).min(width - crop_x),
+                scale(th).min(height - crop_y),
+            );
+            tiles.push(Tile {
+                source,
+                width,
+                height,
+                crop,
+                target,
+            });
+            x += tw;
+        }
+        y += th;
+    }
+    Some(tiles)
 }
 
 // ---------------------------------------------------------------------------
@@ -119,22 +518,69 @@ fn passthrough(glium_ctx: &mut ffgl_glium::FFGLGlium, data: &FFGLData, frame_dat
 #[cfg(target_os = "macos")]
 mod metal_draw {
     use super::*;
+    use crate::dispatch::PendingWork;
     use gpu_interop::metal::GlMetalBridge;
+    use gpu_interop::{BridgeMemory, GlContextId, TextureFormat};
+    use objc2::runtime::ProtocolObject;
+    use objc2::Message;
+    use objc2_metal::MTLTexture;
 
     thread_local! {
         static GPU_CTX: RefCell<Option<GpuContext>> = const { RefCell::new(None) };
         static BRIDGE: RefCell<Option<GlMetalBridge>> = const { RefCell::new(None) };
         static LAST_INSTANCE_ID: RefCell<Option<u64>> = const { RefCell::new(None) };
+        /// Set by [`discard_in_flight`](super::discard_in_flight); the next
+        /// frame doesn't present the in-flight result.
+        static DISCARD_IN_FLIGHT: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
         static GPU_INITIALIZED: RefCell<bool> = const { RefCell::new(false) };
+        /// Host region the in-flight dispatch was taken from; its result is
+        /// blitted back there on the next frame, or held there while the
+        /// input is missing. Empty after a tiled frame.
+        static LAST_REGION: RefCell<PixelRect> = const {
+            RefCell::new(PixelRect { x: 0, y: 0, width: 0, height: 0 })
+        };
     }
 
     fn release_resources() {
-        BRIDGE.with(|cell| {
-            if let Some(bridge) = cell.borrow_mut().as_mut() {
-                bridge.cleanup();
-            }
+        objc2::rc::autoreleasepool(|_pool| {
+            BRIDGE.with(|cell| {
+                if let Some(bridge) = cell.borrow_mut().as_mut() {
+                    bridge.cleanup();
+                }
+            });
         });
         GPU_INITIALIZED.with(|cell| *cell.borrow_mut() = false);
+        LAST_REGION.with(|cell| *cell.borrow_mut() = PixelRect::default());
+    }
+
+    /// Present the last untiled frame's output again, for
+    /// [`MissingInput::HoldLastFrame`]. Returns `false` if there is none.
+    fn hold_last_frame(host_fbo: GLuint, use_bilinear: bool) -> bool {
+        let region = LAST_REGION.with(|cell| *cell.borrow());
+        if region.width == 0 || region.height == 0 {
+            return false;
+        }
+        BRIDGE.with(|cell| {
+            let mut bridge_opt = cell.borrow_mut();
+            let Some(bridge) = bridge_opt.as_mut() else {
+                return false;
+            };
+            // The front output holds the last dispatch, presented or not.
+            bridge.wait_for_pending();
+            let (width, height) = bridge.dimensions();
+            match bridge.blit_output_to_target_region(
+                host_fbo,
+                PixelRect::full(width, height),
+                region,
+                use_bilinear,
+            ) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn_limited!("Failed to blit held frame: {e}");
+                    false
+                }
+            }
+        })
     }
 
     pub fn ensure_instance_resources(instance_id: u64) {
@@ -142,22 +588,78 @@ mod metal_draw {
             let mut id = cell.borrow_mut();
             if *id != Some(instance_id) {
                 release_resources();
+                if let Some(previous) = *id {
+                    crate::budget::record_bridge(previous, BridgeMemory::default(), None);
+                }
                 *id = Some(instance_id);
             }
         });
     }
 
+    /// `instance_id` moved to this thread. If it drew here before, what it
+    /// left is stale: release it so the next frame rebuilds.
+    pub fn thread_changed(instance_id: u64) {
+        LAST_INSTANCE_ID.with(|cell| {
+            let mut id = cell.borrow_mut();
+            if *id == Some(instance_id) {
+                release_resources();
+                *id = None;
+            }
+        });
+    }
+
+    pub fn discard_in_flight(instance_id: u64) {
+        if LAST_INSTANCE_ID.with(|cell| *cell.borrow() == Some(instance_id)) {
+            DISCARD_IN_FLIGHT.with(|cell| cell.set(true));
+        }
+    }
+
+    /// Block until all work committed to the context's queue has completed,
+    /// with no timeout. Metal runs command buffers in submission order, so an
+    /// empty one completes after everything before it.
+    fn wait_until_idle(ctx: &GpuContext) {
+        match ctx.create_command_buffer() {
+            Ok(cb) => ctx.commit(cb).wait(),
+            Err(e) => error_limited!("Failed to wait for Metal work: {e}"),
+        }
+    }
+
+    /// Record the memory this thread's bridge holds for `instance_id`, in
+    /// the budget registry and the context's memory report.
+    pub fn record_memory(instance_id: u64) {
+        let (memory, format) = BRIDGE.with(|cell| match cell.borrow().as_ref() {
+            Some(bridge) => (bridge.memory_usage(), Some(bridge.format())),
+            None => (BridgeMemory::default(), None),
+        });
+        GPU_CTX.with(|cell| {
+            if let Some(ctx) = cell.borrow().as_ref() {
+                ctx.memory.bridge.set(memory);
+            }
+        });
+        crate::budget::record_bridge(instance_id, memory, format);
+    }
+
     pub fn validate_gl_state() -> bool {
         clear_gl_errors();
         if !is_context_current() {
             return false;
         }
+        let context = GlContextId::current();
         let mut need_release = false;
         BRIDGE.with(|cell| {
-            if let Some(bridge) = cell.borrow().as_ref() {
-                if !bridge.is_valid() {
-                    need_release = true;
-                }
+            let mut slot = cell.borrow_mut();
+            let Some(bridge) = slot.as_mut() else {
+                return;
+            };
+            if bridge.gl_context() != context {
+                // The host recreated its GL context; the bridge's GL names
+                // and interop registrations belong to the old one.
+                tracing::warn!("Host GL context changed, rebuilding GPU bridge");
+                bridge.forget_gl_objects();
+                *slot = None;
+                need_release = true;
+            } else if !bridge.is_valid() {
+                need_release = true;
             }
         });
         if need_release {
@@ -167,39 +669,123 @@ mod metal_draw {
         true
     }
 
+    /// Run the framework's color conversion pass on a bridge texture,
+    /// blending in `dry` for [`MIX`]. Returns the submitted work, if any.
+    fn convert_color(
+        ctx: &GpuContext,
+        texture: Option<&ProtocolObject<dyn MTLTexture>>,
+        ops: u32,
+        dry: Option<(&ProtocolObject<dyn MTLTexture>, f32)>,
+    ) -> Option<PendingWork> {
+        match ctx.convert_color(texture?, ops, dry) {
+            Ok(work) => work,
+            Err(e) => {
+                error_limited!(pass = "color", "Color conversion failed: {e}");
+                None
+            }
+        }
+    }
+
     pub fn draw<P: GpuPlugin>(
         plugin: &mut P,
         instance_id: u64,
-        glium: &mut ffgl_glium::FFGLGlium,
+        fallback: &mut dyn Fallback,
         data: &FFGLData,
         frame_data: GLInput<'_>,
         frame_counter: u64,
         internal_resolution: f32,
         filter_quality: f32,
+        pool_capacity: usize,
         metallib_bytes: &[u8],
-    ) {
+    ) -> bool {
+        // Context and bridge setup, resource release and the held-frame path
+        // create Objective-C objects too, so the whole frame runs in a pool.
+        let audit = crate::objc_audit::begin();
+        let drawn = objc2::rc::autoreleasepool(|_pool| {
+            draw_frame(
+                plugin,
+                instance_id,
+                fallback,
+                data,
+                frame_data,
+                frame_counter,
+                internal_resolution,
+                filter_quality,
+                pool_capacity,
+                metallib_bytes,
+            )
+        });
+        crate::objc_audit::end(instance_id, audit);
+        drawn
+    }
+
+    fn draw_frame<P: GpuPlugin>(
+        plugin: &mut P,
+        instance_id: u64,
+        fallback: &mut dyn Fallback,
+        data: &FFGLData,
+        frame_data: GLInput<'_>,
+        frame_counter: u64,
+        internal_resolution: f32,
+        filter_quality: f32,
+        pool_capacity: usize,
+        metallib_bytes: &[u8],
+    ) -> bool {
         ensure_instance_resources(instance_id);
         if !validate_gl_state() {
-            passthrough(glium, data, frame_data);
-            return;
+            passthrough(fallback, data, frame_data);
+            return false;
         }
 
         let (width, height) = data.get_dimensions();
 
+        // Restrict processing to the plugin's region of interest, if any.
+        let roi = plugin
+            .processing_rect(data)
+            .and_then(|r| r.clamped(width, height))
+            .filter(|r| !r.is_full(width, height));
+        let region = roi.unwrap_or(PixelRect::full(width, height));
+        // The host may render into a sub-rectangle of its framebuffer; only
+        // rectangles in the host FBO are offset, the input texture is not.
+        let viewport = PixelRect::new(data.viewport.x, data.viewport.y, width, height);
+
         // Compute processing dimensions from internal_resolution scale factor.
         let res_scale = internal_resolution.clamp(0.125, 1.0);
-        let proc_width = ((width as f32 * res_scale) as u32).max(2);
-        let proc_height = ((height as f32 * res_scale) as u32).max(2);
+        let (proc_width, proc_height, mapped) =
+            processing_size(plugin.scaling_policy(), region, res_scale);
+        let target = mapped.offset(viewport.x, viewport.y);
         let use_bilinear = filter_quality >= 0.5;
+        let color_space = plugin.color_space();
+        let surface_options = plugin.surface_options();
+        let staleness_window = plugin.staleness_window();
+        let offline = plugin.offline_rendering() || unsafe { crate::quirks::detect() }.synchronous;
+        let texture_format = plugin.texture_format();
+        let (input_ops, mut output_ops) = match plugin.alpha_mode() {
+            AlphaMode::Passthrough => (0, 0),
+            AlphaMode::Straight => (UNPREMULTIPLY, PREMULTIPLY),
+        };
+        let mix = plugin.mix().clamp(0.0, 1.0);
+        if mix < 1.0 {
+            output_ops |= MIX;
+        }
+        let tiles = plugin
+            .tiling()
+            .and_then(|t| plan_tiles(region, &t, width, height, res_scale));
 
         // Ensure GPU context is initialized
         let ctx_available = GPU_CTX.with(|cell| {
             let mut ctx = cell.borrow_mut();
             if ctx.is_none() {
                 match GpuContext::new(metallib_bytes) {
-                    Ok(c) => *ctx = Some(c),
+                    Ok(c) => {
+                        unsafe { crate::device_info::check_same_adapter(&c) };
+                        *ctx = Some(c);
+                    }
                     Err(e) => {
-                        error!("Failed to create GPU context: {e}");
+                        error_limited!(
+                            instance = instance_id,
+                            "Failed to create GPU context: {e}"
+                        );
                         return false;
                     }
                 }
@@ -208,22 +794,58 @@ mod metal_draw {
         });
 
         if !ctx_available {
-            passthrough(glium, data, frame_data);
-            return;
+            passthrough(fallback, data, frame_data);
+            return false;
         }
 
         // Get host FBO and texture
         let host_fbo = frame_data.host;
-        let tex_id = match frame_data.textures.first() {
-            Some(t) => t.Handle,
-            None => {
-                passthrough(glium, data, frame_data);
-                return;
+        let host_texture = match (frame_data.texture(0), plugin.missing_input()) {
+            (Some(t), _) => Some(*t),
+            (None, MissingInput::Source) => None,
+            (None, MissingInput::Passthrough) => {
+                passthrough(fallback, data, frame_data);
+                return false;
+            }
+            (None, policy) => {
+                let saved_state = unsafe { SavedGlState::save() };
+                let held = policy == MissingInput::HoldLastFrame
+                    && hold_last_frame(host_fbo, use_bilinear);
+                if !held {
+                    unsafe { clear_host_region(host_fbo, viewport) };
+                }
+                unsafe {
+                    saved_state.restore_checked();
+                }
+                crate::gl_leak::check("missing input");
+                return false;
             }
         };
 
+        // Outside the region of interest the output is the untouched input,
+        // or nothing when there is none.
+        if roi.is_some() && host_texture.is_some() {
+            passthrough(
+                fallback,
+                data,
+                GLInput {
+                    textures: frame_data.textures,
+                    host: frame_data.host,
+                },
+            );
+        }
+
         let saved_state = unsafe { SavedGlState::save() };
 
+        if roi.is_some() && host_texture.is_none() {
+            unsafe { clear_host_region(host_fbo, viewport) };
+        }
+
+        // Letterbox bars left by the scaling policy are cleared.
+        if mapped != region {
+            unsafe { clear_host_region(host_fbo, region.offset(viewport.x, viewport.y)) };
+        }
+
         let success = objc2::rc::autoreleasepool(|_pool| {
             GPU_CTX.with(|ctx_cell| {
                 let ctx_ref = ctx_cell.borrow();
@@ -234,23 +856,10 @@ mod metal_draw {
                     {
                         let mut bridge_opt = bridge_cell.borrow_mut();
                         if bridge_opt.is_none() {
-                            // Retain-clone the Metal device to pass ownership
-                            // to the bridge. The device is a protocol object
-                            // so we use Retained::retain on its raw pointer.
-                            let device_ref = ctx.device.device();
-                            let device_ptr = device_ref
-                                as *const objc2::runtime::ProtocolObject<
-                                    dyn objc2_metal::MTLDevice,
-                                >
-                                as *mut objc2::runtime::ProtocolObject<
-                                    dyn objc2_metal::MTLDevice,
-                                >;
-                            // SAFETY: device_ptr points to a valid, live ObjC
-                            // object. Retained::retain increments its refcount.
-                            let device_retained =
-                                unsafe { objc2::rc::Retained::retain(device_ptr) }
-                                    .expect("device pointer must be non-null");
-                            *bridge_opt = Some(GlMetalBridge::new(device_retained));
+                            // The bridge shares the context's device; retaining
+                            // it is a refcount bump, nothing is autoreleased.
+                            let device = ctx.device.device().retain();
+                            *bridge_opt = Some(GlMetalBridge::new(device));
                         }
                     }
 
@@ -258,15 +867,19 @@ mod metal_draw {
                     let init_ok = GPU_INITIALIZED.with(|cell| {
                         let mut initialized = cell.borrow_mut();
                         if !*initialized {
-                            match plugin.gpu_init(ctx) {
-                                Ok(()) => {
+                            match catch_panic("GpuPlugin::gpu_init", || plugin.gpu_init(ctx)) {
+                                Some(Ok(())) => {
                                     *initialized = true;
                                     true
                                 }
-                                Err(e) => {
-                                    error!("GpuPlugin::gpu_init failed: {e}");
+                                Some(Err(e)) => {
+                                    error_limited!(
+                                        instance = instance_id,
+                                        "GpuPlugin::gpu_init failed: {e}"
+                                    );
                                     false
                                 }
+                                None => false,
                             }
                         } else {
                             true
@@ -281,93 +894,281 @@ mod metal_draw {
                     // Single mutable borrow for all bridge operations.
                     let mut bridge_opt = bridge_cell.borrow_mut();
                     let bridge = bridge_opt.as_mut().unwrap();
+                    bridge.set_color_space(color_space);
+                    bridge.set_surface_options(surface_options);
+                    bridge.set_pool_capacity(pool_capacity);
+                    bridge.set_staleness_window(staleness_window);
+                    let format = texture_format.unwrap_or_else(|| bridge.preferred_format());
+
+                    ctx.intermediates.next_frame();
+                    let discarded = DISCARD_IN_FLIGHT.with(std::cell::Cell::take);
+
+                    if let Some(tiles) = &tiles {
+                        // Tiled frames are processed synchronously, one tile
+                        // at a time, so any in-flight result is dropped.
+                        let wait_start = std::time::Instant::now();
+                        bridge.wait_for_previous();
+                        let mut gpu_wait = wait_start.elapsed();
+                        let (tile_w, tile_h) = tiles
+                            .iter()
+                            .fold((2, 2), |(w, h), t| (w.max(t.width), h.max(t.height)));
+                        if let Err(e) = bridge.ensure_dimensions_with_format(tile_w, tile_h, format)
+                        {
+                            error_limited!(
+                                instance = instance_id,
+                                frame = frame_counter,
+                                memory = %ctx.memory_report(),
+                                "Failed to ensure bridge dimensions: {e}"
+                            );
+                            return false;
+                        }
+
+                        for tile in tiles {
+                            let filled = match &host_texture {
+                                Some(host_texture) => bridge.blit_input_from_host_region(
+                                    host_texture.Handle,
+                                    texture_rect(tile.source, (width, height), host_texture),
+                                    tile.width,
+                                    tile.height,
+                                    use_bilinear,
+                                ),
+                                None => bridge.clear_input(),
+                            };
+                            if let Err(e) = filled {
+                                error_limited!(
+                                    instance = instance_id,
+                                    frame = frame_counter,
+                                    "Failed to blit input tile: {e}"
+                                );
+                                return false;
+                            }
+                            convert_color(ctx, bridge.input_metal_texture(), input_ops, None);
+
+                            // Retained for the same reason as in the untiled path.
+                            let (Some(input_texture), Some(output_texture)) = (
+                                bridge.input_metal_texture().map(|t| t.retain()),
+                                bridge.output_metal_texture().map(|t| t.retain()),
+                            ) else {
+                                return false;
+                            };
+
+                            let mut draw_input = DrawInput {
+                                input: &input_texture,
+                                output: &output_texture,
+                                width: tile.width,
+                                height: tile.height,
+                                roi: Some(tile.source),
+                                output_size: (tile.source.width, tile.source.height),
+                                viewport,
+                                host_textures: frame_data.textures,
+                                bridge: &mut *bridge,
+                                ctx,
+                                has_back_output: false,
+                            };
+
+                            let drawn = catch_panic("GpuPlugin::gpu_draw", || {
+                                plugin.gpu_draw(ctx, &mut draw_input, data, frame_counter)
+                            });
+                            if drawn.is_none() {
+                                return false;
+                            }
 
-                    if let Err(e) = bridge.ensure_dimensions(proc_width, proc_height) {
-                        error!("Failed to ensure bridge dimensions: {e}");
+                            // Metal runs command buffers in submission order, so waiting
+                            // on the conversion also covers the plugin's work.
+                            let dry = bridge.input_metal_texture().map(|t| (t, mix));
+                            let converted =
+                                convert_color(ctx, bridge.output_metal_texture(), output_ops, dry);
+                            if let Some(work) = converted {
+                                bridge.store_command_buffer(work.into_command_buffer());
+                            }
+                            bridge.mark_dispatch(frame_counter);
+                            let wait_start = std::time::Instant::now();
+                            if offline {
+                                wait_until_idle(ctx);
+                            }
+                            bridge.wait_for_previous();
+                            gpu_wait += wait_start.elapsed();
+                            if let Err(e) = bridge.blit_output_to_target_region(
+                                host_fbo,
+                                tile.crop,
+                                tile.target.offset(viewport.x, viewport.y),
+                                use_bilinear,
+                            ) {
+                                error_limited!(
+                                    instance = instance_id,
+                                    frame = frame_counter,
+                                    "Failed to blit output tile: {e}"
+                                );
+                                return false;
+                            }
+                        }
+
+                        LAST_REGION.with(|cell| *cell.borrow_mut() = PixelRect::default());
+                        crate::pacing::record_frame(
+                            instance_id,
+                            frame_counter,
+                            Presented::Tiled,
+                            gpu_wait,
+                        );
+                        return true;
+                    }
+
+                    if let Err(e) =
+                        bridge.ensure_dimensions_with_format(proc_width, proc_height, format)
+                    {
+                        error_limited!(
+                            instance = instance_id,
+                            frame = frame_counter,
+                            memory = %ctx.memory_report(),
+                            "Failed to ensure bridge dimensions: {e}"
+                        );
                         return false;
                     }
 
-                    let has_prev = bridge.has_result_ready(frame_counter);
+                    let mut has_prev =
+                        !discarded && !offline && bridge.has_result_ready(frame_counter);
 
+                    let wait_start = std::time::Instant::now();
                     bridge.wait_for_previous();
+                    let mut gpu_wait = wait_start.elapsed();
 
                     if has_prev {
                         bridge.swap();
-                        bridge.blit_back_output_to_target_scaled(
+                        let prev_region = LAST_REGION.with(|cell| *cell.borrow());
+                        if let Err(e) = bridge.blit_back_output_to_target_region(
                             host_fbo,
+                            PixelRect::full(proc_width, proc_height),
+                            prev_region,
+                            use_bilinear,
+                        ) {
+                            // Present this frame synchronously instead.
+                            warn_limited!(
+                                instance = instance_id,
+                                frame = frame_counter,
+                                "Failed to blit previous result: {e}"
+                            );
+                            has_prev = false;
+                        }
+                    }
+
+                    let filled = match &host_texture {
+                        Some(host_texture) => bridge.blit_input_from_host_region(
+                            host_texture.Handle,
+                            texture_rect(mapped, (width, height), host_texture),
                             proc_width,
                             proc_height,
-                            width,
-                            height,
                             use_bilinear,
+                        ),
+                        None => bridge.clear_input(),
+                    };
+                    if let Err(e) = filled {
+                        error_limited!(
+                            instance = instance_id,
+                            frame = frame_counter,
+                            "Failed to blit input: {e}"
                         );
+                        return false;
                     }
-
-                    bridge.blit_input_from_host_scaled(
-                        tex_id,
-                        width,
-                        height,
-                        proc_width,
-                        proc_height,
-                        use_bilinear,
-                    );
-
-                    // Extract texture references via raw pointers to avoid
-                    // conflicting borrows (shared refs to textures + mutable
-                    // ref to bridge in DrawInput).
-                    let input_ptr = match bridge.input_metal_texture() {
-                        Some(t) => t as *const _,
-                        None => return false,
-                    };
-                    let output_ptr = match bridge.output_metal_texture() {
-                        Some(t) => t as *const _,
-                        None => return false,
+                    convert_color(ctx, bridge.input_metal_texture(), input_ops, None);
+
+                    // DrawInput borrows the bridge mutably, so it can't also
+                    // borrow the bridge's textures. Retaining them gives it
+                    // references that stay valid whatever gpu_draw does through
+                    // the bridge, even a resize that replaces the pairs.
+                    let (Some(input_texture), Some(output_texture)) = (
+                        bridge.input_metal_texture().map(|t| t.retain()),
+                        bridge.output_metal_texture().map(|t| t.retain()),
+                    ) else {
+                        return false;
                     };
 
-                    // SAFETY: The texture pointers point into bridge's internal
-                    // IOSurface-backed texture pairs. They remain valid for the
-                    // duration of gpu_draw because the bridge is held by this
-                    // scope and no bridge methods that invalidate textures are
-                    // called until after gpu_draw returns.
                     let mut draw_input = DrawInput {
-                        input: unsafe { &*input_ptr },
-                        output: unsafe { &*output_ptr },
+                        input: &input_texture,
+                        output: &output_texture,
                         width: proc_width,
                         height: proc_height,
+                        roi,
+                        output_size: (mapped.width, mapped.height),
+                        viewport,
+                        host_textures: frame_data.textures,
                         bridge,
+                        ctx,
+                        has_back_output: has_prev,
                     };
 
-                    plugin.gpu_draw(ctx, &mut draw_input, data, frame_counter);
+                    let drawn = catch_panic("GpuPlugin::gpu_draw", || {
+                        plugin.gpu_draw(ctx, &mut draw_input, data, frame_counter)
+                    });
+                    if drawn.is_none() {
+                        return false;
+                    }
 
                     // Reclaim bridge from DrawInput for post-draw operations.
                     let bridge = draw_input.bridge;
 
+                    // Metal runs command buffers in submission order, so waiting
+                    // on the conversion also covers the plugin's work.
+                    let dry = bridge.input_metal_texture().map(|t| (t, mix));
+                    let converted =
+                        convert_color(ctx, bridge.output_metal_texture(), output_ops, dry);
+                    if let Some(work) = converted {
+                        bridge.store_command_buffer(work.into_command_buffer());
+                    }
+                    #[cfg(feature = "frame-dump")]
+                    if let Some(dump) = plugin.frame_dump() {
+                        if let (Some(input), Some(output)) =
+                            (bridge.input_metal_texture(), bridge.output_metal_texture())
+                        {
+                            dump.dump_if_requested(ctx, frame_counter, input, output);
+                        }
+                    }
                     bridge.mark_dispatch(frame_counter);
+                    LAST_REGION.with(|cell| *cell.borrow_mut() = target);
 
                     if !has_prev {
+                        let wait_start = std::time::Instant::now();
+                        if offline {
+                            wait_until_idle(ctx);
+                        }
                         bridge.wait_for_pending();
-                        bridge.blit_output_to_target_scaled(
+                        gpu_wait += wait_start.elapsed();
+                        if let Err(e) = bridge.blit_output_to_target_region(
                             host_fbo,
-                            proc_width,
-                            proc_height,
-                            width,
-                            height,
+                            PixelRect::full(proc_width, proc_height),
+                            target,
                             use_bilinear,
-                        );
+                        ) {
+                            error_limited!(
+                                instance = instance_id,
+                                frame = frame_counter,
+                                "Failed to blit output: {e}"
+                            );
+                            return false;
+                        }
                     }
 
+                    let presented = if has_prev {
+                        Presented::Previous
+                    } else if offline {
+                        Presented::Offline
+                    } else {
+                        Presented::Current
+                    };
+                    crate::pacing::record_frame(instance_id, frame_counter, presented, gpu_wait);
                     true
                 })
             })
         });
 
         unsafe {
-            saved_state.restore();
+            saved_state.restore_checked();
         }
+        crate::gl_leak::check("bridge blits / gpu_draw");
 
         if !success {
-            passthrough(glium, data, frame_data);
+            passthrough(fallback, data, frame_data);
         }
+        success
     }
 }
 
@@ -378,13 +1179,25 @@ mod metal_draw {
 #[cfg(target_os = "windows")]
 mod dx11_draw {
     use super::*;
-    use gpu_interop::dx11::GlDx11Bridge;
+    use crate::color::{SRGB_DECODE, SRGB_ENCODE};
+    use gpu_interop::dx11::{Dx11Bridge, GlDx11Bridge, GlPboDx11Bridge};
+    use gpu_interop::{BridgeMemory, ColorSpace, GlContextId, TextureFormat};
+    use windows::Win32::Graphics::Direct3D11::ID3D11Texture2D;
 
     thread_local! {
         static GPU_CTX: RefCell<Option<GpuContext>> = const { RefCell::new(None) };
-        static BRIDGE: RefCell<Option<GlDx11Bridge>> = const { RefCell::new(None) };
+        static BRIDGE: RefCell<Option<Box<dyn Dx11Bridge>>> = const { RefCell::new(None) };
         static LAST_INSTANCE_ID: RefCell<Option<u64>> = const { RefCell::new(None) };
+        /// Set by [`discard_in_flight`](super::discard_in_flight); the next
+        /// frame doesn't present the in-flight result.
+        static DISCARD_IN_FLIGHT: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
         static GPU_INITIALIZED: RefCell<bool> = const { RefCell::new(false) };
+        /// Host region the in-flight dispatch was taken from; its result is
+        /// blitted back there on the next frame, or held there while the
+        /// input is missing. Empty after a tiled frame.
+        static LAST_REGION: RefCell<PixelRect> = const {
+            RefCell::new(PixelRect { x: 0, y: 0, width: 0, height: 0 })
+        };
     }
 
     fn release_resources() {
@@ -394,6 +1207,37 @@ mod dx11_draw {
             }
         });
         GPU_INITIALIZED.with(|cell| *cell.borrow_mut() = false);
+        LAST_REGION.with(|cell| *cell.borrow_mut() = PixelRect::default());
+    }
+
+    /// Present the last untiled frame's output again, for
+    /// [`MissingInput::HoldLastFrame`]. Returns `false` if there is none.
+    fn hold_last_frame(host_fbo: GLuint, use_bilinear: bool) -> bool {
+        let region = LAST_REGION.with(|cell| *cell.borrow());
+        if region.width == 0 || region.height == 0 {
+            return false;
+        }
+        BRIDGE.with(|cell| {
+            let mut bridge_opt = cell.borrow_mut();
+            let Some(bridge) = bridge_opt.as_mut() else {
+                return false;
+            };
+            // The front output holds the last dispatch, presented or not.
+            bridge.wait_for_pending();
+            let (width, height) = bridge.dimensions();
+            match bridge.blit_output_to_target_region(
+                host_fbo,
+                PixelRect::full(width, height),
+                region,
+                use_bilinear,
+            ) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn_limited!("Failed to blit held frame: {e}");
+                    false
+                }
+            }
+        })
     }
 
     pub fn ensure_instance_resources(instance_id: u64) {
@@ -401,22 +1245,74 @@ mod dx11_draw {
             let mut id = cell.borrow_mut();
             if *id != Some(instance_id) {
                 release_resources();
+                if let Some(previous) = *id {
+                    crate::budget::record_bridge(previous, BridgeMemory::default(), None);
+                }
                 *id = Some(instance_id);
             }
         });
     }
 
+    /// `instance_id` moved to this thread. If it drew here before, what it
+    /// left is stale: release it so the next frame rebuilds.
+    pub fn thread_changed(instance_id: u64) {
+        LAST_INSTANCE_ID.with(|cell| {
+            let mut id = cell.borrow_mut();
+            if *id == Some(instance_id) {
+                release_resources();
+                *id = None;
+            }
+        });
+    }
+
+    pub fn discard_in_flight(instance_id: u64) {
+        if LAST_INSTANCE_ID.with(|cell| *cell.borrow() == Some(instance_id)) {
+            DISCARD_IN_FLIGHT.with(|cell| cell.set(true));
+        }
+    }
+
+    /// Block until all work issued through the context has completed, with
+    /// no timeout.
+    fn wait_until_idle(ctx: &GpuContext) {
+        ctx.submit().wait();
+    }
+
+    /// Record the memory this thread's bridge holds for `instance_id`, in
+    /// the budget registry and the context's memory report.
+    pub fn record_memory(instance_id: u64) {
+        let (memory, format) = BRIDGE.with(|cell| match cell.borrow().as_ref() {
+            Some(bridge) => (bridge.memory_usage(), Some(bridge.format())),
+            None => (BridgeMemory::default(), None),
+        });
+        GPU_CTX.with(|cell| {
+            if let Some(ctx) = cell.borrow().as_ref() {
+                ctx.memory.bridge.set(memory);
+            }
+        });
+        crate::budget::record_bridge(instance_id, memory, format);
+    }
+
     pub fn validate_gl_state() -> bool {
         clear_gl_errors();
         if !is_context_current() {
             return false;
         }
+        let context = GlContextId::current();
         let mut need_release = false;
         BRIDGE.with(|cell| {
-            if let Some(bridge) = cell.borrow().as_ref() {
-                if !bridge.is_valid() {
-                    need_release = true;
-                }
+            let mut slot = cell.borrow_mut();
+            let Some(bridge) = slot.as_mut() else {
+                return;
+            };
+            if bridge.gl_context() != context {
+                // The host recreated its GL context; the bridge's GL names
+                // and interop registrations belong to the old one.
+                tracing::warn!("Host GL context changed, rebuilding GPU bridge");
+                bridge.forget_gl_objects();
+                *slot = None;
+                need_release = true;
+            } else if !bridge.is_valid() {
+                need_release = true;
             }
         });
         if need_release {
@@ -426,38 +1322,127 @@ mod dx11_draw {
         true
     }
 
+    /// Size the bridge for the frame. If the interop bridge cannot share
+    /// textures on this driver, switch to the PBO bridge and retry.
+    fn ensure_bridge(
+        slot: &mut Option<Box<dyn Dx11Bridge>>,
+        ctx: &GpuContext,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> anyhow::Result<()> {
+        let Some(bridge) = slot.as_mut() else {
+            anyhow::bail!("No DX11 bridge");
+        };
+        let Err(e) = bridge.ensure_dimensions_with_format(width, height, format) else {
+            return Ok(());
+        };
+        if !bridge.as_any().is::<GlDx11Bridge>() {
+            return Err(e);
+        }
+        tracing::warn!(
+            "WGL_NV_DX_interop2 texture sharing failed, copying frames through PBOs: {e}"
+        );
+        let mut pbo = GlPboDx11Bridge::new(ctx.device.device(), ctx.device.context());
+        pbo.set_color_space(bridge.color_space());
+        pbo.ensure_dimensions_with_format(width, height, format)?;
+        bridge.cleanup();
+        *slot = Some(Box::new(pbo));
+        Ok(())
+    }
+
+    /// Run the framework's color conversion pass on a bridge texture,
+    /// blending in `dry` for [`MIX`].
+    fn convert_color(
+        ctx: &GpuContext,
+        texture: Option<ID3D11Texture2D>,
+        ops: u32,
+        dry: Option<(ID3D11Texture2D, f32)>,
+    ) {
+        if let Some(texture) = texture {
+            let dry = dry.as_ref().map(|(t, mix)| (t, *mix));
+            if let Err(e) = ctx.convert_color(&texture, ops, dry) {
+                error_limited!(pass = "color", "Color conversion failed: {e}");
+            }
+        }
+    }
+
     pub fn draw<P: GpuPlugin>(
         plugin: &mut P,
         instance_id: u64,
-        glium: &mut ffgl_glium::FFGLGlium,
+        fallback: &mut dyn Fallback,
         data: &FFGLData,
         frame_data: GLInput<'_>,
         frame_counter: u64,
         internal_resolution: f32,
         filter_quality: f32,
+        pool_capacity: usize,
         _metallib_bytes: &[u8],
-    ) {
+    ) -> bool {
         ensure_instance_resources(instance_id);
         if !validate_gl_state() {
-            passthrough(glium, data, frame_data);
-            return;
+            passthrough(fallback, data, frame_data);
+            return false;
         }
 
         let (width, height) = data.get_dimensions();
 
+        // Restrict processing to the plugin's region of interest, if any.
+        let roi = plugin
+            .processing_rect(data)
+            .and_then(|r| r.clamped(width, height))
+            .filter(|r| !r.is_full(width, height));
+        let region = roi.unwrap_or(PixelRect::full(width, height));
+        // The host may render into a sub-rectangle of its framebuffer; only
+        // rectangles in the host FBO are offset, the input texture is not.
+        let viewport = PixelRect::new(data.viewport.x, data.viewport.y, width, height);
+
+        // Compute processing dimensions from internal_resolution scale factor.
         let res_scale = internal_resolution.clamp(0.125, 1.0);
-        let proc_width = ((width as f32 * res_scale) as u32).max(2);
-        let proc_height = ((height as f32 * res_scale) as u32).max(2);
+        let (proc_width, proc_height, mapped) =
+            processing_size(plugin.scaling_policy(), region, res_scale);
+        let target = mapped.offset(viewport.x, viewport.y);
         let use_bilinear = filter_quality >= 0.5;
+        let color_space = plugin.color_space();
+        let staleness_window = plugin.staleness_window();
+        let offline = plugin.offline_rendering() || unsafe { crate::quirks::detect() }.synchronous;
+        let texture_format = plugin.texture_format();
+        let keyed_mutex = plugin.dx11_keyed_mutex();
+        let (mut input_ops, mut output_ops) = match color_space {
+            ColorSpace::Unmanaged => (0, 0),
+            ColorSpace::Srgb => (SRGB_DECODE, SRGB_ENCODE),
+        };
+        if plugin.alpha_mode() == AlphaMode::Straight {
+            input_ops |= UNPREMULTIPLY;
+            output_ops |= PREMULTIPLY;
+        }
+        let mix = plugin.mix().clamp(0.0, 1.0);
+        if mix < 1.0 {
+            output_ops |= MIX;
+        }
+        let tiles = plugin
+            .tiling()
+            .and_then(|t| plan_tiles(region, &t, width, height, res_scale));
 
         // Ensure D3D11 context is initialized
         let ctx_available = GPU_CTX.with(|cell| {
             let mut ctx = cell.borrow_mut();
             if ctx.is_none() {
-                match GpuContext::new() {
-                    Ok(c) => *ctx = Some(c),
+                let created = if plugin.dx11_deferred_context() {
+                    GpuContext::with_deferred_recording()
+                } else {
+                    GpuContext::new()
+                };
+                match created {
+                    Ok(c) => {
+                        unsafe { crate::device_info::check_same_adapter(&c) };
+                        *ctx = Some(c);
+                    }
                     Err(e) => {
-                        error!("Failed to create GPU context: {e}");
+                        error_limited!(
+                            instance = instance_id,
+                            "Failed to create GPU context: {e}"
+                        );
                         return false;
                     }
                 }
@@ -466,42 +1451,80 @@ mod dx11_draw {
         });
 
         if !ctx_available {
-            passthrough(glium, data, frame_data);
-            return;
+            passthrough(fallback, data, frame_data);
+            return false;
         }
 
-        // Ensure GL-D3D11 interop bridge is initialized
-        let bridge_available = GPU_CTX.with(|ctx_cell| {
+        // Ensure the GL-D3D11 bridge is initialized, copying through PBOs
+        // when the driver lacks WGL_NV_DX_interop2.
+        GPU_CTX.with(|ctx_cell| {
             let ctx = ctx_cell.borrow();
             let ctx = ctx.as_ref().unwrap();
             BRIDGE.with(|bridge_cell| {
                 let mut bridge = bridge_cell.borrow_mut();
                 if bridge.is_none() {
-                    *bridge = GlDx11Bridge::new(
-                        ctx.device.device(),
-                        ctx.device.context(),
-                    );
+                    let device = ctx.device.device();
+                    let context = ctx.device.context();
+                    *bridge = Some(match GlDx11Bridge::new(device, context) {
+                        Some(interop) => Box::new(interop),
+                        None => {
+                            tracing::warn!(
+                                "WGL_NV_DX_interop2 unavailable, copying frames through PBOs"
+                            );
+                            Box::new(GlPboDx11Bridge::new(device, context))
+                        }
+                    });
                 }
-                bridge.is_some()
             })
         });
 
-        if !bridge_available {
-            passthrough(glium, data, frame_data);
-            return;
-        }
-
         let host_fbo = frame_data.host;
-        let tex_id = match frame_data.textures.first() {
-            Some(t) => t.Handle,
-            None => {
-                passthrough(glium, data, frame_data);
-                return;
+        let host_texture = match (frame_data.texture(0), plugin.missing_input()) {
+            (Some(t), _) => Some(*t),
+            (None, MissingInput::Source) => None,
+            (None, MissingInput::Passthrough) => {
+                passthrough(fallback, data, frame_data);
+                return false;
+            }
+            (None, policy) => {
+                let saved_state = unsafe { SavedGlState::save() };
+                let held = policy == MissingInput::HoldLastFrame
+                    && hold_last_frame(host_fbo, use_bilinear);
+                if !held {
+                    unsafe { clear_host_region(host_fbo, viewport) };
+                }
+                unsafe {
+                    saved_state.restore_checked();
+                }
+                crate::gl_leak::check("missing input");
+                return false;
             }
         };
 
+        // Outside the region of interest the output is the untouched input,
+        // or nothing when there is none.
+        if roi.is_some() && host_texture.is_some() {
+            passthrough(
+                fallback,
+                data,
+                GLInput {
+                    textures: frame_data.textures,
+                    host: frame_data.host,
+                },
+            );
+        }
+
         let saved_state = unsafe { SavedGlState::save() };
 
+        if roi.is_some() && host_texture.is_none() {
+            unsafe { clear_host_region(host_fbo, viewport) };
+        }
+
+        // Letterbox bars left by the scaling policy are cleared.
+        if mapped != region {
+            unsafe { clear_host_region(host_fbo, region.offset(viewport.x, viewport.y)) };
+        }
+
         let success = GPU_CTX.with(|ctx_cell| {
             let ctx_ref = ctx_cell.borrow();
             let ctx = ctx_ref.as_ref().unwrap();
@@ -511,15 +1534,19 @@ mod dx11_draw {
                 let init_ok = GPU_INITIALIZED.with(|cell| {
                     let mut initialized = cell.borrow_mut();
                     if !*initialized {
-                        match plugin.gpu_init(ctx) {
-                            Ok(()) => {
+                        match catch_panic("GpuPlugin::gpu_init", || plugin.gpu_init(ctx)) {
+                            Some(Ok(())) => {
                                 *initialized = true;
                                 true
                             }
-                            Err(e) => {
-                                error!("GpuPlugin::gpu_init failed: {e}");
+                            Some(Err(e)) => {
+                                error_limited!(
+                                    instance = instance_id,
+                                    "GpuPlugin::gpu_init failed: {e}"
+                                );
                                 false
                             }
+                            None => false,
                         }
                     } else {
                         true
@@ -534,36 +1561,179 @@ mod dx11_draw {
                 // Single mutable borrow for all bridge operations.
                 let mut bridge_opt = bridge_cell.borrow_mut();
                 let bridge = bridge_opt.as_mut().unwrap();
+                bridge.set_color_space(color_space);
+                bridge.set_keyed_mutex(keyed_mutex);
+                bridge.set_pool_capacity(pool_capacity);
+                bridge.set_staleness_window(staleness_window);
+                let format = texture_format.unwrap_or_else(|| bridge.preferred_format());
+
+                ctx.intermediates.next_frame();
+                let discarded = DISCARD_IN_FLIGHT.with(std::cell::Cell::take);
+
+                if let Some(tiles) = &tiles {
+                    // Tiled frames are processed synchronously, one tile at a
+                    // time, so any in-flight result is dropped.
+                    let wait_start = std::time::Instant::now();
+                    bridge.wait_for_previous();
+                    let mut gpu_wait = wait_start.elapsed();
+                    let (tile_w, tile_h) = tiles
+                        .iter()
+                        .fold((2, 2), |(w, h), t| (w.max(t.width), h.max(t.height)));
+                    if let Err(e) = ensure_bridge(&mut bridge_opt, ctx, tile_w, tile_h, format) {
+                        error_limited!(
+                            instance = instance_id,
+                            frame = frame_counter,
+                            memory = %ctx.memory_report(),
+                            "Failed to ensure bridge dimensions: {e}"
+                        );
+                        return false;
+                    }
+                    let bridge = bridge_opt.as_deref_mut().unwrap();
+
+                    for tile in tiles {
+                        let filled = match &host_texture {
+                            Some(host_texture) => bridge.blit_input_from_host_region(
+                                host_texture.Handle,
+                                texture_rect(tile.source, (width, height), host_texture),
+                                tile.width,
+                                tile.height,
+                                use_bilinear,
+                            ),
+                            None => bridge.clear_input(),
+                        };
+                        if let Err(e) = filled {
+                            error_limited!(
+                                instance = instance_id,
+                                frame = frame_counter,
+                                "Failed to blit input tile: {e}"
+                            );
+                            return false;
+                        }
+                        ctx.begin_recording();
+                        convert_color(ctx, bridge.input_texture(), input_ops, None);
+
+                        let (Some(input_srv), Some(output_uav), Some(output_texture)) = (
+                            bridge.input_srv(),
+                            bridge.output_uav(),
+                            bridge.output_texture(),
+                        ) else {
+                            return false;
+                        };
+
+                        let mut draw_input = DrawInput {
+                            input_srv,
+                            output_uav,
+                            output_texture,
+                            width: tile.width,
+                            height: tile.height,
+                            roi: Some(tile.source),
+                            output_size: (tile.source.width, tile.source.height),
+                            viewport,
+                            host_textures: frame_data.textures,
+                            bridge: &mut *bridge,
+                            ctx,
+                            has_back_output: false,
+                        };
+
+                        let drawn = catch_panic("GpuPlugin::gpu_draw", || {
+                            plugin.gpu_draw(ctx, &mut draw_input, data, frame_counter)
+                        });
+                        if drawn.is_none() {
+                            return false;
+                        }
 
-                if let Err(e) = bridge.ensure_dimensions(proc_width, proc_height) {
-                    error!("Failed to ensure bridge dimensions: {e}");
+                        let dry = bridge.input_texture().map(|t| (t, mix));
+                        convert_color(ctx, bridge.output_texture(), output_ops, dry);
+                        ctx.end_recording();
+                        bridge.mark_dispatch(frame_counter);
+                        let wait_start = std::time::Instant::now();
+                        if offline {
+                            wait_until_idle(ctx);
+                        }
+                        bridge.wait_for_previous();
+                        gpu_wait += wait_start.elapsed();
+                        if let Err(e) = bridge.blit_output_to_target_region(
+                            host_fbo,
+                            tile.crop,
+                            tile.target.offset(viewport.x, viewport.y),
+                            use_bilinear,
+                        ) {
+                            error_limited!(
+                                instance = instance_id,
+                                frame = frame_counter,
+                                "Failed to blit output tile: {e}"
+                            );
+                            return false;
+                        }
+                    }
+
+                    LAST_REGION.with(|cell| *cell.borrow_mut() = PixelRect::default());
+                    crate::pacing::record_frame(
+                        instance_id,
+                        frame_counter,
+                        Presented::Tiled,
+                        gpu_wait,
+                    );
+                    return true;
+                }
+
+                if let Err(e) = ensure_bridge(&mut bridge_opt, ctx, proc_width, proc_height, format)
+                {
+                    error_limited!(
+                        instance = instance_id,
+                        frame = frame_counter,
+                        memory = %ctx.memory_report(),
+                        "Failed to ensure bridge dimensions: {e}"
+                    );
                     return false;
                 }
+                let bridge = bridge_opt.as_deref_mut().unwrap();
 
-                let has_prev = bridge.has_result_ready(frame_counter);
+                let mut has_prev = !discarded && !offline && bridge.has_result_ready(frame_counter);
 
+                let wait_start = std::time::Instant::now();
                 bridge.wait_for_previous();
+                let mut gpu_wait = wait_start.elapsed();
 
                 if has_prev {
                     bridge.swap();
-                    bridge.blit_back_output_to_target_scaled(
+                    let prev_region = LAST_REGION.with(|cell| *cell.borrow());
+                    if let Err(e) = bridge.blit_back_output_to_target_region(
                         host_fbo,
+                        PixelRect::full(proc_width, proc_height),
+                        prev_region,
+                        use_bilinear,
+                    ) {
+                        // Present this frame synchronously instead.
+                        warn_limited!(
+                            instance = instance_id,
+                            frame = frame_counter,
+                            "Failed to blit previous result: {e}"
+                        );
+                        has_prev = false;
+                    }
+                }
+
+                let filled = match &host_texture {
+                    Some(host_texture) => bridge.blit_input_from_host_region(
+                        host_texture.Handle,
+                        texture_rect(mapped, (width, height), host_texture),
                         proc_width,
                         proc_height,
-                        width,
-                        height,
                         use_bilinear,
+                    ),
+                    None => bridge.clear_input(),
+                };
+                if let Err(e) = filled {
+                    error_limited!(
+                        instance = instance_id,
+                        frame = frame_counter,
+                        "Failed to blit input: {e}"
                     );
+                    return false;
                 }
-
-                bridge.blit_input_from_host_scaled(
-                    tex_id,
-                    width,
-                    height,
-                    proc_width,
-                    proc_height,
-                    use_bilinear,
-                );
+                ctx.begin_recording();
+                convert_color(ctx, bridge.input_texture(), input_ops, None);
 
                 // Extract owned COM refs from bridge (cheap AddRef).
                 let input_srv = match bridge.input_srv() {
@@ -585,39 +1755,82 @@ mod dx11_draw {
                     output_texture,
                     width: proc_width,
                     height: proc_height,
+                    roi,
+                    output_size: (mapped.width, mapped.height),
+                    viewport,
+                    host_textures: frame_data.textures,
                     bridge,
+                    ctx,
+                    has_back_output: has_prev,
                 };
 
-                plugin.gpu_draw(ctx, &mut draw_input, data, frame_counter);
+                let drawn = catch_panic("GpuPlugin::gpu_draw", || {
+                    plugin.gpu_draw(ctx, &mut draw_input, data, frame_counter)
+                });
+                if drawn.is_none() {
+                    return false;
+                }
 
                 // Reclaim bridge from DrawInput for post-draw operations.
                 let bridge = draw_input.bridge;
 
+                let dry = bridge.input_texture().map(|t| (t, mix));
+                convert_color(ctx, bridge.output_texture(), output_ops, dry);
+                ctx.end_recording();
+                #[cfg(feature = "frame-dump")]
+                if let Some(dump) = plugin.frame_dump() {
+                    if let (Some(input), Some(output)) =
+                        (bridge.input_texture(), bridge.output_texture())
+                    {
+                        dump.dump_if_requested(ctx, frame_counter, &input, &output);
+                    }
+                }
                 bridge.mark_dispatch(frame_counter);
+                LAST_REGION.with(|cell| *cell.borrow_mut() = target);
 
                 if !has_prev {
+                    let wait_start = std::time::Instant::now();
+                    if offline {
+                        wait_until_idle(ctx);
+                    }
                     bridge.wait_for_pending();
-                    bridge.blit_output_to_target_scaled(
+                    gpu_wait += wait_start.elapsed();
+                    if let Err(e) = bridge.blit_output_to_target_region(
                         host_fbo,
-                        proc_width,
-                        proc_height,
-                        width,
-                        height,
+                        PixelRect::full(proc_width, proc_height),
+                        target,
                         use_bilinear,
-                    );
+                    ) {
+                        error_limited!(
+                            instance = instance_id,
+                            frame = frame_counter,
+                            "Failed to blit output: {e}"
+                        );
+                        return false;
+                    }
                 }
 
+                let presented = if has_prev {
+                    Presented::Previous
+                } else if offline {
+                    Presented::Offline
+                } else {
+                    Presented::Current
+                };
+                crate::pacing::record_frame(instance_id, frame_counter, presented, gpu_wait);
                 true
             })
         });
 
         unsafe {
-            saved_state.restore();
+            saved_state.restore_checked();
         }
+        crate::gl_leak::check("bridge blits / gpu_draw");
 
         if !success {
-            passthrough(glium, data, frame_data);
+            passthrough(fallback, data, frame_data);
         }
+        success
     }
 }
 
@@ -638,6 +1851,24 @@ pub fn ensure_instance_gl_resources(instance_id: u64) {
     let _ = instance_id;
 }
 
+/// Don't present `instance_id`'s in-flight result on its next frame; draw
+/// that frame synchronously instead.
+///
+/// Call this from the plugin's `on_disconnect`, or wherever it learns that
+/// the host stopped drawing it, so reselecting it doesn't show a frame from
+/// before. It complements the wall-clock
+/// [`staleness_window`](GpuPlugin::staleness_window), which can be relaxed
+/// or disabled for hosts that do this.
+pub fn discard_in_flight(instance_id: u64) {
+    #[cfg(target_os = "macos")]
+    metal_draw::discard_in_flight(instance_id);
+
+    #[cfg(target_os = "windows")]
+    dx11_draw::discard_in_flight(instance_id);
+
+    crate::pacing::discarded(instance_id);
+}
+
 /// Validate GL state before drawing. Returns `false` if the GL context is
 /// invalid and drawing should be skipped.
 pub fn validate_gl_state_before_draw() -> bool {
@@ -665,11 +1896,15 @@ pub fn validate_gl_state_before_draw() -> bool {
 /// * `plugin` - The plugin instance implementing [`GpuPlugin`].
 /// * `instance_id` - Unique identifier for this plugin instance (for
 ///   thread-local resource tracking).
-/// * `glium` - The glium context (used for passthrough fallback).
+/// * `fallback` - Draws the input unchanged while the GPU path is
+///   unavailable, e.g. [`RawGlFallback`](crate::RawGlFallback) or (with the
+///   `glium` feature) an `FFGLGlium`.
 /// * `data` - Host-provided FFGL data (viewport dimensions, timing, etc).
 /// * `frame_data` - Host input textures and FBO.
 /// * `frame_counter` - Monotonically increasing frame counter.
-/// * `internal_resolution` - Resolution scale factor `[0.125, 1.0]`.
+/// * `internal_resolution` - Resolution scale factor `[0.125, 1.0]`. If the
+///   plugin provides [`GpuPlugin::adaptive_resolution`], this is the upper
+///   bound for the adaptive scale.
 /// * `filter_quality` - Filter quality `[0.0, 1.0]`. Values >= 0.5 use
 ///   bilinear filtering.
 /// * `metallib_bytes` - Compiled Metal shader library bytes (from
@@ -677,7 +1912,7 @@ pub fn validate_gl_state_before_draw() -> bool {
 pub fn draw_gpu_effect<P: GpuPlugin>(
     plugin: &mut P,
     instance_id: u64,
-    glium: &mut ffgl_glium::FFGLGlium,
+    fallback: &mut impl Fallback,
     data: &FFGLData,
     frame_data: GLInput<'_>,
     frame_counter: u64,
@@ -685,31 +1920,102 @@ pub fn draw_gpu_effect<P: GpuPlugin>(
     filter_quality: f32,
     metallib_bytes: &[u8],
 ) {
+    crate::gl_leak::begin();
+
+    // Contexts lacking what the bridge needs get the input passed through.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    if is_context_current() && unsafe { crate::gl_caps::bridge_fallback_reason() }.is_some() {
+        passthrough(fallback, data, frame_data);
+        crate::gl_leak::check("draw_gpu_effect");
+        return;
+    }
+
+    // Hosts may move an instance between threads, and with it between the
+    // thread-local contexts and bridges.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    match crate::affinity::enter(instance_id, plugin.thread_migration()) {
+        crate::affinity::Affinity::Same => {}
+        crate::affinity::Affinity::Moved => {
+            #[cfg(target_os = "macos")]
+            metal_draw::thread_changed(instance_id);
+            #[cfg(target_os = "windows")]
+            dx11_draw::thread_changed(instance_id);
+            crate::pacing::thread_changed(instance_id);
+        }
+        crate::affinity::Affinity::Foreign => {
+            passthrough(fallback, data, frame_data);
+            crate::gl_leak::check("draw_gpu_effect");
+            return;
+        }
+    }
+
+    // An adaptive controller can lower the scale below the host's setting.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    let internal_resolution = match plugin.adaptive_resolution() {
+        Some(adaptive) => internal_resolution.min(adaptive.scale()),
+        None => internal_resolution,
+    };
+    // Stacked instances share the soft cap, if one is set.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    let budget = crate::budget::begin_frame(instance_id, data.viewport.width, data.viewport.height);
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    let internal_resolution = internal_resolution.min(budget.scale);
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    let _current = crate::budget::enter(instance_id);
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    let frame_start = std::time::Instant::now();
+
     #[cfg(target_os = "macos")]
-    metal_draw::draw(
-        plugin,
-        instance_id,
-        glium,
-        data,
-        frame_data,
-        frame_counter,
-        internal_resolution,
-        filter_quality,
-        metallib_bytes,
-    );
+    let drawn = {
+        let drawn = metal_draw::draw(
+            plugin,
+            instance_id,
+            fallback,
+            data,
+            frame_data,
+            frame_counter,
+            internal_resolution,
+            filter_quality,
+            budget.pool_capacity(),
+            metallib_bytes,
+        );
+        metal_draw::record_memory(instance_id);
+        drawn
+    };
 
     #[cfg(target_os = "windows")]
-    dx11_draw::draw(
-        plugin,
-        instance_id,
-        glium,
-        data,
-        frame_data,
-        frame_counter,
-        internal_resolution,
-        filter_quality,
-        metallib_bytes,
-    );
+    let drawn = {
+        let drawn = dx11_draw::draw(
+            plugin,
+            instance_id,
+            fallback,
+            data,
+            frame_data,
+            frame_counter,
+            internal_resolution,
+            filter_quality,
+            budget.pool_capacity(),
+            metallib_bytes,
+        );
+        dx11_draw::record_memory(instance_id);
+        drawn
+    };
+
+    // Passthrough frames say nothing about what the effect costs.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    if drawn {
+        if let Some(adaptive) = plugin.adaptive_resolution() {
+            adaptive.record_frame(frame_start.elapsed());
+        }
+    }
+
+    // Changes stay flagged until a frame actually used them.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    if drawn {
+        if let Some(params) = plugin.param_store() {
+            params.clear_changed();
+        }
+    }
 
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     {
@@ -721,6 +2027,8 @@ pub fn draw_gpu_effect<P: GpuPlugin>(
             filter_quality,
             metallib_bytes,
         );
-        passthrough(glium, data, frame_data);
+        passthrough(fallback, data, frame_data);
     }
+
+    crate::gl_leak::check("draw_gpu_effect");
 }
@@ -0,0 +1,39 @@
+//! What [`draw_gpu_effect`](crate::draw_gpu_effect) draws when the GPU path
+//! can't run.
+//!
+//! Before the GPU context and bridge exist, on contexts the bridge doesn't
+//! support, and on platforms without a backend, the host's first input is
+//! passed through unchanged. [`Fallback`] lets the plugin choose how that
+//! copy is made. Its default implementation,
+//! [`GLInput::blit_input_to_host`], attaches the input to a temporary FBO and
+//! `glBlitFramebuffer`s it into the host's, touching no state beyond what it
+//! restores. Plugins that don't otherwise use glium can
+//! pass [`RawGlFallback`] and leave the `glium` feature off.
+
+use ffgl_core::inputs::GLInput;
+use ffgl_core::FFGLData;
+use gpu_interop::warn_limited;
+
+/// Draws the host's input into the host framebuffer when the GPU path is
+/// unavailable.
+pub trait Fallback {
+    /// Copy input 0 of `frame_data` into the host FBO's viewport, scaled to
+    /// fill it. Draws nothing if input 0 is disconnected.
+    fn passthrough(&mut self, data: &FFGLData, frame_data: GLInput<'_>) {
+        if let Err(e) = unsafe { frame_data.blit_input_to_host(&data.viewport) } {
+            warn_limited!("Passthrough unavailable: {e}");
+        }
+    }
+}
+
+/// The default [`Fallback`]: a raw-GL blit, no glium context needed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawGlFallback;
+
+impl Fallback for RawGlFallback {}
+
+/// Uses the same raw-GL blit rather than drawing through glium: the fallback
+/// runs exactly when something is already wrong, and rebuilding glium's
+/// context view every frame there could fail too.
+#[cfg(feature = "glium")]
+impl Fallback for ffgl_glium::FFGLGlium {}
@@ -0,0 +1,838 @@
+//! Built-in blur, morphology and histogram filters.
+//!
+//! [`GpuContext::gaussian_blur`], [`GpuContext::area_max`] /
+//! [`GpuContext::area_min`] (dilate / erode with a rectangular window) and
+//! [`GpuContext::histogram`] cover kernels most effect plugins end up writing
+//! by hand. On macOS they run on MetalPerformanceShaders, which ships kernels
+//! tuned per GPU family. On Windows the same operations run as small HLSL
+//! compute passes compiled on first use.
+//!
+//! Edges are clamped on both backends. The histogram counts each channel over
+//! `[0, 1]` and is written as `4 * bins` `u32` values: all red bins, then
+//! green, blue and alpha.
+
+/// Largest blur radius, in pixels, of the HLSL fallback. Sigmas beyond a
+/// third of this are truncated.
+#[cfg(target_os = "windows")]
+const MAX_BLUR_RADIUS: i32 = 64;
+
+/// Check that a histogram of `bins` entries per channel fits in a buffer of
+/// `buffer_size` bytes.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn check_histogram_layout(bins: usize, buffer_size: usize) -> anyhow::Result<()> {
+    if !bins.is_power_of_two() || !(2..=256).contains(&bins) {
+        return Err(anyhow::anyhow!(
+            "histogram: bins must be a power of two between 2 and 256, got {bins}"
+        ));
+    }
+    let needed = 4 * bins * std::mem::size_of::<u32>();
+    if buffer_size < needed {
+        return Err(anyhow::anyhow!(
+            "histogram: buffer of {buffer_size} bytes is too small for {bins} bins \
+             ({needed} bytes needed)"
+        ));
+    }
+    Ok(())
+}
+
+/// Check that a morphology window is odd-sized in both directions.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn check_window(op: &str, width: usize, height: usize) -> anyhow::Result<()> {
+    if width % 2 == 0 || height % 2 == 0 {
+        return Err(anyhow::anyhow!(
+            "{op}: window must be odd-sized, got {width}x{height}"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod metal_impl {
+    use super::*;
+    use crate::buffer::GpuBuffer;
+    use crate::context::GpuContext;
+    use crate::dispatch::{CommandBuffer, PendingWork};
+    use anyhow::Result;
+    use objc2::rc::{Allocated, Retained};
+    use objc2::runtime::{AnyClass, AnyObject, Bool, ProtocolObject, Sel};
+    use objc2::{msg_send, sel};
+    use objc2_foundation::NSRange;
+    use objc2_metal::*;
+    use std::cell::RefCell;
+    use std::ffi::CStr;
+
+    #[link(name = "MetalPerformanceShaders", kind = "framework")]
+    extern "C" {
+        fn MPSSupportsMTLDevice(device: *const ProtocolObject<dyn MTLDevice>) -> Bool;
+    }
+
+    /// `MPSImageEdgeModeClamp`.
+    const MPS_EDGE_MODE_CLAMP: usize = 1;
+
+    /// `vector_float4`.
+    #[repr(C, align(16))]
+    struct Float4([f32; 4]);
+
+    /// `MPSImageHistogramInfo`.
+    #[repr(C)]
+    struct HistogramInfo {
+        number_of_histogram_entries: usize,
+        histogram_for_alpha: Bool,
+        min_pixel_value: Float4,
+        max_pixel_value: Float4,
+    }
+
+    /// MPS kernels, created on first use and kept while their parameters
+    /// stay the same.
+    pub(crate) struct FilterPipeline {
+        blur: RefCell<Option<(u32, Retained<AnyObject>)>>,
+        area_max: RefCell<Option<((usize, usize), Retained<AnyObject>)>>,
+        area_min: RefCell<Option<((usize, usize), Retained<AnyObject>)>>,
+        histogram: RefCell<Option<(usize, Retained<AnyObject>)>>,
+    }
+
+    impl FilterPipeline {
+        fn new(device: &ProtocolObject<dyn MTLDevice>) -> Result<Self> {
+            if !unsafe { MPSSupportsMTLDevice(device) }.as_bool() {
+                return Err(anyhow::anyhow!(
+                    "MetalPerformanceShaders is not supported on this GPU"
+                ));
+            }
+            Ok(Self {
+                blur: RefCell::new(None),
+                area_max: RefCell::new(None),
+                area_min: RefCell::new(None),
+                histogram: RefCell::new(None),
+            })
+        }
+    }
+
+    fn mps_class(name: &CStr) -> Result<&'static AnyClass> {
+        AnyClass::get(name)
+            .ok_or_else(|| anyhow::anyhow!("MetalPerformanceShaders class {name:?} not found"))
+    }
+
+    fn new_gaussian_blur(
+        device: &ProtocolObject<dyn MTLDevice>,
+        sigma: f32,
+    ) -> Result<Retained<AnyObject>> {
+        let alloc: Allocated<AnyObject> =
+            unsafe { msg_send![mps_class(c"MPSImageGaussianBlur")?, alloc] };
+        let kernel: Option<Retained<AnyObject>> =
+            unsafe { msg_send![alloc, initWithDevice: device, sigma: sigma] };
+        let kernel =
+            kernel.ok_or_else(|| anyhow::anyhow!("Failed to create MPSImageGaussianBlur"))?;
+        let _: () = unsafe { msg_send![&*kernel, setEdgeMode: MPS_EDGE_MODE_CLAMP] };
+        Ok(kernel)
+    }
+
+    fn new_area_kernel(
+        device: &ProtocolObject<dyn MTLDevice>,
+        class: &CStr,
+        (width, height): (usize, usize),
+    ) -> Result<Retained<AnyObject>> {
+        let alloc: Allocated<AnyObject> = unsafe { msg_send![mps_class(class)?, alloc] };
+        let kernel: Option<Retained<AnyObject>> = unsafe {
+            msg_send![alloc, initWithDevice: device, kernelWidth: width, kernelHeight: height]
+        };
+        let kernel = kernel.ok_or_else(|| anyhow::anyhow!("Failed to create {class:?}"))?;
+        let _: () = unsafe { msg_send![&*kernel, setEdgeMode: MPS_EDGE_MODE_CLAMP] };
+        Ok(kernel)
+    }
+
+    fn new_histogram(
+        device: &ProtocolObject<dyn MTLDevice>,
+        bins: usize,
+    ) -> Result<Retained<AnyObject>> {
+        let info = HistogramInfo {
+            number_of_histogram_entries: bins,
+            histogram_for_alpha: Bool::YES,
+            min_pixel_value: Float4([0.0; 4]),
+            max_pixel_value: Float4([1.0; 4]),
+        };
+
+        // `msg_send!` can't describe `vector_float4` fields to objc2's
+        // encoding checks, so call the initializer through `objc_msgSend`.
+        type Init = unsafe extern "C-unwind" fn(
+            *mut AnyObject,
+            Sel,
+            *const ProtocolObject<dyn MTLDevice>,
+            *const HistogramInfo,
+        ) -> *mut AnyObject;
+        let mut alloc: Allocated<AnyObject> =
+            unsafe { msg_send![mps_class(c"MPSImageHistogram")?, alloc] };
+        let receiver = Allocated::as_mut_ptr(&mut alloc);
+        // `init` consumes the allocation.
+        std::mem::forget(alloc);
+        let kernel = unsafe {
+            let init: Init =
+                std::mem::transmute(objc2::ffi::objc_msgSend as unsafe extern "C-unwind" fn());
+            Retained::from_raw(init(
+                receiver,
+                sel!(initWithDevice:histogramInfo:),
+                device,
+                &info,
+            ))
+        };
+        kernel.ok_or_else(|| anyhow::anyhow!("Failed to create MPSImageHistogram"))
+    }
+
+    /// Get the kernel cached in `slot` for `key`, creating it with `create`
+    /// if the slot is empty or holds a different key.
+    fn cached<K: PartialEq + Copy>(
+        slot: &RefCell<Option<(K, Retained<AnyObject>)>>,
+        key: K,
+        create: impl FnOnce() -> Result<Retained<AnyObject>>,
+    ) -> Result<Retained<AnyObject>> {
+        let mut slot = slot.borrow_mut();
+        if let Some((cached_key, kernel)) = slot.as_ref() {
+            if *cached_key == key {
+                return Ok(kernel.clone());
+            }
+        }
+        let kernel = create()?;
+        *slot = Some((key, kernel.clone()));
+        Ok(kernel)
+    }
+
+    fn encode_unary(
+        kernel: &AnyObject,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        src: &ProtocolObject<dyn MTLTexture>,
+        dst: &ProtocolObject<dyn MTLTexture>,
+    ) {
+        let _: () = unsafe {
+            msg_send![
+                kernel,
+                encodeToCommandBuffer: command_buffer,
+                sourceTexture: src,
+                destinationTexture: dst
+            ]
+        };
+    }
+
+    fn check_distinct(
+        op: &str,
+        src: &ProtocolObject<dyn MTLTexture>,
+        dst: &ProtocolObject<dyn MTLTexture>,
+    ) -> Result<()> {
+        if std::ptr::eq(src, dst) {
+            return Err(anyhow::anyhow!("{op}: source and destination must differ"));
+        }
+        Ok(())
+    }
+
+    /// The filter pipeline, created on first use.
+    fn pipeline(ctx: &GpuContext) -> Result<&FilterPipeline> {
+        match ctx.filters.get() {
+            Some(p) => Ok(p),
+            None => {
+                let p = FilterPipeline::new(ctx.device.device())?;
+                Ok(ctx.filters.get_or_init(|| p))
+            }
+        }
+    }
+
+    fn encode_gaussian_blur_inner(
+        ctx: &GpuContext,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        src: &ProtocolObject<dyn MTLTexture>,
+        dst: &ProtocolObject<dyn MTLTexture>,
+        sigma: f32,
+    ) -> Result<()> {
+        check_distinct("gaussian_blur", src, dst)?;
+        if sigma <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "gaussian_blur: sigma must be positive, got {sigma}"
+            ));
+        }
+        let kernel = cached(&pipeline(ctx)?.blur, sigma.to_bits(), || {
+            new_gaussian_blur(ctx.device.device(), sigma)
+        })?;
+        encode_unary(&kernel, command_buffer, src, dst);
+        Ok(())
+    }
+
+    fn encode_area_inner(
+        ctx: &GpuContext,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        src: &ProtocolObject<dyn MTLTexture>,
+        dst: &ProtocolObject<dyn MTLTexture>,
+        window: (usize, usize),
+        max: bool,
+    ) -> Result<()> {
+        let op = if max { "area_max" } else { "area_min" };
+        check_distinct(op, src, dst)?;
+        check_window(op, window.0, window.1)?;
+        let pipeline = pipeline(ctx)?;
+        let (slot, class) = if max {
+            (&pipeline.area_max, c"MPSImageAreaMax")
+        } else {
+            (&pipeline.area_min, c"MPSImageAreaMin")
+        };
+        let kernel = cached(slot, window, || {
+            new_area_kernel(ctx.device.device(), class, window)
+        })?;
+        encode_unary(&kernel, command_buffer, src, dst);
+        Ok(())
+    }
+
+    fn encode_histogram_inner(
+        ctx: &GpuContext,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        src: &ProtocolObject<dyn MTLTexture>,
+        buffer: &GpuBuffer,
+        bins: usize,
+    ) -> Result<()> {
+        check_histogram_layout(bins, buffer.size)?;
+        let kernel = cached(&pipeline(ctx)?.histogram, bins, || {
+            new_histogram(ctx.device.device(), bins)
+        })?;
+
+        let blit = command_buffer
+            .blitCommandEncoder()
+            .ok_or_else(|| anyhow::anyhow!("Failed to create Metal blit encoder"))?;
+        blit.fillBuffer_range_value(&buffer.metal, NSRange::new(0, 4 * bins * 4), 0);
+        blit.endEncoding();
+
+        let _: () = unsafe {
+            msg_send![
+                &*kernel,
+                encodeToCommandBuffer: command_buffer,
+                sourceTexture: src,
+                histogram: &*buffer.metal,
+                histogramOffset: 0usize
+            ]
+        };
+        Ok(())
+    }
+
+    impl GpuContext {
+        /// Blur `src` into `dst` with a Gaussian of standard deviation
+        /// `sigma` pixels, and return a [`PendingWork`] token.
+        ///
+        /// `src` and `dst` must be different textures of the same size, and
+        /// `dst` must be shader-writable.
+        pub fn gaussian_blur(
+            &self,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            sigma: f32,
+        ) -> Result<PendingWork> {
+            let cb = self.create_command_buffer()?;
+            encode_gaussian_blur_inner(self, &cb.inner, src, dst, sigma)?;
+            Ok(self.commit(cb))
+        }
+
+        /// Encode a [`gaussian_blur`](Self::gaussian_blur) on an existing
+        /// command buffer.
+        pub fn encode_gaussian_blur(
+            &self,
+            cb: &CommandBuffer,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            sigma: f32,
+        ) -> Result<()> {
+            encode_gaussian_blur_inner(self, &cb.inner, src, dst, sigma)
+        }
+
+        /// Write the per-channel maximum over a `width` x `height` window
+        /// around each pixel of `src` into `dst` (dilation), and return a
+        /// [`PendingWork`] token. Both window sides must be odd.
+        pub fn area_max(
+            &self,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            width: usize,
+            height: usize,
+        ) -> Result<PendingWork> {
+            let cb = self.create_command_buffer()?;
+            encode_area_inner(self, &cb.inner, src, dst, (width, height), true)?;
+            Ok(self.commit(cb))
+        }
+
+        /// Encode an [`area_max`](Self::area_max) on an existing command
+        /// buffer.
+        pub fn encode_area_max(
+            &self,
+            cb: &CommandBuffer,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            width: usize,
+            height: usize,
+        ) -> Result<()> {
+            encode_area_inner(self, &cb.inner, src, dst, (width, height), true)
+        }
+
+        /// Like [`area_max`](Self::area_max), but takes the minimum
+        /// (erosion).
+        pub fn area_min(
+            &self,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            width: usize,
+            height: usize,
+        ) -> Result<PendingWork> {
+            let cb = self.create_command_buffer()?;
+            encode_area_inner(self, &cb.inner, src, dst, (width, height), false)?;
+            Ok(self.commit(cb))
+        }
+
+        /// Encode an [`area_min`](Self::area_min) on an existing command
+        /// buffer.
+        pub fn encode_area_min(
+            &self,
+            cb: &CommandBuffer,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            width: usize,
+            height: usize,
+        ) -> Result<()> {
+            encode_area_inner(self, &cb.inner, src, dst, (width, height), false)
+        }
+
+        /// Count the pixels of `src` into `bins` bins per channel, writing
+        /// `4 * bins` `u32` counts to `buffer`, and return a [`PendingWork`]
+        /// token.
+        ///
+        /// `bins` must be a power of two up to 256. Read the counts back
+        /// with a shared buffer (see
+        /// [`create_shared_buffer`](Self::create_shared_buffer)) once the
+        /// work completes.
+        pub fn histogram(
+            &self,
+            src: &ProtocolObject<dyn MTLTexture>,
+            buffer: &GpuBuffer,
+            bins: usize,
+        ) -> Result<PendingWork> {
+            let cb = self.create_command_buffer()?;
+            encode_histogram_inner(self, &cb.inner, src, buffer, bins)?;
+            Ok(self.commit(cb))
+        }
+
+        /// Encode a [`histogram`](Self::histogram) on an existing command
+        /// buffer.
+        pub fn encode_histogram(
+            &self,
+            cb: &CommandBuffer,
+            src: &ProtocolObject<dyn MTLTexture>,
+            buffer: &GpuBuffer,
+            bins: usize,
+        ) -> Result<()> {
+            encode_histogram_inner(self, &cb.inner, src, buffer, bins)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) use dx11_impl::FilterPipeline;
+#[cfg(target_os = "macos")]
+pub(crate) use metal_impl::FilterPipeline;
+
+#[cfg(target_os = "windows")]
+mod dx11_impl {
+    use super::*;
+    use crate::blit::{blob_bytes, compile_hlsl};
+    use crate::buffer::GpuBuffer;
+    use crate::context::GpuContext;
+    use anyhow::Result;
+    use std::cell::RefCell;
+    use windows::Win32::Graphics::Direct3D11::*;
+
+    /// Separable blur / min / max passes and the histogram kernel.
+    const FILTERS_HLSL: &str = r#"
+Texture2D<float4> src : register(t0);
+RWTexture2D<float4> dst : register(u0);
+RWStructuredBuffer<uint> hist : register(u1);
+
+cbuffer Params : register(b0) {
+    int2 dir;
+    int radius;
+    float sigma;
+    uint mode;
+    uint bins;
+    uint2 _pad;
+};
+
+[numthreads(8, 8, 1)]
+void cs_blur(uint3 id : SV_DispatchThreadID) {
+    uint w, h;
+    dst.GetDimensions(w, h);
+    if (id.x >= w || id.y >= h) return;
+
+    float4 sum = 0.0;
+    float total = 0.0;
+    for (int i = -radius; i <= radius; i++) {
+        int2 p = clamp(int2(id.xy) + dir * i, int2(0, 0), int2(w - 1, h - 1));
+        float weight = exp(-(i * i) / (2.0 * sigma * sigma));
+        sum += src[p] * weight;
+        total += weight;
+    }
+    dst[id.xy] = sum / total;
+}
+
+[numthreads(8, 8, 1)]
+void cs_area(uint3 id : SV_DispatchThreadID) {
+    uint w, h;
+    dst.GetDimensions(w, h);
+    if (id.x >= w || id.y >= h) return;
+
+    float4 acc = src[id.xy];
+    for (int i = -radius; i <= radius; i++) {
+        int2 p = clamp(int2(id.xy) + dir * i, int2(0, 0), int2(w - 1, h - 1));
+        acc = mode != 0 ? max(acc, src[p]) : min(acc, src[p]);
+    }
+    dst[id.xy] = acc;
+}
+
+[numthreads(8, 8, 1)]
+void cs_histogram(uint3 id : SV_DispatchThreadID) {
+    uint w, h;
+    src.GetDimensions(w, h);
+    if (id.x >= w || id.y >= h) return;
+
+    uint4 b = min(uint4(saturate(src[id.xy]) * bins), bins - 1);
+    InterlockedAdd(hist[b.r], 1);
+    InterlockedAdd(hist[bins + b.g], 1);
+    InterlockedAdd(hist[2 * bins + b.b], 1);
+    InterlockedAdd(hist[3 * bins + b.a], 1);
+}
+"#;
+
+    /// Matches `cbuffer Params` in [`FILTERS_HLSL`].
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct Params {
+        dir: [i32; 2],
+        radius: i32,
+        sigma: f32,
+        mode: u32,
+        bins: u32,
+        _pad: [u32; 2],
+    }
+
+    /// Intermediate for the separable passes.
+    struct Scratch {
+        srv: ID3D11ShaderResourceView,
+        uav: ID3D11UnorderedAccessView,
+        desc: D3D11_TEXTURE2D_DESC,
+    }
+
+    /// Lazily-created compute shaders and scratch texture for the filters.
+    pub(crate) struct FilterPipeline {
+        blur: ID3D11ComputeShader,
+        area: ID3D11ComputeShader,
+        histogram: ID3D11ComputeShader,
+        params: ID3D11Buffer,
+        scratch: RefCell<Option<Scratch>>,
+    }
+
+    fn create_shader(device: &ID3D11Device, entry: &[u8]) -> Result<ID3D11ComputeShader> {
+        let blob = compile_hlsl(FILTERS_HLSL, entry, b"cs_5_0\0")?;
+        let mut shader = None;
+        unsafe { device.CreateComputeShader(blob_bytes(&blob), None, Some(&mut shader as *mut _)) }
+            .map_err(|e| anyhow::anyhow!("Failed to create filter compute shader: {e}"))?;
+        shader.ok_or_else(|| anyhow::anyhow!("D3D11 CreateComputeShader returned null"))
+    }
+
+    impl FilterPipeline {
+        /// Size of the scratch texture, if one has been created.
+        pub(crate) fn scratch_bytes(&self) -> u64 {
+            self.scratch
+                .borrow()
+                .as_ref()
+                .map_or(0, |s| crate::color::scratch_size(&s.desc))
+        }
+
+        fn new(device: &ID3D11Device) -> Result<Self> {
+            let desc = D3D11_BUFFER_DESC {
+                ByteWidth: std::mem::size_of::<Params>() as u32,
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: D3D11_BIND_CONSTANT_BUFFER.0 as u32,
+                ..Default::default()
+            };
+            let mut params = None;
+            unsafe { device.CreateBuffer(&desc, None, Some(&mut params as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create filter params buffer: {e}"))?;
+            let params =
+                params.ok_or_else(|| anyhow::anyhow!("D3D11 CreateBuffer returned null"))?;
+
+            Ok(Self {
+                blur: create_shader(device, b"cs_blur\0")?,
+                area: create_shader(device, b"cs_area\0")?,
+                histogram: create_shader(device, b"cs_histogram\0")?,
+                params,
+                scratch: RefCell::new(None),
+            })
+        }
+    }
+
+    fn create_scratch(device: &ID3D11Device, like: &D3D11_TEXTURE2D_DESC) -> Result<Scratch> {
+        let desc = D3D11_TEXTURE2D_DESC {
+            Width: like.Width,
+            Height: like.Height,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: like.Format,
+            SampleDesc: like.SampleDesc,
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: (D3D11_BIND_SHADER_RESOURCE.0 | D3D11_BIND_UNORDERED_ACCESS.0) as u32,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+        };
+        let mut texture = None;
+        unsafe { device.CreateTexture2D(&desc, None, Some(&mut texture as *mut _)) }
+            .map_err(|e| anyhow::anyhow!("Failed to create filter scratch texture: {e}"))?;
+        let texture =
+            texture.ok_or_else(|| anyhow::anyhow!("D3D11 CreateTexture2D returned null"))?;
+        let mut srv = None;
+        unsafe { device.CreateShaderResourceView(&texture, None, Some(&mut srv as *mut _)) }
+            .map_err(|e| anyhow::anyhow!("Failed to create filter scratch SRV: {e}"))?;
+        let srv =
+            srv.ok_or_else(|| anyhow::anyhow!("D3D11 CreateShaderResourceView returned null"))?;
+        let mut uav = None;
+        unsafe { device.CreateUnorderedAccessView(&texture, None, Some(&mut uav as *mut _)) }
+            .map_err(|e| anyhow::anyhow!("Failed to create filter scratch UAV: {e}"))?;
+        let uav =
+            uav.ok_or_else(|| anyhow::anyhow!("D3D11 CreateUnorderedAccessView returned null"))?;
+        Ok(Scratch { srv, uav, desc })
+    }
+
+    impl GpuContext {
+        /// The filter pipeline, created on first use.
+        fn filter_pipeline(&self) -> Result<&FilterPipeline> {
+            match self.filters.get() {
+                Some(p) => Ok(p),
+                None => {
+                    let p = FilterPipeline::new(self.device.device())?;
+                    Ok(self.filters.get_or_init(|| p))
+                }
+            }
+        }
+
+        /// Run `shader` over `dst` reading `src` with `params`, then unbind.
+        fn filter_pass(
+            &self,
+            pipeline: &FilterPipeline,
+            shader: &ID3D11ComputeShader,
+            src: &ID3D11ShaderResourceView,
+            dst: &ID3D11UnorderedAccessView,
+            (width, height): (u32, u32),
+            params: &Params,
+        ) {
+            let ctx = self.command_context();
+            unsafe {
+                ctx.UpdateSubresource(
+                    &pipeline.params,
+                    0,
+                    None,
+                    params as *const Params as *const _,
+                    0,
+                    0,
+                );
+
+                ctx.CSSetShader(shader, None);
+                ctx.CSSetShaderResources(0, Some(&[Some(src.clone())]));
+                let uavs = [Some(dst.clone())];
+                ctx.CSSetUnorderedAccessViews(0, 1, Some(uavs.as_ptr()), None);
+                ctx.CSSetConstantBuffers(0, Some(&[Some(pipeline.params.clone())]));
+                ctx.Dispatch(width.div_ceil(8), height.div_ceil(8), 1);
+
+                let null_uavs: [Option<ID3D11UnorderedAccessView>; 1] = Default::default();
+                ctx.CSSetUnorderedAccessViews(0, 1, Some(null_uavs.as_ptr()), None);
+                let null_srvs: [Option<ID3D11ShaderResourceView>; 1] = Default::default();
+                ctx.CSSetShaderResources(0, Some(&null_srvs));
+                let null_cbufs: [Option<ID3D11Buffer>; 1] = Default::default();
+                ctx.CSSetConstantBuffers(0, Some(&null_cbufs));
+            }
+        }
+
+        /// Run a horizontal pass from `src` into the scratch texture and a
+        /// vertical pass from the scratch texture into `dst`.
+        fn separable_filter(
+            &self,
+            op: &str,
+            src: &ID3D11Texture2D,
+            dst: &ID3D11Texture2D,
+            shader: impl Fn(&FilterPipeline) -> &ID3D11ComputeShader,
+            (radius_x, radius_y): (i32, i32),
+            params: Params,
+        ) -> Result<()> {
+            if src == dst {
+                return Err(anyhow::anyhow!("{op}: source and destination must differ"));
+            }
+            let device = self.device.device();
+            let pipeline = self.filter_pipeline()?;
+
+            let mut desc = D3D11_TEXTURE2D_DESC::default();
+            unsafe { dst.GetDesc(&mut desc) };
+
+            let mut scratch = pipeline.scratch.borrow_mut();
+            let stale = scratch.as_ref().is_none_or(|s| {
+                (s.desc.Width, s.desc.Height, s.desc.Format)
+                    != (desc.Width, desc.Height, desc.Format)
+            });
+            if stale {
+                *scratch = Some(create_scratch(device, &desc)?);
+            }
+            let scratch = scratch.as_ref().unwrap();
+
+            let mut srv = None;
+            unsafe { device.CreateShaderResourceView(src, None, Some(&mut srv as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create SRV for {op} source: {e}"))?;
+            let srv =
+                srv.ok_or_else(|| anyhow::anyhow!("D3D11 CreateShaderResourceView returned null"))?;
+            let mut uav = None;
+            unsafe { device.CreateUnorderedAccessView(dst, None, Some(&mut uav as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create UAV for {op} target: {e}"))?;
+            let uav = uav
+                .ok_or_else(|| anyhow::anyhow!("D3D11 CreateUnorderedAccessView returned null"))?;
+
+            let size = (desc.Width, desc.Height);
+            let shader = shader(pipeline);
+            let horizontal = Params {
+                dir: [1, 0],
+                radius: radius_x,
+                ..params
+            };
+            self.filter_pass(pipeline, shader, &srv, &scratch.uav, size, &horizontal);
+            let vertical = Params {
+                dir: [0, 1],
+                radius: radius_y,
+                ..params
+            };
+            self.filter_pass(pipeline, shader, &scratch.srv, &uav, size, &vertical);
+            Ok(())
+        }
+
+        /// Blur `src` into `dst` with a Gaussian of standard deviation
+        /// `sigma` pixels, on the [`command_context`](Self::command_context).
+        ///
+        /// `src` and `dst` must be different textures of the same size;
+        /// `src` must be bindable as a shader resource and `dst` for
+        /// unordered access. The kernel radius is capped at 64 pixels.
+        pub fn gaussian_blur(
+            &self,
+            src: &ID3D11Texture2D,
+            dst: &ID3D11Texture2D,
+            sigma: f32,
+        ) -> Result<()> {
+            if sigma <= 0.0 {
+                return Err(anyhow::anyhow!(
+                    "gaussian_blur: sigma must be positive, got {sigma}"
+                ));
+            }
+            let radius = ((sigma * 3.0).ceil() as i32).clamp(1, MAX_BLUR_RADIUS);
+            let params = Params {
+                sigma,
+                ..Default::default()
+            };
+            self.separable_filter(
+                "gaussian_blur",
+                src,
+                dst,
+                |p| &p.blur,
+                (radius, radius),
+                params,
+            )
+        }
+
+        /// Write the per-channel maximum over a `width` x `height` window
+        /// around each pixel of `src` into `dst` (dilation). Both window
+        /// sides must be odd.
+        pub fn area_max(
+            &self,
+            src: &ID3D11Texture2D,
+            dst: &ID3D11Texture2D,
+            width: usize,
+            height: usize,
+        ) -> Result<()> {
+            self.area_filter("area_max", src, dst, (width, height), 1)
+        }
+
+        /// Like [`area_max`](Self::area_max), but takes the minimum
+        /// (erosion).
+        pub fn area_min(
+            &self,
+            src: &ID3D11Texture2D,
+            dst: &ID3D11Texture2D,
+            width: usize,
+            height: usize,
+        ) -> Result<()> {
+            self.area_filter("area_min", src, dst, (width, height), 0)
+        }
+
+        fn area_filter(
+            &self,
+            op: &str,
+            src: &ID3D11Texture2D,
+            dst: &ID3D11Texture2D,
+            (width, height): (usize, usize),
+            mode: u32,
+        ) -> Result<()> {
+            check_window(op, width, height)?;
+            let params = Params {
+                mode,
+                ..Default::default()
+            };
+            let radii = ((width / 2) as i32, (height / 2) as i32);
+            self.separable_filter(op, src, dst, |p| &p.area, radii, params)
+        }
+
+        /// Count the pixels of `src` into `bins` bins per channel, writing
+        /// `4 * bins` `u32` counts to `buffer`, on the
+        /// [`command_context`](Self::command_context).
+        ///
+        /// `bins` must be a power of two up to 256, and `buffer` a
+        /// [`create_buffer`](Self::create_buffer) with 4-byte elements.
+        pub fn histogram(
+            &self,
+            src: &ID3D11Texture2D,
+            buffer: &GpuBuffer,
+            bins: usize,
+        ) -> Result<()> {
+            check_histogram_layout(bins, buffer.size)?;
+            let device = self.device.device();
+            let pipeline = self.filter_pipeline()?;
+            let ctx = self.command_context();
+
+            let mut desc = D3D11_TEXTURE2D_DESC::default();
+            unsafe { src.GetDesc(&mut desc) };
+
+            let mut srv = None;
+            unsafe { device.CreateShaderResourceView(src, None, Some(&mut srv as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create SRV for histogram source: {e}"))?;
+
+            let params = Params {
+                bins: bins as u32,
+                ..Default::default()
+            };
+            unsafe {
+                ctx.ClearUnorderedAccessViewUint(&buffer.dx11_uav, &[0; 4]);
+                ctx.UpdateSubresource(
+                    &pipeline.params,
+                    0,
+                    None,
+                    &params as *const Params as *const _,
+                    0,
+                    0,
+                );
+
+                ctx.CSSetShader(&pipeline.histogram, None);
+                ctx.CSSetShaderResources(0, Some(&[srv]));
+                let uavs = [Some(buffer.dx11_uav.clone())];
+                ctx.CSSetUnorderedAccessViews(1, 1, Some(uavs.as_ptr()), None);
+                ctx.CSSetConstantBuffers(0, Some(&[Some(pipeline.params.clone())]));
+                ctx.Dispatch(desc.Width.div_ceil(8), desc.Height.div_ceil(8), 1);
+
+                let null_uavs: [Option<ID3D11UnorderedAccessView>; 1] = Default::default();
+                ctx.CSSetUnorderedAccessViews(1, 1, Some(null_uavs.as_ptr()), None);
+                let null_srvs: [Option<ID3D11ShaderResourceView>; 1] = Default::default();
+                ctx.CSSetShaderResources(0, Some(&null_srvs));
+                let null_cbufs: [Option<ID3D11Buffer>; 1] = Default::default();
+                ctx.CSSetConstantBuffers(0, Some(&null_cbufs));
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,554 @@
+//! Coarse optical flow between consecutive input frames.
+//!
+//! [`OpticalFlow`] estimates how far each part of the image moved since the
+//! previous frame, as a building block for motion blur, datamosh and
+//! time-displacement effects. Each [`update`](OpticalFlow::update) reduces
+//! the input to luminance at `1 / scale` of the processing size, solves
+//! Lucas-Kanade over a 5x5 window against the previous frame's luminance,
+//! and keeps the current luminance for the next frame. Flow is estimated at
+//! the reduced size, so motion up to about `2 * scale` pixels per frame is
+//! tracked.
+//!
+//! Update once per frame and not with [`Tiling`](crate::Tiling), which would
+//! compare each tile with the one before it.
+//!
+//! The [`flow`](OpticalFlow::flow) texture holds, per reduced pixel:
+//!
+//! - `rg`: displacement since the previous frame in processing pixels,
+//!   texture x right and y down, encoded as `0.5 + d / (2 * max)` where
+//!   `max` is [`max_displacement`](OpticalFlow::max_displacement).
+//! - `b`: confidence from 0 (flat or ambiguous area, no flow) to 1.
+//! - `a`: 1.
+//!
+//! Decode it in a shader with `(flow.rg - 0.5) * 2.0 * max`, sampling
+//! linearly to spread the flow back over the full-size image. The flow
+//! points from where a pixel was to where it is; sample the previous
+//! position at `uv - d / size`.
+
+/// Largest supported reduction factor.
+const MAX_SCALE: u32 = 8;
+
+/// Matches `FlowParams` in the kernels.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FlowParams {
+    size: [u32; 2],
+    scale: u32,
+    max_displacement: f32,
+}
+
+/// Optical flow state for one plugin instance: the flow field and the
+/// previous frame's reduced luminance.
+pub struct OpticalFlow {
+    scale: u32,
+    max_displacement: f32,
+    /// Reduced luminance of the current and previous frame, alternating.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    luma: [Option<crate::GpuTexture>; 2],
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    flow: Option<crate::GpuTexture>,
+    /// Index into `luma` of the texture the next update writes.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    current: usize,
+    /// Whether the other `luma` texture holds the previous frame.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    has_previous: bool,
+}
+
+impl OpticalFlow {
+    /// Estimate flow at `1 / scale` of the processing size, clamped to
+    /// 1–8, with displacements encoded up to 32 pixels.
+    pub fn new(scale: u32) -> Self {
+        Self {
+            scale: scale.clamp(1, MAX_SCALE),
+            max_displacement: 32.0,
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            luma: [None, None],
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            flow: None,
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            current: 0,
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            has_previous: false,
+        }
+    }
+
+    /// Set the displacement, in processing pixels, that the flow texture's
+    /// full range encodes. Larger motion is clamped.
+    pub fn with_max_displacement(mut self, pixels: f32) -> Self {
+        self.max_displacement = pixels.max(1.0);
+        self
+    }
+
+    /// Reduction factor of the flow texture.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Displacement encoded by the flow texture's full range, see the
+    /// [module docs](crate::flow).
+    pub fn max_displacement(&self) -> f32 {
+        self.max_displacement
+    }
+
+    /// Forget the previous frame, e.g. after a cut. The next update reports
+    /// no motion.
+    pub fn reset(&mut self) {
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        {
+            self.has_previous = false;
+        }
+    }
+
+    /// The flow field from the last update, at the reduced size. `None`
+    /// before the first update.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub fn flow(&self) -> Option<&crate::GpuTexture> {
+        self.flow.as_ref()
+    }
+
+    /// Size of the reduced textures for a `width` x `height` frame.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn reduced_size(&self, width: u32, height: u32) -> (u32, u32) {
+        (
+            width.div_ceil(self.scale).max(1),
+            height.div_ceil(self.scale).max(1),
+        )
+    }
+
+    /// (Re)create the textures for a `width` x `height` frame, forgetting the
+    /// previous frame if they change size, and return the uniforms for the
+    /// update.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn prepare(
+        &mut self,
+        ctx: &crate::GpuContext,
+        width: u32,
+        height: u32,
+    ) -> anyhow::Result<FlowParams> {
+        let (w, h) = self.reduced_size(width, height);
+        if !self.flow.as_ref().is_some_and(|t| t.has_dims(w, h)) {
+            self.flow = Some(ctx.create_texture(w, h)?);
+            self.luma = [
+                Some(ctx.create_texture(w, h)?),
+                Some(ctx.create_texture(w, h)?),
+            ];
+            self.has_previous = false;
+        }
+        Ok(FlowParams {
+            size: [width, height],
+            scale: self.scale,
+            max_displacement: self.max_displacement,
+        })
+    }
+
+    /// Swap the luminance textures after an update.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn advance(&mut self) {
+        self.current = 1 - self.current;
+        self.has_previous = true;
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod metal_impl {
+    use super::{FlowParams, OpticalFlow};
+    use crate::context::GpuContext;
+    use crate::dispatch::CommandBuffer;
+    use crate::DrawInput;
+    use anyhow::Result;
+    use objc2::rc::Retained;
+    use objc2::runtime::ProtocolObject;
+    use objc2_foundation::NSString;
+    use objc2_metal::*;
+
+    const FLOW_MSL: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct FlowParams {
+    uint2 size;
+    uint scale;
+    float max_displacement;
+};
+
+kernel void ffgl_gpu_flow_luma(texture2d<float, access::read> src [[texture(0)]],
+                               texture2d<float, access::write> dst [[texture(1)]],
+                               constant FlowParams& p [[buffer(0)]],
+                               uint2 gid [[thread_position_in_grid]]) {
+    if (gid.x >= dst.get_width() || gid.y >= dst.get_height()) return;
+
+    float sum = 0.0;
+    float count = 0.0;
+    for (uint y = 0; y < p.scale; y++) {
+        for (uint x = 0; x < p.scale; x++) {
+            uint2 q = gid * p.scale + uint2(x, y);
+            if (q.x < p.size.x && q.y < p.size.y) {
+                sum += dot(src.read(q).rgb, float3(0.2126, 0.7152, 0.0722));
+                count += 1.0;
+            }
+        }
+    }
+    float l = count > 0.0 ? sum / count : 0.0;
+    dst.write(float4(l, l, l, 1.0), gid);
+}
+
+kernel void ffgl_gpu_flow_lk(texture2d<float, access::read> cur [[texture(0)]],
+                             texture2d<float, access::read> prev [[texture(1)]],
+                             texture2d<float, access::write> flow [[texture(2)]],
+                             constant FlowParams& p [[buffer(0)]],
+                             uint2 gid [[thread_position_in_grid]]) {
+    int2 size = int2(flow.get_width(), flow.get_height());
+    if (int(gid.x) >= size.x || int(gid.y) >= size.y) return;
+
+    float sxx = 0.0, sxy = 0.0, syy = 0.0, sxt = 0.0, syt = 0.0;
+    for (int y = -2; y <= 2; y++) {
+        for (int x = -2; x <= 2; x++) {
+            int2 q = clamp(int2(gid) + int2(x, y), int2(0), size - 1);
+            uint2 l = uint2(max(q.x - 1, 0), q.y);
+            uint2 r = uint2(min(q.x + 1, size.x - 1), q.y);
+            uint2 u = uint2(q.x, max(q.y - 1, 0));
+            uint2 d = uint2(q.x, min(q.y + 1, size.y - 1));
+            float ix = 0.25 * (cur.read(r).r - cur.read(l).r + prev.read(r).r - prev.read(l).r);
+            float iy = 0.25 * (cur.read(d).r - cur.read(u).r + prev.read(d).r - prev.read(u).r);
+            float it = cur.read(uint2(q)).r - prev.read(uint2(q)).r;
+            sxx += ix * ix;
+            sxy += ix * iy;
+            syy += iy * iy;
+            sxt += ix * it;
+            syt += iy * it;
+        }
+    }
+
+    float det = sxx * syy - sxy * sxy;
+    float2 v = float2(0.0);
+    float confidence = 0.0;
+    if (det > 1e-8) {
+        v = float2(sxy * syt - syy * sxt, sxy * sxt - sxx * syt) / det;
+        float tr = sxx + syy;
+        float lambda_min = 0.5 * (tr - sqrt(max(tr * tr - 4.0 * det, 0.0)));
+        confidence = saturate(lambda_min * 20.0);
+    }
+    v = clamp(v * float(p.scale), -p.max_displacement, p.max_displacement);
+    flow.write(float4(0.5 + v / (2.0 * p.max_displacement), confidence, 1.0), gid);
+}
+"#;
+
+    /// The flow kernels, compiled on first use.
+    pub(crate) struct FlowPipeline {
+        luma: Retained<ProtocolObject<dyn MTLComputePipelineState>>,
+        lk: Retained<ProtocolObject<dyn MTLComputePipelineState>>,
+    }
+
+    impl FlowPipeline {
+        fn new(device: &ProtocolObject<dyn MTLDevice>) -> Result<Self> {
+            let library = device
+                .newLibraryWithSource_options_error(&NSString::from_str(FLOW_MSL), None)
+                .map_err(|e| anyhow::anyhow!("Failed to compile optical flow shader: {e}"))?;
+            let state = |name: &str| {
+                let function = library
+                    .newFunctionWithName(&NSString::from_str(name))
+                    .ok_or_else(|| anyhow::anyhow!("Optical flow kernel {name} not found"))?;
+                device
+                    .newComputePipelineStateWithFunction_error(&function)
+                    .map_err(|e| anyhow::anyhow!("Failed to create optical flow pipeline: {e}"))
+            };
+            Ok(Self {
+                luma: state("ffgl_gpu_flow_luma")?,
+                lk: state("ffgl_gpu_flow_lk")?,
+            })
+        }
+    }
+
+    fn dispatch(
+        encoder: &ProtocolObject<dyn MTLComputeCommandEncoder>,
+        state: &ProtocolObject<dyn MTLComputePipelineState>,
+        textures: &[&ProtocolObject<dyn MTLTexture>],
+        params: &FlowParams,
+        (width, height): (u32, u32),
+    ) {
+        encoder.setComputePipelineState(state);
+        unsafe {
+            for (i, texture) in textures.iter().enumerate() {
+                encoder.setTexture_atIndex(Some(*texture), i);
+            }
+            encoder.setBytes_length_atIndex(
+                std::ptr::NonNull::from(params).cast(),
+                std::mem::size_of::<FlowParams>(),
+                0,
+            );
+        }
+        encoder.dispatchThreads_threadsPerThreadgroup(
+            MTLSize {
+                width: width as usize,
+                height: height as usize,
+                depth: 1,
+            },
+            MTLSize {
+                width: 8,
+                height: 8,
+                depth: 1,
+            },
+        );
+    }
+
+    impl OpticalFlow {
+        /// Encode the flow between `input.input` and the previous update's
+        /// input on `cb`. The [`flow`](Self::flow) texture holds the result
+        /// once `cb` completes, so encode the passes reading it after this.
+        pub fn update(
+            &mut self,
+            ctx: &GpuContext,
+            cb: &CommandBuffer,
+            input: &DrawInput<'_>,
+        ) -> Result<()> {
+            let params = self.prepare(ctx, input.width, input.height)?;
+            let pipeline = match ctx.flow.get() {
+                Some(p) => p,
+                None => {
+                    let p = FlowPipeline::new(ctx.device.device())?;
+                    ctx.flow.get_or_init(|| p)
+                }
+            };
+            let current = self.luma[self.current].as_ref().unwrap().metal_texture();
+            let previous = if self.has_previous {
+                self.luma[1 - self.current]
+                    .as_ref()
+                    .unwrap()
+                    .metal_texture()
+            } else {
+                current
+            };
+            let flow = self.flow.as_ref().unwrap();
+            let size = (flow.width(), flow.height());
+
+            let encoder = cb
+                .inner
+                .computeCommandEncoder()
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal compute encoder"))?;
+            dispatch(
+                &encoder,
+                &pipeline.luma,
+                &[input.input, current],
+                &params,
+                size,
+            );
+            dispatch(
+                &encoder,
+                &pipeline.lk,
+                &[current, previous, flow.metal_texture()],
+                &params,
+                size,
+            );
+            encoder.endEncoding();
+
+            self.advance();
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) use dx11_impl::FlowPipeline;
+#[cfg(target_os = "macos")]
+pub(crate) use metal_impl::FlowPipeline;
+
+#[cfg(target_os = "windows")]
+mod dx11_impl {
+    use super::{FlowParams, OpticalFlow};
+    use crate::blit::{blob_bytes, compile_hlsl};
+    use crate::context::GpuContext;
+    use crate::DrawInput;
+    use anyhow::Result;
+    use windows::Win32::Graphics::Direct3D11::*;
+
+    const FLOW_HLSL: &str = r#"
+Texture2D<float4> src : register(t0);
+Texture2D<float4> prev : register(t1);
+RWTexture2D<float4> dst : register(u0);
+
+cbuffer FlowParams : register(b0) {
+    uint2 size;
+    uint scale;
+    float max_displacement;
+};
+
+[numthreads(8, 8, 1)]
+void cs_luma(uint3 id : SV_DispatchThreadID) {
+    uint w, h;
+    dst.GetDimensions(w, h);
+    if (id.x >= w || id.y >= h) return;
+
+    float sum = 0.0;
+    float count = 0.0;
+    for (uint y = 0; y < scale; y++) {
+        for (uint x = 0; x < scale; x++) {
+            uint2 q = id.xy * scale + uint2(x, y);
+            if (q.x < size.x && q.y < size.y) {
+                sum += dot(src[q].rgb, float3(0.2126, 0.7152, 0.0722));
+                count += 1.0;
+            }
+        }
+    }
+    float l = count > 0.0 ? sum / count : 0.0;
+    dst[id.xy] = float4(l, l, l, 1.0);
+}
+
+// `src` is the current frame's luminance here.
+[numthreads(8, 8, 1)]
+void cs_lk(uint3 id : SV_DispatchThreadID) {
+    uint w, h;
+    dst.GetDimensions(w, h);
+    int2 dims = int2(w, h);
+    if (id.x >= w || id.y >= h) return;
+
+    float sxx = 0.0, sxy = 0.0, syy = 0.0, sxt = 0.0, syt = 0.0;
+    for (int y = -2; y <= 2; y++) {
+        for (int x = -2; x <= 2; x++) {
+            int2 q = clamp(int2(id.xy) + int2(x, y), int2(0, 0), dims - 1);
+            int2 l = int2(max(q.x - 1, 0), q.y);
+            int2 r = int2(min(q.x + 1, dims.x - 1), q.y);
+            int2 u = int2(q.x, max(q.y - 1, 0));
+            int2 d = int2(q.x, min(q.y + 1, dims.y - 1));
+            float ix = 0.25 * (src[r].r - src[l].r + prev[r].r - prev[l].r);
+            float iy = 0.25 * (src[d].r - src[u].r + prev[d].r - prev[u].r);
+            float it = src[q].r - prev[q].r;
+            sxx += ix * ix;
+            sxy += ix * iy;
+            syy += iy * iy;
+            sxt += ix * it;
+            syt += iy * it;
+        }
+    }
+
+    float det = sxx * syy - sxy * sxy;
+    float2 v = 0.0;
+    float confidence = 0.0;
+    if (det > 1e-8) {
+        v = float2(sxy * syt - syy * sxt, sxy * sxt - sxx * syt) / det;
+        float tr = sxx + syy;
+        float lambda_min = 0.5 * (tr - sqrt(max(tr * tr - 4.0 * det, 0.0)));
+        confidence = saturate(lambda_min * 20.0);
+    }
+    v = clamp(v * float(scale), -max_displacement, max_displacement);
+    dst[id.xy] = float4(0.5 + v / (2.0 * max_displacement), confidence, 1.0);
+}
+"#;
+
+    /// The flow shaders and their constant buffer, created on first use.
+    pub(crate) struct FlowPipeline {
+        luma: ID3D11ComputeShader,
+        lk: ID3D11ComputeShader,
+        params: ID3D11Buffer,
+    }
+
+    fn create_shader(device: &ID3D11Device, entry: &[u8]) -> Result<ID3D11ComputeShader> {
+        let blob = compile_hlsl(FLOW_HLSL, entry, b"cs_5_0\0")?;
+        let mut shader = None;
+        unsafe { device.CreateComputeShader(blob_bytes(&blob), None, Some(&mut shader as *mut _)) }
+            .map_err(|e| anyhow::anyhow!("Failed to create optical flow shader: {e}"))?;
+        shader.ok_or_else(|| anyhow::anyhow!("D3D11 CreateComputeShader returned null"))
+    }
+
+    impl FlowPipeline {
+        fn new(device: &ID3D11Device) -> Result<Self> {
+            let desc = D3D11_BUFFER_DESC {
+                ByteWidth: std::mem::size_of::<FlowParams>() as u32,
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: D3D11_BIND_CONSTANT_BUFFER.0 as u32,
+                ..Default::default()
+            };
+            let mut params = None;
+            unsafe { device.CreateBuffer(&desc, None, Some(&mut params as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create optical flow params buffer: {e}"))?;
+            let params =
+                params.ok_or_else(|| anyhow::anyhow!("D3D11 CreateBuffer returned null"))?;
+
+            Ok(Self {
+                luma: create_shader(device, b"cs_luma\0")?,
+                lk: create_shader(device, b"cs_lk\0")?,
+                params,
+            })
+        }
+    }
+
+    /// Run `shader` over `dst` reading `srvs`, then unbind.
+    fn flow_pass(
+        ctx: &ID3D11DeviceContext,
+        shader: &ID3D11ComputeShader,
+        srvs: &[Option<ID3D11ShaderResourceView>],
+        dst: &ID3D11UnorderedAccessView,
+        (width, height): (u32, u32),
+    ) {
+        unsafe {
+            ctx.CSSetShader(shader, None);
+            ctx.CSSetShaderResources(0, Some(srvs));
+            let uavs = [Some(dst.clone())];
+            ctx.CSSetUnorderedAccessViews(0, 1, Some(uavs.as_ptr()), None);
+            ctx.Dispatch(width.div_ceil(8), height.div_ceil(8), 1);
+
+            let null_uavs: [Option<ID3D11UnorderedAccessView>; 1] = Default::default();
+            ctx.CSSetUnorderedAccessViews(0, 1, Some(null_uavs.as_ptr()), None);
+            let null_srvs: [Option<ID3D11ShaderResourceView>; 2] = Default::default();
+            ctx.CSSetShaderResources(0, Some(&null_srvs));
+        }
+    }
+
+    impl OpticalFlow {
+        /// Compute the flow between `input.input_srv` and the previous
+        /// update's input, on the [`command_context`](GpuContext::command_context).
+        pub fn update(&mut self, ctx: &GpuContext, input: &DrawInput<'_>) -> Result<()> {
+            let params = self.prepare(ctx, input.width, input.height)?;
+            let pipeline = match ctx.flow.get() {
+                Some(p) => p,
+                None => {
+                    let p = FlowPipeline::new(ctx.device.device())?;
+                    ctx.flow.get_or_init(|| p)
+                }
+            };
+            let current = self.luma[self.current].as_ref().unwrap();
+            let previous = if self.has_previous {
+                self.luma[1 - self.current].as_ref().unwrap()
+            } else {
+                current
+            };
+            let flow = self.flow.as_ref().unwrap();
+            let size = (flow.width(), flow.height());
+
+            let dc = ctx.command_context();
+            unsafe {
+                dc.UpdateSubresource(
+                    &pipeline.params,
+                    0,
+                    None,
+                    &params as *const FlowParams as *const _,
+                    0,
+                    0,
+                );
+                dc.CSSetConstantBuffers(0, Some(&[Some(pipeline.params.clone())]));
+            }
+            flow_pass(
+                dc,
+                &pipeline.luma,
+                &[Some(input.input_srv.clone())],
+                current.dx11_uav(),
+                size,
+            );
+            flow_pass(
+                dc,
+                &pipeline.lk,
+                &[current.srv(), previous.srv()],
+                flow.dx11_uav(),
+                size,
+            );
+            unsafe {
+                let null_cbufs: [Option<ID3D11Buffer>; 1] = Default::default();
+                dc.CSSetConstantBuffers(0, Some(&null_cbufs));
+            }
+
+            self.advance();
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Writing a frame's textures to PNG files, for diagnosing pass-specific
+//! corruption on machines without a GPU debugger.
+//!
+//! Keep a [`FrameDump`] in the plugin, declare [`FrameDump::param`] among
+//! its parameters, pass that parameter's value to
+//! [`FrameDump::set_trigger`] and return the dump from
+//! [`GpuPlugin::frame_dump`](crate::GpuPlugin::frame_dump). When the user
+//! presses the button, the framework reads back the next frame's input,
+//! every intermediate used in it and the output, and writes them to the
+//! dump's directory:
+//!
+//! ```text
+//! frame000123_input.png
+//! frame000123_blurred.png
+//! frame000123_output.png
+//! ```
+//!
+//! The readback waits for the GPU, so it only happens on request. Pixels
+//! are written as stored, without color conversion; float textures are
+//! clamped to 0–1 and written as 8-bit. The files are encoded on a
+//! background thread. A request made while the plugin tiles its frames
+//! waits for an untiled frame.
+
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
+
+use ffgl_core::parameters::{ParameterTypes, SimpleParamInfo};
+
+/// A frame dump trigger and where the files go, for one plugin instance.
+#[derive(Debug, Clone)]
+pub struct FrameDump {
+    dir: PathBuf,
+    /// The trigger parameter's last value, to detect presses.
+    trigger: bool,
+    requested: bool,
+}
+
+impl Default for FrameDump {
+    /// Dumps to `ffgl-gpu-dumps` in the system's temporary directory.
+    fn default() -> Self {
+        Self::new(std::env::temp_dir().join("ffgl-gpu-dumps"))
+    }
+}
+
+impl FrameDump {
+    /// Dumps to `dir`, which is created on the first dump.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            trigger: false,
+            requested: false,
+        }
+    }
+
+    /// A "Dump Frame" event parameter, for [`set_trigger`](Self::set_trigger).
+    pub fn param() -> SimpleParamInfo {
+        SimpleParamInfo {
+            name: CString::new("Dump Frame").unwrap(),
+            param_type: ParameterTypes::Event,
+            default: Some(0.0),
+            ..Default::default()
+        }
+    }
+
+    /// Feed the trigger parameter's value; a press requests a dump.
+    pub fn set_trigger(&mut self, value: f32) {
+        let pressed = value >= 0.5;
+        if pressed && !self.trigger {
+            self.request();
+        }
+        self.trigger = pressed;
+    }
+
+    /// Dump the next drawn frame.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Whether a dump is waiting for the next frame.
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    /// Directory the files are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// If a dump was requested, read back `input`, the context's
+    /// intermediates used this frame and `output`, and write them out.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) fn dump_if_requested(
+        &mut self,
+        ctx: &crate::GpuContext,
+        frame: u64,
+        input: &NativeTexture,
+        output: &NativeTexture,
+    ) {
+        if !std::mem::take(&mut self.requested) {
+            return;
+        }
+
+        let intermediates = ctx.intermediates.used_this_frame();
+        let mut textures: Vec<(String, &NativeTexture)> = vec![("input".to_owned(), input)];
+        for (name, texture) in &intermediates {
+            #[cfg(target_os = "macos")]
+            let native = texture.metal_texture();
+            #[cfg(target_os = "windows")]
+            let native = texture.dx11_texture();
+            textures.push((name.clone(), native));
+        }
+        textures.push(("output".to_owned(), output));
+
+        let mut images = Vec::with_capacity(textures.len());
+        for (name, texture) in textures {
+            match read_image(ctx, texture) {
+                Ok(image) => images.push((file_name(frame, &name), image)),
+                Err(e) => gpu_interop::warn_limited!("Frame dump: failed to read {name}: {e}"),
+            }
+        }
+
+        let dir = self.dir.clone();
+        let spawned = std::thread::Builder::new()
+            .name("ffgl-gpu frame dump".into())
+            .spawn(move || write_images(&dir, images));
+        if let Err(e) = spawned {
+            gpu_interop::warn_limited!("Frame dump: failed to start writer thread: {e}");
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+type NativeTexture = objc2::runtime::ProtocolObject<dyn objc2_metal::MTLTexture>;
+#[cfg(target_os = "windows")]
+type NativeTexture = windows::Win32::Graphics::Direct3D11::ID3D11Texture2D;
+
+/// A texture read back to the CPU, as tightly packed RGBA8.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+struct Image {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+/// `frame000123_name.png`, with anything but letters, digits, `-` and `_`
+/// in the name replaced.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn file_name(frame: u64, name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("frame{frame:06}_{name}.png")
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn write_images(dir: &Path, images: Vec<(String, Image)>) {
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        tracing::error!("Frame dump: failed to create {}: {e}", dir.display());
+        return;
+    }
+    for (name, image) in &images {
+        let path = dir.join(name);
+        match write_png(&path, image) {
+            Ok(()) => tracing::info!("Frame dump: wrote {}", path.display()),
+            Err(e) => tracing::error!("Frame dump: failed to write {}: {e}", path.display()),
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn write_png(path: &Path, image: &Image) -> anyhow::Result<()> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&image.rgba)?;
+    Ok(())
+}
+
+/// How a texture's pixels are stored.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Debug, Clone, Copy)]
+enum Layout {
+    Bgra8,
+    Rgba8,
+    Rg11b10Float,
+    Rgba16Float,
+    Rgba32Float,
+}
+
+#[cfg(target_os = "macos")]
+fn read_image(ctx: &crate::GpuContext, texture: &NativeTexture) -> anyhow::Result<Image> {
+    use objc2_metal::{MTLPixelFormat, MTLTexture};
+
+    let layout = match texture.pixelFormat() {
+        MTLPixelFormat::BGRA8Unorm | MTLPixelFormat::BGRA8Unorm_sRGB => Layout::Bgra8,
+        MTLPixelFormat::RGBA8Unorm | MTLPixelFormat::RGBA8Unorm_sRGB => Layout::Rgba8,
+        MTLPixelFormat::RG11B10Float => Layout::Rg11b10Float,
+        MTLPixelFormat::RGBA16Float => Layout::Rgba16Float,
+        MTLPixelFormat::RGBA32Float => Layout::Rgba32Float,
+        other => anyhow::bail!("unsupported pixel format {other:?}"),
+    };
+    let bytes = ctx.read_texture(texture)?;
+    Ok(Image {
+        width: texture.width() as u32,
+        height: texture.height() as u32,
+        rgba: to_rgba8(layout, &bytes),
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn read_image(ctx: &crate::GpuContext, texture: &NativeTexture) -> anyhow::Result<Image> {
+    use windows::Win32::Graphics::Direct3D11::D3D11_TEXTURE2D_DESC;
+    use windows::Win32::Graphics::Dxgi::Common::*;
+
+    let mut desc = D3D11_TEXTURE2D_DESC::default();
+    unsafe { texture.GetDesc(&mut desc) };
+    let layout = match desc.Format {
+        DXGI_FORMAT_B8G8R8A8_UNORM | DXGI_FORMAT_B8G8R8A8_UNORM_SRGB => Layout::Bgra8,
+        DXGI_FORMAT_R8G8B8A8_UNORM | DXGI_FORMAT_R8G8B8A8_UNORM_SRGB => Layout::Rgba8,
+        DXGI_FORMAT_R11G11B10_FLOAT => Layout::Rg11b10Float,
+        DXGI_FORMAT_R16G16B16A16_FLOAT => Layout::Rgba16Float,
+        DXGI_FORMAT_R32G32B32A32_FLOAT => Layout::Rgba32Float,
+        other => anyhow::bail!("unsupported texture format {other:?}"),
+    };
+    let bytes = ctx.read_texture(texture)?;
+    Ok(Image {
+        width: desc.Width,
+        height: desc.Height,
+        rgba: to_rgba8(layout, &bytes),
+    })
+}
+
+/// Convert tightly packed pixels to RGBA8.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn to_rgba8(layout: Layout, bytes: &[u8]) -> Vec<u8> {
+    fn byte(c: f32) -> u8 {
+        (c.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+    let u32_at = |b: &[u8], i: usize| u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+
+    match layout {
+        Layout::Rgba8 => bytes.to_vec(),
+        Layout::Bgra8 => bytes
+            .chunks_exact(4)
+            .flat_map(|p| [p[2], p[1], p[0], p[3]])
+            .collect(),
+        Layout::Rg11b10Float => bytes
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let v = u32_at(p, 0);
+                [
+                    byte(small_float(v & 0x7ff, 6)),
+                    byte(small_float((v >> 11) & 0x7ff, 6)),
+                    byte(small_float(v >> 22, 5)),
+                    255,
+                ]
+            })
+            .collect(),
+        Layout::Rgba16Float => bytes
+            .chunks_exact(2)
+            .map(|h| byte(half_to_f32(u16::from_le_bytes([h[0], h[1]]))))
+            .collect(),
+        Layout::Rgba32Float => bytes
+            .chunks_exact(4)
+            .map(|f| byte(f32::from_bits(u32_at(f, 0))))
+            .collect(),
+    }
+}
+
+/// An IEEE 754 half-precision float.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn half_to_f32(h: u16) -> f32 {
+    let sign = if h & 0x8000 != 0 { -1.0 } else { 1.0 };
+    sign * small_float(u32::from(h & 0x7fff), 10)
+}
+
+/// An unsigned float with a 5-bit exponent and `mantissa_bits` of mantissa,
+/// as in half floats and the packed 11- and 10-bit formats.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn small_float(bits: u32, mantissa_bits: u32) -> f32 {
+    let exponent = (bits >> mantissa_bits) & 0x1f;
+    let scale = (1u32 << mantissa_bits) as f32;
+    let mantissa = (bits & ((1 << mantissa_bits) - 1)) as f32 / scale;
+    match exponent {
+        0 => mantissa * 2f32.powi(-14),
+        31 if mantissa == 0.0 => f32::INFINITY,
+        31 => f32::NAN,
+        e => (1.0 + mantissa) * 2f32.powi(e as i32 - 15),
+    }
+}
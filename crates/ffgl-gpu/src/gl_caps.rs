@@ -0,0 +1,210 @@
+//! Capability probe for the host's OpenGL context.
+//!
+//! The bridges move frames between the host and the GPU API with
+//! `glBlitFramebuffer` (GL 3.0 / `GL_ARB_framebuffer_object`), and the Metal
+//! bridge binds IOSurfaces as rectangle textures (GL 3.1 /
+//! `GL_ARB_texture_rectangle`). On an older context those entry points may
+//! be missing entirely and the first call would crash the host, so the
+//! draw loop probes the context once per thread and, if anything is
+//! missing, passes frames through untouched and reports the
+//! [`FallbackReason`] instead.
+
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::fmt;
+
+use gl::types::GLint;
+
+/// Why [`draw_gpu_effect`](crate::draw_gpu_effect) is passing frames through
+/// instead of running the plugin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FallbackReason {
+    /// The host context is older than the bridge needs and lacks the
+    /// extension that would cover it.
+    GlVersionTooOld {
+        /// Version of the host context.
+        found: (i32, i32),
+        /// Lowest version providing the missing feature.
+        required: (i32, i32),
+        /// Extension that would provide it on an older context.
+        extension: &'static str,
+    },
+    /// The context reports the feature but the driver doesn't export the
+    /// entry point.
+    MissingEntryPoint(&'static str),
+}
+
+impl fmt::Display for FallbackReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GlVersionTooOld {
+                found,
+                required,
+                extension,
+            } => write!(
+                f,
+                "host OpenGL context is {}.{}; GPU processing needs {}.{} or {extension}",
+                found.0, found.1, required.0, required.1,
+            ),
+            Self::MissingEntryPoint(name) => {
+                write!(f, "OpenGL driver does not export {name}")
+            }
+        }
+    }
+}
+
+/// What the host's OpenGL context supports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlCapabilities {
+    /// Context version as `(major, minor)`.
+    pub version: (i32, i32),
+    /// `glBlitFramebuffer` is available.
+    pub framebuffer_blit: bool,
+    /// Rectangle textures are available.
+    pub texture_rectangle: bool,
+    /// Compute shaders (`glDispatchCompute`) are available, for plugins
+    /// issuing their own GL compute work.
+    pub compute_shaders: bool,
+    extensions: Vec<String>,
+}
+
+impl GlCapabilities {
+    /// Query the current context.
+    ///
+    /// # Safety
+    ///
+    /// A GL context must be current and the `gl` function pointers loaded.
+    pub unsafe fn probe() -> Self {
+        let version = context_version();
+        let extensions = context_extensions(version);
+        let has = |name: &str| extensions.iter().any(|e| e == name);
+
+        let framebuffer_blit = version >= (3, 0) || has("GL_ARB_framebuffer_object");
+        let texture_rectangle =
+            version >= (3, 1) || has("GL_ARB_texture_rectangle") || has("GL_EXT_texture_rectangle");
+        let compute_shaders =
+            (version >= (4, 3) || has("GL_ARB_compute_shader")) && gl::DispatchCompute::is_loaded();
+
+        Self {
+            version,
+            framebuffer_blit,
+            texture_rectangle,
+            compute_shaders,
+            extensions,
+        }
+    }
+
+    /// Whether the context advertises `name`.
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extensions.iter().any(|e| e == name)
+    }
+
+    /// Check that everything the platform bridge needs is present.
+    pub fn check_bridge_support(&self) -> Result<(), FallbackReason> {
+        if !self.framebuffer_blit {
+            return Err(FallbackReason::GlVersionTooOld {
+                found: self.version,
+                required: (3, 0),
+                extension: "GL_ARB_framebuffer_object",
+            });
+        }
+        if !gl::BlitFramebuffer::is_loaded() {
+            return Err(FallbackReason::MissingEntryPoint("glBlitFramebuffer"));
+        }
+        if cfg!(target_os = "macos") && !self.texture_rectangle {
+            return Err(FallbackReason::GlVersionTooOld {
+                found: self.version,
+                required: (3, 1),
+                extension: "GL_ARB_texture_rectangle",
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Version of the current context. `GL_MAJOR_VERSION` only exists from GL
+/// 3.0 on, so older contexts are parsed from the `GL_VERSION` string.
+unsafe fn context_version() -> (i32, i32) {
+    let (mut major, mut minor): (GLint, GLint) = (0, 0);
+    gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+    gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    if major > 0 {
+        return (major, minor);
+    }
+    // Discard the INVALID_ENUM the queries raise on pre-3.0 contexts.
+    while gl::GetError() != gl::NO_ERROR {}
+
+    let version = gl::GetString(gl::VERSION);
+    if version.is_null() {
+        return (0, 0);
+    }
+    let version = CStr::from_ptr(version as *const _).to_string_lossy();
+    let mut numbers = version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|n| n.parse().ok());
+    (numbers.next().unwrap_or(0), numbers.next().unwrap_or(0))
+}
+
+/// Extensions advertised by the current context.
+unsafe fn context_extensions(version: (i32, i32)) -> Vec<String> {
+    if version >= (3, 0) && gl::GetStringi::is_loaded() {
+        let mut count = 0;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        return (0..count.max(0) as u32)
+            .filter_map(|i| {
+                let name = gl::GetStringi(gl::EXTENSIONS, i);
+                (!name.is_null()).then(|| {
+                    CStr::from_ptr(name as *const _)
+                        .to_string_lossy()
+                        .into_owned()
+                })
+            })
+            .collect();
+    }
+    let names = gl::GetString(gl::EXTENSIONS);
+    if names.is_null() {
+        return Vec::new();
+    }
+    CStr::from_ptr(names as *const _)
+        .to_string_lossy()
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect()
+}
+
+thread_local! {
+    /// Probe result for the context used on this thread.
+    static BRIDGE_SUPPORT: RefCell<Option<Result<(), FallbackReason>>> =
+        const { RefCell::new(None) };
+}
+
+/// Why GPU processing is unavailable on the current thread's context, if it
+/// is. The context is probed on the first call; the reason is logged once.
+///
+/// # Safety
+///
+/// A GL context must be current.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) unsafe fn bridge_fallback_reason() -> Option<FallbackReason> {
+    BRIDGE_SUPPORT.with(|cell| {
+        cell.borrow_mut()
+            .get_or_insert_with(|| {
+                let caps = GlCapabilities::probe();
+                tracing::debug!(?caps, "Probed host GL context");
+                let support = caps.check_bridge_support();
+                if let Err(reason) = &support {
+                    tracing::warn!("GPU processing disabled, passing frames through: {reason}");
+                }
+                support
+            })
+            .clone()
+            .err()
+    })
+}
+
+/// Why [`draw_gpu_effect`](crate::draw_gpu_effect) passes frames through on
+/// this thread, if it has found the host's GL context lacking. `None` until
+/// the first frame has been drawn.
+pub fn fallback_reason() -> Option<FallbackReason> {
+    BRIDGE_SUPPORT.with(|cell| cell.borrow().clone().and_then(Result::err))
+}
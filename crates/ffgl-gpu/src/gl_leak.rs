@@ -0,0 +1,179 @@
+//! GL state leak detector (`gl-leak-check` feature).
+//!
+//! Hosts share their GL context with every plugin, so a binding we forget to
+//! restore shows up as corruption somewhere else in the host, far from the
+//! cause. With the feature enabled, [`draw_gpu_effect`] snapshots a broad set
+//! of GL state on entry and compares against it after each stage, logging
+//! every value that changed together with the stage that changed it.
+//!
+//! Plugins doing their own GL work can call [`check`] after it with their own
+//! pass name. Without the feature both functions compile to nothing.
+//!
+//! [`draw_gpu_effect`]: crate::draw_gpu_effect
+
+/// Record the current GL state as the baseline for [`check`].
+#[inline]
+pub fn begin() {
+    #[cfg(feature = "gl-leak-check")]
+    imp::begin();
+}
+
+/// Compare the GL state against the baseline and log a warning for each
+/// value `pass` left changed. The current state then becomes the baseline,
+/// so a leak is reported once, against the pass that caused it.
+#[inline]
+pub fn check(pass: &str) {
+    #[cfg(feature = "gl-leak-check")]
+    imp::check(pass);
+    #[cfg(not(feature = "gl-leak-check"))]
+    let _ = pass;
+}
+
+#[cfg(feature = "gl-leak-check")]
+mod imp {
+    use gl::types::{GLenum, GLint};
+    use std::cell::RefCell;
+    use tracing::warn;
+
+    /// Texture units, indexed buffer slots and image units inspected.
+    const MAX_INDEXED: GLint = 16;
+
+    thread_local! {
+        static BASELINE: RefCell<Option<Snapshot>> = const { RefCell::new(None) };
+    }
+
+    /// Named GL state values. Multi-valued state (viewport, write mask) is
+    /// stored as one entry.
+    struct Snapshot(Vec<(String, Vec<GLint>)>);
+
+    impl Snapshot {
+        unsafe fn take() -> Self {
+            let mut s = Vec::new();
+
+            let mut int = |name: &str, pname: GLenum, len: usize| {
+                let mut v = vec![0; len];
+                gl::GetIntegerv(pname, v.as_mut_ptr());
+                s.push((name.to_owned(), v));
+            };
+            int("CURRENT_PROGRAM", gl::CURRENT_PROGRAM, 1);
+            int("ACTIVE_TEXTURE", gl::ACTIVE_TEXTURE, 1);
+            int("VERTEX_ARRAY_BINDING", gl::VERTEX_ARRAY_BINDING, 1);
+            int("ARRAY_BUFFER_BINDING", gl::ARRAY_BUFFER_BINDING, 1);
+            int(
+                "PIXEL_PACK_BUFFER_BINDING",
+                gl::PIXEL_PACK_BUFFER_BINDING,
+                1,
+            );
+            int(
+                "PIXEL_UNPACK_BUFFER_BINDING",
+                gl::PIXEL_UNPACK_BUFFER_BINDING,
+                1,
+            );
+            int("DRAW_FRAMEBUFFER_BINDING", gl::DRAW_FRAMEBUFFER_BINDING, 1);
+            int("READ_FRAMEBUFFER_BINDING", gl::READ_FRAMEBUFFER_BINDING, 1);
+            int("RENDERBUFFER_BINDING", gl::RENDERBUFFER_BINDING, 1);
+            int("READ_BUFFER", gl::READ_BUFFER, 1);
+            int("DRAW_BUFFER", gl::DRAW_BUFFER, 1);
+            int("VIEWPORT", gl::VIEWPORT, 4);
+            int("SCISSOR_BOX", gl::SCISSOR_BOX, 4);
+            int("BLEND_SRC_RGB", gl::BLEND_SRC_RGB, 1);
+            int("BLEND_DST_RGB", gl::BLEND_DST_RGB, 1);
+            int("BLEND_SRC_ALPHA", gl::BLEND_SRC_ALPHA, 1);
+            int("BLEND_DST_ALPHA", gl::BLEND_DST_ALPHA, 1);
+            int("BLEND_EQUATION_RGB", gl::BLEND_EQUATION_RGB, 1);
+            int("BLEND_EQUATION_ALPHA", gl::BLEND_EQUATION_ALPHA, 1);
+            int("COLOR_WRITEMASK", gl::COLOR_WRITEMASK, 4);
+            int("PACK_ALIGNMENT", gl::PACK_ALIGNMENT, 1);
+            int("UNPACK_ALIGNMENT", gl::UNPACK_ALIGNMENT, 1);
+            int("UNPACK_ROW_LENGTH", gl::UNPACK_ROW_LENGTH, 1);
+
+            for (name, cap) in [
+                ("BLEND", gl::BLEND),
+                ("SCISSOR_TEST", gl::SCISSOR_TEST),
+                ("DEPTH_TEST", gl::DEPTH_TEST),
+                ("STENCIL_TEST", gl::STENCIL_TEST),
+                ("CULL_FACE", gl::CULL_FACE),
+                ("FRAMEBUFFER_SRGB", gl::FRAMEBUFFER_SRGB),
+            ] {
+                s.push((name.to_owned(), vec![gl::IsEnabled(cap) as GLint]));
+            }
+
+            // Per-unit texture and sampler bindings need the unit selected.
+            let mut active = 0;
+            let mut units = 0;
+            gl::GetIntegerv(gl::ACTIVE_TEXTURE, &mut active);
+            gl::GetIntegerv(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut units);
+            for unit in 0..units.min(MAX_INDEXED) {
+                gl::ActiveTexture(gl::TEXTURE0 + unit as GLenum);
+                for (name, pname) in [
+                    ("TEXTURE_BINDING_2D", gl::TEXTURE_BINDING_2D),
+                    ("TEXTURE_BINDING_RECTANGLE", gl::TEXTURE_BINDING_RECTANGLE),
+                    ("SAMPLER_BINDING", gl::SAMPLER_BINDING),
+                ] {
+                    let mut v = 0;
+                    gl::GetIntegerv(pname, &mut v);
+                    s.push((format!("{name}[{unit}]"), vec![v]));
+                }
+            }
+            gl::ActiveTexture(active as GLenum);
+
+            let (mut major, mut minor) = (0, 0);
+            gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+            gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+            let version = (major, minor);
+
+            let mut indexed = |name: &str, pname: GLenum, max: GLenum| {
+                let mut count = 0;
+                gl::GetIntegerv(max, &mut count);
+                for i in 0..count.min(MAX_INDEXED) {
+                    let mut v = 0;
+                    gl::GetIntegeri_v(pname, i as u32, &mut v);
+                    s.push((format!("{name}[{i}]"), vec![v]));
+                }
+            };
+            if version >= (3, 1) {
+                indexed(
+                    "UNIFORM_BUFFER_BINDING",
+                    gl::UNIFORM_BUFFER_BINDING,
+                    gl::MAX_UNIFORM_BUFFER_BINDINGS,
+                );
+            }
+            if version >= (4, 2) {
+                indexed(
+                    "IMAGE_BINDING_NAME",
+                    gl::IMAGE_BINDING_NAME,
+                    gl::MAX_IMAGE_UNITS,
+                );
+            }
+            if version >= (4, 3) {
+                indexed(
+                    "SHADER_STORAGE_BUFFER_BINDING",
+                    gl::SHADER_STORAGE_BUFFER_BINDING,
+                    gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS,
+                );
+            }
+
+            Self(s)
+        }
+    }
+
+    pub(super) fn begin() {
+        let snapshot = unsafe { Snapshot::take() };
+        BASELINE.with(|cell| *cell.borrow_mut() = Some(snapshot));
+    }
+
+    pub(super) fn check(pass: &str) {
+        let current = unsafe { Snapshot::take() };
+        BASELINE.with(|cell| {
+            let mut baseline = cell.borrow_mut();
+            if let Some(before) = baseline.as_ref() {
+                for ((name, was), (_, now)) in before.0.iter().zip(&current.0) {
+                    if was != now {
+                        warn!(pass, state = %name, ?was, ?now, "GL state leaked");
+                    }
+                }
+            }
+            *baseline = Some(current);
+        });
+    }
+}
@@ -0,0 +1,204 @@
+//! Heap-backed texture pool for intermediate passes (macOS).
+//!
+//! [`GpuContext::acquire_texture`] hands out [`GpuTexture`]s suballocated
+//! from an `MTLHeap` instead of creating each one on the device. The heap
+//! grows to fit the plugin's working set, and once it has, acquiring
+//! intermediates costs no device allocation at all.
+//!
+//! Textures given back with [`GpuContext::recycle_texture`] keep their
+//! contents and are handed out again by the next matching acquire.
+//! Textures given back with [`GpuContext::alias_texture`] are made
+//! aliasable: their memory is reused by later allocations, so passes that
+//! never overlap (e.g. the intermediates of a blur followed by those of a
+//! glow) share the same memory.
+//!
+//! The heap tracks hazards, so Metal orders a pass writing an aliased
+//! allocation after the passes that still read the previous one. Heaps are
+//! made resident on compute encoders that bind an
+//! [`ArgumentBuffer`](crate::ArgumentBuffer), since resources reached
+//! through one aren't tracked individually.
+//!
+//! ```rust,ignore
+//! let blurred = ctx.acquire_texture(width, height)?;
+//! // ... passes writing and reading `blurred` ...
+//! ctx.alias_texture(blurred);
+//! ```
+
+#[cfg(target_os = "macos")]
+pub(crate) use metal_impl::TexturePool;
+
+#[cfg(target_os = "macos")]
+mod metal_impl {
+    use crate::budget::Allocation;
+    use crate::context::GpuContext;
+    use crate::dispatch::intermediate_texture_descriptor;
+    use crate::intermediate::IntermediateFormat;
+    use crate::texture::GpuTexture;
+    use anyhow::Result;
+    use objc2::rc::Retained;
+    use objc2::runtime::ProtocolObject;
+    use objc2_metal::*;
+    use std::cell::RefCell;
+
+    /// Upper bound on recycled textures kept for reuse. Beyond this the
+    /// oldest is made aliasable instead.
+    const MAX_RECYCLED_TEXTURES: usize = 16;
+
+    /// Heaps and recycled textures backing [`GpuContext::acquire_texture`].
+    #[derive(Default)]
+    pub(crate) struct TexturePool {
+        /// Every heap that still holds allocations; the last one is the one
+        /// new textures come from.
+        heaps: RefCell<Vec<Retained<ProtocolObject<dyn MTLHeap>>>>,
+        /// Textures handed back with `recycle_texture`.
+        recycled: RefCell<Vec<GpuTexture>>,
+    }
+
+    impl TexturePool {
+        /// Grow to a new heap that fits `needed` more bytes on top of what
+        /// the current heaps already hold.
+        fn grow(&self, device: &ProtocolObject<dyn MTLDevice>, needed: usize) -> Result<()> {
+            let mut heaps = self.heaps.borrow_mut();
+            heaps.retain(|heap| heap.usedSize() > 0);
+            let used: usize = heaps.iter().map(|heap| heap.usedSize()).sum();
+            let last = heaps.last().map_or(0, |heap| heap.size());
+            let size = (used + needed).max(last * 2);
+
+            let desc = MTLHeapDescriptor::new();
+            desc.setSize(size);
+            desc.setStorageMode(MTLStorageMode::Private);
+            desc.setHazardTrackingMode(MTLHazardTrackingMode::Tracked);
+            let heap = device
+                .newHeapWithDescriptor(&desc)
+                .ok_or_else(|| anyhow::anyhow!("Failed to create Metal heap of {size} bytes"))?;
+            tracing::debug!("Texture heap grown to {size} bytes ({used} bytes in older heaps)");
+            heaps.push(heap);
+            Ok(())
+        }
+
+        /// Total size of the heaps, used or not.
+        pub(crate) fn allocated_bytes(&self) -> u64 {
+            self.heaps
+                .borrow()
+                .iter()
+                .map(|heap| heap.size() as u64)
+                .sum()
+        }
+
+        /// Declare every heap resident on `encoder`.
+        pub(crate) fn use_heaps(&self, encoder: &ProtocolObject<dyn MTLComputeCommandEncoder>) {
+            for heap in self.heaps.borrow().iter() {
+                encoder.useHeap(heap);
+            }
+        }
+    }
+
+    impl GpuContext {
+        /// Hand out a `BGRA8Unorm` intermediate texture of the given size,
+        /// with the same usage as [`create_texture`](Self::create_texture).
+        ///
+        /// A matching recycled texture is reused if there is one; otherwise
+        /// the texture is suballocated from the texture heap, which grows
+        /// when it is full.
+        pub fn acquire_texture(&self, width: u32, height: u32) -> Result<GpuTexture> {
+            self.acquire_texture_with_format(width, height, IntermediateFormat::Rgba8)
+        }
+
+        /// [`acquire_texture`](Self::acquire_texture) in another format.
+        pub fn acquire_texture_with_format(
+            &self,
+            width: u32,
+            height: u32,
+            format: IntermediateFormat,
+        ) -> Result<GpuTexture> {
+            {
+                let mut recycled = self.textures.recycled.borrow_mut();
+                if let Some(i) = recycled
+                    .iter()
+                    .position(|t| t.has_dims(width, height) && t.format == format)
+                {
+                    return Ok(recycled.swap_remove(i));
+                }
+            }
+
+            let device = self.device.device();
+            let desc = intermediate_texture_descriptor(width, height, format);
+            let size_and_align = device.heapTextureSizeAndAlignWithDescriptor(&desc);
+
+            let fits = self.textures.heaps.borrow().last().is_some_and(|heap| {
+                heap.maxAvailableSizeWithAlignment(size_and_align.align) >= size_and_align.size
+            });
+            if !fits {
+                self.textures
+                    .grow(device, size_and_align.size)
+                    .map_err(|e| self.allocation_error(e.to_string()))?;
+            }
+
+            let texture = self
+                .textures
+                .heaps
+                .borrow()
+                .last()
+                .and_then(|heap| heap.newTextureWithDescriptor(&desc))
+                .ok_or_else(|| {
+                    self.allocation_error(format!(
+                        "Failed to allocate heap texture ({width}x{height})"
+                    ))
+                })?;
+
+            Ok(GpuTexture {
+                width,
+                height,
+                format,
+                metal: texture,
+                _allocation: Allocation::new(
+                    width as u64 * height as u64 * format.bytes_per_pixel() as u64,
+                ),
+                _tracked: None,
+            })
+        }
+
+        /// Give `texture` back for reuse by a later
+        /// [`acquire_texture`](Self::acquire_texture) of the same size and
+        /// format. Its contents are kept, so this suits cached frames and
+        /// feedback buffers.
+        pub fn recycle_texture(&self, texture: GpuTexture) {
+            let mut recycled = self.textures.recycled.borrow_mut();
+            if recycled.len() >= MAX_RECYCLED_TEXTURES {
+                let oldest = recycled.remove(0);
+                self.alias_texture(oldest);
+            }
+            recycled.push(texture);
+        }
+
+        /// Give `texture` back and let later allocations reuse its memory.
+        ///
+        /// Call this once every pass using the texture has been encoded; the
+        /// contents are undefined afterward. Textures that didn't come from
+        /// [`acquire_texture`](Self::acquire_texture) are simply dropped.
+        pub fn alias_texture(&self, texture: GpuTexture) {
+            if texture.metal.heap().is_some() {
+                unsafe { texture.metal.makeAliasable() };
+            }
+        }
+
+        /// Grow the texture heap up front to fit `count` 8-bit intermediates
+        /// of the given size, so the first frames don't pay for growing it.
+        pub fn reserve_textures(&self, width: u32, height: u32, count: usize) -> Result<()> {
+            let device = self.device.device();
+            let desc = intermediate_texture_descriptor(width, height, IntermediateFormat::Rgba8);
+            let size_and_align = device.heapTextureSizeAndAlignWithDescriptor(&desc);
+            let needed = size_and_align.size.next_multiple_of(size_and_align.align) * count;
+
+            let available = self.textures.heaps.borrow().last().map_or(0, |heap| {
+                heap.maxAvailableSizeWithAlignment(size_and_align.align)
+            });
+            if available < needed {
+                self.textures
+                    .grow(device, needed)
+                    .map_err(|e| self.allocation_error(e.to_string()))?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Named intermediate textures for [`DrawInput::intermediate`].
+//!
+//! Multi-pass effects need scratch textures at the processing size. Rather
+//! than keeping one `Option<GpuTexture>` field per pass and reallocating
+//! them on resize, a plugin asks for a texture by name each frame:
+//!
+//! ```rust,ignore
+//! let after_gray = input.intermediate("after_gray")?;
+//! ```
+//!
+//! The context keeps one texture per name and size, and hands the same one
+//! out again on later frames. Textures no name has asked for in
+//! [`EVICT_AFTER_FRAMES`] draws are dropped. On macOS they come from the
+//! [texture heap](crate::heap).
+//!
+//! The contents are only meaningful within the frame: every instance drawn
+//! on the thread shares the context, and with it the names. Use
+//! [`GpuContext::create_texture`](crate::GpuContext::create_texture) for
+//! textures that must keep their contents between frames.
+//!
+//! Intermediates are 8-bit unless asked for in another
+//! [`IntermediateFormat`] with
+//! [`DrawInput::intermediate_with_format`]; the same name in two formats is
+//! two textures.
+//!
+//! [`DrawInput::intermediate`]: crate::DrawInput::intermediate
+//! [`DrawInput::intermediate_with_format`]: crate::DrawInput::intermediate_with_format
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::cell::{Cell, RefCell};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::rc::Rc;
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::{GpuContext, GpuTexture};
+
+/// Draws after which an intermediate nobody asked for is dropped.
+pub const EVICT_AFTER_FRAMES: u64 = 120;
+
+/// Pixel format of a [`GpuTexture`](crate::GpuTexture) created by the
+/// framework.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IntermediateFormat {
+    /// 8 bits per channel, unsigned normalized, stored as BGRA.
+    #[default]
+    Rgba8,
+    /// Packed 11-, 11- and 10-bit floats for red, green and blue, with no
+    /// alpha (it reads as 1). Same size as `Rgba8`, with the range for HDR
+    /// color such as accumulated bloom.
+    Rg11b10Float,
+    /// 16-bit float per channel.
+    Rgba16Float,
+    /// 32-bit float per channel.
+    Rgba32Float,
+}
+
+impl IntermediateFormat {
+    /// Size of one pixel in bytes.
+    pub fn bytes_per_pixel(self) -> u32 {
+        match self {
+            Self::Rgba8 | Self::Rg11b10Float => 4,
+            Self::Rgba16Float => 8,
+            Self::Rgba32Float => 16,
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    pub(crate) fn metal(self) -> objc2_metal::MTLPixelFormat {
+        use objc2_metal::MTLPixelFormat;
+        match self {
+            Self::Rgba8 => MTLPixelFormat::BGRA8Unorm,
+            Self::Rg11b10Float => MTLPixelFormat::RG11B10Float,
+            Self::Rgba16Float => MTLPixelFormat::RGBA16Float,
+            Self::Rgba32Float => MTLPixelFormat::RGBA32Float,
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    pub(crate) fn dxgi(self) -> windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT {
+        use windows::Win32::Graphics::Dxgi::Common::*;
+        match self {
+            Self::Rgba8 => DXGI_FORMAT_B8G8R8A8_UNORM,
+            Self::Rg11b10Float => DXGI_FORMAT_R11G11B10_FLOAT,
+            Self::Rgba16Float => DXGI_FORMAT_R16G16B16A16_FLOAT,
+            Self::Rgba32Float => DXGI_FORMAT_R32G32B32A32_FLOAT,
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+struct Entry {
+    name: String,
+    texture: Rc<GpuTexture>,
+    last_used: u64,
+}
+
+/// The named intermediates of one context.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Default)]
+pub(crate) struct NamedTextures {
+    entries: RefCell<Vec<Entry>>,
+    frame: Cell<u64>,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl NamedTextures {
+    /// Start a new draw and drop the textures unused for too long.
+    pub(crate) fn next_frame(&self) {
+        let frame = self.frame.get() + 1;
+        self.frame.set(frame);
+        self.entries
+            .borrow_mut()
+            .retain(|e| frame - e.last_used <= EVICT_AFTER_FRAMES);
+    }
+
+    /// The textures handed out during the current draw, with their names.
+    #[cfg(feature = "frame-dump")]
+    pub(crate) fn used_this_frame(&self) -> Vec<(String, Rc<GpuTexture>)> {
+        let frame = self.frame.get();
+        self.entries
+            .borrow()
+            .iter()
+            .filter(|e| e.last_used == frame)
+            .map(|e| (e.name.clone(), e.texture.clone()))
+            .collect()
+    }
+
+    /// The texture called `name` at `width` x `height` in `format`, created
+    /// if there is none yet.
+    pub(crate) fn get(
+        &self,
+        ctx: &GpuContext,
+        name: &str,
+        width: u32,
+        height: u32,
+        format: IntermediateFormat,
+    ) -> anyhow::Result<Rc<GpuTexture>> {
+        let frame = self.frame.get();
+        let mut entries = self.entries.borrow_mut();
+        let found = entries.iter_mut().find(|e| {
+            e.name == name && e.texture.has_dims(width, height) && e.texture.format == format
+        });
+        if let Some(entry) = found {
+            entry.last_used = frame;
+            return Ok(entry.texture.clone());
+        }
+
+        #[cfg(target_os = "macos")]
+        let texture = ctx.acquire_texture_with_format(width, height, format)?;
+        #[cfg(target_os = "windows")]
+        let texture = ctx.create_texture_with_format(width, height, format)?;
+        let texture = Rc::new(texture);
+        entries.push(Entry {
+            name: name.to_owned(),
+            texture: texture.clone(),
+            last_used: frame,
+        });
+        Ok(texture)
+    }
+}
@@ -0,0 +1,411 @@
+//! Built-in chroma key pass.
+//!
+//! [`GpuContext::chroma_key`] makes pixels close to a key color transparent.
+//! Closeness is the distance between the pixel's and the key's Cb/Cr
+//! (BT.709), averaged over a 3x3 neighbourhood, so brightness changes across
+//! a lit backdrop don't affect the matte. Past [`ChromaKey::similarity`] the
+//! alpha ramps up over [`ChromaKey::softness`]; pixels within
+//! [`ChromaKey::spill`] of the threshold are also desaturated, removing the
+//! key color reflected onto the subject.
+//!
+//! The pass multiplies the input's alpha by the matte and leaves color
+//! unpremultiplied, so plugins using it should return
+//! [`AlphaMode::Straight`](crate::AlphaMode::Straight) from
+//! [`GpuPlugin::alpha_mode`](crate::GpuPlugin::alpha_mode).
+//!
+//! [`ChromaKey::params`] and [`ChromaKey::from_params`] expose the settings
+//! as FFGL parameters, with the key color as a hue / saturation / brightness
+//! picker.
+
+use std::ffi::CString;
+
+use ffgl_core::parameters::color::{hsv_to_rgb, rgb_to_hsv};
+use ffgl_core::parameters::{ParameterTypes, SimpleParamInfo};
+
+/// Chroma key settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChromaKey {
+    /// Color to remove, RGB 0–1.
+    pub key_color: [f32; 3],
+    /// Chroma distance up to which pixels are fully transparent. Cb/Cr
+    /// distances run from 0 to about 0.7.
+    pub similarity: f32,
+    /// Distance past `similarity` over which alpha ramps up to opaque.
+    pub softness: f32,
+    /// Distance past `similarity` over which kept pixels are desaturated.
+    pub spill: f32,
+}
+
+impl Default for ChromaKey {
+    /// A green screen.
+    fn default() -> Self {
+        Self {
+            key_color: [0.0, 1.0, 0.0],
+            similarity: 0.4,
+            softness: 0.08,
+            spill: 0.1,
+        }
+    }
+}
+
+impl ChromaKey {
+    /// Number of parameters in [`params`](Self::params).
+    pub const PARAM_COUNT: usize = 6;
+
+    /// Key Hue, Key Saturation, Key Brightness, Similarity, Softness and
+    /// Spill Reduction parameters, defaulting to [`ChromaKey::default`].
+    pub fn params() -> Vec<SimpleParamInfo> {
+        let defaults = Self::default();
+        let [hue, saturation, brightness] = rgb_to_hsv(defaults.key_color);
+        let param = |name: &str, param_type, default| SimpleParamInfo {
+            name: CString::new(name).unwrap(),
+            param_type,
+            default: Some(default),
+            ..Default::default()
+        };
+        vec![
+            param("Key Hue", ParameterTypes::Hue, hue),
+            param("Key Saturation", ParameterTypes::Saturation, saturation),
+            param("Key Brightness", ParameterTypes::Brightness, brightness),
+            param("Similarity", ParameterTypes::Standard, defaults.similarity),
+            param("Softness", ParameterTypes::Standard, defaults.softness),
+            param("Spill Reduction", ParameterTypes::Standard, defaults.spill),
+        ]
+    }
+
+    /// Settings from the values of the parameters in [`params`](Self::params),
+    /// in the same order. `values` must hold at least
+    /// [`PARAM_COUNT`](Self::PARAM_COUNT) entries.
+    pub fn from_params(values: &[f32]) -> Self {
+        let v = &values[..Self::PARAM_COUNT];
+        Self {
+            key_color: hsv_to_rgb(v[0], v[1], v[2]),
+            similarity: v[3],
+            softness: v[4],
+            spill: v[5],
+        }
+    }
+
+    /// The kernel's uniforms for these settings.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn uniforms(&self) -> Uniforms {
+        let [r, g, b] = self.key_color;
+        Uniforms {
+            key_cbcr: [
+                -0.1146 * r - 0.3854 * g + 0.5 * b,
+                0.5 * r - 0.4542 * g - 0.0458 * b,
+            ],
+            similarity: self.similarity,
+            softness: self.softness.max(1e-4),
+            spill: self.spill.max(1e-4),
+            _pad: [0.0; 3],
+        }
+    }
+}
+
+/// Matches `KeyParams` in the kernels.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Uniforms {
+    key_cbcr: [f32; 2],
+    similarity: f32,
+    softness: f32,
+    spill: f32,
+    _pad: [f32; 3],
+}
+
+#[cfg(target_os = "macos")]
+mod metal_impl {
+    use super::ChromaKey;
+    use crate::context::GpuContext;
+    use crate::dispatch::{CommandBuffer, PendingWork};
+    use anyhow::Result;
+    use objc2::rc::Retained;
+    use objc2::runtime::ProtocolObject;
+    use objc2_foundation::NSString;
+    use objc2_metal::*;
+
+    const KEYING_MSL: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct KeyParams {
+    float2 key_cbcr;
+    float similarity;
+    float softness;
+    float spill;
+};
+
+static float chroma_dist(float3 c, float2 key) {
+    float2 cbcr = float2(dot(c, float3(-0.1146, -0.3854, 0.5)),
+                         dot(c, float3(0.5, -0.4542, -0.0458)));
+    return distance(cbcr, key);
+}
+
+kernel void ffgl_gpu_chroma_key(texture2d<float, access::read> src [[texture(0)]],
+                                texture2d<float, access::write> dst [[texture(1)]],
+                                constant KeyParams& p [[buffer(0)]],
+                                uint2 gid [[thread_position_in_grid]]) {
+    uint w = dst.get_width();
+    uint h = dst.get_height();
+    if (gid.x >= w || gid.y >= h) return;
+
+    float dist = 0.0;
+    for (int y = -1; y <= 1; y++) {
+        for (int x = -1; x <= 1; x++) {
+            int2 q = clamp(int2(gid) + int2(x, y), int2(0), int2(w - 1, h - 1));
+            dist += chroma_dist(src.read(uint2(q)).rgb, p.key_cbcr);
+        }
+    }
+    float base = dist / 9.0 - p.similarity;
+
+    float4 c = src.read(gid);
+    c.a *= pow(saturate(base / p.softness), 1.5);
+    float gray = dot(c.rgb, float3(0.2126, 0.7152, 0.0722));
+    c.rgb = mix(float3(gray), c.rgb, pow(saturate(base / p.spill), 1.5));
+    dst.write(c, gid);
+}
+"#;
+
+    /// The keying kernel, compiled on first use.
+    pub(crate) struct KeyingPipeline {
+        state: Retained<ProtocolObject<dyn MTLComputePipelineState>>,
+    }
+
+    impl KeyingPipeline {
+        fn new(device: &ProtocolObject<dyn MTLDevice>) -> Result<Self> {
+            let library = device
+                .newLibraryWithSource_options_error(&NSString::from_str(KEYING_MSL), None)
+                .map_err(|e| anyhow::anyhow!("Failed to compile chroma key shader: {e}"))?;
+            let function = library
+                .newFunctionWithName(&NSString::from_str("ffgl_gpu_chroma_key"))
+                .ok_or_else(|| anyhow::anyhow!("Chroma key kernel function not found"))?;
+            let state = device
+                .newComputePipelineStateWithFunction_error(&function)
+                .map_err(|e| anyhow::anyhow!("Failed to create chroma key pipeline: {e}"))?;
+            Ok(Self { state })
+        }
+    }
+
+    fn encode_chroma_key_inner(
+        ctx: &GpuContext,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+        src: &ProtocolObject<dyn MTLTexture>,
+        dst: &ProtocolObject<dyn MTLTexture>,
+        key: &ChromaKey,
+    ) -> Result<()> {
+        if std::ptr::eq(src, dst) {
+            return Err(anyhow::anyhow!(
+                "chroma_key: source and destination must differ"
+            ));
+        }
+        let pipeline = match ctx.keying.get() {
+            Some(p) => p,
+            None => {
+                let p = KeyingPipeline::new(ctx.device.device())?;
+                ctx.keying.get_or_init(|| p)
+            }
+        };
+        let uniforms = key.uniforms();
+
+        let encoder = command_buffer
+            .computeCommandEncoder()
+            .ok_or_else(|| anyhow::anyhow!("Failed to create Metal compute encoder"))?;
+        encoder.setComputePipelineState(&pipeline.state);
+        unsafe {
+            encoder.setTexture_atIndex(Some(src), 0);
+            encoder.setTexture_atIndex(Some(dst), 1);
+            encoder.setBytes_length_atIndex(
+                std::ptr::NonNull::from(&uniforms).cast(),
+                std::mem::size_of_val(&uniforms),
+                0,
+            );
+        }
+        encoder.dispatchThreads_threadsPerThreadgroup(
+            MTLSize {
+                width: dst.width(),
+                height: dst.height(),
+                depth: 1,
+            },
+            MTLSize {
+                width: 8,
+                height: 8,
+                depth: 1,
+            },
+        );
+        encoder.endEncoding();
+        Ok(())
+    }
+
+    impl GpuContext {
+        /// Key `src` into `dst` with the settings in `key` (see
+        /// [`crate::keying`]), and return a [`PendingWork`] token.
+        ///
+        /// `src` and `dst` must be different textures of the same size, and
+        /// `dst` must be shader-writable.
+        pub fn chroma_key(
+            &self,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            key: &ChromaKey,
+        ) -> Result<PendingWork> {
+            let cb = self.create_command_buffer()?;
+            encode_chroma_key_inner(self, &cb.inner, src, dst, key)?;
+            Ok(self.commit(cb))
+        }
+
+        /// Encode a [`chroma_key`](Self::chroma_key) on an existing command
+        /// buffer.
+        pub fn encode_chroma_key(
+            &self,
+            cb: &CommandBuffer,
+            src: &ProtocolObject<dyn MTLTexture>,
+            dst: &ProtocolObject<dyn MTLTexture>,
+            key: &ChromaKey,
+        ) -> Result<()> {
+            encode_chroma_key_inner(self, &cb.inner, src, dst, key)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) use dx11_impl::KeyingPipeline;
+#[cfg(target_os = "macos")]
+pub(crate) use metal_impl::KeyingPipeline;
+
+#[cfg(target_os = "windows")]
+mod dx11_impl {
+    use super::{ChromaKey, Uniforms};
+    use crate::blit::{blob_bytes, compile_hlsl};
+    use crate::context::GpuContext;
+    use anyhow::Result;
+    use windows::Win32::Graphics::Direct3D11::*;
+
+    const KEYING_HLSL: &str = r#"
+Texture2D<float4> src : register(t0);
+RWTexture2D<float4> dst : register(u0);
+
+cbuffer KeyParams : register(b0) {
+    float2 key_cbcr;
+    float similarity;
+    float softness;
+    float spill;
+    float3 _pad;
+};
+
+float chroma_dist(float3 c) {
+    float2 cbcr = float2(dot(c, float3(-0.1146, -0.3854, 0.5)),
+                         dot(c, float3(0.5, -0.4542, -0.0458)));
+    return distance(cbcr, key_cbcr);
+}
+
+[numthreads(8, 8, 1)]
+void cs_main(uint3 id : SV_DispatchThreadID) {
+    uint w, h;
+    dst.GetDimensions(w, h);
+    if (id.x >= w || id.y >= h) return;
+
+    float dist = 0.0;
+    for (int y = -1; y <= 1; y++) {
+        for (int x = -1; x <= 1; x++) {
+            int2 q = clamp(int2(id.xy) + int2(x, y), int2(0, 0), int2(w - 1, h - 1));
+            dist += chroma_dist(src[q].rgb);
+        }
+    }
+    float base = dist / 9.0 - similarity;
+
+    float4 c = src[id.xy];
+    c.a *= pow(saturate(base / softness), 1.5);
+    float gray = dot(c.rgb, float3(0.2126, 0.7152, 0.0722));
+    c.rgb = lerp(gray.xxx, c.rgb, pow(saturate(base / spill), 1.5));
+    dst[id.xy] = c;
+}
+"#;
+
+    /// The keying shader and its constant buffer, created on first use.
+    pub(crate) struct KeyingPipeline {
+        shader: ID3D11ComputeShader,
+        params: ID3D11Buffer,
+    }
+
+    impl KeyingPipeline {
+        fn new(device: &ID3D11Device) -> Result<Self> {
+            let blob = compile_hlsl(KEYING_HLSL, b"cs_main\0", b"cs_5_0\0")?;
+            let mut shader = None;
+            unsafe {
+                device.CreateComputeShader(blob_bytes(&blob), None, Some(&mut shader as *mut _))
+            }
+            .map_err(|e| anyhow::anyhow!("Failed to create chroma key shader: {e}"))?;
+            let shader =
+                shader.ok_or_else(|| anyhow::anyhow!("D3D11 CreateComputeShader returned null"))?;
+
+            let desc = D3D11_BUFFER_DESC {
+                ByteWidth: std::mem::size_of::<Uniforms>() as u32,
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: D3D11_BIND_CONSTANT_BUFFER.0 as u32,
+                ..Default::default()
+            };
+            let mut params = None;
+            unsafe { device.CreateBuffer(&desc, None, Some(&mut params as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create chroma key params buffer: {e}"))?;
+            let params =
+                params.ok_or_else(|| anyhow::anyhow!("D3D11 CreateBuffer returned null"))?;
+
+            Ok(Self { shader, params })
+        }
+    }
+
+    impl GpuContext {
+        /// Key `src` into `dst` with the settings in `key` (see
+        /// [`crate::keying`]) over `width` x `height` pixels, on the
+        /// [`command_context`](Self::command_context).
+        ///
+        /// `src` and `dst` must view different textures, e.g.
+        /// [`DrawInput::input_srv`](crate::DrawInput::input_srv) and
+        /// [`DrawInput::output_uav`](crate::DrawInput::output_uav).
+        pub fn chroma_key(
+            &self,
+            src: &ID3D11ShaderResourceView,
+            dst: &ID3D11UnorderedAccessView,
+            width: u32,
+            height: u32,
+            key: &ChromaKey,
+        ) -> Result<()> {
+            let pipeline = match self.keying.get() {
+                Some(p) => p,
+                None => {
+                    let p = KeyingPipeline::new(self.device.device())?;
+                    self.keying.get_or_init(|| p)
+                }
+            };
+            let uniforms = key.uniforms();
+            let ctx = self.command_context();
+            unsafe {
+                ctx.UpdateSubresource(
+                    &pipeline.params,
+                    0,
+                    None,
+                    &uniforms as *const Uniforms as *const _,
+                    0,
+                    0,
+                );
+
+                ctx.CSSetShader(&pipeline.shader, None);
+                ctx.CSSetShaderResources(0, Some(&[Some(src.clone())]));
+                let uavs = [Some(dst.clone())];
+                ctx.CSSetUnorderedAccessViews(0, 1, Some(uavs.as_ptr()), None);
+                ctx.CSSetConstantBuffers(0, Some(&[Some(pipeline.params.clone())]));
+                ctx.Dispatch(width.div_ceil(8), height.div_ceil(8), 1);
+
+                let null_uavs: [Option<ID3D11UnorderedAccessView>; 1] = Default::default();
+                ctx.CSSetUnorderedAccessViews(0, 1, Some(null_uavs.as_ptr()), None);
+                let null_srvs: [Option<ID3D11ShaderResourceView>; 1] = Default::default();
+                ctx.CSSetShaderResources(0, Some(&null_srvs));
+                let null_cbufs: [Option<ID3D11Buffer>; 1] = Default::default();
+                ctx.CSSetConstantBuffers(0, Some(&null_cbufs));
+            }
+            Ok(())
+        }
+    }
+}
@@ -2,18 +2,46 @@
 
 //! High-level FFGL GPU plugin framework.
 //!
-//! This crate ties together [`ffgl_core`] (host protocol), [`ffgl_glium`]
-//! (OpenGL context), and [`gpu_interop`] (GL-to-Metal/DX11 bridging) into a
-//! single framework for writing GPU-accelerated FFGL plugins.
+//! This crate ties together [`ffgl_core`] (host protocol), `ffgl_glium`
+//! (OpenGL context, optional), and [`gpu_interop`] (GL-to-Metal/DX11
+//! bridging) into a single framework for writing GPU-accelerated FFGL
+//! plugins.
 //!
 //! # Overview
 //!
 //! - [`GpuContext`] wraps the platform GPU device and shader library.
 //! - [`ComputePipeline`] / [`RenderPipeline`] are compiled pipeline states.
 //! - [`GpuBuffer`] is a GPU buffer for structured compute data.
+//! - [`GpuTexture`] is an owned texture for intermediate passes.
 //! - [`GpuPlugin`] is the trait plugin authors implement.
+//! - [`gpu_passes!`] chains compute and render passes through named
+//!   intermediates for both backends.
+//! - [`filters`] provides built-in blur, morphology and histogram kernels.
+//! - [`keying`] provides a built-in chroma key pass and its parameters.
+//! - [`Palette`] builds gradient textures from color parameters or presets.
+//! - [`random`] ships hash-based random number helpers for shaders, seeded
+//!   per instance and frame.
+//! - [`sampling`] ships texture addressing helpers for shaders, so pixel
+//!   loads and sampled reads line up on both backends.
+//! - [`OpticalFlow`] estimates coarse motion between consecutive frames.
+//! - [`WeightBuffer`] computes blur and resampling kernels on the CPU and
+//!   uploads them when they change.
+//! - `text` (with the `text` feature) draws labels and tickers from a
+//!   glyph atlas.
+//! - `frame_dump` (with the `frame-dump` feature) writes a frame's input,
+//!   intermediates and output to PNG files on request.
 //! - [`draw_gpu_effect`] is the main entry point that manages the
 //!   double-buffered draw loop.
+//! - [`DeviceInfo`] names the adapters behind the GL context and the
+//!   compute device.
+//! - [`MemoryReport`] breaks down the GPU memory one context holds.
+//! - [`budget`] tracks GPU memory across instances, with an optional soft
+//!   cap.
+//! - [`pacing`] counts pipelined, synchronous and stale frames per instance.
+//! - `objc_audit` (macOS) counts the heap blocks each frame leaves
+//!   allocated in debug builds, to catch per-frame Objective-C leaks.
+//! - [`quirks`] adjusts defaults for hosts with known peculiarities.
+//! - [`Fallback`] draws the input unchanged when the GPU path is unavailable.
 //! - [`build_support`] provides shader compilation helpers for `build.rs`.
 //!
 //! # Build-time shader compilation
@@ -23,20 +51,88 @@
 //! load the compiled shaders with [`include_metallib!`] and
 //! [`include_hlsl_shader!`].
 
+pub mod adaptive;
+mod affinity;
+pub mod argument_buffer;
+pub mod blit;
+pub mod budget;
 pub mod buffer;
 pub mod build_support;
 pub mod bytes;
+pub mod color;
+mod completion;
 pub mod context;
+pub mod device_info;
 pub mod dispatch;
 pub mod drawing;
+pub mod fallback;
+pub mod filters;
+pub mod flow;
+#[cfg(feature = "frame-dump")]
+pub mod frame_dump;
+pub mod gl_caps;
+pub mod gl_leak;
+pub mod heap;
+pub mod intermediate;
+pub mod keying;
+pub mod memory;
+#[cfg(target_os = "macos")]
+pub mod objc_audit;
+pub mod pacing;
+pub mod palette;
+pub mod passes;
 pub mod pipeline;
 pub mod plugin;
+pub mod quirks;
+pub mod random;
+pub mod sampling;
+#[cfg(feature = "text")]
+pub mod text;
+pub mod texture;
+pub mod weights;
 
 // Re-export primary types at crate root for convenience.
+pub use adaptive::AdaptiveResolution;
+#[cfg(target_os = "macos")]
+pub use argument_buffer::ArgumentBuffer;
+pub use blit::BlitFilter;
 pub use buffer::GpuBuffer;
 pub use bytes::AsBytes;
 pub use context::GpuContext;
+pub use device_info::DeviceInfo;
 pub use dispatch::{Binding, CommandBuffer, PendingWork};
-pub use drawing::{draw_gpu_effect, ensure_instance_gl_resources, validate_gl_state_before_draw};
-pub use pipeline::{ComputePipeline, RenderPipeline};
-pub use plugin::{DrawInput, GpuPlugin};
+#[cfg(target_os = "windows")]
+pub use dispatch::{RenderTarget, SrvBinding, UavBinding};
+pub use gpu_interop::{
+    ColorSpace, PixelRect, SurfaceCacheMode, SurfaceColorSpace, SurfaceOptions, TextureFormat,
+};
+pub use intermediate::IntermediateFormat;
+pub use gl_caps::{fallback_reason, FallbackReason, GlCapabilities};
+pub use drawing::{
+    discard_in_flight, draw_gpu_effect, ensure_instance_gl_resources,
+    validate_gl_state_before_draw,
+};
+pub use fallback::{Fallback, RawGlFallback};
+pub use flow::OpticalFlow;
+#[cfg(feature = "frame-dump")]
+pub use frame_dump::FrameDump;
+pub use keying::ChromaKey;
+pub use memory::MemoryReport;
+pub use pacing::FrameStats;
+pub use palette::{Palette, PalettePreset};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub use passes::Passes;
+pub use passes::PassTexture;
+pub use pipeline::{ComputePipeline, RenderPipeline, ShaderSource};
+pub use plugin::{
+    AlphaMode, DrawInput, GpuPlugin, MissingInput, ScalingPolicy, ThreadMigration, Tiling,
+};
+#[cfg(target_os = "windows")]
+pub use plugin::Dx11Resources;
+#[cfg(target_os = "macos")]
+pub use plugin::MetalResources;
+pub use quirks::{host_quirks, HostQuirks};
+#[cfg(feature = "text")]
+pub use text::{Font, TextAlign, TextRenderer, TextStyle};
+pub use texture::GpuTexture;
+pub use weights::{Kernel, WeightBuffer};
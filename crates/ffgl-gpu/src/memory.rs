@@ -0,0 +1,159 @@
+//! GPU memory held by one [`GpuContext`](crate::GpuContext), by category.
+//!
+//! [`GpuContext::memory_report`](crate::GpuContext::memory_report) adds up
+//! the bridge's shared texture pairs, the intermediates and buffers created
+//! through the context, and its pipelines. Failed allocations log the report
+//! alongside the error, so a composition that runs out of memory at 8K shows
+//! what was holding it.
+//!
+//! The totals are what the framework asked for, not what the driver
+//! actually committed: alignment and driver-side pipeline state aren't
+//! visible through either API.
+
+use std::fmt;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::sync::Arc;
+
+/// Bytes held by a [`GpuContext`](crate::GpuContext), as returned by
+/// [`memory_report`](crate::GpuContext::memory_report).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryReport {
+    /// Textures shared with the host's GL context for the current frame.
+    pub shared_pairs: u64,
+    /// Retired shared pairs kept for reuse at other sizes.
+    pub pooled_pairs: u64,
+    /// [`GpuTexture`](crate::GpuTexture)s, the texture heap, and the
+    /// framework's scratch textures.
+    pub intermediates: u64,
+    /// [`GpuBuffer`](crate::GpuBuffer)s and uniform staging buffers.
+    pub buffers: u64,
+    /// Vertex buffers owned by pipelines.
+    pub pipelines: u64,
+    /// Pipelines created through the context.
+    pub pipeline_count: u64,
+}
+
+impl MemoryReport {
+    pub fn total(&self) -> u64 {
+        self.shared_pairs + self.pooled_pairs + self.intermediates + self.buffers + self.pipelines
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+        write!(
+            f,
+            "{:.1} MiB total: shared pairs {:.1} MiB (+{:.1} MiB pooled), intermediates {:.1} MiB, \
+             buffers {:.1} MiB, {} pipelines {:.1} MiB",
+            mib(self.total()),
+            mib(self.shared_pairs),
+            mib(self.pooled_pairs),
+            mib(self.intermediates),
+            mib(self.buffers),
+            self.pipeline_count,
+            mib(self.pipelines),
+        )
+    }
+}
+
+/// Running total for one category.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Default)]
+pub(crate) struct Counter {
+    bytes: AtomicU64,
+    count: AtomicU64,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Counter {
+    fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+/// Counters for the objects a context hands out, which outlive any borrow
+/// of it.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Default)]
+pub(crate) struct MemoryCounters {
+    pub(crate) intermediates: Arc<Counter>,
+    pub(crate) buffers: Arc<Counter>,
+    pub(crate) pipelines: Arc<Counter>,
+    /// Bridge memory as of the last frame drawn with this context.
+    pub(crate) bridge: std::cell::Cell<gpu_interop::BridgeMemory>,
+}
+
+/// One object's share of a [`Counter`], released on drop.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) struct Tracked {
+    counter: Arc<Counter>,
+    bytes: u64,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Tracked {
+    pub(crate) fn new(counter: &Arc<Counter>, bytes: u64) -> Self {
+        counter.bytes.fetch_add(bytes, Ordering::Relaxed);
+        counter.count.fetch_add(1, Ordering::Relaxed);
+        Self {
+            counter: counter.clone(),
+            bytes,
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.counter.bytes.fetch_sub(self.bytes, Ordering::Relaxed);
+        self.counter.count.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl crate::GpuContext {
+    /// GPU memory held through this context, by category.
+    ///
+    /// The shared pairs are those of the bridge as of the last frame drawn.
+    pub fn memory_report(&self) -> MemoryReport {
+        let counters = &self.memory;
+        let bridge = counters.bridge.get();
+        let mut report = MemoryReport {
+            shared_pairs: bridge.active,
+            pooled_pairs: bridge.pooled,
+            intermediates: counters.intermediates.bytes(),
+            buffers: counters.buffers.bytes(),
+            pipelines: counters.pipelines.bytes(),
+            pipeline_count: counters.pipelines.count(),
+        };
+        #[cfg(target_os = "macos")]
+        {
+            report.intermediates += self.textures.allocated_bytes();
+            report.buffers += self.staging.allocated_bytes();
+        }
+        if let Some(color) = self.color.get() {
+            report.intermediates += color.scratch_bytes();
+        }
+        #[cfg(target_os = "windows")]
+        if let Some(filters) = self.filters.get() {
+            report.intermediates += filters.scratch_bytes();
+        }
+        report
+    }
+
+    /// Log a failed allocation with the current [`memory_report`] and return
+    /// it as an error.
+    ///
+    /// [`memory_report`]: Self::memory_report
+    pub(crate) fn allocation_error(&self, message: String) -> anyhow::Error {
+        tracing::error!("{message}; GPU memory in use: {}", self.memory_report());
+        anyhow::anyhow!(message)
+    }
+}
@@ -0,0 +1,96 @@
+//! Per-frame allocation audit (macOS, debug builds).
+//!
+//! Objective-C objects the draw loop creates are released when the frame's
+//! autorelease pool drains. One created outside a pool, or retained by
+//! something that is never dropped, survives the frame, and a leak of one
+//! object per frame takes hours to show up in a host's memory graph.
+//!
+//! In debug builds [`draw_gpu_effect`] counts the heap blocks in use before
+//! the frame and after its pool has drained. Objective-C objects live on the
+//! malloc heap, so the difference is what the frame left allocated. A
+//! steady-state frame leaves nothing; when every frame of a long run leaves
+//! something a warning is logged. Other threads allocate on the same heap,
+//! so a single frame's count is noisy and only the trend is reported.
+//!
+//! [`last_frame_allocations`] returns the latest count for plugins chasing
+//! a leak of their own. In release builds nothing is counted.
+//!
+//! [`draw_gpu_effect`]: crate::draw_gpu_effect
+
+use std::cell::Cell;
+use std::ffi::c_void;
+
+/// Consecutive frames leaving blocks allocated before a warning is logged.
+const GROWTH_FRAMES: u32 = 600;
+
+thread_local! {
+    static LAST: Cell<Option<i64>> = const { Cell::new(None) };
+    /// Frames in a row that left blocks allocated, and how many in total.
+    static GROWTH: Cell<(u32, i64)> = const { Cell::new((0, 0)) };
+}
+
+/// `malloc_statistics_t` from `<malloc/malloc.h>`. Only the block count is
+/// read.
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct MallocStatistics {
+    blocks_in_use: u32,
+    size_in_use: usize,
+    max_size_in_use: usize,
+    size_allocated: usize,
+}
+
+extern "C" {
+    fn malloc_zone_statistics(zone: *mut c_void, stats: *mut MallocStatistics);
+}
+
+/// Heap blocks in use across all malloc zones.
+fn heap_blocks() -> u32 {
+    let mut stats = MallocStatistics::default();
+    // SAFETY: a null zone asks for the statistics of all zones combined.
+    unsafe { malloc_zone_statistics(std::ptr::null_mut(), &mut stats) };
+    stats.blocks_in_use
+}
+
+/// Net heap blocks the last frame drawn on this thread left allocated,
+/// counted once its autorelease pool had drained. `None` in release builds
+/// or before the first frame.
+pub fn last_frame_allocations() -> Option<i64> {
+    LAST.with(Cell::get)
+}
+
+/// Start counting a frame. `None` in release builds.
+pub(crate) fn begin() -> Option<u32> {
+    cfg!(debug_assertions).then(heap_blocks)
+}
+
+/// Finish counting the frame [`begin`] started, after its autorelease pool
+/// has drained.
+pub(crate) fn end(instance_id: u64, start: Option<u32>) {
+    let Some(start) = start else {
+        return;
+    };
+    let grown = i64::from(heap_blocks()) - i64::from(start);
+    LAST.with(|cell| cell.set(Some(grown)));
+
+    let (frames, total) = GROWTH.with(|cell| {
+        let (frames, total) = cell.get();
+        let next = if grown > 0 {
+            (frames + 1, total + grown)
+        } else {
+            (0, 0)
+        };
+        cell.set(next);
+        next
+    });
+    if frames >= GROWTH_FRAMES {
+        gpu_interop::warn_limited!(
+            instance = instance_id,
+            "Each of the last {frames} frames left heap blocks allocated ({} per frame on \
+             average); an object created per frame is not being released",
+            total / i64::from(frames)
+        );
+        GROWTH.with(|cell| cell.set((0, 0)));
+    }
+}
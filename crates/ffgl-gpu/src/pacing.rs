@@ -0,0 +1,201 @@
+//! Frame pacing statistics per plugin instance.
+//!
+//! [`draw_gpu_effect`] records every frame it draws on the GPU path: the
+//! time since the host's previous call for the instance, how long the draw
+//! blocked on GPU work, and whether it presented the previous frame's result
+//! (pipelined) or had to wait for its own (synchronous). [`stats`] returns
+//! the running counters.
+//!
+//! A synchronous frame right after the instance's previous one means the
+//! bridge discarded that frame's result as stale, because the host took
+//! longer than the bridge's staleness window between the two calls. Those
+//! frames are counted in [`FrameStats::stale`] and logged at debug level with
+//! the interval, as are frame counter gaps. Frames after another instance
+//! used the thread's bridge, tiled frames and offline rendering are
+//! synchronous by design and not counted as stale, as are frames after the
+//! host moved the instance to another thread.
+//!
+//! [`draw_gpu_effect`]: crate::draw_gpu_effect
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Frame pacing counters for one instance, as returned by [`stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FrameStats {
+    /// Frames drawn on the GPU path.
+    pub frames: u64,
+    /// Frames that presented the previous frame's result.
+    pub pipelined: u64,
+    /// Frames that waited for their own GPU work.
+    pub synchronous: u64,
+    /// Synchronous frames whose previous result was discarded as stale.
+    pub stale: u64,
+    /// Frame counter values skipped between two draws.
+    pub skipped: u64,
+    /// Smoothed time between host draw calls.
+    pub host_interval: Duration,
+    /// Longest time between host draw calls.
+    pub max_host_interval: Duration,
+    /// Smoothed time per frame spent blocked on GPU work.
+    pub gpu_wait: Duration,
+    /// Longest time a frame spent blocked on GPU work.
+    pub max_gpu_wait: Duration,
+    /// Times the host moved the instance to another thread.
+    pub thread_changes: u64,
+}
+
+/// How a frame's output reached the host.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Presented {
+    /// The previous frame's result, while this frame's work runs.
+    Previous,
+    /// This frame's own result.
+    Current,
+    /// This frame's own result, drawn tile by tile.
+    Tiled,
+    /// This frame's own result, with
+    /// [`offline_rendering`](crate::GpuPlugin::offline_rendering) on or a
+    /// [synchronous](crate::HostQuirks::synchronous) host.
+    Offline,
+}
+
+// Only the draw loops (macOS and Windows) create entries.
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+struct Entry {
+    stats: FrameStats,
+    last_frame: u64,
+    last_draw: Instant,
+    /// Whether the last frame left a result in flight for the next one.
+    last_in_flight: bool,
+}
+
+static REGISTRY: Mutex<BTreeMap<u64, Entry>> = Mutex::new(BTreeMap::new());
+
+fn registry() -> MutexGuard<'static, BTreeMap<u64, Entry>> {
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Pacing counters for `instance_id`, if it has drawn recently.
+pub fn stats(instance_id: u64) -> Option<FrameStats> {
+    registry().get(&instance_id).map(|e| e.stats)
+}
+
+/// Reset `instance_id`'s counters, e.g. to measure one section of a show.
+pub fn reset(instance_id: u64) {
+    if let Some(entry) = registry().get_mut(&instance_id) {
+        entry.stats = FrameStats::default();
+    }
+}
+
+/// The instance's in-flight result was dropped on purpose, so a
+/// synchronous next frame isn't stale.
+pub(crate) fn discarded(instance_id: u64) {
+    if let Some(entry) = registry().get_mut(&instance_id) {
+        entry.last_in_flight = false;
+    }
+}
+
+/// The instance moved to another thread and rebuilds there; its next frame
+/// is synchronous by design.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn thread_changed(instance_id: u64) {
+    if let Some(entry) = registry().get_mut(&instance_id) {
+        entry.stats.thread_changes += 1;
+        entry.last_in_flight = false;
+    }
+}
+
+/// Forget `instance_id`. Called by [`budget::unregister`](crate::budget::unregister).
+pub(crate) fn unregister(instance_id: u64) {
+    registry().remove(&instance_id);
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+thread_local! {
+    /// Instance whose frame was last recorded on this thread, and so last
+    /// used the thread's bridge.
+    static LAST_INSTANCE: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Record one frame of `instance_id` that spent `gpu_wait` blocked on GPU
+/// work.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn record_frame(
+    instance_id: u64,
+    frame_counter: u64,
+    presented: Presented,
+    gpu_wait: Duration,
+) {
+    const SMOOTHING: f64 = 0.1;
+    let smooth = |avg: Duration, sample: Duration| {
+        let avg = avg.as_secs_f64();
+        Duration::from_secs_f64(avg + (sample.as_secs_f64() - avg) * SMOOTHING)
+    };
+
+    let now = Instant::now();
+    let bridge_kept = LAST_INSTANCE.with(|c| c.replace(Some(instance_id))) == Some(instance_id);
+    let mut registry = registry();
+    registry.retain(|&id, e| {
+        id == instance_id || now.duration_since(e.last_draw) < crate::budget::IDLE_TIMEOUT
+    });
+    let previous = registry
+        .get(&instance_id)
+        .map(|e| (e.last_frame, e.last_draw, e.last_in_flight));
+    let entry = registry.entry(instance_id).or_insert(Entry {
+        stats: FrameStats::default(),
+        last_frame: frame_counter,
+        last_draw: now,
+        last_in_flight: false,
+    });
+    let stats = &mut entry.stats;
+
+    stats.frames += 1;
+    match presented {
+        Presented::Previous => stats.pipelined += 1,
+        Presented::Current | Presented::Tiled | Presented::Offline => stats.synchronous += 1,
+    }
+    stats.gpu_wait = if stats.frames == 1 {
+        gpu_wait
+    } else {
+        smooth(stats.gpu_wait, gpu_wait)
+    };
+    stats.max_gpu_wait = stats.max_gpu_wait.max(gpu_wait);
+
+    if let Some((last_frame, last_draw, in_flight)) = previous {
+        let interval = now.duration_since(last_draw);
+        stats.host_interval = if stats.max_host_interval.is_zero() {
+            interval
+        } else {
+            smooth(stats.host_interval, interval)
+        };
+        stats.max_host_interval = stats.max_host_interval.max(interval);
+
+        let consecutive = frame_counter == last_frame.wrapping_add(1);
+        if consecutive && in_flight && bridge_kept && presented == Presented::Current {
+            stats.stale += 1;
+            tracing::debug!(
+                instance = instance_id,
+                frame = frame_counter,
+                interval_ms = interval.as_millis() as u64,
+                "Previous frame's result was stale, presenting synchronously"
+            );
+        } else if frame_counter > last_frame.wrapping_add(1) {
+            let skipped = frame_counter - last_frame - 1;
+            stats.skipped += skipped;
+            tracing::debug!(
+                instance = instance_id,
+                frame = frame_counter,
+                skipped,
+                "Frame counter skipped ahead"
+            );
+        }
+    }
+    entry.last_frame = frame_counter;
+    entry.last_draw = now;
+    entry.last_in_flight = matches!(presented, Presented::Previous | Presented::Current);
+}
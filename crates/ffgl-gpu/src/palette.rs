@@ -0,0 +1,263 @@
+//! Gradient textures from color parameters or presets.
+//!
+//! A [`Palette`] holds a list of evenly spaced color stops and the 256x1
+//! texture interpolated from them, for false-color, duotone and
+//! audio-visualizer effects. Sample it at `(t, 0.5)` with a clamping,
+//! linear sampler to map a 0–1 value to a color.
+//!
+//! [`Palette::params`] declares a "Palette" option listing the
+//! [`PalettePreset`]s plus "Custom", and a hue / saturation / brightness
+//! picker per custom stop. Pass the values to
+//! [`Palette::update_from_params`] each frame; the texture is only
+//! re-uploaded when the stops actually change.
+
+use std::ffi::CString;
+
+use ffgl_core::parameters::color::hsv_to_rgb;
+use ffgl_core::parameters::{ParameterTypes, SimpleParamInfo};
+
+/// Built-in gradients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PalettePreset {
+    /// Black to white.
+    #[default]
+    Grayscale,
+    /// Black through red and yellow to white.
+    Heat,
+    /// Black through blue and cyan to white.
+    Ice,
+    /// The hue circle from red back to red.
+    Rainbow,
+    /// Deep purple through magenta and orange to yellow.
+    Sunset,
+    /// Blue to magenta to cyan.
+    Neon,
+}
+
+impl PalettePreset {
+    /// Every preset, in the order [`Palette::params`] lists them.
+    pub const ALL: [Self; 6] = [
+        Self::Grayscale,
+        Self::Heat,
+        Self::Ice,
+        Self::Rainbow,
+        Self::Sunset,
+        Self::Neon,
+    ];
+
+    /// Name shown in the "Palette" option.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Grayscale => "Grayscale",
+            Self::Heat => "Heat",
+            Self::Ice => "Ice",
+            Self::Rainbow => "Rainbow",
+            Self::Sunset => "Sunset",
+            Self::Neon => "Neon",
+        }
+    }
+
+    /// The preset's color stops, RGB 0–1, evenly spaced.
+    pub fn stops(self) -> &'static [[f32; 3]] {
+        match self {
+            Self::Grayscale => &[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]],
+            Self::Heat => &[
+                [0.0, 0.0, 0.0],
+                [0.8, 0.0, 0.0],
+                [1.0, 0.6, 0.0],
+                [1.0, 1.0, 1.0],
+            ],
+            Self::Ice => &[
+                [0.0, 0.0, 0.0],
+                [0.0, 0.2, 0.8],
+                [0.0, 0.8, 1.0],
+                [1.0, 1.0, 1.0],
+            ],
+            Self::Rainbow => &[
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 1.0, 1.0],
+                [0.0, 0.0, 1.0],
+                [1.0, 0.0, 1.0],
+                [1.0, 0.0, 0.0],
+            ],
+            Self::Sunset => &[
+                [0.15, 0.0, 0.3],
+                [0.8, 0.1, 0.5],
+                [1.0, 0.5, 0.1],
+                [1.0, 0.9, 0.3],
+            ],
+            Self::Neon => &[[0.1, 0.0, 1.0], [1.0, 0.0, 0.8], [0.0, 1.0, 1.0]],
+        }
+    }
+}
+
+/// A gradient and its texture, for one plugin instance.
+pub struct Palette {
+    stops: Vec<[f32; 3]>,
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    texture: Option<crate::GpuTexture>,
+    /// Whether `stops` changed since the texture was last uploaded.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    dirty: bool,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::from_preset(PalettePreset::default())
+    }
+}
+
+impl Palette {
+    /// Width of the gradient texture. It is one pixel high.
+    pub const WIDTH: u32 = 256;
+
+    /// A palette with the given stops, RGB 0–1, spread evenly from left to
+    /// right. One stop gives a solid color and none gives black.
+    pub fn new(stops: &[[f32; 3]]) -> Self {
+        Self {
+            stops: stops.to_vec(),
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            texture: None,
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            dirty: true,
+        }
+    }
+
+    /// A palette with a preset's stops.
+    pub fn from_preset(preset: PalettePreset) -> Self {
+        Self::new(preset.stops())
+    }
+
+    /// The current stops.
+    pub fn stops(&self) -> &[[f32; 3]] {
+        &self.stops
+    }
+
+    /// Replace the stops. The texture is updated on the next
+    /// [`texture`](Self::texture) call, and only if they differ.
+    pub fn set_stops(&mut self, stops: &[[f32; 3]]) {
+        if self.stops != stops {
+            self.stops = stops.to_vec();
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            {
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Switch to a preset's stops.
+    pub fn set_preset(&mut self, preset: PalettePreset) {
+        self.set_stops(preset.stops());
+    }
+
+    /// The gradient's color at `t`, clamped to 0–1.
+    pub fn color_at(&self, t: f32) -> [f32; 3] {
+        match self.stops.as_slice() {
+            [] => [0.0; 3],
+            [only] => *only,
+            stops => {
+                let x = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+                let i = (x as usize).min(stops.len() - 2);
+                let f = x - i as f32;
+                let (a, b) = (stops[i], stops[i + 1]);
+                [
+                    a[0] + (b[0] - a[0]) * f,
+                    a[1] + (b[1] - a[1]) * f,
+                    a[2] + (b[2] - a[2]) * f,
+                ]
+            }
+        }
+    }
+
+    /// Number of parameters [`params`](Self::params) declares for
+    /// `colors` custom stops.
+    pub fn param_count(colors: usize) -> usize {
+        1 + 3 * colors
+    }
+
+    /// A "Palette" option (the presets, then "Custom") followed by Color N
+    /// Hue, Saturation and Brightness for each of `colors` custom stops. The
+    /// option defaults to `default`; the custom stops default to an even
+    /// walk around the hue circle.
+    pub fn params(colors: usize, default: PalettePreset) -> Vec<SimpleParamInfo> {
+        let mut elements: Vec<(CString, f32)> = PalettePreset::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, preset)| (CString::new(preset.name()).unwrap(), i as f32))
+            .collect();
+        elements.push((
+            CString::new("Custom").unwrap(),
+            PalettePreset::ALL.len() as f32,
+        ));
+        let selected = PalettePreset::ALL
+            .iter()
+            .position(|&p| p == default)
+            .unwrap_or(0);
+
+        let mut params = vec![SimpleParamInfo {
+            name: CString::new("Palette").unwrap(),
+            param_type: ParameterTypes::Option,
+            default: Some(selected as f32),
+            elements: Some(elements),
+            ..Default::default()
+        }];
+        for n in 0..colors {
+            let param = |channel: &str, param_type, default| SimpleParamInfo {
+                name: CString::new(format!("Color {} {channel}", n + 1)).unwrap(),
+                param_type,
+                default: Some(default),
+                ..Default::default()
+            };
+            params.push(param("Hue", ParameterTypes::Hue, n as f32 / colors as f32));
+            params.push(param("Saturation", ParameterTypes::Saturation, 1.0));
+            params.push(param("Brightness", ParameterTypes::Brightness, 1.0));
+        }
+        params
+    }
+
+    /// Set the stops from the values of the parameters in
+    /// [`params`](Self::params), in the same order. `values` must hold at
+    /// least [`param_count(colors)`](Self::param_count) entries.
+    pub fn update_from_params(&mut self, values: &[f32], colors: usize) {
+        let values = &values[..Self::param_count(colors)];
+        match PalettePreset::ALL.get(values[0].round().max(0.0) as usize) {
+            Some(&preset) => self.set_preset(preset),
+            None => {
+                let stops: Vec<[f32; 3]> = values[1..]
+                    .chunks_exact(3)
+                    .map(|hsv| hsv_to_rgb(hsv[0], hsv[1], hsv[2]))
+                    .collect();
+                self.set_stops(&stops);
+            }
+        }
+    }
+
+    /// The gradient as tightly packed RGBA8, [`WIDTH`](Self::WIDTH) pixels.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        (0..Self::WIDTH)
+            .flat_map(|x| {
+                let [r, g, b] = self.color_at(x as f32 / (Self::WIDTH - 1) as f32);
+                let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+                [byte(r), byte(g), byte(b), 255]
+            })
+            .collect()
+    }
+
+    /// The gradient texture, created on first use and re-uploaded when the
+    /// stops changed since the last call.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub fn texture(&mut self, ctx: &crate::GpuContext) -> anyhow::Result<&crate::GpuTexture> {
+        if self.texture.is_none() {
+            self.texture = Some(ctx.create_texture(Self::WIDTH, 1)?);
+            self.dirty = true;
+        }
+        let texture = self.texture.as_ref().unwrap();
+        if self.dirty {
+            ctx.update_texture_from_rgba(texture, &self.to_rgba())?;
+            self.dirty = false;
+        }
+        Ok(texture)
+    }
+}
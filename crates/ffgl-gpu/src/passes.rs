@@ -0,0 +1,730 @@
+//! Chains of compute and render passes between `input` and `output`,
+//! written once for both backends.
+//!
+//! A multi-pass effect otherwise spells out every pass twice: as
+//! `encode_compute_pass` / `encode_render_pass` on a Metal command buffer,
+//! and as `dispatch_compute` / `dispatch_render` with views and a constant
+//! buffer on DX11. [`Passes`] takes the textures by role instead, with the
+//! scratch textures in between coming from
+//! [`DrawInput::intermediate`](crate::DrawInput::intermediate). The
+//! [`gpu_passes!`](crate::gpu_passes) macro writes the chain as data flow:
+//!
+//! ```rust,ignore
+//! gpu_passes!(ctx, input, uniforms: params, {
+//!     after_gray = compute(grayscale)(input);
+//!     after_tint = render(tint)(after_gray);
+//!     output = compute(blend)(input, after_tint);
+//! })?;
+//! ```
+//!
+//! Uniforms are bound by name. `with` gives a pass the values of the
+//! uniforms its shaders declare, matched against the pipeline's reflection:
+//! the argument name on Metal, the cbuffer name on DX11. Each pass finds
+//! its own buffer index or register, so shaders needn't agree on slots:
+//!
+//! ```rust,ignore
+//! gpu_passes!(ctx, input, {
+//!     blurred = compute(blur)(input) with { blur_params: blur };
+//!     output = render(composite)(input, blurred) with { mix_params: mix };
+//! })?;
+//! ```
+//!
+//! A value stays set for the passes after the one it is given with, until
+//! another `with` replaces it. The optional `uniforms:` block is the older
+//! shortcut: every pass gets it at buffer index 0 on Metal (the fragment
+//! stage of a render pass) and in constant buffer `b0` on DX11, unless a
+//! named uniform takes that slot.
+//!
+//! A compute pass binds the textures it reads in order, then the one it
+//! writes (Metal texture indices; DX11 SRVs from `t0` and the UAV at `u0`),
+//! and runs over the processing size with the kernel's
+//! [`threadgroup_size`](crate::ComputePipeline::threadgroup_size).
+//! A render pass draws the fullscreen quad into the texture it writes, with
+//! the textures it reads bound to the fragment stage.
+//!
+//! Intermediates are 8-bit unless a pass asks for more precision, e.g. to
+//! accumulate bloom in floats while the rest of the chain stays 8-bit:
+//!
+//! ```rust,ignore
+//! gpu_passes!(ctx, input, {
+//!     bloom = compute(threshold)(input) as Rgba16Float;
+//!     output = render(composite)(input, bloom);
+//! })?;
+//! ```
+//!
+//! `as` takes an [`IntermediateFormat`](crate::IntermediateFormat) variant
+//! and applies to the written intermediate from that pass on; later passes
+//! reading it get the float texture. Shaders read and write every format as
+//! `float4`, so compute passes and DX11 render passes store into any of
+//! them directly. A Metal render pipeline only draws into the format it was
+//! created for (see
+//! [`create_render_pipeline_with_format`](crate::GpuContext::create_render_pipeline_with_format)),
+//! so where the target's format differs the chain renders into a scratch
+//! texture in the pipeline's format and inserts a conversion blit into the
+//! target.
+//!
+//! Passes can run below the processing resolution, the usual way to make
+//! bloom or glow cheap. A line ending in `/ 4` stores the intermediate it
+//! writes at a quarter of the processing width and height:
+//!
+//! ```rust,ignore
+//! gpu_passes!(ctx, input, {
+//!     bright = compute(threshold)(input) / 4 as Rgba16Float;
+//!     blurred = compute(blur)(bright) / 4 as Rgba16Float;
+//!     output = render(composite)(input, blurred);
+//! })?;
+//! ```
+//!
+//! Each pass runs at the size of the texture it writes. Textures it reads
+//! at another size are first stretched to that size with a bilinear blit
+//! into a scratch intermediate, so kernels always read and write at the
+//! same coordinates. Above, `input` is downsampled for the threshold and
+//! `blurred` upsampled for the composite. Each such read is resampled
+//! again; on DX11 the output can't be read, resampled or not.
+//!
+//! On Metal all passes go into one command buffer, which
+//! [`finish`](Passes::finish) commits and hands to the bridge. Passes that
+//! need other bindings can still be encoded by hand around a chain.
+
+/// A texture in a pass chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassTexture<'n> {
+    /// The host's frame.
+    Input,
+    /// The frame's output.
+    Output,
+    /// The [intermediate](crate::DrawInput::intermediate) with this name.
+    Intermediate(&'n str),
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub use imp::Passes;
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+mod imp {
+    use super::PassTexture;
+    use crate::pipeline::UniformSlot;
+    use crate::{
+        AsBytes, BlitFilter, ComputePipeline, DrawInput, GpuContext, GpuTexture,
+        IntermediateFormat, RenderPipeline,
+    };
+    use anyhow::{bail, Result};
+    use gpu_interop::{GpuBridge as _, TextureFormat};
+    use std::rc::Rc;
+
+    /// Intermediates a pass reads through when a texture it reads has
+    /// another size than the one it writes, numbered by read slot.
+    const RESAMPLE_SCRATCH: &str = "ffgl_gpu::passes::resample";
+
+    /// Builder for a chain of passes over one frame; see the
+    /// [module docs](crate::passes).
+    pub struct Passes<'p, 'a> {
+        ctx: &'p GpuContext,
+        input: &'p mut DrawInput<'a>,
+        uniforms: &'p [u8],
+        /// Uniforms set with [`uniform`](Passes::uniform).
+        named: Vec<NamedUniform>,
+        /// Intermediates given a format with [`format`](Passes::format).
+        formats: Vec<(String, IntermediateFormat)>,
+        /// Intermediates given a divisor with [`downscale`](Passes::downscale).
+        divisors: Vec<(String, u32)>,
+        #[cfg(target_os = "macos")]
+        command_buffer: crate::CommandBuffer,
+        /// Constant buffer holding `uniforms`, shared by every pass.
+        #[cfg(target_os = "windows")]
+        cbuf: Option<windows::Win32::Graphics::Direct3D11::ID3D11Buffer>,
+    }
+
+    /// A uniform bound by name to the passes whose shaders declare it.
+    struct NamedUniform {
+        name: String,
+        bytes: Vec<u8>,
+        /// Whether a pass has bound it.
+        bound: bool,
+    }
+
+    /// Where a pass binds a uniform: an index into `named`, or `None` for
+    /// the shared block, with the buffer index or register.
+    type Binding = (Option<usize>, usize);
+
+    /// A resolved [`PassTexture`]: one of the frame's own, or an
+    /// intermediate kept alive for the chain.
+    enum Resolved {
+        Input,
+        Output,
+        Intermediate(Rc<GpuTexture>),
+    }
+
+    impl<'p, 'a> Passes<'p, 'a> {
+        /// Start a chain for the frame in `input`.
+        pub fn new(ctx: &'p GpuContext, input: &'p mut DrawInput<'a>) -> Result<Self> {
+            Ok(Self {
+                ctx,
+                input,
+                uniforms: &[],
+                named: Vec::new(),
+                formats: Vec::new(),
+                divisors: Vec::new(),
+                #[cfg(target_os = "macos")]
+                command_buffer: ctx.create_command_buffer()?,
+                #[cfg(target_os = "windows")]
+                cbuf: None,
+            })
+        }
+
+        /// Bind `uniforms` to every pass that follows, at buffer index 0 /
+        /// register `b0`.
+        pub fn uniforms<U: AsBytes>(mut self, uniforms: &'p U) -> Self {
+            self.uniforms = uniforms.as_bytes();
+            #[cfg(target_os = "windows")]
+            {
+                self.cbuf = self.pass_cbuf(0, self.uniforms);
+            }
+            self
+        }
+
+        /// Bind `value` to the uniform called `name` in every pass that
+        /// follows whose shaders declare it, at the slot the pipeline's
+        /// reflection gives it. Setting `name` again replaces the value for
+        /// the passes after that.
+        pub fn uniform<U: AsBytes>(mut self, name: &str, value: &U) -> Self {
+            let bytes = value.as_bytes().to_vec();
+            match self.named.iter_mut().find(|u| u.name == name) {
+                Some(u) => u.bytes = bytes,
+                None => self.named.push(NamedUniform {
+                    name: name.to_owned(),
+                    bytes,
+                    bound: false,
+                }),
+            }
+            self
+        }
+
+        /// The uniforms a stage declaring `slots` binds, with the shared
+        /// block at slot 0 if `shared` and no named uniform takes it.
+        fn bindings(&mut self, slots: &[UniformSlot], shared: bool) -> Vec<Binding> {
+            let mut bindings: Vec<Binding> = self
+                .named
+                .iter_mut()
+                .enumerate()
+                .filter_map(|(i, u)| {
+                    let slot = slots.iter().find(|slot| slot.name == u.name)?;
+                    u.bound = true;
+                    Some((Some(i), slot.index))
+                })
+                .collect();
+            if shared && !self.uniforms.is_empty() && bindings.iter().all(|&(_, i)| i != 0) {
+                bindings.push((None, 0));
+            }
+            bindings
+        }
+
+        /// Warn about uniforms no pass of the chain declared, usually a
+        /// misspelt name.
+        fn check_bound(&self) {
+            for u in self.named.iter().filter(|u| !u.bound) {
+                gpu_interop::warn_limited!(
+                    "Uniform '{}' was set but no pass in the chain declares it",
+                    u.name
+                );
+            }
+        }
+
+        /// Store the intermediate `name` in `format` from here on: the next
+        /// pass writing it writes a texture in that format, and the passes
+        /// after it read that texture.
+        pub fn format(mut self, name: &str, format: IntermediateFormat) -> Self {
+            match self.formats.iter_mut().find(|(n, _)| n == name) {
+                Some((_, f)) => *f = format,
+                None => self.formats.push((name.to_owned(), format)),
+            }
+            self
+        }
+
+        /// Store the intermediate `name` at 1/`divisor` of the processing
+        /// width and height (rounded up) from here on. Passes writing it run
+        /// at that size; see the [module docs](crate::passes) for how reads
+        /// at other sizes are resampled. A divisor of 1 is the processing
+        /// size.
+        pub fn downscale(mut self, name: &str, divisor: u32) -> Self {
+            let divisor = divisor.max(1);
+            match self.divisors.iter_mut().find(|(n, _)| n == name) {
+                Some((_, d)) => *d = divisor,
+                None => self.divisors.push((name.to_owned(), divisor)),
+            }
+            self
+        }
+
+        /// The format of the intermediate `name` in this chain.
+        fn format_of(&self, name: &str) -> IntermediateFormat {
+            self.formats
+                .iter()
+                .find(|(n, _)| n == name)
+                .map_or(IntermediateFormat::Rgba8, |(_, f)| *f)
+        }
+
+        /// The size of the intermediate `name` in this chain.
+        fn size_of(&self, name: &str) -> (u32, u32) {
+            let divisor = self
+                .divisors
+                .iter()
+                .find(|(n, _)| n == name)
+                .map_or(1, |(_, d)| *d);
+            let (width, height) = (self.input.width, self.input.height);
+            (width.div_ceil(divisor), height.div_ceil(divisor))
+        }
+
+        /// The intermediate `name` at `size` in `format`.
+        fn scratch(
+            &self,
+            name: &str,
+            (width, height): (u32, u32),
+            format: IntermediateFormat,
+        ) -> Result<Rc<GpuTexture>> {
+            self.ctx
+                .intermediates
+                .get(self.ctx, name, width, height, format)
+        }
+
+        fn resolve(&self, texture: PassTexture<'_>) -> Result<Resolved> {
+            Ok(match texture {
+                PassTexture::Input => Resolved::Input,
+                PassTexture::Output => Resolved::Output,
+                PassTexture::Intermediate(name) => Resolved::Intermediate(self.scratch(
+                    name,
+                    self.size_of(name),
+                    self.format_of(name),
+                )?),
+            })
+        }
+
+        fn size(&self, texture: &Resolved) -> (u32, u32) {
+            match texture {
+                Resolved::Intermediate(t) => (t.width(), t.height()),
+                Resolved::Input | Resolved::Output => (self.input.width, self.input.height),
+            }
+        }
+
+        /// The format a copy of `texture` keeps its values in.
+        fn copy_format(&self, texture: &Resolved) -> IntermediateFormat {
+            match texture {
+                Resolved::Intermediate(t) => t.format(),
+                Resolved::Input | Resolved::Output => match self.input.bridge.format() {
+                    TextureFormat::Rgba8 => IntermediateFormat::Rgba8,
+                    TextureFormat::Rgba16Float => IntermediateFormat::Rgba16Float,
+                    TextureFormat::Rgba32Float => IntermediateFormat::Rgba32Float,
+                },
+            }
+        }
+
+        /// `reads` at the size of `write`, stretching the others into
+        /// scratch intermediates.
+        fn resample(&self, reads: Vec<Resolved>, write: &Resolved) -> Result<Vec<Resolved>> {
+            let size = self.size(write);
+            reads
+                .into_iter()
+                .enumerate()
+                .map(|(slot, read)| {
+                    if self.size(&read) == size {
+                        return Ok(read);
+                    }
+                    let scratch = self.scratch(
+                        &format!("{RESAMPLE_SCRATCH}{slot}"),
+                        size,
+                        self.copy_format(&read),
+                    )?;
+                    self.stretch(&read, &scratch)?;
+                    Ok(Resolved::Intermediate(scratch))
+                })
+                .collect()
+        }
+
+        fn resolve_all(
+            &self,
+            reads: &[PassTexture<'_>],
+            write: PassTexture<'_>,
+        ) -> Result<(Vec<Resolved>, Resolved)> {
+            if write == PassTexture::Input {
+                bail!("a pass can't write the input texture");
+            }
+            let reads = reads
+                .iter()
+                .map(|&t| self.resolve(t))
+                .collect::<Result<Vec<_>>>()?;
+            let write = self.resolve(write)?;
+            Ok((self.resample(reads, &write)?, write))
+        }
+
+        /// The grid a pass writing `write` runs over.
+        fn grid(&self, write: &Resolved) -> (usize, usize) {
+            let (width, height) = self.size(write);
+            (width as usize, height as usize)
+        }
+    }
+
+    /// Intermediate a Metal render pass draws into when its pipeline's
+    /// format differs from the target's, before the conversion blit.
+    #[cfg(target_os = "macos")]
+    const CONVERT_SCRATCH: &str = "ffgl_gpu::passes::convert";
+
+    /// Whether `pipeline` can draw straight into a texture of `format`.
+    /// sRGB views of 8-bit storage are drawn into as before.
+    #[cfg(target_os = "macos")]
+    fn renders_into(pipeline: &RenderPipeline, format: objc2_metal::MTLPixelFormat) -> bool {
+        use objc2_metal::MTLPixelFormat;
+        let own = pipeline.format.metal();
+        format == own
+            || (own == MTLPixelFormat::BGRA8Unorm && format == MTLPixelFormat::BGRA8Unorm_sRGB)
+    }
+
+    #[cfg(target_os = "macos")]
+    impl Passes<'_, '_> {
+        fn metal<'t>(
+            &'t self,
+            texture: &'t Resolved,
+        ) -> &'t objc2::runtime::ProtocolObject<dyn objc2_metal::MTLTexture> {
+            match texture {
+                Resolved::Input => self.input.input,
+                Resolved::Output => self.input.output,
+                Resolved::Intermediate(t) => t.metal_texture(),
+            }
+        }
+
+        /// Encode a bilinear blit of `src` over the whole of `dst`.
+        fn stretch(&self, src: &Resolved, dst: &GpuTexture) -> Result<()> {
+            self.ctx.encode_blit_texture(
+                &self.command_buffer,
+                self.metal(src),
+                dst.metal_texture(),
+                BlitFilter::Linear,
+            )
+        }
+
+        /// The uniform bytes for `bindings`, with their buffer indices.
+        fn bytes(&self, bindings: &[Binding]) -> Vec<(&[u8], usize)> {
+            bindings
+                .iter()
+                .map(|&(u, index)| (u.map_or(self.uniforms, |u| &self.named[u].bytes[..]), index))
+                .collect()
+        }
+
+        /// Run `pipeline` over the frame, reading `reads` and writing
+        /// `write`.
+        pub fn compute(
+            mut self,
+            pipeline: &ComputePipeline,
+            reads: &[PassTexture<'_>],
+            write: PassTexture<'_>,
+        ) -> Result<Self> {
+            let bindings = self.bindings(&pipeline.uniforms, true);
+            let (reads, write) = self.resolve_all(reads, write)?;
+            let textures: Vec<_> = reads
+                .iter()
+                .chain([&write])
+                .map(|t| self.metal(t))
+                .collect();
+            self.ctx.encode_compute_pass(
+                &self.command_buffer,
+                pipeline,
+                &textures,
+                &[],
+                &self.bytes(&bindings),
+                self.grid(&write),
+                pipeline.threadgroup_size(),
+            )?;
+            Ok(self)
+        }
+
+        /// Draw `pipeline`'s fullscreen quad into `write`, with `reads`
+        /// bound to the fragment stage.
+        pub fn render(
+            mut self,
+            pipeline: &RenderPipeline,
+            reads: &[PassTexture<'_>],
+            write: PassTexture<'_>,
+        ) -> Result<Self> {
+            let vertex = self.bindings(&pipeline.vertex_uniforms, false);
+            let fragment = self.bindings(&pipeline.fragment_uniforms, true);
+            let (reads, write) = self.resolve_all(reads, write)?;
+            let textures: Vec<_> = reads.iter().map(|t| self.metal(t)).collect();
+
+            let target = self.metal(&write);
+            let scratch = if renders_into(pipeline, target.pixelFormat()) {
+                None
+            } else {
+                Some(self.scratch(CONVERT_SCRATCH, self.size(&write), pipeline.format)?)
+            };
+            self.ctx.encode_render_pass(
+                &self.command_buffer,
+                pipeline,
+                scratch.as_ref().map_or(target, |t| t.metal_texture()),
+                &textures,
+                &self.bytes(&fragment),
+                &[],
+                &[],
+                &self.bytes(&vertex),
+            )?;
+            if let Some(scratch) = &scratch {
+                self.ctx.encode_blit_texture(
+                    &self.command_buffer,
+                    scratch.metal_texture(),
+                    target,
+                    BlitFilter::Nearest,
+                )?;
+            }
+            Ok(self)
+        }
+
+        /// Commit the chain and hand it to the bridge, which waits for it
+        /// before presenting the output.
+        pub fn finish(self) -> Result<()> {
+            self.check_bound();
+            let pending = self.ctx.commit(self.command_buffer);
+            self.input
+                .metal_bridge()
+                .store_command_buffer(pending.into_command_buffer());
+            Ok(())
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    impl Passes<'_, '_> {
+        fn srv<'t>(&'t self, texture: &'t Resolved) -> Result<crate::SrvBinding<'t>> {
+            Ok(match texture {
+                Resolved::Input => self.input.input_srv.clone().into(),
+                Resolved::Output => bail!("the output texture can't be read by a pass"),
+                Resolved::Intermediate(t) => crate::SrvBinding::Texture(t),
+            })
+        }
+
+        /// Blit `src` over the whole of `dst` with bilinear filtering.
+        fn stretch(&self, src: &Resolved, dst: &GpuTexture) -> Result<()> {
+            let src = match src {
+                Resolved::Input => match self.input.bridge.input_texture() {
+                    Some(t) => t,
+                    None => bail!("the input texture is not available"),
+                },
+                Resolved::Output => bail!("the output texture can't be read by a pass"),
+                Resolved::Intermediate(t) => t.dx11_texture().clone(),
+            };
+            self.ctx
+                .blit_texture(&src, dst.dx11_texture(), BlitFilter::Linear)
+        }
+
+        /// The context's uniforms buffer number `pool`, filled with `data`.
+        fn pass_cbuf(
+            &self,
+            pool: usize,
+            data: &[u8],
+        ) -> Option<windows::Win32::Graphics::Direct3D11::ID3D11Buffer> {
+            let len = data.len();
+            let mut cached = self.ctx.pass_cbufs.borrow_mut();
+            if cached.len() <= pool {
+                cached.resize_with(pool + 1, || None);
+            }
+            let entry = &mut cached[pool];
+            if entry.as_ref().is_none_or(|(_, size)| *size < len) {
+                let Some(cbuf) =
+                    gpu_interop::dx11::create_dynamic_cbuf(self.ctx.dx11_device().device(), len)
+                else {
+                    gpu_interop::error_limited!(
+                        "Failed to create the pass uniforms constant buffer"
+                    );
+                    return None;
+                };
+                *entry = Some((cbuf, len));
+            }
+            let (cbuf, _) = entry.as_ref()?;
+            self.ctx.update_constant_buffer(cbuf, data);
+            Some(cbuf.clone())
+        }
+
+        /// Fill a constant buffer for each named uniform one of `stages`
+        /// binds. Indexed like `named`.
+        fn named_cbufs(
+            &self,
+            stages: &[&[Binding]],
+        ) -> Vec<Option<windows::Win32::Graphics::Direct3D11::ID3D11Buffer>> {
+            let used = |u: usize| {
+                stages
+                    .iter()
+                    .any(|bindings| bindings.iter().any(|&(b, _)| b == Some(u)))
+            };
+            self.named
+                .iter()
+                .enumerate()
+                .map(|(u, named)| {
+                    used(u)
+                        .then(|| self.pass_cbuf(1 + u, &named.bytes))
+                        .flatten()
+                })
+                .collect()
+        }
+
+        /// The constant buffers for `bindings`, indexed by register.
+        fn cbufs(
+            &self,
+            bindings: &[Binding],
+            named: &[Option<windows::Win32::Graphics::Direct3D11::ID3D11Buffer>],
+        ) -> Vec<Option<windows::Win32::Graphics::Direct3D11::ID3D11Buffer>> {
+            let len = bindings.iter().map(|&(_, r)| r + 1).max().unwrap_or(0);
+            let mut cbufs = vec![None; len];
+            for &(u, register) in bindings {
+                cbufs[register] = match u {
+                    Some(u) => named[u].clone(),
+                    None => self.cbuf.clone(),
+                };
+            }
+            cbufs
+        }
+
+        /// Run `pipeline` over the frame, reading `reads` and writing
+        /// `write`.
+        pub fn compute(
+            mut self,
+            pipeline: &ComputePipeline,
+            reads: &[PassTexture<'_>],
+            write: PassTexture<'_>,
+        ) -> Result<Self> {
+            let bindings = self.bindings(&pipeline.uniforms, true);
+            let (reads, write) = self.resolve_all(reads, write)?;
+            let srvs = reads
+                .iter()
+                .map(|t| self.srv(t))
+                .collect::<Result<Vec<_>>>()?;
+            let uav = match &write {
+                Resolved::Input => unreachable!("rejected by resolve_all"),
+                Resolved::Output => self.input.output_uav.clone().into(),
+                Resolved::Intermediate(t) => crate::UavBinding::Texture(t),
+            };
+            let named = self.named_cbufs(&[&bindings]);
+            self.ctx.dispatch_compute(
+                pipeline,
+                &[uav],
+                &srvs,
+                &self.cbufs(&bindings, &named),
+                self.grid(&write),
+                pipeline.threadgroup_size(),
+            );
+            Ok(self)
+        }
+
+        /// Draw `pipeline`'s fullscreen quad into `write`, with `reads`
+        /// bound to the pixel stage.
+        pub fn render(
+            mut self,
+            pipeline: &RenderPipeline,
+            reads: &[PassTexture<'_>],
+            write: PassTexture<'_>,
+        ) -> Result<Self> {
+            let vertex = self.bindings(&pipeline.vertex_uniforms, false);
+            let pixel = self.bindings(&pipeline.fragment_uniforms, true);
+            let (reads, write) = self.resolve_all(reads, write)?;
+            let srvs = reads
+                .iter()
+                .map(|t| self.srv(t))
+                .collect::<Result<Vec<_>>>()?;
+            let target = match &write {
+                Resolved::Input => unreachable!("rejected by resolve_all"),
+                Resolved::Output => (&self.input.output_texture).into(),
+                Resolved::Intermediate(t) => crate::RenderTarget::Texture(t),
+            };
+            let named = self.named_cbufs(&[&vertex, &pixel]);
+            self.ctx.dispatch_render(
+                pipeline,
+                target,
+                &srvs,
+                &self.cbufs(&pixel, &named),
+                &[],
+                &self.cbufs(&vertex, &named),
+            )?;
+            Ok(self)
+        }
+
+        /// End the chain. DX11 passes are already submitted in order.
+        pub fn finish(self) -> Result<()> {
+            self.check_bound();
+            Ok(())
+        }
+    }
+}
+
+/// Run a chain of passes written as data flow; see [`passes`](crate::passes).
+///
+/// ```rust,ignore
+/// gpu_passes!(ctx, input, {
+///     after_gray = compute(grayscale_pipeline)(input) with { gray_params: gray };
+///     after_tint = render(tint_pipeline)(after_gray) with { tint_params: tint };
+///     output = compute(blend_pipeline)(input, after_tint) with { blend_params: blend };
+/// })?;
+/// ```
+///
+/// Each line names the texture written, the kind of pass and its pipeline
+/// (any expression evaluating to `&ComputePipeline` / `&RenderPipeline`),
+/// and the textures read. `input` and `output` are the frame's own textures;
+/// any other name is an [intermediate](crate::DrawInput::intermediate).
+/// A line ending in `as Rgba16Float` (or another
+/// [`IntermediateFormat`](crate::IntermediateFormat) variant) stores the
+/// intermediate it writes in that format; see
+/// [`Passes::format`](crate::passes::Passes::format). `/ 4` before it (or
+/// in its place) runs the pass at a quarter of the processing size; see
+/// [`Passes::downscale`](crate::passes::Passes::downscale). A trailing
+/// `with { name: value, .. }` binds each value to the uniform of that name
+/// from that pass on; see [`Passes::uniform`](crate::passes::Passes::uniform).
+/// `uniforms:` is optional. Evaluates to `anyhow::Result<()>`.
+#[macro_export]
+macro_rules! gpu_passes {
+    (@chain $passes:ident, $write:ident = $kind:ident($pipeline:expr)($($read:ident),* $(,)?) $(/ $divisor:literal)? $(as $format:ident)? $(with { $($name:ident: $value:expr),* $(,)? })?; $($rest:tt)*) => {{
+        $(let $passes = $crate::gpu_passes!(@downscale $passes, $write, $divisor);)?
+        $(let $passes = $crate::gpu_passes!(@format $passes, $write, $format);)?
+        $($(let $passes = $passes.uniform(stringify!($name), &$value);)*)?
+        let $passes = $passes.$kind(
+            $pipeline,
+            &[$($crate::gpu_passes!(@texture $read)),*],
+            $crate::gpu_passes!(@texture $write),
+        )?;
+        $crate::gpu_passes!(@chain $passes, $($rest)*)
+    }};
+    (@chain $passes:ident,) => {
+        $passes.finish()
+    };
+    (@format $passes:ident, input, $format:ident) => {
+        compile_error!("the input can't be written")
+    };
+    (@format $passes:ident, output, $format:ident) => {
+        compile_error!("the output's format is set by `GpuPlugin::texture_format`")
+    };
+    (@format $passes:ident, $name:ident, $format:ident) => {
+        $passes.format(stringify!($name), $crate::IntermediateFormat::$format)
+    };
+    (@downscale $passes:ident, input, $divisor:literal) => {
+        compile_error!("the input can't be written")
+    };
+    (@downscale $passes:ident, output, $divisor:literal) => {
+        compile_error!("the output is always at the processing size")
+    };
+    (@downscale $passes:ident, $name:ident, $divisor:literal) => {
+        $passes.downscale(stringify!($name), $divisor)
+    };
+    (@texture input) => {
+        $crate::passes::PassTexture::Input
+    };
+    (@texture output) => {
+        $crate::passes::PassTexture::Output
+    };
+    (@texture $name:ident) => {
+        $crate::passes::PassTexture::Intermediate(stringify!($name))
+    };
+    ($ctx:expr, $input:expr, uniforms: $uniforms:expr, { $($body:tt)* }) => {
+        (|| -> ::anyhow::Result<()> {
+            let passes = $crate::passes::Passes::new($ctx, $input)?.uniforms(&$uniforms);
+            $crate::gpu_passes!(@chain passes, $($body)*)
+        })()
+    };
+    ($ctx:expr, $input:expr, { $($body:tt)* }) => {
+        (|| -> ::anyhow::Result<()> {
+            let passes = $crate::passes::Passes::new($ctx, $input)?;
+            $crate::gpu_passes!(@chain passes, $($body)*)
+        })()
+    };
+}
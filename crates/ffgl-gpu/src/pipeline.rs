@@ -1,14 +1,62 @@
 //! GPU pipeline types for compute and render operations.
 //!
 //! These wrap platform-specific pipeline state objects created from shader
-//! functions in the GPU context's shader library.
+//! functions in the GPU context's shader library, or from a [`ShaderSource`].
 
 #[cfg(target_os = "macos")]
 use objc2::rc::Retained;
 #[cfg(target_os = "macos")]
 use objc2::runtime::ProtocolObject;
 #[cfg(target_os = "macos")]
-use objc2_metal::{MTLBuffer, MTLComputePipelineState, MTLRenderPipelineState};
+use objc2_metal::{MTLBuffer, MTLComputePipelineState, MTLFunction, MTLRenderPipelineState};
+
+/// Where [`create_compute_pipeline`](crate::GpuContext::create_compute_pipeline)
+/// gets a kernel from.
+///
+/// Every variant is accepted on every backend, so a plugin can pick one
+/// shape and keep a single call site:
+///
+/// | Variant | macOS | Windows |
+/// |---|---|---|
+/// | `NamedEntry` | function in the context's Metal library | error, D3D11 has no library |
+/// | `Bytecode` | `.metallib` holding one kernel | `.cso` |
+/// | `Source` | MSL, compiled at runtime | HLSL `cs_5_0`, compiled at runtime |
+///
+/// `&str` converts to `NamedEntry` and byte slices to `Bytecode`, so the
+/// existing `create_compute_pipeline("name")` and
+/// `create_compute_pipeline(BYTES)` calls keep working.
+#[derive(Debug, Clone, Copy)]
+pub enum ShaderSource<'a> {
+    /// A kernel function by name in the context's shader library.
+    NamedEntry(&'a str),
+    /// Pre-compiled shader bytes for the platform's shading language.
+    Bytecode(&'a [u8]),
+    /// Shader source compiled when the pipeline is created.
+    Source {
+        /// MSL on macOS, HLSL on Windows.
+        code: &'a str,
+        /// Kernel function to create the pipeline for.
+        entry: &'a str,
+    },
+}
+
+impl<'a> From<&'a str> for ShaderSource<'a> {
+    fn from(name: &'a str) -> Self {
+        ShaderSource::NamedEntry(name)
+    }
+}
+
+impl<'a> From<&'a [u8]> for ShaderSource<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        ShaderSource::Bytecode(bytes)
+    }
+}
+
+impl<'a, const N: usize> From<&'a [u8; N]> for ShaderSource<'a> {
+    fn from(bytes: &'a [u8; N]) -> Self {
+        ShaderSource::Bytecode(bytes)
+    }
+}
 
 /// A compiled compute pipeline (kernel).
 ///
@@ -17,9 +65,42 @@ use objc2_metal::{MTLBuffer, MTLComputePipelineState, MTLRenderPipelineState};
 pub struct ComputePipeline {
     #[cfg(target_os = "macos")]
     pub(crate) state: Retained<ProtocolObject<dyn MTLComputePipelineState>>,
+    /// Kernel function, kept for creating argument encoders.
+    #[cfg(target_os = "macos")]
+    pub(crate) function: Retained<ProtocolObject<dyn MTLFunction>>,
 
     #[cfg(target_os = "windows")]
     pub(crate) shader: windows::Win32::Graphics::Direct3D11::ID3D11ComputeShader,
+    /// Register of the kernel's `FfglGpuGrid` cbuffer, bound by
+    /// `dispatch_compute`.
+    #[cfg(target_os = "windows")]
+    pub(crate) grid_slot: Option<u32>,
+
+    /// Uniform slots reflected from the kernel.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) uniforms: Vec<UniformSlot>,
+
+    /// Threadgroup size the kernel declares (D3D11) or runs best at
+    /// (Metal).
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) threadgroup: (usize, usize),
+
+    /// Counts the pipeline in its context's memory report.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) _tracked: crate::memory::Tracked,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl ComputePipeline {
+    /// Threadgroup size to dispatch this kernel with, as used by
+    /// [`dispatch_auto`](crate::GpuContext::dispatch_auto).
+    ///
+    /// On Windows this is the kernel's `[numthreads(x, y, 1)]`. Metal
+    /// kernels don't declare one, so it is the pipeline's SIMD width by as
+    /// many rows as its thread limit allows.
+    pub fn threadgroup_size(&self) -> (usize, usize) {
+        self.threadgroup
+    }
 }
 
 /// A compiled render pipeline (vertex + fragment).
@@ -31,6 +112,9 @@ pub struct ComputePipeline {
 pub struct RenderPipeline {
     #[cfg(target_os = "macos")]
     pub(crate) state: Retained<ProtocolObject<dyn MTLRenderPipelineState>>,
+    /// Pixel format of the render target `state` was built for.
+    #[cfg(target_os = "macos")]
+    pub(crate) format: crate::intermediate::IntermediateFormat,
     /// Fullscreen quad vertex buffer (4 vertices: position + texcoord).
     #[cfg(target_os = "macos")]
     pub(crate) quad_vb: Retained<ProtocolObject<dyn MTLBuffer>>,
@@ -45,4 +129,129 @@ pub struct RenderPipeline {
     pub(crate) quad_vb: windows::Win32::Graphics::Direct3D11::ID3D11Buffer,
     #[cfg(target_os = "windows")]
     pub(crate) sampler: windows::Win32::Graphics::Direct3D11::ID3D11SamplerState,
+
+    /// Uniform slots reflected from the vertex stage.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) vertex_uniforms: Vec<UniformSlot>,
+    /// Uniform slots reflected from the fragment / pixel stage.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) fragment_uniforms: Vec<UniformSlot>,
+
+    /// Counts the pipeline and its vertex buffer in its context's memory
+    /// report.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) _tracked: crate::memory::Tracked,
+}
+
+/// A uniform / constant buffer slot as declared by a shader, recorded from
+/// Metal or D3D reflection when a pipeline is created.
+///
+/// [`Passes`](crate::Passes) binds uniforms to slots by name. In debug
+/// builds the slots also catch Rust structs whose size or padding has
+/// drifted from the shader-side declaration before they turn into silently
+/// garbled output.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[derive(Debug, Clone)]
+pub(crate) struct UniformSlot {
+    /// Buffer index (Metal) or `b#` register (D3D).
+    pub(crate) index: usize,
+    /// Size in bytes the shader expects.
+    pub(crate) size: usize,
+    /// Required alignment in bytes (`1` when unknown).
+    pub(crate) alignment: usize,
+    /// Name of the argument / cbuffer in the shader source.
+    pub(crate) name: String,
+}
+
+/// Log a precise error if `len` bytes bound at `index` don't match the
+/// reflected uniform slot for that index, rate limited since it repeats
+/// every frame. Debug builds only.
+///
+/// A payload smaller than the declared size is always an error. On Metal a
+/// larger payload is only accepted when it is a whole number of elements,
+/// since reflection reports the element size for `device const T*`
+/// arguments; D3D constant buffers may simply be larger than declared.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn check_uniform_size(stage: &str, expected: &[UniformSlot], index: usize, len: usize) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let Some(slot) = expected.iter().find(|slot| slot.index == index) else {
+        return;
+    };
+    if slot.size == 0 {
+        return;
+    }
+    let size_mismatch = len < slot.size || (cfg!(target_os = "macos") && len % slot.size != 0);
+    if size_mismatch {
+        gpu_interop::error_limited!(
+            "{stage} uniform '{}' at index {index}: bound {len} bytes but the shader expects {} \
+             bytes (alignment {}); check the Rust struct's field order and padding",
+            slot.name,
+            slot.size,
+            slot.alignment,
+        );
+    } else if len % slot.alignment != 0 {
+        gpu_interop::error_limited!(
+            "{stage} uniform '{}' at index {index}: bound {len} bytes is not a multiple of the \
+             shader's {}-byte alignment",
+            slot.name,
+            slot.alignment,
+        );
+    }
+}
+
+/// Names of the functions in a Metal library, sorted.
+#[cfg(target_os = "macos")]
+pub(crate) fn function_names(library: &ProtocolObject<dyn objc2_metal::MTLLibrary>) -> Vec<String> {
+    use objc2_metal::MTLLibrary;
+
+    let mut names: Vec<String> = library
+        .functionNames()
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Error for a function `name` missing from a library holding `available`,
+/// suggesting the closest name when it looks like a typo.
+#[cfg(target_os = "macos")]
+pub(crate) fn missing_function(kind: &str, name: &str, available: &[String]) -> anyhow::Error {
+    // Allow about one edit per three characters, so "blurr" finds "blur"
+    // but "glow" doesn't turn into "blur".
+    let limit = (name.chars().count() / 3).max(1);
+    let suggestion = available
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| format!(" (did you mean '{candidate}'?)"))
+        .unwrap_or_default();
+    if available.is_empty() {
+        anyhow::anyhow!("{kind} '{name}' not found; the library has no functions")
+    } else {
+        anyhow::anyhow!(
+            "{kind} '{name}' not found in library{suggestion}; available: {}",
+            available.join(", ")
+        )
+    }
+}
+
+/// Levenshtein distance between two strings, by character.
+#[cfg(target_os = "macos")]
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
 }
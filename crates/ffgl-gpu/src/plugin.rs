@@ -6,8 +6,12 @@
 //! then [`GpuPlugin::gpu_draw`] each frame with a [`DrawInput`] containing
 //! pre-extracted platform textures.
 
+use crate::adaptive::AdaptiveResolution;
 use crate::context::GpuContext;
+use ffgl_core::parameters::ParamStore;
 use ffgl_core::FFGLData;
+use gpu_interop::{ColorSpace, PixelRect, SurfaceOptions, TextureFormat, DEFAULT_STALENESS_WINDOW};
+use std::time::Duration;
 
 // ---------------------------------------------------------------------------
 // DrawInput — platform-specific pre-extracted textures
@@ -15,9 +19,14 @@ use ffgl_core::FFGLData;
 
 #[cfg(target_os = "macos")]
 mod draw_input_impl {
+    use ffgl_core::ffi::FFGLTextureStruct;
     use gpu_interop::metal::GlMetalBridge;
+    use gpu_interop::PixelRect;
     use objc2::runtime::ProtocolObject;
-    use objc2_metal::MTLTexture;
+    use objc2_metal::{MTLCommandQueue, MTLDevice, MTLTexture};
+    use std::rc::Rc;
+
+    use crate::{GpuContext, GpuTexture, IntermediateFormat};
 
     /// Pre-extracted GPU textures for the current frame.
     ///
@@ -35,23 +44,112 @@ mod draw_input_impl {
         pub width: u32,
         /// Processing height in pixels.
         pub height: u32,
+        /// Region of the host frame being processed, in host pixels (GL
+        /// bottom-left origin), or `None` for the full frame. The input and
+        /// output textures cover only this region.
+        pub roi: Option<PixelRect>,
+        /// Host viewport: where the frame lands in the host framebuffer.
+        /// Non-zero `x`/`y` when the host renders into part of a larger
+        /// target; `roi` is relative to this rectangle's origin.
+        pub viewport: PixelRect,
+        /// All host input textures, one entry per input slot (`None` when
+        /// disconnected). `input` is bridged from the first; the others are
+        /// raw GL textures, see [`FFGLTextureStruct::uv_scale`] for padded
+        /// allocations.
+        pub host_textures: &'a [Option<&'a FFGLTextureStruct>],
+        pub(crate) output_size: (u32, u32),
         pub(crate) bridge: &'a mut GlMetalBridge,
+        pub(crate) ctx: &'a GpuContext,
+        pub(crate) has_back_output: bool,
+    }
+
+    /// The Metal objects behind a frame, for plugins encoding their own
+    /// work (see [`DrawInput::metal`]).
+    pub struct MetalResources<'r> {
+        /// Device the context and bridge textures were created on.
+        pub device: &'r ProtocolObject<dyn MTLDevice>,
+        /// Queue the framework commits to. Command buffers from it run in
+        /// order with the framework's own passes.
+        pub command_queue: &'r ProtocolObject<dyn MTLCommandQueue>,
+        /// Same as [`DrawInput::input`].
+        pub input: &'r ProtocolObject<dyn MTLTexture>,
+        /// Same as [`DrawInput::output`].
+        pub output: &'r ProtocolObject<dyn MTLTexture>,
+        /// Same as [`DrawInput::back_output`].
+        pub back_output: Option<&'r ProtocolObject<dyn MTLTexture>>,
     }
 
     impl<'a> DrawInput<'a> {
         /// Access the underlying Metal bridge for advanced operations
-        /// (e.g. `store_command_buffer`, `back_output_metal_texture`).
+        /// (e.g. `store_command_buffer`).
         pub fn metal_bridge(&mut self) -> &mut GlMetalBridge {
             self.bridge
         }
+
+        /// The frame's device, command queue and textures in one place.
+        ///
+        /// Always `Some` on macOS. The `Option` gives it the shape of the
+        /// Windows [`dx11`](DrawInput::dx11), which has nothing to return
+        /// while the bridge's textures are missing.
+        pub fn metal(&self) -> Option<MetalResources<'_>> {
+            Some(MetalResources {
+                device: self.ctx.device.device(),
+                command_queue: self.ctx.device.command_queue(),
+                input: self.input,
+                output: self.output,
+                back_output: self.back_output(),
+            })
+        }
+
+        /// The previous frame's output (same size and format as `output`),
+        /// for temporal accumulation and interlaced field modes. It holds the
+        /// result as presented, after the framework's alpha conversion.
+        ///
+        /// `None` unless the previous frame was pipelined into this one, so
+        /// always after a gap, a resize, a synchronous frame, or when tiling.
+        pub fn back_output(&self) -> Option<&ProtocolObject<dyn MTLTexture>> {
+            if !self.has_back_output {
+                return None;
+            }
+            self.bridge.back_output_metal_texture()
+        }
+
+        /// A scratch texture called `name` at the processing size
+        /// (`width` x `height`), for passes between `input` and `output`.
+        ///
+        /// Asking for the same name again, in this frame or a later one,
+        /// returns the same texture as long as the size is unchanged. Its
+        /// contents are undefined at the start of each frame. See
+        /// [`crate::intermediate`].
+        pub fn intermediate(&self, name: &str) -> anyhow::Result<Rc<GpuTexture>> {
+            self.intermediate_with_format(name, IntermediateFormat::Rgba8)
+        }
+
+        /// [`intermediate`](Self::intermediate) in `format`, e.g. a float
+        /// format for accumulating HDR values. The same name in another
+        /// format is a different texture.
+        pub fn intermediate_with_format(
+            &self,
+            name: &str,
+            format: IntermediateFormat,
+        ) -> anyhow::Result<Rc<GpuTexture>> {
+            self.ctx
+                .intermediates
+                .get(self.ctx, name, self.width, self.height, format)
+        }
     }
 }
 
 #[cfg(target_os = "windows")]
 mod draw_input_impl {
-    use gpu_interop::dx11::GlDx11Bridge;
+    use ffgl_core::ffi::FFGLTextureStruct;
+    use gpu_interop::dx11::Dx11Bridge;
+    use gpu_interop::PixelRect;
+    use std::rc::Rc;
     use windows::Win32::Graphics::Direct3D11::*;
 
+    use crate::{GpuContext, GpuTexture, IntermediateFormat};
+
     /// Pre-extracted GPU textures for the current frame.
     ///
     /// The framework populates this from the bridge before calling
@@ -69,31 +167,303 @@ mod draw_input_impl {
         pub width: u32,
         /// Processing height in pixels.
         pub height: u32,
-        pub(crate) bridge: &'a mut GlDx11Bridge,
+        /// Region of the host frame being processed, in host pixels (GL
+        /// bottom-left origin), or `None` for the full frame. The input and
+        /// output textures cover only this region.
+        pub roi: Option<PixelRect>,
+        /// Host viewport: where the frame lands in the host framebuffer.
+        /// Non-zero `x`/`y` when the host renders into part of a larger
+        /// target; `roi` is relative to this rectangle's origin.
+        pub viewport: PixelRect,
+        /// All host input textures, one entry per input slot (`None` when
+        /// disconnected). `input` is bridged from the first; the others are
+        /// raw GL textures, see [`FFGLTextureStruct::uv_scale`] for padded
+        /// allocations.
+        pub host_textures: &'a [Option<&'a FFGLTextureStruct>],
+        pub(crate) output_size: (u32, u32),
+        pub(crate) bridge: &'a mut dyn Dx11Bridge,
+        pub(crate) ctx: &'a GpuContext,
+        pub(crate) has_back_output: bool,
+    }
+
+    /// The D3D11 objects behind a frame, for plugins issuing their own
+    /// calls (see [`DrawInput::dx11`]). COM references, so they can be kept
+    /// across the borrow of the [`DrawInput`].
+    pub struct Dx11Resources {
+        /// Device the context and bridge textures were created on.
+        pub device: ID3D11Device,
+        /// Context to issue work on:
+        /// [`command_context`](GpuContext::command_context), so calls land
+        /// in order with the framework's passes when it records into a
+        /// deferred context.
+        pub context: ID3D11DeviceContext,
+        /// Texture behind [`DrawInput::input_srv`].
+        pub input_texture: ID3D11Texture2D,
+        /// Same as [`DrawInput::input_srv`].
+        pub input_srv: ID3D11ShaderResourceView,
+        /// Same as [`DrawInput::output_uav`].
+        pub output_uav: ID3D11UnorderedAccessView,
+        /// Same as [`DrawInput::output_texture`].
+        pub output_texture: ID3D11Texture2D,
+        /// Same as [`DrawInput::back_output`].
+        pub back_output: Option<ID3D11ShaderResourceView>,
     }
 
     impl<'a> DrawInput<'a> {
         /// Access the underlying DX11 bridge for advanced operations
-        /// (e.g. `device`, `context`, `back_output_srv`).
-        pub fn dx11_bridge(&mut self) -> &mut GlDx11Bridge {
+        /// (e.g. `device`, `context`). This is a
+        /// [`GlDx11Bridge`](gpu_interop::dx11::GlDx11Bridge) unless the
+        /// driver forced the PBO fallback.
+        pub fn dx11_bridge(&mut self) -> &mut dyn Dx11Bridge {
             self.bridge
         }
+
+        /// The frame's device, context and textures in one place, without
+        /// going through [`dx11_bridge`](Self::dx11_bridge). `None` if the
+        /// bridge lost its input texture.
+        pub fn dx11(&self) -> Option<Dx11Resources> {
+            Some(Dx11Resources {
+                device: self.ctx.dx11_device().device().clone(),
+                context: self.ctx.command_context().clone(),
+                input_texture: self.bridge.input_texture()?,
+                input_srv: self.input_srv.clone(),
+                output_uav: self.output_uav.clone(),
+                output_texture: self.output_texture.clone(),
+                back_output: self.back_output(),
+            })
+        }
+
+        /// SRV for the previous frame's output (same size and format as
+        /// `output_texture`), for temporal accumulation and interlaced field
+        /// modes. It holds the result as presented, after the framework's
+        /// color and alpha conversions.
+        ///
+        /// `None` unless the previous frame was pipelined into this one, so
+        /// always after a gap, a resize, a synchronous frame, when tiling, or
+        /// with the PBO fallback bridge.
+        pub fn back_output(&self) -> Option<ID3D11ShaderResourceView> {
+            if !self.has_back_output {
+                return None;
+            }
+            self.bridge.back_output_srv()
+        }
+
+        /// A scratch texture called `name` at the processing size
+        /// (`width` x `height`), for passes between `input` and `output`.
+        ///
+        /// Asking for the same name again, in this frame or a later one,
+        /// returns the same texture as long as the size is unchanged. Its
+        /// contents are undefined at the start of each frame. See
+        /// [`crate::intermediate`].
+        pub fn intermediate(&self, name: &str) -> anyhow::Result<Rc<GpuTexture>> {
+            self.intermediate_with_format(name, IntermediateFormat::Rgba8)
+        }
+
+        /// [`intermediate`](Self::intermediate) in `format`, e.g. a float
+        /// format for accumulating HDR values. The same name in another
+        /// format is a different texture.
+        pub fn intermediate_with_format(
+            &self,
+            name: &str,
+            format: IntermediateFormat,
+        ) -> anyhow::Result<Rc<GpuTexture>> {
+            self.ctx
+                .intermediates
+                .get(self.ctx, name, self.width, self.height, format)
+        }
     }
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
 mod draw_input_impl {
+    use ffgl_core::ffi::FFGLTextureStruct;
+    use gpu_interop::PixelRect;
+
     /// Stub for unsupported platforms.
     pub struct DrawInput<'a> {
         /// Processing width in pixels.
         pub width: u32,
         /// Processing height in pixels.
         pub height: u32,
+        /// Region of the host frame being processed, in host pixels (GL
+        /// bottom-left origin), or `None` for the full frame. The input and
+        /// output textures cover only this region.
+        pub roi: Option<PixelRect>,
+        /// Host viewport: where the frame lands in the host framebuffer.
+        /// Non-zero `x`/`y` when the host renders into part of a larger
+        /// target; `roi` is relative to this rectangle's origin.
+        pub viewport: PixelRect,
+        /// All host input textures, one entry per input slot (`None` when
+        /// disconnected).
+        pub host_textures: &'a [Option<&'a FFGLTextureStruct>],
+        pub(crate) output_size: (u32, u32),
         pub(crate) _lifetime: std::marker::PhantomData<&'a ()>,
     }
 }
 
 pub use draw_input_impl::DrawInput;
+#[cfg(target_os = "windows")]
+pub use draw_input_impl::Dx11Resources;
+#[cfg(target_os = "macos")]
+pub use draw_input_impl::MetalResources;
+
+impl DrawInput<'_> {
+    /// Size in host pixels of the area the output is presented at: the
+    /// frame, the region of interest or the tile including its overlap.
+    /// Equal to `width` x `height` at full internal resolution.
+    pub fn output_dimensions(&self) -> (u32, u32) {
+        self.output_size
+    }
+
+    /// Processing pixels per output pixel on each axis, below 1 when the
+    /// internal resolution is reduced. Multiply lengths given in output
+    /// pixels, such as a blur radius, by it to get processing pixels.
+    pub fn scale(&self) -> (f32, f32) {
+        let (w, h) = self.output_size;
+        (
+            self.width as f32 / w.max(1) as f32,
+            self.height as f32 / h.max(1) as f32,
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Scaling policy
+// ---------------------------------------------------------------------------
+
+/// How the processing size is derived from the frame size and the
+/// `internal_resolution` scale, returned from [`GpuPlugin::scaling_policy`].
+///
+/// Odd or fractional processing sizes shimmer with nearest filtering because
+/// the pixel mapping changes across the frame; the non-default policies keep
+/// the mapping regular. Letterbox bars are cleared to transparent black.
+/// Tiled frames always use [`ScalingPolicy::Stretch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalingPolicy {
+    /// Scale both axes by the factor and stretch back to the frame.
+    #[default]
+    Stretch,
+    /// As [`Stretch`](Self::Stretch), with dimensions rounded down to even
+    /// numbers.
+    EvenDimensions,
+    /// Keep the frame's exact aspect ratio. If no processing size near the
+    /// requested scale has that ratio, a centred letterboxed area is
+    /// processed instead.
+    PreserveAspect,
+    /// Only downscale by whole-number divisors (1/2, 1/3, ...) so every
+    /// processing pixel covers an exact block of frame pixels. Leftover edge
+    /// pixels are letterboxed.
+    IntegerDivisor,
+}
+
+// ---------------------------------------------------------------------------
+// Alpha mode
+// ---------------------------------------------------------------------------
+
+/// Alpha convention seen by [`GpuPlugin::gpu_draw`], returned from
+/// [`GpuPlugin::alpha_mode`].
+///
+/// Hosts differ on whether FFGL textures carry premultiplied alpha. Effects
+/// that modify color independently of alpha (color grading, keying) usually
+/// want straight alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    /// Alpha is passed through exactly as the host provides it.
+    #[default]
+    Passthrough,
+    /// The host's textures are premultiplied; `gpu_draw` sees straight
+    /// alpha. The framework un-premultiplies the input after blit-in and
+    /// premultiplies the output before blit-out with a built-in compute
+    /// pass.
+    Straight,
+}
+
+// ---------------------------------------------------------------------------
+// Missing input
+// ---------------------------------------------------------------------------
+
+/// What a frame outputs when the host provides no input texture, returned
+/// from [`GpuPlugin::missing_input`].
+///
+/// Hosts leave the input empty when nothing is connected upstream, e.g. for
+/// an effect at the top of a chain or a mixer with one side unplugged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingInput {
+    /// Hand the frame to the fallback's passthrough, as when the GPU path
+    /// is unavailable.
+    #[default]
+    Passthrough,
+    /// Clear the viewport to transparent black.
+    Clear,
+    /// Present the last drawn frame again, in the region it was drawn to.
+    /// Cleared instead if nothing has been drawn yet, or the last frame was
+    /// tiled.
+    HoldLastFrame,
+    /// Run [`GpuPlugin::gpu_draw`] as usual on an input cleared to
+    /// transparent black, for generators and effects that draw without
+    /// input.
+    Source,
+}
+
+// ---------------------------------------------------------------------------
+// Tiling
+// ---------------------------------------------------------------------------
+
+/// Tiled processing configuration, returned from [`GpuPlugin::tiling`].
+///
+/// When the processed region is larger than one tile, the frame is split into
+/// tiles that are blitted, drawn, and written back one after another within
+/// the frame. [`GpuPlugin::gpu_draw`] is called once per tile with
+/// [`DrawInput::roi`] set to the tile's host region, so intermediate textures
+/// sized from `input.width` / `input.height` stay bounded by the tile size.
+///
+/// Edge tiles can be smaller than the shared textures; always dispatch over
+/// `input.width` x `input.height` rather than the texture size. Tiled frames
+/// are processed synchronously (no one-frame pipelining).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tiling {
+    /// Maximum tile width in host pixels, excluding overlap.
+    pub tile_width: u32,
+    /// Maximum tile height in host pixels, excluding overlap.
+    pub tile_height: u32,
+    /// Extra host pixels read on each side of a tile and discarded on
+    /// write-back, for kernels that sample neighbouring pixels.
+    pub overlap: u32,
+}
+
+impl Tiling {
+    pub fn new(tile_width: u32, tile_height: u32, overlap: u32) -> Self {
+        Self {
+            tile_width,
+            tile_height,
+            overlap,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Thread migration
+// ---------------------------------------------------------------------------
+
+/// What happens when the host draws an instance on a different thread than
+/// before, returned from [`GpuPlugin::thread_migration`].
+///
+/// The GPU context and bridge are kept per thread, next to the GL context
+/// the host makes current there. An instance that moves to another thread
+/// finds that thread's context, not the one its pipelines were created on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThreadMigration {
+    /// Release the new thread's bridge state and call
+    /// [`GpuPlugin::gpu_init`] again with that thread's context. The first
+    /// frame on the new thread is drawn synchronously. What the instance
+    /// held on the old thread is released when another instance draws there.
+    #[default]
+    Rebuild,
+    /// Keep drawing only on the first thread the instance drew on, and pass
+    /// frames through on any other. For plugins holding resources that
+    /// can't be recreated.
+    Pin,
+}
 
 // ---------------------------------------------------------------------------
 // GpuPlugin trait
@@ -132,6 +502,159 @@ pub trait GpuPlugin: Send + Sync + 'static {
     /// provides access to the platform GPU device and shader library.
     fn gpu_init(&mut self, ctx: &GpuContext) -> anyhow::Result<()>;
 
+    /// Region of the frame to process this frame, in host pixels (GL
+    /// bottom-left origin).
+    ///
+    /// Effects that only touch part of the frame (e.g. a ticker in the lower
+    /// third) can return that rectangle so only it is blitted to the GPU and
+    /// processed; the rest of the frame is passed through unchanged. The
+    /// rectangle is clamped to the viewport and reported back in
+    /// [`DrawInput::roi`]. Return `None` (the default) to process the whole
+    /// frame.
+    fn processing_rect(&self, data: &FFGLData) -> Option<PixelRect> {
+        let _ = data;
+        None
+    }
+
+    /// Tiling for very large frames. Return `Some` to split regions larger
+    /// than one tile into sequentially processed tiles (see [`Tiling`]), or
+    /// `None` (the default) to always process the region in one pass.
+    fn tiling(&self) -> Option<Tiling> {
+        None
+    }
+
+    /// How the processing size is derived from `internal_resolution` (see
+    /// [`ScalingPolicy`]). Defaults to [`ScalingPolicy::Stretch`].
+    fn scaling_policy(&self) -> ScalingPolicy {
+        ScalingPolicy::Stretch
+    }
+
+    /// Color management for the input and output textures. With
+    /// [`ColorSpace::Srgb`] the host's sRGB-encoded frame is decoded so
+    /// `gpu_draw` reads and writes linear values, and the result is encoded
+    /// again on the way out. Defaults to [`ColorSpace::Unmanaged`].
+    ///
+    /// On macOS this uses `BGRA8Unorm_sRGB` bridge textures; compute writes
+    /// to sRGB textures require an Apple-silicon GPU, render pipelines work
+    /// everywhere.
+    fn color_space(&self) -> ColorSpace {
+        ColorSpace::Unmanaged
+    }
+
+    /// Pixel format of the bridge textures, e.g.
+    /// [`TextureFormat::Rgba16Float`] for HDR or high-precision processing.
+    /// `None` (the default) uses the bridge's preferred format: `Rgba8` on
+    /// macOS, `Rgba16Float` on Windows.
+    ///
+    /// On macOS only `Rgba8` can be combined with [`ColorSpace::Srgb`].
+    fn texture_format(&self) -> Option<TextureFormat> {
+        None
+    }
+
+    /// Color space tag and cache mode of the IOSurfaces behind the macOS
+    /// bridge textures (see [`SurfaceOptions`]). Changing them recreates the
+    /// textures. macOS only; defaults to `SurfaceOptions::default()`, which
+    /// tags surfaces sRGB with [`ColorSpace::Srgb`] and leaves them untagged
+    /// otherwise.
+    fn surface_options(&self) -> SurfaceOptions {
+        SurfaceOptions::default()
+    }
+
+    /// Alpha convention for the input and output textures (see
+    /// [`AlphaMode`]). Defaults to [`AlphaMode::Passthrough`]. Return a
+    /// host-dependent value if the plugin knows which hosts premultiply.
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Passthrough
+    }
+
+    /// Amount of the processed output kept, 0–1; the rest is the dry input,
+    /// blended in by the framework after `gpu_draw`. Return the value of a
+    /// [`mix_param`](crate::color::mix_param) to give the effect an A/B
+    /// ramp. Defaults to 1, which skips the blend.
+    fn mix(&self) -> f32 {
+        1.0
+    }
+
+    /// What to output when the host provides no input texture (see
+    /// [`MissingInput`]). Read every frame; defaults to
+    /// [`MissingInput::Passthrough`].
+    fn missing_input(&self) -> MissingInput {
+        MissingInput::Passthrough
+    }
+
+    /// Longest gap between two host calls over which the previous frame's
+    /// result is still presented; after a longer gap the frame is drawn
+    /// synchronously. Defaults to [`DEFAULT_STALENESS_WINDOW`] (100 ms).
+    ///
+    /// Compositions rendering below 10 fps exceed the default every frame
+    /// and lose pipelining. Raise the window for them, or return `None` and
+    /// call [`discard_in_flight`](crate::discard_in_flight) when the host
+    /// disconnects the plugin instead.
+    fn staleness_window(&self) -> Option<Duration> {
+        Some(DEFAULT_STALENESS_WINDOW)
+    }
+
+    /// Present each frame's own result rather than the previous frame's, so
+    /// output frame N always comes from input frame N. For hosts exporting
+    /// offline, where the one-frame pipelining latency would shift the
+    /// render against the timeline. Each frame waits for its GPU work
+    /// without the bridge's sync timeout. Read every frame; defaults to
+    /// `false`.
+    fn offline_rendering(&self) -> bool {
+        false
+    }
+
+    /// How to handle the host drawing this instance on another thread than
+    /// before (see [`ThreadMigration`]). Defaults to
+    /// [`ThreadMigration::Rebuild`].
+    fn thread_migration(&self) -> ThreadMigration {
+        ThreadMigration::Rebuild
+    }
+
+    /// Share the DX11 bridge textures through DXGI keyed mutexes
+    /// (`SHARED_NTHANDLE | SHARED_KEYEDMUTEX`), held by D3D11 from the input
+    /// blit until the dispatch is submitted. Enable this if output flickers
+    /// on drivers where the WGL interop lock alone does not serialize GL and
+    /// D3D11 access. Windows only; defaults to `false`.
+    fn dx11_keyed_mutex(&self) -> bool {
+        false
+    }
+
+    /// Record each frame's DX11 passes into a deferred context and execute
+    /// them as one command list (see
+    /// [`GpuContext::with_deferred_recording`]), reducing immediate-context
+    /// overhead for plugins with many passes. Read once, when the GPU context
+    /// is created. Windows only; defaults to `false`.
+    fn dx11_deferred_context(&self) -> bool {
+        false
+    }
+
+    /// Adaptive resolution controller. Return `Some` to let the framework
+    /// scale the processing resolution from measured frame times (see
+    /// [`AdaptiveResolution`]), or `None` (the default) to use the host's
+    /// `internal_resolution` as-is.
+    fn adaptive_resolution(&mut self) -> Option<&mut AdaptiveResolution> {
+        None
+    }
+
+    /// The plugin's parameter values, if it keeps them in a [`ParamStore`].
+    /// The framework clears the store's change flags after each frame
+    /// `gpu_draw` completed, so [`ParamStore::changed`] and
+    /// [`ParamStore::any_changed`] in `gpu_draw` report what changed since
+    /// the last drawn frame. Frames passed through keep their changes for
+    /// the next one. Defaults to `None`.
+    fn param_store(&mut self) -> Option<&mut ParamStore> {
+        None
+    }
+
+    /// Frame dump trigger. Return `Some` to let the framework write the
+    /// next frame's textures to PNG files when the dump is requested (see
+    /// [`FrameDump`](crate::FrameDump)). Defaults to `None`.
+    #[cfg(feature = "frame-dump")]
+    fn frame_dump(&mut self) -> Option<&mut crate::FrameDump> {
+        None
+    }
+
     /// Called each frame to perform GPU rendering.
     ///
     /// The [`DrawInput`] provides pre-extracted input/output textures for the
@@ -140,8 +663,10 @@ pub trait GpuPlugin: Send + Sync + 'static {
     /// 2. Perform GPU compute/render work
     /// 3. Write results to the output texture
     ///
-    /// For advanced operations (e.g. `store_command_buffer` on Metal), use
-    /// `input.metal_bridge()` or `input.dx11_bridge()`.
+    /// For work of its own, `input.metal()` or `input.dx11()` bundles the
+    /// device, queue or context and textures. For bridge operations (e.g.
+    /// `store_command_buffer` on Metal), use `input.metal_bridge()` or
+    /// `input.dx11_bridge()`.
     ///
     /// The `frame` counter is monotonically increasing and can be used for
     /// animation or temporal effects.
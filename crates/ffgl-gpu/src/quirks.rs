@@ -0,0 +1,112 @@
+//! Known host behaviors that change the draw loop's defaults.
+//!
+//! The host is identified by its executable's file name and the renderer
+//! string of its GL context, on the first frame drawn in the process. The
+//! matching [`HostQuirks`] are logged once at info level and applied to every
+//! instance. [`synchronous`](HostQuirks::synchronous) turns pipelining off,
+//! as [`GpuPlugin::offline_rendering`](crate::GpuPlugin::offline_rendering)
+//! does, for hosts that expect each frame's own output.
+//!
+//! Plugins can read the detected quirks through [`host_quirks`].
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::DeviceInfo;
+
+/// Adjustments for the host the plugin is loaded into.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HostQuirks {
+    /// The host's executable name without extension, e.g. `Arena`.
+    pub host: String,
+    /// Renderer string of the host's GL context.
+    pub gl_renderer: String,
+    /// Present each frame's own result instead of pipelining.
+    pub synchronous: bool,
+}
+
+/// One entry of the quirks table. Fields left `None` match anything.
+struct Rule {
+    /// Executable name without extension, compared case-insensitively.
+    host: Option<&'static str>,
+    /// Substring of the GL renderer, compared case-insensitively.
+    renderer: Option<&'static str>,
+    apply: fn(&mut HostQuirks),
+}
+
+const RULES: &[Rule] = &[
+    // Magic Music Visuals renders its scene graph on demand and presents
+    // the output of the call that produced it.
+    Rule {
+        host: Some("magic"),
+        renderer: None,
+        apply: |q| q.synchronous = true,
+    },
+];
+
+impl Rule {
+    fn matches(&self, host: &str, renderer: &str) -> bool {
+        self.host.is_none_or(|h| host.eq_ignore_ascii_case(h))
+            && self
+                .renderer
+                .is_none_or(|r| renderer.to_lowercase().contains(r))
+    }
+}
+
+static QUIRKS: OnceLock<HostQuirks> = OnceLock::new();
+
+/// Quirks of the host, once the first frame has been drawn.
+pub fn host_quirks() -> Option<&'static HostQuirks> {
+    QUIRKS.get()
+}
+
+/// Detect the host's quirks on first use and return them.
+///
+/// # Safety
+///
+/// A GL context must be current.
+// Only the draw loops (macOS and Windows) detect quirks.
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+pub(crate) unsafe fn detect() -> &'static HostQuirks {
+    QUIRKS.get_or_init(|| {
+        let host = std::env::current_exe()
+            .ok()
+            .as_deref()
+            .and_then(executable_name)
+            .unwrap_or_default();
+        let gl_renderer = DeviceInfo::current_gl().renderer;
+        let quirks = quirks_for(host, gl_renderer);
+        tracing::info!(
+            host = quirks.host,
+            synchronous = quirks.synchronous,
+            "Detected host quirks"
+        );
+        quirks
+    })
+}
+
+fn quirks_for(host: String, gl_renderer: String) -> HostQuirks {
+    let mut quirks = HostQuirks {
+        host,
+        gl_renderer,
+        ..Default::default()
+    };
+    for rule in RULES {
+        if rule.matches(&quirks.host, &quirks.gl_renderer) {
+            (rule.apply)(&mut quirks);
+        }
+    }
+    quirks
+}
+
+/// The application's name: the bundle name for executables inside a macOS
+/// `.app`, the file stem otherwise.
+fn executable_name(path: &Path) -> Option<String> {
+    let bundle = path
+        .ancestors()
+        .find(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("app")));
+    bundle
+        .unwrap_or(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+}
@@ -0,0 +1,82 @@
+//! Hash-based random numbers for shaders.
+//!
+//! [`FFGLData::frame_seed`](ffgl_core::FFGLData::frame_seed) gives each
+//! frame of each instance its own seed. Pass it to the shader in the
+//! plugin's uniforms and combine it with the pixel coordinate:
+//!
+//! ```text
+//! #include "ffgl_gpu_random.h"   // "ffgl_gpu_random.hlsli" in HLSL
+//!
+//! float noise = ffgl_random(gid, uniforms.seed);
+//! ```
+//!
+//! The [`build_support`](crate::build_support) compile functions write both
+//! headers to `OUT_DIR` and add it to the include path. The functions are
+//! the same on both backends:
+//!
+//! - `uint ffgl_hash(uint v)`: the PCG hash from Jarzynski and Olano,
+//!   "Hash Functions for GPU Rendering" (2020).
+//! - `float ffgl_random(uint2 pixel, uint seed)`: a value in `[0, 1)`, from
+//!   the top 24 bits of the hash so it never rounds up to 1.
+//! - `float4 ffgl_random4(uint2 pixel, uint seed)`: four independent
+//!   values in `[0, 1)`.
+//!
+//! Seeds come from the OS's random source when an instance is created, so
+//! stacked instances don't produce the same noise and reloading a
+//! composition doesn't replay it.
+
+/// File name of the Metal header.
+pub const MSL_HEADER: &str = "ffgl_gpu_random.h";
+
+/// File name of the HLSL header.
+pub const HLSL_HEADER: &str = "ffgl_gpu_random.hlsli";
+
+/// Metal source of the helpers, for shaders compiled at runtime.
+pub const RANDOM_MSL: &str = r#"
+#pragma once
+#include <metal_stdlib>
+
+static inline uint ffgl_hash(uint v) {
+    uint state = v * 747796405u + 2891336453u;
+    uint word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+static inline float ffgl_random(uint2 pixel, uint seed) {
+    return float(ffgl_hash(pixel.x ^ ffgl_hash(pixel.y ^ ffgl_hash(seed))) >> 8) * (1.0 / 16777216.0);
+}
+
+static inline float4 ffgl_random4(uint2 pixel, uint seed) {
+    uint h = ffgl_hash(pixel.x ^ ffgl_hash(pixel.y ^ ffgl_hash(seed)));
+    uint a = ffgl_hash(h);
+    uint b = ffgl_hash(a);
+    uint c = ffgl_hash(b);
+    return float4(uint4(h, a, b, c) >> 8) * (1.0 / 16777216.0);
+}
+"#;
+
+/// HLSL source of the helpers.
+pub const RANDOM_HLSL: &str = r#"
+#ifndef FFGL_GPU_RANDOM
+#define FFGL_GPU_RANDOM
+
+uint ffgl_hash(uint v) {
+    uint state = v * 747796405u + 2891336453u;
+    uint word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+float ffgl_random(uint2 pixel, uint seed) {
+    return float(ffgl_hash(pixel.x ^ ffgl_hash(pixel.y ^ ffgl_hash(seed))) >> 8) * (1.0 / 16777216.0);
+}
+
+float4 ffgl_random4(uint2 pixel, uint seed) {
+    uint h = ffgl_hash(pixel.x ^ ffgl_hash(pixel.y ^ ffgl_hash(seed)));
+    uint a = ffgl_hash(h);
+    uint b = ffgl_hash(a);
+    uint c = ffgl_hash(b);
+    return float4(uint4(h, a, b, c) >> 8) * (1.0 / 16777216.0);
+}
+
+#endif
+"#;
@@ -0,0 +1,99 @@
+//! Texture addressing helpers for shaders.
+//!
+//! The bridge textures are plain 2D textures on both backends, while their
+//! GL side is a `TEXTURE_RECTANGLE` on macOS. Kernels that address them by
+//! pixel therefore behave the same everywhere; sampled reads need
+//! normalized coordinates, and converting by hand is where off-by-half-texel
+//! and edge differences creep in. The helpers do the conversion one way on
+//! both backends:
+//!
+//! ```text
+//! #include "ffgl_gpu_sampling.h"   // "ffgl_gpu_sampling.hlsli" in HLSL
+//!
+//! float4 center = ffgl_load(input, int2(gid));
+//! float4 left = ffgl_load(input, int2(gid) - int2(1, 0));
+//! float4 warped = ffgl_sample_pixel(input, linear_clamp, float2(gid) + 0.5 + offset);
+//! ```
+//!
+//! The convention:
+//!
+//! - Coordinates are in pixels of the texture read, `(0, 0)` at the first
+//!   texel of the first row. Row 0 is the first row GL stores, the bottom
+//!   of the host's frame, on both backends.
+//! - Texel `(x, y)` has its center at `(x + 0.5, y + 0.5)`. Sampling at a
+//!   center returns the texel unfiltered; `ffgl_pixel_uv` gives the
+//!   normalized coordinate of a thread's texel center.
+//! - Loads outside the texture clamp to the nearest edge texel, as the
+//!   framework's linear/clamp sampler does for sampled reads.
+//! - Sampled reads use mip level 0, so they work in compute kernels.
+//!
+//! The [`build_support`](crate::build_support) compile functions write both
+//! headers to `OUT_DIR` next to the [`random`](crate::random) ones. The
+//! functions are the same on both backends:
+//!
+//! - `float2 ffgl_pixel_uv(uint2 pixel, uint2 size)`: normalized coordinate
+//!   of the center of `pixel` in a texture of `size`.
+//! - `float4 ffgl_load(texture, int2 pixel)`: the texel at `pixel`, clamped
+//!   to the edge.
+//! - `float4 ffgl_sample_pixel(texture, sampler, float2 position)`: a
+//!   filtered read at a position in pixels.
+
+/// File name of the Metal header.
+pub const MSL_HEADER: &str = "ffgl_gpu_sampling.h";
+
+/// File name of the HLSL header.
+pub const HLSL_HEADER: &str = "ffgl_gpu_sampling.hlsli";
+
+/// Metal source of the helpers, for shaders compiled at runtime.
+pub const SAMPLING_MSL: &str = r#"
+#pragma once
+#include <metal_stdlib>
+
+static inline float2 ffgl_pixel_uv(uint2 pixel, uint2 size) {
+    return (float2(pixel) + 0.5) / float2(size);
+}
+
+static inline float4 ffgl_load(metal::texture2d<float, metal::access::read> t, int2 pixel) {
+    int2 last = int2(t.get_width(), t.get_height()) - 1;
+    return t.read(uint2(metal::clamp(pixel, int2(0), last)));
+}
+
+static inline float4 ffgl_load(metal::texture2d<float, metal::access::sample> t, int2 pixel) {
+    int2 last = int2(t.get_width(), t.get_height()) - 1;
+    return t.read(uint2(metal::clamp(pixel, int2(0), last)));
+}
+
+static inline float4 ffgl_sample_pixel(
+    metal::texture2d<float, metal::access::sample> t,
+    metal::sampler s,
+    float2 position
+) {
+    float2 size = float2(t.get_width(), t.get_height());
+    return t.sample(s, position / size, metal::level(0));
+}
+"#;
+
+/// HLSL source of the helpers.
+pub const SAMPLING_HLSL: &str = r#"
+#ifndef FFGL_GPU_SAMPLING
+#define FFGL_GPU_SAMPLING
+
+float2 ffgl_pixel_uv(uint2 pixel, uint2 size) {
+    return (float2(pixel) + 0.5) / float2(size);
+}
+
+float4 ffgl_load(Texture2D<float4> t, int2 pixel) {
+    uint width, height;
+    t.GetDimensions(width, height);
+    int2 last = int2(width, height) - 1;
+    return t.Load(int3(clamp(pixel, int2(0, 0), last), 0));
+}
+
+float4 ffgl_sample_pixel(Texture2D<float4> t, SamplerState s, float2 position) {
+    uint width, height;
+    t.GetDimensions(width, height);
+    return t.SampleLevel(s, position / float2(width, height), 0);
+}
+
+#endif
+"#;
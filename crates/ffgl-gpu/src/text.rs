@@ -0,0 +1,856 @@
+//! Glyph-atlas text rendering, behind the `text` feature.
+//!
+//! [`TextRenderer`] draws lines of text for tickers, lower thirds and other
+//! overlays without pulling in a GUI stack. Glyphs are rasterized on the CPU
+//! with `fontdue` the first time they are used at a given size, packed into
+//! a 1024x1024 atlas texture, and drawn as one instanced quad each with the
+//! renderer's own pipeline, blended over the target.
+//!
+//! Text is [queued](TextRenderer::queue) in target pixels, top-left origin,
+//! and drawn by the next `draw`, which empties the queue. The font usually
+//! comes from an FFGL file parameter: declare it with
+//! [`ParameterTypes::File`](ffgl_core::parameters::ParameterTypes::File) and
+//! pass the path from
+//! [`set_text_param`](ffgl_core::handler::simplified::SimpleFFGLInstance::set_text_param)
+//! to [`TextRenderer::load_font_file`].
+//!
+//! `draw` blends premultiplied text onto what the target already holds, so
+//! effects copy or render their input into the output first; sources can
+//! pass a clear color instead.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+
+/// Width and height of the glyph atlas, in pixels.
+const ATLAS_SIZE: u32 = 1024;
+
+/// Transparent pixels kept around each glyph so linear sampling doesn't
+/// bleed into its neighbours.
+const PADDING: u32 = 1;
+
+/// A font loaded from TrueType or OpenType data.
+pub struct Font {
+    inner: fontdue::Font,
+}
+
+impl Font {
+    /// Parse a `.ttf` / `.otf` file held in memory.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let inner = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())
+            .map_err(|e| anyhow::anyhow!("Failed to parse font: {e}"))?;
+        Ok(Self { inner })
+    }
+
+    /// Read and parse a `.ttf` / `.otf` file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read font {}: {e}", path.display()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Horizontal alignment of queued text relative to its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    /// Lines start at the position.
+    #[default]
+    Left,
+    /// Lines are centred on the position.
+    Center,
+    /// Lines end at the position.
+    Right,
+}
+
+impl TextAlign {
+    /// Fraction of a line's width left of the position.
+    fn offset(self) -> f32 {
+        match self {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => 0.5,
+            TextAlign::Right => 1.0,
+        }
+    }
+}
+
+/// How queued text looks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    /// Font size in pixels (the em height).
+    pub size: f32,
+    /// Straight-alpha RGBA, 0–1.
+    pub color: [f32; 4],
+    /// Alignment of each line relative to the queued position.
+    pub align: TextAlign,
+}
+
+impl Default for TextStyle {
+    /// White, 48 pixels, left-aligned.
+    fn default() -> Self {
+        Self {
+            size: 48.0,
+            color: [1.0; 4],
+            align: TextAlign::Left,
+        }
+    }
+}
+
+/// Matches `Glyph` in the shaders: one quad in target pixels, its atlas
+/// coordinates, and its color.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct GlyphQuad {
+    rect: [f32; 4],
+    uv: [f32; 4],
+    color: [f32; 4],
+}
+
+/// Matches `TextParams` in the shaders.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TextParams {
+    target_size: [f32; 2],
+    _pad: [f32; 2],
+}
+
+/// Where a rasterized glyph sits in the atlas, and its offset from the pen
+/// position.
+#[derive(Clone, Copy)]
+struct CachedGlyph {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    xmin: i32,
+    ymin: i32,
+}
+
+/// CPU copy of the atlas and a shelf packer filling it row by row.
+struct Atlas {
+    pixels: Vec<u8>,
+    /// Keyed by glyph index and size in quarter pixels. `None` for glyphs
+    /// with no pixels, such as spaces.
+    glyphs: HashMap<(u16, u32), Option<CachedGlyph>>,
+    cursor: (u32, u32),
+    row_height: u32,
+    /// Whether `pixels` changed since the last upload.
+    dirty: bool,
+}
+
+impl Atlas {
+    fn new() -> Self {
+        Self {
+            pixels: vec![0; (ATLAS_SIZE * ATLAS_SIZE * 4) as usize],
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            row_height: 0,
+            dirty: true,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pixels.fill(0);
+        self.glyphs.clear();
+        self.cursor = (0, 0);
+        self.row_height = 0;
+        self.dirty = true;
+    }
+
+    /// The atlas entry for glyph `index` at `size_key` quarter pixels,
+    /// rasterizing it on first use. `Err` when the atlas is full.
+    fn glyph(
+        &mut self,
+        font: &fontdue::Font,
+        index: u16,
+        size_key: u32,
+    ) -> std::result::Result<Option<CachedGlyph>, ()> {
+        if let Some(glyph) = self.glyphs.get(&(index, size_key)) {
+            return Ok(*glyph);
+        }
+
+        let (metrics, coverage) = font.rasterize_indexed(index, size_key as f32 / 4.0);
+        let (width, height) = (metrics.width as u32, metrics.height as u32);
+        if width == 0 || height == 0 {
+            self.glyphs.insert((index, size_key), None);
+            return Ok(None);
+        }
+
+        let (cell_w, cell_h) = (width + 2 * PADDING, height + 2 * PADDING);
+        if self.cursor.0 + cell_w > ATLAS_SIZE {
+            self.cursor = (0, self.cursor.1 + self.row_height);
+            self.row_height = 0;
+        }
+        if cell_w > ATLAS_SIZE || self.cursor.1 + cell_h > ATLAS_SIZE {
+            return Err(());
+        }
+
+        let (x, y) = (self.cursor.0 + PADDING, self.cursor.1 + PADDING);
+        for row in 0..height {
+            for col in 0..width {
+                let alpha = coverage[(row * width + col) as usize];
+                let offset = (((y + row) * ATLAS_SIZE + x + col) * 4) as usize;
+                self.pixels[offset..offset + 4].copy_from_slice(&[255, 255, 255, alpha]);
+            }
+        }
+        self.cursor.0 += cell_w;
+        self.row_height = self.row_height.max(cell_h);
+        self.dirty = true;
+
+        let glyph = CachedGlyph {
+            x,
+            y,
+            width,
+            height,
+            xmin: metrics.xmin,
+            ymin: metrics.ymin,
+        };
+        self.glyphs.insert((index, size_key), Some(glyph));
+        Ok(Some(glyph))
+    }
+}
+
+/// Text waiting for the next draw.
+struct QueuedText {
+    text: String,
+    position: [f32; 2],
+    style: TextStyle,
+}
+
+/// Text rendering state for one plugin instance: the font, its glyph atlas
+/// and the text queued for the next draw.
+pub struct TextRenderer {
+    font: Option<Font>,
+    atlas: Atlas,
+    queue: Vec<QueuedText>,
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    texture: Option<crate::GpuTexture>,
+    /// Glyph quads for the draw, grown as needed.
+    #[cfg(target_os = "windows")]
+    quads: Option<crate::GpuBuffer>,
+}
+
+impl Default for TextRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextRenderer {
+    /// A renderer with no font; nothing is drawn until one is set.
+    pub fn new() -> Self {
+        Self {
+            font: None,
+            atlas: Atlas::new(),
+            queue: Vec::new(),
+            #[cfg(any(target_os = "macos", target_os = "windows"))]
+            texture: None,
+            #[cfg(target_os = "windows")]
+            quads: None,
+        }
+    }
+
+    /// Replace the font, dropping the glyphs rasterized from the old one.
+    pub fn set_font(&mut self, font: Option<Font>) {
+        self.font = font;
+        self.atlas.clear();
+    }
+
+    /// Load the font at `path`, typically the value of a file parameter. An
+    /// empty path removes the font. On error the current font is kept.
+    pub fn load_font_file(&mut self, path: &str) -> Result<()> {
+        let font = if path.is_empty() {
+            None
+        } else {
+            Some(Font::from_file(path)?)
+        };
+        self.set_font(font);
+        Ok(())
+    }
+
+    /// The current font, if any.
+    pub fn font(&self) -> Option<&Font> {
+        self.font.as_ref()
+    }
+
+    /// Width of the widest line of `text` and the height of all its lines at
+    /// `size` pixels. Zero without a font.
+    pub fn measure(&self, text: &str, size: f32) -> [f32; 2] {
+        let Some(font) = &self.font else {
+            return [0.0, 0.0];
+        };
+        let (_, line_height) = line_metrics(&font.inner, size);
+        let mut width: f32 = 0.0;
+        let mut lines = 0;
+        for line in text.lines() {
+            width = width.max(line_width(&font.inner, line, size));
+            lines += 1;
+        }
+        [width, lines as f32 * line_height]
+    }
+
+    /// Queue `text` for the next draw. `position` is the top of the first
+    /// line, at its left edge, centre or right edge depending on
+    /// `style.align`; `\n` starts a new line.
+    pub fn queue(&mut self, text: &str, position: [f32; 2], style: &TextStyle) {
+        self.queue.push(QueuedText {
+            text: text.to_owned(),
+            position,
+            style: *style,
+        });
+    }
+
+    /// Lay out the queued text into quads, rasterizing new glyphs. Starts
+    /// over with an empty atlas once if it fills up; glyphs that still don't
+    /// fit are skipped.
+    #[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+    fn layout(&mut self) -> Vec<GlyphQuad> {
+        let queue = std::mem::take(&mut self.queue);
+        let Some(font) = &self.font else {
+            return Vec::new();
+        };
+        match layout_queue(&font.inner, &mut self.atlas, &queue) {
+            Ok(quads) => quads,
+            Err(_) => {
+                self.atlas.clear();
+                layout_queue(&font.inner, &mut self.atlas, &queue).unwrap_or_else(|quads| {
+                    gpu_interop::warn_limited!(
+                        "Text needs more glyphs than fit in the {ATLAS_SIZE}px atlas; some are skipped"
+                    );
+                    quads
+                })
+            }
+        }
+    }
+
+    /// Upload the atlas if glyphs were added since the last draw.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn upload_atlas(&mut self, ctx: &crate::GpuContext) -> Result<()> {
+        if self.texture.is_none() {
+            self.texture = Some(ctx.create_texture(ATLAS_SIZE, ATLAS_SIZE)?);
+            self.atlas.dirty = true;
+        }
+        if self.atlas.dirty {
+            ctx.update_texture_from_rgba(self.texture.as_ref().unwrap(), &self.atlas.pixels)?;
+            self.atlas.dirty = false;
+        }
+        Ok(())
+    }
+}
+
+/// Advance width of `line` at `size` pixels, including kerning.
+fn line_width(font: &fontdue::Font, line: &str, size: f32) -> f32 {
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in line.chars() {
+        let index = font.lookup_glyph_index(c);
+        if let Some(kern) = previous.and_then(|p| font.horizontal_kern_indexed(p, index, size)) {
+            width += kern;
+        }
+        width += font.metrics_indexed(index, size).advance_width;
+        previous = Some(index);
+    }
+    width
+}
+
+/// Quads for all of `queue`. `Err` holds the quads laid out before the atlas
+/// filled up.
+fn layout_queue(
+    font: &fontdue::Font,
+    atlas: &mut Atlas,
+    queue: &[QueuedText],
+) -> std::result::Result<Vec<GlyphQuad>, Vec<GlyphQuad>> {
+    let scale = 1.0 / ATLAS_SIZE as f32;
+    let mut quads = Vec::new();
+    for item in queue {
+        let size_key = (item.style.size.max(1.0) * 4.0).round() as u32;
+        let size = size_key as f32 / 4.0;
+        let (ascent, line_height) = line_metrics(font, size);
+        let [x, y] = item.position;
+
+        for (line_index, line) in item.text.lines().enumerate() {
+            let mut pen = x - item.style.align.offset() * line_width(font, line, size);
+            let baseline = (y + ascent + line_index as f32 * line_height).round();
+            let mut previous = None;
+            for c in line.chars() {
+                let index = font.lookup_glyph_index(c);
+                if let Some(kern) =
+                    previous.and_then(|p| font.horizontal_kern_indexed(p, index, size))
+                {
+                    pen += kern;
+                }
+                previous = Some(index);
+
+                let glyph = match atlas.glyph(font, index, size_key) {
+                    Ok(glyph) => glyph,
+                    Err(()) => return Err(quads),
+                };
+                if let Some(g) = glyph {
+                    let left = pen.round() + g.xmin as f32;
+                    let top = baseline - (g.ymin + g.height as i32) as f32;
+                    quads.push(GlyphQuad {
+                        rect: [left, top, left + g.width as f32, top + g.height as f32],
+                        uv: [
+                            g.x as f32 * scale,
+                            g.y as f32 * scale,
+                            (g.x + g.width) as f32 * scale,
+                            (g.y + g.height) as f32 * scale,
+                        ],
+                        color: item.style.color,
+                    });
+                }
+                pen += font.metrics_indexed(index, size).advance_width;
+            }
+        }
+    }
+    Ok(quads)
+}
+
+/// Ascent and distance between baselines at `size` pixels.
+fn line_metrics(font: &fontdue::Font, size: f32) -> (f32, f32) {
+    match font.horizontal_line_metrics(size) {
+        Some(m) => (m.ascent, m.new_line_size),
+        None => (size, size * 1.2),
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod metal_impl {
+    use super::{TextParams, TextRenderer};
+    use crate::buffer::MAX_INLINE_BYTES;
+    use crate::context::GpuContext;
+    use crate::dispatch::CommandBuffer;
+    use anyhow::Result;
+    use objc2::rc::Retained;
+    use objc2::runtime::ProtocolObject;
+    use objc2_foundation::NSString;
+    use objc2_metal::*;
+
+    const TEXT_MSL: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct Glyph {
+    float4 rect;
+    float4 uv;
+    float4 color;
+};
+
+struct TextParams {
+    float2 target_size;
+};
+
+struct TextVertexOut {
+    float4 position [[position]];
+    float2 uv;
+    float4 color;
+};
+
+vertex TextVertexOut ffgl_gpu_text_vertex(uint vid [[vertex_id]],
+                                          uint iid [[instance_id]],
+                                          constant Glyph* glyphs [[buffer(0)]],
+                                          constant TextParams& p [[buffer(1)]]) {
+    Glyph g = glyphs[iid];
+    float2 corner = float2(vid & 1, vid >> 1);
+    float2 pixel = mix(g.rect.xy, g.rect.zw, corner);
+    TextVertexOut out;
+    out.position = float4(pixel / p.target_size * float2(2.0, -2.0) + float2(-1.0, 1.0), 0.0, 1.0);
+    out.uv = mix(g.uv.xy, g.uv.zw, corner);
+    out.color = g.color;
+    return out;
+}
+
+fragment float4 ffgl_gpu_text_fragment(TextVertexOut in [[stage_in]],
+                                       texture2d<float> atlas [[texture(0)]]) {
+    constexpr sampler s(filter::linear, address::clamp_to_edge);
+    float a = in.color.a * atlas.sample(s, in.uv).a;
+    return float4(in.color.rgb * a, a);
+}
+"#;
+
+    /// The text render pipeline, compiled on first use.
+    pub(crate) struct TextPipeline {
+        state: Retained<ProtocolObject<dyn MTLRenderPipelineState>>,
+    }
+
+    impl TextPipeline {
+        fn new(device: &ProtocolObject<dyn MTLDevice>) -> Result<Self> {
+            let library = device
+                .newLibraryWithSource_options_error(&NSString::from_str(TEXT_MSL), None)
+                .map_err(|e| anyhow::anyhow!("Failed to compile text shaders: {e}"))?;
+            let vs_func = library
+                .newFunctionWithName(&NSString::from_str("ffgl_gpu_text_vertex"))
+                .ok_or_else(|| anyhow::anyhow!("Text vertex function not found"))?;
+            let fs_func = library
+                .newFunctionWithName(&NSString::from_str("ffgl_gpu_text_fragment"))
+                .ok_or_else(|| anyhow::anyhow!("Text fragment function not found"))?;
+
+            let desc = MTLRenderPipelineDescriptor::new();
+            desc.setVertexFunction(Some(&vs_func));
+            desc.setFragmentFunction(Some(&fs_func));
+            {
+                let attachment = unsafe { desc.colorAttachments().objectAtIndexedSubscript(0) };
+                attachment.setPixelFormat(MTLPixelFormat::BGRA8Unorm);
+                attachment.setBlendingEnabled(true);
+                attachment.setSourceRGBBlendFactor(MTLBlendFactor::One);
+                attachment.setDestinationRGBBlendFactor(MTLBlendFactor::OneMinusSourceAlpha);
+                attachment.setSourceAlphaBlendFactor(MTLBlendFactor::One);
+                attachment.setDestinationAlphaBlendFactor(MTLBlendFactor::OneMinusSourceAlpha);
+            }
+
+            let state = device
+                .newRenderPipelineStateWithDescriptor_error(&desc)
+                .map_err(|e| anyhow::anyhow!("Failed to create text pipeline: {e}"))?;
+            Ok(Self { state })
+        }
+    }
+
+    impl TextRenderer {
+        /// Encode the queued text onto `target` on `cb`, and empty the queue.
+        ///
+        /// With `clear`, `target` is first filled with that straight-alpha
+        /// color; otherwise the text is blended over its contents. `target`
+        /// must be `BGRA8Unorm` and renderable, such as
+        /// [`DrawInput::output`](crate::DrawInput::output).
+        pub fn draw(
+            &mut self,
+            ctx: &GpuContext,
+            cb: &CommandBuffer,
+            target: &ProtocolObject<dyn MTLTexture>,
+            clear: Option<[f32; 4]>,
+        ) -> Result<()> {
+            let quads = self.layout();
+            self.upload_atlas(ctx)?;
+            let pipeline = match ctx.text.get() {
+                Some(p) => p,
+                None => {
+                    let p = TextPipeline::new(ctx.device.device())?;
+                    ctx.text.get_or_init(|| p)
+                }
+            };
+            let params = TextParams {
+                target_size: [target.width() as f32, target.height() as f32],
+                _pad: [0.0; 2],
+            };
+
+            let render_desc = MTLRenderPassDescriptor::new();
+            {
+                let attachment =
+                    unsafe { render_desc.colorAttachments().objectAtIndexedSubscript(0) };
+                attachment.setTexture(Some(target));
+                match clear {
+                    Some([r, g, b, a]) => {
+                        attachment.setLoadAction(MTLLoadAction::Clear);
+                        attachment.setClearColor(MTLClearColor {
+                            red: (r * a) as f64,
+                            green: (g * a) as f64,
+                            blue: (b * a) as f64,
+                            alpha: a as f64,
+                        });
+                    }
+                    None => attachment.setLoadAction(MTLLoadAction::Load),
+                }
+                attachment.setStoreAction(MTLStoreAction::Store);
+            }
+
+            let encoder = cb
+                .inner
+                .renderCommandEncoderWithDescriptor(&render_desc)
+                .ok_or_else(|| anyhow::anyhow!("Failed to create text render encoder"))?;
+            if !quads.is_empty() {
+                // SAFETY: `GlyphQuad` is `repr(C)` plain f32 data.
+                let data = unsafe {
+                    std::slice::from_raw_parts(
+                        quads.as_ptr() as *const u8,
+                        std::mem::size_of_val(quads.as_slice()),
+                    )
+                };
+                encoder.setRenderPipelineState(&pipeline.state);
+                unsafe {
+                    if data.len() <= MAX_INLINE_BYTES {
+                        encoder.setVertexBytes_length_atIndex(
+                            std::ptr::NonNull::new_unchecked(data.as_ptr() as *mut _),
+                            data.len(),
+                            0,
+                        );
+                    } else {
+                        let buffer = ctx
+                            .staging
+                            .stage(ctx.device.device(), &cb.inner, data)
+                            .ok_or_else(|| {
+                                anyhow::anyhow!("Failed to stage {} glyph quads", quads.len())
+                            })?;
+                        encoder.setVertexBuffer_offset_atIndex(Some(&buffer), 0, 0);
+                    }
+                    encoder.setVertexBytes_length_atIndex(
+                        std::ptr::NonNull::from(&params).cast(),
+                        std::mem::size_of::<TextParams>(),
+                        1,
+                    );
+                    encoder.setFragmentTexture_atIndex(
+                        Some(self.texture.as_ref().unwrap().metal_texture()),
+                        0,
+                    );
+                    encoder.drawPrimitives_vertexStart_vertexCount_instanceCount(
+                        MTLPrimitiveType::TriangleStrip,
+                        0,
+                        4,
+                        quads.len(),
+                    );
+                }
+            }
+            encoder.endEncoding();
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) use dx11_impl::TextPipeline;
+#[cfg(target_os = "macos")]
+pub(crate) use metal_impl::TextPipeline;
+
+#[cfg(target_os = "windows")]
+mod dx11_impl {
+    use super::{GlyphQuad, TextParams, TextRenderer};
+    use crate::blit::{blob_bytes, compile_hlsl};
+    use crate::context::GpuContext;
+    use anyhow::Result;
+    use windows::Win32::Graphics::Direct3D11::*;
+
+    const TEXT_HLSL: &str = r#"
+struct Glyph {
+    float4 rect;
+    float4 uv;
+    float4 color;
+};
+
+StructuredBuffer<Glyph> glyphs : register(t1);
+Texture2D<float4> atlas : register(t0);
+SamplerState samp : register(s0);
+
+cbuffer TextParams : register(b0) {
+    float2 target_size;
+    float2 _pad;
+};
+
+struct TextVertexOut {
+    float4 position : SV_Position;
+    float2 uv : TEXCOORD0;
+    float4 color : COLOR0;
+};
+
+TextVertexOut vs_main(uint vid : SV_VertexID, uint iid : SV_InstanceID) {
+    Glyph g = glyphs[iid];
+    float2 corner = float2(vid & 1, vid >> 1);
+    float2 pixel = lerp(g.rect.xy, g.rect.zw, corner);
+    TextVertexOut o;
+    o.position = float4(pixel / target_size * float2(2.0, -2.0) + float2(-1.0, 1.0), 0.0, 1.0);
+    o.uv = lerp(g.uv.xy, g.uv.zw, corner);
+    o.color = g.color;
+    return o;
+}
+
+float4 ps_main(TextVertexOut i) : SV_Target {
+    float a = i.color.a * atlas.Sample(samp, i.uv).a;
+    return float4(i.color.rgb * a, a);
+}
+"#;
+
+    /// The text shaders and fixed-function state, created on first use.
+    pub(crate) struct TextPipeline {
+        vs: ID3D11VertexShader,
+        ps: ID3D11PixelShader,
+        sampler: ID3D11SamplerState,
+        blend: ID3D11BlendState,
+        params: ID3D11Buffer,
+    }
+
+    impl TextPipeline {
+        fn new(device: &ID3D11Device) -> Result<Self> {
+            let vs_blob = compile_hlsl(TEXT_HLSL, b"vs_main\0", b"vs_5_0\0")?;
+            let ps_blob = compile_hlsl(TEXT_HLSL, b"ps_main\0", b"ps_5_0\0")?;
+
+            let mut vs = None;
+            unsafe {
+                device.CreateVertexShader(blob_bytes(&vs_blob), None, Some(&mut vs as *mut _))
+            }
+            .map_err(|e| anyhow::anyhow!("Failed to create text vertex shader: {e}"))?;
+            let vs = vs.ok_or_else(|| anyhow::anyhow!("D3D11 CreateVertexShader returned null"))?;
+
+            let mut ps = None;
+            unsafe {
+                device.CreatePixelShader(blob_bytes(&ps_blob), None, Some(&mut ps as *mut _))
+            }
+            .map_err(|e| anyhow::anyhow!("Failed to create text pixel shader: {e}"))?;
+            let ps = ps.ok_or_else(|| anyhow::anyhow!("D3D11 CreatePixelShader returned null"))?;
+
+            let sampler_desc = D3D11_SAMPLER_DESC {
+                Filter: D3D11_FILTER_MIN_MAG_MIP_LINEAR,
+                AddressU: D3D11_TEXTURE_ADDRESS_CLAMP,
+                AddressV: D3D11_TEXTURE_ADDRESS_CLAMP,
+                AddressW: D3D11_TEXTURE_ADDRESS_CLAMP,
+                MaxAnisotropy: 1,
+                ComparisonFunc: D3D11_COMPARISON_NEVER,
+                MinLOD: 0.0,
+                MaxLOD: f32::MAX,
+                ..Default::default()
+            };
+            let mut sampler = None;
+            unsafe { device.CreateSamplerState(&sampler_desc, Some(&mut sampler as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create text sampler: {e}"))?;
+            let sampler =
+                sampler.ok_or_else(|| anyhow::anyhow!("D3D11 CreateSamplerState returned null"))?;
+
+            let mut blend_desc = D3D11_BLEND_DESC::default();
+            blend_desc.RenderTarget[0] = D3D11_RENDER_TARGET_BLEND_DESC {
+                BlendEnable: true.into(),
+                SrcBlend: D3D11_BLEND_ONE,
+                DestBlend: D3D11_BLEND_INV_SRC_ALPHA,
+                BlendOp: D3D11_BLEND_OP_ADD,
+                SrcBlendAlpha: D3D11_BLEND_ONE,
+                DestBlendAlpha: D3D11_BLEND_INV_SRC_ALPHA,
+                BlendOpAlpha: D3D11_BLEND_OP_ADD,
+                RenderTargetWriteMask: D3D11_COLOR_WRITE_ENABLE_ALL.0 as u8,
+            };
+            let mut blend = None;
+            unsafe { device.CreateBlendState(&blend_desc, Some(&mut blend as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create text blend state: {e}"))?;
+            let blend =
+                blend.ok_or_else(|| anyhow::anyhow!("D3D11 CreateBlendState returned null"))?;
+
+            let params_desc = D3D11_BUFFER_DESC {
+                ByteWidth: std::mem::size_of::<TextParams>() as u32,
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: D3D11_BIND_CONSTANT_BUFFER.0 as u32,
+                ..Default::default()
+            };
+            let mut params = None;
+            unsafe { device.CreateBuffer(&params_desc, None, Some(&mut params as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create text params buffer: {e}"))?;
+            let params =
+                params.ok_or_else(|| anyhow::anyhow!("D3D11 CreateBuffer returned null"))?;
+
+            Ok(Self {
+                vs,
+                ps,
+                sampler,
+                blend,
+                params,
+            })
+        }
+    }
+
+    impl TextRenderer {
+        /// Draw the queued text onto `target` on the
+        /// [`command_context`](GpuContext::command_context), and empty the
+        /// queue.
+        ///
+        /// With `clear`, `target` is first filled with that straight-alpha
+        /// color; otherwise the text is blended over its contents. `target`
+        /// must be bindable as a render target, such as
+        /// [`DrawInput::output_texture`](crate::DrawInput::output_texture).
+        pub fn draw(
+            &mut self,
+            ctx: &GpuContext,
+            target: &ID3D11Texture2D,
+            clear: Option<[f32; 4]>,
+        ) -> Result<()> {
+            let quads = self.layout();
+            self.upload_atlas(ctx)?;
+            let pipeline = match ctx.text.get() {
+                Some(p) => p,
+                None => {
+                    let p = TextPipeline::new(ctx.device.device())?;
+                    ctx.text.get_or_init(|| p)
+                }
+            };
+
+            let device = ctx.device.device();
+            let mut desc = D3D11_TEXTURE2D_DESC::default();
+            unsafe { target.GetDesc(&mut desc) };
+            let mut rtv = None;
+            unsafe { device.CreateRenderTargetView(target, None, Some(&mut rtv as *mut _)) }
+                .map_err(|e| anyhow::anyhow!("Failed to create RTV for text target: {e}"))?;
+            let rtv = rtv.ok_or_else(|| anyhow::anyhow!("D3D11 CreateRTV returned null"))?;
+
+            let dc = ctx.command_context();
+            if let Some([r, g, b, a]) = clear {
+                unsafe { dc.ClearRenderTargetView(&rtv, &[r * a, g * a, b * a, a]) };
+            }
+            if quads.is_empty() {
+                return Ok(());
+            }
+
+            if !self
+                .quads
+                .as_ref()
+                .is_some_and(|b| b.size() >= std::mem::size_of_val(quads.as_slice()))
+            {
+                let capacity = quads.len().next_power_of_two().max(64);
+                self.quads = Some(ctx.create_buffer(capacity, std::mem::size_of::<GlyphQuad>())?);
+            }
+            let buffer = self.quads.as_ref().unwrap();
+            // SAFETY: `GlyphQuad` is `repr(C)` plain f32 data.
+            let data = unsafe {
+                std::slice::from_raw_parts(
+                    quads.as_ptr() as *const u8,
+                    std::mem::size_of_val(quads.as_slice()),
+                )
+            };
+            ctx.update_buffer(buffer, data);
+
+            let params = TextParams {
+                target_size: [desc.Width as f32, desc.Height as f32],
+                _pad: [0.0; 2],
+            };
+            unsafe {
+                dc.UpdateSubresource(
+                    &pipeline.params,
+                    0,
+                    None,
+                    &params as *const TextParams as *const _,
+                    0,
+                    0,
+                );
+
+                let viewport = D3D11_VIEWPORT {
+                    TopLeftX: 0.0,
+                    TopLeftY: 0.0,
+                    Width: desc.Width as f32,
+                    Height: desc.Height as f32,
+                    MinDepth: 0.0,
+                    MaxDepth: 1.0,
+                };
+                dc.RSSetViewports(Some(&[viewport]));
+
+                dc.IASetInputLayout(None);
+                dc.IASetPrimitiveTopology(
+                    windows::Win32::Graphics::Direct3D::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+                );
+                dc.VSSetShader(&pipeline.vs, None);
+                dc.VSSetShaderResources(1, Some(&[Some(buffer.dx11_srv().clone())]));
+                dc.VSSetConstantBuffers(0, Some(&[Some(pipeline.params.clone())]));
+                dc.PSSetShader(&pipeline.ps, None);
+                dc.PSSetShaderResources(0, Some(&[self.texture.as_ref().unwrap().srv()]));
+                dc.PSSetSamplers(0, Some(&[Some(pipeline.sampler.clone())]));
+                dc.OMSetBlendState(&pipeline.blend, None, u32::MAX);
+                dc.OMSetRenderTargets(Some(&[Some(rtv)]), None);
+
+                dc.DrawInstanced(4, quads.len() as u32, 0, 0);
+
+                dc.OMSetBlendState(None, None, u32::MAX);
+                let null_rtvs: [Option<ID3D11RenderTargetView>; 1] = Default::default();
+                dc.OMSetRenderTargets(Some(&null_rtvs), None);
+                let null_srvs: [Option<ID3D11ShaderResourceView>; 2] = Default::default();
+                dc.VSSetShaderResources(0, Some(&null_srvs));
+                dc.PSSetShaderResources(0, Some(&null_srvs[..1]));
+                let null_cbufs: [Option<ID3D11Buffer>; 1] = Default::default();
+                dc.VSSetConstantBuffers(0, Some(&null_cbufs));
+            }
+            Ok(())
+        }
+    }
+}
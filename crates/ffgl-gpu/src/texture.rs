@@ -0,0 +1,121 @@
+//! GPU texture type for intermediate render/compute targets.
+//!
+//! On macOS this wraps a `MTLTexture`. On Windows it wraps an
+//! `ID3D11Texture2D` with associated SRV, UAV, and RTV views so the same
+//! texture can be read, written by compute, and rendered into.
+
+use crate::intermediate::IntermediateFormat;
+#[cfg(target_os = "macos")]
+use objc2::rc::Retained;
+#[cfg(target_os = "macos")]
+use objc2::runtime::ProtocolObject;
+#[cfg(target_os = "macos")]
+use objc2_metal::MTLTexture;
+
+/// An owned GPU texture, created with
+/// [`GpuContext::create_texture`](crate::GpuContext::create_texture).
+///
+/// Use this for intermediate targets in multi-pass effects (ping-pong
+/// buffers, cached previous frames, etc.) instead of storing raw Metal or
+/// DX11 objects in plugin state. The accessors return the platform objects in
+/// the form the dispatch calls expect.
+///
+/// On macOS this is a `BGRA8Unorm` `MTLTexture` in private storage, usable as
+/// shader read, shader write, and render target. On Windows it is a
+/// `DXGI_FORMAT_B8G8R8A8_UNORM` `ID3D11Texture2D` with SRV, UAV, and RTV
+/// views. Textures from
+/// [`create_texture_with_format`](crate::GpuContext::create_texture_with_format)
+/// use the matching float format instead.
+pub struct GpuTexture {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) format: IntermediateFormat,
+
+    #[cfg(target_os = "macos")]
+    pub(crate) metal: Retained<ProtocolObject<dyn MTLTexture>>,
+
+    #[cfg(target_os = "windows")]
+    pub(crate) dx11_texture: windows::Win32::Graphics::Direct3D11::ID3D11Texture2D,
+    #[cfg(target_os = "windows")]
+    pub(crate) dx11_srv: windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView,
+    #[cfg(target_os = "windows")]
+    pub(crate) dx11_uav: windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView,
+    #[cfg(target_os = "windows")]
+    pub(crate) dx11_rtv: windows::Win32::Graphics::Direct3D11::ID3D11RenderTargetView,
+
+    /// Counts the texture against its creator in [`crate::budget`].
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) _allocation: crate::budget::Allocation,
+    /// Counts the texture in its context's memory report. `None` for heap
+    /// textures, whose heap is counted instead.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) _tracked: Option<crate::memory::Tracked>,
+}
+
+impl GpuTexture {
+    /// Width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Pixel format.
+    pub fn format(&self) -> IntermediateFormat {
+        self.format
+    }
+
+    /// Whether this texture already has the given dimensions, for
+    /// reallocate-on-resize checks.
+    pub fn has_dims(&self, width: u32, height: u32) -> bool {
+        self.width == width && self.height == height
+    }
+
+    /// Borrow the underlying Metal texture (macOS).
+    #[cfg(target_os = "macos")]
+    pub fn metal_texture(&self) -> &ProtocolObject<dyn MTLTexture> {
+        &self.metal
+    }
+
+    /// Borrow the underlying DX11 texture (Windows).
+    #[cfg(target_os = "windows")]
+    pub fn dx11_texture(&self) -> &windows::Win32::Graphics::Direct3D11::ID3D11Texture2D {
+        &self.dx11_texture
+    }
+
+    /// Borrow the DX11 shader resource view (Windows).
+    #[cfg(target_os = "windows")]
+    pub fn dx11_srv(&self) -> &windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView {
+        &self.dx11_srv
+    }
+
+    /// Borrow the DX11 unordered access view (Windows).
+    #[cfg(target_os = "windows")]
+    pub fn dx11_uav(&self) -> &windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView {
+        &self.dx11_uav
+    }
+
+    /// Borrow the DX11 render target view (Windows).
+    #[cfg(target_os = "windows")]
+    pub fn dx11_rtv(&self) -> &windows::Win32::Graphics::Direct3D11::ID3D11RenderTargetView {
+        &self.dx11_rtv
+    }
+
+    /// The SRV in the form taken by the `srvs` slices of
+    /// [`dispatch_compute`](crate::GpuContext::dispatch_compute) and
+    /// [`dispatch_render`](crate::GpuContext::dispatch_render) (Windows).
+    #[cfg(target_os = "windows")]
+    pub fn srv(&self) -> Option<windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView> {
+        Some(self.dx11_srv.clone())
+    }
+
+    /// The UAV in the form taken by the `uavs` slice of
+    /// [`dispatch_compute`](crate::GpuContext::dispatch_compute) (Windows).
+    #[cfg(target_os = "windows")]
+    pub fn uav(&self) -> Option<windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView> {
+        Some(self.dx11_uav.clone())
+    }
+}
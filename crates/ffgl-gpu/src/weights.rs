@@ -0,0 +1,200 @@
+//! Convolution kernels computed on the CPU and uploaded once.
+//!
+//! A [`WeightBuffer`] turns a [`Kernel`] into a list of taps and keeps them
+//! in a [`GpuBuffer`](crate::GpuBuffer), re-uploading only when the kernel
+//! changes. Set it from the parameters each frame; a blur whose radius
+//! hasn't moved costs no upload.
+//!
+//! Every tap is four floats, matching this shader struct on both backends:
+//!
+//! ```text
+//! struct Tap {
+//!     float2 offset; // in pixels
+//!     float weight;  // the weights of a kernel sum to 1
+//!     float _pad;
+//! };
+//! ```
+//!
+//! One-dimensional kernels put their offsets in `offset.x`; a separable pass
+//! uses `offset.yx` for the vertical direction. Pass
+//! [`WeightBuffer::len`] to the shader as the tap count.
+
+/// Upper bound on the taps in one kernel.
+pub const MAX_TAPS: usize = 1024;
+
+/// A kernel [`WeightBuffer`] can generate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kernel {
+    /// Normalized 1D Gaussian over `±ceil(3 * sigma)` pixels.
+    Gaussian { sigma: f32 },
+    /// 1D Lanczos with `lobes` lobes, stretched by `scale` for downscaling
+    /// by that factor (1 for plain interpolation at integer offsets).
+    Lanczos { lobes: u32, scale: f32 },
+    /// Equal weights along a line of `length` pixels centred on the pixel,
+    /// at `angle` radians from the x axis: a motion blur.
+    Directional { angle: f32, length: f32 },
+}
+
+impl Kernel {
+    /// The kernel's taps, as laid out in the [module docs](crate::weights).
+    pub fn taps(&self) -> Vec<[f32; 4]> {
+        let mut taps: Vec<[f32; 4]> = match *self {
+            Kernel::Gaussian { sigma } => {
+                let sigma = sigma.max(1e-3);
+                let radius = ((3.0 * sigma).ceil() as i32).min(((MAX_TAPS - 1) / 2) as i32);
+                (-radius..=radius)
+                    .map(|x| {
+                        let x = x as f32;
+                        [x, 0.0, (-x * x / (2.0 * sigma * sigma)).exp(), 0.0]
+                    })
+                    .collect()
+            }
+            Kernel::Lanczos { lobes, scale } => {
+                let lobes = lobes.max(1) as f32;
+                let scale = scale.max(1.0);
+                let radius = ((lobes * scale).ceil() as i32).min(((MAX_TAPS - 1) / 2) as i32);
+                (-radius..=radius)
+                    .map(|x| {
+                        let t = x as f32 / scale;
+                        let weight = if t.abs() < lobes {
+                            sinc(t) * sinc(t / lobes)
+                        } else {
+                            0.0
+                        };
+                        [x as f32, 0.0, weight, 0.0]
+                    })
+                    .collect()
+            }
+            Kernel::Directional { angle, length } => {
+                let count = (length.max(1.0).round() as usize).min(MAX_TAPS);
+                let (sin, cos) = angle.sin_cos();
+                (0..count)
+                    .map(|i| {
+                        let d = i as f32 - (count - 1) as f32 / 2.0;
+                        [d * cos, d * sin, 1.0, 0.0]
+                    })
+                    .collect()
+            }
+        };
+
+        let sum: f32 = taps.iter().map(|t| t[2]).sum();
+        if sum != 0.0 {
+            for tap in &mut taps {
+                tap[2] /= sum;
+            }
+        }
+        taps
+    }
+}
+
+/// Normalized sinc, `sin(pi x) / (pi x)`.
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-6 {
+        1.0
+    } else {
+        let px = std::f32::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+/// A kernel's taps and the GPU buffer holding them, for one plugin
+/// instance.
+#[derive(Default)]
+pub struct WeightBuffer {
+    kernel: Option<Kernel>,
+    taps: Vec<[f32; 4]>,
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    buffer: Option<crate::GpuBuffer>,
+    /// Whether `taps` changed since the buffer was last uploaded.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    dirty: bool,
+}
+
+impl WeightBuffer {
+    /// An empty buffer; set a kernel before binding it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Switch to `kernel`, recomputing the taps only if it differs from the
+    /// current one.
+    pub fn set_kernel(&mut self, kernel: Kernel) {
+        if self.kernel == Some(kernel) {
+            return;
+        }
+        self.kernel = Some(kernel);
+        self.taps = kernel.taps();
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        {
+            self.dirty = true;
+        }
+    }
+
+    /// The current kernel, if one was set.
+    pub fn kernel(&self) -> Option<Kernel> {
+        self.kernel
+    }
+
+    /// The current taps.
+    pub fn taps(&self) -> &[[f32; 4]] {
+        &self.taps
+    }
+
+    /// Number of taps, for the shader's loop.
+    pub fn len(&self) -> usize {
+        self.taps.len()
+    }
+
+    /// Whether there are no taps, i.e. no kernel was set.
+    pub fn is_empty(&self) -> bool {
+        self.taps.is_empty()
+    }
+
+    /// The taps as bytes, for binding inline instead of through the buffer.
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: `[f32; 4]` is plain data with no padding.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.taps.as_ptr() as *const u8,
+                std::mem::size_of_val(self.taps.as_slice()),
+            )
+        }
+    }
+
+    /// The buffer holding the taps, uploaded if the kernel changed since
+    /// the last call.
+    ///
+    /// On Metal a changed kernel gets a new shared buffer, so frames still
+    /// in flight keep reading the old one. On DX11 the buffer is updated in
+    /// place on the [`command_context`](crate::GpuContext::command_context)
+    /// and only reallocated when it needs to grow.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub fn buffer(&mut self, ctx: &crate::GpuContext) -> anyhow::Result<&crate::GpuBuffer> {
+        let tap_size = std::mem::size_of::<[f32; 4]>();
+        let count = self.taps.len().max(1);
+
+        #[cfg(target_os = "macos")]
+        if self.dirty || self.buffer.is_none() {
+            let buffer = ctx.create_shared_buffer(count, tap_size)?;
+            // SAFETY: the buffer is new, shared and at least as large as the
+            // taps.
+            unsafe { crate::buffer::copy_to_buffer(&buffer.metal, self.as_bytes()) };
+            self.buffer = Some(buffer);
+        }
+
+        #[cfg(target_os = "windows")]
+        if self.dirty || self.buffer.is_none() {
+            if !self
+                .buffer
+                .as_ref()
+                .is_some_and(|b| b.size() >= count * tap_size)
+            {
+                self.buffer = Some(ctx.create_buffer(count.next_power_of_two(), tap_size)?);
+            }
+            ctx.update_buffer(self.buffer.as_ref().unwrap(), self.as_bytes());
+        }
+
+        self.dirty = false;
+        Ok(self.buffer.as_ref().unwrap())
+    }
+}
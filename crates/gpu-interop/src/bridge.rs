@@ -1,8 +1,240 @@
 //! Common interface for GL-to-GPU texture bridging.
 
+use std::fmt;
+use std::time::Duration;
+
 use anyhow::Result;
 use gl::types::GLuint;
 
+use crate::GlContextId;
+
+/// Default for [`GpuBridge::set_staleness_window`]: a dispatched frame's
+/// result older than this is not presented by the next frame.
+pub const DEFAULT_STALENESS_WINDOW: Duration = Duration::from_millis(100);
+
+/// A rectangle in GL pixel coordinates (origin at the bottom-left).
+///
+/// Used to restrict bridge blits to a sub-region of the host texture/FBO.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// A rectangle covering a full `width` x `height` surface.
+    pub fn full(width: u32, height: u32) -> Self {
+        Self::new(0, 0, width, height)
+    }
+
+    /// Clamp to a `width` x `height` surface. Returns `None` if nothing of
+    /// the rectangle remains.
+    pub fn clamped(&self, width: u32, height: u32) -> Option<Self> {
+        let x0 = self.x.min(width);
+        let y0 = self.y.min(height);
+        let x1 = self.x.saturating_add(self.width).min(width);
+        let y1 = self.y.saturating_add(self.height).min(height);
+        (x1 > x0 && y1 > y0).then(|| Self::new(x0, y0, x1 - x0, y1 - y0))
+    }
+
+    /// Whether this rectangle covers the whole `width` x `height` surface.
+    pub fn is_full(&self, width: u32, height: u32) -> bool {
+        *self == Self::full(width, height)
+    }
+
+    /// The same rectangle moved by (`x`, `y`).
+    pub fn offset(&self, x: u32, y: u32) -> Self {
+        Self::new(self.x + x, self.y + y, self.width, self.height)
+    }
+}
+
+/// How pixel values in the shared textures relate to the host's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Values are passed through exactly as the host stores them.
+    #[default]
+    Unmanaged,
+    /// Host textures hold sRGB-encoded color. GPU code reads and writes
+    /// linear values; the bridge (or the framework on top of it) decodes on
+    /// the way in and encodes on the way out.
+    Srgb,
+}
+
+/// Pixel format of the bridge's shared textures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureFormat {
+    /// 8 bits per channel, unsigned normalized (stored as BGRA on macOS).
+    Rgba8,
+    /// 16-bit float per channel. Holds values outside 0-1 for HDR content.
+    Rgba16Float,
+    /// 32-bit float per channel.
+    Rgba32Float,
+}
+
+impl TextureFormat {
+    /// Size of one pixel in bytes.
+    pub fn bytes_per_pixel(self) -> u32 {
+        match self {
+            Self::Rgba8 => 4,
+            Self::Rgba16Float => 8,
+            Self::Rgba32Float => 16,
+        }
+    }
+}
+
+/// Attributes of the IOSurfaces behind the macOS bridge's shared textures
+/// (see `GlMetalBridge::set_surface_options`). Ignored on Windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SurfaceOptions {
+    /// Color space the surfaces are tagged with (`kIOSurfaceColorSpace`).
+    pub color_space: SurfaceColorSpace,
+    /// CPU cache mode of the surface memory (`kIOSurfaceCacheMode`).
+    pub cache_mode: SurfaceCacheMode,
+}
+
+/// Color space tag of a shared IOSurface.
+///
+/// The tag doesn't change what GL or Metal read and write; it tells the
+/// system how to interpret the pixels wherever it color-matches them, such
+/// as when a surface is displayed or captured. An untagged surface is taken
+/// as the display's color space, which shifts colors on wide-gamut screens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SurfaceColorSpace {
+    /// sRGB for [`ColorSpace::Srgb`], untagged for
+    /// [`ColorSpace::Unmanaged`], whose values are whatever the host's are.
+    #[default]
+    Auto,
+    /// No tag.
+    Untagged,
+    /// `kCGColorSpaceSRGB`.
+    Srgb,
+    /// `kCGColorSpaceLinearSRGB`.
+    LinearSrgb,
+    /// `kCGColorSpaceExtendedLinearSRGB`, for float surfaces holding values
+    /// outside 0-1.
+    ExtendedLinearSrgb,
+    /// `kCGColorSpaceDisplayP3`.
+    DisplayP3,
+}
+
+impl SurfaceColorSpace {
+    /// CoreGraphics name of the tag for surfaces created for `color_space`,
+    /// `None` for no tag.
+    pub fn name(self, color_space: ColorSpace) -> Option<&'static str> {
+        match self {
+            Self::Auto => match color_space {
+                ColorSpace::Srgb => Some("kCGColorSpaceSRGB"),
+                ColorSpace::Unmanaged => None,
+            },
+            Self::Untagged => None,
+            Self::Srgb => Some("kCGColorSpaceSRGB"),
+            Self::LinearSrgb => Some("kCGColorSpaceLinearSRGB"),
+            Self::ExtendedLinearSrgb => Some("kCGColorSpaceExtendedLinearSRGB"),
+            Self::DisplayP3 => Some("kCGColorSpaceDisplayP3"),
+        }
+    }
+}
+
+/// CPU cache mode of a shared IOSurface's memory.
+///
+/// The bridge's surfaces are only touched by the GPU, through GL and Metal,
+/// so the default suits them. The other modes matter to plugins that map a
+/// surface on the CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SurfaceCacheMode {
+    /// Copy-back caching, coherent with the GPU on Apple silicon
+    /// (`kIOMapDefaultCache`).
+    #[default]
+    Default,
+    /// Write-combined (`kIOMapWriteCombineCache`): fast CPU writes the GPU
+    /// reads, very slow CPU reads.
+    WriteCombine,
+    /// Uncached (`kIOMapInhibitCache`).
+    Inhibit,
+}
+
+impl SurfaceCacheMode {
+    /// Value of the `kIOSurfaceCacheMode` property.
+    pub fn value(self) -> u32 {
+        match self {
+            Self::Default => 0,
+            Self::Inhibit => 1,
+            Self::WriteCombine => 4,
+        }
+    }
+}
+
+/// GPU memory held by a bridge's textures, in bytes. Estimated from texture
+/// sizes and formats; drivers may pad allocations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BridgeMemory {
+    /// Textures for the current dimensions.
+    pub active: u64,
+    /// Retired textures kept for reuse (see
+    /// [`GpuBridge::set_pool_capacity`]).
+    pub pooled: u64,
+}
+
+/// Why a bridge blit failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeError {
+    /// The shared textures don't exist yet (or were torn down by a failed
+    /// [`ensure_dimensions`](GpuBridge::ensure_dimensions)).
+    NotInitialized,
+    /// The host texture could not be attached as a complete read framebuffer.
+    IncompleteFramebuffer {
+        /// GL name of the host texture.
+        texture: GLuint,
+    },
+    /// `wglDXLockObjectsNV` or `wglDXUnlockObjectsNV` refused a shared texture.
+    LockFailed(&'static str),
+    /// Mapping a buffer or staging texture for a CPU-side copy failed.
+    MapFailed(&'static str),
+    /// The keyed mutex guarding the shared textures wasn't released in time.
+    KeyedMutexTimeout {
+        /// How long the bridge waited.
+        timeout_ms: u32,
+    },
+    /// The GPU work writing the back output hadn't finished when the wait
+    /// for it timed out.
+    BackOutputBusy {
+        /// How long the bridge waited.
+        timeout_ms: u64,
+    },
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInitialized => write!(f, "shared textures have not been created"),
+            Self::IncompleteFramebuffer { texture } => {
+                write!(f, "READ_FRAMEBUFFER incomplete for host texture {texture}")
+            }
+            Self::LockFailed(what) => write!(f, "failed to lock {what} for GL access"),
+            Self::MapFailed(what) => write!(f, "failed to map {what}"),
+            Self::KeyedMutexTimeout { timeout_ms } => {
+                write!(f, "keyed mutex not acquired within {timeout_ms} ms")
+            }
+            Self::BackOutputBusy { timeout_ms } => {
+                write!(f, "back output still being written after {timeout_ms} ms")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BridgeError {}
+
 /// Common interface for GL-to-GPU texture bridging.
 ///
 /// Implementations exist for Metal (macOS via IOSurface) and DX11 (Windows via
@@ -18,12 +250,53 @@ pub trait GpuBridge {
 
     /// Mutable downcast to a concrete type.
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
-    /// Recreate shared textures if dimensions changed.
-    fn ensure_dimensions(&mut self, width: u32, height: u32) -> Result<()>;
+    /// Recreate shared textures if dimensions changed, keeping the current
+    /// [`format`](Self::format).
+    fn ensure_dimensions(&mut self, width: u32, height: u32) -> Result<()> {
+        self.ensure_dimensions_with_format(width, height, self.format())
+    }
+
+    /// Recreate shared textures if dimensions or format changed. Fails if the
+    /// bridge cannot share textures of `format` (in the current color space).
+    fn ensure_dimensions_with_format(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> Result<()>;
+
+    /// The format the bridge shares most efficiently with the host, used
+    /// until another one is requested.
+    fn preferred_format(&self) -> TextureFormat;
+
+    /// Current format of the shared textures.
+    fn format(&self) -> TextureFormat;
+
+    /// Keep up to `capacity` retired texture pairs for reuse when
+    /// [`ensure_dimensions`](Self::ensure_dimensions) switches back to a
+    /// size (and format) seen recently, so hosts that alternate output sizes
+    /// don't reallocate every time. Each pooled entry holds a full set of
+    /// shared textures; 0 disables pooling. Default no-op for bridges that
+    /// don't pool.
+    fn set_pool_capacity(&mut self, _capacity: usize) {}
+
+    /// Set how long a dispatched frame's result stays ready for
+    /// [`has_result_ready`](Self::has_result_ready). Hosts reselecting an
+    /// effect can call it with consecutive frame counters after a pause; the
+    /// window keeps the result from before the pause from being shown.
+    /// `None` disables the check, leaving only frame counter gaps to drop
+    /// the result. Default no-op for bridges that don't pipeline.
+    fn set_staleness_window(&mut self, _window: Option<Duration>) {}
+
+    /// Set the color space of the shared textures. Takes effect at the next
+    /// [`ensure_dimensions`](Self::ensure_dimensions) call, which recreates
+    /// the textures if the setting changed.
+    fn set_color_space(&mut self, color_space: ColorSpace);
+
+    /// Current color space of the shared textures.
+    fn color_space(&self) -> ColorSpace;
 
     /// Copy host OpenGL texture into the bridge's front input texture.
-    ///
-    /// Returns `false` if setup failed.
     fn blit_input_from_host_scaled(
         &mut self,
         host_texture: GLuint,
@@ -32,11 +305,17 @@ pub trait GpuBridge {
         dst_w: u32,
         dst_h: u32,
         bilinear: bool,
-    ) -> bool;
+    ) -> Result<(), BridgeError> {
+        self.blit_input_from_host_region(
+            host_texture,
+            PixelRect::full(src_w, src_h),
+            dst_w,
+            dst_h,
+            bilinear,
+        )
+    }
 
     /// Copy the back output texture (previous frame result) to the host FBO.
-    ///
-    /// Returns `false` if setup failed.
     fn blit_back_output_to_target_scaled(
         &mut self,
         host_fbo: GLuint,
@@ -45,11 +324,16 @@ pub trait GpuBridge {
         dst_w: u32,
         dst_h: u32,
         bilinear: bool,
-    ) -> bool;
+    ) -> Result<(), BridgeError> {
+        self.blit_back_output_to_target_region(
+            host_fbo,
+            PixelRect::full(src_w, src_h),
+            PixelRect::full(dst_w, dst_h),
+            bilinear,
+        )
+    }
 
     /// Copy the front output texture (current frame, sync path) to the host FBO.
-    ///
-    /// Returns `false` if setup failed.
     fn blit_output_to_target_scaled(
         &mut self,
         host_fbo: GLuint,
@@ -58,12 +342,60 @@ pub trait GpuBridge {
         dst_w: u32,
         dst_h: u32,
         bilinear: bool,
-    ) -> bool;
+    ) -> Result<(), BridgeError> {
+        self.blit_output_to_target_region(
+            host_fbo,
+            PixelRect::full(src_w, src_h),
+            PixelRect::full(dst_w, dst_h),
+            bilinear,
+        )
+    }
+
+    /// Copy the `src` region of the host OpenGL texture into the full
+    /// `dst_w` x `dst_h` area of the bridge's front input texture.
+    fn blit_input_from_host_region(
+        &mut self,
+        host_texture: GLuint,
+        src: PixelRect,
+        dst_w: u32,
+        dst_h: u32,
+        bilinear: bool,
+    ) -> Result<(), BridgeError>;
+
+    /// Clear the bridge's front input texture to transparent black, in place
+    /// of [`blit_input_from_host_region`](Self::blit_input_from_host_region)
+    /// when the host provides no texture.
+    fn clear_input(&mut self) -> Result<(), BridgeError>;
+
+    /// Copy the `src` region of the back output texture into the `dst`
+    /// region of the host FBO. Pixels outside `dst` are left untouched.
+    fn blit_back_output_to_target_region(
+        &mut self,
+        host_fbo: GLuint,
+        src: PixelRect,
+        dst: PixelRect,
+        bilinear: bool,
+    ) -> Result<(), BridgeError>;
+
+    /// Copy the `src` region of the front output texture into the `dst`
+    /// region of the host FBO. Pixels outside `dst` are left untouched.
+    fn blit_output_to_target_region(
+        &mut self,
+        host_fbo: GLuint,
+        src: PixelRect,
+        dst: PixelRect,
+        bilinear: bool,
+    ) -> Result<(), BridgeError>;
 
     /// Check if a previous frame's result is ready for presentation.
     fn has_result_ready(&self, current_frame: u64) -> bool;
 
     /// Block until the previous frame's GPU work completes. Clears pending state.
+    ///
+    /// A bridge whose wait can time out may keep the pending state instead,
+    /// so the next wait covers the work again, and fail
+    /// [`blit_back_output_to_target_region`](Self::blit_back_output_to_target_region)
+    /// until then.
     fn wait_for_previous(&mut self);
 
     /// Block until pending GPU work completes WITHOUT clearing pending state.
@@ -78,6 +410,39 @@ pub trait GpuBridge {
     /// Clean up all GPU resources.
     fn cleanup(&mut self);
 
+    /// Native GL context the bridge's GL objects were created in. If the
+    /// current context differs, the bridge must be rebuilt.
+    fn gl_context(&self) -> Option<GlContextId>;
+
+    /// Drop every GL object name and interop registration without deleting
+    /// them, for when their context is gone and GL calls would hit another
+    /// context's objects. Non-GL resources are still released normally;
+    /// the bridge must be rebuilt before further use.
+    fn forget_gl_objects(&mut self);
+
     /// Get current dimensions of the shared textures.
     fn dimensions(&self) -> (u32, u32);
+
+    /// GPU memory held by the current and pooled textures.
+    fn memory_usage(&self) -> BridgeMemory;
+}
+
+/// Clear the color attachment of the bound draw framebuffer to transparent
+/// black, ignoring the scissor and leaving scissor and clear color state as
+/// found.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) unsafe fn clear_draw_framebuffer() {
+    let scissor_enabled = gl::IsEnabled(gl::SCISSOR_TEST) == gl::TRUE;
+    let mut clear_color: [f32; 4] = [0.0; 4];
+    gl::GetFloatv(gl::COLOR_CLEAR_VALUE, clear_color.as_mut_ptr());
+
+    gl::Disable(gl::SCISSOR_TEST);
+    gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+    gl::Clear(gl::COLOR_BUFFER_BIT);
+
+    let [r, g, b, a] = clear_color;
+    gl::ClearColor(r, g, b, a);
+    if scissor_enabled {
+        gl::Enable(gl::SCISSOR_TEST);
+    }
 }
@@ -5,9 +5,13 @@
 //! buffers.  Consumers (e.g. `ffgl-gpu`) are responsible for loading their own
 //! shaders and building pipelines on top of this device.
 
-use windows::Win32::Foundation::HMODULE;
+use std::time::{Duration, Instant};
+
+use windows::core::{Interface, PCWSTR};
+use windows::Win32::Foundation::{CloseHandle, HANDLE, HMODULE};
 use windows::Win32::Graphics::Direct3D::*;
 use windows::Win32::Graphics::Direct3D11::*;
+use windows::Win32::System::Threading::{CreateEventW, WaitForSingleObject};
 
 use tracing::{debug, error};
 
@@ -30,6 +34,17 @@ impl Dx11Device {
     ///
     /// Returns `None` if D3D11 is unavailable with any driver type.
     pub fn new() -> Option<Self> {
+        Self::create(D3D11_CREATE_DEVICE_SINGLETHREADED)
+    }
+
+    /// Like [`new`](Self::new), but without `D3D11_CREATE_DEVICE_SINGLETHREADED`,
+    /// which `CreateDeferredContext` rejects. The runtime then locks around
+    /// every context call, so only use this when deferred contexts are needed.
+    pub fn new_with_deferred_contexts() -> Option<Self> {
+        Self::create(D3D11_CREATE_DEVICE_FLAG(0))
+    }
+
+    fn create(flags: D3D11_CREATE_DEVICE_FLAG) -> Option<Self> {
         let mut device = None;
         let mut context = None;
 
@@ -43,7 +58,7 @@ impl Dx11Device {
                     None,
                     driver_type,
                     HMODULE::default(),
-                    D3D11_CREATE_DEVICE_SINGLETHREADED,
+                    flags,
                     Some(&[D3D_FEATURE_LEVEL_11_0]),
                     D3D11_SDK_VERSION,
                     Some(&mut device as *mut _),
@@ -119,3 +134,78 @@ pub fn create_event_query(device: &ID3D11Device) -> Option<ID3D11Query> {
     unsafe { device.CreateQuery(&desc, Some(&mut query as *mut _)) }.ok()?;
     query
 }
+
+/// D3D11.4 fence with an event for blocking waits, used instead of polling
+/// an event query. Needs an `ID3D11Device5` (Windows 10 1703+ runtime).
+pub(crate) struct GpuFence {
+    fence: ID3D11Fence,
+    context: ID3D11DeviceContext4,
+    /// Auto-reset event passed to `SetEventOnCompletion`.
+    event: HANDLE,
+    /// Value of the most recent signal.
+    last_signaled: u64,
+}
+
+impl GpuFence {
+    /// Returns `None` if the runtime does not support D3D11.4 fences.
+    pub(crate) fn new(device: &ID3D11Device, context: &ID3D11DeviceContext) -> Option<Self> {
+        let device5: ID3D11Device5 = device.cast().ok()?;
+        let context: ID3D11DeviceContext4 = context.cast().ok()?;
+        let mut fence: Option<ID3D11Fence> = None;
+        unsafe { device5.CreateFence(0, D3D11_FENCE_FLAG_NONE, &mut fence) }.ok()?;
+        let fence = fence?;
+        let event = unsafe { CreateEventW(None, false, false, PCWSTR::null()) }.ok()?;
+        Some(Self {
+            fence,
+            context,
+            event,
+            last_signaled: 0,
+        })
+    }
+
+    /// Enqueue a signal of the next fence value after all work submitted so
+    /// far on the immediate context.
+    pub(crate) fn signal(&mut self) -> windows::core::Result<()> {
+        let value = self.last_signaled + 1;
+        unsafe { self.context.Signal(&self.fence, value) }?;
+        self.last_signaled = value;
+        Ok(())
+    }
+
+    /// Value of the most recent [`signal`](Self::signal).
+    pub(crate) fn last_signaled(&self) -> u64 {
+        self.last_signaled
+    }
+
+    pub(crate) fn is_complete(&self, value: u64) -> bool {
+        unsafe { self.fence.GetCompletedValue() >= value }
+    }
+
+    /// Block until the GPU reaches `value` or `timeout` elapses. Returns
+    /// whether the value was reached.
+    pub(crate) fn wait(&self, value: u64, timeout: Duration) -> bool {
+        let start = Instant::now();
+        // Loop because the auto-reset event can still be set from an earlier
+        // wait that timed out before its value completed.
+        while !self.is_complete(value) {
+            let remaining = timeout.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                return false;
+            }
+            if unsafe { self.fence.SetEventOnCompletion(value, self.event) }.is_err() {
+                return false;
+            }
+            let ms = remaining.as_millis().clamp(1, u32::MAX as u128 - 1) as u32;
+            unsafe { WaitForSingleObject(self.event, ms) };
+        }
+        true
+    }
+}
+
+impl Drop for GpuFence {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.event);
+        }
+    }
+}
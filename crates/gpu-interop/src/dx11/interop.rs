@@ -6,18 +6,27 @@
 //! overlap with host compositing between draw calls.
 
 use std::ffi::CStr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::{bail, Result};
 use gl::types::{GLenum, GLint, GLsizei, GLuint, GLvoid};
-use tracing::{debug, error, warn};
+use tracing::{debug, error};
+use windows::core::Interface;
 use windows::Win32::Graphics::Direct3D::D3D_SRV_DIMENSION_TEXTURE2D;
 use windows::Win32::Graphics::Direct3D11::*;
 use windows::Win32::Graphics::Dxgi::Common::*;
+use windows::Win32::Graphics::Dxgi::IDXGIKeyedMutex;
 use windows::Win32::Graphics::Gdi::HDC;
 use windows::Win32::Graphics::OpenGL::*;
 
-use crate::GpuBridge;
+use super::device::GpuFence;
+use super::Dx11Bridge;
+use crate::bridge::clear_draw_framebuffer;
+use crate::pool::{pair_set_bytes, PairPool, DEFAULT_POOL_CAPACITY};
+use crate::{
+    BridgeError, BridgeMemory, ColorSpace, GlContextId, GpuBridge, PixelRect, TextureFormat,
+    DEFAULT_STALENESS_WINDOW,
+};
 
 /// WGL_NV_DX_interop2 constants.
 const WGL_ACCESS_READ_WRITE_NV: GLenum = 0x0001;
@@ -26,6 +35,9 @@ const WGL_ACCESS_READ_WRITE_NV: GLenum = 0x0001;
 /// (which are already complete) before checking the latest, reducing spin time.
 const PIPELINE_DEPTH: usize = 3;
 
+/// Default for [`GlDx11Bridge::set_sync_timeout`].
+const DEFAULT_SYNC_TIMEOUT: Duration = Duration::from_millis(100);
+
 // ---------------------------------------------------------------------------
 // WGL function pointer types
 // ---------------------------------------------------------------------------
@@ -110,12 +122,31 @@ impl WglInteropFunctions {
 // SharedTexture / SharedTexturePair
 // ---------------------------------------------------------------------------
 
+/// Keyed-mutex key used for every acquire/release. GL and D3D11 never wait
+/// on each other through the mutex (the WGL lock orders them), so one key is
+/// enough to make the driver serialize access.
+const KEYED_MUTEX_KEY: u64 = 0;
+
+/// How long D3D11 waits for a keyed mutex before giving up on the frame.
+const KEYED_MUTEX_TIMEOUT_MS: u32 = 100;
+
+/// DXGI format of shared textures in `format`.
+pub(super) fn dxgi_format(format: TextureFormat) -> DXGI_FORMAT {
+    match format {
+        TextureFormat::Rgba8 => DXGI_FORMAT_R8G8B8A8_UNORM,
+        TextureFormat::Rgba16Float => DXGI_FORMAT_R16G16B16A16_FLOAT,
+        TextureFormat::Rgba32Float => DXGI_FORMAT_R32G32B32A32_FLOAT,
+    }
+}
+
 /// A D3D11 texture shared with OpenGL via WGL_NV_DX_interop2.
 struct SharedTexture {
     d3d_texture: ID3D11Texture2D,
     gl_texture: GLuint,
     /// WGL interop handle returned by wglDXRegisterObjectNV.
     interop_handle: *mut GLvoid,
+    /// Present when the texture was created with `SHARED_KEYEDMUTEX`.
+    keyed_mutex: Option<IDXGIKeyedMutex>,
 }
 
 impl SharedTexture {
@@ -134,15 +165,23 @@ impl SharedTexture {
         interop_device: *mut GLvoid,
         width: u32,
         height: u32,
+        format: TextureFormat,
         extra_bind_flags: u32,
+        keyed_mutex: bool,
     ) -> Option<Self> {
-        // Create D3D11 texture with SHARED flag for WGL interop
+        // Create D3D11 texture with SHARED flag for WGL interop. Keyed-mutex
+        // textures must use NT handles instead of the legacy SHARED flag.
+        let misc_flags = if keyed_mutex {
+            D3D11_RESOURCE_MISC_SHARED_NTHANDLE.0 | D3D11_RESOURCE_MISC_SHARED_KEYEDMUTEX.0
+        } else {
+            D3D11_RESOURCE_MISC_SHARED.0
+        };
         let desc = D3D11_TEXTURE2D_DESC {
             Width: width,
             Height: height,
             MipLevels: 1,
             ArraySize: 1,
-            Format: DXGI_FORMAT_R16G16B16A16_FLOAT,
+            Format: dxgi_format(format),
             SampleDesc: DXGI_SAMPLE_DESC {
                 Count: 1,
                 Quality: 0,
@@ -151,13 +190,25 @@ impl SharedTexture {
             BindFlags: (D3D11_BIND_SHADER_RESOURCE.0 | D3D11_BIND_UNORDERED_ACCESS.0) as u32
                 | extra_bind_flags,
             CPUAccessFlags: 0,
-            MiscFlags: D3D11_RESOURCE_MISC_SHARED.0 as u32,
+            MiscFlags: misc_flags as u32,
         };
 
         let mut d3d_texture = None;
         unsafe { device.CreateTexture2D(&desc, None, Some(&mut d3d_texture as *mut _)) }.ok()?;
         let d3d_texture = d3d_texture?;
 
+        let keyed_mutex = if keyed_mutex {
+            match d3d_texture.cast::<IDXGIKeyedMutex>() {
+                Ok(m) => Some(m),
+                Err(e) => {
+                    error!("Shared texture has no IDXGIKeyedMutex: {e}");
+                    return None;
+                }
+            }
+        } else {
+            None
+        };
+
         // Create GL texture name
         let mut gl_texture: GLuint = 0;
         unsafe { gl::GenTextures(1, &mut gl_texture) };
@@ -193,8 +244,36 @@ impl SharedTexture {
             d3d_texture,
             gl_texture,
             interop_handle,
+            keyed_mutex,
         })
     }
+
+    /// Acquire the keyed mutex for D3D11 access. Always succeeds for
+    /// textures without one.
+    fn acquire(&self) -> bool {
+        let Some(mutex) = &self.keyed_mutex else {
+            return true;
+        };
+        // Called through the vtable: the wrapper maps WAIT_TIMEOUT and
+        // WAIT_ABANDONED (success codes) to `Ok`, hiding a failed acquire.
+        let hr = unsafe {
+            (Interface::vtable(mutex).AcquireSync)(
+                Interface::as_raw(mutex),
+                KEYED_MUTEX_KEY,
+                KEYED_MUTEX_TIMEOUT_MS,
+            )
+        };
+        hr.0 == 0
+    }
+
+    /// Release the keyed mutex taken by [`acquire`](Self::acquire).
+    fn release(&self) {
+        if let Some(mutex) = &self.keyed_mutex {
+            if let Err(e) = unsafe { mutex.ReleaseSync(KEYED_MUTEX_KEY) } {
+                crate::warn_limited!("Failed to release keyed mutex: {e}");
+            }
+        }
+    }
 }
 
 impl Drop for SharedTexture {
@@ -210,6 +289,10 @@ impl Drop for SharedTexture {
     }
 }
 
+/// What a set of pairs was created for: dimensions, format and whether the
+/// textures carry keyed mutexes.
+type PairKey = (u32, u32, TextureFormat, bool);
+
 /// A paired input/output shared texture set for one frame slot.
 /// SRV and UAV views are cached here (created once per resize, not every frame).
 struct SharedTexturePair {
@@ -230,8 +313,19 @@ impl SharedTexturePair {
         interop_device: *mut GLvoid,
         width: u32,
         height: u32,
+        format: TextureFormat,
+        keyed_mutex: bool,
     ) -> Option<Self> {
-        let input = SharedTexture::new(device, wgl_fns, interop_device, width, height, 0)?;
+        let input = SharedTexture::new(
+            device,
+            wgl_fns,
+            interop_device,
+            width,
+            height,
+            format,
+            0,
+            keyed_mutex,
+        )?;
         // Output texture also needs RENDER_TARGET so render pipelines can draw to it.
         let output = SharedTexture::new(
             device,
@@ -239,12 +333,14 @@ impl SharedTexturePair {
             interop_device,
             width,
             height,
+            format,
             D3D11_BIND_RENDER_TARGET.0 as u32,
+            keyed_mutex,
         )?;
 
         // Create and cache the SRV for the input texture
         let srv_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
-            Format: DXGI_FORMAT_R16G16B16A16_FLOAT,
+            Format: dxgi_format(format),
             ViewDimension: D3D_SRV_DIMENSION_TEXTURE2D,
             Anonymous: D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
                 Texture2D: D3D11_TEX2D_SRV {
@@ -265,7 +361,7 @@ impl SharedTexturePair {
 
         // Create and cache the UAV for the output texture
         let uav_desc = D3D11_UNORDERED_ACCESS_VIEW_DESC {
-            Format: DXGI_FORMAT_R16G16B16A16_FLOAT,
+            Format: dxgi_format(format),
             ViewDimension: D3D11_UAV_DIMENSION_TEXTURE2D,
             Anonymous: D3D11_UNORDERED_ACCESS_VIEW_DESC_0 {
                 Texture2D: D3D11_TEX2D_UAV { MipSlice: 0 },
@@ -300,6 +396,33 @@ impl SharedTexturePair {
             output_srv: output_srv?,
         })
     }
+
+    /// Acquire both keyed mutexes for D3D11 access. Nothing stays held on
+    /// failure.
+    fn acquire(&self) -> bool {
+        if !self.input.acquire() {
+            return false;
+        }
+        if !self.output.acquire() {
+            self.input.release();
+            return false;
+        }
+        true
+    }
+
+    fn release(&self) {
+        self.output.release();
+        self.input.release();
+    }
+
+    /// Give up the GL texture names and interop handles without releasing
+    /// them.
+    fn forget_gl(&mut self) {
+        for texture in [&mut self.input, &mut self.output] {
+            texture.gl_texture = 0;
+            texture.interop_handle = std::ptr::null_mut();
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -324,12 +447,23 @@ pub struct GlDx11Bridge {
     dispatch_count: u64,
     /// Number of queries not yet confirmed complete (0..PIPELINE_DEPTH).
     pending_queries: u32,
+    /// D3D11.4 fence signalled alongside each query. Waits block on its
+    /// event instead of spinning on the queries, which remain the fallback
+    /// when fences are unavailable.
+    fence: Option<GpuFence>,
+    /// Longest a wait for D3D11 work may block the render thread.
+    sync_timeout: Duration,
     /// Loaded WGL interop function pointers.
     wgl_fns: WglInteropFunctions,
     /// WGL interop device handle from wglDXOpenDeviceNV.
     interop_device: *mut GLvoid,
     /// Double-buffered shared texture pairs.
     pairs: [Option<SharedTexturePair>; 2],
+    /// What the current pairs were created for.
+    pair_key: Option<PairKey>,
+    /// Pairs retired by earlier dimension changes, kept (still registered
+    /// with interop) for reuse.
+    pool: PairPool<PairKey, [SharedTexturePair; 2]>,
     /// Index of the pair currently being written by D3D11 compute.
     front: usize,
     /// Frame counter from the most recent draw call that dispatched D3D11 compute.
@@ -338,9 +472,26 @@ pub struct GlDx11Bridge {
     /// back-buffer data after deselection/reselection (where the frame counter
     /// is consecutive but real time has a gap).
     last_dispatch_time: Instant,
+    /// How old the last dispatch may be for its result to be presented.
+    staleness_window: Option<Duration>,
     read_fbo: GLuint,
     draw_fbo: GLuint,
     dimensions: (u32, u32),
+    /// Color space requested by the caller. The shared textures are never
+    /// sRGB formats; sRGB conversion is done by the caller's compute passes
+    /// on the D3D11 side.
+    color_space: ColorSpace,
+    /// Pixel format the shared textures are created with.
+    format: TextureFormat,
+    /// Create the shared textures with `SHARED_NTHANDLE | SHARED_KEYEDMUTEX`
+    /// and hold the keyed mutexes while D3D11 works on them.
+    keyed_mutex: bool,
+    /// Pair whose keyed mutexes D3D11 currently holds, between the input
+    /// blit and `mark_dispatch`.
+    mutex_held: Option<usize>,
+    /// WGL context current when the bridge (and its interop device) was
+    /// created.
+    gl_context: Option<GlContextId>,
 }
 
 impl GlDx11Bridge {
@@ -372,6 +523,11 @@ impl GlDx11Bridge {
             super::device::create_event_query(device)?,
         ];
 
+        let fence = GpuFence::new(device, context);
+        if fence.is_none() {
+            debug!("D3D11.4 fences unavailable, falling back to query polling");
+        }
+
         debug!("GL-D3D11 interop bridge initialized via WGL_NV_DX_interop2");
 
         Some(Self {
@@ -380,15 +536,25 @@ impl GlDx11Bridge {
             gpu_queries,
             dispatch_count: 0,
             pending_queries: 0,
+            fence,
+            sync_timeout: DEFAULT_SYNC_TIMEOUT,
             wgl_fns,
             interop_device,
             pairs: [None, None],
+            pair_key: None,
+            pool: PairPool::new(DEFAULT_POOL_CAPACITY),
             front: 0,
             last_dispatch_frame: None,
             last_dispatch_time: Instant::now(),
+            staleness_window: Some(DEFAULT_STALENESS_WINDOW),
             read_fbo: 0,
             draw_fbo: 0,
             dimensions: (0, 0),
+            color_space: ColorSpace::Unmanaged,
+            format: TextureFormat::Rgba16Float,
+            keyed_mutex: false,
+            mutex_held: None,
+            gl_context: GlContextId::current(),
         })
     }
 
@@ -439,6 +605,12 @@ impl GlDx11Bridge {
         Some(self.pairs[self.front].as_ref()?.output_uav.clone())
     }
 
+    /// Get the D3D11 input texture for the front pair.
+    /// Returns a cloned COM reference (cheap AddRef, no device allocation).
+    pub fn input_texture(&self) -> Option<ID3D11Texture2D> {
+        Some(self.pairs[self.front].as_ref()?.input.d3d_texture.clone())
+    }
+
     /// Get the D3D11 output texture for the front pair (for render pipeline targets).
     /// Returns a cloned COM reference (cheap AddRef, no device allocation).
     pub fn output_texture(&self) -> Option<ID3D11Texture2D> {
@@ -468,6 +640,40 @@ impl GlDx11Bridge {
         &self.gpu_queries[slot]
     }
 
+    /// Share the interop textures through keyed mutexes (`SHARED_NTHANDLE |
+    /// SHARED_KEYEDMUTEX`) instead of the legacy `SHARED` flag.
+    ///
+    /// D3D11 then acquires the front pair's mutexes after the input blit and
+    /// releases them in `mark_dispatch`, which fixes flicker on drivers where
+    /// the WGL lock alone does not fully serialize GL and D3D11 access. A
+    /// change takes effect at the next `ensure_dimensions`, which recreates
+    /// the textures.
+    pub fn set_keyed_mutex(&mut self, enabled: bool) {
+        if self.keyed_mutex != enabled {
+            self.keyed_mutex = enabled;
+            // Force recreation on the next ensure_dimensions.
+            self.dimensions = (0, 0);
+        }
+    }
+
+    /// Whether keyed-mutex sharing is enabled (see [`set_keyed_mutex`](Self::set_keyed_mutex)).
+    pub fn keyed_mutex(&self) -> bool {
+        self.keyed_mutex
+    }
+
+    /// Set how long waiting for in-flight D3D11 work may block the render
+    /// thread before the bridge gives up and proceeds (default 100 ms). Longer
+    /// timeouts avoid tearing under heavy load at the cost of stalling the
+    /// host.
+    pub fn set_sync_timeout(&mut self, timeout: Duration) {
+        self.sync_timeout = timeout;
+    }
+
+    /// Current wait timeout (see [`set_sync_timeout`](Self::set_sync_timeout)).
+    pub fn sync_timeout(&self) -> Duration {
+        self.sync_timeout
+    }
+
     /// Check whether the bridge FBO handles are still valid.
     pub fn is_valid(&self) -> bool {
         if self.read_fbo == 0 && self.draw_fbo == 0 {
@@ -555,6 +761,34 @@ impl GlDx11Bridge {
         result != 0
     }
 
+    // -- Keyed mutex helpers --------------------------------------------------
+
+    /// Acquire the front pair's keyed mutexes for D3D11 access.
+    fn acquire_front_mutex(&mut self) -> bool {
+        self.release_mutex();
+        let Some(pair) = &self.pairs[self.front] else {
+            return false;
+        };
+        if pair.input.keyed_mutex.is_none() {
+            return true;
+        }
+        if !pair.acquire() {
+            return false;
+        }
+        self.mutex_held = Some(self.front);
+        true
+    }
+
+    /// Release the keyed mutexes D3D11 holds, if any. Also covers frames
+    /// that acquired but never reached `mark_dispatch`.
+    fn release_mutex(&mut self) {
+        if let Some(index) = self.mutex_held.take() {
+            if let Some(pair) = &self.pairs[index] {
+                pair.release();
+            }
+        }
+    }
+
     // -- GPU query polling ----------------------------------------------------
 
     /// Non-blocking check of the oldest pending GPU query.
@@ -580,6 +814,13 @@ impl GlDx11Bridge {
         done != 0
     }
 
+    /// Block on the fence until the most recent dispatch completes. Returns
+    /// `None` when there is no fence, so the caller polls queries instead.
+    fn wait_for_fence(&self) -> Option<bool> {
+        let fence = self.fence.as_ref()?;
+        Some(fence.wait(fence.last_signaled(), self.sync_timeout))
+    }
+
     /// Wait for all pending D3D11 dispatches to complete, draining oldest-first.
     /// Older queries (2+ frames ago) are typically already complete, making their
     /// checks instantaneous and reducing total spin time on the most recent query.
@@ -587,12 +828,25 @@ impl GlDx11Bridge {
         if self.pending_queries == 0 {
             return;
         }
+        if let Some(done) = self.wait_for_fence() {
+            if !done {
+                crate::warn_limited!(
+                    timeout_ms = self.sync_timeout.as_millis() as u64,
+                    "GPU fence wait timed out, proceeding anyway"
+                );
+            }
+            self.pending_queries = 0;
+            return;
+        }
         let start = Instant::now();
         while self.pending_queries > 0 {
             if self.poll_oldest_query() {
                 self.pending_queries -= 1;
-            } else if start.elapsed().as_millis() > 100 {
-                warn!("GPU query timed out after 100ms, proceeding anyway");
+            } else if start.elapsed() > self.sync_timeout {
+                crate::warn_limited!(
+                    timeout_ms = self.sync_timeout.as_millis() as u64,
+                    "GPU query timed out, proceeding anyway"
+                );
                 self.pending_queries = 0;
                 break;
             } else {
@@ -608,6 +862,15 @@ impl GlDx11Bridge {
         if self.pending_queries == 0 {
             return;
         }
+        if let Some(done) = self.wait_for_fence() {
+            if !done {
+                crate::warn_limited!(
+                    timeout_ms = self.sync_timeout.as_millis() as u64,
+                    "GPU fence wait timed out, proceeding anyway"
+                );
+            }
+            return;
+        }
         // Wait for the latest query (most recently issued dispatch)
         let latest_slot = ((self.dispatch_count - 1) % PIPELINE_DEPTH as u64) as usize;
         let start = Instant::now();
@@ -624,31 +887,58 @@ impl GlDx11Bridge {
             if done != 0 {
                 break;
             }
-            if start.elapsed().as_millis() > 100 {
-                warn!("GPU query timed out after 100ms, proceeding anyway");
+            if start.elapsed() > self.sync_timeout {
+                crate::warn_limited!(
+                    timeout_ms = self.sync_timeout.as_millis() as u64,
+                    "GPU query timed out, proceeding anyway"
+                );
                 break;
             }
             std::thread::yield_now();
         }
     }
 
-    /// Unregister all shared textures and drop the pairs.
+    /// Unregister a pair's shared textures and drop it.
+    fn destroy_pair(&self, mut p: SharedTexturePair) {
+        unsafe {
+            (self.wgl_fns.dx_unregister_object)(self.interop_device, p.input.interop_handle);
+            p.input.interop_handle = std::ptr::null_mut();
+            p.input.delete_gl_texture();
+            (self.wgl_fns.dx_unregister_object)(self.interop_device, p.output.interop_handle);
+            p.output.interop_handle = std::ptr::null_mut();
+            p.output.delete_gl_texture();
+        }
+    }
+
+    /// Unregister all shared textures and drop the current pairs.
     fn destroy_pairs(&mut self) {
-        for pair in &mut self.pairs {
-            if let Some(mut p) = pair.take() {
-                unsafe {
-                    (self.wgl_fns.dx_unregister_object)(
-                        self.interop_device,
-                        p.input.interop_handle,
-                    );
-                    p.input.interop_handle = std::ptr::null_mut();
-                    p.input.delete_gl_texture();
-                    (self.wgl_fns.dx_unregister_object)(
-                        self.interop_device,
-                        p.output.interop_handle,
-                    );
-                    p.output.interop_handle = std::ptr::null_mut();
-                    p.output.delete_gl_texture();
+        self.release_mutex();
+        self.pair_key = None;
+        for pair in std::mem::take(&mut self.pairs).into_iter().flatten() {
+            self.destroy_pair(pair);
+        }
+    }
+
+    /// Unregister and drop the given pooled pair sets.
+    fn destroy_pooled(&self, evicted: Vec<[SharedTexturePair; 2]>) {
+        for pair in evicted.into_iter().flatten() {
+            self.destroy_pair(pair);
+        }
+    }
+
+    /// Park the current pairs in the pool so a later switch back to their
+    /// dimensions can reuse them, destroying whatever the pool evicts.
+    fn retire_pairs(&mut self) {
+        self.release_mutex();
+        match (self.pair_key.take(), std::mem::take(&mut self.pairs)) {
+            (Some(key), [Some(a), Some(b)]) => {
+                let evicted = self.pool.put(key, [a, b]);
+                self.destroy_pooled(evicted);
+            }
+            // Half-built leftovers; nothing worth keeping.
+            (_, pairs) => {
+                for pair in pairs.into_iter().flatten() {
+                    self.destroy_pair(pair);
                 }
             }
         }
@@ -668,19 +958,24 @@ impl GpuBridge for GlDx11Bridge {
         self
     }
 
-    fn ensure_dimensions(&mut self, width: u32, height: u32) -> Result<()> {
+    fn ensure_dimensions_with_format(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> Result<()> {
         if self.dimensions == (width, height)
+            && self.format == format
             && self.pairs[0].is_some()
             && self.pairs[1].is_some()
         {
             return Ok(());
         }
 
-        // Dimension change: wait for any in-flight work before destroying textures
+        // Dimension change: wait for any in-flight work before parking the
+        // current textures
         self.wait_for_previous();
-
-        // Clean up old pairs (unregister from interop first)
-        self.destroy_pairs();
+        self.retire_pairs();
 
         // Clean up old FBOs (unbind first to avoid deleting a bound FBO)
         unsafe {
@@ -693,28 +988,37 @@ impl GpuBridge for GlDx11Bridge {
             }
         }
 
-        self.pairs[0] = SharedTexturePair::new(
-            &self.device,
-            &self.wgl_fns,
-            self.interop_device,
-            width,
-            height,
-        );
-        self.pairs[1] = SharedTexturePair::new(
-            &self.device,
-            &self.wgl_fns,
-            self.interop_device,
-            width,
-            height,
-        );
-
-        if self.pairs[0].is_none() || self.pairs[1].is_none() {
-            self.destroy_pairs();
-            self.read_fbo = 0;
-            self.draw_fbo = 0;
-            self.dimensions = (0, 0);
-            bail!("Failed to create shared D3D11-GL texture pairs");
-        }
+        let key = (width, height, format, self.keyed_mutex);
+        let pairs = match self.pool.take(&key) {
+            Some(pairs) => pairs,
+            None => {
+                let new_pair = || {
+                    SharedTexturePair::new(
+                        &self.device,
+                        &self.wgl_fns,
+                        self.interop_device,
+                        width,
+                        height,
+                        format,
+                        self.keyed_mutex,
+                    )
+                };
+                match (new_pair(), new_pair()) {
+                    (Some(a), Some(b)) => [a, b],
+                    (a, b) => {
+                        for pair in [a, b].into_iter().flatten() {
+                            self.destroy_pair(pair);
+                        }
+                        self.read_fbo = 0;
+                        self.draw_fbo = 0;
+                        self.dimensions = (0, 0);
+                        bail!("Failed to create shared D3D11-GL texture pairs");
+                    }
+                }
+            }
+        };
+        self.pairs = pairs.map(Some);
+        self.pair_key = Some(key);
 
         // Create separate FBOs for read and draw
         unsafe {
@@ -723,29 +1027,55 @@ impl GpuBridge for GlDx11Bridge {
         }
 
         self.dimensions = (width, height);
+        self.format = format;
         self.front = 0;
         self.last_dispatch_frame = None;
         Ok(())
     }
 
-    fn blit_input_from_host_scaled(
+    fn set_color_space(&mut self, color_space: ColorSpace) {
+        // The textures hold linear or encoded values equally well, so a
+        // change does not require recreating them.
+        self.color_space = color_space;
+    }
+
+    fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    fn preferred_format(&self) -> TextureFormat {
+        TextureFormat::Rgba16Float
+    }
+
+    fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    fn set_pool_capacity(&mut self, capacity: usize) {
+        let evicted = self.pool.set_capacity(capacity);
+        self.destroy_pooled(evicted);
+    }
+
+    fn set_staleness_window(&mut self, window: Option<Duration>) {
+        self.staleness_window = window;
+    }
+
+    fn blit_input_from_host_region(
         &mut self,
         host_texture: GLuint,
-        src_w: u32,
-        src_h: u32,
+        src: PixelRect,
         dst_w: u32,
         dst_h: u32,
         bilinear: bool,
-    ) -> bool {
+    ) -> Result<(), BridgeError> {
         let input_gl = match &self.pairs[self.front] {
             Some(pair) => pair.input.gl_texture,
-            None => return false,
+            None => return Err(BridgeError::NotInitialized),
         };
 
         // Lock only the front input for GL access (output is not touched here).
         if unsafe { !self.lock_gl_texture_front_input() } {
-            warn!("Failed to lock GL input texture for input blit");
-            return false;
+            return Err(BridgeError::LockFailed("shared input texture"));
         }
 
         unsafe {
@@ -760,10 +1090,11 @@ impl GpuBridge for GlDx11Bridge {
             );
 
             if gl::CheckFramebufferStatus(gl::READ_FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
-                warn!("READ_FRAMEBUFFER incomplete for host texture {host_texture}");
                 gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
                 self.unlock_gl_texture_front_input();
-                return false;
+                return Err(BridgeError::IncompleteFramebuffer {
+                    texture: host_texture,
+                });
             }
             gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
 
@@ -781,10 +1112,10 @@ impl GpuBridge for GlDx11Bridge {
             let filter = if bilinear { gl::LINEAR } else { gl::NEAREST };
 
             gl::BlitFramebuffer(
-                0,
-                0,
-                src_w as GLsizei,
-                src_h as GLsizei,
+                src.x as GLint,
+                src.y as GLint,
+                (src.x + src.width) as GLint,
+                (src.y + src.height) as GLint,
                 0,
                 0,
                 dst_w as GLsizei,
@@ -799,28 +1130,67 @@ impl GpuBridge for GlDx11Bridge {
             // Unlock so D3D11 can access the input texture
             self.unlock_gl_texture_front_input();
         }
-        true
+
+        if !self.acquire_front_mutex() {
+            return Err(BridgeError::KeyedMutexTimeout {
+                timeout_ms: KEYED_MUTEX_TIMEOUT_MS,
+            });
+        }
+        Ok(())
+    }
+
+    fn clear_input(&mut self) -> Result<(), BridgeError> {
+        let input_gl = match &self.pairs[self.front] {
+            Some(pair) => pair.input.gl_texture,
+            None => return Err(BridgeError::NotInitialized),
+        };
+
+        if unsafe { !self.lock_gl_texture_front_input() } {
+            return Err(BridgeError::LockFailed("shared input texture"));
+        }
+
+        unsafe {
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.draw_fbo);
+            gl::FramebufferTexture2D(
+                gl::DRAW_FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                input_gl,
+                0,
+            );
+            gl::DrawBuffer(gl::COLOR_ATTACHMENT0);
+            clear_draw_framebuffer();
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Flush();
+
+            self.unlock_gl_texture_front_input();
+        }
+
+        if !self.acquire_front_mutex() {
+            return Err(BridgeError::KeyedMutexTimeout {
+                timeout_ms: KEYED_MUTEX_TIMEOUT_MS,
+            });
+        }
+        Ok(())
     }
 
-    fn blit_back_output_to_target_scaled(
+    fn blit_back_output_to_target_region(
         &mut self,
         host_fbo: GLuint,
-        src_w: u32,
-        src_h: u32,
-        dst_w: u32,
-        dst_h: u32,
+        src: PixelRect,
+        dst: PixelRect,
         bilinear: bool,
-    ) -> bool {
+    ) -> Result<(), BridgeError> {
         let back = 1 - self.front;
         let output_gl = match &self.pairs[back] {
             Some(pair) => pair.output.gl_texture,
-            None => return false,
+            None => return Err(BridgeError::NotInitialized),
         };
 
         // Lock back output for GL access
         if unsafe { !self.lock_gl_texture_back_output() } {
-            warn!("Failed to lock back output GL texture for blit");
-            return false;
+            return Err(BridgeError::LockFailed("back output texture"));
         }
 
         unsafe {
@@ -841,14 +1211,14 @@ impl GpuBridge for GlDx11Bridge {
             let filter = if bilinear { gl::LINEAR } else { gl::NEAREST };
 
             gl::BlitFramebuffer(
-                0,
-                0,
-                src_w as GLsizei,
-                src_h as GLsizei,
-                0,
-                0,
-                dst_w as GLsizei,
-                dst_h as GLsizei,
+                src.x as GLint,
+                src.y as GLint,
+                (src.x + src.width) as GLint,
+                (src.y + src.height) as GLint,
+                dst.x as GLint,
+                dst.y as GLint,
+                (dst.x + dst.width) as GLint,
+                (dst.y + dst.height) as GLint,
                 gl::COLOR_BUFFER_BIT,
                 filter,
             );
@@ -858,27 +1228,24 @@ impl GpuBridge for GlDx11Bridge {
 
             self.unlock_gl_texture_back_output();
         }
-        true
+        Ok(())
     }
 
-    fn blit_output_to_target_scaled(
+    fn blit_output_to_target_region(
         &mut self,
         host_fbo: GLuint,
-        src_w: u32,
-        src_h: u32,
-        dst_w: u32,
-        dst_h: u32,
+        src: PixelRect,
+        dst: PixelRect,
         bilinear: bool,
-    ) -> bool {
+    ) -> Result<(), BridgeError> {
         let output_gl = match &self.pairs[self.front] {
             Some(pair) => pair.output.gl_texture,
-            None => return false,
+            None => return Err(BridgeError::NotInitialized),
         };
 
         // Lock front output for GL access
         if unsafe { !self.lock_gl_texture_front_output() } {
-            warn!("Failed to lock front output GL texture for blit");
-            return false;
+            return Err(BridgeError::LockFailed("front output texture"));
         }
 
         unsafe {
@@ -897,14 +1264,14 @@ impl GpuBridge for GlDx11Bridge {
             let filter = if bilinear { gl::LINEAR } else { gl::NEAREST };
 
             gl::BlitFramebuffer(
-                0,
-                0,
-                src_w as GLsizei,
-                src_h as GLsizei,
-                0,
-                0,
-                dst_w as GLsizei,
-                dst_h as GLsizei,
+                src.x as GLint,
+                src.y as GLint,
+                (src.x + src.width) as GLint,
+                (src.y + src.height) as GLint,
+                dst.x as GLint,
+                dst.y as GLint,
+                (dst.x + dst.width) as GLint,
+                (dst.y + dst.height) as GLint,
                 gl::COLOR_BUFFER_BIT,
                 filter,
             );
@@ -914,12 +1281,14 @@ impl GpuBridge for GlDx11Bridge {
 
             self.unlock_gl_texture_front_output();
         }
-        true
+        Ok(())
     }
 
     fn has_result_ready(&self, current_frame: u64) -> bool {
         self.pending_queries > 0
-            && self.last_dispatch_time.elapsed().as_millis() < 250
+            && self
+                .staleness_window
+                .is_none_or(|window| self.last_dispatch_time.elapsed() < window)
             && self
                 .last_dispatch_frame
                 .is_some_and(|last| current_frame == last.wrapping_add(1))
@@ -942,6 +1311,14 @@ impl GpuBridge for GlDx11Bridge {
         unsafe {
             self.context.End(&self.gpu_queries[slot]);
         }
+        if let Some(fence) = &mut self.fence {
+            if let Err(e) = fence.signal() {
+                crate::warn_limited!("Failed to signal D3D11 fence, using queries: {e}");
+                self.fence = None;
+            }
+        }
+        // All D3D11 work on the front pair is submitted; hand it back.
+        self.release_mutex();
         self.dispatch_count += 1;
         self.pending_queries = (self.pending_queries + 1).min(PIPELINE_DEPTH as u32);
         self.last_dispatch_frame = Some(frame);
@@ -951,6 +1328,8 @@ impl GpuBridge for GlDx11Bridge {
     fn cleanup(&mut self) {
         self.wait_for_gpu();
         self.destroy_pairs();
+        let pooled = self.pool.drain();
+        self.destroy_pooled(pooled);
         self.front = 0;
         self.last_dispatch_frame = None;
         unsafe {
@@ -970,6 +1349,73 @@ impl GpuBridge for GlDx11Bridge {
     fn dimensions(&self) -> (u32, u32) {
         self.dimensions
     }
+
+    fn memory_usage(&self) -> BridgeMemory {
+        let bytes = |&(width, height, format, _): &PairKey| pair_set_bytes(width, height, format);
+        BridgeMemory {
+            active: self.pair_key.as_ref().map_or(0, bytes),
+            pooled: self.pool.keys().map(bytes).sum(),
+        }
+    }
+
+    fn gl_context(&self) -> Option<GlContextId> {
+        self.gl_context
+    }
+
+    fn forget_gl_objects(&mut self) {
+        self.release_mutex();
+        for pair in self.pairs.iter_mut().flatten() {
+            pair.forget_gl();
+        }
+        for mut pairs in self.pool.drain() {
+            pairs.iter_mut().for_each(SharedTexturePair::forget_gl);
+        }
+        self.pairs = [None, None];
+        self.pair_key = None;
+        self.read_fbo = 0;
+        self.draw_fbo = 0;
+        // The interop device went away with its context.
+        self.interop_device = std::ptr::null_mut();
+        self.dimensions = (0, 0);
+    }
+}
+
+impl Dx11Bridge for GlDx11Bridge {
+    fn input_srv(&self) -> Option<ID3D11ShaderResourceView> {
+        GlDx11Bridge::input_srv(self)
+    }
+
+    fn output_uav(&self) -> Option<ID3D11UnorderedAccessView> {
+        GlDx11Bridge::output_uav(self)
+    }
+
+    fn input_texture(&self) -> Option<ID3D11Texture2D> {
+        GlDx11Bridge::input_texture(self)
+    }
+
+    fn output_texture(&self) -> Option<ID3D11Texture2D> {
+        GlDx11Bridge::output_texture(self)
+    }
+
+    fn back_output_srv(&self) -> Option<ID3D11ShaderResourceView> {
+        GlDx11Bridge::back_output_srv(self)
+    }
+
+    fn device(&self) -> &ID3D11Device {
+        GlDx11Bridge::device(self)
+    }
+
+    fn context(&self) -> &ID3D11DeviceContext {
+        GlDx11Bridge::context(self)
+    }
+
+    fn is_valid(&self) -> bool {
+        GlDx11Bridge::is_valid(self)
+    }
+
+    fn set_keyed_mutex(&mut self, enabled: bool) {
+        GlDx11Bridge::set_keyed_mutex(self, enabled)
+    }
 }
 
 // SAFETY: GlDx11Bridge contains a raw `*mut GLvoid` (the WGL interop device
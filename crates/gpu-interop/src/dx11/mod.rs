@@ -2,6 +2,47 @@
 
 pub mod device;
 pub mod interop;
+pub mod pbo;
 
 pub use device::{Dx11Device, create_dynamic_cbuf};
 pub use interop::GlDx11Bridge;
+pub use pbo::GlPboDx11Bridge;
+
+use windows::Win32::Graphics::Direct3D11::*;
+
+use crate::GpuBridge;
+
+/// D3D11 resources shared by the DX11 bridges.
+///
+/// [`GlDx11Bridge`] shares textures with GL through WGL_NV_DX_interop2;
+/// [`GlPboDx11Bridge`] copies them through pixel buffer objects on drivers
+/// where that fails. The draw loop holds either one through this trait.
+pub trait Dx11Bridge: GpuBridge {
+    /// SRV for the front input texture (read by compute shaders).
+    fn input_srv(&self) -> Option<ID3D11ShaderResourceView>;
+
+    /// UAV for the front output texture (written by compute shaders).
+    fn output_uav(&self) -> Option<ID3D11UnorderedAccessView>;
+
+    /// Front input texture.
+    fn input_texture(&self) -> Option<ID3D11Texture2D>;
+
+    /// Front output texture (for render pipeline targets).
+    fn output_texture(&self) -> Option<ID3D11Texture2D>;
+
+    /// SRV for the back output texture (previous frame's result).
+    fn back_output_srv(&self) -> Option<ID3D11ShaderResourceView>;
+
+    /// The D3D11 device the textures live on.
+    fn device(&self) -> &ID3D11Device;
+
+    /// The D3D11 immediate context.
+    fn context(&self) -> &ID3D11DeviceContext;
+
+    /// Check whether the bridge's GL handles are still valid.
+    fn is_valid(&self) -> bool;
+
+    /// Request keyed-mutex sharing (see [`GlDx11Bridge::set_keyed_mutex`]).
+    /// Ignored by bridges that don't share textures.
+    fn set_keyed_mutex(&mut self, enabled: bool);
+}
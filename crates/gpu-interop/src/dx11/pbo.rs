@@ -0,0 +1,698 @@
+//! GL-D3D11 bridge that copies frames through pixel buffer objects.
+//!
+//! Fallback for drivers where WGL_NV_DX_interop2 is missing or refuses to
+//! register the shared textures. Frames make a round trip through system
+//! memory: the input is read back from GL into a pack PBO and uploaded to
+//! D3D11 with `UpdateSubresource`, and the output is read back from D3D11
+//! through a staging texture and uploaded to GL through an unpack PBO.
+//!
+//! Reading the output back waits for the D3D11 work, so the bridge is
+//! single-buffered and never reports a pipelined result: every frame takes
+//! the synchronous path of the draw loop.
+
+use anyhow::{bail, Result};
+use gl::types::{GLenum, GLint, GLsizei, GLsizeiptr, GLuint};
+use tracing::error;
+use windows::Win32::Graphics::Direct3D11::*;
+use windows::Win32::Graphics::Dxgi::Common::*;
+
+use super::interop::dxgi_format;
+use super::Dx11Bridge;
+use crate::{
+    BridgeError, BridgeMemory, ColorSpace, GlContextId, GpuBridge, PixelRect, TextureFormat,
+};
+
+/// GL `(internal format, format, type)` with the same byte layout as the
+/// DXGI format of `format`, so rows copy between the APIs unchanged.
+fn gl_format(format: TextureFormat) -> (GLenum, GLenum, GLenum) {
+    match format {
+        TextureFormat::Rgba8 => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+        TextureFormat::Rgba16Float => (gl::RGBA16F, gl::RGBA, gl::HALF_FLOAT),
+        TextureFormat::Rgba32Float => (gl::RGBA32F, gl::RGBA, gl::FLOAT),
+    }
+}
+
+/// Run `f` with tightly packed rows for the pack or unpack pixel store,
+/// restoring the host's settings afterward.
+///
+/// # Safety
+/// A GL context must be current.
+unsafe fn with_tight_rows<R>(row_length: GLenum, alignment: GLenum, f: impl FnOnce() -> R) -> R {
+    let (mut saved_length, mut saved_alignment): (GLint, GLint) = (0, 0);
+    gl::GetIntegerv(row_length, &mut saved_length);
+    gl::GetIntegerv(alignment, &mut saved_alignment);
+    gl::PixelStorei(row_length, 0);
+    gl::PixelStorei(alignment, 4);
+    let result = f();
+    gl::PixelStorei(row_length, saved_length);
+    gl::PixelStorei(alignment, saved_alignment);
+    result
+}
+
+fn create_d3d_texture(
+    device: &ID3D11Device,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    usage: D3D11_USAGE,
+    bind_flags: u32,
+    cpu_access_flags: u32,
+) -> Option<ID3D11Texture2D> {
+    let desc = D3D11_TEXTURE2D_DESC {
+        Width: width,
+        Height: height,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: dxgi_format(format),
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: usage,
+        BindFlags: bind_flags,
+        CPUAccessFlags: cpu_access_flags,
+        MiscFlags: 0,
+    };
+    let mut texture = None;
+    unsafe { device.CreateTexture2D(&desc, None, Some(&mut texture as *mut _)) }.ok()?;
+    texture
+}
+
+/// Create a GL `TEXTURE_2D` matching `format`.
+///
+/// # Safety
+/// A GL context must be current.
+unsafe fn create_gl_texture(width: u32, height: u32, format: TextureFormat) -> GLuint {
+    let (internal_format, pixel_format, pixel_type) = gl_format(format);
+    let mut texture: GLuint = 0;
+    gl::GenTextures(1, &mut texture);
+    gl::BindTexture(gl::TEXTURE_2D, texture);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+    gl::TexImage2D(
+        gl::TEXTURE_2D,
+        0,
+        internal_format as GLint,
+        width as GLsizei,
+        height as GLsizei,
+        0,
+        pixel_format,
+        pixel_type,
+        std::ptr::null(),
+    );
+    gl::BindTexture(gl::TEXTURE_2D, 0);
+    texture
+}
+
+/// Create a pixel buffer object of `size` bytes bound to `target`.
+///
+/// # Safety
+/// A GL context must be current.
+unsafe fn create_pbo(target: GLenum, size: usize, usage: GLenum) -> GLuint {
+    let mut buffer: GLuint = 0;
+    gl::GenBuffers(1, &mut buffer);
+    gl::BindBuffer(target, buffer);
+    gl::BufferData(target, size as GLsizeiptr, std::ptr::null(), usage);
+    gl::BindBuffer(target, 0);
+    buffer
+}
+
+// ---------------------------------------------------------------------------
+// PboTextures
+// ---------------------------------------------------------------------------
+
+/// The D3D11 textures the plugin works on and their GL counterparts.
+struct PboTextures {
+    input: ID3D11Texture2D,
+    input_srv: ID3D11ShaderResourceView,
+    /// Lets [`GpuBridge::clear_input`] clear `input` on the GPU.
+    input_rtv: ID3D11RenderTargetView,
+    output: ID3D11Texture2D,
+    output_uav: ID3D11UnorderedAccessView,
+    output_srv: ID3D11ShaderResourceView,
+    /// CPU-readable copy of `output`.
+    staging: ID3D11Texture2D,
+    /// Receives the host input before it is read back into `pack_pbo`.
+    gl_input: GLuint,
+    /// Receives the output from `unpack_pbo` before it is blitted to the host.
+    gl_output: GLuint,
+    pack_pbo: GLuint,
+    unpack_pbo: GLuint,
+}
+
+impl PboTextures {
+    fn new(device: &ID3D11Device, width: u32, height: u32, format: TextureFormat) -> Option<Self> {
+        let input = create_d3d_texture(
+            device,
+            width,
+            height,
+            format,
+            D3D11_USAGE_DEFAULT,
+            (D3D11_BIND_SHADER_RESOURCE.0 | D3D11_BIND_RENDER_TARGET.0) as u32,
+            0,
+        )?;
+        // Output also needs RENDER_TARGET so render pipelines can draw to it.
+        let output = create_d3d_texture(
+            device,
+            width,
+            height,
+            format,
+            D3D11_USAGE_DEFAULT,
+            (D3D11_BIND_SHADER_RESOURCE.0
+                | D3D11_BIND_UNORDERED_ACCESS.0
+                | D3D11_BIND_RENDER_TARGET.0) as u32,
+            0,
+        )?;
+        let staging = create_d3d_texture(
+            device,
+            width,
+            height,
+            format,
+            D3D11_USAGE_STAGING,
+            0,
+            D3D11_CPU_ACCESS_READ.0 as u32,
+        )?;
+
+        let mut input_srv = None;
+        let mut input_rtv = None;
+        let mut output_uav = None;
+        let mut output_srv = None;
+        unsafe {
+            device
+                .CreateShaderResourceView(&input, None, Some(&mut input_srv as *mut _))
+                .ok()?;
+            device
+                .CreateRenderTargetView(&input, None, Some(&mut input_rtv as *mut _))
+                .ok()?;
+            device
+                .CreateUnorderedAccessView(&output, None, Some(&mut output_uav as *mut _))
+                .ok()?;
+            device
+                .CreateShaderResourceView(&output, None, Some(&mut output_srv as *mut _))
+                .ok()?;
+        }
+
+        let size = (width * height * format.bytes_per_pixel()) as usize;
+        let (gl_input, gl_output, pack_pbo, unpack_pbo) = unsafe {
+            (
+                create_gl_texture(width, height, format),
+                create_gl_texture(width, height, format),
+                create_pbo(gl::PIXEL_PACK_BUFFER, size, gl::STREAM_READ),
+                create_pbo(gl::PIXEL_UNPACK_BUFFER, size, gl::STREAM_DRAW),
+            )
+        };
+
+        let mut textures = Self {
+            input,
+            input_srv: input_srv?,
+            input_rtv: input_rtv?,
+            output,
+            output_uav: output_uav?,
+            output_srv: output_srv?,
+            staging,
+            gl_input,
+            gl_output,
+            pack_pbo,
+            unpack_pbo,
+        };
+        if [gl_input, gl_output, pack_pbo, unpack_pbo].contains(&0) {
+            error!("Failed to create GL objects for the PBO bridge");
+            textures.delete_gl_objects();
+            return None;
+        }
+        Some(textures)
+    }
+
+    fn delete_gl_objects(&mut self) {
+        unsafe {
+            for texture in [&mut self.gl_input, &mut self.gl_output] {
+                if *texture != 0 {
+                    gl::DeleteTextures(1, texture);
+                    *texture = 0;
+                }
+            }
+            for buffer in [&mut self.pack_pbo, &mut self.unpack_pbo] {
+                if *buffer != 0 {
+                    gl::DeleteBuffers(1, buffer);
+                    *buffer = 0;
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// GlPboDx11Bridge
+// ---------------------------------------------------------------------------
+
+/// Single-buffered bridge between OpenGL and D3D11 that copies frames
+/// through pixel buffer objects instead of sharing textures.
+///
+/// Slower than [`GlDx11Bridge`](super::GlDx11Bridge) but needs nothing beyond
+/// GL 3.0 and D3D11, so the draw loop switches to it when WGL_NV_DX_interop2
+/// is unavailable or fails to register the shared textures.
+pub struct GlPboDx11Bridge {
+    device: ID3D11Device,
+    context: ID3D11DeviceContext,
+    textures: Option<PboTextures>,
+    read_fbo: GLuint,
+    draw_fbo: GLuint,
+    dimensions: (u32, u32),
+    format: TextureFormat,
+    color_space: ColorSpace,
+    /// WGL context current when the bridge was created.
+    gl_context: Option<GlContextId>,
+}
+
+impl GlPboDx11Bridge {
+    /// Create an uninitialised bridge on a D3D11 device and its immediate
+    /// context. Call [`GpuBridge::ensure_dimensions`] before use.
+    pub fn new(device: &ID3D11Device, context: &ID3D11DeviceContext) -> Self {
+        Self {
+            device: device.clone(),
+            context: context.clone(),
+            textures: None,
+            read_fbo: 0,
+            draw_fbo: 0,
+            dimensions: (0, 0),
+            format: TextureFormat::Rgba16Float,
+            color_space: ColorSpace::Unmanaged,
+            gl_context: GlContextId::current(),
+        }
+    }
+
+    /// Read the D3D11 output back into the GL output texture, then blit its
+    /// `src` region into the `dst` region of the host FBO.
+    fn present(
+        &mut self,
+        host_fbo: GLuint,
+        src: PixelRect,
+        dst: PixelRect,
+        bilinear: bool,
+    ) -> Result<(), BridgeError> {
+        let Some(textures) = &self.textures else {
+            return Err(BridgeError::NotInitialized);
+        };
+        let (width, height) = self.dimensions;
+        let (_, pixel_format, pixel_type) = gl_format(self.format);
+        let row_bytes = (width * self.format.bytes_per_pixel()) as usize;
+
+        unsafe {
+            // Blocks until the D3D11 work writing the output has finished.
+            self.context
+                .CopyResource(&textures.staging, &textures.output);
+            let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+            if self
+                .context
+                .Map(&textures.staging, 0, D3D11_MAP_READ, 0, Some(&mut mapped))
+                .is_err()
+            {
+                return Err(BridgeError::MapFailed("D3D11 staging texture"));
+            }
+
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, textures.unpack_pbo);
+            // Orphan the previous upload so mapping doesn't wait on it.
+            gl::BufferData(
+                gl::PIXEL_UNPACK_BUFFER,
+                (row_bytes * height as usize) as GLsizeiptr,
+                std::ptr::null(),
+                gl::STREAM_DRAW,
+            );
+            let dst_ptr = gl::MapBuffer(gl::PIXEL_UNPACK_BUFFER, gl::WRITE_ONLY) as *mut u8;
+            if dst_ptr.is_null() {
+                gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+                self.context.Unmap(&textures.staging, 0);
+                return Err(BridgeError::MapFailed("GL unpack buffer"));
+            }
+            let src_ptr = mapped.pData as *const u8;
+            for y in 0..height as usize {
+                std::ptr::copy_nonoverlapping(
+                    src_ptr.add(y * mapped.RowPitch as usize),
+                    dst_ptr.add(y * row_bytes),
+                    row_bytes,
+                );
+            }
+            gl::UnmapBuffer(gl::PIXEL_UNPACK_BUFFER);
+            self.context.Unmap(&textures.staging, 0);
+
+            gl::BindTexture(gl::TEXTURE_2D, textures.gl_output);
+            with_tight_rows(gl::UNPACK_ROW_LENGTH, gl::UNPACK_ALIGNMENT, || {
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    width as GLsizei,
+                    height as GLsizei,
+                    pixel_format,
+                    pixel_type,
+                    std::ptr::null(),
+                )
+            });
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.read_fbo);
+            gl::FramebufferTexture2D(
+                gl::READ_FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                textures.gl_output,
+                0,
+            );
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, host_fbo);
+
+            let filter = if bilinear { gl::LINEAR } else { gl::NEAREST };
+
+            gl::BlitFramebuffer(
+                src.x as GLint,
+                src.y as GLint,
+                (src.x + src.width) as GLint,
+                (src.y + src.height) as GLint,
+                dst.x as GLint,
+                dst.y as GLint,
+                (dst.x + dst.width) as GLint,
+                (dst.y + dst.height) as GLint,
+                gl::COLOR_BUFFER_BIT,
+                filter,
+            );
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// GpuBridge trait implementation
+// ---------------------------------------------------------------------------
+
+impl GpuBridge for GlPboDx11Bridge {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn ensure_dimensions_with_format(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> Result<()> {
+        if self.dimensions == (width, height) && self.format == format && self.textures.is_some() {
+            return Ok(());
+        }
+
+        self.cleanup();
+
+        let Some(textures) = PboTextures::new(&self.device, width, height, format) else {
+            bail!("Failed to create PBO bridge textures");
+        };
+        self.textures = Some(textures);
+
+        unsafe {
+            gl::GenFramebuffers(1, &mut self.read_fbo);
+            gl::GenFramebuffers(1, &mut self.draw_fbo);
+        }
+
+        self.dimensions = (width, height);
+        self.format = format;
+        Ok(())
+    }
+
+    fn preferred_format(&self) -> TextureFormat {
+        TextureFormat::Rgba16Float
+    }
+
+    fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    fn set_color_space(&mut self, color_space: ColorSpace) {
+        // The textures hold linear or encoded values equally well, so a
+        // change does not require recreating them.
+        self.color_space = color_space;
+    }
+
+    fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    fn blit_input_from_host_region(
+        &mut self,
+        host_texture: GLuint,
+        src: PixelRect,
+        dst_w: u32,
+        dst_h: u32,
+        bilinear: bool,
+    ) -> Result<(), BridgeError> {
+        let Some(textures) = &self.textures else {
+            return Err(BridgeError::NotInitialized);
+        };
+        let (_, pixel_format, pixel_type) = gl_format(self.format);
+
+        unsafe {
+            // READ side: attach the host texture (always TEXTURE_2D on Windows)
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.read_fbo);
+            gl::FramebufferTexture2D(
+                gl::READ_FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                host_texture,
+                0,
+            );
+
+            if gl::CheckFramebufferStatus(gl::READ_FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                return Err(BridgeError::IncompleteFramebuffer {
+                    texture: host_texture,
+                });
+            }
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.draw_fbo);
+            gl::FramebufferTexture2D(
+                gl::DRAW_FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                textures.gl_input,
+                0,
+            );
+            gl::DrawBuffer(gl::COLOR_ATTACHMENT0);
+
+            let filter = if bilinear { gl::LINEAR } else { gl::NEAREST };
+
+            gl::BlitFramebuffer(
+                src.x as GLint,
+                src.y as GLint,
+                (src.x + src.width) as GLint,
+                (src.y + src.height) as GLint,
+                0,
+                0,
+                dst_w as GLsizei,
+                dst_h as GLsizei,
+                gl::COLOR_BUFFER_BIT,
+                filter,
+            );
+
+            // Read the scaled input back into the pack PBO. Mapping it right
+            // after ReadPixels waits for the read to finish; that is
+            // deliberate, since the bridge is single-buffered and the input
+            // has to be on the D3D11 side before this frame's dispatch.
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.draw_fbo);
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, textures.pack_pbo);
+            with_tight_rows(gl::PACK_ROW_LENGTH, gl::PACK_ALIGNMENT, || {
+                gl::ReadPixels(
+                    0,
+                    0,
+                    dst_w as GLsizei,
+                    dst_h as GLsizei,
+                    pixel_format,
+                    pixel_type,
+                    std::ptr::null_mut(),
+                )
+            });
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+            let data = gl::MapBuffer(gl::PIXEL_PACK_BUFFER, gl::READ_ONLY);
+            if data.is_null() {
+                gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+                return Err(BridgeError::MapFailed("GL pack buffer"));
+            }
+            let region = D3D11_BOX {
+                left: 0,
+                top: 0,
+                front: 0,
+                right: dst_w,
+                bottom: dst_h,
+                back: 1,
+            };
+            self.context.UpdateSubresource(
+                &textures.input,
+                0,
+                Some(&region),
+                data,
+                dst_w * self.format.bytes_per_pixel(),
+                0,
+            );
+            gl::UnmapBuffer(gl::PIXEL_PACK_BUFFER);
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+        }
+        Ok(())
+    }
+
+    fn clear_input(&mut self) -> Result<(), BridgeError> {
+        let Some(textures) = &self.textures else {
+            return Err(BridgeError::NotInitialized);
+        };
+        unsafe {
+            self.context
+                .ClearRenderTargetView(&textures.input_rtv, &[0.0; 4]);
+        }
+        Ok(())
+    }
+
+    fn blit_back_output_to_target_region(
+        &mut self,
+        host_fbo: GLuint,
+        src: PixelRect,
+        dst: PixelRect,
+        bilinear: bool,
+    ) -> Result<(), BridgeError> {
+        // Single-buffered: the back output is the front output.
+        self.present(host_fbo, src, dst, bilinear)
+    }
+
+    fn blit_output_to_target_region(
+        &mut self,
+        host_fbo: GLuint,
+        src: PixelRect,
+        dst: PixelRect,
+        bilinear: bool,
+    ) -> Result<(), BridgeError> {
+        self.present(host_fbo, src, dst, bilinear)
+    }
+
+    fn has_result_ready(&self, _current_frame: u64) -> bool {
+        // Never pipelined; the output is read back in the same frame.
+        false
+    }
+
+    fn wait_for_previous(&mut self) {
+        // Reading the output back already waits for the D3D11 work.
+    }
+
+    fn wait_for_pending(&mut self) {}
+
+    fn swap(&mut self) {}
+
+    fn mark_dispatch(&mut self, _frame: u64) {
+        unsafe { self.context.Flush() };
+    }
+
+    fn cleanup(&mut self) {
+        if let Some(mut textures) = self.textures.take() {
+            textures.delete_gl_objects();
+        }
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            if self.read_fbo != 0 {
+                gl::DeleteFramebuffers(1, &self.read_fbo);
+                self.read_fbo = 0;
+            }
+            if self.draw_fbo != 0 {
+                gl::DeleteFramebuffers(1, &self.draw_fbo);
+                self.draw_fbo = 0;
+            }
+        }
+        self.dimensions = (0, 0);
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        self.dimensions
+    }
+
+    fn memory_usage(&self) -> BridgeMemory {
+        if self.textures.is_none() {
+            return BridgeMemory::default();
+        }
+        // Three D3D11 textures (input, output, staging), two GL textures and
+        // two PBOs, all one frame in size.
+        let (width, height) = self.dimensions;
+        BridgeMemory {
+            active: 7 * width as u64 * height as u64 * self.format.bytes_per_pixel() as u64,
+            pooled: 0,
+        }
+    }
+
+    fn gl_context(&self) -> Option<GlContextId> {
+        self.gl_context
+    }
+
+    fn forget_gl_objects(&mut self) {
+        // PboTextures only deletes its GL objects when asked to.
+        self.textures = None;
+        self.read_fbo = 0;
+        self.draw_fbo = 0;
+        self.dimensions = (0, 0);
+    }
+}
+
+impl Dx11Bridge for GlPboDx11Bridge {
+    fn input_srv(&self) -> Option<ID3D11ShaderResourceView> {
+        Some(self.textures.as_ref()?.input_srv.clone())
+    }
+
+    fn output_uav(&self) -> Option<ID3D11UnorderedAccessView> {
+        Some(self.textures.as_ref()?.output_uav.clone())
+    }
+
+    fn input_texture(&self) -> Option<ID3D11Texture2D> {
+        Some(self.textures.as_ref()?.input.clone())
+    }
+
+    fn output_texture(&self) -> Option<ID3D11Texture2D> {
+        Some(self.textures.as_ref()?.output.clone())
+    }
+
+    fn back_output_srv(&self) -> Option<ID3D11ShaderResourceView> {
+        // Single-buffered: the back output is the front output.
+        Some(self.textures.as_ref()?.output_srv.clone())
+    }
+
+    fn device(&self) -> &ID3D11Device {
+        &self.device
+    }
+
+    fn context(&self) -> &ID3D11DeviceContext {
+        &self.context
+    }
+
+    fn is_valid(&self) -> bool {
+        if self.read_fbo == 0 && self.draw_fbo == 0 {
+            return self.dimensions == (0, 0); // not yet initialised is valid
+        }
+        unsafe { gl::IsFramebuffer(self.read_fbo) != 0 && gl::IsFramebuffer(self.draw_fbo) != 0 }
+    }
+
+    fn set_keyed_mutex(&mut self, _enabled: bool) {
+        // Nothing is shared, so there is nothing to guard.
+    }
+}
+
+impl Drop for GlPboDx11Bridge {
+    fn drop(&mut self) {
+        // Only delete GL resources if a GL context is still current (see
+        // GlDx11Bridge's Drop).
+        let has_context = unsafe { !gl::GetString(gl::VERSION).is_null() };
+        if has_context {
+            self.cleanup();
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Identity of the host's native GL context.
+//!
+//! GL object names and interop registrations belong to the context they were
+//! created in. Hosts occasionally destroy and recreate their context (e.g.
+//! when moving a window between GPUs), which leaves a bridge holding names
+//! that are either dangling or refer to unrelated objects in the new context.
+//! Bridges record the context they were created in so callers can detect this.
+
+/// Opaque identity of a native GL context (the CGL or WGL context pointer).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlContextId(usize);
+
+impl GlContextId {
+    /// The context current on the calling thread, or `None` if there is none
+    /// (or the platform has no native context to query).
+    pub fn current() -> Option<Self> {
+        #[cfg(target_os = "macos")]
+        let raw = unsafe { objc2_open_gl::CGLGetCurrentContext() } as usize;
+        #[cfg(target_os = "windows")]
+        let raw = unsafe { windows::Win32::Graphics::OpenGL::wglGetCurrentContext() }.0 as usize;
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let raw = 0;
+        (raw != 0).then_some(Self(raw))
+    }
+}
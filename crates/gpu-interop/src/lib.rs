@@ -5,7 +5,17 @@
 //! Direct3D 11 on Windows) and back.
 
 pub mod bridge;
-pub use bridge::GpuBridge;
+pub mod gl_context;
+pub mod rate_limit;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+mod pool;
+pub use bridge::{
+    BridgeError, BridgeMemory, ColorSpace, GpuBridge, PixelRect, SurfaceCacheMode,
+    SurfaceColorSpace, SurfaceOptions, TextureFormat, DEFAULT_STALENESS_WINDOW,
+};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub use pool::DEFAULT_POOL_CAPACITY;
+pub use gl_context::GlContextId;
 
 // Platform-specific implementations.
 // These modules will be populated in subsequent tasks.
@@ -9,7 +9,7 @@
 // FFGL hosts that provide an OpenGL context.
 #![allow(deprecated)]
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::{bail, Result};
 use gl::types::{GLenum, GLint, GLsizei, GLuint};
@@ -18,20 +18,35 @@ use objc2::runtime::ProtocolObject;
 use objc2_core_foundation::{CFDictionary, CFNumber, CFRetained, CFString};
 use objc2_io_surface::IOSurfaceRef;
 use objc2_metal::{
-    MTLCommandBuffer, MTLDevice, MTLPixelFormat, MTLStorageMode, MTLTexture,
-    MTLTextureDescriptor, MTLTextureType, MTLTextureUsage,
+    MTLCommandBuffer, MTLCommandBufferStatus, MTLCommandQueue, MTLDevice, MTLPixelFormat,
+    MTLSharedEvent, MTLStorageMode, MTLTexture, MTLTextureDescriptor, MTLTextureType,
+    MTLTextureUsage,
 };
 use objc2_open_gl::{CGLError, CGLGetCurrentContext, CGLTexImageIOSurface2D};
-use tracing::{error, warn};
+use tracing::error;
 
-use crate::GpuBridge;
+use crate::bridge::clear_draw_framebuffer;
+use crate::pool::{pair_set_bytes, PairPool, DEFAULT_POOL_CAPACITY};
+use crate::{
+    BridgeError, BridgeMemory, ColorSpace, GlContextId, GpuBridge, PixelRect, SurfaceOptions,
+    TextureFormat, DEFAULT_STALENESS_WINDOW,
+};
 
 /// Pixel format FourCC for BGRA8 ('BGRA' = 0x42475241).
 const IOSURFACE_PIXEL_FORMAT_BGRA: u32 = 0x42475241;
 
+/// Pixel format FourCC for RGBA half float ('RGhA' = 0x52476841).
+const IOSURFACE_PIXEL_FORMAT_RGBA_HALF: u32 = 0x52476841;
+
+/// Pixel format FourCC for RGBA float ('RGfA' = 0x52476641).
+const IOSURFACE_PIXEL_FORMAT_RGBA_FLOAT: u32 = 0x52476641;
+
 /// `GL_TEXTURE_RECTANGLE` is not in the `gl` crate's default API.
 const GL_TEXTURE_RECTANGLE: GLenum = 0x84F5;
 
+/// Default for [`GlMetalBridge::set_sync_timeout`].
+const DEFAULT_SYNC_TIMEOUT: Duration = Duration::from_millis(100);
+
 // ---------------------------------------------------------------------------
 // Internal helpers
 // ---------------------------------------------------------------------------
@@ -45,11 +60,25 @@ struct SharedTexture {
 }
 
 impl SharedTexture {
-    fn new(device: &ProtocolObject<dyn MTLDevice>, width: u32, height: u32) -> Option<Self> {
-        let iosurface = create_iosurface(width, height)?;
-        let gl_texture = unsafe { create_gl_texture_from_iosurface(&iosurface, width, height)? };
-        let metal_texture =
-            create_metal_texture_from_iosurface(device, &iosurface, width, height)?;
+    fn new(
+        device: &ProtocolObject<dyn MTLDevice>,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        color_space: ColorSpace,
+        options: SurfaceOptions,
+    ) -> Option<Self> {
+        let iosurface = create_iosurface(width, height, format, color_space, options)?;
+        let gl_texture =
+            unsafe { create_gl_texture_from_iosurface(&iosurface, width, height, format)? };
+        let metal_texture = create_metal_texture_from_iosurface(
+            device,
+            &iosurface,
+            width,
+            height,
+            format,
+            color_space,
+        )?;
 
         Some(Self {
             _iosurface: iosurface,
@@ -70,6 +99,10 @@ impl Drop for SharedTexture {
     }
 }
 
+/// What a set of pairs was created for: dimensions, format, color space and
+/// surface options.
+type PairKey = (u32, u32, TextureFormat, ColorSpace, SurfaceOptions);
+
 /// A paired input/output IOSurface set for one frame slot.
 struct IoSurfacePair {
     input: SharedTexture,
@@ -77,37 +110,75 @@ struct IoSurfacePair {
 }
 
 impl IoSurfacePair {
-    fn new(device: &ProtocolObject<dyn MTLDevice>, width: u32, height: u32) -> Option<Self> {
+    fn new(
+        device: &ProtocolObject<dyn MTLDevice>,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        color_space: ColorSpace,
+        options: SurfaceOptions,
+    ) -> Option<Self> {
         Some(Self {
-            input: SharedTexture::new(device, width, height)?,
-            output: SharedTexture::new(device, width, height)?,
+            input: SharedTexture::new(device, width, height, format, color_space, options)?,
+            output: SharedTexture::new(device, width, height, format, color_space, options)?,
         })
     }
+
+    /// Give up the GL texture names without deleting them.
+    fn forget_gl(&mut self) {
+        self.input.gl_texture = 0;
+        self.output.gl_texture = 0;
+    }
 }
 
 // ---------------------------------------------------------------------------
 // IOSurface / texture creation
 // ---------------------------------------------------------------------------
 
-/// Create an IOSurface with BGRA8 pixel format via the CoreFoundation API.
-fn create_iosurface(width: u32, height: u32) -> Option<CFRetained<IOSurfaceRef>> {
+/// Create an IOSurface of the given format via the CoreFoundation API.
+///
+/// The surface is tagged with the color space `options` ask for, by default
+/// sRGB with [`ColorSpace::Srgb`] so the system interprets its contents
+/// consistently with the host, and created with their cache mode.
+fn create_iosurface(
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    color_space: ColorSpace,
+    options: SurfaceOptions,
+) -> Option<CFRetained<IOSurfaceRef>> {
+    let pixel_format = match format {
+        TextureFormat::Rgba8 => IOSURFACE_PIXEL_FORMAT_BGRA,
+        TextureFormat::Rgba16Float => IOSURFACE_PIXEL_FORMAT_RGBA_HALF,
+        TextureFormat::Rgba32Float => IOSURFACE_PIXEL_FORMAT_RGBA_FLOAT,
+    };
     unsafe {
         let k_width = objc2_io_surface::kIOSurfaceWidth;
         let k_height = objc2_io_surface::kIOSurfaceHeight;
         let k_bpe = objc2_io_surface::kIOSurfaceBytesPerElement;
         let k_pf = objc2_io_surface::kIOSurfacePixelFormat;
+        let k_cache = objc2_io_surface::kIOSurfaceCacheMode;
 
         let v_width = CFNumber::new_i32(width as i32);
         let v_height = CFNumber::new_i32(height as i32);
-        let v_bpe = CFNumber::new_i32(4);
-        let v_pf = CFNumber::new_i32(IOSURFACE_PIXEL_FORMAT_BGRA as i32);
+        let v_bpe = CFNumber::new_i32(format.bytes_per_pixel() as i32);
+        let v_pf = CFNumber::new_i32(pixel_format as i32);
+        let v_cache = CFNumber::new_i32(options.cache_mode.value() as i32);
 
-        let keys: &[&CFString] = &[k_width, k_height, k_bpe, k_pf];
-        let values: &[&CFNumber] = &[&v_width, &v_height, &v_bpe, &v_pf];
+        let keys: &[&CFString] = &[k_width, k_height, k_bpe, k_pf, k_cache];
+        let values: &[&CFNumber] = &[&v_width, &v_height, &v_bpe, &v_pf, &v_cache];
 
         let props = CFDictionary::from_slices(keys, values);
         let props_untyped: &CFDictionary = props.cast_unchecked();
-        IOSurfaceRef::new(props_untyped)
+        let surface = IOSurfaceRef::new(props_untyped)?;
+
+        // CoreGraphics' color space name constants hold their own names.
+        if let Some(name) = options.color_space.name(color_space) {
+            let name = CFString::from_static_str(name);
+            surface.set_value(objc2_io_surface::kIOSurfaceColorSpace, &name);
+        }
+
+        Some(surface)
     }
 }
 
@@ -120,6 +191,7 @@ unsafe fn create_gl_texture_from_iosurface(
     surface: &IOSurfaceRef,
     width: u32,
     height: u32,
+    format: TextureFormat,
 ) -> Option<GLuint> {
     let cgl_ctx = CGLGetCurrentContext();
     if cgl_ctx.is_null() {
@@ -127,6 +199,12 @@ unsafe fn create_gl_texture_from_iosurface(
         return None;
     }
 
+    let (internal_format, pixel_format, pixel_type) = match format {
+        TextureFormat::Rgba8 => (gl::RGBA, gl::BGRA, gl::UNSIGNED_INT_8_8_8_8_REV),
+        TextureFormat::Rgba16Float => (gl::RGBA16F, gl::RGBA, gl::HALF_FLOAT),
+        TextureFormat::Rgba32Float => (gl::RGBA32F, gl::RGBA, gl::FLOAT),
+    };
+
     let mut tex: GLuint = 0;
     gl::GenTextures(1, &mut tex);
     gl::BindTexture(GL_TEXTURE_RECTANGLE, tex);
@@ -145,11 +223,11 @@ unsafe fn create_gl_texture_from_iosurface(
     let err = CGLTexImageIOSurface2D(
         cgl_ctx,
         GL_TEXTURE_RECTANGLE,
-        gl::RGBA as GLenum,
+        internal_format as GLenum,
         width as GLsizei,
         height as GLsizei,
-        gl::BGRA,
-        gl::UNSIGNED_INT_8_8_8_8_REV,
+        pixel_format,
+        pixel_type,
         surface,
         0, // plane
     );
@@ -166,21 +244,37 @@ unsafe fn create_gl_texture_from_iosurface(
 }
 
 /// Create a Metal texture backed by an IOSurface.
+///
+/// With [`ColorSpace::Srgb`] the texture uses `BGRA8Unorm_sRGB`, so shader
+/// reads decode to linear and writes encode back to sRGB in hardware. Only
+/// [`TextureFormat::Rgba8`] has an sRGB variant.
 fn create_metal_texture_from_iosurface(
     device: &ProtocolObject<dyn MTLDevice>,
     surface: &IOSurfaceRef,
     width: u32,
     height: u32,
+    format: TextureFormat,
+    color_space: ColorSpace,
 ) -> Option<Retained<ProtocolObject<dyn MTLTexture>>> {
     let desc = MTLTextureDescriptor::new();
     desc.setTextureType(MTLTextureType::Type2D);
-    desc.setPixelFormat(MTLPixelFormat::BGRA8Unorm);
+    desc.setPixelFormat(match (format, color_space) {
+        (TextureFormat::Rgba8, ColorSpace::Unmanaged) => MTLPixelFormat::BGRA8Unorm,
+        (TextureFormat::Rgba8, ColorSpace::Srgb) => MTLPixelFormat::BGRA8Unorm_sRGB,
+        (TextureFormat::Rgba16Float, _) => MTLPixelFormat::RGBA16Float,
+        (TextureFormat::Rgba32Float, _) => MTLPixelFormat::RGBA32Float,
+    });
     unsafe {
         desc.setWidth(width as usize);
         desc.setHeight(height as usize);
     }
     desc.setStorageMode(MTLStorageMode::Shared);
-    desc.setUsage(MTLTextureUsage::ShaderRead | MTLTextureUsage::ShaderWrite);
+    let mut usage = MTLTextureUsage::ShaderRead | MTLTextureUsage::ShaderWrite;
+    if color_space == ColorSpace::Srgb && format == TextureFormat::Rgba8 {
+        // Allows a non-sRGB view for passes that work on the stored values.
+        usage |= MTLTextureUsage::PixelFormatView;
+    }
+    desc.setUsage(usage);
 
     device.newTextureWithDescriptor_iosurface_plane(&desc, surface, 0)
 }
@@ -201,10 +295,27 @@ pub struct GlMetalBridge {
     device: Retained<ProtocolObject<dyn MTLDevice>>,
     /// Double-buffered IOSurface pairs.
     pairs: [Option<IoSurfacePair>; 2],
+    /// What the current pairs were created for.
+    pair_key: Option<PairKey>,
+    /// Pairs retired by earlier dimension changes, kept for reuse.
+    pool: PairPool<PairKey, [IoSurfacePair; 2]>,
     /// Index of the pair currently being written by Metal compute.
     front: usize,
     /// The command buffer from the most recent Metal dispatch, if any.
     pending_command_buffer: Option<Retained<ProtocolObject<dyn MTLCommandBuffer>>>,
+    /// Shared event signalled on the command queue after each stored
+    /// command buffer, so waits can poll it and give up after
+    /// `sync_timeout` instead of blocking in `waitUntilCompleted`.
+    event: Option<Retained<ProtocolObject<dyn MTLSharedEvent>>>,
+    /// Event value signalled after the pending command buffer.
+    pending_event_value: Option<u64>,
+    /// The last [`GpuBridge::wait_for_previous`] timed out, so the back
+    /// output may still be being written and must not be presented.
+    previous_timed_out: bool,
+    /// Last event value handed out.
+    event_value: u64,
+    /// Longest a wait for Metal work may block the render thread.
+    sync_timeout: Duration,
     /// Frame counter from the most recent draw call that dispatched Metal
     /// compute.  Used to detect gaps (deselection) -- if the current frame
     /// counter is not `last_frame + 1`, we had a gap and must not use stale
@@ -214,6 +325,8 @@ pub struct GlMetalBridge {
     /// back-buffer data after deselection/reselection (where the frame counter
     /// is consecutive but real time has a gap).
     last_dispatch_time: Option<Instant>,
+    /// How old the last dispatch may be for its result to be presented.
+    staleness_window: Option<Duration>,
     read_fbo: GLuint,
     draw_fbo: GLuint,
     dimensions: (u32, u32),
@@ -221,23 +334,44 @@ pub struct GlMetalBridge {
     /// (`TEXTURE_2D` or `TEXTURE_RECTANGLE`).  Zero means not yet probed --
     /// will be determined on first blit and cached.
     host_texture_type: GLenum,
+    /// Color space the shared textures are created with.
+    color_space: ColorSpace,
+    /// IOSurface attributes the shared textures are created with.
+    surface_options: SurfaceOptions,
+    /// Pixel format the shared textures are created with.
+    format: TextureFormat,
+    /// CGL context current when the bridge was created.
+    gl_context: Option<GlContextId>,
 }
 
 impl GlMetalBridge {
     /// Create an uninitialised bridge.  Call [`GpuBridge::ensure_dimensions`]
     /// before use.
     pub fn new(device: Retained<ProtocolObject<dyn MTLDevice>>) -> Self {
+        let event = device.newSharedEvent();
         Self {
             device,
             pairs: [None, None],
+            pair_key: None,
+            pool: PairPool::new(DEFAULT_POOL_CAPACITY),
             front: 0,
             pending_command_buffer: None,
+            event,
+            pending_event_value: None,
+            previous_timed_out: false,
+            event_value: 0,
+            sync_timeout: DEFAULT_SYNC_TIMEOUT,
             last_dispatch_frame: None,
             last_dispatch_time: None,
+            staleness_window: Some(DEFAULT_STALENESS_WINDOW),
             read_fbo: 0,
             draw_fbo: 0,
             dimensions: (0, 0),
             host_texture_type: 0,
+            color_space: ColorSpace::Unmanaged,
+            surface_options: SurfaceOptions::default(),
+            format: TextureFormat::Rgba8,
+            gl_context: GlContextId::current(),
         }
     }
 
@@ -250,9 +384,99 @@ impl GlMetalBridge {
         &mut self,
         command_buffer: Retained<ProtocolObject<dyn MTLCommandBuffer>>,
     ) {
+        self.pending_event_value = self.signal_after(&command_buffer);
         self.pending_command_buffer = Some(command_buffer);
     }
 
+    /// Set how long waiting for in-flight Metal work may block the render
+    /// thread before the bridge gives up and proceeds (default 100 ms).
+    pub fn set_sync_timeout(&mut self, timeout: Duration) {
+        self.sync_timeout = timeout;
+    }
+
+    /// Current wait timeout (see [`set_sync_timeout`](Self::set_sync_timeout)).
+    pub fn sync_timeout(&self) -> Duration {
+        self.sync_timeout
+    }
+
+    /// Set the color space tag and cache mode of the IOSurfaces behind the
+    /// shared textures. A change recreates them on the next
+    /// [`GpuBridge::ensure_dimensions`].
+    pub fn set_surface_options(&mut self, options: SurfaceOptions) {
+        if self.surface_options != options {
+            self.surface_options = options;
+            // Force ensure_dimensions to recreate the textures.
+            self.dimensions = (0, 0);
+        }
+    }
+
+    /// Current IOSurface attributes (see
+    /// [`set_surface_options`](Self::set_surface_options)).
+    pub fn surface_options(&self) -> SurfaceOptions {
+        self.surface_options
+    }
+
+    /// Arrange for the shared event to be signalled once `command_buffer`
+    /// completes, returning the value to wait for. Encoded on the command
+    /// buffer itself if it has not been committed yet, otherwise on a small
+    /// command buffer queued behind it.
+    fn signal_after(
+        &mut self,
+        command_buffer: &ProtocolObject<dyn MTLCommandBuffer>,
+    ) -> Option<u64> {
+        let event = self.event.as_ref()?;
+        let value = self.event_value + 1;
+        if command_buffer.status() == MTLCommandBufferStatus::NotEnqueued {
+            command_buffer.encodeSignalEvent_value(ProtocolObject::from_ref(&**event), value);
+        } else {
+            let signal = command_buffer.commandQueue().commandBuffer()?;
+            signal.encodeSignalEvent_value(ProtocolObject::from_ref(&**event), value);
+            signal.commit();
+        }
+        self.event_value = value;
+        Some(value)
+    }
+
+    /// Wait for the pending command buffer. Returns at once if it already
+    /// finished; otherwise waits on the shared event for at most
+    /// `sync_timeout`. `waitUntilCompleted` is only used without an event.
+    /// Returns `false` if the wait timed out.
+    fn wait_for_command_buffer(&self) -> bool {
+        let Some(cb) = &self.pending_command_buffer else {
+            return true;
+        };
+        let status = cb.status();
+        if status == MTLCommandBufferStatus::Completed || status == MTLCommandBufferStatus::Error {
+            return true;
+        }
+        match (&self.event, self.pending_event_value) {
+            (Some(event), Some(value)) => {
+                if event.signaledValue() >= value {
+                    return true;
+                }
+                let timeout_ms = self.sync_timeout.as_millis() as u64;
+                if !event.waitUntilSignaledValue_timeoutMS(value, timeout_ms) {
+                    crate::warn_limited!(timeout_ms, "Metal command buffer wait timed out");
+                    return false;
+                }
+                true
+            }
+            _ => {
+                cb.waitUntilCompleted();
+                true
+            }
+        }
+    }
+
+    /// Park the current pairs in the pool so a later switch back to their
+    /// dimensions can reuse them. Pairs evicted from the pool are dropped.
+    fn retire_pairs(&mut self) {
+        let pairs = std::mem::take(&mut self.pairs);
+        if let (Some(key), [Some(a), Some(b)]) = (self.pair_key.take(), pairs) {
+            self.pool.put(key, [a, b]);
+        }
+    }
+
     /// Get the Metal texture for the front input (read by compute shaders).
     pub fn input_metal_texture(&self) -> Option<&ProtocolObject<dyn MTLTexture>> {
         self.pairs[self.front]
@@ -301,16 +525,27 @@ impl GpuBridge for GlMetalBridge {
         self
     }
 
-    fn ensure_dimensions(&mut self, width: u32, height: u32) -> Result<()> {
+    fn ensure_dimensions_with_format(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> Result<()> {
         if self.dimensions == (width, height)
+            && self.format == format
             && self.pairs[0].is_some()
             && self.pairs[1].is_some()
         {
             return Ok(());
         }
+        if self.color_space == ColorSpace::Srgb && format != TextureFormat::Rgba8 {
+            bail!("sRGB color space requires Rgba8 shared textures, got {format:?}");
+        }
 
-        // Dimension change: wait for any in-flight work before destroying textures.
+        // Dimension change: wait for any in-flight work before parking the
+        // current textures.
         self.wait_for_previous();
+        self.retire_pairs();
 
         // Clean up old FBOs (unbind first to avoid deleting a bound FBO).
         unsafe {
@@ -323,16 +558,28 @@ impl GpuBridge for GlMetalBridge {
             }
         }
 
-        self.pairs[0] = IoSurfacePair::new(&self.device, width, height);
-        self.pairs[1] = IoSurfacePair::new(&self.device, width, height);
-
-        if self.pairs[0].is_none() || self.pairs[1].is_none() {
-            self.pairs = [None, None];
-            self.read_fbo = 0;
-            self.draw_fbo = 0;
-            self.dimensions = (0, 0);
-            bail!("Failed to create shared IOSurface texture pairs");
-        }
+        let (color_space, options) = (self.color_space, self.surface_options);
+        let key = (width, height, format, color_space, options);
+        let pairs = match self.pool.take(&key) {
+            Some(pairs) => pairs,
+            None => {
+                let first =
+                    IoSurfacePair::new(&self.device, width, height, format, color_space, options);
+                let second =
+                    IoSurfacePair::new(&self.device, width, height, format, color_space, options);
+                match (first, second) {
+                    (Some(a), Some(b)) => [a, b],
+                    _ => {
+                        self.read_fbo = 0;
+                        self.draw_fbo = 0;
+                        self.dimensions = (0, 0);
+                        bail!("Failed to create shared IOSurface texture pairs");
+                    }
+                }
+            }
+        };
+        self.pairs = pairs.map(Some);
+        self.pair_key = Some(key);
 
         // Create separate FBOs for read and draw to avoid undefined behaviour.
         unsafe {
@@ -341,6 +588,7 @@ impl GpuBridge for GlMetalBridge {
         }
 
         self.dimensions = (width, height);
+        self.format = format;
         self.front = 0;
         self.last_dispatch_frame = None;
         self.last_dispatch_time = None;
@@ -348,18 +596,45 @@ impl GpuBridge for GlMetalBridge {
         Ok(())
     }
 
-    fn blit_input_from_host_scaled(
+    fn set_color_space(&mut self, color_space: ColorSpace) {
+        if self.color_space != color_space {
+            self.color_space = color_space;
+            // Force ensure_dimensions to recreate the textures.
+            self.dimensions = (0, 0);
+        }
+    }
+
+    fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    fn preferred_format(&self) -> TextureFormat {
+        TextureFormat::Rgba8
+    }
+
+    fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    fn set_pool_capacity(&mut self, capacity: usize) {
+        self.pool.set_capacity(capacity);
+    }
+
+    fn set_staleness_window(&mut self, window: Option<Duration>) {
+        self.staleness_window = window;
+    }
+
+    fn blit_input_from_host_region(
         &mut self,
         host_texture: GLuint,
-        src_w: u32,
-        src_h: u32,
+        src: PixelRect,
         dst_w: u32,
         dst_h: u32,
         bilinear: bool,
-    ) -> bool {
+    ) -> Result<(), BridgeError> {
         let input_gl = match &self.pairs[self.front] {
             Some(pair) => pair.input.gl_texture,
-            None => return false,
+            None => return Err(BridgeError::NotInitialized),
         };
 
         unsafe {
@@ -388,11 +663,10 @@ impl GpuBridge for GlMetalBridge {
                     {
                         self.host_texture_type = GL_TEXTURE_RECTANGLE;
                     } else {
-                        warn!(
-                            "READ_FRAMEBUFFER incomplete for host texture {host_texture}"
-                        );
                         gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-                        return false;
+                        return Err(BridgeError::IncompleteFramebuffer {
+                            texture: host_texture,
+                        });
                     }
                 }
             } else {
@@ -420,10 +694,10 @@ impl GpuBridge for GlMetalBridge {
             let filter = if bilinear { gl::LINEAR } else { gl::NEAREST };
 
             gl::BlitFramebuffer(
-                0,
-                0,
-                src_w as GLsizei,
-                src_h as GLsizei,
+                src.x as GLint,
+                src.y as GLint,
+                (src.x + src.width) as GLint,
+                (src.y + src.height) as GLint,
                 0,
                 0,
                 dst_w as GLsizei,
@@ -435,22 +709,49 @@ impl GpuBridge for GlMetalBridge {
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
             gl::Flush();
         }
-        true
+        Ok(())
+    }
+
+    fn clear_input(&mut self) -> Result<(), BridgeError> {
+        let input_gl = match &self.pairs[self.front] {
+            Some(pair) => pair.input.gl_texture,
+            None => return Err(BridgeError::NotInitialized),
+        };
+
+        unsafe {
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.draw_fbo);
+            gl::FramebufferTexture2D(
+                gl::DRAW_FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                GL_TEXTURE_RECTANGLE,
+                input_gl,
+                0,
+            );
+            gl::DrawBuffer(gl::COLOR_ATTACHMENT0);
+            clear_draw_framebuffer();
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Flush();
+        }
+        Ok(())
     }
 
-    fn blit_back_output_to_target_scaled(
+    fn blit_back_output_to_target_region(
         &mut self,
         host_fbo: GLuint,
-        src_w: u32,
-        src_h: u32,
-        dst_w: u32,
-        dst_h: u32,
+        src: PixelRect,
+        dst: PixelRect,
         bilinear: bool,
-    ) -> bool {
+    ) -> Result<(), BridgeError> {
+        if self.previous_timed_out {
+            return Err(BridgeError::BackOutputBusy {
+                timeout_ms: self.sync_timeout.as_millis() as u64,
+            });
+        }
         let back = 1 - self.front;
         let output_gl = match &self.pairs[back] {
             Some(pair) => pair.output.gl_texture,
-            None => return false,
+            None => return Err(BridgeError::NotInitialized),
         };
 
         unsafe {
@@ -469,35 +770,33 @@ impl GpuBridge for GlMetalBridge {
             let filter = if bilinear { gl::LINEAR } else { gl::NEAREST };
 
             gl::BlitFramebuffer(
-                0,
-                0,
-                src_w as GLsizei,
-                src_h as GLsizei,
-                0,
-                0,
-                dst_w as GLsizei,
-                dst_h as GLsizei,
+                src.x as GLint,
+                src.y as GLint,
+                (src.x + src.width) as GLint,
+                (src.y + src.height) as GLint,
+                dst.x as GLint,
+                dst.y as GLint,
+                (dst.x + dst.width) as GLint,
+                (dst.y + dst.height) as GLint,
                 gl::COLOR_BUFFER_BIT,
                 filter,
             );
 
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
         }
-        true
+        Ok(())
     }
 
-    fn blit_output_to_target_scaled(
+    fn blit_output_to_target_region(
         &mut self,
         host_fbo: GLuint,
-        src_w: u32,
-        src_h: u32,
-        dst_w: u32,
-        dst_h: u32,
+        src: PixelRect,
+        dst: PixelRect,
         bilinear: bool,
-    ) -> bool {
+    ) -> Result<(), BridgeError> {
         let output_gl = match &self.pairs[self.front] {
             Some(pair) => pair.output.gl_texture,
-            None => return false,
+            None => return Err(BridgeError::NotInitialized),
         };
 
         unsafe {
@@ -516,21 +815,21 @@ impl GpuBridge for GlMetalBridge {
             let filter = if bilinear { gl::LINEAR } else { gl::NEAREST };
 
             gl::BlitFramebuffer(
-                0,
-                0,
-                src_w as GLsizei,
-                src_h as GLsizei,
-                0,
-                0,
-                dst_w as GLsizei,
-                dst_h as GLsizei,
+                src.x as GLint,
+                src.y as GLint,
+                (src.x + src.width) as GLint,
+                (src.y + src.height) as GLint,
+                dst.x as GLint,
+                dst.y as GLint,
+                (dst.x + dst.width) as GLint,
+                (dst.y + dst.height) as GLint,
                 gl::COLOR_BUFFER_BIT,
                 filter,
             );
 
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
         }
-        true
+        Ok(())
     }
 
     fn has_result_ready(&self, current_frame: u64) -> bool {
@@ -538,21 +837,24 @@ impl GpuBridge for GlMetalBridge {
             && self
                 .last_dispatch_frame
                 .is_some_and(|last| current_frame == last.wrapping_add(1))
-            && self
-                .last_dispatch_time
-                .is_some_and(|t| t.elapsed().as_millis() < 100)
+            && self.last_dispatch_time.is_some_and(|t| {
+                self.staleness_window
+                    .is_none_or(|window| t.elapsed() < window)
+            })
     }
 
     fn wait_for_previous(&mut self) {
-        if let Some(cb) = self.pending_command_buffer.take() {
-            cb.waitUntilCompleted();
+        // Work that timed out may still be writing a pair, so keep waiting
+        // on it next frame rather than reusing the pair unsynchronised.
+        self.previous_timed_out = !self.wait_for_command_buffer();
+        if !self.previous_timed_out {
+            self.pending_command_buffer = None;
+            self.pending_event_value = None;
         }
     }
 
     fn wait_for_pending(&mut self) {
-        if let Some(cb) = self.pending_command_buffer.as_ref() {
-            cb.waitUntilCompleted();
-        }
+        self.wait_for_command_buffer();
     }
 
     fn swap(&mut self) {
@@ -565,10 +867,16 @@ impl GpuBridge for GlMetalBridge {
     }
 
     fn cleanup(&mut self) {
+        // The shared textures are about to be released, so this must not
+        // time out.
         if let Some(cb) = self.pending_command_buffer.take() {
             cb.waitUntilCompleted();
         }
+        self.pending_event_value = None;
+        self.previous_timed_out = false;
         self.pairs = [None, None];
+        self.pair_key = None;
+        self.pool.drain();
         self.front = 0;
         self.last_dispatch_frame = None;
         self.last_dispatch_time = None;
@@ -590,6 +898,33 @@ impl GpuBridge for GlMetalBridge {
     fn dimensions(&self) -> (u32, u32) {
         self.dimensions
     }
+
+    fn memory_usage(&self) -> BridgeMemory {
+        let bytes = |&(width, height, format, ..): &PairKey| pair_set_bytes(width, height, format);
+        BridgeMemory {
+            active: self.pair_key.as_ref().map_or(0, bytes),
+            pooled: self.pool.keys().map(bytes).sum(),
+        }
+    }
+
+    fn gl_context(&self) -> Option<GlContextId> {
+        self.gl_context
+    }
+
+    fn forget_gl_objects(&mut self) {
+        for pair in self.pairs.iter_mut().flatten() {
+            pair.forget_gl();
+        }
+        for mut pairs in self.pool.drain() {
+            pairs.iter_mut().for_each(IoSurfacePair::forget_gl);
+        }
+        self.pairs = [None, None];
+        self.pair_key = None;
+        self.read_fbo = 0;
+        self.draw_fbo = 0;
+        self.dimensions = (0, 0);
+        self.host_texture_type = 0;
+    }
 }
 
 impl Drop for GlMetalBridge {
@@ -600,6 +935,7 @@ impl Drop for GlMetalBridge {
         }
         // Drop pairs (releases IOSurfaces and GL textures via SharedTexture::drop).
         self.pairs = [None, None];
+        self.pool.drain();
         // Unbind before deleting to avoid GL errors on some drivers.
         unsafe {
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
@@ -0,0 +1,67 @@
+//! Small LRU of retired shared-texture pairs.
+//!
+//! Some hosts alternate between output sizes every few frames (preview vs.
+//! output). Rather than destroying the shared textures on every change, the
+//! bridges park the outgoing pairs here, keyed by what they were created for,
+//! and take them back when the same dimensions come around again.
+
+use crate::TextureFormat;
+
+/// Default for [`GpuBridge::set_pool_capacity`](crate::GpuBridge::set_pool_capacity).
+pub const DEFAULT_POOL_CAPACITY: usize = 2;
+
+/// Bytes in one set of shared textures: two pairs of input and output
+/// textures of `width` x `height` in `format`.
+pub(crate) fn pair_set_bytes(width: u32, height: u32, format: TextureFormat) -> u64 {
+    4 * width as u64 * height as u64 * format.bytes_per_pixel() as u64
+}
+
+/// Retired texture pairs, least recently used first.
+pub(crate) struct PairPool<K, T> {
+    entries: Vec<(K, T)>,
+    capacity: usize,
+}
+
+impl<K: PartialEq, T> PairPool<K, T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Change the capacity. Returns the entries that no longer fit, oldest
+    /// first, so the caller can release them.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) -> Vec<T> {
+        self.capacity = capacity;
+        self.evict()
+    }
+
+    /// Remove and return the entry created for `key`, if any.
+    pub(crate) fn take(&mut self, key: &K) -> Option<T> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Park `value` as the most recently used entry. Returns the entries
+    /// evicted to stay within capacity (all of them if the capacity is 0).
+    pub(crate) fn put(&mut self, key: K, value: T) -> Vec<T> {
+        self.entries.push((key, value));
+        self.evict()
+    }
+
+    /// Keys of the parked entries, least recently used first.
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Remove and return every entry.
+    pub(crate) fn drain(&mut self) -> Vec<T> {
+        self.entries.drain(..).map(|(_, v)| v).collect()
+    }
+
+    fn evict(&mut self) -> Vec<T> {
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.entries.drain(..excess).map(|(_, v)| v).collect()
+    }
+}
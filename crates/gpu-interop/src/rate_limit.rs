@@ -0,0 +1,92 @@
+//! Rate limiting for per-frame log messages.
+//!
+//! A failure that happens every frame (a texture lock that keeps failing, a
+//! GPU query that keeps timing out) would otherwise log 60 times a second and
+//! bury everything else in the host's log. [`error_limited!`] and
+//! [`warn_limited!`] log the first occurrence at each call site, then at most
+//! one line per [`RateLimit::INTERVAL`] carrying a `suppressed` count of the
+//! occurrences skipped in between.
+//!
+//! ```ignore
+//! gpu_interop::warn_limited!(frame = frame_counter, "Failed to lock GL texture");
+//! ```
+//!
+//! [`error_limited!`]: crate::error_limited
+//! [`warn_limited!`]: crate::warn_limited
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Per-call-site limiter state, created by the logging macros.
+#[derive(Debug)]
+pub struct RateLimit {
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    last_logged: Option<Instant>,
+    suppressed: u64,
+}
+
+impl RateLimit {
+    /// Minimum time between two log lines from one call site.
+    pub const INTERVAL: Duration = Duration::from_secs(5);
+
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                last_logged: None,
+                suppressed: 0,
+            }),
+        }
+    }
+
+    /// Record an occurrence. Returns the number of occurrences suppressed
+    /// since the last logged one if this one should be logged.
+    pub fn check(&self) -> Option<u64> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let due = state
+            .last_logged
+            .is_none_or(|t| now.duration_since(t) >= Self::INTERVAL);
+        if due {
+            state.last_logged = Some(now);
+            Some(std::mem::take(&mut state.suppressed))
+        } else {
+            state.suppressed += 1;
+            None
+        }
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+pub use tracing as __tracing;
+
+/// `tracing::error!`, rate limited per call site. See [`crate::rate_limit`].
+#[macro_export]
+macro_rules! error_limited {
+    ($($arg:tt)+) => {{
+        static LIMIT: $crate::rate_limit::RateLimit = $crate::rate_limit::RateLimit::new();
+        if let Some(suppressed) = LIMIT.check() {
+            $crate::rate_limit::__tracing::error!(suppressed, $($arg)+);
+        }
+    }};
+}
+
+/// `tracing::warn!`, rate limited per call site. See [`crate::rate_limit`].
+#[macro_export]
+macro_rules! warn_limited {
+    ($($arg:tt)+) => {{
+        static LIMIT: $crate::rate_limit::RateLimit = $crate::rate_limit::RateLimit::new();
+        if let Some(suppressed) = LIMIT.check() {
+            $crate::rate_limit::__tracing::warn!(suppressed, $($arg)+);
+        }
+    }};
+}
@@ -19,7 +19,7 @@ YELLOW='\033[1;33m'
 BLUE='\033[0;34m'
 NC='\033[0m' # No Color
 
-ALL_PLUGINS="passthrough invert blur kitchen-sink"
+ALL_PLUGINS="passthrough invert blur kitchen-sink chroma-key"
 
 # Display name for a plugin key
 get_plugin_display() {
@@ -28,6 +28,7 @@ get_plugin_display() {
         invert)       echo "Invert"       ;;
         blur)         echo "Blur"         ;;
         kitchen-sink) echo "KitchenSink"  ;;
+        chroma-key)   echo "ChromaKey"    ;;
         *) return 1 ;;
     esac
 }
@@ -37,7 +38,7 @@ usage() {
     echo "Usage: $0 [OPTIONS]"
     echo ""
     echo "Options:"
-    echo "  --plugin PLUGIN        Plugin to deploy: passthrough, invert, blur, kitchen-sink, all (default: all)"
+    echo "  --plugin PLUGIN        Plugin to deploy: passthrough, invert, blur, kitchen-sink, chroma-key, all (default: all)"
     echo "  --arch ARCH            Architecture: arm64, x86_64, universal, current (default: current)"
     echo "  --source-dir DIR       Source directory (default: auto-detect from dist/)"
     echo "  --dry-run              Show what would be deployed without actually copying"
@@ -3,8 +3,8 @@
 //!
 //! Demonstrates multi-pass compute with an FFGL parameter on DX11. A separable
 //! box blur is implemented as two compute dispatches (horizontal then vertical)
-//! using an intermediate texture. The "Radius" parameter (0.0-1.0) maps to
-//! 0-20 pixels of blur.
+//! using an intermediate texture. The "Radius" parameter is declared as
+//! 0-20 pixels, which the host shows; the plugin scales its 0-1 value back.
 
 use std::ffi::CString;
 use std::sync::OnceLock;
@@ -21,12 +21,8 @@ use ffgl_gpu::{AsBytes, DrawInput, GpuContext, draw_gpu_effect};
 
 static NEXT_INSTANCE_ID: AtomicU64 = AtomicU64::new(1);
 
-#[cfg(target_os = "windows")]
-use windows::Win32::Graphics::Direct3D::D3D_SRV_DIMENSION_TEXTURE2D;
 #[cfg(target_os = "windows")]
 use windows::Win32::Graphics::Direct3D11::*;
-#[cfg(target_os = "windows")]
-use windows::Win32::Graphics::Dxgi::Common::*;
 
 /// Compiled HLSL horizontal blur shader, embedded at build time.
 #[cfg(target_os = "windows")]
@@ -43,15 +39,14 @@ const V_SHADER: &[u8] = &[];
 /// No Metal shaders for this DX11-only example.
 const METALLIB_BYTES: &[u8] = &[];
 
-/// Maximum blur radius in pixels when the parameter is at 1.0.
-const MAX_RADIUS: f32 = 20.0;
-
 fn cached_params() -> &'static [SimpleParamInfo] {
     static PARAMS: OnceLock<Vec<SimpleParamInfo>> = OnceLock::new();
     PARAMS.get_or_init(|| {
         vec![SimpleParamInfo {
             name: CString::new("Radius").unwrap(),
             default: Some(0.25),
+            max: Some(20.0),
+            unit: Some("px".into()),
             ..Default::default()
         }]
     })
@@ -72,106 +67,24 @@ struct GpuState {
     h_pipeline: Option<ComputePipeline>,
     v_pipeline: Option<ComputePipeline>,
     #[cfg(target_os = "windows")]
-    intermediate_texture: Option<windows::Win32::Graphics::Direct3D11::ID3D11Texture2D>,
-    #[cfg(target_os = "windows")]
-    intermediate_srv: Option<windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView>,
-    #[cfg(target_os = "windows")]
-    intermediate_uav: Option<windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView>,
-    #[cfg(target_os = "windows")]
-    intermediate_dims: (u32, u32),
+    intermediate_texture: Option<ffgl_gpu::GpuTexture>,
     #[cfg(target_os = "windows")]
     cbuf: Option<windows::Win32::Graphics::Direct3D11::ID3D11Buffer>,
 }
 
 #[cfg(target_os = "windows")]
 impl GpuState {
-    /// Create or re-create the intermediate texture (and SRV/UAV views) when
-    /// dimensions change.
-    fn ensure_intermediate_texture(
-        &mut self,
-        device: &ID3D11Device,
-        width: u32,
-        height: u32,
-    ) {
-        if self.intermediate_dims == (width, height) && self.intermediate_texture.is_some() {
-            return;
-        }
-
-        let desc = D3D11_TEXTURE2D_DESC {
-            Width: width,
-            Height: height,
-            MipLevels: 1,
-            ArraySize: 1,
-            Format: DXGI_FORMAT_B8G8R8A8_UNORM,
-            SampleDesc: DXGI_SAMPLE_DESC {
-                Count: 1,
-                Quality: 0,
-            },
-            Usage: D3D11_USAGE_DEFAULT,
-            BindFlags: (D3D11_BIND_SHADER_RESOURCE.0 | D3D11_BIND_UNORDERED_ACCESS.0) as u32,
-            CPUAccessFlags: 0,
-            MiscFlags: 0,
-        };
-
-        let mut texture = None;
-        let hr = unsafe { device.CreateTexture2D(&desc, None, Some(&mut texture as *mut _)) };
-        if hr.is_err() {
-            return;
-        }
-        let texture = match texture {
-            Some(t) => t,
-            None => return,
-        };
-
-        // Create SRV for reading the intermediate texture
-        let srv_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
-            Format: DXGI_FORMAT_B8G8R8A8_UNORM,
-            ViewDimension: D3D_SRV_DIMENSION_TEXTURE2D,
-            Anonymous: D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
-                Texture2D: D3D11_TEX2D_SRV {
-                    MostDetailedMip: 0,
-                    MipLevels: 1,
-                },
-            },
-        };
-        let mut srv = None;
-        let hr = unsafe {
-            device.CreateShaderResourceView(
-                &texture,
-                Some(&srv_desc),
-                Some(&mut srv as *mut _),
-            )
-        };
-        if hr.is_err() {
-            return;
-        }
-
-        // Create UAV for writing the intermediate texture
-        let uav_desc = D3D11_UNORDERED_ACCESS_VIEW_DESC {
-            Format: DXGI_FORMAT_B8G8R8A8_UNORM,
-            ViewDimension: D3D11_UAV_DIMENSION_TEXTURE2D,
-            Anonymous: D3D11_UNORDERED_ACCESS_VIEW_DESC_0 {
-                Texture2D: D3D11_TEX2D_UAV { MipSlice: 0 },
-            },
-        };
-        let mut uav = None;
-        let hr = unsafe {
-            device.CreateUnorderedAccessView(
-                &texture,
-                Some(&uav_desc),
-                Some(&mut uav as *mut _),
-            )
-        };
-        if hr.is_err() {
+    /// Create or re-create the intermediate texture when dimensions change.
+    fn ensure_intermediate_texture(&mut self, ctx: &GpuContext, width: u32, height: u32) {
+        if self
+            .intermediate_texture
+            .as_ref()
+            .is_some_and(|t| t.has_dims(width, height))
+        {
             return;
         }
 
-        if srv.is_some() && uav.is_some() {
-            self.intermediate_texture = Some(texture);
-            self.intermediate_srv = srv;
-            self.intermediate_uav = uav;
-            self.intermediate_dims = (width, height);
-        }
+        self.intermediate_texture = ctx.create_texture(width, height).ok();
     }
 
     /// Map the dynamic constant buffer, write data, and unmap.
@@ -224,31 +137,25 @@ impl GpuPlugin for GpuState {
         {
             let (w, h) = (input.width, input.height);
 
-            // Get the device reference for ensure_intermediate_texture.
-            let bridge = input.dx11_bridge();
-            let device = bridge.device().clone();
-            let dx11_context = bridge.context().clone();
-
-            // Ensure intermediate texture is allocated at the correct size.
-            self.ensure_intermediate_texture(&device, w, h);
-
-            let intermediate_srv = match &self.intermediate_srv {
-                Some(s) => s.clone(),
-                None => return,
-            };
-            let intermediate_uav = match &self.intermediate_uav {
-                Some(u) => u.clone(),
-                None => return,
+            let Some(dx11) = input.dx11() else {
+                return;
             };
 
-            // Compute pixel radius from the normalized parameter.
-            let pixel_radius = (self.radius_param * MAX_RADIUS).round() as i32;
+            // Ensure intermediate texture is allocated at the correct size.
+            self.ensure_intermediate_texture(ctx, w, h);
+
+            // Compute pixel radius from the normalized parameter. The radius
+            // is in output pixels; at reduced internal resolution fewer
+            // processing pixels cover it.
+            let (scale, _) = input.scale();
+            let pixel_radius =
+                (cached_params()[0].scale(self.radius_param) * scale).round() as i32;
             let params = BlurParams {
                 radius: pixel_radius,
             };
 
             // Update the constant buffer with the current blur radius.
-            self.update_cbuf(&dx11_context, params.as_bytes());
+            self.update_cbuf(&dx11.context, params.as_bytes());
 
             let cbuf_ref = match &self.cbuf {
                 Some(b) => b.clone(),
@@ -263,6 +170,10 @@ impl GpuPlugin for GpuState {
                 Some(p) => p,
                 None => return,
             };
+            let intermediate = match &self.intermediate_texture {
+                Some(t) => t,
+                None => return,
+            };
 
             // Pass 1: horizontal blur (input -> intermediate)
             // dispatch_compute unbinds all CS resources after each dispatch,
@@ -270,8 +181,8 @@ impl GpuPlugin for GpuState {
             // it as an SRV.
             ctx.dispatch_compute(
                 h_pipeline,
-                &[Some(intermediate_uav)],
-                &[Some(input.input_srv.clone())],
+                &[intermediate.into()],
+                &[dx11.input_srv.into()],
                 &[Some(cbuf_ref.clone())],
                 (w as usize, h as usize),
                 (16, 16),
@@ -280,8 +191,8 @@ impl GpuPlugin for GpuState {
             // Pass 2: vertical blur (intermediate -> output)
             ctx.dispatch_compute(
                 v_pipeline,
-                &[Some(input.output_uav.clone())],
-                &[Some(intermediate_srv)],
+                &[dx11.output_uav.into()],
+                &[intermediate.into()],
                 &[Some(cbuf_ref)],
                 (w as usize, h as usize),
                 (16, 16),
@@ -325,12 +236,6 @@ impl SimpleFFGLInstance for DxBlur {
                 #[cfg(target_os = "windows")]
                 intermediate_texture: None,
                 #[cfg(target_os = "windows")]
-                intermediate_srv: None,
-                #[cfg(target_os = "windows")]
-                intermediate_uav: None,
-                #[cfg(target_os = "windows")]
-                intermediate_dims: (0, 0),
-                #[cfg(target_os = "windows")]
                 cbuf: None,
             },
             frame_counter: 0,
@@ -354,6 +259,7 @@ impl SimpleFFGLInstance for DxBlur {
             about: "DX11 separable box blur via multi-pass compute".to_string(),
             description: "Two-pass DX11 GPU compute blur with adjustable radius parameter"
                 .to_string(),
+            thumbnail: None,
         }
     }
 
@@ -380,6 +286,10 @@ impl SimpleFFGLInstance for DxBlur {
             METALLIB_BYTES,
         );
     }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
 }
 
 ffgl_core::plugin_main!(SimpleFFGLHandler<DxBlur>);
@@ -0,0 +1,11 @@
+fn main() {
+    #[cfg(target_os = "windows")]
+    ffgl_gpu::build_support::compile_hlsl_shaders(
+        std::path::Path::new("shaders"),
+        &[ffgl_gpu::build_support::HlslEntry {
+            file: "matte.hlsl",
+            entry_point: "show_matte",
+            target: "cs_5_0",
+        }],
+    );
+}
@@ -0,0 +1,15 @@
+// Shows the keyed image's alpha as an opaque grayscale matte.
+
+Texture2D<float4> input : register(t0);
+RWTexture2D<float4> output : register(u0);
+
+[numthreads(16, 16, 1)]
+void show_matte(uint3 id : SV_DispatchThreadID)
+{
+    uint w, h;
+    output.GetDimensions(w, h);
+    if (id.x >= w || id.y >= h) return;
+
+    float a = input[id.xy].a;
+    output[id.xy] = float4(a, a, a, 1.0);
+}
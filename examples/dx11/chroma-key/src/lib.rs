@@ -0,0 +1,196 @@
+//! DX11 Chroma Key FFGL plugin example.
+//!
+//! Demonstrates the built-in [`ChromaKey`] pass and its parameters on DX11:
+//! the key color as a hue / saturation / brightness picker plus similarity,
+//! softness and spill reduction. The plugin asks for straight alpha so the
+//! pass can write the matte into the alpha channel without touching color.
+//! "Show Matte" chains a compute pass of the plugin's own after the key,
+//! through an intermediate texture, to display the alpha as grayscale.
+
+use std::ffi::CString;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ffgl_core::handler::simplified::{SimpleFFGLHandler, SimpleFFGLInstance};
+use ffgl_core::info::{PluginInfo, PluginType};
+use ffgl_core::parameters::{ParamInfo, ParameterTypes, SimpleParamInfo};
+use ffgl_core::{FFGLData, GLInput};
+use ffgl_glium::FFGLGlium;
+use ffgl_gpu::pipeline::ComputePipeline;
+use ffgl_gpu::plugin::GpuPlugin;
+use ffgl_gpu::{AlphaMode, ChromaKey, DrawInput, GpuContext, draw_gpu_effect};
+
+static NEXT_INSTANCE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Compiled HLSL matte shader, embedded at build time.
+#[cfg(target_os = "windows")]
+const MATTE_SHADER: &[u8] = ffgl_gpu::include_hlsl_shader!("show_matte");
+
+/// No Metal shaders for this DX11-only example.
+const METALLIB_BYTES: &[u8] = &[];
+
+const PARAM_SHOW_MATTE: usize = ChromaKey::PARAM_COUNT;
+
+fn cached_params() -> &'static [SimpleParamInfo] {
+    static PARAMS: OnceLock<Vec<SimpleParamInfo>> = OnceLock::new();
+    PARAMS.get_or_init(|| {
+        let mut params = ChromaKey::params();
+        params.push(SimpleParamInfo {
+            name: CString::new("Show Matte").unwrap(),
+            param_type: ParameterTypes::Boolean,
+            ..Default::default()
+        });
+        params
+    })
+}
+
+/// Inner GPU state, separate from glium to avoid double-borrow.
+struct GpuState {
+    params: Vec<f32>,
+    matte_pipeline: Option<ComputePipeline>,
+}
+
+impl GpuPlugin for GpuState {
+    fn gpu_init(&mut self, ctx: &GpuContext) -> anyhow::Result<()> {
+        #[cfg(target_os = "windows")]
+        {
+            self.matte_pipeline = Some(ctx.create_compute_pipeline(MATTE_SHADER)?);
+        }
+        let _ = ctx;
+        Ok(())
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Straight
+    }
+
+    fn gpu_draw(
+        &mut self,
+        ctx: &GpuContext,
+        input: &mut DrawInput<'_>,
+        _data: &FFGLData,
+        _frame: u64,
+    ) {
+        let key = ChromaKey::from_params(&self.params);
+        let show_matte = self.params[PARAM_SHOW_MATTE] > 0.5;
+
+        #[cfg(target_os = "windows")]
+        {
+            let (w, h) = (input.width, input.height);
+
+            if show_matte {
+                let matte_pipeline = match &self.matte_pipeline {
+                    Some(p) => p,
+                    None => return,
+                };
+                let keyed = match input.intermediate("keyed") {
+                    Ok(t) => t,
+                    Err(_) => return,
+                };
+
+                // Pass 1: key (input -> intermediate)
+                if ctx
+                    .chroma_key(&input.input_srv, keyed.dx11_uav(), w, h, &key)
+                    .is_err()
+                {
+                    return;
+                }
+
+                // Pass 2: matte (intermediate -> output)
+                ctx.dispatch_compute(
+                    matte_pipeline,
+                    &[input.output_uav.clone().into()],
+                    &[ffgl_gpu::SrvBinding::Texture(&keyed)],
+                    &[],
+                    (w as usize, h as usize),
+                    (16, 16),
+                );
+            } else {
+                let _ = ctx.chroma_key(&input.input_srv, &input.output_uav, w, h, &key);
+            }
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (ctx, input, key, show_matte, &self.matte_pipeline);
+        }
+    }
+}
+
+// SAFETY: FFGL plugins are called single-threaded from the host.
+unsafe impl Send for GpuState {}
+unsafe impl Sync for GpuState {}
+
+pub struct ChromaKeyPlugin {
+    glium: FFGLGlium,
+    gpu: GpuState,
+    frame_counter: u64,
+    instance_id: u64,
+}
+
+// SAFETY: FFGL plugins are called single-threaded from the host.
+unsafe impl Send for ChromaKeyPlugin {}
+unsafe impl Sync for ChromaKeyPlugin {}
+
+impl SimpleFFGLInstance for ChromaKeyPlugin {
+    fn new(inst_data: &FFGLData) -> Self {
+        Self {
+            glium: FFGLGlium::new(inst_data),
+            gpu: GpuState {
+                params: cached_params().iter().map(|p| p.default_val()).collect(),
+                matte_pipeline: None,
+            },
+            frame_counter: 0,
+            instance_id: NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    fn num_params() -> usize {
+        cached_params().len()
+    }
+
+    fn param_info(index: usize) -> &'static dyn ParamInfo {
+        &cached_params()[index]
+    }
+
+    fn plugin_info() -> PluginInfo {
+        PluginInfo {
+            unique_id: *b"CKEY",
+            name: *b"Chroma Key\0\0\0\0\0\0",
+            ty: PluginType::Effect,
+            about: "Chroma key via the built-in keying pass".to_string(),
+            description: "Keys out a color with softness and spill reduction".to_string(),
+            thumbnail: None,
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        self.gpu.params[index]
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        self.gpu.params[index] = value;
+    }
+
+    fn draw(&mut self, data: &FFGLData, frame_data: GLInput) {
+        self.frame_counter = self.frame_counter.wrapping_add(1);
+        let id = self.instance_id;
+        draw_gpu_effect(
+            &mut self.gpu,
+            id,
+            &mut self.glium,
+            data,
+            frame_data,
+            self.frame_counter,
+            1.0,
+            1.0,
+            METALLIB_BYTES,
+        );
+    }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
+}
+
+ffgl_core::plugin_main!(SimpleFFGLHandler<ChromaKeyPlugin>);
@@ -61,8 +61,10 @@ impl GpuPlugin for GpuState {
 
             let _ = ctx.dispatch_render(
                 pipeline,
-                input.output_texture.clone(),
-                &[Some(input.input_srv.clone())],
+                &input.output_texture,
+                &[input.input_srv.clone().into()],
+                &[],
+                &[],
                 &[],
             );
         }
@@ -109,6 +111,7 @@ impl SimpleFFGLInstance for DxInvert {
             ty: PluginType::Effect,
             about: "DX11 color inversion via render pipeline".to_string(),
             description: "Inverts colors using a DX11 vertex/pixel shader pair".to_string(),
+            thumbnail: None,
         }
     }
 
@@ -127,6 +130,10 @@ impl SimpleFFGLInstance for DxInvert {
             METALLIB_BYTES,
         );
     }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
 }
 
 ffgl_core::plugin_main!(SimpleFFGLHandler<DxInvert>);
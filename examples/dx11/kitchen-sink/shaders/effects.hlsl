@@ -5,36 +5,25 @@
 // referenced by each entry point are used during compilation.
 
 // -----------------------------------------------------------------------
-// Shared uniform struct
+// Uniforms, one cbuffer per pass. The plugin binds them by name, so each
+// can sit in any register.
 // -----------------------------------------------------------------------
 
-cbuffer EffectParams : register(b0)
+cbuffer gray_params : register(b0)
 {
     float grayscale_amount;
-    float tint_hue;
-    float tint_saturation;
-    float blend_amount;
 };
 
-// -----------------------------------------------------------------------
-// HSV -> RGB helper
-// -----------------------------------------------------------------------
+cbuffer tint_params : register(b1)
+{
+    float3 tint; // RGB of the tint hue and saturation
+    float tint_saturation;
+};
 
-float3 hsv_to_rgb(float h, float s, float v)
+cbuffer blend_params : register(b2)
 {
-    float c = v * s;
-    float hp = h * 6.0;
-    float x = c * (1.0 - abs(fmod(hp, 2.0) - 1.0));
-    float3 rgb;
-    if      (hp < 1.0) rgb = float3(c, x, 0);
-    else if (hp < 2.0) rgb = float3(x, c, 0);
-    else if (hp < 3.0) rgb = float3(0, c, x);
-    else if (hp < 4.0) rgb = float3(0, x, c);
-    else if (hp < 5.0) rgb = float3(x, 0, c);
-    else               rgb = float3(c, 0, x);
-    float m = v - c;
-    return rgb + m;
-}
+    float blend_amount;
+};
 
 // -----------------------------------------------------------------------
 // Pass 1: Grayscale (compute)
@@ -88,9 +77,6 @@ float4 tint_ps(VSOutput input) : SV_TARGET
 {
     float4 color = tint_input.Sample(samp, input.uv);
 
-    // Generate tint colour from hue + saturation
-    float3 tint = hsv_to_rgb(tint_hue, tint_saturation, 1.0);
-
     // Multiply blend: overlay the tint onto the grayscaled image
     float3 tinted = color.rgb * tint;
 
@@ -13,9 +13,11 @@
 //!
 //! This shows how to:
 //! - Use multiple pipelines (compute and render) in a single DX11 plugin.
-//! - Pass uniform data via a dynamic constant buffer.
-//! - Chain intermediate textures across passes.
+//! - Chain the passes through intermediate textures with `gpu_passes!`.
+//! - Bind each pass's constant buffer by its cbuffer name.
 //! - Expose multiple FFGL parameters.
+//! - Convert hue/saturation parameters to RGB on the CPU with
+//!   `ffgl_core::parameters::color`.
 
 use std::ffi::CString;
 use std::sync::OnceLock;
@@ -23,6 +25,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 
 use ffgl_core::handler::simplified::{SimpleFFGLHandler, SimpleFFGLInstance};
 use ffgl_core::info::{PluginInfo, PluginType};
+use ffgl_core::parameters::color::hsv_to_rgb;
 use ffgl_core::parameters::{ParamInfo, ParameterTypes, SimpleParamInfo};
 use ffgl_core::{FFGLData, GLInput};
 use ffgl_glium::FFGLGlium;
@@ -97,17 +100,31 @@ fn cached_params() -> &'static [SimpleParamInfo] {
     })
 }
 
-/// Uniform struct matching `EffectParams` in the HLSL shaders.
+/// Uniforms of the grayscale pass, cbuffer `gray_params` in the HLSL
+/// shaders.
 #[repr(C)]
-struct EffectParams {
-    grayscale_amount: f32,
-    tint_hue: f32,
-    tint_saturation: f32,
-    blend: f32,
+struct GrayscaleParams {
+    amount: f32,
 }
 
-// SAFETY: EffectParams is #[repr(C)] with only plain f32 fields.
-unsafe impl AsBytes for EffectParams {}
+/// Uniforms of the tint pass, cbuffer `tint_params` in the HLSL shaders.
+#[repr(C)]
+struct TintParams {
+    /// `float3`, packed with `saturation` into one register.
+    tint: [f32; 3],
+    saturation: f32,
+}
+
+/// Uniforms of the blend pass, cbuffer `blend_params` in the HLSL shaders.
+#[repr(C)]
+struct BlendParams {
+    amount: f32,
+}
+
+// SAFETY: the uniform structs are #[repr(C)] with only plain f32 fields.
+unsafe impl AsBytes for GrayscaleParams {}
+unsafe impl AsBytes for TintParams {}
+unsafe impl AsBytes for BlendParams {}
 
 // ---------------------------------------------------------------------------
 // GPU state
@@ -121,28 +138,6 @@ struct GpuState {
     grayscale_pipeline: Option<ComputePipeline>,
     tint_pipeline: Option<RenderPipeline>,
     blend_pipeline: Option<ComputePipeline>,
-
-    // DX11-specific intermediate textures and views
-    #[cfg(target_os = "windows")]
-    tex_after_grayscale: Option<windows::Win32::Graphics::Direct3D11::ID3D11Texture2D>,
-    #[cfg(target_os = "windows")]
-    tex_after_grayscale_srv:
-        Option<windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView>,
-    #[cfg(target_os = "windows")]
-    tex_after_grayscale_uav:
-        Option<windows::Win32::Graphics::Direct3D11::ID3D11UnorderedAccessView>,
-
-    #[cfg(target_os = "windows")]
-    tex_after_tint: Option<windows::Win32::Graphics::Direct3D11::ID3D11Texture2D>,
-    #[cfg(target_os = "windows")]
-    tex_after_tint_srv: Option<windows::Win32::Graphics::Direct3D11::ID3D11ShaderResourceView>,
-
-    #[cfg(target_os = "windows")]
-    intermediate_dims: (u32, u32),
-
-    /// Dynamic constant buffer for `EffectParams`.
-    #[cfg(target_os = "windows")]
-    cbuf: Option<windows::Win32::Graphics::Direct3D11::ID3D11Buffer>,
 }
 
 // SAFETY: GpuState contains DX11 COM pointers created with
@@ -152,153 +147,6 @@ struct GpuState {
 unsafe impl Send for GpuState {}
 unsafe impl Sync for GpuState {}
 
-// ---------------------------------------------------------------------------
-// DX11 intermediate texture management
-// ---------------------------------------------------------------------------
-
-#[cfg(target_os = "windows")]
-impl GpuState {
-    /// Ensure intermediate textures exist at the correct dimensions.
-    ///
-    /// - `tex_after_grayscale`: BGRA8, bind SRV + UAV (written by compute, read
-    ///   by render).
-    /// - `tex_after_tint`: BGRA8, bind SRV + RENDER_TARGET (written by render,
-    ///   read by compute).
-    fn ensure_intermediate_textures(&mut self, ctx: &GpuContext, width: u32, height: u32) {
-        use windows::Win32::Graphics::Direct3D::D3D_SRV_DIMENSION_TEXTURE2D;
-        use windows::Win32::Graphics::Direct3D11::*;
-        use windows::Win32::Graphics::Dxgi::Common::*;
-
-        if self.intermediate_dims == (width, height)
-            && self.tex_after_grayscale.is_some()
-            && self.tex_after_tint.is_some()
-        {
-            return;
-        }
-
-        let device = ctx.dx11_device().device();
-
-        // --- tex_after_grayscale: SRV + UAV (compute write, render read) ---
-        {
-            let desc = D3D11_TEXTURE2D_DESC {
-                Width: width,
-                Height: height,
-                MipLevels: 1,
-                ArraySize: 1,
-                Format: DXGI_FORMAT_B8G8R8A8_UNORM,
-                SampleDesc: DXGI_SAMPLE_DESC {
-                    Count: 1,
-                    Quality: 0,
-                },
-                Usage: D3D11_USAGE_DEFAULT,
-                BindFlags: (D3D11_BIND_SHADER_RESOURCE.0 | D3D11_BIND_UNORDERED_ACCESS.0) as u32,
-                CPUAccessFlags: 0,
-                MiscFlags: 0,
-            };
-            let mut tex = None;
-            let _ = unsafe { device.CreateTexture2D(&desc, None, Some(&mut tex as *mut _)) };
-            self.tex_after_grayscale = tex;
-
-            if let Some(ref texture) = self.tex_after_grayscale {
-                // SRV
-                let srv_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
-                    Format: DXGI_FORMAT_B8G8R8A8_UNORM,
-                    ViewDimension: D3D_SRV_DIMENSION_TEXTURE2D,
-                    Anonymous: D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
-                        Texture2D: D3D11_TEX2D_SRV {
-                            MostDetailedMip: 0,
-                            MipLevels: 1,
-                        },
-                    },
-                };
-                let mut srv = None;
-                let _ = unsafe {
-                    device.CreateShaderResourceView(
-                        texture,
-                        Some(&srv_desc),
-                        Some(&mut srv as *mut _),
-                    )
-                };
-                self.tex_after_grayscale_srv = srv;
-
-                // UAV
-                let uav_desc = D3D11_UNORDERED_ACCESS_VIEW_DESC {
-                    Format: DXGI_FORMAT_B8G8R8A8_UNORM,
-                    ViewDimension: D3D11_UAV_DIMENSION_TEXTURE2D,
-                    Anonymous: D3D11_UNORDERED_ACCESS_VIEW_DESC_0 {
-                        Texture2D: D3D11_TEX2D_UAV { MipSlice: 0 },
-                    },
-                };
-                let mut uav = None;
-                let _ = unsafe {
-                    device.CreateUnorderedAccessView(
-                        texture,
-                        Some(&uav_desc),
-                        Some(&mut uav as *mut _),
-                    )
-                };
-                self.tex_after_grayscale_uav = uav;
-            }
-        }
-
-        // --- tex_after_tint: SRV + RENDER_TARGET (render write, compute read) ---
-        {
-            let desc = D3D11_TEXTURE2D_DESC {
-                Width: width,
-                Height: height,
-                MipLevels: 1,
-                ArraySize: 1,
-                Format: DXGI_FORMAT_B8G8R8A8_UNORM,
-                SampleDesc: DXGI_SAMPLE_DESC {
-                    Count: 1,
-                    Quality: 0,
-                },
-                Usage: D3D11_USAGE_DEFAULT,
-                BindFlags: (D3D11_BIND_SHADER_RESOURCE.0 | D3D11_BIND_RENDER_TARGET.0) as u32,
-                CPUAccessFlags: 0,
-                MiscFlags: 0,
-            };
-            let mut tex = None;
-            let _ = unsafe { device.CreateTexture2D(&desc, None, Some(&mut tex as *mut _)) };
-            self.tex_after_tint = tex;
-
-            if let Some(ref texture) = self.tex_after_tint {
-                let srv_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
-                    Format: DXGI_FORMAT_B8G8R8A8_UNORM,
-                    ViewDimension: D3D_SRV_DIMENSION_TEXTURE2D,
-                    Anonymous: D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
-                        Texture2D: D3D11_TEX2D_SRV {
-                            MostDetailedMip: 0,
-                            MipLevels: 1,
-                        },
-                    },
-                };
-                let mut srv = None;
-                let _ = unsafe {
-                    device.CreateShaderResourceView(
-                        texture,
-                        Some(&srv_desc),
-                        Some(&mut srv as *mut _),
-                    )
-                };
-                self.tex_after_tint_srv = srv;
-            }
-        }
-
-        // Only update cached dims if all textures and views were created
-        // successfully, otherwise the early-return check at the top of this
-        // method would skip recreation on the next call.
-        if self.tex_after_grayscale.is_some()
-            && self.tex_after_grayscale_srv.is_some()
-            && self.tex_after_grayscale_uav.is_some()
-            && self.tex_after_tint.is_some()
-            && self.tex_after_tint_srv.is_some()
-        {
-            self.intermediate_dims = (width, height);
-        }
-    }
-}
-
 // ---------------------------------------------------------------------------
 // GpuPlugin implementation
 // ---------------------------------------------------------------------------
@@ -310,10 +158,6 @@ impl GpuPlugin for GpuState {
             self.grayscale_pipeline = Some(ctx.create_compute_pipeline(GRAYSCALE_CS)?);
             self.tint_pipeline = Some(ctx.create_render_pipeline(TINT_VS, TINT_PS)?);
             self.blend_pipeline = Some(ctx.create_compute_pipeline(BLEND_CS)?);
-            self.cbuf = gpu_interop::dx11::create_dynamic_cbuf(
-                ctx.dx11_device().device(),
-                std::mem::size_of::<EffectParams>(),
-            );
         }
         let _ = ctx;
         Ok(())
@@ -328,84 +172,37 @@ impl GpuPlugin for GpuState {
     ) {
         #[cfg(target_os = "windows")]
         {
-            let (w, h) = (input.width, input.height);
-
-            // Ensure intermediate textures before borrowing pipelines.
-            self.ensure_intermediate_textures(ctx, w, h);
-
-            let grayscale_pl = match &self.grayscale_pipeline {
-                Some(p) => p,
-                None => return,
-            };
-            let tint_pl = match &self.tint_pipeline {
-                Some(p) => p,
-                None => return,
-            };
-            let blend_pl = match &self.blend_pipeline {
-                Some(p) => p,
-                None => return,
-            };
-
-            let after_gray_srv = match &self.tex_after_grayscale_srv {
-                Some(v) => v.clone(),
-                None => return,
+            let (Some(grayscale), Some(tint), Some(blend)) = (
+                &self.grayscale_pipeline,
+                &self.tint_pipeline,
+                &self.blend_pipeline,
+            ) else {
+                return;
             };
-            let after_gray_uav = match &self.tex_after_grayscale_uav {
-                Some(v) => v.clone(),
-                None => return,
+            let gray = GrayscaleParams {
+                amount: self.params[PARAM_GRAYSCALE],
             };
-            let after_tint_texture = match &self.tex_after_tint {
-                Some(t) => t.clone(),
-                None => return,
+            let tint_params = TintParams {
+                tint: hsv_to_rgb(
+                    self.params[PARAM_TINT_HUE],
+                    self.params[PARAM_TINT_SAT],
+                    1.0,
+                ),
+                saturation: self.params[PARAM_TINT_SAT],
             };
-            let after_tint_srv = match &self.tex_after_tint_srv {
-                Some(v) => v.clone(),
-                None => return,
-            };
-            let cbuf = match &self.cbuf {
-                Some(b) => b.clone(),
-                None => return,
+            let mix = BlendParams {
+                amount: self.params[PARAM_BLEND],
             };
 
-            // Update constant buffer with current parameters.
-            let uniforms = EffectParams {
-                grayscale_amount: self.params[PARAM_GRAYSCALE],
-                tint_hue: self.params[PARAM_TINT_HUE],
-                tint_saturation: self.params[PARAM_TINT_SAT],
-                blend: self.params[PARAM_BLEND],
-            };
-            ctx.update_constant_buffer(&cbuf, uniforms.as_bytes());
-
-            let grid = (w as usize, h as usize);
-            let threadgroup = (16, 16);
-
-            // --- Pass 1: grayscale compute (input_srv -> after_gray_uav) ---
-            ctx.dispatch_compute(
-                grayscale_pl,
-                &[Some(after_gray_uav)],
-                &[Some(input.input_srv.clone())],
-                &[Some(cbuf.clone())],
-                grid,
-                threadgroup,
-            );
-
-            // --- Pass 2: tint render (after_gray_srv -> after_tint texture) ---
-            let _ = ctx.dispatch_render(
-                tint_pl,
-                &after_tint_texture,
-                &[Some(after_gray_srv)],
-                &[Some(cbuf.clone())],
-            );
-
-            // --- Pass 3: blend compute (input + after_tint -> output) ---
-            ctx.dispatch_compute(
-                blend_pl,
-                &[Some(input.output_uav.clone())],
-                &[Some(input.input_srv.clone()), Some(after_tint_srv)],
-                &[Some(cbuf)],
-                grid,
-                threadgroup,
-            );
+            // Each pass gets its own constant buffer, found by cbuffer
+            // name; the intermediates are reused across frames.
+            if let Err(e) = ffgl_gpu::gpu_passes!(ctx, input, {
+                after_gray = compute(grayscale)(input) with { gray_params: gray };
+                after_tint = render(tint)(after_gray) with { tint_params: tint_params };
+                output = compute(blend)(input, after_tint) with { blend_params: mix };
+            }) {
+                gpu_interop::warn_limited!("Kitchen sink passes failed: {e:#}");
+            }
         }
 
         #[cfg(not(target_os = "windows"))]
@@ -445,20 +242,6 @@ impl SimpleFFGLInstance for DxKitchenSink {
                 grayscale_pipeline: None,
                 tint_pipeline: None,
                 blend_pipeline: None,
-                #[cfg(target_os = "windows")]
-                tex_after_grayscale: None,
-                #[cfg(target_os = "windows")]
-                tex_after_grayscale_srv: None,
-                #[cfg(target_os = "windows")]
-                tex_after_grayscale_uav: None,
-                #[cfg(target_os = "windows")]
-                tex_after_tint: None,
-                #[cfg(target_os = "windows")]
-                tex_after_tint_srv: None,
-                #[cfg(target_os = "windows")]
-                intermediate_dims: (0, 0),
-                #[cfg(target_os = "windows")]
-                cbuf: None,
             },
             frame_counter: 0,
             instance_id: NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed),
@@ -481,6 +264,7 @@ impl SimpleFFGLInstance for DxKitchenSink {
             about: "DX11 mixed compute + render pipeline demo".to_string(),
             description: "Grayscale (compute) -> Tint (render) -> Blend (compute) on DX11"
                 .to_string(),
+            thumbnail: None,
         }
     }
 
@@ -509,6 +293,10 @@ impl SimpleFFGLInstance for DxKitchenSink {
             METALLIB_BYTES,
         );
     }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
 }
 
 ffgl_core::plugin_main!(SimpleFFGLHandler<DxKitchenSink>);
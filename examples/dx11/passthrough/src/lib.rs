@@ -57,13 +57,12 @@ impl GpuPlugin for GpuState {
                 None => return,
             };
 
-            ctx.dispatch_compute(
+            ctx.dispatch_auto(
                 pipeline,
-                &[Some(input.output_uav.clone())],
-                &[Some(input.input_srv.clone())],
+                &[input.output_uav.clone().into()],
+                &[input.input_srv.clone().into()],
                 &[],
                 (input.width as usize, input.height as usize),
-                (16, 16),
             );
         }
 
@@ -109,6 +108,7 @@ impl SimpleFFGLInstance for Passthrough {
             ty: PluginType::Effect,
             about: "DX11 Passthrough GPU compute example".to_string(),
             description: "Copies input to output via a DX11 compute shader".to_string(),
+            thumbnail: None,
         }
     }
 
@@ -127,6 +127,10 @@ impl SimpleFFGLInstance for Passthrough {
             METALLIB_BYTES,
         );
     }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
 }
 
 ffgl_core::plugin_main!(SimpleFFGLHandler<Passthrough>);
@@ -2,8 +2,8 @@
 //!
 //! Demonstrates multi-pass compute with an FFGL parameter. A separable box
 //! blur is implemented as two compute dispatches (horizontal then vertical)
-//! using an intermediate texture. The "Radius" parameter (0.0-1.0) maps to
-//! 0-20 pixels of blur.
+//! using an intermediate texture. The "Radius" parameter is declared as
+//! 0-20 pixels, which the host shows; the plugin scales its 0-1 value back.
 
 use std::ffi::CString;
 use std::sync::OnceLock;
@@ -27,15 +27,14 @@ const METALLIB_BYTES: &[u8] = ffgl_gpu::include_metallib!();
 #[cfg(not(target_os = "macos"))]
 const METALLIB_BYTES: &[u8] = &[];
 
-/// Maximum blur radius in pixels when the parameter is at 1.0.
-const MAX_RADIUS: f32 = 20.0;
-
 fn cached_params() -> &'static [SimpleParamInfo] {
     static PARAMS: OnceLock<Vec<SimpleParamInfo>> = OnceLock::new();
     PARAMS.get_or_init(|| {
         vec![SimpleParamInfo {
             name: CString::new("Radius").unwrap(),
             default: Some(0.25),
+            max: Some(20.0),
+            unit: Some("px".into()),
             ..Default::default()
         }]
     })
@@ -56,33 +55,21 @@ struct GpuState {
     h_pipeline: Option<ComputePipeline>,
     v_pipeline: Option<ComputePipeline>,
     #[cfg(target_os = "macos")]
-    intermediate_texture:
-        Option<objc2::rc::Retained<objc2::runtime::ProtocolObject<dyn objc2_metal::MTLTexture>>>,
-    #[cfg(target_os = "macos")]
-    intermediate_dims: (u32, u32),
+    intermediate_texture: Option<ffgl_gpu::GpuTexture>,
 }
 
 #[cfg(target_os = "macos")]
 impl GpuState {
     fn ensure_intermediate_texture(&mut self, ctx: &GpuContext, width: u32, height: u32) {
-        use objc2_metal::*;
-
-        if self.intermediate_dims == (width, height) && self.intermediate_texture.is_some() {
+        if self
+            .intermediate_texture
+            .as_ref()
+            .is_some_and(|t| t.has_dims(width, height))
+        {
             return;
         }
 
-        let desc = MTLTextureDescriptor::new();
-        desc.setTextureType(MTLTextureType::Type2D);
-        desc.setPixelFormat(MTLPixelFormat::BGRA8Unorm);
-        unsafe {
-            desc.setWidth(width as usize);
-            desc.setHeight(height as usize);
-        }
-        desc.setStorageMode(MTLStorageMode::Private);
-        desc.setUsage(MTLTextureUsage::ShaderRead | MTLTextureUsage::ShaderWrite);
-
-        self.intermediate_texture = ctx.metal_device().device().newTextureWithDescriptor(&desc);
-        self.intermediate_dims = (width, height);
+        self.intermediate_texture = ctx.create_texture(width, height).ok();
     }
 }
 
@@ -116,11 +103,15 @@ impl GpuPlugin for GpuState {
                 None => return,
             };
             let intermediate_tex = match &self.intermediate_texture {
-                Some(t) => t,
+                Some(t) => t.metal_texture(),
                 None => return,
             };
 
-            let pixel_radius = (self.radius_param * MAX_RADIUS).round() as i32;
+            // The radius is in output pixels; at reduced internal resolution
+            // fewer processing pixels cover it.
+            let (scale, _) = input.scale();
+            let pixel_radius =
+                (cached_params()[0].scale(self.radius_param) * scale).round() as i32;
             let params = BlurParams {
                 radius: pixel_radius,
             };
@@ -201,8 +192,6 @@ impl SimpleFFGLInstance for Blur {
                 v_pipeline: None,
                 #[cfg(target_os = "macos")]
                 intermediate_texture: None,
-                #[cfg(target_os = "macos")]
-                intermediate_dims: (0, 0),
             },
             frame_counter: 0,
             instance_id: NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed),
@@ -224,6 +213,7 @@ impl SimpleFFGLInstance for Blur {
             ty: PluginType::Effect,
             about: "Separable box blur via multi-pass compute".to_string(),
             description: "Two-pass GPU compute blur with adjustable radius parameter".to_string(),
+            thumbnail: None,
         }
     }
 
@@ -250,6 +240,10 @@ impl SimpleFFGLInstance for Blur {
             METALLIB_BYTES,
         );
     }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
 }
 
 ffgl_core::plugin_main!(SimpleFFGLHandler<Blur>);
@@ -0,0 +1,14 @@
+#include <metal_stdlib>
+using namespace metal;
+
+/// Shows the keyed image's alpha as an opaque grayscale matte.
+kernel void show_matte(
+    texture2d<float, access::read> input [[texture(0)]],
+    texture2d<float, access::write> output [[texture(1)]],
+    uint2 gid [[thread_position_in_grid]])
+{
+    if (gid.x >= output.get_width() || gid.y >= output.get_height()) return;
+
+    float a = input.read(gid).a;
+    output.write(float4(a, a, a, 1.0), gid);
+}
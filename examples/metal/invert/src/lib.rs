@@ -47,7 +47,15 @@ impl GpuPlugin for GpuState {
                 None => return,
             };
 
-            let pending = match ctx.dispatch_render(pipeline, input.output, &[input.input], &[]) {
+            let pending = match ctx.dispatch_render(
+                pipeline,
+                input.output,
+                &[input.input],
+                &[],
+                &[],
+                &[],
+                &[],
+            ) {
                 Ok(p) => p,
                 Err(_) => return,
             };
@@ -94,6 +102,7 @@ impl SimpleFFGLInstance for Invert {
             ty: PluginType::Effect,
             about: "Color inversion via render pipeline".to_string(),
             description: "Inverts colors using a vertex/fragment shader pair".to_string(),
+            thumbnail: None,
         }
     }
 
@@ -112,6 +121,10 @@ impl SimpleFFGLInstance for Invert {
             METALLIB_BYTES,
         );
     }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
 }
 
 ffgl_core::plugin_main!(SimpleFFGLHandler<Invert>);
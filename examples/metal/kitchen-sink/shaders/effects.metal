@@ -2,34 +2,22 @@
 using namespace metal;
 
 // -----------------------------------------------------------------------
-// Shared uniform struct
+// Uniforms, one struct per pass. The argument names are what the plugin
+// binds them by; the HLSL cbuffers carry the same names.
 // -----------------------------------------------------------------------
 
-struct EffectParams {
-    float grayscale_amount; // 0..1
-    float tint_hue;         // 0..1 (maps to 0..360 degrees)
-    float tint_saturation;  // 0..1
-    float blend;            // 0..1  mix(processed, original)
+struct GrayscaleParams {
+    float amount; // 0..1
 };
 
-// -----------------------------------------------------------------------
-// HSV <-> RGB helpers
-// -----------------------------------------------------------------------
+struct TintParams {
+    packed_float3 tint; // RGB of the tint hue and saturation
+    float saturation;   // 0..1
+};
 
-static float3 hsv_to_rgb(float h, float s, float v) {
-    float c = v * s;
-    float hp = h * 6.0;
-    float x = c * (1.0 - abs(fmod(hp, 2.0) - 1.0));
-    float3 rgb;
-    if      (hp < 1.0) rgb = float3(c, x, 0);
-    else if (hp < 2.0) rgb = float3(x, c, 0);
-    else if (hp < 3.0) rgb = float3(0, c, x);
-    else if (hp < 4.0) rgb = float3(0, x, c);
-    else if (hp < 5.0) rgb = float3(x, 0, c);
-    else               rgb = float3(c, 0, x);
-    float m = v - c;
-    return rgb + m;
-}
+struct BlendParams {
+    float amount; // 0..1  mix(original, processed)
+};
 
 // -----------------------------------------------------------------------
 // Pass 1: grayscale (compute)
@@ -38,7 +26,7 @@ static float3 hsv_to_rgb(float h, float s, float v) {
 kernel void grayscale(
     texture2d<float, access::read> input [[texture(0)]],
     texture2d<float, access::write> output [[texture(1)]],
-    constant EffectParams& params [[buffer(0)]],
+    constant GrayscaleParams& gray_params [[buffer(0)]],
     uint2 gid [[thread_position_in_grid]])
 {
     uint w = input.get_width();
@@ -48,7 +36,7 @@ kernel void grayscale(
     float4 color = input.read(gid);
     float lum = dot(color.rgb, float3(0.2126, 0.7152, 0.0722));
     float3 gray = float3(lum);
-    float3 result = mix(color.rgb, gray, params.grayscale_amount);
+    float3 result = mix(color.rgb, gray, gray_params.amount);
     output.write(float4(result, color.a), gid);
 }
 
@@ -74,18 +62,17 @@ vertex VertexOut tint_vertex(
 fragment float4 tint_fragment(
     VertexOut in [[stage_in]],
     texture2d<float> input [[texture(0)]],
-    constant EffectParams& params [[buffer(0)]])
+    constant TintParams& tint_params [[buffer(0)]])
 {
     constexpr sampler s(mag_filter::linear, min_filter::linear);
     float4 color = input.sample(s, in.texcoord);
 
-    // Generate tint color from hue + saturation
-    float3 tint = hsv_to_rgb(params.tint_hue, params.tint_saturation, 1.0);
+    float3 tint = float3(tint_params.tint);
 
     // Multiply blend: overlay the tint onto the grayscaled image
     float3 tinted = color.rgb * tint;
     // Mix between untinted and tinted based on saturation strength
-    float3 result = mix(color.rgb, tinted, params.tint_saturation);
+    float3 result = mix(color.rgb, tinted, tint_params.saturation);
     return float4(result, color.a);
 }
 
@@ -97,7 +84,7 @@ kernel void blend(
     texture2d<float, access::read> original [[texture(0)]],
     texture2d<float, access::read> processed [[texture(1)]],
     texture2d<float, access::write> output [[texture(2)]],
-    constant EffectParams& params [[buffer(0)]],
+    constant BlendParams& blend_params [[buffer(0)]],
     uint2 gid [[thread_position_in_grid]])
 {
     uint w = original.get_width();
@@ -106,6 +93,6 @@ kernel void blend(
 
     float4 orig = original.read(gid);
     float4 proc = processed.read(gid);
-    float4 result = mix(orig, proc, params.blend);
+    float4 result = mix(orig, proc, blend_params.amount);
     output.write(result, gid);
 }
@@ -12,9 +12,11 @@
 //!
 //! This shows how to:
 //! - Use multiple pipelines (compute and render) in a single plugin.
-//! - Pass uniform data to both compute and fragment shaders.
-//! - Chain intermediate textures across passes.
+//! - Chain the passes through intermediate textures with `gpu_passes!`.
+//! - Bind each pass's uniforms by the name its shader gives them.
 //! - Expose multiple FFGL parameters.
+//! - Convert hue/saturation parameters to RGB on the CPU with
+//!   `ffgl_core::parameters::color`.
 
 use std::ffi::CString;
 use std::sync::OnceLock;
@@ -22,6 +24,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 
 use ffgl_core::handler::simplified::{SimpleFFGLHandler, SimpleFFGLInstance};
 use ffgl_core::info::{PluginInfo, PluginType};
+use ffgl_core::parameters::color::hsv_to_rgb;
 use ffgl_core::parameters::{ParamInfo, ParameterTypes, SimpleParamInfo};
 use ffgl_core::{FFGLData, GLInput};
 use ffgl_glium::FFGLGlium;
@@ -78,17 +81,30 @@ fn cached_params() -> &'static [SimpleParamInfo] {
     })
 }
 
-/// Uniform struct matching `EffectParams` in the Metal shaders.
+/// Uniforms of the grayscale pass, `GrayscaleParams` in the Metal shaders.
 #[repr(C)]
-struct EffectParams {
-    grayscale_amount: f32,
-    tint_hue: f32,
-    tint_saturation: f32,
-    blend: f32,
+struct GrayscaleParams {
+    amount: f32,
 }
 
-// SAFETY: EffectParams is #[repr(C)] with only plain f32 fields.
-unsafe impl AsBytes for EffectParams {}
+/// Uniforms of the tint pass, `TintParams` in the Metal shaders.
+#[repr(C)]
+struct TintParams {
+    /// `packed_float3`, followed directly by `saturation`.
+    tint: [f32; 3],
+    saturation: f32,
+}
+
+/// Uniforms of the blend pass, `BlendParams` in the Metal shaders.
+#[repr(C)]
+struct BlendParams {
+    amount: f32,
+}
+
+// SAFETY: the uniform structs are #[repr(C)] with only plain f32 fields.
+unsafe impl AsBytes for GrayscaleParams {}
+unsafe impl AsBytes for TintParams {}
+unsafe impl AsBytes for BlendParams {}
 
 /// Inner GPU state, separate from glium to avoid double-borrow.
 struct GpuState {
@@ -98,60 +114,12 @@ struct GpuState {
     grayscale_pipeline: Option<ComputePipeline>,
     tint_pipeline: Option<RenderPipeline>,
     blend_pipeline: Option<ComputePipeline>,
-
-    // Intermediate textures (macOS only)
-    #[cfg(target_os = "macos")]
-    tex_after_grayscale:
-        Option<objc2::rc::Retained<objc2::runtime::ProtocolObject<dyn objc2_metal::MTLTexture>>>,
-    #[cfg(target_os = "macos")]
-    tex_after_tint:
-        Option<objc2::rc::Retained<objc2::runtime::ProtocolObject<dyn objc2_metal::MTLTexture>>>,
-    #[cfg(target_os = "macos")]
-    intermediate_dims: (u32, u32),
 }
 
 // SAFETY: FFGL plugins are called single-threaded from the host.
 unsafe impl Send for GpuState {}
 unsafe impl Sync for GpuState {}
 
-#[cfg(target_os = "macos")]
-impl GpuState {
-    fn ensure_intermediate_textures(&mut self, ctx: &GpuContext, width: u32, height: u32) {
-        use objc2_metal::*;
-
-        if self.intermediate_dims == (width, height)
-            && self.tex_after_grayscale.is_some()
-            && self.tex_after_tint.is_some()
-        {
-            return;
-        }
-
-        let make_texture = |usage: MTLTextureUsage| -> Option<
-            objc2::rc::Retained<objc2::runtime::ProtocolObject<dyn MTLTexture>>,
-        > {
-            let desc = MTLTextureDescriptor::new();
-            desc.setTextureType(MTLTextureType::Type2D);
-            desc.setPixelFormat(MTLPixelFormat::BGRA8Unorm);
-            unsafe {
-                desc.setWidth(width as usize);
-                desc.setHeight(height as usize);
-            }
-            desc.setStorageMode(MTLStorageMode::Private);
-            desc.setUsage(usage);
-            ctx.metal_device().device().newTextureWithDescriptor(&desc)
-        };
-
-        // After grayscale: read by tint fragment + written by compute
-        self.tex_after_grayscale =
-            make_texture(MTLTextureUsage::ShaderRead | MTLTextureUsage::ShaderWrite);
-        // After tint: read by blend compute, written by render pass
-        self.tex_after_tint =
-            make_texture(MTLTextureUsage::ShaderRead | MTLTextureUsage::RenderTarget);
-
-        self.intermediate_dims = (width, height);
-    }
-}
-
 impl GpuPlugin for GpuState {
     fn gpu_init(&mut self, ctx: &GpuContext) -> anyhow::Result<()> {
         self.grayscale_pipeline = Some(ctx.create_compute_pipeline("grayscale")?);
@@ -169,96 +137,37 @@ impl GpuPlugin for GpuState {
     ) {
         #[cfg(target_os = "macos")]
         {
-            let (w, h) = (input.width, input.height);
-
-            // Ensure intermediate textures before borrowing pipelines.
-            self.ensure_intermediate_textures(ctx, w, h);
-
-            let grayscale_pl = match &self.grayscale_pipeline {
-                Some(p) => p,
-                None => return,
-            };
-            let tint_pl = match &self.tint_pipeline {
-                Some(p) => p,
-                None => return,
-            };
-            let blend_pl = match &self.blend_pipeline {
-                Some(p) => p,
-                None => return,
-            };
-
-            let after_gray = match &self.tex_after_grayscale {
-                Some(t) => t,
-                None => return,
+            let (Some(grayscale), Some(tint), Some(blend)) = (
+                &self.grayscale_pipeline,
+                &self.tint_pipeline,
+                &self.blend_pipeline,
+            ) else {
+                return;
             };
-            let after_tint = match &self.tex_after_tint {
-                Some(t) => t,
-                None => return,
+            let gray = GrayscaleParams {
+                amount: self.params[PARAM_GRAYSCALE],
             };
-
-            let uniforms = EffectParams {
-                grayscale_amount: self.params[PARAM_GRAYSCALE],
-                tint_hue: self.params[PARAM_TINT_HUE],
-                tint_saturation: self.params[PARAM_TINT_SAT],
-                blend: self.params[PARAM_BLEND],
+            let tint_params = TintParams {
+                tint: hsv_to_rgb(
+                    self.params[PARAM_TINT_HUE],
+                    self.params[PARAM_TINT_SAT],
+                    1.0,
+                ),
+                saturation: self.params[PARAM_TINT_SAT],
             };
-
-            // Encode all three passes into a single command buffer.
-            // Metal serialises encoders automatically — zero mid-frame waits.
-            let cb = match ctx.create_command_buffer() {
-                Ok(cb) => cb,
-                Err(_) => return,
+            let mix = BlendParams {
+                amount: self.params[PARAM_BLEND],
             };
 
-            // --- Pass 1: grayscale compute (input -> after_gray) ---
-            if ctx
-                .encode_compute_pass(
-                    &cb,
-                    grayscale_pl,
-                    &[input.input, after_gray],
-                    &[],
-                    &[(uniforms.as_bytes(), 0)],
-                    (w as usize, h as usize),
-                    (16, 16),
-                )
-                .is_err()
-            {
-                return;
-            }
-
-            // --- Pass 2: tint render (after_gray -> after_tint) ---
-            if ctx
-                .encode_render_pass(
-                    &cb,
-                    tint_pl,
-                    after_tint,
-                    &[after_gray],
-                    &[(uniforms.as_bytes(), 0)],
-                )
-                .is_err()
-            {
-                return;
+            // All three passes go into one command buffer; the
+            // intermediates are reused across frames.
+            if let Err(e) = ffgl_gpu::gpu_passes!(ctx, input, {
+                after_gray = compute(grayscale)(input) with { gray_params: gray };
+                after_tint = render(tint)(after_gray) with { tint_params: tint_params };
+                output = compute(blend)(input, after_tint) with { blend_params: mix };
+            }) {
+                gpu_interop::warn_limited!("Kitchen sink passes failed: {e:#}");
             }
-
-            // --- Pass 3: blend compute (original + after_tint -> output) ---
-            if ctx
-                .encode_compute_pass(
-                    &cb,
-                    blend_pl,
-                    &[input.input, after_tint, input.output],
-                    &[],
-                    &[(uniforms.as_bytes(), 0)],
-                    (w as usize, h as usize),
-                    (16, 16),
-                )
-                .is_err()
-            {
-                return;
-            }
-
-            // Single commit — all three passes submitted as one GPU unit.
-            let pending = ctx.commit(cb);
-            input.metal_bridge().store_command_buffer(pending.into_command_buffer());
         }
 
         #[cfg(not(target_os = "macos"))]
@@ -294,12 +203,6 @@ impl SimpleFFGLInstance for KitchenSink {
                 grayscale_pipeline: None,
                 tint_pipeline: None,
                 blend_pipeline: None,
-                #[cfg(target_os = "macos")]
-                tex_after_grayscale: None,
-                #[cfg(target_os = "macos")]
-                tex_after_tint: None,
-                #[cfg(target_os = "macos")]
-                intermediate_dims: (0, 0),
             },
             frame_counter: 0,
             instance_id: NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed),
@@ -321,6 +224,7 @@ impl SimpleFFGLInstance for KitchenSink {
             ty: PluginType::Effect,
             about: "Mixed compute + render pipeline demo".to_string(),
             description: "Grayscale (compute) -> Tint (render) -> Blend (compute)".to_string(),
+            thumbnail: None,
         }
     }
 
@@ -349,6 +253,10 @@ impl SimpleFFGLInstance for KitchenSink {
             METALLIB_BYTES,
         );
     }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
 }
 
 ffgl_core::plugin_main!(SimpleFFGLHandler<KitchenSink>);
@@ -48,13 +48,12 @@ impl GpuPlugin for GpuState {
                 None => return,
             };
 
-            let pending = match ctx.dispatch_compute(
+            let pending = match ctx.dispatch_auto(
                 pipeline,
                 &[input.input, input.output],
                 &[],
                 &[],
                 (input.width as usize, input.height as usize),
-                (16, 16),
             ) {
                 Ok(p) => p,
                 Err(_) => return,
@@ -101,6 +100,7 @@ impl SimpleFFGLInstance for Passthrough {
             ty: PluginType::Effect,
             about: "Passthrough GPU compute example".to_string(),
             description: "Copies input to output via a Metal/DX11 compute shader".to_string(),
+            thumbnail: None,
         }
     }
 
@@ -119,6 +119,10 @@ impl SimpleFFGLInstance for Passthrough {
             METALLIB_BYTES,
         );
     }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
 }
 
 ffgl_core::plugin_main!(SimpleFFGLHandler<Passthrough>);
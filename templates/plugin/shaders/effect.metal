@@ -0,0 +1,32 @@
+#include <metal_stdlib>
+using namespace metal;
+
+// Matches `EffectParams` in src/lib.rs.
+struct EffectParams {
+    float gain;
+};
+
+struct VertexOut {
+    float4 position [[position]];
+    float2 texcoord;
+};
+
+vertex VertexOut effect_vertex(
+    const device float4* vertices [[buffer(0)]],
+    uint vid [[vertex_id]])
+{
+    VertexOut out;
+    out.position = float4(vertices[vid].xy, 0, 1);
+    out.texcoord = vertices[vid].zw;
+    return out;
+}
+
+fragment float4 effect_fragment(
+    VertexOut in [[stage_in]],
+    texture2d<float> input [[texture(0)]],
+    constant EffectParams& params [[buffer(0)]])
+{
+    constexpr sampler s(mag_filter::linear, min_filter::linear);
+    float4 color = input.sample(s, in.texcoord);
+    return float4(color.rgb * params.gain, color.a);
+}
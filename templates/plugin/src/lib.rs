@@ -0,0 +1,207 @@
+//! {{plugin_name}} FFGL plugin.
+//!
+//! One render pass scaling the input's color by the "Gain" parameter, on
+//! Metal (macOS) and DX11 (Windows). Add passes to the `gpu_passes!` chain
+//! in `gpu_draw`, and kernels or entry points to `shaders/` and `build.rs`.
+
+use std::ffi::CString;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use ffgl_core::handler::simplified::{SimpleFFGLHandler, SimpleFFGLInstance};
+use ffgl_core::info::{PluginInfo, PluginType};
+use ffgl_core::parameters::{ParamInfo, SimpleParamInfo};
+use ffgl_core::{FFGLData, GLInput};
+use ffgl_glium::FFGLGlium;
+use ffgl_gpu::{draw_gpu_effect, AsBytes, DrawInput, GpuContext, GpuPlugin, RenderPipeline};
+
+static NEXT_INSTANCE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Compiled Metal shader library, embedded at build time.
+#[cfg(target_os = "macos")]
+const METALLIB_BYTES: &[u8] = ffgl_gpu::include_metallib!();
+#[cfg(not(target_os = "macos"))]
+const METALLIB_BYTES: &[u8] = &[];
+
+/// Compiled HLSL shaders, embedded at build time.
+#[cfg(target_os = "windows")]
+const VS_SHADER: &[u8] = ffgl_gpu::include_hlsl_shader!("vs_main");
+#[cfg(target_os = "windows")]
+const PS_SHADER: &[u8] = ffgl_gpu::include_hlsl_shader!("ps_main");
+
+const PARAM_GAIN: usize = 0;
+
+fn cached_params() -> &'static [SimpleParamInfo] {
+    static PARAMS: OnceLock<Vec<SimpleParamInfo>> = OnceLock::new();
+    PARAMS.get_or_init(|| {
+        vec![SimpleParamInfo {
+            name: CString::new("Gain").unwrap(),
+            default: Some(0.5),
+            max: Some(2.0),
+            unit: Some("x".into()),
+            ..Default::default()
+        }]
+    })
+}
+
+/// Uniform struct matching `EffectParams` in the shaders.
+#[repr(C)]
+struct EffectParams {
+    gain: f32,
+}
+
+// SAFETY: EffectParams is #[repr(C)] with only plain f32 fields.
+unsafe impl AsBytes for EffectParams {}
+
+/// Inner GPU state, separate from glium to avoid double-borrow.
+struct GpuState {
+    params: Vec<f32>,
+    pipeline: Option<RenderPipeline>,
+}
+
+impl GpuPlugin for GpuState {
+    fn gpu_init(&mut self, ctx: &GpuContext) -> anyhow::Result<()> {
+        #[cfg(target_os = "macos")]
+        {
+            self.pipeline = Some(ctx.create_render_pipeline("effect_vertex", "effect_fragment")?);
+        }
+        #[cfg(target_os = "windows")]
+        {
+            self.pipeline = Some(ctx.create_render_pipeline(VS_SHADER, PS_SHADER)?);
+        }
+        let _ = ctx;
+        Ok(())
+    }
+
+    fn gpu_draw(
+        &mut self,
+        ctx: &GpuContext,
+        input: &mut DrawInput<'_>,
+        _data: &FFGLData,
+        _frame: u64,
+    ) {
+        let params = EffectParams {
+            gain: cached_params()[PARAM_GAIN].scale(self.params[PARAM_GAIN]),
+        };
+
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        {
+            let Some(pipeline) = &self.pipeline else {
+                return;
+            };
+            if let Err(e) = ffgl_gpu::gpu_passes!(ctx, input, uniforms: params, {
+                output = render(pipeline)(input);
+            }) {
+                ffgl_core::tracing::warn!("Drawing failed: {e:#}");
+            }
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        {
+            let _ = (ctx, input, params.gain, &self.pipeline);
+        }
+    }
+}
+
+// SAFETY: FFGL plugins are called single-threaded from the host.
+unsafe impl Send for GpuState {}
+unsafe impl Sync for GpuState {}
+
+pub struct Plugin {
+    glium: FFGLGlium,
+    gpu: GpuState,
+    frame_counter: u64,
+    instance_id: u64,
+}
+
+// SAFETY: FFGL plugins are called single-threaded from the host.
+unsafe impl Send for Plugin {}
+unsafe impl Sync for Plugin {}
+
+impl SimpleFFGLInstance for Plugin {
+    fn new(inst_data: &FFGLData) -> Self {
+        Self {
+            glium: FFGLGlium::new(inst_data),
+            gpu: GpuState {
+                params: cached_params().iter().map(|p| p.default_val()).collect(),
+                pipeline: None,
+            },
+            frame_counter: 0,
+            instance_id: NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    fn plugin_info() -> PluginInfo {
+        PluginInfo::builder()
+            .name("{{plugin_name}}")
+            .unique_id("{{unique_id}}")
+            .ty(PluginType::Effect)
+            .about("{{plugin_name}}")
+            .description("{{plugin_name}} effect")
+            .build()
+            .expect("valid plugin info")
+    }
+
+    fn num_params() -> usize {
+        cached_params().len()
+    }
+
+    fn param_info(index: usize) -> &'static dyn ParamInfo {
+        &cached_params()[index]
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        self.gpu.params[index]
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        self.gpu.params[index] = value;
+    }
+
+    fn draw(&mut self, data: &FFGLData, frame_data: GLInput) {
+        self.frame_counter = self.frame_counter.wrapping_add(1);
+        draw_gpu_effect(
+            &mut self.gpu,
+            self.instance_id,
+            &mut self.glium,
+            data,
+            frame_data,
+            self.frame_counter,
+            1.0,
+            1.0,
+            METALLIB_BYTES,
+        );
+    }
+
+    fn deinstantiate(&mut self) {
+        ffgl_gpu::budget::unregister(self.instance_id);
+    }
+}
+
+ffgl_core::plugin_main!(SimpleFFGLHandler<Plugin>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugin_info_accepts_the_placeholders() {
+        let info = Plugin::plugin_info();
+        assert_eq!(info.name_str(), "{{plugin_name}}");
+        assert_eq!(&info.unique_id, b"{{unique_id}}");
+    }
+
+    #[test]
+    fn gain_defaults_to_unity() {
+        let gain = &cached_params()[PARAM_GAIN];
+        assert_eq!(gain.scale(gain.default_val()), 1.0);
+    }
+
+    #[test]
+    fn gain_range_round_trips() {
+        let gain = &cached_params()[PARAM_GAIN];
+        for value in [0.0, 0.25, 1.0, 2.0] {
+            assert!((gain.scale(gain.normalize(value)) - value).abs() < 1e-6);
+        }
+    }
+}